@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wasmcloud_core::{HostData, InterfaceLinkDefinition};
+
+// Providers decode the host data passed on startup and the link definitions put while they run
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<HostData>(data);
+    let _ = serde_json::from_slice::<InterfaceLinkDefinition>(data);
+});
@@ -1,7 +1,65 @@
+use core::fmt;
+use core::str::FromStr;
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
 const DEFAULT_TOPIC_PREFIX: &str = "wasmbus.ctl";
 // Copied from https://docs.rs/wasmcloud-core/0.15.0/wasmcloud_core/constant.CTL_API_VERSION_1.html
 // to avoid a dependency on a crate for one constant
 const CTL_API_VERSION_1: &str = "v1";
+const CTL_API_VERSION_2: &str = "v2";
+
+/// Version of the control interface subject scheme used by a [`Client`](crate::Client).
+///
+/// - `v1` subjects name the resource and action first, followed by the target host ID, e.g.
+///   `wasmbus.ctl.v1.default.component.scale.<host_id>`
+/// - `v2` subjects group all host-targeted operations under the host, e.g.
+///   `wasmbus.ctl.v2.default.host.<host_id>.component.scale`, so that a single wildcard subscription
+///   covers everything addressed to a host. Lattice-wide operations keep the same shape as in `v1`.
+///
+/// Version 1 is the default, and is understood by all hosts. Hosts serve both versions as of
+/// wasmCloud 1.10, see [`ClientBuilder::protocol_version`](crate::ClientBuilder::protocol_version)
+/// for how clients deal with older hosts.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtocolVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ProtocolVersion {
+    /// The version segment used in control interface subjects, e.g. `v1`
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V1 => CTL_API_VERSION_1,
+            Self::V2 => CTL_API_VERSION_2,
+        }
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProtocolVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            CTL_API_VERSION_1 | "1" => Ok(Self::V1),
+            CTL_API_VERSION_2 | "2" => Ok(Self::V2),
+            _ => Err(format!(
+                "unsupported control interface protocol version [{s}], expected `v1` or `v2`"
+            )),
+        }
+    }
+}
 
 fn prefix(topic_prefix: &Option<String>, lattice: &str, version: &str) -> String {
     format!(
@@ -12,6 +70,443 @@ fn prefix(topic_prefix: &Option<String>, lattice: &str, version: &str) -> String
     )
 }
 
+/// A control interface subject split into its parts
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct ParsedSubject<'a> {
+    pub(crate) version: ProtocolVersion,
+    /// The resource the operation applies to, e.g. `component` or `host`
+    pub(crate) resource: &'a str,
+    /// The action performed on the resource, e.g. `scale` or `get`
+    pub(crate) action: &'a str,
+    /// The target host ID or config name, which is empty for lattice-wide operations. Host-scoped
+    /// config operations take a `<host_id>.<config_name>` argument in both versions
+    pub(crate) arg: Cow<'a, str>,
+}
+
+/// Parse the part of a subject following the lattice, e.g. `component.scale.<host_id>`, in the
+/// subject scheme of the given version
+pub(crate) fn parse_operation(version: ProtocolVersion, operation: &str) -> ParsedSubject<'_> {
+    let (resource, rest) = operation.split_once('.').unwrap_or((operation, ""));
+    if version == ProtocolVersion::V2 && resource == "host" && rest != "ping" {
+        let (host_id, rest) = rest.split_once('.').unwrap_or((rest, ""));
+        let (resource, action) = rest.split_once('.').unwrap_or(("host", rest));
+        if resource == "config" {
+            let (action, config_name) = action.split_once('.').unwrap_or((action, ""));
+            let action = match action {
+                "put" => "put_host",
+                "del" => "del_host",
+                "get" => "get_host",
+                action => action,
+            };
+            return ParsedSubject {
+                version,
+                resource,
+                action,
+                arg: Cow::Owned(format!("{host_id}.{config_name}")),
+            };
+        }
+        return ParsedSubject {
+            version,
+            resource,
+            action,
+            arg: Cow::Borrowed(host_id),
+        };
+    }
+    let (action, arg) = rest.split_once('.').unwrap_or((rest, ""));
+    ParsedSubject {
+        version,
+        resource,
+        action,
+        arg: Cow::Borrowed(arg),
+    }
+}
+
+/// Parse a control interface subject of any supported version for the given lattice
+pub(crate) fn parse<'a>(
+    topic_prefix: &Option<String>,
+    lattice: &str,
+    subject: &'a str,
+) -> Option<ParsedSubject<'a>> {
+    [ProtocolVersion::V1, ProtocolVersion::V2]
+        .into_iter()
+        .find_map(|version| {
+            let operation = subject
+                .strip_prefix(&prefix(topic_prefix, lattice, version.as_str()))?
+                .strip_prefix('.')?;
+            Some(parse_operation(version, operation))
+        })
+}
+
+/// Translate a `v2` control interface subject of the given lattice into the `v1` subject of the
+/// same operation, e.g. `wasmbus.ctl.v2.default.host.<host_id>.component.scale` into
+/// `wasmbus.ctl.v1.default.component.scale.<host_id>`. Returns `None` if the subject is not a `v2`
+/// subject of the lattice.
+///
+/// Hosts use this to handle requests of both versions alike, and clients to fall back to `v1`
+/// subjects for hosts that do not serve `v2` ones.
+#[must_use]
+pub fn v1_subject(topic_prefix: &Option<String>, lattice: &str, subject: &str) -> Option<String> {
+    let operation = subject
+        .strip_prefix(&prefix(topic_prefix, lattice, CTL_API_VERSION_2))?
+        .strip_prefix('.')?;
+    let ParsedSubject {
+        resource,
+        action,
+        arg,
+        ..
+    } = parse_operation(ProtocolVersion::V2, operation);
+    let prefix = prefix(topic_prefix, lattice, CTL_API_VERSION_1);
+    if arg.is_empty() {
+        Some(format!("{prefix}.{resource}.{action}"))
+    } else {
+        Some(format!("{prefix}.{resource}.{action}.{arg}"))
+    }
+}
+
+/// Name of the operation a control interface subject belongs to, e.g. `component.auction` or
+/// `host.get`, without the lattice prefix or any trailing host ID or config name
+pub fn operation(topic_prefix: &Option<String>, lattice: &str, subject: &str) -> String {
+    match parse(topic_prefix, lattice, subject) {
+        Some(ParsedSubject {
+            resource, action, ..
+        }) => format!("{resource}.{action}"),
+        None => subject.splitn(3, '.').take(2).collect::<Vec<_>>().join("."),
+    }
+}
+
+/// Subjects of a client, in the scheme of its [`ProtocolVersion`]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Subjects<'a> {
+    pub(crate) topic_prefix: &'a Option<String>,
+    pub(crate) lattice: &'a str,
+    pub(crate) version: ProtocolVersion,
+}
+
+impl Subjects<'_> {
+    /// Subject hosts publish lattice events of the given type on, e.g. `>` for all events. Hosts
+    /// publish `v2` events on their own subjects, which are not prefixed with the topic prefix
+    pub(crate) fn events(&self, event_type: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => format!("wasmbus.evt.{}.{event_type}", self.lattice),
+            ProtocolVersion::V2 => format!("wasmbus.evt.v2.{}.{event_type}", self.lattice),
+        }
+    }
+
+    pub(crate) fn provider_auction_subject(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::provider_auction_subject(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::provider_auction_subject(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn component_auction_subject(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::component_auction_subject(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::component_auction_subject(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn put_link(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::put_link(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::put_link(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn delete_link(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::delete_link(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::delete_link(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn publish_registries(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::publish_registries(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::publish_registries(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn put_config(&self, config_name: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::put_config(self.topic_prefix, self.lattice, config_name),
+            ProtocolVersion::V2 => v2::put_config(self.topic_prefix, self.lattice, config_name),
+        }
+    }
+
+    pub(crate) fn delete_config(&self, config_name: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::delete_config(self.topic_prefix, self.lattice, config_name),
+            ProtocolVersion::V2 => v2::delete_config(self.topic_prefix, self.lattice, config_name),
+        }
+    }
+
+    pub(crate) fn put_host_config(&self, host_id: &str, config_name: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::put_host_config(self.topic_prefix, self.lattice, host_id, config_name)
+            }
+            ProtocolVersion::V2 => {
+                v2::put_host_config(self.topic_prefix, self.lattice, host_id, config_name)
+            }
+        }
+    }
+
+    pub(crate) fn delete_host_config(&self, host_id: &str, config_name: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::delete_host_config(self.topic_prefix, self.lattice, host_id, config_name)
+            }
+            ProtocolVersion::V2 => {
+                v2::delete_host_config(self.topic_prefix, self.lattice, host_id, config_name)
+            }
+        }
+    }
+
+    pub(crate) fn put_label(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::put_label(self.topic_prefix, self.lattice, host_id),
+            ProtocolVersion::V2 => v2::put_label(self.topic_prefix, self.lattice, host_id),
+        }
+    }
+
+    pub(crate) fn delete_label(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::delete_label(self.topic_prefix, self.lattice, host_id),
+            ProtocolVersion::V2 => v2::delete_label(self.topic_prefix, self.lattice, host_id),
+        }
+    }
+
+    pub(crate) fn put_labels(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::put_labels(self.topic_prefix, self.lattice, host_id),
+            ProtocolVersion::V2 => v2::put_labels(self.topic_prefix, self.lattice, host_id),
+        }
+    }
+
+    pub(crate) fn delete_labels(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::delete_labels(self.topic_prefix, self.lattice, host_id),
+            ProtocolVersion::V2 => v2::delete_labels(self.topic_prefix, self.lattice, host_id),
+        }
+    }
+
+    pub(crate) fn scale_component(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::scale_component(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::scale_component(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn stop_components(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::stop_components(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::stop_components(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn start_provider(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::start_provider(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::start_provider(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn stop_provider(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::stop_provider(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::stop_provider(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn update_provider_config(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::update_provider_config(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::update_provider_config(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn update_component(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::update_component(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::update_component(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn stop_host(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::stop_host(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::stop_host(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn drain_host(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::drain_host(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::drain_host(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn prefetch_images(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::prefetch_images(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::prefetch_images(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn profile_component(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::profile_component(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::profile_component(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn cleanup_host_data(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::cleanup_host_data(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::cleanup_host_data(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn update_host_tracing(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::commands::update_host_tracing(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::commands::update_host_tracing(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn link_definitions(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::queries::link_definitions(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::queries::link_definitions(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn claims(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::queries::claims(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::queries::claims(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn host_inventory(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::queries::host_inventory(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::queries::host_inventory(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn top_memory_consumers(&self, host_id: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::queries::top_memory_consumers(self.topic_prefix, self.lattice, host_id)
+            }
+            ProtocolVersion::V2 => {
+                v2::queries::top_memory_consumers(self.topic_prefix, self.lattice, host_id)
+            }
+        }
+    }
+
+    pub(crate) fn hosts(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::queries::hosts(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::queries::hosts(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn config(&self, config_name: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::queries::config(self.topic_prefix, self.lattice, config_name)
+            }
+            ProtocolVersion::V2 => {
+                v2::queries::config(self.topic_prefix, self.lattice, config_name)
+            }
+        }
+    }
+
+    pub(crate) fn configs(&self) -> String {
+        match self.version {
+            ProtocolVersion::V1 => v1::queries::configs(self.topic_prefix, self.lattice),
+            ProtocolVersion::V2 => v2::queries::configs(self.topic_prefix, self.lattice),
+        }
+    }
+
+    pub(crate) fn host_config(&self, host_id: &str, config_name: &str) -> String {
+        match self.version {
+            ProtocolVersion::V1 => {
+                v1::queries::host_config(self.topic_prefix, self.lattice, host_id, config_name)
+            }
+            ProtocolVersion::V2 => {
+                v2::queries::host_config(self.topic_prefix, self.lattice, host_id, config_name)
+            }
+        }
+    }
+}
+
+/// Name of the JetStream KV bucket that stores named configuration for the given lattice
+pub fn config_bucket(lattice: &str) -> String {
+    format!("CONFIGDATA_{lattice}")
+}
+
+/// Name of the JetStream KV bucket that stores lattice metadata (links, claims, component specs
+/// and aliases) for the given lattice
+pub fn data_bucket(lattice: &str) -> String {
+    format!("LATTICEDATA_{lattice}")
+}
+
 pub mod v1 {
     use crate::broker::CTL_API_VERSION_1;
 
@@ -70,6 +565,30 @@ pub mod v1 {
         )
     }
 
+    pub fn put_host_config(
+        topic_prefix: &Option<String>,
+        lattice: &str,
+        host_id: &str,
+        config_name: &str,
+    ) -> String {
+        format!(
+            "{}.config.put_host.{host_id}.{config_name}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+        )
+    }
+
+    pub fn delete_host_config(
+        topic_prefix: &Option<String>,
+        lattice: &str,
+        host_id: &str,
+        config_name: &str,
+    ) -> String {
+        format!(
+            "{}.config.del_host.{host_id}.{config_name}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+        )
+    }
+
     pub fn put_label(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
         format!(
             "{}.label.put.{host_id}",
@@ -84,6 +603,20 @@ pub mod v1 {
         )
     }
 
+    pub fn put_labels(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!(
+            "{}.label.put_many.{host_id}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+        )
+    }
+
+    pub fn delete_labels(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!(
+            "{}.label.del_many.{host_id}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+        )
+    }
+
     pub mod commands {
         use crate::broker::CTL_API_VERSION_1;
 
@@ -100,6 +633,17 @@ pub mod v1 {
             )
         }
 
+        pub fn stop_components(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.component.stop.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
         pub fn start_provider(
             topic_prefix: &Option<String>,
             lattice: &str,
@@ -122,6 +666,17 @@ pub mod v1 {
             )
         }
 
+        pub fn update_provider_config(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.provider.update.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
         pub fn update_component(
             topic_prefix: &Option<String>,
             lattice: &str,
@@ -139,6 +694,57 @@ pub mod v1 {
                 prefix(topic_prefix, lattice, CTL_API_VERSION_1)
             )
         }
+
+        pub fn drain_host(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+            format!(
+                "{}.host.drain.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
+        pub fn prefetch_images(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.host.prefetch.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
+        pub fn cleanup_host_data(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.host.cleanup.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
+        pub fn update_host_tracing(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.host.tracing.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
+        pub fn profile_component(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.component.profile.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
     }
 
     pub mod queries {
@@ -171,6 +777,17 @@ pub mod v1 {
             )
         }
 
+        pub fn top_memory_consumers(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!(
+                "{}.component.memory.{host_id}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1)
+            )
+        }
+
         pub fn hosts(topic_prefix: &Option<String>, lattice: &str) -> String {
             format!(
                 "{}.host.ping",
@@ -184,5 +801,561 @@ pub mod v1 {
                 prefix(topic_prefix, lattice, CTL_API_VERSION_1),
             )
         }
+
+        pub fn configs(topic_prefix: &Option<String>, lattice: &str) -> String {
+            format!(
+                "{}.config.get_many",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1),
+            )
+        }
+
+        pub fn host_config(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+            config_name: &str,
+        ) -> String {
+            format!(
+                "{}.config.get_host.{host_id}.{config_name}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_1),
+            )
+        }
+    }
+}
+
+pub mod v2 {
+    //! Version 2 subjects, which group host-targeted operations under
+    //! `<prefix>.v2.<lattice>.host.<host_id>`
+
+    use crate::broker::CTL_API_VERSION_2;
+
+    use super::prefix;
+
+    fn host(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!(
+            "{}.host.{host_id}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn provider_auction_subject(topic_prefix: &Option<String>, lattice: &str) -> String {
+        format!(
+            "{}.provider.auction",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn component_auction_subject(topic_prefix: &Option<String>, lattice: &str) -> String {
+        format!(
+            "{}.component.auction",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn put_link(topic_prefix: &Option<String>, lattice: &str) -> String {
+        format!(
+            "{}.link.put",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn delete_link(topic_prefix: &Option<String>, lattice: &str) -> String {
+        format!(
+            "{}.link.del",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn publish_registries(topic_prefix: &Option<String>, lattice: &str) -> String {
+        format!(
+            "{}.registry.put",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn put_config(topic_prefix: &Option<String>, lattice: &str, config_name: &str) -> String {
+        format!(
+            "{}.config.put.{config_name}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn delete_config(
+        topic_prefix: &Option<String>,
+        lattice: &str,
+        config_name: &str,
+    ) -> String {
+        format!(
+            "{}.config.del.{config_name}",
+            prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+        )
+    }
+
+    pub fn put_host_config(
+        topic_prefix: &Option<String>,
+        lattice: &str,
+        host_id: &str,
+        config_name: &str,
+    ) -> String {
+        format!(
+            "{}.config.put.{config_name}",
+            host(topic_prefix, lattice, host_id)
+        )
+    }
+
+    pub fn delete_host_config(
+        topic_prefix: &Option<String>,
+        lattice: &str,
+        host_id: &str,
+        config_name: &str,
+    ) -> String {
+        format!(
+            "{}.config.del.{config_name}",
+            host(topic_prefix, lattice, host_id)
+        )
+    }
+
+    pub fn put_label(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!("{}.label.put", host(topic_prefix, lattice, host_id))
+    }
+
+    pub fn delete_label(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!("{}.label.del", host(topic_prefix, lattice, host_id))
+    }
+
+    pub fn put_labels(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!("{}.label.put_many", host(topic_prefix, lattice, host_id))
+    }
+
+    pub fn delete_labels(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+        format!("{}.label.del_many", host(topic_prefix, lattice, host_id))
+    }
+
+    pub mod commands {
+        use super::host;
+
+        pub fn scale_component(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.component.scale", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn stop_components(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.component.stop", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn start_provider(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.provider.start", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn stop_provider(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.provider.stop", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn update_provider_config(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.provider.update", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn update_component(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.component.update", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn stop_host(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+            format!("{}.stop", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn drain_host(topic_prefix: &Option<String>, lattice: &str, host_id: &str) -> String {
+            format!("{}.drain", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn prefetch_images(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.prefetch", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn cleanup_host_data(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.cleanup", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn update_host_tracing(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.tracing", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn profile_component(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.component.profile", host(topic_prefix, lattice, host_id))
+        }
+    }
+
+    pub mod queries {
+        use crate::broker::CTL_API_VERSION_2;
+
+        use super::{host, prefix};
+
+        pub fn link_definitions(topic_prefix: &Option<String>, lattice: &str) -> String {
+            format!(
+                "{}.link.get",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+            )
+        }
+
+        pub fn claims(topic_prefix: &Option<String>, lattice: &str) -> String {
+            format!(
+                "{}.claims.get",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+            )
+        }
+
+        pub fn host_inventory(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.get", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn top_memory_consumers(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+        ) -> String {
+            format!("{}.component.memory", host(topic_prefix, lattice, host_id))
+        }
+
+        pub fn hosts(topic_prefix: &Option<String>, lattice: &str) -> String {
+            format!(
+                "{}.host.ping",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_2)
+            )
+        }
+
+        pub fn config(topic_prefix: &Option<String>, lattice: &str, config_name: &str) -> String {
+            format!(
+                "{}.config.get.{config_name}",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_2),
+            )
+        }
+
+        pub fn configs(topic_prefix: &Option<String>, lattice: &str) -> String {
+            format!(
+                "{}.config.get_many",
+                prefix(topic_prefix, lattice, CTL_API_VERSION_2),
+            )
+        }
+
+        pub fn host_config(
+            topic_prefix: &Option<String>,
+            lattice: &str,
+            host_id: &str,
+            config_name: &str,
+        ) -> String {
+            format!(
+                "{}.config.get.{config_name}",
+                host(topic_prefix, lattice, host_id),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::collections::BTreeMap;
+
+    use async_nats::HeaderMap;
+    use bytes::Bytes;
+    use futures::future::BoxFuture;
+    use futures::stream::BoxStream;
+    use futures::FutureExt as _;
+
+    use super::{operation, parse, v1_subject, ParsedSubject, ProtocolVersion, Subjects};
+    use crate::testing::MockLattice;
+    use crate::{ClientBuilder, ControlTransport, Host, NoResponders, Result, TransportMessage};
+
+    const HOST_ID: &str = "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC";
+
+    #[test]
+    fn subjects_round_trip() {
+        let topic_prefix = Some("wasmbus.ctl".to_string());
+        let host_config = format!("{HOST_ID}.my-config");
+        for version in [ProtocolVersion::V1, ProtocolVersion::V2] {
+            let subjects = Subjects {
+                topic_prefix: &topic_prefix,
+                lattice: "default",
+                version,
+            };
+            for (subject, expected) in [
+                (subjects.hosts(), ("host", "ping", "")),
+                (subjects.host_inventory(HOST_ID), ("host", "get", HOST_ID)),
+                (
+                    subjects.top_memory_consumers(HOST_ID),
+                    ("component", "memory", HOST_ID),
+                ),
+                (subjects.stop_host(HOST_ID), ("host", "stop", HOST_ID)),
+                (subjects.drain_host(HOST_ID), ("host", "drain", HOST_ID)),
+                (
+                    subjects.prefetch_images(HOST_ID),
+                    ("host", "prefetch", HOST_ID),
+                ),
+                (
+                    subjects.cleanup_host_data(HOST_ID),
+                    ("host", "cleanup", HOST_ID),
+                ),
+                (
+                    subjects.update_host_tracing(HOST_ID),
+                    ("host", "tracing", HOST_ID),
+                ),
+                (
+                    subjects.profile_component(HOST_ID),
+                    ("component", "profile", HOST_ID),
+                ),
+                (
+                    subjects.scale_component(HOST_ID),
+                    ("component", "scale", HOST_ID),
+                ),
+                (
+                    subjects.stop_components(HOST_ID),
+                    ("component", "stop", HOST_ID),
+                ),
+                (
+                    subjects.update_provider_config(HOST_ID),
+                    ("provider", "update", HOST_ID),
+                ),
+                (
+                    subjects.start_provider(HOST_ID),
+                    ("provider", "start", HOST_ID),
+                ),
+                (subjects.put_labels(HOST_ID), ("label", "put_many", HOST_ID)),
+                (
+                    subjects.component_auction_subject(),
+                    ("component", "auction", ""),
+                ),
+                (subjects.put_link(), ("link", "put", "")),
+                (subjects.config("my.config"), ("config", "get", "my.config")),
+                (subjects.configs(), ("config", "get_many", "")),
+                (
+                    subjects.host_config(HOST_ID, "my-config"),
+                    ("config", "get_host", host_config.as_str()),
+                ),
+                (
+                    subjects.put_host_config(HOST_ID, "my-config"),
+                    ("config", "put_host", host_config.as_str()),
+                ),
+                (
+                    subjects.delete_host_config(HOST_ID, "my-config"),
+                    ("config", "del_host", host_config.as_str()),
+                ),
+            ] {
+                let (resource, action, arg) = expected;
+                assert_eq!(
+                    parse(&topic_prefix, "default", &subject),
+                    Some(ParsedSubject {
+                        version,
+                        resource,
+                        action,
+                        arg: arg.into()
+                    }),
+                    "{subject}"
+                );
+                // `v2` subjects translate to the `v1` subject of the same operation
+                let v1 = v1_subject(&topic_prefix, "default", &subject);
+                assert_eq!(v1.is_some(), version == ProtocolVersion::V2, "{subject}");
+                if let Some(v1) = v1 {
+                    assert_eq!(
+                        parse(&topic_prefix, "default", &v1),
+                        Some(ParsedSubject {
+                            version: ProtocolVersion::V1,
+                            resource,
+                            action,
+                            arg: arg.into()
+                        }),
+                        "{subject}"
+                    );
+                }
+                assert_eq!(
+                    operation(&topic_prefix, "default", &subject),
+                    format!("{resource}.{action}")
+                );
+            }
+        }
+
+        let subjects = Subjects {
+            topic_prefix: &None,
+            lattice: "default",
+            version: ProtocolVersion::V2,
+        };
+        assert_eq!(
+            subjects.scale_component(HOST_ID),
+            format!("wasmbus.ctl.v2.default.host.{HOST_ID}.component.scale")
+        );
+        assert_eq!(
+            subjects.host_inventory(HOST_ID),
+            format!("wasmbus.ctl.v2.default.host.{HOST_ID}.get")
+        );
+        assert_eq!(
+            subjects.put_host_config(HOST_ID, "my-config"),
+            format!("wasmbus.ctl.v2.default.host.{HOST_ID}.config.put.my-config")
+        );
+        assert_eq!(
+            v1_subject(&None, "default", &subjects.scale_component(HOST_ID)),
+            Some(format!("wasmbus.ctl.v1.default.component.scale.{HOST_ID}"))
+        );
+        assert_eq!(v1_subject(&None, "other", &subjects.hosts()), None);
+        assert_eq!(parse(&None, "other", &subjects.hosts()), None);
+        assert_eq!(subjects.events(">"), "wasmbus.evt.v2.default.>");
+        assert_eq!(
+            Subjects {
+                version: ProtocolVersion::V1,
+                ..subjects
+            }
+            .events("component_scaled"),
+            "wasmbus.evt.default.component_scaled"
+        );
+        assert_eq!("2".parse::<ProtocolVersion>(), Ok(ProtocolVersion::V2));
+        assert!("v3".parse::<ProtocolVersion>().is_err());
+    }
+
+    #[tokio::test]
+    async fn clients_speak_both_protocol_versions() -> Result<()> {
+        let lattice = MockLattice::new("default");
+        lattice.add_host_with_labels(HOST_ID, BTreeMap::new());
+        for version in [ProtocolVersion::V1, ProtocolVersion::V2] {
+            let client = lattice.client_builder().protocol_version(version).build();
+            assert_eq!(client.protocol_version(), version);
+            assert_eq!(client.get_hosts().await?.len(), 1);
+            assert!(client.put_label(HOST_ID, "zone", "a").await?.succeeded());
+            let inventory = client.get_host_inventory(HOST_ID).await?;
+            assert_eq!(
+                inventory.data().map(|inv| inv.labels().get("zone")),
+                Some(Some(&"a".to_string()))
+            );
+        }
+        let requests = lattice.requests();
+        assert!(requests
+            .iter()
+            .any(|s| *s == format!("wasmbus.ctl.v1.default.label.put.{HOST_ID}")));
+        assert!(requests
+            .iter()
+            .any(|s| *s == format!("wasmbus.ctl.v2.default.host.{HOST_ID}.label.put")));
+        Ok(())
+    }
+
+    /// Transport of a lattice whose hosts predate `v2` subjects
+    #[derive(Debug)]
+    struct V1Lattice(MockLattice);
+
+    impl ControlTransport for V1Lattice {
+        fn request(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+            timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            if subject.contains(".v2.") {
+                return futures::future::ready(Err(NoResponders::new(&subject).into())).boxed();
+            }
+            self.0.request(subject, headers, payload, timeout)
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.0.request_many(subject, headers, payload)
+        }
+
+        fn publish(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            self.0.publish(subject, headers, payload)
+        }
+
+        fn subscribe(
+            &self,
+            subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.0.subscribe(subject)
+        }
+    }
+
+    #[tokio::test]
+    async fn v2_clients_fall_back_to_v1_subjects() -> Result<()> {
+        let lattice = MockLattice::new("default");
+        lattice.add_host_with_labels(HOST_ID, BTreeMap::new());
+        let client = ClientBuilder::with_transport(V1Lattice(lattice.clone()))
+            .lattice("default")
+            .protocol_version(ProtocolVersion::V2)
+            .build();
+        assert!(client.put_label(HOST_ID, "zone", "a").await?.succeeded());
+        assert_eq!(
+            lattice.requests(),
+            [format!("wasmbus.ctl.v1.default.label.put.{HOST_ID}")]
+        );
+
+        // Hosts too old to serve `v2` subjects are sent `v1` requests right away, and so are
+        // lattice-wide requests until every host is known to serve `v2` subjects
+        let lattice = MockLattice::new("default");
+        lattice.add_host(Host {
+            id: HOST_ID.into(),
+            version: Some("1.9.0".into()),
+            ..Default::default()
+        });
+        let client = lattice
+            .client_builder()
+            .protocol_version(ProtocolVersion::V2)
+            .build();
+        assert_eq!(client.get_hosts().await?.len(), 1);
+        assert!(client.put_label(HOST_ID, "zone", "a").await?.succeeded());
+        assert_eq!(
+            lattice.requests(),
+            [
+                "wasmbus.ctl.v1.default.host.ping".to_string(),
+                format!("wasmbus.ctl.v1.default.label.put.{HOST_ID}"),
+            ]
+        );
+        Ok(())
     }
 }
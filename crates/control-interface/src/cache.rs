@@ -0,0 +1,442 @@
+//! An opt-in, event-driven cache of lattice state for read-heavy consumers such as dashboards
+
+use core::fmt::{self, Debug};
+use core::time::Duration;
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use cloudevents::event::Event;
+use cloudevents::{AttributesReader, Data};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{debug, trace, warn};
+
+use crate::types::ctl::CtlResponse;
+use crate::types::event::EventStreamGap;
+use crate::types::host::Host;
+use crate::types::link::Link;
+use crate::{Client, Result};
+
+/// Default amount of time cached state is served before it is queried from the lattice again
+pub const DEFAULT_CACHE_MAX_AGE: Duration = Duration::from_secs(30);
+
+/// Lattice events that the cache applies or uses to invalidate cached state
+const CACHE_EVENTS: &[&str] = &[
+    "host_started",
+    "host_stopped",
+    "host_heartbeat",
+    "labels_changed",
+    "linkdef_set",
+    "linkdef_deleted",
+    "component_scaled",
+    "provider_started",
+    "provider_stopped",
+];
+
+/// A [`Client`] wrapper that maintains an in-memory view of the hosts, links and claims in a
+/// lattice.
+///
+/// The view is kept up to date from the lattice event stream and fully reconciled with the
+/// lattice every `max_age`, as well as whenever the event stream signals that events may have been
+/// missed. [`CachingClient::get_hosts`], [`CachingClient::get_links`] and
+/// [`CachingClient::get_claims`] are answered from the cache while it is fresh, and otherwise fall
+/// back to a scatter-gather query of the lattice. All other operations are available on the
+/// wrapped client through [`CachingClient::client`].
+pub struct CachingClient {
+    client: Client,
+    max_age: Duration,
+    state: Arc<RwLock<CacheState>>,
+    updater: JoinHandle<()>,
+}
+
+impl Debug for CachingClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachingClient")
+            .field("client", &self.client)
+            .field("max_age", &self.max_age)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for CachingClient {
+    fn drop(&mut self) {
+        self.updater.abort();
+    }
+}
+
+impl CachingClient {
+    /// Wrap a [`Client`], caching state for at most `max_age` (see [`DEFAULT_CACHE_MAX_AGE`]).
+    ///
+    /// This subscribes to lattice events and spawns a background task that keeps the cache up to
+    /// date until the [`CachingClient`] is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if subscribing to lattice events fails
+    pub async fn new(client: Client, max_age: Duration) -> Result<Self> {
+        let mut events = client
+            .events_receiver(CACHE_EVENTS.iter().map(ToString::to_string).collect())
+            .await?;
+        let state = Arc::new(RwLock::new(CacheState::default()));
+        let updater = tokio::spawn({
+            let client = client.clone();
+            let state = Arc::clone(&state);
+            async move {
+                let mut reconcile = tokio::time::interval(max_age);
+                reconcile.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    tokio::select! {
+                        evt = events.recv() => {
+                            let Some(evt) = evt else {
+                                warn!("lattice event stream closed, cache will no longer be updated from events");
+                                return;
+                            };
+                            if EventStreamGap::from_event(&evt).is_some() {
+                                debug!("lattice events may have been missed, reconciling cache");
+                                reconcile.reset_immediately();
+                                continue;
+                            }
+                            write(&state).apply_event(&evt);
+                        }
+                        _ = reconcile.tick() => {
+                            trace!("reconciling lattice cache");
+                            if let Err(e) = reconcile_state(&client, &state).await {
+                                warn!(error = %e, "failed to reconcile lattice cache");
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            client,
+            max_age,
+            state,
+            updater,
+        })
+    }
+
+    /// Get the wrapped [`Client`]
+    #[must_use]
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Drop all cached state, so that the next query of each kind goes to the lattice
+    pub fn invalidate(&self) {
+        *write(&self.state) = CacheState::default();
+    }
+
+    /// Get all responsive hosts in the lattice, from the cache if it is fresh.
+    ///
+    /// See [`Client::get_hosts`].
+    pub async fn get_hosts(&self) -> Result<Vec<CtlResponse<Host>>> {
+        let cached = read(&self.state).hosts.fresh(self.max_age);
+        let hosts = match cached {
+            Some(hosts) => hosts,
+            None => {
+                let hosts = fetch_hosts(&self.client).await?;
+                write(&self.state).hosts = Cached::new(hosts.clone());
+                hosts
+            }
+        };
+        Ok(hosts.into_iter().map(CtlResponse::ok).collect())
+    }
+
+    /// Get all links in the lattice, from the cache if it is fresh.
+    ///
+    /// See [`Client::get_links`].
+    pub async fn get_links(&self) -> Result<CtlResponse<Vec<Link>>> {
+        let cached = read(&self.state).links.fresh(self.max_age);
+        if let Some(links) = cached {
+            return Ok(CtlResponse::ok(links));
+        }
+        let res = self.client.get_links().await?;
+        if let Some(links) = res.response.as_ref().filter(|_| res.success) {
+            write(&self.state).links = Cached::new(links.clone());
+        }
+        Ok(res)
+    }
+
+    /// Get the claims of all components and providers in the lattice, from the cache if it is
+    /// fresh.
+    ///
+    /// See [`Client::get_claims`].
+    pub async fn get_claims(&self) -> Result<CtlResponse<Vec<HashMap<String, String>>>> {
+        let cached = read(&self.state).claims.fresh(self.max_age);
+        if let Some(claims) = cached {
+            return Ok(CtlResponse::ok(claims));
+        }
+        let res = self.client.get_claims().await?;
+        if let Some(claims) = res.response.as_ref().filter(|_| res.success) {
+            write(&self.state).claims = Cached::new(claims.clone());
+        }
+        Ok(res)
+    }
+}
+
+/// A cached value along with the time it was last reconciled with the lattice
+#[derive(Debug)]
+struct Cached<T>(Option<(T, Instant)>);
+
+impl<T> Default for Cached<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: Clone> Cached<T> {
+    fn new(value: T) -> Self {
+        Self(Some((value, Instant::now())))
+    }
+
+    /// Get a copy of the value, if it was reconciled within `max_age`
+    fn fresh(&self, max_age: Duration) -> Option<T> {
+        self.0
+            .as_ref()
+            .filter(|(_, at)| at.elapsed() < max_age)
+            .map(|(value, _)| value.clone())
+    }
+
+    /// Update the value in place without marking it as reconciled, if it is cached
+    fn update(&mut self, f: impl FnOnce(&mut T)) {
+        if let Some((value, _)) = &mut self.0 {
+            f(value);
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.0 = None;
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    hosts: Cached<Vec<Host>>,
+    links: Cached<Vec<Link>>,
+    claims: Cached<Vec<HashMap<String, String>>>,
+}
+
+impl CacheState {
+    /// Apply a lattice event to the cached state, invalidating any state that cannot be updated
+    /// from the event alone
+    fn apply_event(&mut self, evt: &Event) {
+        let ty = evt.ty().rsplit('.').next().unwrap_or_default();
+        let Some(Data::Json(data)) = evt.data() else {
+            return;
+        };
+        let str_field = |key: &str| data.get(key).and_then(serde_json::Value::as_str);
+        match ty {
+            "host_heartbeat" => {
+                let host_id = evt.source().as_str();
+                let mut known = false;
+                self.hosts.update(|hosts| {
+                    let Some(host) = hosts.iter_mut().find(|h| h.id == host_id) else {
+                        return;
+                    };
+                    known = true;
+                    if let Some(labels) = data
+                        .get("labels")
+                        .and_then(|l| serde_json::from_value(l.clone()).ok())
+                    {
+                        host.labels = labels;
+                    }
+                    if let Some(uptime) = data.get("uptime_seconds").and_then(|u| u.as_u64()) {
+                        host.uptime_seconds = uptime;
+                    }
+                    if let Some(uptime) = str_field("uptime_human") {
+                        host.uptime_human = Some(uptime.to_string());
+                    }
+                    if let Some(version) = str_field("version") {
+                        host.version = Some(version.to_string());
+                    }
+                });
+                // A heartbeat from an unknown host means the cached host list is incomplete
+                if !known {
+                    self.hosts.invalidate();
+                }
+            }
+            "host_started" => self.hosts.invalidate(),
+            "host_stopped" => {
+                let host_id = evt.source().as_str();
+                self.hosts.update(|hosts| hosts.retain(|h| h.id != host_id));
+                self.claims.invalidate();
+            }
+            "labels_changed" => {
+                let (Some(host_id), Some(labels)) = (
+                    str_field("host_id"),
+                    data.get("labels")
+                        .and_then(|l| serde_json::from_value(l.clone()).ok()),
+                ) else {
+                    self.hosts.invalidate();
+                    return;
+                };
+                self.hosts.update(|hosts| {
+                    if let Some(host) = hosts.iter_mut().find(|h| h.id == host_id) {
+                        host.labels = labels;
+                    }
+                });
+            }
+            "linkdef_set" => match serde_json::from_value::<Link>(data.clone()) {
+                Ok(link) => self.links.update(|links| {
+                    links.retain(|l| !same_link(l, &link));
+                    links.push(link);
+                }),
+                Err(e) => {
+                    debug!(error = %e, "failed to parse link from event, invalidating links");
+                    self.links.invalidate();
+                }
+            },
+            "linkdef_deleted" => {
+                let (Some(source_id), Some(name), Some(wit_namespace), Some(wit_package)) = (
+                    str_field("source_id"),
+                    str_field("name"),
+                    str_field("wit_namespace"),
+                    str_field("wit_package"),
+                ) else {
+                    self.links.invalidate();
+                    return;
+                };
+                self.links.update(|links| {
+                    links.retain(|l| {
+                        !(l.source_id == source_id
+                            && l.name == name
+                            && l.wit_namespace == wit_namespace
+                            && l.wit_package == wit_package)
+                    });
+                });
+            }
+            "component_scaled" | "provider_started" | "provider_stopped" => {
+                self.claims.invalidate();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns true if both links share a source, name and WIT package, in which case setting one
+/// replaces the other
+fn same_link(a: &Link, b: &Link) -> bool {
+    a.source_id == b.source_id
+        && a.name == b.name
+        && a.wit_namespace == b.wit_namespace
+        && a.wit_package == b.wit_package
+}
+
+async fn fetch_hosts(client: &Client) -> Result<Vec<Host>> {
+    Ok(client
+        .get_hosts()
+        .await?
+        .into_iter()
+        .filter_map(|res| res.response)
+        .collect())
+}
+
+/// Query the lattice for all cached state
+async fn reconcile_state(client: &Client, state: &RwLock<CacheState>) -> Result<()> {
+    let (hosts, links, claims) =
+        futures::join!(fetch_hosts(client), client.get_links(), client.get_claims());
+    let hosts = hosts?;
+    let (links, claims) = (links?, claims?);
+    let mut state = write(state);
+    state.hosts = Cached::new(hosts);
+    if let Some(links) = links.response.filter(|_| links.success) {
+        state.links = Cached::new(links);
+    }
+    if let Some(claims) = claims.response.filter(|_| claims.success) {
+        state.claims = Cached::new(claims);
+    }
+    Ok(())
+}
+
+fn read(state: &RwLock<CacheState>) -> std::sync::RwLockReadGuard<'_, CacheState> {
+    state.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write(state: &RwLock<CacheState>) -> std::sync::RwLockWriteGuard<'_, CacheState> {
+    state.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use cloudevents::{EventBuilder, EventBuilderV10};
+
+    use super::{CacheState, Cached};
+    use crate::{Host, Link};
+
+    fn event(ty: &str, source: &str, data: serde_json::Value) -> cloudevents::Event {
+        EventBuilderV10::new()
+            .id("id")
+            .source(source)
+            .ty(format!("com.wasmcloud.lattice.{ty}"))
+            .data("application/json", data)
+            .build()
+            .expect("failed to build event")
+    }
+
+    #[test]
+    fn applies_events_to_cached_state() {
+        let mut state = CacheState {
+            hosts: Cached::new(vec![
+                Host {
+                    id: "host-a".into(),
+                    ..Default::default()
+                },
+                Host {
+                    id: "host-b".into(),
+                    ..Default::default()
+                },
+            ]),
+            links: Cached::new(Vec::new()),
+            claims: Cached::new(Vec::new()),
+        };
+        let max_age = std::time::Duration::from_secs(60);
+
+        state.apply_event(&event(
+            "labels_changed",
+            "host-a",
+            serde_json::json!({ "host_id": "host-a", "labels": { "zone": "a" } }),
+        ));
+        state.apply_event(&event("host_stopped", "host-b", serde_json::json!({})));
+        let hosts = state.hosts.fresh(max_age).expect("hosts should be cached");
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(
+            hosts[0].labels,
+            BTreeMap::from([("zone".to_string(), "a".to_string())])
+        );
+
+        let link = serde_json::json!({
+            "source_id": "component",
+            "target": "provider",
+            "name": "default",
+            "wit_namespace": "wasi",
+            "wit_package": "keyvalue",
+            "interfaces": ["store"],
+        });
+        state.apply_event(&event("linkdef_set", "host-a", link.clone()));
+        state.apply_event(&event("linkdef_set", "host-a", link.clone()));
+        let links = state.links.fresh(max_age).expect("links should be cached");
+        assert_eq!(links, vec![serde_json::from_value::<Link>(link).unwrap()]);
+
+        state.apply_event(&event(
+            "linkdef_deleted",
+            "host-a",
+            serde_json::json!({
+                "source_id": "component",
+                "name": "default",
+                "wit_namespace": "wasi",
+                "wit_package": "keyvalue",
+            }),
+        ));
+        assert!(state.links.fresh(max_age).unwrap().is_empty());
+
+        state.apply_event(&event("provider_started", "host-a", serde_json::json!({})));
+        assert!(state.claims.fresh(max_age).is_none());
+
+        state.apply_event(&event("host_heartbeat", "host-c", serde_json::json!({})));
+        assert!(state.hosts.fresh(max_age).is_none());
+    }
+}
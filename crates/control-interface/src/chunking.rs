@@ -0,0 +1,332 @@
+//! Chunked replies to control interface queries whose results can exceed the NATS maximum payload
+//! size, such as [`Client::get_links`](crate::Client::get_links) and
+//! [`Client::get_claims`](crate::Client::get_claims).
+//!
+//! The protocol works as follows:
+//!
+//! 1. The client marks requests that it can reassemble chunked replies for with the
+//!    [`ACCEPT_CHUNKED_HEADER`] header, and waits for any number of replies on its reply inbox.
+//! 2. A host whose reply exceeds the maximum payload size splits it into chunks with [`split`] and
+//!    publishes each chunk to the reply subject, carrying the [`CHUNK_INDEX_HEADER`] and
+//!    [`CHUNK_COUNT_HEADER`] headers. Replies that fit into a single message are sent unchanged,
+//!    as are replies to clients that did not set the header.
+//! 3. The client collects chunks until all of them were received, and concatenates their payloads
+//!    in order.
+
+use async_nats::HeaderMap;
+use bytes::{Bytes, BytesMut};
+use futures::stream::BoxStream;
+use futures::StreamExt as _;
+
+use crate::limits::ReplyLimits;
+use crate::transport::TransportMessage;
+use crate::Result;
+
+/// Header set on requests by clients that can reassemble chunked replies
+pub const ACCEPT_CHUNKED_HEADER: &str = "Wasmcloud-Accept-Chunked";
+/// Header carrying the zero-based index of a chunk of a reply
+pub const CHUNK_INDEX_HEADER: &str = "Wasmcloud-Chunk-Index";
+/// Header carrying the total number of chunks of a reply
+pub const CHUNK_COUNT_HEADER: &str = "Wasmcloud-Chunk-Count";
+/// Number of bytes of every chunk reserved for headers, which count towards the maximum payload
+pub const CHUNK_HEADER_RESERVE: usize = 1024;
+/// Maximum number of chunks of a reply accepted by clients, which bounds the memory allocated for
+/// a reply before its chunks are received
+pub const MAX_CHUNKS: usize = 4096;
+
+/// Returns whether a request with the given headers accepts chunked replies
+#[must_use]
+pub fn accepts_chunks(headers: Option<&HeaderMap>) -> bool {
+    headers.is_some_and(|headers| headers.get(ACCEPT_CHUNKED_HEADER).is_some())
+}
+
+/// Split a reply payload into chunks that fit into messages of `max_payload` bytes, returning the
+/// headers and payload of every chunk. Payloads that fit into a single message are returned as a
+/// single chunk without headers.
+#[must_use]
+pub fn split(payload: Bytes, max_payload: usize) -> Vec<(HeaderMap, Bytes)> {
+    if payload.len() <= max_payload {
+        return vec![(HeaderMap::new(), payload)];
+    }
+    let chunk_size = max_payload.saturating_sub(CHUNK_HEADER_RESERVE).max(1);
+    let count = payload.len().div_ceil(chunk_size);
+    (0..count)
+        .map(|index| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CHUNK_INDEX_HEADER, index.to_string().as_str());
+            headers.insert(CHUNK_COUNT_HEADER, count.to_string().as_str());
+            let end = payload.len().min((index + 1) * chunk_size);
+            (headers, payload.slice(index * chunk_size..end))
+        })
+        .collect()
+}
+
+fn header_usize(msg: &TransportMessage, name: &str) -> Result<Option<usize>> {
+    msg.headers
+        .get(name)
+        .map(|value| {
+            value
+                .as_str()
+                .parse()
+                .map_err(|e| format!("invalid `{name}` header in chunked reply: {e}").into())
+        })
+        .transpose()
+}
+
+/// Read a reply to a request on `subject` from `replies`, reassembling it if it was chunked. The
+/// returned message carries the headers of the first received chunk. Receiving chunks stops as soon
+/// as they exceed the size limit of `limits`.
+pub(crate) async fn reassemble(
+    mut replies: BoxStream<'static, TransportMessage>,
+    subject: &str,
+    limits: &ReplyLimits,
+) -> Result<TransportMessage> {
+    let first = replies
+        .next()
+        .await
+        .ok_or("reply stream closed without a reply")?;
+    let Some(count) = header_usize(&first, CHUNK_COUNT_HEADER)? else {
+        return Ok(first);
+    };
+    if count == 0 || count > MAX_CHUNKS {
+        return Err(format!(
+            "chunked reply has an invalid chunk count of {count}, expected 1 to {MAX_CHUNKS}"
+        )
+        .into());
+    }
+    let mut chunks: Vec<Option<Bytes>> = vec![None; count];
+    let mut received = 0;
+    let mut size = 0usize;
+    let mut msg = first.clone();
+    loop {
+        let index = header_usize(&msg, CHUNK_INDEX_HEADER)?
+            .filter(|index| *index < count)
+            .ok_or("chunked reply is missing a valid chunk index")?;
+        size = size.saturating_add(msg.payload.len());
+        limits.check_bytes(subject, size)?;
+        if chunks[index].replace(msg.payload).is_none() {
+            received += 1;
+        }
+        if received == count {
+            break;
+        }
+        msg = replies
+            .next()
+            .await
+            .ok_or_else(|| format!("chunked reply ended after {received} of {count} chunks"))?;
+    }
+    let mut payload = BytesMut::new();
+    for chunk in chunks.into_iter().flatten() {
+        payload.extend_from_slice(&chunk);
+    }
+    Ok(TransportMessage {
+        payload: payload.freeze(),
+        ..first
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::collections::HashMap;
+
+    use async_nats::HeaderMap;
+    use bytes::Bytes;
+    use futures::future::{self, BoxFuture};
+    use futures::stream::{self, BoxStream};
+    use futures::{FutureExt as _, StreamExt as _};
+
+    use super::{accepts_chunks, reassemble, split, CHUNK_COUNT_HEADER};
+    use crate::limits::ReplyLimits;
+    use crate::testing::MockLattice;
+    use crate::transport::{ControlTransport, TransportMessage};
+    use crate::{ClientBuilder, Link, ReplyTooLarge, Result};
+
+    /// Transport that chunks replies of a mock lattice like a host with a small maximum payload
+    #[derive(Debug)]
+    struct SmallPayloads(MockLattice);
+
+    const MAX_PAYLOAD: usize = 1100;
+
+    impl ControlTransport for SmallPayloads {
+        fn request(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+            timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            self.0.request(subject, headers, payload, timeout)
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            async move {
+                let chunked = accepts_chunks(Some(&headers));
+                let replies: Vec<_> = self
+                    .0
+                    .request_many(subject, headers, payload)
+                    .await?
+                    .collect()
+                    .await;
+                let mut chunks = Vec::new();
+                for reply in replies {
+                    if !chunked {
+                        chunks.push(reply);
+                        continue;
+                    }
+                    // Deliver chunks out of order to exercise reassembly
+                    let mut split = split(reply.payload.clone(), MAX_PAYLOAD);
+                    split.reverse();
+                    chunks.extend(split.into_iter().map(|(headers, payload)| {
+                        TransportMessage::new(reply.subject.clone(), payload).with_headers(headers)
+                    }));
+                }
+                Ok(stream::iter(chunks).boxed())
+            }
+            .boxed()
+        }
+
+        fn publish(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            self.0.publish(subject, headers, payload)
+        }
+
+        fn subscribe(
+            &self,
+            subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.0.subscribe(subject)
+        }
+    }
+
+    #[tokio::test]
+    async fn large_replies_are_reassembled() -> Result<()> {
+        let lattice = MockLattice::new("default");
+        let client = ClientBuilder::with_transport(SmallPayloads(lattice.clone())).build();
+        for i in 0..50 {
+            let link = Link::builder()
+                .source_id(&format!("component-{i}"))
+                .target(&format!("provider-{i}"))
+                .wit_namespace("wasi")
+                .wit_package("keyvalue")
+                .interfaces(vec!["store".to_string()])
+                .name("default")
+                .build()?;
+            assert!(client.put_link(link).await?.succeeded());
+        }
+        lattice.set_claims(vec![HashMap::from([(
+            "padding".to_string(),
+            "x".repeat(5 * MAX_PAYLOAD),
+        )])]);
+
+        let links = client.get_links().await?.into_data().unwrap_or_default();
+        assert_eq!(links.len(), 50);
+        assert_eq!(links[49].source_id(), "component-49");
+        let claims = client.get_claims().await?.into_data().unwrap_or_default();
+        assert_eq!(claims[0]["padding"].len(), 5 * MAX_PAYLOAD);
+
+        let chunks = split(Bytes::from(vec![0; 3 * MAX_PAYLOAD]), MAX_PAYLOAD);
+        assert!(chunks.len() > 3);
+        assert!(chunks.iter().all(
+            |(headers, payload)| headers.get(CHUNK_COUNT_HEADER).is_some()
+                && payload.len() + 1024 <= MAX_PAYLOAD
+        ));
+        assert_eq!(split(Bytes::from_static(b"small"), MAX_PAYLOAD).len(), 1);
+
+        // Missing chunks are reported instead of returning a truncated reply
+        let (headers, payload) = chunks[0].clone();
+        let incomplete =
+            stream::iter([TransportMessage::new("reply", payload).with_headers(headers)]).boxed();
+        assert!(reassemble(incomplete, "subject", &ReplyLimits::default())
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("ended after 1"));
+
+        // Chunked replies are aborted once they exceed the size limit of the client
+        let limited = ClientBuilder::with_transport(SmallPayloads(lattice.clone()))
+            .reply_limits(ReplyLimits::default().max_bytes(2 * MAX_PAYLOAD))
+            .build();
+        let err = limited.get_claims().await.unwrap_err();
+        let err = err
+            .downcast_ref::<ReplyTooLarge>()
+            .expect("reply too large");
+        assert!(err.subject().ends_with("claims.get"));
+        assert!(err.actual() <= 3 * MAX_PAYLOAD);
+
+        // Chunk counts from malformed replies are bounded before allocating
+        for count in ["0", "18446744073709551615"] {
+            let mut headers = HeaderMap::new();
+            headers.insert(CHUNK_COUNT_HEADER, count);
+            let reply =
+                stream::iter([TransportMessage::new("reply", Bytes::new()).with_headers(headers)])
+                    .boxed();
+            assert!(reassemble(reply, "subject", &ReplyLimits::default())
+                .await
+                .is_err());
+        }
+        Ok(())
+    }
+
+    /// Transport of a lattice whose hosts never reply, not even to start a request
+    #[derive(Debug)]
+    struct Stalled;
+
+    impl ControlTransport for Stalled {
+        fn request(
+            &self,
+            _subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+            _timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            future::pending().boxed()
+        }
+
+        fn request_many(
+            &self,
+            _subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            future::pending().boxed()
+        }
+
+        fn publish(
+            &self,
+            _subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            future::pending().boxed()
+        }
+
+        fn subscribe(
+            &self,
+            _subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            future::pending().boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn chunked_requests_time_out_as_a_whole() {
+        let client = ClientBuilder::with_transport(Stalled)
+            .timeout(Duration::from_millis(50))
+            .build();
+        let res = tokio::time::timeout(Duration::from_secs(5), client.get_claims())
+            .await
+            .expect("request should time out on its own");
+        assert!(res.is_err());
+    }
+}
@@ -1,42 +1,123 @@
 //! Control interface client
 
+use core::cmp::Reverse;
 use core::fmt::{self, Debug};
+use core::future::Future;
 use core::time::Duration;
 
 use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use async_nats::Subscriber;
+use async_nats::jetstream::consumer::pull::OrderedConfig;
+use async_nats::jetstream::consumer::DeliverPolicy;
+
+use async_nats::jetstream::kv::Operation;
 use cloudevents::event::Event;
-use futures::{StreamExt, TryFutureExt};
+use cloudevents::{AttributesReader, Data};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use nkeys::KeyPair;
 use serde::de::DeserializeOwned;
-use tokio::sync::mpsc::Receiver;
-use tracing::{debug, error, instrument, trace};
+use serde::Deserialize;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{debug, error, instrument, trace, warn, Span};
 
+use crate::broker::ProtocolVersion;
+use crate::chunking;
+use crate::connect::NatsConnectOptions;
+use crate::encoding::{decode, Encoding, ACCEPT_ENCODING_HEADER};
+use crate::interceptor::{ControlInterceptor, ControlRequest};
+use crate::limits::{ReplyLimits, ReplyTooLarge};
+use crate::mux::SubscriptionMux;
+use crate::otel;
+use crate::stats::{self, ClientStats, Outcome, StatsRecorder};
+use crate::transport::{is_no_responders, ControlTransport, TransportMessage};
+use crate::types::annotations::Annotations;
+use crate::types::config::{
+    ConfigChange, ConfigNames, ConfigRef, ConfigRefs, ConfigRevision, ConfigVersion, ConfigsByName,
+};
 use crate::types::ctl::{
-    CtlResponse, ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
-    UpdateComponentCommand,
+    sign_sender, CleanupHostDataCommand, CtlResponse, DrainHostCommand, DrainOptions,
+    PrefetchImagesCommand, RevisionQuery, ScaleComponentCommand, StartProviderCommand,
+    StopComponentsCommand, StopHostCommand, StopProviderCommand, UpdateComponentCommand,
+    UpdateHostTracingCommand, UpdateProviderConfigCommand, COMMAND_ID_HEADER, SENDER_HEADER,
+    SENDER_SIGNATURE_HEADER,
+};
+use crate::types::epoch::{LatticeEpoch, EPOCH_HEADER, LATTICE_EPOCH_KEY};
+use crate::types::event::{
+    EventMatcher, EventStreamGap, EventStreamGapReason, LatticeEvent, DEFAULT_EVENT_STREAM,
+};
+use crate::types::host::{
+    DataCategory, DataDirUsage, Host, HostDecommission, HostInventory, HostInventoryPage,
+    HostLabel, HostLabelIdentifiers, HostLabels, InventoryPageRequest,
+    HOST_DECOMMISSION_KEY_PREFIX,
+};
+use crate::types::label::{diff_labels, LabelChange, LabelSelector};
+use crate::types::link::{diff_links, Link, LinkChange, COMPONENT_SPEC_KEY_PREFIX};
+use crate::types::naming::{validate_alias_name, AliasTarget, LatticeAlias, ALIAS_KEY_PREFIX};
+use crate::types::profile::{
+    ComponentMemoryUsage, ComponentProfile, ProfileComponentCommand, TopMemoryQuery,
 };
-use crate::types::host::{Host, HostInventory, HostLabel};
-use crate::types::link::Link;
 use crate::types::registry::RegistryCredential;
+use crate::types::route::{
+    validate_route_name, IngressRoute, TrafficSplit, INGRESS_ROUTE_KEY_PREFIX,
+    TRAFFIC_SPLIT_KEY_PREFIX,
+};
 use crate::types::rpc::{
-    ComponentAuctionAck, ComponentAuctionRequest, DeleteInterfaceLinkDefinitionRequest,
-    ProviderAuctionAck, ProviderAuctionRequest,
+    AuctionBid, ComponentAuctionAck, ComponentAuctionRequest, DeleteInterfaceLinkDefinitionRequest,
+    ProviderAuctionAck, ProviderAuctionRequest, PutLinkRequest, ResourceRequirements,
 };
+use crate::types::snapshot::LatticeSnapshot;
+use crate::version::HostVersions;
 use crate::{
-    broker, json_deserialize, json_serialize, otel, HostLabelIdentifier, IdentifierKind, Result,
+    broker, json_deserialize, json_serialize, ComponentId, HostId, HostLabelIdentifier,
+    HostNotFound, IdentifierKind, IntoId, LinkName, ProviderRef, Result,
 };
 
+/// Maximum amount of time to wait for each lifecycle event of a restart
+const RESTART_EVENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Interval between host queries in [`Client::wait_for_hosts`]
+const HOST_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often event receivers check the transport connection state to detect reconnects
+const EVENT_CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Initial delay before an event receiver retries a failed resubscribe, doubled on every failure
+const EVENT_RESUBSCRIBE_MIN_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Maximum delay between attempts of an event receiver to resubscribe
+const EVENT_RESUBSCRIBE_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Error message of hosts replying to a control interface subject they do not know
+const UNSUPPORTED_SUBJECT: &str = "unsupported subject";
+
 /// A client builder that can be used to fluently provide configuration settings used to construct
 /// the control interface client
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub struct ClientBuilder {
-    nc: async_nats::Client,
+    nc: Option<async_nats::Client>,
+    transport: Arc<dyn ControlTransport>,
     topic_prefix: Option<String>,
     lattice: String,
     timeout: Duration,
     auction_timeout: Duration,
+    js_domain: Option<String>,
+    protocol_version: ProtocolVersion,
+    encoding: Encoding,
+    interceptors: Vec<Arc<dyn ControlInterceptor>>,
+    verify_hosts: bool,
+    reply_limits: ReplyLimits,
+    subscriptions: SubscriptionMux,
+    epoch: u64,
+    sender: Option<Arc<KeyPair>>,
+    event_stream: String,
+    default_annotations: Annotations,
 }
 
 impl ClientBuilder {
@@ -45,11 +126,62 @@ impl ClientBuilder {
     #[must_use]
     pub fn new(nc: async_nats::Client) -> ClientBuilder {
         ClientBuilder {
-            nc,
+            nc: Some(nc.clone()),
+            ..ClientBuilder::with_transport(nc)
+        }
+    }
+
+    /// Connects to NATS with the given options, returning a client builder that uses the new
+    /// connection with all other configuration values set to their defaults
+    ///
+    /// ```rust,no_run
+    /// use wasmcloud_control_interface::{ClientBuilder, NatsConnectOptions};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// let client = ClientBuilder::connect(
+    ///     NatsConnectOptions::new("tls://nats.example.com:4222")
+    ///         .credsfile("/etc/wasmcloud/user.creds")
+    ///         .tls_ca_file("/etc/wasmcloud/ca.pem"),
+    /// )
+    /// .await?
+    /// .lattice("production")
+    /// .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the credentials are invalid or the connection cannot be established
+    pub async fn connect(options: NatsConnectOptions) -> Result<ClientBuilder> {
+        Ok(ClientBuilder::new(options.connect().await?))
+    }
+
+    /// Creates a new client builder that exchanges control interface messages over the given
+    /// transport, with all configuration values set to their defaults.
+    ///
+    /// Clients built without a NATS connection cannot access lattice metadata buckets, so
+    /// operations such as [`Client::watch_config`] and the alias operations fail.
+    #[must_use]
+    pub fn with_transport(transport: impl ControlTransport) -> ClientBuilder {
+        ClientBuilder {
+            nc: None,
+            transport: Arc::new(transport),
             topic_prefix: None,
             lattice: "default".to_string(),
             timeout: Duration::from_secs(2),
             auction_timeout: Duration::from_secs(5),
+            js_domain: None,
+            protocol_version: ProtocolVersion::default(),
+            encoding: Encoding::default(),
+            interceptors: Vec::new(),
+            verify_hosts: false,
+            reply_limits: ReplyLimits::default(),
+            subscriptions: SubscriptionMux::default(),
+            epoch: 0,
+            sender: None,
+            event_stream: DEFAULT_EVENT_STREAM.to_string(),
+            default_annotations: Annotations::new(),
         }
     }
 
@@ -90,15 +222,148 @@ impl ClientBuilder {
         }
     }
 
-    /// Constructs the client with the given configuration from the builder
+    /// Sets the JetStream domain used when accessing lattice metadata buckets directly (for
+    /// example when watching configuration). If not set, the default JetStream domain is used
+    #[must_use]
+    pub fn js_domain(self, domain: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            js_domain: Some(domain.into()),
+            ..self
+        }
+    }
+
+    /// Sets the version of the control interface subject scheme used for requests and events. If
+    /// not set, the default will be [`ProtocolVersion::V1`], which all hosts understand.
+    ///
+    /// With [`ProtocolVersion::V2`], requests to a host use `v2` subjects unless the host is known
+    /// to be too old to serve them, and are retried on the `v1` subject if no host responds.
+    /// Lattice-wide requests, such as auctions, only use `v2` subjects once [`Client::get_hosts`]
+    /// or host heartbeats have shown that every host of the lattice serves them. Events are
+    /// received on the `v2` event subjects, which hosts only publish to when configured to do so
+    #[must_use]
+    pub fn protocol_version(self, protocol_version: ProtocolVersion) -> ClientBuilder {
+        ClientBuilder {
+            protocol_version,
+            ..self
+        }
+    }
+
+    /// Sets the encoding the client asks hosts to use for replies. If not set, the default will be
+    /// [`Encoding::Json`]. Hosts that do not support the requested encoding reply with JSON, which
+    /// the client always understands
+    #[must_use]
+    pub fn encoding(self, encoding: Encoding) -> ClientBuilder {
+        ClientBuilder { encoding, ..self }
+    }
+
+    /// Adds an interceptor that observes and may modify every request sent by the client and
+    /// observes the replies. Interceptors run in the order they were added
+    #[must_use]
+    pub fn interceptor(mut self, interceptor: impl ControlInterceptor) -> ClientBuilder {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// Sets whether the client checks that a host is responsive before sending it a command. If
+    /// not set, the default is to not check hosts.
+    ///
+    /// Commands to hosts that no longer exist fail with [`HostNotFound`] either way. Checking
+    /// hosts first also detects hosts that are still subscribed but stopped responding, at the
+    /// cost of an additional request per command
+    #[must_use]
+    pub fn verify_hosts(self, verify_hosts: bool) -> ClientBuilder {
+        ClientBuilder {
+            verify_hosts,
+            ..self
+        }
+    }
+
+    /// Sets the limits on the size of replies accepted by the client. If not set, replies of any
+    /// size are accepted.
+    ///
+    /// Replies exceeding a limit fail with a [`ReplyTooLarge`](crate::ReplyTooLarge) error before
+    /// they are decoded, which protects long-running controllers from misbehaving hosts. Replies
+    /// to queries answered by several hosts, like [`Client::get_hosts`], that exceed a limit are
+    /// dropped instead
+    #[must_use]
+    pub fn reply_limits(self, reply_limits: ReplyLimits) -> ClientBuilder {
+        ClientBuilder {
+            reply_limits,
+            ..self
+        }
+    }
+
+    /// Sets the lattice epoch sent with every command, which hosts use to reject commands of
+    /// controllers that are no longer the leader. If not set, commands carry no epoch and are
+    /// always accepted. See [`Client::advance_lattice_epoch`]
+    #[must_use]
+    pub fn epoch(self, epoch: u64) -> ClientBuilder {
+        ClientBuilder { epoch, ..self }
+    }
+
+    /// Sets the key identifying this client to hosts, which rate limit requests per sender. Requests
+    /// are signed with the key, so that other clients can't send requests in its name. If not set,
+    /// hosts identify the client by the reply subjects of its requests
+    #[must_use]
+    pub fn sender(self, key: KeyPair) -> ClientBuilder {
+        ClientBuilder {
+            sender: Some(Arc::new(key)),
+            ..self
+        }
+    }
+
+    /// Sets the name of the JetStream stream retaining lattice events, which is read by
+    /// [`Client::replay_events`]. If not set, the default will be
+    /// [`DEFAULT_EVENT_STREAM`](crate::DEFAULT_EVENT_STREAM)
+    #[must_use]
+    pub fn event_stream(self, name: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            event_stream: name.into(),
+            ..self
+        }
+    }
+
+    /// Sets annotations carried by every scale, update and start provider command sent by the
+    /// client, e.g. to record the owner or environment of the workloads it manages. Annotations
+    /// passed to a command take precedence over defaults with the same key. If not set, commands
+    /// carry only the annotations passed to them
+    #[must_use]
+    pub fn default_annotations(self, annotations: impl Into<Annotations>) -> ClientBuilder {
+        ClientBuilder {
+            default_annotations: annotations.into(),
+            ..self
+        }
+    }
+
+    /// Constructs the client with the given configuration from the builder.
+    ///
+    /// Clients built from clones of the same builder share their event subscriptions, so that
+    /// applications creating many clients, e.g. one per lattice or tenant, hold a single
+    /// subscription per event subject rather than one per client.
     #[must_use]
     pub fn build(self) -> Client {
         Client {
             nc: self.nc,
+            transport: self.transport,
             topic_prefix: self.topic_prefix,
             lattice: self.lattice,
             timeout: self.timeout,
             auction_timeout: self.auction_timeout,
+            js_domain: self.js_domain,
+            protocol_version: self.protocol_version,
+            encoding: self.encoding,
+            host_versions: HostVersions::default(),
+            interceptors: self.interceptors,
+            verify_hosts: self.verify_hosts,
+            reply_limits: self.reply_limits,
+            subscriptions: self.subscriptions,
+            epoch: Arc::new(AtomicU64::new(self.epoch)),
+            sender: self.sender,
+            event_stream: self.event_stream,
+            default_annotations: self.default_annotations,
+            stats: StatsRecorder::default(),
+            tasks: TaskTracker::new(),
+            shutdown: CancellationToken::new(),
         }
     }
 }
@@ -107,8 +372,10 @@ impl ClientBuilder {
 #[derive(Clone)]
 #[non_exhaustive]
 pub struct Client {
-    /// Internal `async-nats` client
-    nc: async_nats::Client,
+    /// Internal `async-nats` client, used to access lattice metadata buckets
+    nc: Option<async_nats::Client>,
+    /// Transport used to exchange control interface messages
+    transport: Arc<dyn ControlTransport>,
     /// Topic prefix that should be used with this lattice control client
     topic_prefix: Option<String>,
     /// Lattice prefix
@@ -117,6 +384,36 @@ pub struct Client {
     timeout: Duration,
     /// Timeout to use when limiting auctions
     auction_timeout: Duration,
+    /// JetStream domain used to access lattice metadata buckets
+    js_domain: Option<String>,
+    /// Version of the subject scheme used for requests
+    protocol_version: ProtocolVersion,
+    /// Encoding requested for replies
+    encoding: Encoding,
+    /// Host versions observed in host listings and heartbeats
+    host_versions: HostVersions,
+    /// Interceptors applied to every request
+    interceptors: Vec<Arc<dyn ControlInterceptor>>,
+    /// Whether hosts are checked to be responsive before sending them commands
+    verify_hosts: bool,
+    /// Limits on the size of accepted replies
+    reply_limits: ReplyLimits,
+    /// Event subscriptions shared by all clients built from the same builder
+    subscriptions: SubscriptionMux,
+    /// Lattice epoch sent with every command, or 0 to send none
+    epoch: Arc<AtomicU64>,
+    /// Key identifying this client to hosts, signing every request if set
+    sender: Option<Arc<KeyPair>>,
+    /// Name of the JetStream stream retaining lattice events
+    event_stream: String,
+    /// Annotations added to every scale, update and start provider command
+    default_annotations: Annotations,
+    /// Statistics of the requests sent by this client and its clones
+    stats: StatsRecorder,
+    /// Background tasks spawned by this client and its clones
+    tasks: TaskTracker,
+    /// Cancelled once the client is closed, stopping all background tasks
+    shutdown: CancellationToken,
 }
 
 impl Debug for Client {
@@ -126,6 +423,16 @@ impl Debug for Client {
             .field("lattice", &self.lattice)
             .field("timeout", &self.timeout)
             .field("auction_timeout", &self.auction_timeout)
+            .field("js_domain", &self.js_domain)
+            .field("protocol_version", &self.protocol_version)
+            .field("encoding", &self.encoding)
+            .field("interceptors", &self.interceptors)
+            .field("verify_hosts", &self.verify_hosts)
+            .field("reply_limits", &self.reply_limits)
+            .field("epoch", &self.epoch())
+            .field("sender", &self.sender.as_ref().map(|key| key.public_key()))
+            .field("event_stream", &self.event_stream)
+            .field("default_annotations", &self.default_annotations)
             .finish_non_exhaustive()
     }
 }
@@ -139,9 +446,11 @@ impl Client {
     }
 
     /// Get a copy of the NATS client in use by this control client
+    ///
+    /// Returns `None` if the client was built with [`ClientBuilder::with_transport`]
     #[allow(unused)]
     #[must_use]
-    pub fn nats_client(&self) -> async_nats::Client {
+    pub fn nats_client(&self) -> Option<async_nats::Client> {
         self.nc.clone()
     }
 
@@ -150,6 +459,119 @@ impl Client {
         self.lattice.as_ref()
     }
 
+    /// Retrieve the version of the subject scheme used by the [`Client`]
+    #[must_use]
+    pub fn protocol_version(&self) -> ProtocolVersion {
+        self.protocol_version
+    }
+
+    /// Retrieve the lattice epoch sent with commands by the [`Client`] and its clones, or 0 if
+    /// commands carry no epoch
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.epoch.load(Ordering::Relaxed)
+    }
+
+    /// Retrieve the annotations added to every scale, update and start provider command sent by
+    /// the [`Client`]
+    #[must_use]
+    pub fn default_annotations(&self) -> &Annotations {
+        &self.default_annotations
+    }
+
+    /// Merges the default annotations of the client into the annotations of a command, with the
+    /// annotations of the command taking precedence
+    fn with_default_annotations(&self, annotations: Option<Annotations>) -> Option<Annotations> {
+        merge_default_annotations(&self.default_annotations, annotations)
+    }
+
+    /// Subjects of this client's lattice, in the scheme of its protocol version
+    fn subjects(&self) -> broker::Subjects<'_> {
+        broker::Subjects {
+            topic_prefix: &self.topic_prefix,
+            lattice: &self.lattice,
+            version: self.protocol_version,
+        }
+    }
+
+    /// Subjects of requests that any host of the lattice may answer, which only use `v2` subjects
+    /// once every host observed on the lattice serves them
+    fn lattice_subjects(&self) -> broker::Subjects<'_> {
+        let subjects = self.subjects();
+        if subjects.version == ProtocolVersion::V2 && !self.host_versions.lattice_serves_v2() {
+            return broker::Subjects {
+                version: ProtocolVersion::V1,
+                ..subjects
+            };
+        }
+        subjects
+    }
+
+    /// Subjects of requests to the given host, which only use `v2` subjects if the host is
+    /// expected to serve them
+    fn host_subjects(&self, host_id: &str) -> broker::Subjects<'_> {
+        let subjects = self.subjects();
+        if subjects.version == ProtocolVersion::V2 && !self.host_versions.serves_v2(host_id) {
+            return broker::Subjects {
+                version: ProtocolVersion::V1,
+                ..subjects
+            };
+        }
+        subjects
+    }
+
+    /// Get the last version reported by the given host, if the client has observed one.
+    ///
+    /// Host versions are recorded whenever [`Client::get_hosts`] is called and when host heartbeats
+    /// are received via [`Client::events_receiver`]. Host-targeted operations that a host's version
+    /// is known not to support fail with an [`UnsupportedByHost`](crate::UnsupportedByHost) error
+    /// instead of timing out.
+    #[must_use]
+    pub fn host_version(&self, host_id: &str) -> Option<semver::Version> {
+        self.host_versions.get(host_id)
+    }
+
+    /// Get a snapshot of the statistics of the control interface operations performed by this
+    /// client and its clones
+    #[must_use]
+    pub fn stats(&self) -> ClientStats {
+        self.stats.snapshot()
+    }
+
+    /// Record the outcome of a request on `subject` that started at `start`
+    fn record(&self, subject: &str, start: Instant, outcome: Outcome) {
+        let operation = broker::operation(&self.topic_prefix, &self.lattice, subject);
+        self.stats.record(&operation, start.elapsed(), outcome);
+    }
+
+    /// Record the outcome of a failed request on `subject` that started at `start`
+    fn record_error(
+        &self,
+        subject: &str,
+        start: Instant,
+        error: &(dyn std::error::Error + 'static),
+    ) {
+        let outcome = if stats::is_timeout(error) {
+            Outcome::TimedOut
+        } else {
+            Outcome::Failed
+        };
+        self.record(subject, start, outcome);
+    }
+
+    /// Create a JetStream context for accessing lattice metadata buckets
+    pub(crate) fn jetstream(&self) -> Result<async_nats::jetstream::Context> {
+        let nc = self
+            .nc
+            .clone()
+            .ok_or("accessing lattice metadata requires a NATS connection")?;
+        if let Some(domain) = &self.js_domain {
+            Ok(async_nats::jetstream::with_domain(nc, domain))
+        } else {
+            Ok(async_nats::jetstream::new(nc))
+        }
+    }
+
     /// Perform a request with a timeout
     #[instrument(level = "debug", skip_all)]
     pub(crate) async fn request_timeout(
@@ -157,55 +579,365 @@ impl Client {
         subject: String,
         payload: Vec<u8>,
         timeout: Duration,
-    ) -> Result<async_nats::Message> {
-        match tokio::time::timeout(
-            timeout,
-            self.nc.request_with_headers(
-                subject,
-                otel::HeaderInjector::default_with_span().into(),
-                payload.into(),
-            ),
-        )
-        .await
+    ) -> Result<TransportMessage> {
+        self.request_with(subject, payload, timeout, false).await
+    }
+
+    /// Send a request like [`Client::request_timeout`], accepting a reply that the host splits
+    /// into multiple chunks if it exceeds the maximum payload size
+    async fn request_chunked(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<TransportMessage> {
+        self.request_with(subject, payload, timeout, true).await
+    }
+
+    async fn request_with(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+        timeout: Duration,
+        chunked: bool,
+    ) -> Result<TransportMessage> {
+        let Some(v1_subject) = broker::v1_subject(&self.topic_prefix, &self.lattice, &subject)
+        else {
+            return self.request_once(subject, payload, timeout, chunked).await;
+        };
+        match self
+            .request_once(subject.clone(), payload.clone(), timeout, chunked)
+            .await
         {
-            Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out").into()),
-            Ok(Ok(message)) => Ok(message),
-            Ok(Err(e)) => Err(e.into()),
+            // Hosts released before `v2` subjects were introduced only serve `v1` subjects
+            Err(e) if is_no_responders(e.as_ref()) => {
+                debug!(%subject, %v1_subject, "no responders on v2 subject, retrying on v1 subject");
+                self.request_once(v1_subject, payload, timeout, chunked)
+                    .await
+            }
+            res => res,
+        }
+    }
+
+    async fn request_once(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+        timeout: Duration,
+        chunked: bool,
+    ) -> Result<TransportMessage> {
+        let start = Instant::now();
+        let mut request = self.intercept_request(subject.clone(), payload)?;
+        let res = if chunked {
+            request
+                .headers
+                .insert(chunking::ACCEPT_CHUNKED_HEADER, "true");
+            // The timeout covers the whole exchange, a host that never replies must not leave the
+            // request waiting any longer than one that stops sending chunks
+            let exchange = async {
+                let replies = self
+                    .transport
+                    .request_many(
+                        request.subject.clone(),
+                        request.headers.clone(),
+                        request.payload.clone(),
+                    )
+                    .await?;
+                chunking::reassemble(replies, &subject, &self.reply_limits).await
+            };
+            match tokio::time::timeout(timeout, exchange).await {
+                Ok(res) => res,
+                Err(_) => {
+                    Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out").into())
+                }
+            }
+        } else {
+            self.transport
+                .request(
+                    request.subject.clone(),
+                    request.headers.clone(),
+                    request.payload.clone(),
+                    timeout,
+                )
+                .await
+        };
+        let res = res.and_then(|msg| {
+            self.reply_limits.check(&subject, &msg)?;
+            otel::link_remote_context(&Span::current(), &msg.headers);
+            Ok(msg)
+        });
+        match &res {
+            Ok(_) => self.record(&subject, start, Outcome::Responses(1)),
+            Err(e) => self.record_error(&subject, start, e.as_ref()),
+        }
+        for interceptor in &self.interceptors {
+            interceptor.on_response(&request, &res);
+        }
+        res
+    }
+
+    /// Send a request to a single host, failing with [`HostNotFound`] if the host is not running
+    /// in the lattice
+    async fn host_request(
+        &self,
+        host_id: &HostId,
+        operation: &str,
+        subject: String,
+        payload: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<TransportMessage> {
+        if self.verify_hosts {
+            self.verify_host(host_id, operation).await?;
+        }
+        self.request_timeout(subject, payload, timeout)
+            .await
+            .map_err(|e| {
+                if is_no_responders(e.as_ref()) {
+                    HostNotFound::new(host_id.as_str(), operation).into()
+                } else {
+                    e
+                }
+            })
+    }
+
+    /// Check that a host is responsive by requesting the first page of its inventory
+    async fn verify_host(&self, host_id: &HostId, operation: &str) -> Result<()> {
+        let subject = self.host_subjects(host_id).host_inventory(host_id);
+        let bytes = json_serialize(InventoryPageRequest::new(1))?;
+        match self.request_timeout(subject, bytes, self.timeout).await {
+            Ok(_) => Ok(()),
+            Err(e) if is_no_responders(e.as_ref()) || stats::is_timeout(e.as_ref()) => {
+                debug!(%host_id, operation, "host did not respond to liveness check");
+                Err(HostNotFound::new(host_id.as_str(), operation).into())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Build a request, running it through all interceptors
+    fn intercept_request(&self, subject: String, payload: Vec<u8>) -> Result<ControlRequest> {
+        let mut request = ControlRequest::new(subject, payload);
+        let command_id = wasmcloud_core::id::command_id();
+        debug!(subject = %request.subject, command_id, "sending control interface request");
+        request
+            .headers
+            .insert(COMMAND_ID_HEADER, command_id.as_str());
+        if self.encoding != Encoding::Json {
+            request
+                .headers
+                .insert(ACCEPT_ENCODING_HEADER, self.encoding.content_type());
         }
+        let epoch = self.epoch();
+        if epoch > 0 {
+            request
+                .headers
+                .insert(EPOCH_HEADER, epoch.to_string().as_str());
+        }
+        if let Some(key) = &self.sender {
+            let signature = sign_sender(key, &request.subject, &command_id)?;
+            request
+                .headers
+                .insert(SENDER_HEADER, key.public_key().as_str());
+            request
+                .headers
+                .insert(SENDER_SIGNATURE_HEADER, signature.as_str());
+        }
+        for interceptor in &self.interceptors {
+            interceptor.on_request(&mut request)?;
+        }
+        Ok(request)
     }
 
     /// Queries the lattice for all responsive hosts, waiting for the full period specified by
     /// _timeout_.
     #[instrument(level = "debug", skip_all)]
     pub async fn get_hosts(&self) -> Result<Vec<CtlResponse<Host>>> {
-        let subject = broker::v1::queries::hosts(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().hosts();
         debug!("get_hosts:publish {}", &subject);
-        self.publish_and_wait(subject, Vec::new()).await
+        let hosts: Vec<CtlResponse<Host>> = self.publish_and_wait(subject, Vec::new()).await?;
+        for host in hosts.iter().filter_map(CtlResponse::data) {
+            if let Some(version) = host.version() {
+                self.host_versions.observe(host.id(), version);
+            }
+        }
+        Ok(hosts)
+    }
+
+    /// Queries the lattice for all responsive hosts whose labels match the given selector, e.g.
+    /// `arch=amd64,zone in (us-east-1,us-east-2),!draining`. See [`LabelSelector`] for the
+    /// supported expressions.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_hosts_by_label(&self, selector: &str) -> Result<Vec<Host>> {
+        let selector: LabelSelector = selector.parse()?;
+        Ok(self
+            .get_hosts()
+            .await?
+            .into_iter()
+            .filter_map(CtlResponse::into_data)
+            .filter(|host| selector.matches(host.labels()))
+            .collect())
+    }
+
+    /// Waits until at least `n` hosts in the lattice are responsive, returning the responsive
+    /// hosts.
+    ///
+    /// The lattice is queried for hosts until enough hosts respond, and again whenever a host
+    /// starts. This is useful to wait for a lattice to come up in tests and bootstrap scripts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fewer than `n` hosts responded before the `timeout` elapsed
+    #[instrument(level = "debug", skip(self))]
+    pub async fn wait_for_hosts(&self, n: usize, timeout: Duration) -> Result<Vec<Host>> {
+        let deadline = Instant::now() + timeout;
+        // Host started events are only used to query again early, so hosts are still found by
+        // polling if subscribing fails
+        let mut started = self
+            .events_receiver(vec!["host_started".to_string()])
+            .await
+            .map_err(|e| debug!(?e, "failed to subscribe to host_started events"))
+            .ok();
+        let mut found = 0;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match tokio::time::timeout(remaining, self.get_hosts()).await {
+                Ok(Ok(hosts)) => {
+                    let hosts: Vec<Host> = hosts
+                        .into_iter()
+                        .filter_map(CtlResponse::into_data)
+                        .collect();
+                    if hosts.len() >= n {
+                        return Ok(hosts);
+                    }
+                    found = hosts.len();
+                    trace!(found, n, "waiting for hosts");
+                }
+                Ok(Err(e)) => debug!(?e, "failed to query hosts"),
+                Err(_) => {}
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(
+                    format!("only {found} of {n} hosts responded within {timeout:?}").into(),
+                );
+            }
+            let wait = remaining.min(HOST_POLL_INTERVAL);
+            match started.as_mut() {
+                Some(events) => {
+                    let _ = tokio::time::timeout(wait, events.recv()).await;
+                }
+                None => tokio::time::sleep(wait).await,
+            }
+        }
     }
 
     /// Retrieves the contents of a running host
     #[instrument(level = "debug", skip_all)]
-    pub async fn get_host_inventory(&self, host_id: &str) -> Result<CtlResponse<HostInventory>> {
-        let subject = broker::v1::queries::host_inventory(
-            &self.topic_prefix,
-            &self.lattice,
-            IdentifierKind::is_host_id(host_id)?.as_str(),
-        );
+    pub async fn get_host_inventory(
+        &self,
+        host_id: impl IntoId<HostId>,
+    ) -> Result<CtlResponse<HostInventory>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "get_host_inventory")?;
+        let subject = self.host_subjects(&host_id).host_inventory(&host_id);
         debug!("get_host_inventory:request {}", &subject);
-        match self.request_timeout(subject, vec![], self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive host inventory from target host: {e}").into()),
+        match self
+            .host_request(
+                &host_id,
+                "get_host_inventory",
+                subject,
+                vec![],
+                self.timeout,
+            )
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive host inventory from target host",
+            )),
+        }
+    }
+
+    /// Retrieves a page of the contents of a running host, which avoids exceeding payload limits
+    /// on hosts running thousands of components.
+    ///
+    /// Hosts that do not support paging return their complete inventory as a single page.
+    ///
+    /// ```rust
+    /// use wasmcloud_control_interface::{Client, InventoryPageRequest};
+    ///
+    /// async fn count_components(client: &Client, host_id: &str) -> anyhow::Result<usize> {
+    ///     let mut count = 0;
+    ///     let mut request = InventoryPageRequest::new(500);
+    ///     loop {
+    ///         let resp = client
+    ///             .get_host_inventory_page(host_id, &request)
+    ///             .await
+    ///             .map_err(|e| anyhow::anyhow!(e))?;
+    ///         let page = resp.into_data().ok_or_else(|| anyhow::anyhow!("no inventory"))?;
+    ///         count += page.inventory().components().len();
+    ///         match page.next_cursor() {
+    ///             Some(cursor) => request = request.with_cursor(cursor),
+    ///             None => return Ok(count),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_host_inventory_page(
+        &self,
+        host_id: impl IntoId<HostId>,
+        request: &InventoryPageRequest,
+    ) -> Result<CtlResponse<HostInventoryPage>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "get_host_inventory")?;
+        let subject = self.host_subjects(&host_id).host_inventory(&host_id);
+        debug!(%subject, cursor = ?request.cursor(), limit = request.limit(), "get_host_inventory_page:request");
+        let bytes = json_serialize(request)?;
+        match self
+            .host_request(&host_id, "get_host_inventory", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive host inventory from target host",
+            )),
         }
     }
 
     /// Retrieves the full set of all cached claims in the lattice.
     #[instrument(level = "debug", skip_all)]
     pub async fn get_claims(&self) -> Result<CtlResponse<Vec<HashMap<String, String>>>> {
-        let subject = broker::v1::queries::claims(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().claims();
         debug!("get_claims:request {}", &subject);
-        match self.request_timeout(subject, vec![], self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive claims from lattice: {e}").into()),
+        match self.request_chunked(subject, vec![], self.timeout).await {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(e, "Did not receive claims from lattice")),
+        }
+    }
+
+    /// Retrieves the claims like [`Client::get_claims`], reflecting at least all changes up to and
+    /// including `min_revision` of the lattice data bucket.
+    ///
+    /// The revision of the returned claims is available with [`CtlResponse::revision`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the responding host did not reach `min_revision` in time or does
+    /// not support revision pinning
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_claims_at(
+        &self,
+        min_revision: u64,
+    ) -> Result<CtlResponse<Vec<HashMap<String, String>>>> {
+        let subject = self.lattice_subjects().claims();
+        debug!(%subject, min_revision, "get_claims_at:request");
+        let bytes = json_serialize(RevisionQuery::at_or_after(min_revision))?;
+        match self.request_chunked(subject, bytes, self.timeout).await {
+            Ok(msg) => check_revision(decode(&msg)?, min_revision),
+            Err(e) => Err(request_error(e, "Did not receive claims from lattice")),
         }
     }
 
@@ -218,15 +950,35 @@ impl Client {
     pub async fn perform_component_auction(
         &self,
         component_ref: &str,
-        component_id: &str,
+        component_id: impl IntoId<ComponentId>,
+        constraints: impl Into<BTreeMap<String, String>>,
+    ) -> Result<Vec<CtlResponse<ComponentAuctionAck>>> {
+        self.perform_component_auction_with_requirements(
+            component_ref,
+            component_id,
+            constraints,
+            ResourceRequirements::default(),
+        )
+        .await
+    }
+
+    /// Performs a component auction like [`Client::perform_component_auction`], where hosts
+    /// only respond if they can additionally satisfy the given resource requirements.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn perform_component_auction_with_requirements(
+        &self,
+        component_ref: &str,
+        component_id: impl IntoId<ComponentId>,
         constraints: impl Into<BTreeMap<String, String>>,
+        requirements: ResourceRequirements,
     ) -> Result<Vec<CtlResponse<ComponentAuctionAck>>> {
-        let subject = broker::v1::component_auction_subject(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().component_auction_subject();
         let bytes = json_serialize(
             ComponentAuctionRequest::builder()
                 .component_ref(IdentifierKind::is_component_ref(component_ref)?)
-                .component_id(IdentifierKind::is_component_id(component_id)?)
+                .component_id(component_id.into_id()?.into_string())
                 .constraints(constraints.into())
+                .requirements(requirements)
                 .build()?,
         )?;
         debug!("component_auction:publish {}", &subject);
@@ -251,16 +1003,36 @@ impl Client {
     #[instrument(level = "debug", skip_all)]
     pub async fn perform_provider_auction(
         &self,
-        provider_ref: &str,
+        provider_ref: impl IntoId<ProviderRef>,
+        provider_id: &str,
+        constraints: impl Into<BTreeMap<String, String>>,
+    ) -> Result<Vec<CtlResponse<ProviderAuctionAck>>> {
+        self.perform_provider_auction_with_requirements(
+            provider_ref,
+            provider_id,
+            constraints,
+            ResourceRequirements::default(),
+        )
+        .await
+    }
+
+    /// Performs a provider auction like [`Client::perform_provider_auction`], where hosts only
+    /// respond if they can additionally satisfy the given resource requirements.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn perform_provider_auction_with_requirements(
+        &self,
+        provider_ref: impl IntoId<ProviderRef>,
         provider_id: &str,
         constraints: impl Into<BTreeMap<String, String>>,
+        requirements: ResourceRequirements,
     ) -> Result<Vec<CtlResponse<ProviderAuctionAck>>> {
-        let subject = broker::v1::provider_auction_subject(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().provider_auction_subject();
         let bytes = json_serialize(
             ProviderAuctionRequest::builder()
-                .provider_ref(IdentifierKind::is_provider_ref(provider_ref)?)
+                .provider_ref(provider_ref.into_id()?.into_string())
                 .provider_id(IdentifierKind::is_provider_id(provider_id)?)
                 .constraints(constraints.into())
+                .requirements(requirements)
                 .build()?,
         )?;
         debug!("provider_auction:publish {}", &subject);
@@ -285,39 +1057,46 @@ impl Client {
     /// * `component_ref` - The OCI reference of the component to scale
     /// * `max_instances` - The maximum number of instances this component can run concurrently. Specifying `0` will stop the component.
     /// * `annotations` - Optional annotations to apply to the component
-    /// * `config` - List of named configuration to use for the component
+    /// * `config` - Named configuration to use for the component, see [`ConfigRefs`]
     /// * `allow_update` - Whether to perform allow updates to the component (triggering a separate update)
     ///
     #[instrument(level = "debug", skip_all)]
     #[allow(clippy::too_many_arguments)]
     pub async fn scale_component(
         &self,
-        host_id: &str,
+        host_id: impl IntoId<HostId>,
         component_ref: &str,
-        component_id: &str,
+        component_id: impl IntoId<ComponentId>,
         max_instances: u32,
-        annotations: Option<BTreeMap<String, String>>,
-        config: Vec<String>,
+        annotations: Option<Annotations>,
+        config: impl Into<ConfigRefs>,
     ) -> Result<CtlResponse<()>> {
-        let host_id = IdentifierKind::is_host_id(host_id)?;
-        let subject = broker::v1::commands::scale_component(
-            &self.topic_prefix,
-            &self.lattice,
-            host_id.as_str(),
-        );
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "scale_component")?;
+        let (config, pinned_config) = self.check_config_refs(config.into()).await?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .scale_component(host_id.as_str());
         debug!("scale_component:request {}", &subject);
         let bytes = json_serialize(ScaleComponentCommand {
             max_instances,
             component_ref: IdentifierKind::is_component_ref(component_ref)?,
-            component_id: IdentifierKind::is_component_id(component_id)?,
-            host_id,
-            annotations,
+            component_id: component_id.into_id()?.into_string(),
+            host_id: host_id.to_string(),
+            annotations: self.with_default_annotations(annotations).map(Into::into),
             config,
+            pinned_config,
             ..Default::default()
         })?;
-        match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive scale component acknowledgement: {e}").into()),
+        match self
+            .host_request(&host_id, "scale_component", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive scale component acknowledgement",
+            )),
         }
     }
 
@@ -337,17 +1116,19 @@ impl Client {
         &self,
         registries: HashMap<String, RegistryCredential>,
     ) -> Result<CtlResponse<()>> {
-        let subject = broker::v1::publish_registries(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().publish_registries();
         debug!("put_registries:publish {}", &subject);
         let bytes = json_serialize(&registries)?;
+        let start = Instant::now();
+        let request = self.intercept_request(subject.clone(), bytes)?;
         let resp = self
-            .nc
-            .publish_with_headers(
-                subject,
-                otel::HeaderInjector::default_with_span().into(),
-                bytes.into(),
-            )
+            .transport
+            .publish(request.subject, request.headers, request.payload)
             .await;
+        match &resp {
+            Ok(()) => self.record(&subject, start, Outcome::Responses(0)),
+            Err(e) => self.record_error(&subject, start, e.as_ref()),
+        }
         if let Err(e) = resp {
             Err(format!("Failed to push registry credential map: {e}").into())
         } else {
@@ -359,23 +1140,72 @@ impl Client {
 
     /// Puts a link into the lattice.
     ///
+    /// If a link already exists for the same source, WIT interface and link name but with a
+    /// different target, the host rejects the new link. Use [`Client::put_link_with_force`] to
+    /// replace such a link, or [`Client::put_link_if_absent`] to reject any existing link.
+    ///
     /// # Errors
     ///
     /// Returns an error if it was unable to put the link
     #[instrument(level = "debug", skip_all)]
     pub async fn put_link(&self, link: Link) -> Result<CtlResponse<()>> {
-        // Validate link parameters
-        IdentifierKind::is_component_id(&link.source_id)?;
+        self.put_link_request(PutLinkRequest::from(link)).await
+    }
+
+    /// Puts a link into the lattice, optionally replacing an existing link for the same source,
+    /// WIT interface and link name that points to a different target.
+    ///
+    /// # Arguments
+    ///
+    /// * `link` - The link to put
+    /// * `force` - Whether to replace a conflicting link with a different target
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it was unable to put the link
+    #[instrument(level = "debug", skip_all)]
+    pub async fn put_link_with_force(&self, link: Link, force: bool) -> Result<CtlResponse<()>> {
+        self.put_link_request(PutLinkRequest::builder().link(link).force(force).build()?)
+            .await
+    }
+
+    /// Puts a link into the lattice only if no link exists yet for the same source, WIT interface
+    /// and link name. Unlike [`Client::put_link`], an existing link to the same target is not
+    /// updated either; the host responds with an error instead.
+    ///
+    /// Since any host of the lattice may handle the request, and hosts that predate this option
+    /// would ignore it, the request is refused while such a host has been observed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if it was unable to put the link
+    #[instrument(level = "debug", skip_all)]
+    pub async fn put_link_if_absent(&self, link: Link) -> Result<CtlResponse<()>> {
+        self.host_versions.check_lattice("put_link_if_absent")?;
+        self.put_link_request(
+            PutLinkRequest::builder()
+                .link(link)
+                .if_absent(true)
+                .build()?,
+        )
+        .await
+    }
+
+    /// Send a [`PutLinkRequest`] to the lattice
+    async fn put_link_request(&self, request: PutLinkRequest) -> Result<CtlResponse<()>> {
+        // Validate link parameters
+        let link = request.link();
+        IdentifierKind::is_component_id(&link.source_id)?;
         IdentifierKind::is_component_id(&link.target)?;
         IdentifierKind::is_link_name(&link.name)?;
 
-        let subject = broker::v1::put_link(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().put_link();
         debug!("put_link:request {}", &subject);
 
-        let bytes = crate::json_serialize(link)?;
+        let bytes = crate::json_serialize(request)?;
         match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive put link acknowledgement: {e}").into()),
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(e, "Did not receive put link acknowledgement")),
         }
     }
 
@@ -389,22 +1219,25 @@ impl Client {
     #[instrument(level = "debug", skip_all)]
     pub async fn delete_link(
         &self,
-        source_id: &str,
-        link_name: &str,
+        source_id: impl IntoId<ComponentId>,
+        link_name: impl IntoId<LinkName>,
         wit_namespace: &str,
         wit_package: &str,
     ) -> Result<CtlResponse<()>> {
-        let subject = broker::v1::delete_link(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().delete_link();
         let ld = DeleteInterfaceLinkDefinitionRequest::from_source_and_link_metadata(
-            &IdentifierKind::is_component_id(source_id)?,
-            &IdentifierKind::is_link_name(link_name)?,
+            &source_id.into_id()?,
+            &link_name.into_id()?,
             wit_namespace,
             wit_package,
         );
         let bytes = crate::json_serialize(&ld)?;
         match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive delete link acknowledgement: {e}").into()),
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive delete link acknowledgement",
+            )),
         }
     }
 
@@ -415,11 +1248,32 @@ impl Client {
     ///
     #[instrument(level = "debug", skip_all)]
     pub async fn get_links(&self) -> Result<CtlResponse<Vec<Link>>> {
-        let subject = broker::v1::queries::link_definitions(&self.topic_prefix, &self.lattice);
+        let subject = self.lattice_subjects().link_definitions();
         debug!("get_links:request {}", &subject);
-        match self.request_timeout(subject, vec![], self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive a response to get links: {e}").into()),
+        match self.request_chunked(subject, vec![], self.timeout).await {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(e, "Did not receive a response to get links")),
+        }
+    }
+
+    /// Retrieves the links like [`Client::get_links`], reflecting at least all changes up to and
+    /// including `min_revision` of the lattice data bucket.
+    ///
+    /// The revision of the returned links is available with [`CtlResponse::revision`], which
+    /// allows relating them to revisions observed when watching the bucket.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the responding host did not reach `min_revision` in time or does
+    /// not support revision pinning
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_links_at(&self, min_revision: u64) -> Result<CtlResponse<Vec<Link>>> {
+        let subject = self.lattice_subjects().link_definitions();
+        debug!(%subject, min_revision, "get_links_at:request");
+        let bytes = json_serialize(RevisionQuery::at_or_after(min_revision))?;
+        match self.request_chunked(subject, bytes, self.timeout).await {
+            Ok(msg) => check_revision(decode(&msg)?, min_revision),
+            Err(e) => Err(request_error(e, "Did not receive a response to get links")),
         }
     }
 
@@ -438,12 +1292,15 @@ impl Client {
         config_name: &str,
         config: impl Into<HashMap<String, String>>,
     ) -> Result<CtlResponse<()>> {
-        let subject = broker::v1::put_config(&self.topic_prefix, &self.lattice, config_name);
+        let subject = self.lattice_subjects().put_config(config_name);
         debug!(%subject, %config_name, "Putting config");
         let data = serde_json::to_vec(&config.into())?;
         match self.request_timeout(subject, data, self.timeout).await {
-            Ok(msg) => json_deserialize(&msg.payload),
-            Err(e) => Err(format!("Did not receive a response to put config request: {e}").into()),
+            Ok(msg) => decode(&msg),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to put config request",
+            )),
         }
     }
 
@@ -457,16 +1314,17 @@ impl Client {
     ///
     #[instrument(level = "debug", skip_all)]
     pub async fn delete_config(&self, config_name: &str) -> Result<CtlResponse<()>> {
-        let subject = broker::v1::delete_config(&self.topic_prefix, &self.lattice, config_name);
+        let subject = self.lattice_subjects().delete_config(config_name);
         debug!(%subject, %config_name, "Delete config");
         match self
             .request_timeout(subject, Vec::default(), self.timeout)
             .await
         {
-            Ok(msg) => json_deserialize(&msg.payload),
-            Err(e) => {
-                Err(format!("Did not receive a response to delete config request: {e}").into())
-            }
+            Ok(msg) => decode(&msg),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to delete config request",
+            )),
         }
     }
 
@@ -516,319 +1374,2572 @@ impl Client {
         &self,
         config_name: &str,
     ) -> Result<CtlResponse<HashMap<String, String>>> {
-        let subject = broker::v1::queries::config(&self.topic_prefix, &self.lattice, config_name);
+        let subject = self.lattice_subjects().config(config_name);
         debug!(%subject, %config_name, "Getting config");
         match self
             .request_timeout(subject, Vec::default(), self.timeout)
             .await
         {
-            Ok(msg) => json_deserialize(&msg.payload),
-            Err(e) => Err(format!("Did not receive a response to get config request: {e}").into()),
+            Ok(msg) => decode(&msg),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to get config request",
+            )),
         }
     }
 
-    /// Put a new (or update an existing) label on the given host.
-    ///
-    /// # Arguments
+    /// Get the named config item like [`Client::get_config`], at or after `min_revision` of the
+    /// lattice config bucket.
     ///
-    /// * `host_id` - ID of the host on which the label should be placed
-    /// * `key` - The key of the label
-    /// * `value` - The value of the label
+    /// The revision of the returned config is available with [`CtlResponse::revision`].
     ///
     /// # Errors
     ///
-    /// Will return an error if there is a communication problem with the host
-    ///
-    pub async fn put_label(
+    /// Will return an error if the responding host did not read `min_revision` in time or does not
+    /// support revision pinning
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_config_at(
         &self,
-        host_id: &str,
-        key: &str,
-        value: &str,
-    ) -> Result<CtlResponse<()>> {
-        let subject = broker::v1::put_label(&self.topic_prefix, &self.lattice, host_id);
-        debug!(%subject, "putting label");
-        let bytes = json_serialize(HostLabel {
-            key: key.to_string(),
-            value: value.to_string(),
-        })?;
+        config_name: &str,
+        min_revision: u64,
+    ) -> Result<CtlResponse<HashMap<String, String>>> {
+        let subject = self.lattice_subjects().config(config_name);
+        debug!(%subject, %config_name, min_revision, "Getting config at revision");
+        let bytes = json_serialize(RevisionQuery::at_or_after(min_revision))?;
         match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive put label acknowledgement: {e}").into()),
+            Ok(msg) => check_revision(decode(&msg)?, min_revision),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to get config request",
+            )),
         }
     }
 
-    /// Removes a label from the given host.
+    /// Get several named config items in a single request.
+    ///
+    /// The returned map contains an entry for every requested name. Config items that do not exist
+    /// map to `None`. This saves one round trip per config compared to calling
+    /// [`Client::get_config`] for each name, e.g. when resolving all configs of a provider at
+    /// startup. Hosts that do not support bulk lookups are transparently queried once per name.
     ///
     /// # Arguments
     ///
-    /// * `host_id` - ID of the host on which the label should be deleted
-    /// * `key` - The key of the label that should be deleted
+    /// * `names` - The names of the configs to get
     ///
     /// # Errors
     ///
     /// Will return an error if there is a communication problem with the host
     ///
-    pub async fn delete_label(&self, host_id: &str, key: &str) -> Result<CtlResponse<()>> {
-        let subject = broker::v1::delete_label(&self.topic_prefix, &self.lattice, host_id);
-        debug!(%subject, "removing label");
-        let bytes = json_serialize(HostLabelIdentifier {
-            key: key.to_string(),
-        })?;
-        match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive remove label acknowledgement: {e}").into()),
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_configs(&self, names: Vec<String>) -> Result<CtlResponse<ConfigsByName>> {
+        let subject = self.lattice_subjects().configs();
+        debug!(%subject, count = names.len(), "Getting configs");
+        let bytes = json_serialize(ConfigNames::from_names(names.clone()))?;
+        let resp: CtlResponse<ConfigsByName> =
+            match self.request_timeout(subject, bytes, self.timeout).await {
+                Ok(msg) => decode(&msg)?,
+                Err(e) => {
+                    return Err(request_error(
+                        e,
+                        "Did not receive a response to get configs request",
+                    ))
+                }
+            };
+        if resp.succeeded() || resp.message() != UNSUPPORTED_SUBJECT {
+            return Ok(resp);
+        }
+
+        trace!("host does not support bulk config lookups, getting configs one by one");
+        let configs =
+            futures::future::try_join_all(names.iter().map(|name| self.get_config(name))).await?;
+        let mut found = ConfigsByName::with_capacity(names.len());
+        for (name, resp) in names.into_iter().zip(configs) {
+            if !resp.succeeded() {
+                return Ok(CtlResponse {
+                    success: false,
+                    message: format!("failed to get config `{name}`: {}", resp.message()),
+                    response: None,
+                    revision: None,
+                    code: None,
+                    retry_after_ms: None,
+                });
+            }
+            found.insert(name, resp.into_data());
         }
+        Ok(CtlResponse::ok(found))
     }
 
-    /// Command a host to replace an existing component with a new component indicated by an OCI image reference.
+    /// Puts a named config scoped to the given host, replacing any data that is already present
+    /// for that host.
     ///
-    /// The host will acknowledge this request as soon as it verifies that the target component is running.
+    /// The host merges the values over the lattice-wide config of the same name, so that
+    /// components and providers on that host see the host-scoped values wherever they reference
+    /// the config, e.g. to point them at node-local endpoints. Other hosts are not affected.
     ///
-    /// Note that acknowledgement occurs **before** the new bytes are downloaded. Live-updating an component can take a long time
-    /// and control clients cannot block waiting for a reply that could come several seconds later.
+    /// # Arguments
     ///
-    /// To properly verify that a component has been updated, create  listener for the appropriate [`PublishedEvent`] on the
-    /// control events channel
+    /// * `host_id` - ID of the host the config applies to
+    /// * `config_name` - Name of the lattice-wide config the values override
+    /// * `config` - The values overriding the lattice-wide config on the host
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `host_id` - ID of the host on which the component should be updated
-    /// * `existing_component_id` - ID of the existing component
-    /// * `new_component_ref` - New component reference that should be used
-    /// * `annotations` - Annotations to place on the newly updated component
+    /// Will return an error if there is a communication problem with the host
     ///
     #[instrument(level = "debug", skip_all)]
-    pub async fn update_component(
+    pub async fn put_host_config(
         &self,
-        host_id: &str,
-        existing_component_id: &str,
-        new_component_ref: &str,
-        annotations: Option<BTreeMap<String, String>>,
+        host_id: impl IntoId<HostId>,
+        config_name: &str,
+        config: impl Into<HashMap<String, String>>,
     ) -> Result<CtlResponse<()>> {
-        let host_id = IdentifierKind::is_host_id(host_id)?;
-        let subject = broker::v1::commands::update_component(
-            &self.topic_prefix,
-            &self.lattice,
-            host_id.as_str(),
-        );
-        debug!("update_component:request {}", &subject);
-        let bytes = json_serialize(UpdateComponentCommand {
-            host_id,
-            component_id: IdentifierKind::is_component_id(existing_component_id)?,
-            new_component_ref: IdentifierKind::is_component_ref(new_component_ref)?,
-            annotations,
-        })?;
-        match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive update component acknowledgement: {e}").into()),
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "put_host_config")?;
+        let subject = self
+            .host_subjects(&host_id)
+            .put_host_config(&host_id, config_name);
+        debug!(%subject, %config_name, "Putting host config");
+        let data = serde_json::to_vec(&config.into())?;
+        match self.request_timeout(subject, data, self.timeout).await {
+            Ok(msg) => decode(&msg),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to put host config request",
+            )),
         }
     }
 
-    /// Command a host to start a provider with a given OCI reference.
-    ///
-    /// The specified link name will be used (or "default" if none is specified).
+    /// Get the values of a named config scoped to the given host, as set with
+    /// [`Client::put_host_config`]. This does not include the lattice-wide values of the config.
     ///
-    /// The target wasmCloud host will acknowledge the receipt of this command _before_ downloading the provider's bytes from the
-    /// OCI registry, indicating either a validation failure or success.
+    /// If the host has no values for the config, the response is successful without data.
     ///
-    /// Clients that need deterministic guarantees that the provider has completed its startup process, should
-    /// monitor the control event stream for the appropriate event.
+    /// # Arguments
     ///
-    /// The `provider_configuration` parameter is a list of named configs to use for this provider, and configurations are not required.
+    /// * `host_id` - ID of the host the config applies to
+    /// * `config_name` - Name of the config
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `host_id` - ID of the host on which to start the provider
-    /// * `provider_ref` - Image reference of the provider to start
-    /// * `provider_id` - ID of the provider to start
-    /// * `annotations` - Annotations to place on the started provider
-    /// * `provider_configuration` - Configuration relevant to the provider (if any)
+    /// Will return an error if there is a communication problem with the host
     ///
     #[instrument(level = "debug", skip_all)]
-    pub async fn start_provider(
+    pub async fn get_host_config(
         &self,
-        host_id: &str,
-        provider_ref: &str,
-        provider_id: &str,
-        annotations: Option<BTreeMap<String, String>>,
-        provider_configuration: Vec<String>,
-    ) -> Result<CtlResponse<()>> {
-        let host_id = IdentifierKind::is_host_id(host_id)?;
-        let subject = broker::v1::commands::start_provider(
-            &self.topic_prefix,
-            &self.lattice,
-            host_id.as_str(),
-        );
-        debug!("start_provider:request {}", &subject);
-        let mut cmd = StartProviderCommand::builder()
-            .host_id(&host_id)
-            .provider_ref(&IdentifierKind::is_provider_ref(provider_ref)?)
-            .provider_id(&IdentifierKind::is_component_id(provider_id)?);
-        if let Some(annotations) = annotations {
-            cmd = cmd.annotations(annotations);
-        }
-        let cmd = cmd.config(provider_configuration).build()?;
-        let bytes = json_serialize(cmd)?;
-
-        match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive start provider acknowledgement: {e}").into()),
+        host_id: impl IntoId<HostId>,
+        config_name: &str,
+    ) -> Result<CtlResponse<HashMap<String, String>>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "get_host_config")?;
+        let subject = self
+            .host_subjects(&host_id)
+            .host_config(&host_id, config_name);
+        debug!(%subject, %config_name, "Getting host config");
+        match self
+            .request_timeout(subject, Vec::default(), self.timeout)
+            .await
+        {
+            Ok(msg) => decode(&msg),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to get host config request",
+            )),
         }
     }
 
-    /// Issues a command to a host to stop a provider for the given OCI reference, link name, and
-    /// contract ID.
-    ///
-    /// The target wasmCloud host will acknowledge the receipt of this command, and
-    /// _will not_ supply a discrete confirmation that a provider has terminated. For that kind of
-    /// information, the client must also monitor the control event stream
+    /// Delete the values of a named config scoped to the given host, after which the host uses
+    /// the lattice-wide config again.
     ///
     /// # Arguments
     ///
-    /// * `host_id` - ID of the host on which to stop the provider
-    /// * `provider_id` - ID of the provider to stop
+    /// * `host_id` - ID of the host the config applies to
+    /// * `config_name` - Name of the config
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is a communication problem with the host
     ///
     #[instrument(level = "debug", skip_all)]
-    pub async fn stop_provider(&self, host_id: &str, provider_id: &str) -> Result<CtlResponse<()>> {
-        let host_id = IdentifierKind::is_host_id(host_id)?;
-
-        let subject = broker::v1::commands::stop_provider(
-            &self.topic_prefix,
-            &self.lattice,
-            host_id.as_str(),
-        );
-        debug!("stop_provider:request {}", &subject);
-        let bytes = json_serialize(StopProviderCommand {
-            host_id,
-            provider_id: IdentifierKind::is_component_id(provider_id)?,
-        })?;
-
-        match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive stop provider acknowledgement: {e}").into()),
+    pub async fn delete_host_config(
+        &self,
+        host_id: impl IntoId<HostId>,
+        config_name: &str,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "delete_host_config")?;
+        let subject = self
+            .host_subjects(&host_id)
+            .delete_host_config(&host_id, config_name);
+        debug!(%subject, %config_name, "Deleting host config");
+        match self
+            .request_timeout(subject, Vec::default(), self.timeout)
+            .await
+        {
+            Ok(msg) => decode(&msg),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive a response to delete host config request",
+            )),
         }
     }
 
-    /// Issues a command to a specific host to perform a graceful termination.
+    /// Watch the named config item for changes.
     ///
-    /// The target host will acknowledge receipt of the command before it attempts a shutdown.
-    ///
-    /// To deterministically verify that the host is down, a client should monitor for the "host stopped" event or
-    /// passively detect the host down by way of a lack of heartbeat receipts
+    /// The returned receiver first yields the current revision of the config (if it exists) and
+    /// then every subsequent revision as it is written to the lattice config bucket. Deletions are
+    /// yielded as a [`ConfigRevision`] without data. This allows controllers to react to config
+    /// changes without polling [`Client::get_config`].
     ///
     /// # Arguments
     ///
-    /// * `host_id` - ID of the host to stop
-    /// * `timeout_ms` - (optional) amount of time to allow the host to complete stopping
+    /// * `config_name` - The name of the config to watch. Config names must be valid NATS subject strings and not contain any `.` or `>` characters.
+    ///
+    /// # Errors
     ///
+    /// Returns an error if the lattice config bucket cannot be accessed or watched
     #[instrument(level = "debug", skip_all)]
-    pub async fn stop_host(
-        &self,
-        host_id: &str,
-        timeout_ms: Option<u64>,
-    ) -> Result<CtlResponse<()>> {
-        let host_id = IdentifierKind::is_host_id(host_id)?;
-        let subject =
-            broker::v1::commands::stop_host(&self.topic_prefix, &self.lattice, host_id.as_str());
-        debug!("stop_host:request {}", &subject);
-        let bytes = json_serialize(StopHostCommand {
-            host_id,
-            timeout: timeout_ms,
-        })?;
-
-        match self.request_timeout(subject, bytes, self.timeout).await {
-            Ok(msg) => Ok(json_deserialize(&msg.payload)?),
-            Err(e) => Err(format!("Did not receive stop host acknowledgement: {e}").into()),
-        }
-    }
+    pub async fn watch_config(&self, config_name: &str) -> Result<Receiver<ConfigRevision>> {
+        self.ensure_open()?;
+        let config_name = IdentifierKind::is_config_name(config_name)?;
+        let bucket = broker::config_bucket(&self.lattice);
+        debug!(%bucket, %config_name, "Watching config");
+        let store = self
+            .jetstream()?
+            .get_key_value(&bucket)
+            .await
+            .map_err(|e| format!("Failed to access config bucket {bucket}: {e}"))?;
+        let mut watcher = store
+            .watch_with_history(&config_name)
+            .await
+            .map_err(|e| format!("Failed to watch config {config_name}: {e}"))?;
 
-    /// Publish a message and wait for a response
-    async fn publish_and_wait<D: DeserializeOwned>(
-        &self,
-        subject: String,
-        payload: Vec<u8>,
-    ) -> Result<Vec<D>> {
-        let reply = self.nc.new_inbox();
-        let sub = self.nc.subscribe(reply.clone()).await?;
-        self.nc
-            .publish_with_reply_and_headers(
-                subject.clone(),
-                reply,
-                otel::HeaderInjector::default_with_span().into(),
-                payload.into(),
-            )
-            .await?;
-        let nc = self.nc.clone();
-        tokio::spawn(async move {
-            if let Err(error) = nc.flush().await {
-                error!(%error, "flush after publish");
+        let (sender, receiver) = tokio::sync::mpsc::channel(100);
+        self.spawn(async move {
+            while let Some(entry) = watcher.next().await {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        error!(%config_name, %error, "error while watching config");
+                        continue;
+                    }
+                };
+                let config = match entry.operation {
+                    Operation::Put => match json_deserialize(&entry.value) {
+                        Ok(config) => Some(config),
+                        Err(error) => {
+                            error!(%config_name, %error, "config data was not a map of string -> string");
+                            continue;
+                        }
+                    },
+                    Operation::Delete | Operation::Purge => None,
+                };
+                trace!(%config_name, revision = entry.revision, "received config revision");
+                let revision = ConfigRevision::new(entry.key, entry.revision, config);
+                let Ok(()) = sender.send(revision).await else {
+                    break;
+                };
             }
         });
-        Ok(collect_sub_timeout::<D>(sub, self.auction_timeout, subject.as_str()).await)
+        Ok(receiver)
     }
 
-    /// Returns the receiver end of a channel that subscribes to the lattice event stream.
+    /// Resolve a [`ConfigRef`] to the content of the referenced config, returning `None` if the
+    /// config does not exist.
     ///
-    /// Any [`Event`]s that are published after this channel is created
-    /// will be added to the receiver channel's buffer, which can be observed or handled if needed.
+    /// References pinned to a revision resolve to the content of the config at that revision,
+    /// references pinned to a digest only resolve if the current content of the config matches
+    /// the digest. Resolving pinned references reads the lattice config bucket and requires a
+    /// NATS connection.
     ///
-    /// See the example for how you could use this receiver to handle events.
+    /// # Errors
     ///
-    /// # Example
+    /// Returns an error if the config cannot be read, or if its content does not match the digest
+    /// the reference is pinned to
+    #[instrument(level = "debug", skip_all, fields(config_ref = %config_ref))]
+    pub async fn resolve_config_ref(
+        &self,
+        config_ref: &ConfigRef,
+    ) -> Result<Option<HashMap<String, String>>> {
+        let name = config_ref.name();
+        let entry = match config_ref.version() {
+            None => {
+                let resp = self.get_config(name).await?;
+                if !resp.succeeded() {
+                    return Err(format!("Failed to get config `{name}`: {}", resp.message()).into());
+                }
+                return Ok(resp.into_data());
+            }
+            Some(ConfigVersion::Revision(revision)) => self
+                .config_store()
+                .await?
+                .entry_for_revision(name, *revision)
+                .await
+                .map_err(|e| {
+                    format!("Failed to get revision {revision} of config `{name}`: {e}")
+                })?,
+            Some(ConfigVersion::Digest(_)) => self
+                .config_store()
+                .await?
+                .entry(name)
+                .await
+                .map_err(|e| format!("Failed to get config `{name}`: {e}"))?,
+        };
+        let Some(entry) = entry.filter(|entry| entry.operation == Operation::Put) else {
+            return Ok(None);
+        };
+        let config: HashMap<String, String> = json_deserialize(&entry.value)?;
+        if let Some(ConfigVersion::Digest(digest)) = config_ref.version() {
+            let actual = ConfigRef::digest_of(&config);
+            if actual != *digest {
+                return Err(format!(
+                    "Config `{name}` has digest `{actual}`, which does not match the pinned digest `{digest}`"
+                )
+                .into());
+            }
+        }
+        Ok(Some(config))
+    }
+
+    /// Validate config refs passed to a command, returning the names of the configs and the
+    /// refs pinned to a version.
     ///
-    /// ```rust
-    /// use wasmcloud_control_interface::{Client, ClientBuilder};
-    /// async {
-    ///   let nc = async_nats::connect("127.0.0.1:4222").await.unwrap();
-    ///   let client = ClientBuilder::new(nc)
-    ///                 .timeout(std::time::Duration::from_millis(1000))
-    ///                 .auction_timeout(std::time::Duration::from_millis(1000))
-    ///                 .build();
-    ///   let mut receiver = client.events_receiver(vec!["component_scaled".to_string()]).await.unwrap();
-    ///   while let Some(evt) = receiver.recv().await {
-    ///       println!("Event received: {:?}", evt);
-    ///   }
-    /// };
-    /// ```
+    /// Hosts always use the current version of a config, so references pinned to a version are
+    /// only accepted if the current version of the config matches the pin. This is checked here to
+    /// fail early, the pins are sent along with the command for hosts to verify them against the
+    /// config they actually read.
+    async fn check_config_refs(&self, refs: ConfigRefs) -> Result<(Vec<String>, Vec<ConfigRef>)> {
+        let refs = refs.parse()?;
+        if refs.iter().any(|r| r.version().is_some()) {
+            let store = self.config_store().await?;
+            for config_ref in &refs {
+                let name = config_ref.name();
+                match config_ref.version() {
+                    None => {}
+                    Some(ConfigVersion::Revision(revision)) => {
+                        let entry = store
+                            .entry(name)
+                            .await
+                            .map_err(|e| format!("Failed to get config `{name}`: {e}"))?
+                            .filter(|entry| entry.operation == Operation::Put)
+                            .ok_or_else(|| format!("Config `{name}` does not exist"))?;
+                        if entry.revision != *revision {
+                            return Err(format!(
+                                "Config `{name}` is at revision {}, but revision {revision} was requested",
+                                entry.revision
+                            )
+                            .into());
+                        }
+                    }
+                    Some(ConfigVersion::Digest(_)) => {
+                        // Resolving checks that the current content matches the digest
+                        self.resolve_config_ref(config_ref)
+                            .await?
+                            .ok_or_else(|| format!("Config `{name}` does not exist"))?;
+                    }
+                }
+            }
+        }
+        let names = refs.iter().map(|r| r.name().to_string()).collect();
+        let pinned = refs.into_iter().filter(|r| r.version().is_some()).collect();
+        Ok((names, pinned))
+    }
+
+    async fn config_store(&self) -> Result<async_nats::jetstream::kv::Store> {
+        let bucket = broker::config_bucket(&self.lattice);
+        self.jetstream()?
+            .get_key_value(&bucket)
+            .await
+            .map_err(|e| format!("Failed to access config bucket {bucket}: {e}").into())
+    }
+
+    /// Watch every config of the lattice for changes.
     ///
-    /// # Arguments
+    /// The returned receiver first yields a [`ConfigChange::Put`] for every existing config and
+    /// then every subsequent change as it is written to the lattice config bucket, giving
+    /// controllers a consistent change feed without polling hosts.
     ///
-    /// * `event_types` - List of types of events to listen for
+    /// # Errors
     ///
-    #[allow(clippy::missing_errors_doc)] // TODO: Document errors
-    pub async fn events_receiver(&self, event_types: Vec<String>) -> Result<Receiver<Event>> {
-        let (sender, receiver) = tokio::sync::mpsc::channel(5000);
-        let futs = event_types.into_iter().map(|event_type| {
-            self.nc
-                .subscribe(format!("wasmbus.evt.{}.{}", self.lattice, event_type))
-                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
-        });
-        let subs: Vec<Subscriber> = futures::future::join_all(futs)
+    /// Returns an error if the lattice config bucket cannot be accessed or watched
+    #[instrument(level = "debug", skip_all)]
+    pub async fn config_watcher(&self) -> Result<Receiver<ConfigChange>> {
+        self.ensure_open()?;
+        let bucket = broker::config_bucket(&self.lattice);
+        debug!(%bucket, "Watching configs");
+        let store = self
+            .jetstream()?
+            .get_key_value(&bucket)
             .await
-            .into_iter()
-            .collect::<Result<_>>()?;
-        let mut stream = futures::stream::select_all(subs);
-        tokio::spawn(async move {
-            while let Some(msg) = stream.next().await {
-                let Ok(evt) = json_deserialize::<Event>(&msg.payload) else {
-                    error!("Object received on event stream was not a CloudEvent");
-                    continue;
+            .map_err(|e| format!("Failed to access config bucket {bucket}: {e}"))?;
+        let mut watcher = store
+            .watch_with_history(">")
+            .await
+            .map_err(|e| format!("Failed to watch config bucket {bucket}: {e}"))?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(100);
+        self.spawn(async move {
+            while let Some(entry) = watcher.next().await {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        error!(%bucket, %error, "error while watching configs");
+                        continue;
+                    }
                 };
-                trace!("received event: {:?}", evt);
-                let Ok(()) = sender.send(evt).await else {
+                let change = match entry.operation {
+                    Operation::Put => match json_deserialize(&entry.value) {
+                        Ok(config) => ConfigChange::Put {
+                            name: entry.key,
+                            revision: entry.revision,
+                            config,
+                        },
+                        Err(error) => {
+                            error!(config_name = entry.key, %error, "config data was not a map of string -> string");
+                            continue;
+                        }
+                    },
+                    Operation::Delete | Operation::Purge => ConfigChange::Delete {
+                        name: entry.key,
+                        revision: entry.revision,
+                    },
+                };
+                trace!(config_name = change.name(), revision = change.revision(), "received config change");
+                let Ok(()) = sender.send(change).await else {
                     break;
                 };
             }
         });
         Ok(receiver)
     }
-}
 
-/// Collect `T` values until timeout has elapsed
-pub(crate) async fn collect_sub_timeout<T: DeserializeOwned>(
-    mut sub: async_nats::Subscriber,
+    /// Watch every link of the lattice for changes.
+    ///
+    /// Links are stored in the lattice data bucket as part of the specification of their source
+    /// component. The returned receiver first yields a [`LinkChange::Put`] for every existing link
+    /// and then every link that is put or deleted as component specifications are written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket cannot be accessed or watched
+    #[instrument(level = "debug", skip_all)]
+    pub async fn link_watcher(&self) -> Result<Receiver<LinkChange>> {
+        /// The part of a component specification holding the links of the component
+        #[derive(Deserialize)]
+        struct ComponentLinks {
+            #[serde(default)]
+            links: Vec<Link>,
+        }
+
+        self.ensure_open()?;
+        let bucket = broker::data_bucket(&self.lattice);
+        debug!(%bucket, "Watching links");
+        let store = self
+            .jetstream()?
+            .get_key_value(&bucket)
+            .await
+            .map_err(|e| format!("Failed to access lattice data bucket {bucket}: {e}"))?;
+        let mut watcher = store
+            .watch_with_history(format!("{COMPONENT_SPEC_KEY_PREFIX}*"))
+            .await
+            .map_err(|e| format!("Failed to watch lattice data bucket {bucket}: {e}"))?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(100);
+        self.spawn(async move {
+            let mut links: HashMap<String, Vec<Link>> = HashMap::new();
+            while let Some(entry) = watcher.next().await {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => {
+                        error!(%bucket, %error, "error while watching links");
+                        continue;
+                    }
+                };
+                let new = match entry.operation {
+                    Operation::Put => match json_deserialize::<ComponentLinks>(&entry.value) {
+                        Ok(spec) => spec.links,
+                        Err(error) => {
+                            error!(key = entry.key, %error, "failed to deserialize component specification");
+                            continue;
+                        }
+                    },
+                    Operation::Delete | Operation::Purge => Vec::new(),
+                };
+                let old = links.remove(&entry.key).unwrap_or_default();
+                let changes = diff_links(&old, &new, entry.revision);
+                if !new.is_empty() {
+                    links.insert(entry.key, new);
+                }
+                for change in changes {
+                    trace!(revision = change.revision(), source_id = change.link().source_id(), "received link change");
+                    let Ok(()) = sender.send(change).await else {
+                        return;
+                    };
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    /// Watch the labels of every host in the lattice for changes.
+    ///
+    /// The returned receiver yields a [`LabelChange`] for every label that is added to, changed on
+    /// or removed from a host, derived from the `labels_changed` events published by hosts. Labels
+    /// present when this function is called are not yielded. Labels of hosts that were not known
+    /// when they first changed are yielded as added.
+    ///
+    /// Whenever lattice events may have been missed, the labels of all hosts are queried again and
+    /// the differences are yielded, so that the changes stay consistent with the lattice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if subscribing to events or querying the labels of hosts fails
+    #[instrument(level = "debug", skip_all)]
+    pub async fn label_events(&self) -> Result<Receiver<LabelChange>> {
+        self.ensure_open()?;
+        // Subscribe before querying the current labels so that no change is missed in between
+        let mut events = self
+            .events_receiver(vec!["labels_changed".into(), "host_stopped".into()])
+            .await?;
+        let mut labels = self.host_labels().await?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(100);
+        let client = self.clone();
+        self.spawn(async move {
+            while let Some(evt) = events.recv().await {
+                let changes = if EventStreamGap::from_event(&evt).is_some() {
+                    let current = match client.host_labels().await {
+                        Ok(current) => current,
+                        Err(error) => {
+                            warn!(%error, "failed to query host labels after event stream gap");
+                            continue;
+                        }
+                    };
+                    let changes = current
+                        .iter()
+                        .flat_map(|(host_id, new)| {
+                            let old = labels.get(host_id).cloned().unwrap_or_default();
+                            diff_labels(host_id, &old, new)
+                        })
+                        .collect();
+                    labels = current;
+                    changes
+                } else {
+                    let Ok(evt) = LatticeEvent::try_from(evt) else {
+                        continue;
+                    };
+                    if evt.event_type() == "host_stopped" {
+                        labels.remove(evt.source());
+                        continue;
+                    }
+                    let (Some(host_id), Some(new)) = (
+                        evt.field("host_id").and_then(|v| v.as_str()),
+                        evt.field("labels")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+                    ) else {
+                        debug!("labels_changed event without host ID or labels");
+                        continue;
+                    };
+                    let old = labels.insert(host_id.to_string(), new).unwrap_or_default();
+                    diff_labels(host_id, &old, &labels[host_id])
+                };
+                for change in changes {
+                    trace!(
+                        host_id = change.host_id(),
+                        key = change.key(),
+                        "received label change"
+                    );
+                    let Ok(()) = sender.send(change).await else {
+                        return;
+                    };
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    /// Query the labels of all responsive hosts, by host ID
+    async fn host_labels(&self) -> Result<HashMap<String, BTreeMap<String, String>>> {
+        Ok(self
+            .get_hosts()
+            .await?
+            .into_iter()
+            .filter_map(CtlResponse::into_data)
+            .map(|host| (host.id, host.labels))
+            .collect())
+    }
+
+    /// Capture the hosts, workloads, links and configs of the lattice in a [`LatticeSnapshot`].
+    ///
+    /// Snapshots can be stored and compared with [`LatticeSnapshot::diff`] to find out what
+    /// changed in the lattice between two points in time:
+    ///
+    /// ```rust,no_run
+    /// use wasmcloud_control_interface::{Client, LatticeSnapshot};
+    ///
+    /// async fn changes_since(client: &Client, before: &LatticeSnapshot) -> anyhow::Result<usize> {
+    ///     let now = client.snapshot().await.map_err(anyhow::Error::msg)?;
+    ///     Ok(before.diff(&now).len())
+    /// }
+    /// ```
+    ///
+    /// The snapshot is assembled from several queries and is therefore not atomic. Hosts that stop
+    /// while the snapshot is taken are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hosts, links or configs of the lattice could not be queried
+    #[instrument(level = "debug", skip_all)]
+    pub async fn snapshot(&self) -> Result<LatticeSnapshot> {
+        let taken_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis().try_into().unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        let host_ids: Vec<String> = self
+            .get_hosts()
+            .await?
+            .into_iter()
+            .filter_map(CtlResponse::into_data)
+            .map(|host| host.id().to_string())
+            .collect();
+        let inventories = futures::future::join_all(
+            host_ids
+                .iter()
+                .map(|host_id| self.get_host_inventory(host_id.as_str())),
+        )
+        .await;
+        let mut hosts = BTreeMap::new();
+        for (host_id, inventory) in host_ids.into_iter().zip(inventories) {
+            match inventory.map(CtlResponse::into_data) {
+                Ok(Some(inventory)) => {
+                    hosts.insert(host_id, inventory.into());
+                }
+                Ok(None) => debug!(%host_id, "host returned no inventory, skipping"),
+                Err(e) if e.is::<HostNotFound>() => {
+                    debug!(%host_id, "host stopped while taking snapshot, skipping");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let links = self.get_links().await?;
+        if !links.succeeded() {
+            return Err(format!("Failed to get links: {}", links.message()).into());
+        }
+        let links = links.into_data().unwrap_or_default();
+
+        let store = self.config_store().await?;
+        let mut names = store
+            .keys()
+            .await
+            .map_err(|e| format!("Failed to list configs: {e}"))?;
+        let mut configs = BTreeMap::new();
+        while let Some(name) = names.next().await {
+            let name = name.map_err(|e| format!("Failed to list configs: {e}"))?;
+            let Some(value) = store
+                .get(&name)
+                .await
+                .map_err(|e| format!("Failed to get config `{name}`: {e}"))?
+            else {
+                continue;
+            };
+            match json_deserialize(&value) {
+                Ok(config) => {
+                    configs.insert(name, config);
+                }
+                Err(error) => {
+                    error!(config_name = name, %error, "config data was not a map of string -> string");
+                }
+            }
+        }
+
+        Ok(LatticeSnapshot::new(
+            self.lattice.as_str(),
+            taken_at,
+            hosts,
+            links,
+            configs,
+        ))
+    }
+
+    /// Register a friendly alias for a host or component, replacing any existing alias with the
+    /// same name.
+    ///
+    /// Aliases are stored in the lattice data bucket and are shared by every client of the lattice.
+    /// They are case-insensitive and may only contain ASCII letters, digits, `-` and `_`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The alias, e.g. `edge-van-3`
+    /// * `target` - The host or component the alias refers to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alias is invalid or could not be written to the lattice data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub async fn put_alias(&self, name: &str, target: AliasTarget) -> Result<LatticeAlias> {
+        let alias = LatticeAlias::new(name, target)?;
+        let store = self.data_store().await?;
+        let key = format!("{ALIAS_KEY_PREFIX}{}", alias.name());
+        debug!(%key, target = %alias.target(), "putting alias");
+        store
+            .put(&key, json_serialize(&alias)?.into())
+            .await
+            .map_err(|e| format!("Failed to put alias {}: {e}", alias.name()))?;
+        Ok(alias)
+    }
+
+    /// Remove a previously registered alias. Deleting an alias that does not exist is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the alias is invalid or could not be deleted from the lattice data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub async fn delete_alias(&self, name: &str) -> Result<()> {
+        let name = validate_alias_name(name)?;
+        let store = self.data_store().await?;
+        let key = format!("{ALIAS_KEY_PREFIX}{name}");
+        debug!(%key, "deleting alias");
+        store
+            .delete(&key)
+            .await
+            .map_err(|e| format!("Failed to delete alias {name}: {e}").into())
+    }
+
+    /// Resolve an alias to the host or component it refers to, returning `None` if no such alias
+    /// is registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket could not be read
+    #[instrument(level = "debug", skip_all)]
+    pub async fn resolve_alias(&self, name: &str) -> Result<Option<AliasTarget>> {
+        // Values that can't be aliases (e.g. IDs containing `.`) are simply not registered
+        let Ok(name) = validate_alias_name(name) else {
+            return Ok(None);
+        };
+        let store = self.data_store().await?;
+        let key = format!("{ALIAS_KEY_PREFIX}{name}");
+        let Some(value) = store
+            .get(&key)
+            .await
+            .map_err(|e| format!("Failed to get alias {name}: {e}"))?
+        else {
+            return Ok(None);
+        };
+        let alias: LatticeAlias = json_deserialize(&value)?;
+        Ok(Some(alias.target))
+    }
+
+    /// List all aliases registered on the lattice, sorted by name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket could not be read
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_aliases(&self) -> Result<Vec<LatticeAlias>> {
+        let store = self.data_store().await?;
+        let mut keys = store
+            .keys()
+            .await
+            .map_err(|e| format!("Failed to list aliases: {e}"))?;
+        let mut aliases = Vec::new();
+        while let Some(key) = keys.next().await {
+            let key = key.map_err(|e| format!("Failed to list aliases: {e}"))?;
+            if !key.starts_with(ALIAS_KEY_PREFIX) {
+                continue;
+            }
+            let Some(value) = store
+                .get(&key)
+                .await
+                .map_err(|e| format!("Failed to get alias {key}: {e}"))?
+            else {
+                continue;
+            };
+            match json_deserialize::<LatticeAlias>(&value) {
+                Ok(alias) => aliases.push(alias),
+                Err(error) => error!(%key, %error, "skipping invalid alias"),
+            }
+        }
+        aliases.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(aliases)
+    }
+
+    /// Split the invocations of a link target between several components by weight, replacing
+    /// any previous split of the target. Every host in the lattice applies the split to
+    /// invocations made by the components it runs.
+    ///
+    /// ```rust,no_run
+    /// use wasmcloud_control_interface::{Client, TrafficSplit};
+    ///
+    /// async fn canary(client: &Client) -> anyhow::Result<()> {
+    ///     let split = TrafficSplit::parse("api", "api=90,api-v2=10").map_err(anyhow::Error::msg)?;
+    ///     client.put_traffic_split(&split).await.map_err(anyhow::Error::msg)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the split could not be written to the lattice data bucket
+    #[instrument(level = "debug", skip_all, fields(target = split.target()))]
+    pub async fn put_traffic_split(&self, split: &TrafficSplit) -> Result<()> {
+        let store = self.data_store().await?;
+        let key = format!("{TRAFFIC_SPLIT_KEY_PREFIX}{}", split.target());
+        debug!(%key, weights = ?split.weights(), "putting traffic split");
+        store
+            .put(&key, json_serialize(split)?.into())
+            .await
+            .map_err(|e| format!("Failed to put traffic split of {}: {e}", split.target()))?;
+        Ok(())
+    }
+
+    /// Remove the traffic split of a link target, sending all of its invocations to the target
+    /// again. Deleting a split that does not exist is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target is invalid or the split could not be deleted from the
+    /// lattice data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub async fn delete_traffic_split(&self, target: impl IntoId<ComponentId>) -> Result<()> {
+        let target = target.into_id()?;
+        let store = self.data_store().await?;
+        let key = format!("{TRAFFIC_SPLIT_KEY_PREFIX}{target}");
+        debug!(%key, "deleting traffic split");
+        store
+            .delete(&key)
+            .await
+            .map_err(|e| format!("Failed to delete traffic split of {target}: {e}").into())
+    }
+
+    /// Get the traffic split of a link target, returning `None` if its invocations are not split
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target is invalid or the lattice data bucket could not be read
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_traffic_split(
+        &self,
+        target: impl IntoId<ComponentId>,
+    ) -> Result<Option<TrafficSplit>> {
+        let target = target.into_id()?;
+        let store = self.data_store().await?;
+        let key = format!("{TRAFFIC_SPLIT_KEY_PREFIX}{target}");
+        store
+            .get(&key)
+            .await
+            .map_err(|e| format!("Failed to get traffic split of {target}: {e}"))?
+            .map(|value| json_deserialize(&value))
+            .transpose()
+    }
+
+    /// Add a route to the ingress routing table of the lattice, replacing any route with the same
+    /// name. HTTP server providers in `ingress` routing mode on every host apply the route.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the route could not be written to the lattice data bucket
+    #[instrument(level = "debug", skip_all, fields(name = route.name()))]
+    pub async fn put_ingress_route(&self, route: &IngressRoute) -> Result<()> {
+        let store = self.data_store().await?;
+        let key = format!("{INGRESS_ROUTE_KEY_PREFIX}{}", route.name());
+        debug!(%key, hostname = ?route.hostname(), path_prefix = route.path_prefix(), component_id = route.component_id(), "putting ingress route");
+        store
+            .put(&key, json_serialize(route)?.into())
+            .await
+            .map_err(|e| format!("Failed to put ingress route {}: {e}", route.name()))?;
+        Ok(())
+    }
+
+    /// Remove a route from the ingress routing table of the lattice. Deleting a route that does
+    /// not exist is not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is invalid or the route could not be deleted from the lattice
+    /// data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub async fn delete_ingress_route(&self, name: &str) -> Result<()> {
+        let name = validate_route_name(name)?;
+        let store = self.data_store().await?;
+        let key = format!("{INGRESS_ROUTE_KEY_PREFIX}{name}");
+        debug!(%key, "deleting ingress route");
+        store
+            .delete(&key)
+            .await
+            .map_err(|e| format!("Failed to delete ingress route {name}: {e}").into())
+    }
+
+    /// List the ingress routing table of the lattice, sorted by name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket could not be read
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_ingress_routes(&self) -> Result<Vec<IngressRoute>> {
+        let store = self.data_store().await?;
+        let mut keys = store
+            .keys()
+            .await
+            .map_err(|e| format!("Failed to list ingress routes: {e}"))?;
+        let mut routes = Vec::new();
+        while let Some(key) = keys.next().await {
+            let key = key.map_err(|e| format!("Failed to list ingress routes: {e}"))?;
+            if !key.starts_with(INGRESS_ROUTE_KEY_PREFIX) {
+                continue;
+            }
+            let Some(value) = store
+                .get(&key)
+                .await
+                .map_err(|e| format!("Failed to get ingress route {key}: {e}"))?
+            else {
+                continue;
+            };
+            match json_deserialize::<IngressRoute>(&value) {
+                Ok(route) => routes.push(route),
+                Err(error) => error!(%key, %error, "skipping invalid ingress route"),
+            }
+        }
+        routes.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(routes)
+    }
+
+    /// Get the current epoch of the lattice, returning `None` if no controller has advanced it yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket could not be read
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_lattice_epoch(&self) -> Result<Option<LatticeEpoch>> {
+        let store = self.data_store().await?;
+        let Some(value) = store
+            .get(LATTICE_EPOCH_KEY)
+            .await
+            .map_err(|e| format!("Failed to get lattice epoch: {e}"))?
+        else {
+            return Ok(None);
+        };
+        json_deserialize(&value).map(Some)
+    }
+
+    /// Advance the epoch of the lattice, e.g. after the controller using this client was elected
+    /// leader, returning the new epoch.
+    ///
+    /// The new epoch is sent with every subsequent command of this client and its clones. Hosts
+    /// reject commands carrying an older epoch, so that commands of a previous leader that has not
+    /// noticed it lost its leadership are no longer honored. Concurrent advances never produce the
+    /// same epoch twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `holder` - Optional identifier of the controller taking over, recorded for diagnostics
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket could not be read or written, or if the epoch
+    /// kept being advanced concurrently by other controllers
+    #[instrument(level = "debug", skip_all)]
+    pub async fn advance_lattice_epoch(&self, holder: Option<&str>) -> Result<LatticeEpoch> {
+        const MAX_ATTEMPTS: usize = 5;
+
+        let store = self.data_store().await?;
+        for _ in 0..MAX_ATTEMPTS {
+            let current = store
+                .entry(LATTICE_EPOCH_KEY)
+                .await
+                .map_err(|e| format!("Failed to get lattice epoch: {e}"))?
+                .filter(|entry| entry.operation == Operation::Put);
+            let advanced_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis().try_into().unwrap_or(u64::MAX))
+                .unwrap_or_default();
+            let holder = holder.map(ToString::to_string);
+            // Writes are conditional on the revision that was read, so that two controllers
+            // advancing concurrently cannot both end up holding the same epoch
+            let (next, written) = if let Some(entry) = current {
+                let next =
+                    json_deserialize::<LatticeEpoch>(&entry.value)?.next(holder, advanced_at);
+                let written = store
+                    .update(
+                        LATTICE_EPOCH_KEY,
+                        json_serialize(&next)?.into(),
+                        entry.revision,
+                    )
+                    .await
+                    .is_ok();
+                (next, written)
+            } else {
+                let next = LatticeEpoch::default().next(holder, advanced_at);
+                let written = store
+                    .create(LATTICE_EPOCH_KEY, json_serialize(&next)?.into())
+                    .await
+                    .is_ok();
+                (next, written)
+            };
+            if written {
+                debug!(epoch = next.epoch(), "advanced lattice epoch");
+                self.epoch.fetch_max(next.epoch(), Ordering::Relaxed);
+                return Ok(next);
+            }
+            debug!("lattice epoch was advanced concurrently, retrying");
+        }
+        Err(format!("Failed to advance lattice epoch after {MAX_ATTEMPTS} attempts").into())
+    }
+
+    /// Access the lattice data bucket
+    async fn data_store(&self) -> Result<async_nats::jetstream::kv::Store> {
+        let bucket = broker::data_bucket(&self.lattice);
+        self.jetstream()?
+            .get_key_value(&bucket)
+            .await
+            .map_err(|e| format!("Failed to access lattice data bucket {bucket}: {e}").into())
+    }
+
+    /// Put a new (or update an existing) label on the given host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the label should be placed
+    /// * `key` - The key of the label
+    /// * `value` - The value of the label
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is a communication problem with the host
+    ///
+    pub async fn put_label(
+        &self,
+        host_id: impl IntoId<HostId>,
+        key: &str,
+        value: &str,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "put_label")?;
+        let subject = self.host_subjects(&host_id).put_label(&host_id);
+        debug!(%subject, "putting label");
+        let bytes = json_serialize(HostLabel {
+            key: key.to_string(),
+            value: value.to_string(),
+        })?;
+        match self
+            .host_request(&host_id, "put_label", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive put label acknowledgement",
+            )),
+        }
+    }
+
+    /// Removes a label from the given host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the label should be deleted
+    /// * `key` - The key of the label that should be deleted
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is a communication problem with the host
+    ///
+    pub async fn delete_label(
+        &self,
+        host_id: impl IntoId<HostId>,
+        key: &str,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "delete_label")?;
+        let subject = self.host_subjects(&host_id).delete_label(&host_id);
+        debug!(%subject, "removing label");
+        let bytes = json_serialize(HostLabelIdentifier {
+            key: key.to_string(),
+        })?;
+        match self
+            .host_request(&host_id, "delete_label", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive remove label acknowledgement",
+            )),
+        }
+    }
+
+    /// Put several labels on the given host at once. Either all labels are applied or, if any of
+    /// them is rejected by the host, none are.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the labels should be placed
+    /// * `labels` - The labels to put, keyed by label key
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is a communication problem with the host
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn put_labels(
+        &self,
+        host_id: impl IntoId<HostId>,
+        labels: BTreeMap<String, String>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "put_labels")?;
+        let subject = self.host_subjects(&host_id).put_labels(&host_id);
+        debug!(%subject, "putting labels");
+        let bytes = json_serialize(HostLabels::from_map(labels))?;
+        match self
+            .host_request(&host_id, "put_labels", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive put labels acknowledgement",
+            )),
+        }
+    }
+
+    /// Removes several labels from the given host at once. Keys that are not set on the host are
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the labels should be deleted
+    /// * `keys` - The keys of the labels that should be deleted
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if there is a communication problem with the host
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn delete_labels(
+        &self,
+        host_id: impl IntoId<HostId>,
+        keys: Vec<String>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "delete_labels")?;
+        let subject = self.host_subjects(&host_id).delete_labels(&host_id);
+        debug!(%subject, "removing labels");
+        let bytes = json_serialize(HostLabelIdentifiers::from_keys(keys))?;
+        match self
+            .host_request(&host_id, "delete_labels", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive remove labels acknowledgement",
+            )),
+        }
+    }
+
+    /// Command a host to replace an existing component with a new component indicated by an OCI image reference.
+    ///
+    /// The host will acknowledge this request as soon as it verifies that the target component is running.
+    ///
+    /// Note that acknowledgement occurs **before** the new bytes are downloaded. Live-updating an component can take a long time
+    /// and control clients cannot block waiting for a reply that could come several seconds later.
+    ///
+    /// To properly verify that a component has been updated, create  listener for the appropriate [`PublishedEvent`] on the
+    /// control events channel
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the component should be updated
+    /// * `existing_component_id` - ID of the existing component
+    /// * `new_component_ref` - New component reference that should be used
+    /// * `annotations` - Annotations to place on the newly updated component
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn update_component(
+        &self,
+        host_id: impl IntoId<HostId>,
+        existing_component_id: impl IntoId<ComponentId>,
+        new_component_ref: &str,
+        annotations: Option<Annotations>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "update_component")?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .update_component(host_id.as_str());
+        debug!("update_component:request {}", &subject);
+        let bytes = json_serialize(UpdateComponentCommand {
+            host_id: host_id.to_string(),
+            component_id: existing_component_id.into_id()?.into_string(),
+            new_component_ref: IdentifierKind::is_component_ref(new_component_ref)?,
+            annotations: self.with_default_annotations(annotations).map(Into::into),
+        })?;
+        match self
+            .host_request(&host_id, "update_component", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive update component acknowledgement",
+            )),
+        }
+    }
+
+    /// Command a host to scale every component whose annotations match `annotations` to zero,
+    /// e.g. all components deployed as part of an application. A component matches when it has
+    /// every given annotation with the same value.
+    ///
+    /// The response contains the IDs of the components that were stopped. If some components fail
+    /// to stop, the response is unsuccessful, its message lists the error of each of those
+    /// components and they keep running. At least one annotation is required, hosts reject
+    /// requests that would stop all of their components.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host running the components
+    /// * `annotations` - Annotations that selected components must have
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stop_components_matching(
+        &self,
+        host_id: impl IntoId<HostId>,
+        annotations: BTreeMap<String, String>,
+    ) -> Result<CtlResponse<Vec<String>>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions
+            .check(&host_id, "stop_components_matching")?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .stop_components(host_id.as_str());
+        debug!("stop_components_matching:request {}", &subject);
+        let bytes = json_serialize(StopComponentsCommand::new(&host_id, annotations))?;
+
+        match self
+            .host_request(
+                &host_id,
+                "stop_components_matching",
+                subject,
+                bytes,
+                self.timeout,
+            )
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive stop components acknowledgement",
+            )),
+        }
+    }
+
+    /// Command a host to start a provider with a given OCI reference.
+    ///
+    /// The specified link name will be used (or "default" if none is specified).
+    ///
+    /// The target wasmCloud host will acknowledge the receipt of this command _before_ downloading the provider's bytes from the
+    /// OCI registry, indicating either a validation failure or success.
+    ///
+    /// Clients that need deterministic guarantees that the provider has completed its startup process, should
+    /// monitor the control event stream for the appropriate event.
+    ///
+    /// The `provider_configuration` parameter is a list of named configs to use for this provider, and configurations are not required.
+    /// Configs may be passed as a `Vec<String>` of names or as [`ConfigRef`]s, which can pin a config
+    /// to a revision or digest. Pinned references are checked against the current version of the
+    /// config before the command is sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which to start the provider
+    /// * `provider_ref` - Image reference of the provider to start
+    /// * `provider_id` - ID of the provider to start
+    /// * `annotations` - Annotations to place on the started provider
+    /// * `provider_configuration` - Configuration relevant to the provider (if any)
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn start_provider(
+        &self,
+        host_id: impl IntoId<HostId>,
+        provider_ref: impl IntoId<ProviderRef>,
+        provider_id: &str,
+        annotations: Option<Annotations>,
+        provider_configuration: impl Into<ConfigRefs>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "start_provider")?;
+        let (provider_configuration, pinned_config) = self
+            .check_config_refs(provider_configuration.into())
+            .await?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .start_provider(host_id.as_str());
+        debug!("start_provider:request {}", &subject);
+        let mut cmd = StartProviderCommand::builder()
+            .host_id(&host_id)
+            .provider_ref(&provider_ref.into_id()?)
+            .provider_id(&IdentifierKind::is_component_id(provider_id)?);
+        if let Some(annotations) = self.with_default_annotations(annotations) {
+            cmd = cmd.annotations(annotations);
+        }
+        let cmd = cmd
+            .config(provider_configuration)
+            .pinned_config(pinned_config)
+            .build()?;
+        let bytes = json_serialize(cmd)?;
+
+        match self
+            .host_request(&host_id, "start_provider", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive start provider acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a host to stop a provider for the given OCI reference, link name, and
+    /// contract ID.
+    ///
+    /// The target wasmCloud host will acknowledge the receipt of this command, and
+    /// _will not_ supply a discrete confirmation that a provider has terminated. For that kind of
+    /// information, the client must also monitor the control event stream
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which to stop the provider
+    /// * `provider_id` - ID of the provider to stop
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stop_provider(
+        &self,
+        host_id: impl IntoId<HostId>,
+        provider_id: &str,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "stop_provider")?;
+
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .stop_provider(host_id.as_str());
+        debug!("stop_provider:request {}", &subject);
+        let bytes = json_serialize(StopProviderCommand {
+            host_id: host_id.to_string(),
+            provider_id: IdentifierKind::is_component_id(provider_id)?,
+        })?;
+
+        match self
+            .host_request(&host_id, "stop_provider", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive stop provider acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a host to change the named configs consumed by a running provider,
+    /// without stopping and restarting it.
+    ///
+    /// The host validates that the configs exist and delivers the newly merged configuration to
+    /// the provider as a config update, in the same way as changes to the values of its existing
+    /// configs, before acknowledging the command. Secret references in `config_names` only take
+    /// effect when the provider is restarted.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host on which the provider is running
+    /// * `provider_id` - ID of the provider to update
+    /// * `config_names` - New list of named configs for the provider, replacing the previous list
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn update_provider_config(
+        &self,
+        host_id: impl IntoId<HostId>,
+        provider_id: &str,
+        config_names: Vec<String>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions
+            .check(&host_id, "update_provider_config")?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .update_provider_config(host_id.as_str());
+        debug!("update_provider_config:request {}", &subject);
+        let bytes = json_serialize(UpdateProviderConfigCommand {
+            host_id: host_id.to_string(),
+            provider_id: IdentifierKind::is_component_id(provider_id)?,
+            config: config_names,
+        })?;
+
+        match self
+            .host_request(
+                &host_id,
+                "update_provider_config",
+                subject,
+                bytes,
+                self.timeout,
+            )
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive update provider config acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a specific host to perform a graceful termination.
+    ///
+    /// The target host will acknowledge receipt of the command before it attempts a shutdown.
+    ///
+    /// To deterministically verify that the host is down, a client should monitor for the "host stopped" event or
+    /// passively detect the host down by way of a lack of heartbeat receipts
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host to stop
+    /// * `timeout_ms` - (optional) amount of time to allow the host to complete stopping
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn stop_host(
+        &self,
+        host_id: impl IntoId<HostId>,
+        timeout_ms: Option<u64>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "stop_host")?;
+        self.request_stop_host(host_id, timeout_ms, false).await
+    }
+
+    /// Issues a command to a specific host to stop permanently, e.g. before its node is removed.
+    ///
+    /// In addition to stopping like [`Client::stop_host`], the host writes a [`HostDecommission`]
+    /// record to the lattice data bucket, publishes a `host_decommissioned` event and removes the
+    /// provider logs and flight recordings from its data directory, so that tooling can tell hosts
+    /// that are gone for good from hosts that are restarting. Records are listed with
+    /// [`Client::get_decommissioned_hosts`].
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host to decommission
+    /// * `timeout_ms` - Amount of time to allow the host to complete a graceful shutdown
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn decommission_host(
+        &self,
+        host_id: impl IntoId<HostId>,
+        timeout_ms: Option<u64>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "decommission_host")?;
+        self.request_stop_host(host_id, timeout_ms, true).await
+    }
+
+    /// Get the records of all hosts that were decommissioned with [`Client::decommission_host`],
+    /// ordered by the time they were decommissioned
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice data bucket cannot be accessed
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_decommissioned_hosts(&self) -> Result<Vec<HostDecommission>> {
+        let store = self.data_store().await?;
+        let mut keys = store
+            .keys()
+            .await
+            .map_err(|e| format!("Failed to list decommissioned hosts: {e}"))?;
+        let mut records = Vec::new();
+        while let Some(key) = keys.next().await {
+            let key = key.map_err(|e| format!("Failed to list decommissioned hosts: {e}"))?;
+            if !key.starts_with(HOST_DECOMMISSION_KEY_PREFIX) {
+                continue;
+            }
+            let Some(value) = store
+                .get(&key)
+                .await
+                .map_err(|e| format!("Failed to get decommission record {key}: {e}"))?
+            else {
+                continue;
+            };
+            match json_deserialize::<HostDecommission>(&value) {
+                Ok(record) => records.push(record),
+                Err(error) => error!(%key, %error, "skipping invalid decommission record"),
+            }
+        }
+        records.sort_by_key(HostDecommission::decommissioned_at);
+        Ok(records)
+    }
+
+    /// Send a stop host command, decommissioning the host if requested
+    async fn request_stop_host(
+        &self,
+        host_id: HostId,
+        timeout_ms: Option<u64>,
+        decommission: bool,
+    ) -> Result<CtlResponse<()>> {
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .stop_host(host_id.as_str());
+        debug!("stop_host:request {}", &subject);
+        let bytes = json_serialize(StopHostCommand {
+            host_id: host_id.to_string(),
+            timeout: timeout_ms,
+            decommission,
+        })?;
+
+        match self
+            .host_request(&host_id, "stop_host", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive stop host acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a specific host to drain it for maintenance.
+    ///
+    /// The target host immediately stops accepting new workloads, declining auctions and rejecting
+    /// new components and providers, and acknowledges the command. It then waits for its running
+    /// components and providers to be rescheduled elsewhere (e.g. by wadm) until the deadline in
+    /// `options` passes, after which remaining workloads are force-stopped if requested. The host
+    /// publishes a `host_draining` event when the drain starts and a `host_drained` event when it
+    /// completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host to drain
+    /// * `options` - Deadline and force-stop behavior of the drain
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn drain_host(
+        &self,
+        host_id: impl IntoId<HostId>,
+        options: DrainOptions,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "drain_host")?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .drain_host(host_id.as_str());
+        debug!("drain_host:request {}", &subject);
+        let bytes = json_serialize(DrainHostCommand::new(&host_id, options))?;
+
+        match self
+            .host_request(&host_id, "drain_host", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive drain host acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a specific host to prefetch images into its artifact cache and keep
+    /// them warm, so that the first components and providers started from them do not pay for a
+    /// cold registry pull.
+    ///
+    /// The host acknowledges the command once the images are queued and fetches them in the
+    /// background. The status of each prefetch is reported in the
+    /// [`HostInventory`](crate::HostInventory) of the host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host that should prefetch the images
+    /// * `image_refs` - OCI references of the images to prefetch
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn prefetch_images(
+        &self,
+        host_id: impl IntoId<HostId>,
+        image_refs: Vec<String>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "prefetch_images")?;
+        let subject = self
+            .host_subjects(host_id.as_str())
+            .prefetch_images(host_id.as_str());
+        debug!("prefetch_images:request {}", &subject);
+        let bytes = json_serialize(PrefetchImagesCommand::new(&host_id, image_refs))?;
+
+        match self
+            .host_request(&host_id, "prefetch_images", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive prefetch images acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a specific host to remove data from its data directory, e.g. to reclaim
+    /// disk space. Data of running workloads, such as the logs of running providers, may be kept.
+    ///
+    /// The host replies with the disk usage of its data directory after the cleanup, and fails if
+    /// it does not manage a data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host whose data should be removed
+    /// * `categories` - Categories of data to remove, all categories if empty
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn cleanup_host_data(
+        &self,
+        host_id: impl IntoId<HostId>,
+        categories: Vec<DataCategory>,
+    ) -> Result<CtlResponse<DataDirUsage>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions.check(&host_id, "cleanup_host_data")?;
+        let subject = self.host_subjects(&host_id).cleanup_host_data(&host_id);
+        debug!(%subject, ?categories, "cleanup_host_data:request");
+        let bytes = json_serialize(CleanupHostDataCommand::new(&host_id, categories))?;
+
+        match self
+            .host_request(&host_id, "cleanup_host_data", subject, bytes, self.timeout)
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive cleanup host data acknowledgement",
+            )),
+        }
+    }
+
+    /// Issues a command to a specific host to change the level and sampler of the traces it
+    /// emits, e.g. to temporarily trace at `debug` level while investigating an incident. Settings
+    /// that are not set in `command` are left unchanged.
+    ///
+    /// Changes are not persisted, so the host emits traces according to its configuration again
+    /// once restarted.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The [`UpdateHostTracingCommand`] with the ID of the host and the new settings
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn update_host_tracing(
+        &self,
+        command: &UpdateHostTracingCommand,
+    ) -> Result<CtlResponse<()>> {
+        let host_id: HostId = command.host_id().into_id()?;
+        self.host_versions.check(&host_id, "update_host_tracing")?;
+        let subject = self.host_subjects(&host_id).update_host_tracing(&host_id);
+        debug!(%subject, ?command, "update_host_tracing:request");
+        let bytes = json_serialize(command)?;
+
+        match self
+            .host_request(
+                &host_id,
+                "update_host_tracing",
+                subject,
+                bytes,
+                self.timeout,
+            )
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(
+                e,
+                "Did not receive update host tracing acknowledgement",
+            )),
+        }
+    }
+
+    /// Profiles a component running on a specific host for `duration` and returns the report.
+    ///
+    /// While profiling, the host records the latency of every invocation of the component along
+    /// with the CPU time and linear memory it uses. The request only completes once profiling is
+    /// done, so it waits for `duration` on top of the client timeout.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host running the component
+    /// * `component_id` - ID of the component to profile
+    /// * `duration` - How long to profile the component for
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn profile_component(
+        &self,
+        host_id: impl IntoId<HostId>,
+        component_id: impl IntoId<ComponentId>,
+        duration: Duration,
+    ) -> Result<CtlResponse<ComponentProfile>> {
+        let host_id = host_id.into_id()?;
+        let component_id = component_id.into_id()?;
+        self.host_versions.check(&host_id, "profile_component")?;
+        let subject = self.host_subjects(&host_id).profile_component(&host_id);
+        debug!(%subject, %component_id, ?duration, "profile_component:request");
+        let bytes = json_serialize(ProfileComponentCommand::new(
+            &host_id,
+            &component_id,
+            duration,
+        ))?;
+        match self
+            .host_request(
+                &host_id,
+                "profile_component",
+                subject,
+                bytes,
+                duration + self.timeout,
+            )
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(e, "Did not receive component profile")),
+        }
+    }
+
+    /// Retrieves the `limit` components holding the most linear memory on a host, largest first.
+    ///
+    /// Memory is accounted continuously by the host as instances grow and are dropped, so this
+    /// can be used to find the workload exhausting a host's memory without profiling it.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host to query
+    /// * `limit` - Maximum number of components to return
+    ///
+    #[instrument(level = "debug", skip_all)]
+    pub async fn get_top_memory_consumers(
+        &self,
+        host_id: impl IntoId<HostId>,
+        limit: u32,
+    ) -> Result<CtlResponse<Vec<ComponentMemoryUsage>>> {
+        let host_id = host_id.into_id()?;
+        self.host_versions
+            .check(&host_id, "get_top_memory_consumers")?;
+        let subject = self.host_subjects(&host_id).top_memory_consumers(&host_id);
+        debug!(%subject, limit, "get_top_memory_consumers:request");
+        let bytes = json_serialize(TopMemoryQuery::new(&host_id, limit))?;
+        match self
+            .host_request(
+                &host_id,
+                "get_top_memory_consumers",
+                subject,
+                bytes,
+                self.timeout,
+            )
+            .await
+        {
+            Ok(msg) => Ok(decode(&msg)?),
+            Err(e) => Err(request_error(e, "Did not receive component memory usage")),
+        }
+    }
+
+    /// Restarts a provider on a host by stopping it and starting it again from the same image
+    /// reference with the same annotations, waiting for the `provider_stopped` and
+    /// `provider_started` events in between.
+    ///
+    /// Named configuration is not reported in host inventories, so the provider is restarted
+    /// without any. Use [`Client::restart_provider_with_config`] for providers that were started
+    /// with configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host running the provider
+    /// * `provider_id` - ID of the provider to restart
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider is not running on the host, a command was not acknowledged
+    /// or the expected events were not received in time
+    #[instrument(level = "debug", skip_all)]
+    pub async fn restart_provider(
+        &self,
+        host_id: impl IntoId<HostId>,
+        provider_id: &str,
+    ) -> Result<CtlResponse<()>> {
+        self.restart_provider_with_config(host_id, provider_id, Vec::new())
+            .await
+    }
+
+    /// Restarts a provider on a host like [`Client::restart_provider`], starting it again with the
+    /// given named configuration.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn restart_provider_with_config(
+        &self,
+        host_id: impl IntoId<HostId>,
+        provider_id: &str,
+        provider_configuration: impl Into<ConfigRefs>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        let provider_id = IdentifierKind::is_provider_id(provider_id)?;
+        let provider_configuration = provider_configuration.into();
+        let inventory = self
+            .get_host_inventory(&host_id)
+            .await?
+            .into_data()
+            .ok_or_else(|| format!("Host {host_id} did not return its inventory"))?;
+        let provider = inventory
+            .providers()
+            .iter()
+            .find(|provider| provider.id() == provider_id)
+            .ok_or_else(|| format!("Provider {provider_id} is not running on host {host_id}"))?;
+        let provider_ref = provider
+            .image_ref()
+            .ok_or_else(|| {
+                format!("Provider {provider_id} has no image reference to restart from")
+            })?
+            .to_string();
+        let annotations = provider.annotations().map(Annotations::from);
+
+        // Subscribe before issuing commands so that no events are missed
+        let mut events = self
+            .events_receiver(vec![
+                "provider_stopped".to_string(),
+                "provider_started".to_string(),
+                "provider_start_failed".to_string(),
+            ])
+            .await?;
+        let matches_provider = |data: &serde_json::Value| {
+            data.get("host_id").and_then(|v| v.as_str()) == Some(host_id.as_str())
+                && data.get("provider_id").and_then(|v| v.as_str()) == Some(provider_id.as_str())
+        };
+
+        let ack = self.stop_provider(&host_id, &provider_id).await?;
+        if !ack.succeeded() {
+            return Ok(ack);
+        }
+        wait_for_event(&mut events, RESTART_EVENT_TIMEOUT, |evt| {
+            evt.event_type() == "provider_stopped" && matches_provider(evt.data())
+        })
+        .await
+        .map_err(|e| format!("Provider {provider_id} did not stop: {e}"))?;
+
+        let ack = self
+            .start_provider(
+                &host_id,
+                &provider_ref,
+                &provider_id,
+                annotations,
+                provider_configuration,
+            )
+            .await?;
+        if !ack.succeeded() {
+            return Ok(ack);
+        }
+        let LatticeEvent {
+            event_type: ty,
+            data,
+            ..
+        } = wait_for_event(&mut events, RESTART_EVENT_TIMEOUT, |evt| {
+            matches!(
+                evt.event_type(),
+                "provider_started" | "provider_start_failed"
+            ) && matches_provider(evt.data())
+        })
+        .await
+        .map_err(|e| format!("Provider {provider_id} did not start: {e}"))?;
+        if ty == "provider_start_failed" {
+            let error = data
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            return Ok(CtlResponse::error(&format!(
+                "Provider {provider_id} failed to start after stopping: {error}"
+            )));
+        }
+        Ok(CtlResponse::<()>::success(format!(
+            "Provider {provider_id} restarted on host {host_id}"
+        )))
+    }
+
+    /// Restarts a component on a host by scaling it to zero and back to its current maximum number
+    /// of instances, with the same image reference and annotations, waiting for the corresponding
+    /// `component_scaled` events in between.
+    ///
+    /// Named configuration is not reported in host inventories, so the component is restarted
+    /// without any. Use [`Client::restart_component_with_config`] for components that were started
+    /// with configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `host_id` - ID of the host running the component
+    /// * `component_id` - ID of the component to restart
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the component is not running on the host, a command was not
+    /// acknowledged or the expected events were not received in time
+    #[instrument(level = "debug", skip_all)]
+    pub async fn restart_component(
+        &self,
+        host_id: impl IntoId<HostId>,
+        component_id: impl IntoId<ComponentId>,
+    ) -> Result<CtlResponse<()>> {
+        self.restart_component_with_config(host_id, component_id, Vec::new())
+            .await
+    }
+
+    /// Restarts a component on a host like [`Client::restart_component`], scaling it back up with
+    /// the given named configuration.
+    #[instrument(level = "debug", skip_all)]
+    pub async fn restart_component_with_config(
+        &self,
+        host_id: impl IntoId<HostId>,
+        component_id: impl IntoId<ComponentId>,
+        config: Vec<String>,
+    ) -> Result<CtlResponse<()>> {
+        let host_id = host_id.into_id()?;
+        let component_id = component_id.into_id()?;
+        let inventory = self
+            .get_host_inventory(&host_id)
+            .await?
+            .into_data()
+            .ok_or_else(|| format!("Host {host_id} did not return its inventory"))?;
+        let component = inventory
+            .components()
+            .iter()
+            .find(|component| component.id() == component_id.as_str())
+            .ok_or_else(|| format!("Component {component_id} is not running on host {host_id}"))?;
+        let component_ref = component.image_ref().to_string();
+        let max_instances = component.max_instances();
+        let annotations = component.annotations().map(Annotations::from);
+
+        // Subscribe before issuing commands so that no events are missed
+        let mut events = self
+            .events_receiver(vec![
+                "component_scaled".to_string(),
+                "component_scale_failed".to_string(),
+            ])
+            .await?;
+        let matches_component = |data: &serde_json::Value, max: u32| {
+            data.get("host_id").and_then(|v| v.as_str()) == Some(host_id.as_str())
+                && data.get("component_id").and_then(|v| v.as_str()) == Some(component_id.as_str())
+                && data
+                    .get("max_instances")
+                    .and_then(serde_json::Value::as_u64)
+                    == Some(u64::from(max))
+        };
+
+        let ack = self
+            .scale_component(
+                &host_id,
+                &component_ref,
+                &component_id,
+                0,
+                annotations.clone(),
+                Vec::new(),
+            )
+            .await?;
+        if !ack.succeeded() {
+            return Ok(ack);
+        }
+        let LatticeEvent {
+            event_type: ty,
+            data,
+            ..
+        } = wait_for_event(&mut events, RESTART_EVENT_TIMEOUT, |evt| {
+            matches_component(evt.data(), 0)
+        })
+        .await
+        .map_err(|e| format!("Component {component_id} did not scale to zero: {e}"))?;
+        if ty == "component_scale_failed" {
+            let error = data
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            return Ok(CtlResponse::error(&format!(
+                "Component {component_id} failed to stop: {error}"
+            )));
+        }
+
+        let ack = self
+            .scale_component(
+                &host_id,
+                &component_ref,
+                &component_id,
+                max_instances,
+                annotations,
+                config,
+            )
+            .await?;
+        if !ack.succeeded() {
+            return Ok(ack);
+        }
+        let LatticeEvent {
+            event_type: ty,
+            data,
+            ..
+        } = wait_for_event(&mut events, RESTART_EVENT_TIMEOUT, |evt| {
+            matches_component(evt.data(), max_instances)
+        })
+        .await
+        .map_err(|e| format!("Component {component_id} did not scale back up: {e}"))?;
+        if ty == "component_scale_failed" {
+            let error = data
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            return Ok(CtlResponse::error(&format!(
+                "Component {component_id} failed to start after stopping: {error}"
+            )));
+        }
+        Ok(CtlResponse::<()>::success(format!(
+            "Component {component_id} restarted on host {host_id}"
+        )))
+    }
+
+    /// Publish a message and wait for a response
+    async fn publish_and_wait<D: DeserializeOwned>(
+        &self,
+        subject: String,
+        payload: Vec<u8>,
+    ) -> Result<Vec<D>> {
+        let start = Instant::now();
+        let request = self.intercept_request(subject.clone(), payload)?;
+        let sub = match self
+            .transport
+            .request_many(
+                request.subject.clone(),
+                request.headers.clone(),
+                request.payload.clone(),
+            )
+            .await
+        {
+            Ok(sub) => sub,
+            Err(e) => {
+                self.record_error(&subject, start, e.as_ref());
+                let res = Err(e);
+                for interceptor in &self.interceptors {
+                    interceptor.on_response(&request, &res);
+                }
+                return res.map(|_| Vec::new());
+            }
+        };
+        let span = Span::current();
+        let sub = sub
+            .inspect(move |msg| otel::link_remote_context(&span, &msg.headers))
+            .boxed();
+        let sub = if self.interceptors.is_empty() {
+            sub
+        } else {
+            let interceptors = self.interceptors.clone();
+            let request = request.clone();
+            sub.inspect(move |msg| {
+                let res = Ok(msg.clone());
+                for interceptor in &interceptors {
+                    interceptor.on_response(&request, &res);
+                }
+            })
+            .boxed()
+        };
+        let responses = tokio::select! {
+            responses = collect_sub_timeout::<D>(sub, self.auction_timeout, &request.subject, &self.reply_limits) => responses,
+            () = self.shutdown.cancelled() => Vec::new(),
+        };
+        self.record(
+            &subject,
+            start,
+            Outcome::Responses(responses.len().try_into().unwrap_or(u64::MAX)),
+        );
+        Ok(responses)
+    }
+
+    /// Returns the receiver end of a channel that subscribes to the lattice event stream.
+    ///
+    /// Any [`Event`]s that are published after this channel is created
+    /// will be added to the receiver channel's buffer, which can be observed or handled if needed.
+    ///
+    /// The receiver survives NATS reconnects: if the subscription ends it is re-established with
+    /// backoff. Whenever events may have been missed, after a reconnect or a resubscribe, a
+    /// synthetic [`EventStreamGap`] marker of type
+    /// [`EVENT_STREAM_GAP_TYPE`](crate::EVENT_STREAM_GAP_TYPE) is delivered, so that consumers can
+    /// re-query lattice state. The stream only ends once the receiver is dropped.
+    ///
+    /// Receivers of the same event type share a single subscription across all clones of this
+    /// client and all clients built from the same [`ClientBuilder`].
+    ///
+    /// See the example for how you could use this receiver to handle events.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use wasmcloud_control_interface::{Client, ClientBuilder};
+    /// async {
+    ///   let nc = async_nats::connect("127.0.0.1:4222").await.unwrap();
+    ///   let client = ClientBuilder::new(nc)
+    ///                 .timeout(std::time::Duration::from_millis(1000))
+    ///                 .auction_timeout(std::time::Duration::from_millis(1000))
+    ///                 .build();
+    ///   let mut receiver = client.events_receiver(vec!["component_scaled".to_string()]).await.unwrap();
+    ///   while let Some(evt) = receiver.recv().await {
+    ///       println!("Event received: {:?}", evt);
+    ///   }
+    /// };
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `event_types` - List of types of events to listen for
+    ///
+    #[allow(clippy::missing_errors_doc)] // TODO: Document errors
+    pub async fn events_receiver(&self, event_types: Vec<String>) -> Result<Receiver<Event>> {
+        self.ensure_open()?;
+        let (sender, receiver) = tokio::sync::mpsc::channel(5000);
+        let subjects: Vec<_> = event_types
+            .into_iter()
+            .map(|event_type| self.subjects().events(&event_type))
+            .collect();
+        let stream =
+            subscribe_events(&self.subscriptions, self.transport.as_ref(), &subjects).await?;
+        self.spawn(forward_events(
+            Arc::clone(&self.transport),
+            self.subscriptions.clone(),
+            self.lattice.clone(),
+            subjects,
+            stream,
+            sender,
+            self.host_versions.clone(),
+        ));
+        Ok(receiver)
+    }
+
+    /// Configuration of the JetStream stream read by [`Client::replay_events`], which retains all
+    /// events of the lattice published on the event subjects of the client's [`ProtocolVersion`]
+    /// in file storage. Limits such as `max_age` are left unset, and can be added before creating
+    /// the stream.
+    #[must_use]
+    pub fn event_stream_config(&self) -> async_nats::jetstream::stream::Config {
+        async_nats::jetstream::stream::Config {
+            name: self.event_stream.clone(),
+            description: Some(format!("Events of wasmCloud lattice {}", self.lattice)),
+            subjects: vec![self.subjects().events(">")],
+            storage: async_nats::jetstream::stream::StorageType::File,
+            ..Default::default()
+        }
+    }
+
+    /// Create the JetStream stream read by [`Client::replay_events`] with
+    /// [`Client::event_stream_config`], unless it exists already. Only events published after the
+    /// stream is created can be replayed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client has no NATS connection or the stream cannot be created
+    pub async fn create_event_stream(&self) -> Result<()> {
+        self.jetstream()?
+            .get_or_create_stream(self.event_stream_config())
+            .await
+            .map_err(|e| format!("Failed to create event stream {}: {e}", self.event_stream))?;
+        Ok(())
+    }
+
+    /// Replay the lattice events published between `from` and `to`, or until the most recent event
+    /// if `to` is not given, e.g. for post-incident analysis. Only events matching `filter`, if
+    /// given, are returned.
+    ///
+    /// Events are read in the order they were published from the JetStream stream configured with
+    /// [`ClientBuilder::event_stream`], on the event subjects of the client's
+    /// [`ProtocolVersion`], i.e. `wasmbus.evt.{lattice}.>` for `v1` and
+    /// `wasmbus.evt.v2.{lattice}.>` for `v2`. The stream must exist before the events to replay
+    /// are published, see [`Client::create_event_stream`]. Events published before the retention
+    /// of the stream are not available. Events that are not valid CloudEvents with JSON data are
+    /// skipped.
+    ///
+    /// ```rust,no_run
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// use futures::TryStreamExt as _;
+    /// use wasmcloud_control_interface::{Client, EventMatcher};
+    ///
+    /// async fn failures(client: &Client) -> anyhow::Result<()> {
+    ///     let since = SystemTime::now() - Duration::from_secs(3600);
+    ///     let filter = EventMatcher::new("component_scale_failed").or_event_type("provider_start_failed");
+    ///     let mut events = client
+    ///         .replay_events(since, None, Some(filter))
+    ///         .await
+    ///         .map_err(anyhow::Error::msg)?;
+    ///     while let Some(event) = events.try_next().await.map_err(anyhow::Error::msg)? {
+    ///         println!("{}: {}", event.event_type(), event.data());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event stream does not exist or cannot be read. Errors reading
+    /// events are returned by the stream, which ends after the first error
+    #[instrument(level = "debug", skip_all)]
+    pub async fn replay_events(
+        &self,
+        from: SystemTime,
+        to: Option<SystemTime>,
+        filter: Option<EventMatcher>,
+    ) -> Result<BoxStream<'static, Result<LatticeEvent>>> {
+        let stream = self
+            .jetstream()?
+            .get_stream(&self.event_stream)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Failed to access event stream {}, ensure it was created with `Client::create_event_stream`: {e}",
+                    self.event_stream
+                )
+            })?;
+        let subjects = self.subjects();
+        let filter_subjects = match &filter {
+            Some(filter) => filter
+                .event_types()
+                .iter()
+                .map(|event_type| subjects.events(event_type))
+                .collect(),
+            None => vec![subjects.events(">")],
+        };
+        debug!(stream = %self.event_stream, ?filter_subjects, "replaying events");
+        let mut consumer = stream
+            .create_consumer(OrderedConfig {
+                deliver_policy: DeliverPolicy::ByStartTime {
+                    start_time: time::OffsetDateTime::from(from),
+                },
+                filter_subjects,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| format!("Failed to create event stream consumer: {e}"))?;
+        let pending = consumer
+            .info()
+            .await
+            .map_err(|e| format!("Failed to query event stream consumer: {e}"))?
+            .num_pending;
+        if pending == 0 {
+            return Ok(futures::stream::empty().boxed());
+        }
+        let messages = consumer
+            .messages()
+            .await
+            .map_err(|e| format!("Failed to read event stream: {e}"))?;
+        // The consumer would wait for new events once it caught up, so the replay ends with the
+        // last event that was pending or the first event published after `to`
+        Ok(futures::stream::unfold(
+            (messages, filter, false),
+            move |(mut messages, filter, done)| async move {
+                if done {
+                    return None;
+                }
+                loop {
+                    let msg = match messages.next().await? {
+                        Ok(msg) => msg,
+                        Err(e) => {
+                            let error = format!("Failed to read event stream: {e}").into();
+                            return Some((Err(error), (messages, filter, true)));
+                        }
+                    };
+                    let (published, last) = match msg.info() {
+                        Ok(info) => (SystemTime::from(info.published), info.pending == 0),
+                        Err(e) => {
+                            let error = format!("Invalid event stream message: {e}").into();
+                            return Some((Err(error), (messages, filter, true)));
+                        }
+                    };
+                    if to.is_some_and(|to| published > to) {
+                        return None;
+                    }
+                    let event = serde_json::from_slice::<Event>(&msg.payload)
+                        .ok()
+                        .and_then(|event| LatticeEvent::try_from(event).ok())
+                        .filter(|event| filter.as_ref().is_none_or(|f| f.matches(event)));
+                    match event {
+                        Some(event) => return Some((Ok(event), (messages, filter, last))),
+                        None if last => return None,
+                        None => trace!(subject = %msg.subject, "skipping replayed event"),
+                    }
+                }
+            },
+        )
+        .boxed())
+    }
+
+    /// Wait up to `timeout` for the first lattice event matching `matcher`, e.g. to verify that a
+    /// component was scaled after issuing [`Client::scale_component`].
+    ///
+    /// Only events published after this function is first polled are observed. To avoid missing
+    /// the outcome of a command, start waiting before issuing the command, e.g. with
+    /// [`tokio::join!`] or by spawning the wait.
+    ///
+    /// ```rust
+    /// use core::time::Duration;
+    ///
+    /// use wasmcloud_control_interface::{Client, EventMatcher};
+    ///
+    /// async fn scale_and_verify(client: &Client, host_id: &str) -> anyhow::Result<()> {
+    ///     let scaled = client.wait_for_event(
+    ///         EventMatcher::new("component_scaled")
+    ///             .or_event_type("component_scale_failed")
+    ///             .host(host_id)
+    ///             .component("echo"),
+    ///         Duration::from_secs(30),
+    ///     );
+    ///     let scale = client.scale_component(
+    ///         host_id,
+    ///         "ghcr.io/wasmcloud/components/http-hello-world-rust:0.1.0",
+    ///         "echo",
+    ///         1,
+    ///         None,
+    ///         vec![],
+    ///     );
+    ///     let (event, ack) = tokio::join!(scaled, scale);
+    ///     ack.map_err(anyhow::Error::msg)?;
+    ///     let event = event.map_err(anyhow::Error::msg)?;
+    ///     anyhow::ensure!(event.event_type() == "component_scaled", "{:?}", event.data());
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if subscribing to events fails, or no matching event was received before
+    /// the timeout elapsed
+    pub async fn wait_for_event(
+        &self,
+        matcher: EventMatcher,
+        timeout: Duration,
+    ) -> Result<LatticeEvent> {
+        let mut events = self
+            .events_receiver(matcher.event_types().iter().cloned().collect())
+            .await?;
+        wait_for_event(&mut events, timeout, |evt| matcher.matches(evt)).await
+    }
+
+    /// Close the client, stopping all background tasks spawned by it and its clones and waiting
+    /// for them to finish.
+    ///
+    /// Event and config watch subscriptions are unsubscribed and the receivers returned by
+    /// [`Client::events_receiver`] and [`Client::watch_config`] end. Pending requests that gather
+    /// responses from several hosts, such as [`Client::get_hosts`], return the responses gathered
+    /// so far. Requests issued after closing still work, but new event
+    /// receivers and config watches can no longer be created.
+    ///
+    /// Background tasks also stop when their receiver is dropped, so calling this is only
+    /// required to release subscriptions early, e.g. when clients are recreated frequently.
+    pub async fn close(&self) {
+        self.shutdown.cancel();
+        self.tasks.close();
+        self.tasks.wait().await;
+    }
+
+    /// Returns whether [`Client::close`] was called on this client or one of its clones
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    fn ensure_open(&self) -> Result<()> {
+        if self.is_closed() {
+            return Err("control interface client is closed".into());
+        }
+        Ok(())
+    }
+
+    /// Spawn a background task that runs until it completes or the client is closed
+    fn spawn(&self, task: impl Future<Output = ()> + Send + 'static) {
+        let shutdown = self.shutdown.clone();
+        self.tasks.spawn(async move {
+            tokio::select! {
+                () = task => {}
+                () = shutdown.cancelled() => {}
+            }
+        });
+    }
+}
+
+/// Subscribe to all `subjects` and merge the resulting streams
+async fn subscribe_events(
+    subscriptions: &SubscriptionMux,
+    transport: &dyn ControlTransport,
+    subjects: &[String],
+) -> Result<BoxStream<'static, TransportMessage>> {
+    let subs: Vec<_> = futures::future::join_all(
+        subjects
+            .iter()
+            .map(|subject| subscriptions.subscribe(transport, subject.clone())),
+    )
+    .await
+    .into_iter()
+    .collect::<Result<_>>()?;
+    Ok(futures::stream::select_all(subs).boxed())
+}
+
+/// Forward lattice events from `stream` to `sender` until the receiver is dropped, resubscribing
+/// whenever the subscription ends and emitting [`EventStreamGap`] markers when events may have been
+/// missed
+async fn forward_events(
+    transport: Arc<dyn ControlTransport>,
+    subscriptions: SubscriptionMux,
+    lattice: String,
+    subjects: Vec<String>,
+    mut stream: BoxStream<'static, TransportMessage>,
+    sender: Sender<Event>,
+    host_versions: HostVersions,
+) {
+    let mut connected = transport.is_connected();
+    let mut connection_check = tokio::time::interval(EVENT_CONNECTION_CHECK_INTERVAL);
+    connection_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        let gap = tokio::select! {
+            msg = stream.next() => {
+                if let Some(msg) = msg {
+                    if !forward_event(&msg, &sender, &host_versions).await {
+                        return;
+                    }
+                    continue;
+                }
+                debug!(?subjects, "event subscription ended, resubscribing");
+                let Some(resubscribed) = resubscribe_events(&subscriptions, transport.as_ref(), &subjects, &sender).await else {
+                    return;
+                };
+                stream = resubscribed;
+                EventStreamGapReason::Resubscribed
+            }
+            _ = connection_check.tick() => {
+                let was_connected = core::mem::replace(&mut connected, transport.is_connected());
+                if was_connected || !connected {
+                    continue;
+                }
+                EventStreamGapReason::Reconnected
+            }
+            () = sender.closed() => return,
+        };
+        warn!(lattice, reason = ?gap, "lattice events may have been missed");
+        match EventStreamGap::new(lattice.clone(), gap).to_event() {
+            Ok(evt) => {
+                if sender.send(evt).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => error!(error = %e, "failed to build event stream gap marker"),
+        }
+    }
+}
+
+/// Forward a single event message to `sender`, returning `false` if the receiver was dropped
+async fn forward_event(
+    msg: &TransportMessage,
+    sender: &Sender<Event>,
+    host_versions: &HostVersions,
+) -> bool {
+    let Ok(evt) = json_deserialize::<Event>(&msg.payload) else {
+        error!("Object received on event stream was not a CloudEvent");
+        return true;
+    };
+    trace!("received event: {:?}", evt);
+    if evt.ty().ends_with("host_heartbeat") {
+        if let Some(Data::Json(data)) = evt.data() {
+            if let Some(version) = data.get("version").and_then(|v| v.as_str()) {
+                host_versions.observe(evt.source().as_str(), version);
+            }
+        }
+    }
+    sender.send(evt).await.is_ok()
+}
+
+/// Resubscribe to `subjects` with exponential backoff, returning `None` if the receiver of
+/// `sender` is dropped before the subscription succeeds
+async fn resubscribe_events(
+    subscriptions: &SubscriptionMux,
+    transport: &dyn ControlTransport,
+    subjects: &[String],
+    sender: &Sender<Event>,
+) -> Option<BoxStream<'static, TransportMessage>> {
+    let mut backoff = EVENT_RESUBSCRIBE_MIN_BACKOFF;
+    loop {
+        match subscribe_events(subscriptions, transport, subjects).await {
+            Ok(stream) => return Some(stream),
+            Err(e) => warn!(error = %e, ?backoff, "failed to resubscribe to lattice events"),
+        }
+        tokio::select! {
+            () = tokio::time::sleep(backoff) => {}
+            () = sender.closed() => return None,
+        }
+        backoff = (backoff * 2).min(EVENT_RESUBSCRIBE_MAX_BACKOFF);
+    }
+}
+
+/// Add context to the error of a request, passing [`HostNotFound`] and [`ReplyTooLarge`] through so
+/// that callers can detect them
+fn request_error(
+    e: Box<dyn std::error::Error + Send + Sync>,
+    context: &str,
+) -> Box<dyn std::error::Error + Send + Sync> {
+    if e.is::<HostNotFound>() || e.is::<ReplyTooLarge>() {
+        e
+    } else {
+        format!("{context}: {e}").into()
+    }
+}
+
+/// Ensure that a successful response to a read pinned with a [`RevisionQuery`] reflects at least
+/// `min_revision`, which is not the case if the responding host ignored the query
+fn check_revision<T>(resp: CtlResponse<T>, min_revision: u64) -> Result<CtlResponse<T>> {
+    if !resp.succeeded() || resp.data().is_none() {
+        return Ok(resp);
+    }
+    match resp.revision() {
+        Some(revision) if revision >= min_revision => Ok(resp),
+        Some(revision) => {
+            Err(format!("response reflects revision {revision}, not {min_revision}").into())
+        }
+        None => Err("responding host does not support reads pinned to a revision".into()),
+    }
+}
+
+/// Merge `defaults` into `annotations`, keeping the value of `annotations` for keys present in
+/// both. Returns `None` if neither contains any annotation
+fn merge_default_annotations(
+    defaults: &Annotations,
+    annotations: Option<Annotations>,
+) -> Option<Annotations> {
+    if defaults.is_empty() {
+        return annotations;
+    }
+    let mut merged = defaults.as_map().clone();
+    merged.extend(annotations.map(Annotations::into_inner).unwrap_or_default());
+    Some(merged.into())
+}
+
+/// Wait for the first event with JSON data matching `predicate`
+async fn wait_for_event(
+    events: &mut Receiver<Event>,
+    timeout: Duration,
+    predicate: impl Fn(&LatticeEvent) -> bool,
+) -> Result<LatticeEvent> {
+    let wait = async {
+        while let Some(evt) = events.recv().await {
+            let Ok(evt) = LatticeEvent::try_from(evt) else {
+                continue;
+            };
+            if predicate(&evt) {
+                return Ok(evt);
+            }
+        }
+        Err("event stream closed".into())
+    };
+    tokio::time::timeout(timeout, wait)
+        .await
+        .map_err(|_| format!("timed out after {timeout:?} waiting for event"))?
+}
+
+/// Collect `T` values until timeout has elapsed
+pub(crate) async fn collect_sub_timeout<T: DeserializeOwned>(
+    mut sub: BoxStream<'static, TransportMessage>,
     timeout: Duration,
     reason: &str,
+    limits: &ReplyLimits,
 ) -> Vec<T> {
     let mut items = Vec::new();
     let sleep = tokio::time::sleep(timeout);
@@ -842,7 +3953,11 @@ pub(crate) async fn collect_sub_timeout<T: DeserializeOwned>(
                 if msg.payload.is_empty() {
                     break;
                 }
-                match json_deserialize::<T>(&msg.payload) {
+                if let Err(error) = limits.check(reason, &msg) {
+                    warn!(%error, "dropping reply exceeding the reply limits");
+                    continue;
+                }
+                match decode::<T>(&msg) {
                     Ok(item) => items.push(item),
                     Err(error) => {
                         error!(%reason, %error,
@@ -858,10 +3973,53 @@ pub(crate) async fn collect_sub_timeout<T: DeserializeOwned>(
     items
 }
 
+/// Orders the acknowledgements gathered by an auction from most to least suitable host.
+///
+/// Failed responses are dropped. Hosts that reported capacity hints rank ahead of hosts that did
+/// not, then hosts running fewer workloads of the auctioned kind, then hosts with more free
+/// memory, then hosts that have been up longer. Ties keep the order in which the acks were
+/// received, so hosts without hints are still ranked first responder first.
+///
+/// ```
+/// # use wasmcloud_control_interface::{rank_auction_results, ComponentAuctionAck, CtlResponse};
+/// # fn pick(acks: Vec<CtlResponse<ComponentAuctionAck>>) -> Option<ComponentAuctionAck> {
+/// rank_auction_results(acks).into_iter().next()
+/// # }
+/// ```
+pub fn rank_auction_results<T: AuctionBid>(
+    acks: impl IntoIterator<Item = CtlResponse<T>>,
+) -> Vec<T> {
+    let mut bids: Vec<T> = acks
+        .into_iter()
+        .filter(CtlResponse::succeeded)
+        .filter_map(CtlResponse::into_data)
+        .collect();
+    bids.sort_by_key(|bid| {
+        let hints = bid.hints();
+        (
+            hints.is_empty(),
+            bid.running_count().unwrap_or(u64::MAX),
+            Reverse(hints.free_memory_bytes().unwrap_or_default()),
+            Reverse(hints.uptime_seconds().unwrap_or_default()),
+        )
+    });
+    bids
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const HOST_ID: &str = "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC";
+
+    /// Receive the next item from `receiver`, failing if none arrives within 5 seconds
+    async fn recv<T>(receiver: &mut Receiver<T>) -> Result<T> {
+        tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .map_err(|_| "timed out waiting for item")?
+            .ok_or_else(|| "receiver closed".into())
+    }
+
     /// Note: This test is a means of manually watching the event stream as CloudEvents are received
     /// It does not assert functionality, and so we've marked it as ignore to ensure it's not run by default
     /// It currently listens for 120 seconds then exits
@@ -886,6 +4044,366 @@ mod tests {
         tokio::time::sleep(Duration::from_secs(120)).await;
     }
 
+    #[test]
+    fn test_rank_auction_results() {
+        use crate::AuctionHints;
+
+        let ack = |host_id: &str, hints: AuctionHints| {
+            CtlResponse::ok(
+                ComponentAuctionAck::builder()
+                    .component_ref("ghcr.io/wasmcloud/echo:0.1.0".into())
+                    .component_id("echo".into())
+                    .host_id(host_id.into())
+                    .hints(hints)
+                    .build()
+                    .expect("failed to build ack"),
+            )
+        };
+        let acks = vec![
+            ack("legacy", AuctionHints::default()),
+            ack(
+                "busy",
+                AuctionHints::builder()
+                    .component_count(8)
+                    .free_memory_bytes(1 << 32)
+                    .build(),
+            ),
+            ack(
+                "fresh",
+                AuctionHints::builder()
+                    .component_count(2)
+                    .free_memory_bytes(1 << 30)
+                    .uptime_seconds(10)
+                    .build(),
+            ),
+            CtlResponse {
+                success: false,
+                message: "host is draining".into(),
+                response: None,
+                revision: None,
+                code: None,
+                retry_after_ms: None,
+            },
+            ack(
+                "roomy",
+                AuctionHints::builder()
+                    .component_count(2)
+                    .free_memory_bytes(1 << 31)
+                    .build(),
+            ),
+            ack(
+                "stable",
+                AuctionHints::builder()
+                    .component_count(2)
+                    .free_memory_bytes(1 << 30)
+                    .uptime_seconds(3600)
+                    .build(),
+            ),
+        ];
+        let ranked: Vec<_> = rank_auction_results(acks)
+            .iter()
+            .map(|ack| ack.host_id().to_string())
+            .collect();
+        assert_eq!(ranked, ["roomy", "stable", "fresh", "busy", "legacy"]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_event() -> Result<()> {
+        use cloudevents::{EventBuilder, EventBuilderV10};
+
+        let event = |ty: &str, provider_id: &str| {
+            EventBuilderV10::new()
+                .id("id")
+                .source("host")
+                .ty(format!("com.wasmcloud.lattice.{ty}"))
+                .data(
+                    "application/json",
+                    serde_json::json!({ "provider_id": provider_id }),
+                )
+                .build()
+                .expect("failed to build event")
+        };
+        let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+        tx.send(event("provider_started", "other")).await?;
+        tx.send(event("provider_stopped", "provider")).await?;
+        tx.send(event("provider_started", "provider")).await?;
+
+        let evt = wait_for_event(&mut rx, Duration::from_secs(1), |evt| {
+            evt.event_type() == "provider_started" && evt.data()["provider_id"] == "provider"
+        })
+        .await?;
+        assert_eq!(evt.event_type(), "provider_started");
+        assert_eq!(evt.data()["provider_id"], "provider");
+
+        assert!(
+            wait_for_event(&mut rx, Duration::from_millis(10), |_| true)
+                .await
+                .is_err(),
+            "waiting should time out without further events"
+        );
+        Ok(())
+    }
+
+    /// Transport that ends the first event subscription after one message and can simulate a
+    /// lost connection
+    #[derive(Debug)]
+    struct FlakyTransport {
+        lattice: crate::testing::MockLattice,
+        subscriptions: Arc<std::sync::atomic::AtomicUsize>,
+        connected: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl ControlTransport for FlakyTransport {
+        fn request(
+            &self,
+            subject: String,
+            headers: async_nats::HeaderMap,
+            payload: bytes::Bytes,
+            timeout: Duration,
+        ) -> futures::future::BoxFuture<'_, Result<TransportMessage>> {
+            self.lattice.request(subject, headers, payload, timeout)
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            headers: async_nats::HeaderMap,
+            payload: bytes::Bytes,
+        ) -> futures::future::BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.lattice.request_many(subject, headers, payload)
+        }
+
+        fn publish(
+            &self,
+            subject: String,
+            headers: async_nats::HeaderMap,
+            payload: bytes::Bytes,
+        ) -> futures::future::BoxFuture<'_, Result<()>> {
+            self.lattice.publish(subject, headers, payload)
+        }
+
+        fn subscribe(
+            &self,
+            subject: String,
+        ) -> futures::future::BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            use futures::FutureExt as _;
+
+            let first = self
+                .subscriptions
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                == 0;
+            async move {
+                let sub = self.lattice.subscribe(subject).await?;
+                Ok(if first { sub.take(1).boxed() } else { sub })
+            }
+            .boxed()
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_receiver_signals_gaps() -> Result<()> {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        let lattice = crate::testing::MockLattice::new("default");
+        let subscriptions = Arc::new(AtomicUsize::new(0));
+        let connected = Arc::new(AtomicBool::new(true));
+        let client = ClientBuilder::with_transport(FlakyTransport {
+            lattice: lattice.clone(),
+            subscriptions: Arc::clone(&subscriptions),
+            connected: Arc::clone(&connected),
+        })
+        .lattice("default")
+        .build();
+        let mut events = client
+            .events_receiver(vec!["component_scaled".into()])
+            .await?;
+
+        lattice.publish_event("component_scaled", HOST_ID, serde_json::json!({ "n": 1 }))?;
+        let evt =
+            LatticeEvent::try_from(recv(&mut events).await?).expect("event should have JSON data");
+        assert_eq!(evt.data()["n"], 1);
+
+        // The first subscription ended after a single event and must have been replaced
+        let gap =
+            EventStreamGap::from_event(&recv(&mut events).await?).expect("expected a gap marker");
+        assert_eq!(gap.reason, EventStreamGapReason::Resubscribed);
+        assert_eq!(gap.lattice, "default");
+        assert_eq!(subscriptions.load(Ordering::SeqCst), 2);
+
+        lattice.publish_event("component_scaled", HOST_ID, serde_json::json!({ "n": 2 }))?;
+        let evt =
+            LatticeEvent::try_from(recv(&mut events).await?).expect("event should have JSON data");
+        assert_eq!(evt.data()["n"], 2);
+
+        // Losing and regaining the connection is signaled once reconnected
+        connected.store(false, Ordering::SeqCst);
+        tokio::time::sleep(EVENT_CONNECTION_CHECK_INTERVAL * 2).await;
+        connected.store(true, Ordering::SeqCst);
+        let gap =
+            EventStreamGap::from_event(&recv(&mut events).await?).expect("expected a gap marker");
+        assert_eq!(gap.reason, EventStreamGapReason::Reconnected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_events_receivers_share_subscriptions() -> Result<()> {
+        const SUBJECT: &str = "wasmbus.evt.default.component_scaled";
+
+        let lattice = crate::testing::MockLattice::default();
+        let builder = lattice.client_builder();
+        let clients: Vec<_> = (0..10).map(|_| builder.clone().build()).collect();
+        let mut receivers = Vec::new();
+        for client in &clients {
+            receivers.push(
+                client
+                    .events_receiver(vec!["component_scaled".into()])
+                    .await?,
+            );
+        }
+        assert_eq!(lattice.subscriptions(SUBJECT), 1);
+
+        lattice.publish_event("component_scaled", HOST_ID, serde_json::json!({ "n": 1 }))?;
+        for events in &mut receivers {
+            let evt =
+                LatticeEvent::try_from(recv(events).await?).expect("event should have JSON data");
+            assert_eq!(evt.data()["n"], 1);
+        }
+
+        // Clients built from another builder hold their own subscription
+        let mut other = lattice
+            .client()
+            .events_receiver(vec!["component_scaled".into()])
+            .await?;
+        assert_eq!(lattice.subscriptions(SUBJECT), 2);
+        lattice.publish_event("component_scaled", HOST_ID, serde_json::json!({ "n": 2 }))?;
+        recv(&mut other).await?;
+        drop(other);
+
+        // The shared subscription is released once its last receiver is dropped
+        receivers.truncate(1);
+        lattice.publish_event("component_scaled", HOST_ID, serde_json::json!({ "n": 3 }))?;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(lattice.subscriptions(SUBJECT), 1);
+        drop(receivers);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while lattice.subscriptions(SUBJECT) > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .map_err(|_| "shared subscription was not released")?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_label_events() -> Result<()> {
+        let lattice = crate::testing::MockLattice::default();
+        lattice.add_host_with_labels(
+            HOST_ID,
+            BTreeMap::from([("zone".to_string(), "us-east-1".to_string())]),
+        );
+        let client = lattice.client();
+        let mut changes = client.label_events().await?;
+
+        client.put_label(HOST_ID, "gpu", "true").await?;
+        assert_eq!(
+            recv(&mut changes).await?,
+            LabelChange::Put {
+                host_id: HOST_ID.into(),
+                key: "gpu".into(),
+                value: "true".into(),
+                previous: None,
+            }
+        );
+        client.put_label(HOST_ID, "zone", "us-west-2").await?;
+        assert_eq!(
+            recv(&mut changes).await?,
+            LabelChange::Put {
+                host_id: HOST_ID.into(),
+                key: "zone".into(),
+                value: "us-west-2".into(),
+                previous: Some("us-east-1".into()),
+            }
+        );
+        client.delete_label(HOST_ID, "gpu").await?;
+        assert_eq!(
+            recv(&mut changes).await?,
+            LabelChange::Delete {
+                host_id: HOST_ID.into(),
+                key: "gpu".into(),
+                value: "true".into(),
+            }
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_close_stops_background_tasks() -> Result<()> {
+        let lattice = crate::testing::MockLattice::default();
+        let client = lattice.client();
+        let mut first = client
+            .events_receiver(vec!["component_scaled".into()])
+            .await?;
+        let mut second = client
+            .clone()
+            .events_receiver(vec!["host_heartbeat".into()])
+            .await?;
+        assert!(!client.is_closed());
+
+        tokio::time::timeout(Duration::from_secs(5), client.close())
+            .await
+            .map_err(|_| "timed out closing client")?;
+        assert!(client.is_closed());
+        assert!(first.recv().await.is_none());
+        assert!(second.recv().await.is_none());
+        assert!(client
+            .events_receiver(vec!["component_scaled".into()])
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_follows_protocol_version() -> Result<()> {
+        let lattice = crate::testing::MockLattice::new("prod");
+        let config = lattice.client().event_stream_config();
+        assert_eq!(config.name, DEFAULT_EVENT_STREAM);
+        assert_eq!(config.subjects, ["wasmbus.evt.prod.>"]);
+
+        let client = lattice
+            .client_builder()
+            .protocol_version(ProtocolVersion::V2)
+            .event_stream("prod_events")
+            .build();
+        let config = client.event_stream_config();
+        assert_eq!(config.name, "prod_events");
+        assert_eq!(config.subjects, ["wasmbus.evt.v2.prod.>"]);
+        assert_eq!(
+            client.subjects().events("component_scaled"),
+            "wasmbus.evt.v2.prod.component_scaled"
+        );
+
+        // The mock transport has no JetStream to replay from
+        assert!(client.create_event_stream().await.is_err());
+        assert!(client
+            .replay_events(SystemTime::now(), None, None)
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_nats_client_requires_nats_transport() {
+        let client = ClientBuilder::with_transport(crate::testing::MockLattice::new("default"))
+            .lattice("default")
+            .build();
+        assert!(client.nats_client().is_none());
+    }
+
     #[test]
     fn test_check_identifier() -> Result<()> {
         assert!(IdentifierKind::is_host_id("").is_err());
@@ -912,6 +4430,32 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_default_annotations() -> Result<()> {
+        assert_eq!(merge_default_annotations(&Annotations::new(), None), None);
+
+        let defaults = Annotations::builder()
+            .managed_by("controller")
+            .annotation("team", "platform")
+            .build()?;
+        assert_eq!(
+            merge_default_annotations(&defaults, None),
+            Some(defaults.clone())
+        );
+
+        let annotations = Annotations::builder()
+            .annotation("team", "payments")
+            .annotation("environment", "staging")
+            .build()?;
+        let merged = merge_default_annotations(&defaults, Some(annotations))
+            .expect("annotations should be merged");
+        assert_eq!(merged.managed_by(), Some("controller"));
+        assert_eq!(merged.get("team"), Some("payments"));
+        assert_eq!(merged.get("environment"), Some("staging"));
+        assert_eq!(merged.len(), 3);
+        Ok(())
+    }
+
     #[tokio::test]
     #[ignore]
     /// Test after large 1.0 refcomponents to ensure all return types are formatted as [CtlResponse] types, and that
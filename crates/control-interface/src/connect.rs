@@ -0,0 +1,254 @@
+//! Construction of the NATS connection used by a [`Client`](crate::Client), for consumers that do
+//! not manage their own connection.
+
+use core::fmt;
+use core::time::Duration;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_nats::ConnectOptions;
+
+use crate::Result;
+
+/// Default URL of the NATS server to connect to
+const DEFAULT_NATS_URL: &str = "nats://127.0.0.1:4222";
+
+/// Options used to connect to NATS with [`ClientBuilder::connect`](crate::ClientBuilder::connect).
+///
+/// Authentication uses a credentials file if one is set, otherwise a JWT along with the seed used to
+/// sign the server nonce, otherwise a bare nkey seed. Without any of these the connection is
+/// anonymous.
+#[derive(Clone, Default)]
+#[non_exhaustive]
+#[must_use]
+pub struct NatsConnectOptions {
+    url: Option<String>,
+    credsfile: Option<PathBuf>,
+    jwt: Option<String>,
+    seed: Option<String>,
+    tls_ca_file: Option<PathBuf>,
+    tls_first: bool,
+    name: Option<String>,
+    connection_timeout: Option<Duration>,
+    max_reconnects: Option<usize>,
+    reconnect_delay: Option<Duration>,
+    retry_on_initial_connect: bool,
+}
+
+impl fmt::Debug for NatsConnectOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NatsConnectOptions")
+            .field("url", &self.url)
+            .field("credsfile", &self.credsfile)
+            .field("jwt", &self.jwt.as_ref().map(|_| "<redacted>"))
+            .field("seed", &self.seed.as_ref().map(|_| "<redacted>"))
+            .field("tls_ca_file", &self.tls_ca_file)
+            .field("tls_first", &self.tls_first)
+            .field("name", &self.name)
+            .field("connection_timeout", &self.connection_timeout)
+            .field("max_reconnects", &self.max_reconnects)
+            .field("reconnect_delay", &self.reconnect_delay)
+            .field("retry_on_initial_connect", &self.retry_on_initial_connect)
+            .finish()
+    }
+}
+
+impl NatsConnectOptions {
+    /// Create options for connecting to the NATS server at `url`, e.g. `nats://127.0.0.1:4222`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: Some(url.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Get the URL of the NATS server, which defaults to `nats://127.0.0.1:4222`
+    #[must_use]
+    pub fn url(&self) -> &str {
+        self.url.as_deref().unwrap_or(DEFAULT_NATS_URL)
+    }
+
+    /// Authenticate with the NATS credentials file at `path`
+    pub fn credsfile(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            credsfile: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Authenticate with a user JWT. The nonce is signed with the seed set with
+    /// [`NatsConnectOptions::seed`], or with a newly generated user key if no seed is set
+    pub fn jwt(self, jwt: impl Into<String>) -> Self {
+        Self {
+            jwt: Some(jwt.into()),
+            ..self
+        }
+    }
+
+    /// Set the nkey seed used to authenticate, either along with a JWT or on its own
+    pub fn seed(self, seed: impl Into<String>) -> Self {
+        Self {
+            seed: Some(seed.into()),
+            ..self
+        }
+    }
+
+    /// Require TLS, trusting the CA certificates in the PEM file at `path`
+    pub fn tls_ca_file(self, path: impl Into<PathBuf>) -> Self {
+        Self {
+            tls_ca_file: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Perform the TLS handshake before receiving the server INFO message
+    pub fn tls_first(self, tls_first: bool) -> Self {
+        Self { tls_first, ..self }
+    }
+
+    /// Set the name of the connection, as reported to the NATS server
+    pub fn name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Set the timeout for establishing the connection
+    pub fn connection_timeout(self, timeout: Duration) -> Self {
+        Self {
+            connection_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the maximum number of reconnection attempts after the connection is lost. If not set,
+    /// the client reconnects indefinitely
+    pub fn max_reconnects(self, max_reconnects: usize) -> Self {
+        Self {
+            max_reconnects: Some(max_reconnects),
+            ..self
+        }
+    }
+
+    /// Set a fixed delay between reconnection attempts instead of the default exponential backoff
+    pub fn reconnect_delay(self, delay: Duration) -> Self {
+        Self {
+            reconnect_delay: Some(delay),
+            ..self
+        }
+    }
+
+    /// Keep retrying when the initial connection fails, instead of returning an error
+    pub fn retry_on_initial_connect(self, retry: bool) -> Self {
+        Self {
+            retry_on_initial_connect: retry,
+            ..self
+        }
+    }
+
+    /// Build the [`ConnectOptions`] described by these options
+    async fn connect_options(&self) -> Result<ConnectOptions> {
+        let mut opts = if let Some(credsfile) = &self.credsfile {
+            ConnectOptions::with_credentials_file(credsfile.clone())
+                .await
+                .map_err(|e| {
+                    format!(
+                        "failed to read NATS credentials file [{}]: {e}",
+                        credsfile.display()
+                    )
+                })?
+        } else if let Some(jwt) = &self.jwt {
+            let key_pair = Arc::new(match &self.seed {
+                Some(seed) => nkeys::KeyPair::from_seed(seed)
+                    .map_err(|e| format!("failed to create key pair from seed: {e}"))?,
+                None => nkeys::KeyPair::new_user(),
+            });
+            ConnectOptions::with_jwt(jwt.clone(), move |nonce| {
+                let key_pair = Arc::clone(&key_pair);
+                async move { key_pair.sign(&nonce).map_err(async_nats::AuthError::new) }
+            })
+        } else if let Some(seed) = &self.seed {
+            ConnectOptions::with_nkey(seed.clone())
+        } else {
+            ConnectOptions::new()
+        };
+        if let Some(ca_file) = &self.tls_ca_file {
+            opts = opts
+                .add_root_certificates(ca_file.clone())
+                .require_tls(true);
+        }
+        if self.tls_first {
+            opts = opts.tls_first();
+        }
+        if let Some(name) = &self.name {
+            opts = opts.name(name);
+        }
+        if let Some(timeout) = self.connection_timeout {
+            opts = opts.connection_timeout(timeout);
+        }
+        if let Some(max_reconnects) = self.max_reconnects {
+            opts = opts.max_reconnects(max_reconnects);
+        }
+        if let Some(delay) = self.reconnect_delay {
+            opts = opts.reconnect_delay_callback(move |_| delay);
+        }
+        if self.retry_on_initial_connect {
+            opts = opts.retry_on_initial_connect();
+        }
+        Ok(opts)
+    }
+
+    /// Connect to NATS
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the credentials are invalid or the connection cannot be established
+    pub async fn connect(&self) -> Result<async_nats::Client> {
+        let url = self.url();
+        self.connect_options()
+            .await?
+            .connect(url)
+            .await
+            .map_err(|e| format!("failed to connect to NATS server [{url}]: {e}").into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NatsConnectOptions;
+    use crate::Result;
+
+    #[tokio::test]
+    async fn builds_connect_options() -> Result<()> {
+        let seed = nkeys::KeyPair::new_user().seed()?;
+        let opts = NatsConnectOptions::new("nats://example.com:4222")
+            .jwt("eyJ0eXAiOiJKV1QiLCJhbGciOiJlZDI1NTE5LW5rZXkifQ")
+            .seed(&seed)
+            .max_reconnects(3);
+        assert_eq!(opts.url(), "nats://example.com:4222");
+        assert!(opts.connect_options().await.is_ok());
+        assert!(
+            !format!("{opts:?}").contains(&seed),
+            "seeds must not be logged"
+        );
+
+        let err = NatsConnectOptions::default()
+            .jwt("jwt")
+            .seed("not a seed")
+            .connect_options()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("seed"));
+
+        let err = NatsConnectOptions::default()
+            .credsfile("/does/not/exist.creds")
+            .connect_options()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("/does/not/exist.creds"));
+        assert_eq!(NatsConnectOptions::default().url(), "nats://127.0.0.1:4222");
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+//! Negotiated encoding of control interface replies.
+//!
+//! Replies are JSON encoded by default. Encoding large replies, such as host inventories and
+//! claims, as [MessagePack](https://msgpack.org) is considerably cheaper on large lattices. The
+//! encoding is negotiated as follows:
+//!
+//! 1. A client configured with [`ClientBuilder::encoding`](crate::ClientBuilder::encoding) marks
+//!    its requests with the [`ACCEPT_ENCODING_HEADER`] header. Request payloads are always JSON.
+//! 2. A host that supports the requested encoding encodes its reply with it and sets the
+//!    [`CONTENT_TYPE_HEADER`] header. Hosts that do not know the header reply with JSON.
+//! 3. The client decodes every reply according to its [`CONTENT_TYPE_HEADER`], falling back to
+//!    JSON if it is not set.
+
+use async_nats::HeaderMap;
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::transport::TransportMessage;
+use crate::Result;
+
+/// Header set on requests by clients that accept replies in an encoding other than JSON
+pub const ACCEPT_ENCODING_HEADER: &str = "Wasmcloud-Accept-Encoding";
+/// Header carrying the content type of a reply
+pub const CONTENT_TYPE_HEADER: &str = "Content-Type";
+
+/// Encoding of control interface replies
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// JSON, understood by every host
+    #[default]
+    Json,
+    /// MessagePack, with struct fields encoded as maps
+    MsgPack,
+}
+
+impl Encoding {
+    /// Get the content type of this encoding
+    #[must_use]
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MsgPack => "application/msgpack",
+        }
+    }
+
+    /// Get the encoding with the given content type
+    #[must_use]
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.trim() {
+            "application/json" => Some(Self::Json),
+            "application/msgpack" | "application/x-msgpack" => Some(Self::MsgPack),
+            _ => None,
+        }
+    }
+
+    /// Returns the encoding accepted by a request with the given headers, which is JSON unless the
+    /// request asks for a known encoding
+    #[must_use]
+    pub fn accepted(headers: Option<&HeaderMap>) -> Self {
+        headers
+            .and_then(|headers| headers.get(ACCEPT_ENCODING_HEADER))
+            .and_then(|value| Self::from_content_type(value.as_str()))
+            .unwrap_or_default()
+    }
+
+    /// Returns the encoding of a reply with the given headers, which is JSON if the content type
+    /// is not set
+    pub fn of_reply(headers: &HeaderMap) -> Result<Self> {
+        match headers.get(CONTENT_TYPE_HEADER) {
+            None => Ok(Self::Json),
+            Some(value) => Self::from_content_type(value.as_str())
+                .ok_or_else(|| format!("unsupported reply content type `{value}`").into()),
+        }
+    }
+
+    /// Serialize an item with this encoding
+    pub fn serialize<T: Serialize>(self, item: &T) -> Result<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec(item)
+                .map_err(|e| format!("JSON serialization failure: {e}").into()),
+            Self::MsgPack => rmp_serde::to_vec_named(item)
+                .map_err(|e| format!("MessagePack serialization failure: {e}").into()),
+        }
+    }
+
+    /// Deserialize an item encoded with this encoding
+    pub fn deserialize<T: DeserializeOwned>(self, buf: &[u8]) -> Result<T> {
+        match self {
+            Self::Json => serde_json::from_slice(buf)
+                .map_err(|e| format!("JSON deserialization failure: {e}").into()),
+            Self::MsgPack => rmp_serde::from_slice(buf)
+                .map_err(|e| format!("MessagePack deserialization failure: {e}").into()),
+        }
+    }
+
+    /// Re-encode a JSON encoded reply with this encoding
+    pub fn from_json(self, json: Bytes) -> Result<Bytes> {
+        match self {
+            Self::Json => Ok(json),
+            Self::MsgPack => {
+                let value: serde_json::Value = serde_json::from_slice(&json)
+                    .map_err(|e| format!("JSON deserialization failure: {e}"))?;
+                self.serialize(&value).map(Bytes::from)
+            }
+        }
+    }
+
+    /// Add the headers of a reply with this encoding to `headers`
+    pub fn insert_reply_headers(self, headers: &mut HeaderMap) {
+        if self != Self::Json {
+            headers.insert(CONTENT_TYPE_HEADER, self.content_type());
+        }
+    }
+}
+
+/// Deserialize a reply according to its content type
+pub(crate) fn decode<T: DeserializeOwned>(msg: &TransportMessage) -> Result<T> {
+    Encoding::of_reply(&msg.headers)?.deserialize(&msg.payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use async_nats::HeaderMap;
+    use bytes::Bytes;
+    use futures::future::BoxFuture;
+    use futures::stream::BoxStream;
+
+    use super::{Encoding, ACCEPT_ENCODING_HEADER, CONTENT_TYPE_HEADER};
+    use crate::testing::MockLattice;
+    use crate::transport::{ControlTransport, TransportMessage};
+    use crate::{ClientBuilder, CtlResponse, Host, Result};
+
+    #[test]
+    fn negotiates_encoding() {
+        assert_eq!(Encoding::accepted(None), Encoding::Json);
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING_HEADER, "application/msgpack");
+        assert_eq!(Encoding::accepted(Some(&headers)), Encoding::MsgPack);
+        headers.insert(ACCEPT_ENCODING_HEADER, "application/cbor");
+        assert_eq!(Encoding::accepted(Some(&headers)), Encoding::Json);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(Encoding::of_reply(&headers).unwrap(), Encoding::Json);
+        Encoding::MsgPack.insert_reply_headers(&mut headers);
+        assert_eq!(Encoding::of_reply(&headers).unwrap(), Encoding::MsgPack);
+        headers.insert(CONTENT_TYPE_HEADER, "application/cbor");
+        assert!(Encoding::of_reply(&headers).is_err());
+    }
+
+    #[test]
+    fn transcodes_json_replies() {
+        let reply = CtlResponse::ok(vec![Host {
+            id: "host".into(),
+            ..Default::default()
+        }]);
+        let json = Encoding::Json.serialize(&reply).unwrap();
+        let msgpack = Encoding::MsgPack.from_json(json.into()).unwrap();
+        let decoded: CtlResponse<Vec<Host>> = Encoding::MsgPack.deserialize(&msgpack).unwrap();
+        assert_eq!(decoded.data().unwrap()[0].id(), "host");
+    }
+
+    /// Transport that drops request headers like a host that does not support negotiation
+    #[derive(Debug)]
+    struct LegacyHost(MockLattice);
+
+    impl ControlTransport for LegacyHost {
+        fn request(
+            &self,
+            subject: String,
+            _headers: HeaderMap,
+            payload: Bytes,
+            timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            self.0.request(subject, HeaderMap::new(), payload, timeout)
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            _headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.0.request_many(subject, HeaderMap::new(), payload)
+        }
+
+        fn publish(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            self.0.publish(subject, headers, payload)
+        }
+
+        fn subscribe(
+            &self,
+            subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.0.subscribe(subject)
+        }
+    }
+
+    #[tokio::test]
+    async fn msgpack_client_falls_back_to_json() -> Result<()> {
+        let lattice = MockLattice::default();
+        lattice.add_host(Host {
+            id: "host-a".into(),
+            ..Default::default()
+        });
+        let client = lattice.client_builder().encoding(Encoding::MsgPack).build();
+        let inventory = client.get_host_inventory("host-a").await?;
+        assert_eq!(inventory.data().expect("inventory").host_id(), "host-a");
+        assert_eq!(client.get_hosts().await?.len(), 1);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING_HEADER, Encoding::MsgPack.content_type());
+        let reply = lattice
+            .request(
+                "wasmbus.ctl.v1.default.host.get.host-a".into(),
+                headers,
+                Bytes::new(),
+                Duration::from_secs(1),
+            )
+            .await?;
+        assert_eq!(Encoding::of_reply(&reply.headers)?, Encoding::MsgPack);
+
+        let legacy = ClientBuilder::with_transport(LegacyHost(lattice))
+            .lattice("default")
+            .encoding(Encoding::MsgPack)
+            .build();
+        let inventory = legacy.get_host_inventory("host-a").await?;
+        assert_eq!(inventory.data().expect("inventory").host_id(), "host-a");
+        assert_eq!(legacy.get_hosts().await?.len(), 1);
+        Ok(())
+    }
+}
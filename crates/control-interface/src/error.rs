@@ -0,0 +1,53 @@
+//! Errors of host-targeted control interface operations that callers may want to handle
+//! specifically, e.g. to retry against another host.
+
+use core::fmt;
+
+/// Error returned when an operation targets a host that is not running in the lattice.
+///
+/// Requests to unknown hosts are detected from the no-responders status of the transport, so
+/// this error is returned immediately rather than after the request timed out. With
+/// [`ClientBuilder::verify_hosts`](crate::ClientBuilder::verify_hosts), hosts that stopped
+/// responding are detected before a command is sent as well.
+///
+/// Since the client returns boxed errors, callers can detect this case with
+/// [`downcast_ref`](std::error::Error::downcast_ref).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HostNotFound {
+    pub(crate) host_id: String,
+    pub(crate) operation: String,
+}
+
+impl HostNotFound {
+    pub(crate) fn new(host_id: impl Into<String>, operation: impl Into<String>) -> Self {
+        Self {
+            host_id: host_id.into(),
+            operation: operation.into(),
+        }
+    }
+
+    /// Get the ID of the host that was not found
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    /// Get the name of the operation that targeted the host
+    #[must_use]
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+}
+
+impl fmt::Display for HostNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "host {} was not found in the lattice, `{}` was not delivered",
+            self.host_id, self.operation
+        )
+    }
+}
+
+impl std::error::Error for HostNotFound {}
@@ -0,0 +1,299 @@
+//! Entry points for fuzzing the decoding of control interface messages.
+//!
+//! Each function decodes arbitrary bytes the way hosts decode control interface requests and
+//! clients decode replies and lattice events. Decoding must return an error for malformed input
+//! rather than panic, since a single malformed message must never take down a host or a client.
+//! These functions are used by the fuzz targets in `fuzz/` and by the property tests of this
+//! module, and are not part of the stable API of this crate.
+
+use std::collections::HashMap;
+
+use cloudevents::Event;
+use serde::de::DeserializeOwned;
+
+use crate::{
+    AliasTarget, CleanupHostDataCommand, ComponentAuctionAck, ComponentAuctionRequest,
+    ComponentProfile, ConfigNames, ConfigsByName, CtlResponse, DataDirUsage,
+    DeleteInterfaceLinkDefinitionRequest, DrainHostCommand, Encoding, EventStreamGap, Host,
+    HostInventory, HostInventoryPage, HostLabel, HostLabelIdentifier, HostLabelIdentifiers,
+    HostLabels, InventoryPageRequest, LatticeAlias, LatticeEvent, Link, PrefetchImagesCommand,
+    ProfileComponentCommand, ProviderAuctionAck, ProviderAuctionRequest, RegistryCredential,
+    ScaleComponentCommand, StartProviderCommand, StopComponentsCommand, StopHostCommand,
+    StopProviderCommand, TrafficSplit, UpdateComponentCommand, UpdateHostTracingCommand,
+    UpdateProviderConfigCommand,
+};
+
+/// Decode `data` as `T` with every encoding, discarding the result
+fn decode<T: DeserializeOwned>(data: &[u8]) {
+    let _ = Encoding::Json.deserialize::<T>(data);
+    let _ = Encoding::MsgPack.deserialize::<T>(data);
+}
+
+/// Decode `data` as every control interface request handled by hosts
+pub fn decode_requests(data: &[u8]) {
+    decode::<ScaleComponentCommand>(data);
+    decode::<UpdateComponentCommand>(data);
+    decode::<StopComponentsCommand>(data);
+    decode::<StartProviderCommand>(data);
+    decode::<StopProviderCommand>(data);
+    decode::<UpdateProviderConfigCommand>(data);
+    decode::<StopHostCommand>(data);
+    decode::<DrainHostCommand>(data);
+    decode::<PrefetchImagesCommand>(data);
+    decode::<CleanupHostDataCommand>(data);
+    decode::<UpdateHostTracingCommand>(data);
+    decode::<ProfileComponentCommand>(data);
+    decode::<ComponentAuctionRequest>(data);
+    decode::<ProviderAuctionRequest>(data);
+    decode::<InventoryPageRequest>(data);
+    decode::<ConfigNames>(data);
+    decode::<HostLabel>(data);
+    decode::<HostLabels>(data);
+    decode::<HostLabelIdentifier>(data);
+    decode::<HostLabelIdentifiers>(data);
+    decode::<Link>(data);
+    decode::<DeleteInterfaceLinkDefinitionRequest>(data);
+    decode::<HashMap<String, RegistryCredential>>(data);
+    decode::<HashMap<String, String>>(data);
+}
+
+/// Decode `data` as every control interface reply and lattice data entry decoded by clients
+pub fn decode_replies(data: &[u8]) {
+    decode::<CtlResponse<()>>(data);
+    decode::<CtlResponse<Host>>(data);
+    decode::<CtlResponse<HostInventory>>(data);
+    decode::<CtlResponse<HostInventoryPage>>(data);
+    decode::<CtlResponse<Vec<Link>>>(data);
+    decode::<CtlResponse<Vec<HashMap<String, String>>>>(data);
+    decode::<CtlResponse<ConfigsByName>>(data);
+    decode::<CtlResponse<HashMap<String, String>>>(data);
+    decode::<CtlResponse<ComponentAuctionAck>>(data);
+    decode::<CtlResponse<ProviderAuctionAck>>(data);
+    decode::<CtlResponse<ComponentProfile>>(data);
+    decode::<CtlResponse<DataDirUsage>>(data);
+    decode::<CtlResponse<Vec<String>>>(data);
+    decode::<LatticeAlias>(data);
+    decode::<AliasTarget>(data);
+    decode::<TrafficSplit>(data);
+}
+
+/// Decode `data` as a lattice event, and the event data as every kind of event data clients
+/// interpret
+pub fn decode_events(data: &[u8]) {
+    let Ok(event) = serde_json::from_slice::<Event>(data) else {
+        return;
+    };
+    let _ = EventStreamGap::from_event(&event);
+    let Ok(event) = LatticeEvent::try_from(event) else {
+        return;
+    };
+    let _ = event.field("component_id");
+    let _ = event.field("annotations.app");
+    let _ = event.data_as::<HostInventory>();
+    let _ = event.data_as::<Link>();
+    let _ = event.data_as::<EventStreamGap>();
+}
+
+/// Decode `data` with every entry point of this module
+pub fn decode_all(data: &[u8]) {
+    decode_requests(data);
+    decode_replies(data);
+    decode_events(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::decode_all;
+
+    /// Deterministic pseudo-random number generator, so that failures are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            // xorshift64*
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    const KEYS: &[&str] = &[
+        "host_id",
+        "component_id",
+        "component_ref",
+        "provider_id",
+        "provider_ref",
+        "max_instances",
+        "annotations",
+        "config",
+        "source_id",
+        "target",
+        "name",
+        "wit_namespace",
+        "wit_package",
+        "interfaces",
+        "labels",
+        "success",
+        "message",
+        "response",
+        "components",
+        "providers",
+        "cursor",
+        "limit",
+        "specversion",
+        "type",
+        "source",
+        "id",
+        "data",
+        "datacontenttype",
+    ];
+
+    /// Generate an arbitrary JSON value, biased towards the field names of control messages
+    fn arbitrary_json(rng: &mut Rng, depth: u32) -> Value {
+        match rng.below(if depth == 0 { 5 } else { 7 }) {
+            0 => Value::Null,
+            1 => Value::Bool(rng.below(2) == 0),
+            2 => match rng.below(4) {
+                0 => json!(rng.next()),
+                1 => json!(-(rng.below(1 << 40) as i64)),
+                2 => json!(f64::from(rng.below(1000) as u32) / 7.0),
+                _ => json!(u64::MAX),
+            },
+            3 => Value::String(KEYS[rng.below(KEYS.len() as u64) as usize].to_string()),
+            4 => Value::String("\u{0}\u{fffd}x".repeat(rng.below(4) as usize)),
+            5 => Value::Array(
+                (0..rng.below(4))
+                    .map(|_| arbitrary_json(rng, depth - 1))
+                    .collect(),
+            ),
+            _ => Value::Object(
+                (0..rng.below(6))
+                    .map(|_| {
+                        let key = KEYS[rng.below(KEYS.len() as u64) as usize].to_string();
+                        (key, arbitrary_json(rng, depth - 1))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Valid messages, which are truncated and mutated by the tests
+    fn valid_messages() -> Vec<Value> {
+        vec![
+            json!({
+                "component_ref": "ghcr.io/wasmcloud/components/http-hello-world-rust:0.1.0",
+                "component_id": "hello",
+                "host_id": "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC",
+                "max_instances": 10,
+                "annotations": {"app": "hello"},
+                "config": ["hello-config"],
+                "allow_update": true
+            }),
+            json!({
+                "source_id": "http",
+                "target": "hello",
+                "name": "default",
+                "wit_namespace": "wasi",
+                "wit_package": "http",
+                "interfaces": ["incoming-handler"],
+                "source_config": ["port"],
+                "target_config": []
+            }),
+            json!({
+                "success": true,
+                "message": "",
+                "response": {
+                    "components": [{"id": "hello", "image_ref": "hello.wasm", "max_instances": 1, "revision": 0}],
+                    "providers": [{"id": "http", "revision": 0}],
+                    "host_id": "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC",
+                    "labels": {"hostcore.os": "linux"},
+                    "version": "1.9.0",
+                    "uptime_seconds": 10
+                }
+            }),
+            json!({
+                "specversion": "1.0",
+                "type": "com.wasmcloud.lattice.component_scaled",
+                "source": "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC",
+                "id": "1",
+                "datacontenttype": "application/json",
+                "data": {"component_id": "hello", "max_instances": 1, "annotations": {"app": "hello"}}
+            }),
+        ]
+    }
+
+    /// Replace the value at a random position of `value` with `replacement`
+    fn replace_random(rng: &mut Rng, value: &mut Value, replacement: Value) {
+        let child = match value {
+            Value::Object(map) if !map.is_empty() && rng.below(3) != 0 => {
+                let index = rng.below(map.len() as u64) as usize;
+                map.values_mut().nth(index)
+            }
+            Value::Array(items) if !items.is_empty() && rng.below(3) != 0 => {
+                let index = rng.below(items.len() as u64) as usize;
+                items.get_mut(index)
+            }
+            _ => None,
+        };
+        match child {
+            Some(child) => replace_random(rng, child, replacement),
+            None => *value = replacement,
+        }
+    }
+
+    #[test]
+    fn arbitrary_json_does_not_panic() {
+        let mut rng = Rng(0x5eed);
+        for _ in 0..2000 {
+            let value = arbitrary_json(&mut rng, 4);
+            decode_all(&serde_json::to_vec(&value).unwrap());
+            decode_all(&rmp_serde::to_vec_named(&value).unwrap());
+        }
+    }
+
+    #[test]
+    fn truncated_payloads_do_not_panic() {
+        for message in valid_messages() {
+            for payload in [
+                serde_json::to_vec(&message).unwrap(),
+                rmp_serde::to_vec_named(&message).unwrap(),
+            ] {
+                for len in 0..payload.len() {
+                    decode_all(&payload[..len]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_types_do_not_panic() {
+        let mut rng = Rng(0xc0ffee);
+        for message in valid_messages() {
+            for _ in 0..500 {
+                let mut message = message.clone();
+                for _ in 0..=rng.below(3) {
+                    let replacement = arbitrary_json(&mut rng, 2);
+                    replace_random(&mut rng, &mut message, replacement);
+                }
+                decode_all(&serde_json::to_vec(&message).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn arbitrary_bytes_do_not_panic() {
+        let mut rng = Rng(0xdecaf);
+        for _ in 0..2000 {
+            let len = rng.below(64) as usize;
+            let bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+            decode_all(&bytes);
+        }
+    }
+}
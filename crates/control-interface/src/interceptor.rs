@@ -0,0 +1,183 @@
+//! Hooks for observing and modifying the messages a [`Client`](crate::Client) exchanges with
+//! hosts, e.g. for audit logging, custom authentication headers or metrics.
+//!
+//! Interceptors are registered with
+//! [`ClientBuilder::interceptor`](crate::ClientBuilder::interceptor) and run in the order they
+//! were registered.
+
+use core::fmt::Debug;
+
+use async_nats::HeaderMap;
+use bytes::Bytes;
+
+use crate::transport::TransportMessage;
+use crate::Result;
+
+/// An outgoing control interface message
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ControlRequest {
+    /// The subject the message is sent on
+    pub subject: String,
+    /// Headers sent along with the message
+    pub headers: HeaderMap,
+    /// The message payload
+    pub payload: Bytes,
+}
+
+impl ControlRequest {
+    /// Create a new [`ControlRequest`] without headers
+    #[must_use]
+    pub fn new(subject: impl Into<String>, payload: impl Into<Bytes>) -> Self {
+        Self {
+            subject: subject.into(),
+            headers: HeaderMap::new(),
+            payload: payload.into(),
+        }
+    }
+}
+
+/// Middleware for messages exchanged by a [`Client`](crate::Client).
+///
+/// Both methods do nothing by default, so implementations only need to override the hooks they
+/// are interested in.
+pub trait ControlInterceptor: Debug + Send + Sync + 'static {
+    /// Called before a request or message is sent. The subject, headers and payload may be
+    /// modified, and returning an error aborts the request with that error.
+    fn on_request(&self, request: &mut ControlRequest) -> Result<()> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Called for every reply received to a request, or with the error if the request failed.
+    ///
+    /// Requests that any number of hosts reply to, such as auctions, call this once per reply.
+    /// Messages published without waiting for a reply do not call this.
+    fn on_response(&self, request: &ControlRequest, response: &Result<TransportMessage>) {
+        let _ = (request, response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::sync::{Arc, Mutex};
+
+    use async_nats::HeaderMap;
+    use bytes::Bytes;
+    use futures::future::BoxFuture;
+    use futures::stream::{self, BoxStream};
+    use futures::{FutureExt as _, StreamExt as _};
+
+    use super::{ControlInterceptor, ControlRequest};
+    use crate::transport::{ControlTransport, TransportMessage};
+    use crate::{ClientBuilder, CtlResponse, Result, COMMAND_ID_HEADER};
+
+    /// Transport that only accepts requests carrying an `authorization` header
+    #[derive(Debug)]
+    struct AuthTransport;
+
+    impl AuthTransport {
+        fn reply(subject: String, headers: &HeaderMap) -> Result<TransportMessage> {
+            if headers.get("authorization").is_none() {
+                return Err("unauthorized".into());
+            }
+            let payload = serde_json::to_vec(&CtlResponse::ok(Vec::<()>::new()))?;
+            Ok(TransportMessage::new(subject, payload))
+        }
+    }
+
+    impl ControlTransport for AuthTransport {
+        fn request(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            _payload: Bytes,
+            _timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            async move { Self::reply(subject, &headers) }.boxed()
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            _payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            async move {
+                let reply = Self::reply(subject, &headers)?;
+                Ok(stream::iter([reply.clone(), reply]).boxed())
+            }
+            .boxed()
+        }
+
+        fn publish(
+            &self,
+            _subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            async { Ok(()) }.boxed()
+        }
+
+        fn subscribe(
+            &self,
+            _subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            async { Ok(stream::pending().boxed()) }.boxed()
+        }
+    }
+
+    /// Interceptor that authenticates requests and records the outcome of every reply
+    #[derive(Debug, Default)]
+    struct Audit(Arc<Mutex<Vec<(String, bool)>>>);
+
+    impl ControlInterceptor for Audit {
+        fn on_request(&self, request: &mut ControlRequest) -> Result<()> {
+            if request.subject.ends_with(".claims.get") {
+                return Err("claims are off limits".into());
+            }
+            let command_id = request.headers.get(COMMAND_ID_HEADER);
+            assert_eq!(command_id.map(|id| id.as_str().len()), Some(26));
+            request.headers.insert("authorization", "Bearer token");
+            Ok(())
+        }
+
+        fn on_response(&self, request: &ControlRequest, response: &Result<TransportMessage>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((request.subject.clone(), response.is_ok()));
+        }
+    }
+
+    #[tokio::test]
+    async fn interceptors_modify_requests_and_observe_responses() -> Result<()> {
+        let audit = Audit::default();
+        let log = Arc::clone(&audit.0);
+
+        let client = ClientBuilder::with_transport(AuthTransport).build();
+        assert!(client.get_links().await.is_err(), "no authorization header");
+
+        let client = ClientBuilder::with_transport(AuthTransport)
+            .interceptor(audit)
+            .build();
+        assert!(client.get_links().await?.succeeded());
+        assert_eq!(
+            client
+                .perform_component_auction("ghcr.io/wasmcloud/echo:0.1.0", "echo", [])
+                .await?
+                .len(),
+            2
+        );
+        let err = client.get_claims().await.unwrap_err();
+        assert!(err.to_string().contains("off limits"));
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), 3, "one link reply and two auction replies");
+        assert!(log.iter().all(|(_, ok)| *ok));
+        assert!(log[0].0.ends_with(".link.get"));
+        Ok(())
+    }
+}
@@ -20,19 +20,68 @@
 use serde::{Deserialize, Serialize};
 
 mod broker;
+pub use broker::{v1_subject, ProtocolVersion};
 mod otel;
+mod version;
+pub use version::UnsupportedByHost;
+mod error;
+pub use error::HostNotFound;
 
 pub mod client;
-pub use client::{Client, ClientBuilder};
+pub use client::{rank_auction_results, Client, ClientBuilder};
+
+pub mod cache;
+pub use cache::CachingClient;
+
+pub mod chunking;
+
+pub mod connect;
+
+pub mod encoding;
+pub use encoding::Encoding;
+
+#[doc(hidden)]
+pub mod fuzz;
+
+pub use connect::NatsConnectOptions;
+
+pub mod limits;
+pub use limits::{ReplyLimitKind, ReplyLimits, ReplyTooLarge};
+
+pub mod interceptor;
+pub use interceptor::{ControlInterceptor, ControlRequest};
+
+mod mux;
+
+pub mod multi_lattice;
+pub use multi_lattice::MultiLatticeClient;
+
+pub mod stats;
+pub use stats::{ClientStats, LatencyHistogram, OperationStats};
+
+pub mod testing;
+
+pub mod transport;
+pub use transport::{ControlTransport, NoResponders, TransportMessage};
 
 mod types;
+pub use types::annotations::*;
 pub use types::component::*;
+pub use types::config::*;
 pub use types::ctl::*;
+pub use types::epoch::*;
+pub use types::event::*;
 pub use types::host::*;
+pub use types::id::*;
+pub use types::label::*;
 pub use types::link::*;
+pub use types::naming::*;
+pub use types::profile::*;
 pub use types::provider::*;
 pub use types::registry::*;
+pub use types::route::*;
 pub use types::rpc::*;
+pub use types::snapshot::*;
 
 // NOTE(brooksmtownsend): These are included to avoid a major breaking change
 // in this crate by removing the public type aliases. They should be removed
@@ -71,6 +120,8 @@ pub(crate) enum IdentifierKind {
     ProviderRef,
     /// Identifiers that are link names
     LinkName,
+    /// Identifiers that are config names
+    ConfigName,
 }
 
 impl IdentifierKind {
@@ -103,6 +154,11 @@ impl IdentifierKind {
     fn is_link_name(value: impl AsRef<str>) -> Result<String> {
         assert_non_empty_string(value, "Link Name cannot be empty")
     }
+
+    /// Ensure an identifier is a valid as a config name
+    fn is_config_name(value: impl AsRef<str>) -> Result<String> {
+        assert_non_empty_string(value, "Config name cannot be empty")
+    }
 }
 
 /// Helper function that serializes the data and maps the error
@@ -0,0 +1,374 @@
+//! Limits on the size of control interface replies accepted by the client.
+//!
+//! Replies are decoded into memory in full, so a misbehaving host could make a client run out of
+//! memory, e.g. by replying with a gigantic inventory. [`ReplyLimits`] bound the size of every
+//! reply before it is decoded. Replies exceeding a limit fail with a [`ReplyTooLarge`] error
+//! instead.
+
+use core::fmt;
+
+use crate::encoding::Encoding;
+use crate::transport::TransportMessage;
+
+/// Limits on the size of control interface replies, set with
+/// [`ClientBuilder::reply_limits`](crate::ClientBuilder::reply_limits).
+///
+/// No limits are enforced by default.
+///
+/// ```rust
+/// use wasmcloud_control_interface::ReplyLimits;
+///
+/// let limits = ReplyLimits::default()
+///     .max_bytes(16 * 1024 * 1024)
+///     .max_entries(100_000);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ReplyLimits {
+    max_bytes: Option<usize>,
+    max_entries: Option<usize>,
+}
+
+impl ReplyLimits {
+    /// Limit the size of a reply payload in bytes. Chunked replies are limited by the total size
+    /// of their chunks
+    #[must_use]
+    pub fn max_bytes(self, max_bytes: usize) -> Self {
+        Self {
+            max_bytes: Some(max_bytes),
+            ..self
+        }
+    }
+
+    /// Limit the number of entries of every collection in a reply, e.g. the number of components
+    /// in a host inventory or the number of labels of a host
+    #[must_use]
+    pub fn max_entries(self, max_entries: usize) -> Self {
+        Self {
+            max_entries: Some(max_entries),
+            ..self
+        }
+    }
+
+    /// Get the maximum size of a reply payload in bytes, if limited
+    #[must_use]
+    pub fn get_max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    /// Get the maximum number of entries of every collection in a reply, if limited
+    #[must_use]
+    pub fn get_max_entries(&self) -> Option<usize> {
+        self.max_entries
+    }
+
+    /// Check that `size` bytes received so far in reply to `subject` do not exceed the limit
+    pub(crate) fn check_bytes(&self, subject: &str, size: usize) -> Result<(), ReplyTooLarge> {
+        match self.max_bytes {
+            Some(limit) if size > limit => Err(ReplyTooLarge {
+                subject: subject.to_string(),
+                kind: ReplyLimitKind::Bytes,
+                actual: size,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a reply to `subject` against all limits
+    pub(crate) fn check(&self, subject: &str, msg: &TransportMessage) -> Result<(), ReplyTooLarge> {
+        self.check_bytes(subject, msg.payload.len())?;
+        let Some(limit) = self.max_entries else {
+            return Ok(());
+        };
+        // Replies with an unknown encoding fail to decode later on
+        let Ok(encoding) = Encoding::of_reply(&msg.headers) else {
+            return Ok(());
+        };
+        let entries = match encoding {
+            Encoding::Json => json_entries_above(&msg.payload, limit),
+            Encoding::MsgPack => msgpack_entries_above(&msg.payload, limit),
+        };
+        match entries {
+            Some(actual) => Err(ReplyTooLarge {
+                subject: subject.to_string(),
+                kind: ReplyLimitKind::Entries,
+                actual,
+                limit,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The limit exceeded by a reply, see [`ReplyTooLarge`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ReplyLimitKind {
+    /// The reply payload exceeded [`ReplyLimits::max_bytes`]
+    Bytes,
+    /// A collection in the reply exceeded [`ReplyLimits::max_entries`]
+    Entries,
+}
+
+/// Error returned when a reply exceeds the [`ReplyLimits`] of the client.
+///
+/// Since the client returns boxed errors, callers can detect this case with
+/// [`downcast_ref`](std::error::Error::downcast_ref).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct ReplyTooLarge {
+    pub(crate) subject: String,
+    pub(crate) kind: ReplyLimitKind,
+    pub(crate) actual: usize,
+    pub(crate) limit: usize,
+}
+
+impl ReplyTooLarge {
+    /// Get the subject of the request whose reply exceeded the limit
+    #[must_use]
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Get the limit that was exceeded
+    #[must_use]
+    pub fn kind(&self) -> ReplyLimitKind {
+        self.kind
+    }
+
+    /// Get the size of the reply in bytes or the number of entries of the offending collection,
+    /// depending on the [`kind`](Self::kind) of limit. For chunked replies, this is the size at
+    /// which receiving further chunks was aborted
+    #[must_use]
+    pub fn actual(&self) -> usize {
+        self.actual
+    }
+
+    /// Get the value of the exceeded limit
+    #[must_use]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for ReplyTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ReplyLimitKind::Bytes => write!(
+                f,
+                "reply to `{}` has {} bytes, exceeding the limit of {} bytes",
+                self.subject, self.actual, self.limit
+            ),
+            ReplyLimitKind::Entries => write!(
+                f,
+                "reply to `{}` has a collection of {} entries, exceeding the limit of {} entries",
+                self.subject, self.actual, self.limit
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplyTooLarge {}
+
+/// Scan a JSON document without decoding it, returning the number of entries of the first array
+/// or object with more than `limit` entries
+fn json_entries_above(buf: &[u8], limit: usize) -> Option<usize> {
+    // Number of separators of every open collection
+    let mut open: Vec<usize> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for byte in buf {
+        if in_string {
+            match (escaped, byte) {
+                (true, _) => escaped = false,
+                (false, b'\\') => escaped = true,
+                (false, b'"') => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\r' | b':' => {}
+            b',' => {
+                if let Some(separators) = open.last_mut() {
+                    *separators += 1;
+                    if *separators >= limit {
+                        return Some(*separators + 1);
+                    }
+                }
+            }
+            b']' | b'}' => {
+                open.pop();
+            }
+            _ => {
+                // A value in a collection, which has at least one entry
+                if limit == 0 && !open.is_empty() {
+                    return Some(1);
+                }
+                match byte {
+                    b'[' | b'{' => open.push(0),
+                    b'"' => in_string = true,
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Scan a MessagePack document without decoding it, returning the number of entries of the first
+/// array or map with more than `limit` entries
+fn msgpack_entries_above(buf: &[u8], limit: usize) -> Option<usize> {
+    /// Read a big-endian length of `n` bytes at `pos`
+    fn len(buf: &[u8], pos: usize, n: usize) -> Option<usize> {
+        let bytes = buf.get(pos..pos.checked_add(n)?)?;
+        let len = bytes
+            .iter()
+            .fold(0u64, |len, byte| (len << 8) | u64::from(*byte));
+        usize::try_from(len).ok()
+    }
+
+    // Number of values left to read in every open collection
+    let mut open: Vec<usize> = vec![1];
+    let mut pos = 0;
+    while let Some(remaining) = open.last_mut() {
+        if *remaining == 0 {
+            open.pop();
+            continue;
+        }
+        *remaining -= 1;
+        let marker = *buf.get(pos)?;
+        pos += 1;
+        // Number of entries of a collection and the number of values it contains
+        let mut collection = None;
+        // Number of bytes following the marker and its length, if any
+        let skip = match marker {
+            0x00..=0x7f | 0xc0 | 0xc2 | 0xc3 | 0xe0..=0xff => 0,
+            0x80..=0x8f => {
+                let entries = usize::from(marker & 0x0f);
+                collection = Some((entries, entries * 2));
+                0
+            }
+            0x90..=0x9f => {
+                let entries = usize::from(marker & 0x0f);
+                collection = Some((entries, entries));
+                0
+            }
+            0xa0..=0xbf => usize::from(marker & 0x1f),
+            0xc4 | 0xd9 => 1 + len(buf, pos, 1)?,
+            0xc5 | 0xda => 2 + len(buf, pos, 2)?,
+            0xc6 | 0xdb => 4usize.checked_add(len(buf, pos, 4)?)?,
+            0xc7 => 2 + len(buf, pos, 1)?,
+            0xc8 => 3 + len(buf, pos, 2)?,
+            0xc9 => 5usize.checked_add(len(buf, pos, 4)?)?,
+            0xcc | 0xd0 => 1,
+            0xcd | 0xd1 => 2,
+            0xca | 0xce | 0xd2 => 4,
+            0xcb | 0xcf | 0xd3 => 8,
+            0xd4 => 2,
+            0xd5 => 3,
+            0xd6 => 5,
+            0xd7 => 9,
+            0xd8 => 17,
+            0xdc | 0xde => {
+                let entries = len(buf, pos, 2)?;
+                let values = if marker == 0xde { entries * 2 } else { entries };
+                collection = Some((entries, values));
+                2
+            }
+            0xdd | 0xdf => {
+                let entries = len(buf, pos, 4)?;
+                let values = if marker == 0xdf {
+                    entries.saturating_mul(2)
+                } else {
+                    entries
+                };
+                collection = Some((entries, values));
+                4
+            }
+            // 0xc1 is never used
+            _ => return None,
+        };
+        pos = pos.checked_add(skip)?;
+        if let Some((entries, values)) = collection {
+            if entries > limit {
+                return Some(entries);
+            }
+            open.push(values);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use async_nats::HeaderMap;
+    use serde_json::json;
+
+    use super::{json_entries_above, msgpack_entries_above, ReplyLimitKind, ReplyLimits};
+    use crate::encoding::{Encoding, CONTENT_TYPE_HEADER};
+    use crate::transport::TransportMessage;
+
+    #[test]
+    fn counts_collection_entries() {
+        let values = [
+            json!([]),
+            json!({}),
+            json!([1, 2, 3]),
+            json!({"a": "x,y,z", "b": [1, 2], "c": {"d": [[], [1]]}}),
+            json!({"components": (0..10).collect::<Vec<_>>(), "s": "\"[,,,]\""}),
+        ];
+        for (value, max) in values.iter().zip([0, 0, 3, 3, 10]) {
+            let json = serde_json::to_vec(value).unwrap();
+            let msgpack = rmp_serde::to_vec_named(value).unwrap();
+            assert_eq!(json_entries_above(&json, max), None, "{value}");
+            assert_eq!(msgpack_entries_above(&msgpack, max), None, "{value}");
+            if max > 0 {
+                assert!(json_entries_above(&json, max - 1).is_some(), "{value}");
+                assert!(
+                    msgpack_entries_above(&msgpack, max - 1).is_some(),
+                    "{value}"
+                );
+            }
+        }
+        // Large MessagePack collections are detected from their declared length
+        let huge = [0xdd, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(msgpack_entries_above(&huge, 1000), Some(u32::MAX as usize));
+    }
+
+    #[test]
+    fn checks_reply_limits() {
+        let payload = serde_json::to_vec(&json!({"labels": {"a": "1", "b": "2"}})).unwrap();
+        let msg = TransportMessage::new("reply", payload.clone());
+        assert!(ReplyLimits::default().check("subject", &msg).is_ok());
+
+        let err = ReplyLimits::default()
+            .max_bytes(10)
+            .check("subject", &msg)
+            .unwrap_err();
+        assert_eq!(err.subject(), "subject");
+        assert_eq!(err.kind(), ReplyLimitKind::Bytes);
+        assert_eq!(err.actual(), payload.len());
+
+        let err = ReplyLimits::default()
+            .max_entries(1)
+            .check("subject", &msg)
+            .unwrap_err();
+        assert_eq!(err.kind(), ReplyLimitKind::Entries);
+        assert_eq!(err.actual(), 2);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE_HEADER, Encoding::MsgPack.content_type());
+        let msgpack = Encoding::MsgPack.from_json(payload.into()).unwrap();
+        let msg = TransportMessage::new("reply", msgpack).with_headers(headers);
+        assert!(ReplyLimits::default()
+            .max_entries(2)
+            .check("subject", &msg)
+            .is_ok());
+        assert!(ReplyLimits::default()
+            .max_entries(1)
+            .check("subject", &msg)
+            .is_err());
+    }
+}
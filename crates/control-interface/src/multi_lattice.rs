@@ -0,0 +1,237 @@
+//! A client manager for operating many lattices over a single connection.
+//!
+//! [`MultiLatticeClient`] keeps one [`Client`] per lattice, all built from the same
+//! [`ClientBuilder`] and therefore sharing its NATS connection (or transport), timeouts and
+//! interceptors. Operations can be routed to a single lattice by name, or fanned out to all of
+//! them concurrently.
+
+use core::future::Future;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{Client, ClientBuilder, CtlResponse, Host, Result};
+
+/// Manager of control interface clients for multiple lattices
+#[derive(Clone, Debug)]
+pub struct MultiLatticeClient {
+    /// Builder used to create the client of every lattice
+    builder: ClientBuilder,
+    /// Clients keyed by lattice name
+    clients: Arc<RwLock<BTreeMap<String, Client>>>,
+}
+
+impl MultiLatticeClient {
+    /// Create a manager without any lattices, whose clients use the given NATS connection and
+    /// default settings
+    #[must_use]
+    pub fn new(nc: async_nats::Client) -> Self {
+        Self::from_builder(ClientBuilder::new(nc))
+    }
+
+    /// Create a manager without any lattices, whose clients are built from `builder`. The lattice
+    /// set on the builder is ignored.
+    #[must_use]
+    pub fn from_builder(builder: ClientBuilder) -> Self {
+        Self {
+            builder,
+            clients: Arc::default(),
+        }
+    }
+
+    /// Add the given lattices, see [`MultiLatticeClient::add_lattice`]
+    #[must_use]
+    pub fn with_lattices(self, lattices: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        for lattice in lattices {
+            self.add_lattice(lattice);
+        }
+        self
+    }
+
+    /// Add a lattice, returning its client. If the lattice was already added, its existing client
+    /// is returned.
+    pub fn add_lattice(&self, lattice: impl Into<String>) -> Client {
+        let lattice = lattice.into();
+        self.clients
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(lattice.clone())
+            .or_insert_with(|| self.builder.clone().lattice(lattice).build())
+            .clone()
+    }
+
+    /// Remove a lattice, returning its client if it was added
+    pub fn remove_lattice(&self, lattice: &str) -> Option<Client> {
+        self.clients
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(lattice)
+    }
+
+    /// Get the client of the given lattice, if it was added
+    #[must_use]
+    pub fn lattice(&self, lattice: &str) -> Option<Client> {
+        self.clients
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(lattice)
+            .cloned()
+    }
+
+    /// Get the client of the given lattice, returning an error if it was not added
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lattice was not added to this manager
+    pub fn route(&self, lattice: &str) -> Result<Client> {
+        self.lattice(lattice)
+            .ok_or_else(|| format!("lattice [{lattice}] is not managed by this client").into())
+    }
+
+    /// Get the names of all managed lattices, in sorted order
+    #[must_use]
+    pub fn lattices(&self) -> Vec<String> {
+        self.clients
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Run `op` against the client of every managed lattice concurrently, returning the result
+    /// for each lattice. A failure in one lattice does not affect the others.
+    pub async fn for_each_lattice<T, F, Fut>(&self, op: F) -> BTreeMap<String, Result<T>>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let clients: Vec<_> = self
+            .clients
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(lattice, client)| (lattice.clone(), client.clone()))
+            .collect();
+        let results = futures::future::join_all(clients.into_iter().map(|(lattice, client)| {
+            let fut = op(client);
+            async move { (lattice, fut.await) }
+        }))
+        .await;
+        results.into_iter().collect()
+    }
+
+    /// Query every managed lattice for its responsive hosts, see [`Client::get_hosts`]
+    pub async fn get_hosts_all_lattices(&self) -> BTreeMap<String, Result<Vec<CtlResponse<Host>>>> {
+        self.for_each_lattice(|client| async move { client.get_hosts().await })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::collections::BTreeMap;
+
+    use async_nats::HeaderMap;
+    use bytes::Bytes;
+    use futures::future::BoxFuture;
+    use futures::stream::BoxStream;
+
+    use super::MultiLatticeClient;
+    use crate::testing::MockLattice;
+    use crate::transport::{ControlTransport, TransportMessage};
+    use crate::{ClientBuilder, Result};
+
+    /// Transport routing requests to the mock lattice named in their subject
+    #[derive(Debug)]
+    struct Lattices(Vec<(&'static str, MockLattice)>);
+
+    impl Lattices {
+        fn route(&self, subject: &str) -> &MockLattice {
+            self.0
+                .iter()
+                .find(|(name, _)| subject.contains(&format!(".{name}.")))
+                .map_or(&self.0[0].1, |(_, lattice)| lattice)
+        }
+    }
+
+    impl ControlTransport for Lattices {
+        fn request(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+            timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            self.route(&subject)
+                .request(subject, headers, payload, timeout)
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.route(&subject).request_many(subject, headers, payload)
+        }
+
+        fn publish(
+            &self,
+            subject: String,
+            headers: HeaderMap,
+            payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            self.route(&subject).publish(subject, headers, payload)
+        }
+
+        fn subscribe(
+            &self,
+            subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            self.route(&subject).subscribe(subject)
+        }
+    }
+
+    #[tokio::test]
+    async fn fans_out_and_routes_by_lattice() -> Result<()> {
+        let dev = MockLattice::new("dev");
+        dev.add_host_with_labels("NDEV1", BTreeMap::new());
+        let prod = MockLattice::new("prod");
+        prod.add_host_with_labels("NPROD1", BTreeMap::new());
+        prod.add_host_with_labels("NPROD2", BTreeMap::new());
+
+        let clients =
+            MultiLatticeClient::from_builder(ClientBuilder::with_transport(Lattices(vec![
+                ("dev", dev),
+                ("prod", prod.clone()),
+            ])))
+            .with_lattices(["prod", "dev"]);
+        assert_eq!(clients.lattices(), ["dev", "prod"]);
+
+        let hosts = clients.get_hosts_all_lattices().await;
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts["dev"].as_ref().map(Vec::len).ok(), Some(1));
+        assert_eq!(hosts["prod"].as_ref().map(Vec::len).ok(), Some(2));
+
+        let client = clients.route("prod")?;
+        assert_eq!(client.lattice(), "prod");
+        client.put_label("NPROD1", "zone", "a").await?;
+        assert!(prod
+            .requests()
+            .iter()
+            .any(|s| s.contains(".prod.label.put")));
+        assert!(clients.route("staging").is_err());
+
+        // Lattices without responders fail without affecting the others
+        clients.add_lattice("staging");
+        let hosts = clients.get_hosts_all_lattices().await;
+        assert!(hosts["dev"].is_ok());
+        assert!(hosts["staging"].is_err());
+        assert!(clients.remove_lattice("staging").is_some());
+        assert_eq!(clients.lattices().len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//! Sharing of event subscriptions between clients.
+//!
+//! Every [`Client`](crate::Client) built from the same [`ClientBuilder`](crate::ClientBuilder),
+//! and every clone of such a client, subscribes to lattice events through a single
+//! [`SubscriptionMux`]. The first subscriber of a subject creates the transport subscription, and
+//! later subscribers of the same subject receive copies of its messages. The transport
+//! subscription is dropped once its last subscriber is dropped, so the number of subscriptions
+//! held by an application does not grow with the number of clients it creates.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+
+use futures::stream::BoxStream;
+use futures::StreamExt as _;
+use tokio::sync::{broadcast, Mutex};
+use tokio_util::sync::{CancellationToken, DropGuard};
+use tracing::{debug, warn};
+
+use crate::transport::{ControlTransport, TransportMessage};
+use crate::Result;
+
+/// Number of messages buffered for each subscriber before it lags behind and its stream ends
+const SUBSCRIBER_CAPACITY: usize = 5000;
+
+/// Reference-counted multiplexer of transport subscriptions, keyed by subject
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SubscriptionMux {
+    subjects: Arc<Mutex<HashMap<String, Weak<SharedSubscription>>>>,
+}
+
+/// A transport subscription shared by all subscribers of a subject
+#[derive(Debug)]
+struct SharedSubscription {
+    sender: broadcast::Sender<TransportMessage>,
+    /// Cancelled once the transport subscription ended, after which it can no longer be shared
+    ended: CancellationToken,
+    /// Stops forwarding messages, and with it the transport subscription, once the last
+    /// subscriber is dropped
+    _forwarding: DropGuard,
+}
+
+impl SubscriptionMux {
+    /// Subscribe to `subject`, sharing the transport subscription with all other subscribers of
+    /// the subject.
+    ///
+    /// The returned stream ends when the transport subscription ends, or when the subscriber lags
+    /// too far behind and messages were dropped, so that callers can resubscribe and signal the
+    /// gap in the same way.
+    pub(crate) async fn subscribe(
+        &self,
+        transport: &dyn ControlTransport,
+        subject: String,
+    ) -> Result<BoxStream<'static, TransportMessage>> {
+        let mut subjects = self.subjects.lock().await;
+        subjects.retain(|_, shared| shared.strong_count() > 0);
+        let shared = match subjects.get(&subject).and_then(Weak::upgrade) {
+            Some(shared) if !shared.ended.is_cancelled() => shared,
+            _ => {
+                let stream = transport.subscribe(subject.clone()).await?;
+                let shared = Arc::new(SharedSubscription::new(subject.clone(), stream));
+                subjects.insert(subject, Arc::downgrade(&shared));
+                shared
+            }
+        };
+        drop(subjects);
+
+        let receiver = shared.sender.subscribe();
+        Ok(
+            futures::stream::unfold((shared, receiver), |(shared, mut receiver)| async move {
+                // Deliver all messages received before the transport subscription ended
+                let msg = tokio::select! {
+                    biased;
+                    msg = receiver.recv() => msg,
+                    () = shared.ended.cancelled() => return None,
+                };
+                match msg {
+                    Ok(msg) => Some((msg, (shared, receiver))),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(skipped, "subscriber lagged behind shared subscription");
+                        None
+                    }
+                    Err(broadcast::error::RecvError::Closed) => None,
+                }
+            })
+            .boxed(),
+        )
+    }
+}
+
+impl SharedSubscription {
+    /// Start forwarding the messages of `stream` to all subscribers
+    fn new(subject: String, mut stream: BoxStream<'static, TransportMessage>) -> Self {
+        let (sender, _) = broadcast::channel(SUBSCRIBER_CAPACITY);
+        let ended = CancellationToken::new();
+        let forwarding = CancellationToken::new();
+        tokio::spawn({
+            let sender = sender.clone();
+            let ended = ended.clone();
+            let forwarding = forwarding.clone();
+            async move {
+                loop {
+                    tokio::select! {
+                        msg = stream.next() => {
+                            let Some(msg) = msg else {
+                                debug!(subject, "shared subscription ended");
+                                break;
+                            };
+                            // Sending only fails if there are no subscribers at the moment
+                            let _ = sender.send(msg);
+                        }
+                        () = forwarding.cancelled() => break,
+                    }
+                }
+                ended.cancel();
+            }
+        });
+        Self {
+            sender,
+            ended,
+            _forwarding: forwarding.drop_guard(),
+        }
+    }
+}
@@ -7,7 +7,8 @@
 use std::str::FromStr;
 
 use async_nats::header::{HeaderMap, HeaderName, HeaderValue};
-use opentelemetry::propagation::{Injector, TextMapPropagator};
+use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use opentelemetry::trace::{SpanContext, TraceContextExt as _};
 use opentelemetry_sdk::propagation::TraceContextPropagator;
 use tracing::span::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
@@ -88,3 +89,41 @@ impl From<HeaderInjector> for HeaderMap {
         inj.inner
     }
 }
+
+/// A convenience type that wraps a NATS [`HeaderMap`] and implements the [`Extractor`] trait
+#[derive(Debug)]
+pub(crate) struct HeaderExtractor<'a> {
+    inner: &'a HeaderMap,
+}
+
+impl<'a> HeaderExtractor<'a> {
+    /// Creates a new extractor using the given [`HeaderMap`]
+    pub(crate) fn new(headers: &'a HeaderMap) -> Self {
+        HeaderExtractor { inner: headers }
+    }
+
+    /// Extracts the span context propagated in the headers, if any
+    pub(crate) fn extract_span_context(&self) -> Option<SpanContext> {
+        let ctx_propagator = TraceContextPropagator::new();
+        let span_context = ctx_propagator.extract(self).span().span_context().clone();
+        span_context.is_valid().then_some(span_context)
+    }
+}
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.inner.get(key).map(HeaderValue::as_str)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.inner.iter().map(|(k, _)| k.as_ref()).collect()
+    }
+}
+
+/// Links the given span to the span that processed a message on the remote side, as propagated
+/// in the headers of the message, e.g. the host span that handled a control interface request
+pub(crate) fn link_remote_context(span: &Span, headers: &HeaderMap) {
+    if let Some(span_context) = HeaderExtractor::new(headers).extract_span_context() {
+        span.add_link(span_context);
+    }
+}
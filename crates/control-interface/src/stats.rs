@@ -0,0 +1,291 @@
+//! Statistics about the control interface operations performed by a [`Client`](crate::Client).
+//!
+//! Every request sent by a client is recorded under the name of its operation, which is the
+//! control interface subject with the lattice prefix and any host ID or config name removed, e.g.
+//! `component.auction` or `host.get`. A snapshot of the statistics can be obtained at any time
+//! with [`Client::stats`](crate::Client::stats), e.g. to export them as metrics.
+
+use core::time::Duration;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+const LATENCY_BUCKETS_MS: [u64; 12] =
+    [1, 5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
+/// Snapshot of the statistics of a [`Client`](crate::Client)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ClientStats {
+    pub(crate) operations: BTreeMap<String, OperationStats>,
+}
+
+impl ClientStats {
+    /// Get the statistics of all operations that were performed, keyed by operation name
+    #[must_use]
+    pub fn operations(&self) -> &BTreeMap<String, OperationStats> {
+        &self.operations
+    }
+
+    /// Get the statistics of a single operation, e.g. `component.auction`
+    #[must_use]
+    pub fn operation(&self, name: &str) -> Option<&OperationStats> {
+        self.operations.get(name)
+    }
+
+    /// Total number of requests sent across all operations
+    #[must_use]
+    pub fn requests(&self) -> u64 {
+        self.operations.values().map(|op| op.requests).sum()
+    }
+
+    /// Total number of failed requests across all operations, including timeouts
+    #[must_use]
+    pub fn failures(&self) -> u64 {
+        self.operations.values().map(|op| op.failures).sum()
+    }
+
+    /// Total number of timed out requests across all operations
+    #[must_use]
+    pub fn timeouts(&self) -> u64 {
+        self.operations.values().map(|op| op.timeouts).sum()
+    }
+}
+
+/// Statistics of a single control interface operation
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OperationStats {
+    pub(crate) requests: u64,
+    pub(crate) responses: u64,
+    pub(crate) failures: u64,
+    pub(crate) timeouts: u64,
+    pub(crate) latency: LatencyHistogram,
+}
+
+impl OperationStats {
+    /// Number of requests sent
+    #[must_use]
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Number of responses received. Auctions and other requests answered by multiple hosts count
+    /// every reply.
+    #[must_use]
+    pub fn responses(&self) -> u64 {
+        self.responses
+    }
+
+    /// Number of requests that failed, including timeouts
+    #[must_use]
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// Number of requests that timed out waiting for a reply
+    #[must_use]
+    pub fn timeouts(&self) -> u64 {
+        self.timeouts
+    }
+
+    /// Distribution of request durations. For auctions this is the time spent collecting replies.
+    #[must_use]
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+}
+
+/// Histogram of request durations with fixed buckets ranging from 1ms to 10s
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// Number of observations per bucket, with a final bucket for durations above the largest
+    /// bound
+    counts: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    count: u64,
+    sum: Duration,
+    max: Duration,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; LATENCY_BUCKETS_MS.len() + 1],
+            count: 0,
+            sum: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl LatencyHistogram {
+    pub(crate) fn observe(&mut self, duration: Duration) {
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| duration <= Duration::from_millis(*bound))
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += duration;
+        self.max = self.max.max(duration);
+    }
+
+    /// Number of observed durations
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Sum of all observed durations
+    #[must_use]
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    /// Largest observed duration
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Mean of all observed durations, if any were observed
+    #[must_use]
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count).ok().filter(|count| *count > 0)?;
+        Some(self.sum / count)
+    }
+
+    /// Iterate over the buckets of the histogram as pairs of their inclusive upper bound and the
+    /// number of durations in the bucket. The last bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS_MS
+            .iter()
+            .map(|bound| Some(Duration::from_millis(*bound)))
+            .chain([None])
+            .zip(self.counts.iter().copied())
+    }
+
+    /// Estimate the given quantile (between `0.0` and `1.0`) as the upper bound of the bucket it
+    /// falls into. Durations above the largest bucket are estimated as the maximum observed
+    /// duration.
+    #[must_use]
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let rank = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bound, count) in self.buckets() {
+            seen += count;
+            if seen >= rank {
+                return Some(bound.map_or(self.max, |bound| bound.min(self.max)));
+            }
+        }
+        Some(self.max)
+    }
+}
+
+/// Outcome of a request recorded in [`StatsRecorder`]
+pub(crate) enum Outcome {
+    /// The request succeeded with the given number of responses
+    Responses(u64),
+    Failed,
+    TimedOut,
+}
+
+/// Shared recorder of operation statistics, cloned along with the client
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StatsRecorder(Arc<Mutex<BTreeMap<String, OperationStats>>>);
+
+impl StatsRecorder {
+    pub(crate) fn record(&self, operation: &str, duration: Duration, outcome: Outcome) {
+        let mut operations = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = operations.entry(operation.to_string()).or_default();
+        stats.requests += 1;
+        stats.latency.observe(duration);
+        match outcome {
+            Outcome::Responses(n) => stats.responses += n,
+            Outcome::Failed => stats.failures += 1,
+            Outcome::TimedOut => {
+                stats.failures += 1;
+                stats.timeouts += 1;
+            }
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> ClientStats {
+        ClientStats {
+            operations: self
+                .0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+}
+
+/// Returns whether a transport error was caused by a request timing out
+pub(crate) fn is_timeout(error: &(dyn std::error::Error + 'static)) -> bool {
+    if let Some(error) = error.downcast_ref::<std::io::Error>() {
+        return error.kind() == std::io::ErrorKind::TimedOut;
+    }
+    if let Some(error) = error.downcast_ref::<async_nats::RequestError>() {
+        return error.kind() == async_nats::RequestErrorKind::TimedOut;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::collections::BTreeMap;
+
+    use super::{is_timeout, LatencyHistogram};
+    use crate::testing::MockLattice;
+    use crate::Result;
+
+    #[tokio::test]
+    async fn client_records_operation_stats() -> Result<()> {
+        let lattice = MockLattice::new("default");
+        lattice.add_host_with_labels("NHOST1", BTreeMap::new());
+        lattice.add_host_with_labels("NHOST2", BTreeMap::new());
+        let client = lattice.client();
+
+        assert_eq!(client.get_hosts().await?.len(), 2);
+        client.get_links().await?;
+        client.get_links().await?;
+
+        let stats = client.clone().stats();
+        assert_eq!(stats.requests(), 3);
+        assert_eq!(stats.failures(), 0);
+        let ping = stats.operation("host.ping").expect("host.ping recorded");
+        assert_eq!((ping.requests(), ping.responses()), (1, 2));
+        let links = stats.operation("link.get").expect("link.get recorded");
+        assert_eq!(links.requests(), 2);
+        assert_eq!(links.latency().count(), 2);
+
+        let timeout = std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out");
+        assert!(is_timeout(&timeout));
+        assert!(!is_timeout(&std::io::Error::other("failed")));
+        Ok(())
+    }
+
+    #[test]
+    fn latency_histogram_quantiles() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.quantile(0.5), None);
+        for ms in [3, 4, 20, 20, 20, 30, 40, 90, 200, 15_000] {
+            histogram.observe(Duration::from_millis(ms));
+        }
+        assert_eq!(histogram.count(), 10);
+        assert_eq!(histogram.mean(), Some(Duration::from_micros(1_542_700)));
+        assert_eq!(histogram.quantile(0.1), Some(Duration::from_millis(5)));
+        assert_eq!(histogram.quantile(0.5), Some(Duration::from_millis(25)));
+        assert_eq!(histogram.quantile(0.9), Some(Duration::from_millis(250)));
+        assert_eq!(histogram.quantile(1.0), Some(Duration::from_millis(15_000)));
+        assert_eq!(histogram.buckets().last(), Some((None, 1)));
+    }
+}
@@ -0,0 +1,1189 @@
+//! Utilities for testing code built on top of the control interface [`Client`] without a NATS
+//! server or a wasmCloud host.
+//!
+//! [`MockLattice`] implements the host side of the control interface protocol in-process, keeping
+//! hosts, their inventories, links and named configuration in memory. Commands sent by a client
+//! built with [`MockLattice::client`] are applied to that state, and the corresponding lattice
+//! events are published to subscribers of [`Client::events_receiver`].
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! use std::collections::BTreeMap;
+//!
+//! use wasmcloud_control_interface::testing::MockLattice;
+//!
+//! let lattice = MockLattice::default();
+//! lattice.add_host_with_labels("NHOST", BTreeMap::new());
+//!
+//! let client = lattice.client();
+//! let ack = client
+//!     .scale_component("NHOST", "ghcr.io/wasmcloud/components/echo:0.1.0", "echo", 1, None, vec![])
+//!     .await?;
+//! assert!(ack.succeeded());
+//!
+//! let inventory = lattice.inventory("NHOST").expect("host exists");
+//! assert_eq!(inventory.components().len(), 1);
+//! # Ok(())
+//! # }
+//! ```
+
+use core::time::Duration;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use async_nats::HeaderMap;
+use bytes::Bytes;
+use cloudevents::{EventBuilder as _, EventBuilderV10};
+use futures::channel::mpsc;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream};
+use futures::{FutureExt as _, StreamExt as _};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::broker::{self, ProtocolVersion};
+use crate::encoding::Encoding;
+use crate::transport::{ControlTransport, NoResponders, TransportMessage};
+use crate::{
+    json_deserialize, json_serialize, top_memory_consumers, AuctionHints, CleanupHostDataCommand,
+    Client, ClientBuilder, ComponentAuctionAck, ComponentAuctionRequest, ComponentDescription,
+    ComponentMemoryUsage, ComponentProfile, ConfigNames, ConfigsByName, CtlResponse,
+    DeleteInterfaceLinkDefinitionRequest, DrainHostCommand, Host, HostInventory, HostLabel,
+    HostLabelIdentifier, HostLabelIdentifiers, HostLabels, InventoryPageRequest, Link,
+    PrefetchImagesCommand, PrefetchStatus, PrefetchedImage, ProfileComponentCommand,
+    ProviderAuctionAck, ProviderAuctionRequest, ProviderDescription, PutLinkRequest, Result,
+    ScaleComponentCommand, StartProviderCommand, StopComponentsCommand, StopHostCommand,
+    StopProviderCommand, TopMemoryQuery, UpdateComponentCommand, UpdateHostTracingCommand,
+    UpdateProviderConfigCommand,
+};
+
+/// Lattice used by [`MockLattice::default`]
+const DEFAULT_LATTICE: &str = "default";
+
+/// An in-memory lattice of hosts that responds to control interface requests.
+///
+/// Cloning a [`MockLattice`] returns a handle to the same lattice.
+#[derive(Clone, Debug)]
+pub struct MockLattice {
+    lattice: Arc<str>,
+    state: Arc<Mutex<LatticeState>>,
+}
+
+#[derive(Debug, Default)]
+struct LatticeState {
+    hosts: BTreeMap<String, MockHost>,
+    links: Vec<Link>,
+    configs: BTreeMap<String, HashMap<String, String>>,
+    /// Host-scoped configs, keyed by host ID and config name
+    host_configs: BTreeMap<(String, String), HashMap<String, String>>,
+    claims: Vec<HashMap<String, String>>,
+    /// Revision of the lattice KV buckets, incremented on every change to links or configs
+    revision: u64,
+    requests: Vec<String>,
+    subscribers: Vec<(String, mpsc::UnboundedSender<TransportMessage>)>,
+}
+
+#[derive(Debug)]
+struct MockHost {
+    host: Host,
+    components: BTreeMap<String, ComponentDescription>,
+    providers: BTreeMap<String, ProviderDescription>,
+    prefetched_images: BTreeMap<String, PrefetchedImage>,
+}
+
+impl MockHost {
+    fn inventory(&self) -> HostInventory {
+        HostInventory {
+            components: self.components.values().cloned().collect(),
+            providers: self.providers.values().cloned().collect(),
+            host_id: self.host.id.clone(),
+            friendly_name: self.host.friendly_name.clone(),
+            labels: self.host.labels.clone(),
+            version: self.host.version.clone().unwrap_or_default(),
+            uptime_human: self.host.uptime_human.clone().unwrap_or_default(),
+            uptime_seconds: self.host.uptime_seconds,
+            prefetched_images: self.prefetched_images.values().cloned().collect(),
+            data_dir: None,
+        }
+    }
+
+    fn auction_hints(&self) -> AuctionHints {
+        AuctionHints {
+            component_count: Some(self.components.len() as u64),
+            provider_count: Some(self.providers.len() as u64),
+            free_memory_bytes: None,
+            uptime_seconds: Some(self.host.uptime_seconds),
+            labels: self.host.labels.clone(),
+        }
+    }
+}
+
+/// Result of handling a single control interface request
+enum Reply {
+    /// A single reply
+    One(Vec<u8>),
+    /// Replies from every host that responded
+    Many(Vec<Vec<u8>>),
+}
+
+impl Default for MockLattice {
+    fn default() -> Self {
+        Self::new(DEFAULT_LATTICE)
+    }
+}
+
+impl MockLattice {
+    /// Create an empty lattice with the given name
+    #[must_use]
+    pub fn new(lattice: impl Into<String>) -> Self {
+        Self {
+            lattice: Arc::from(lattice.into()),
+            state: Arc::default(),
+        }
+    }
+
+    /// Build a control interface [`Client`] connected to this lattice
+    #[must_use]
+    pub fn client(&self) -> Client {
+        self.client_builder().build()
+    }
+
+    /// Get a [`ClientBuilder`] connected to this lattice, for clients that need custom settings.
+    ///
+    /// Replies to host listings and auctions are complete once returned, so clients do not need to
+    /// wait for the auction timeout.
+    #[must_use]
+    pub fn client_builder(&self) -> ClientBuilder {
+        ClientBuilder::with_transport(self.clone())
+            .lattice(self.lattice.to_string())
+            .timeout(Duration::from_secs(1))
+    }
+
+    /// Add a host to the lattice, replacing any host with the same ID and its inventory
+    pub fn add_host(&self, mut host: Host) {
+        host.lattice = self.lattice.to_string();
+        let id = host.id.clone();
+        self.state().hosts.insert(
+            id,
+            MockHost {
+                host,
+                components: BTreeMap::new(),
+                providers: BTreeMap::new(),
+                prefetched_images: BTreeMap::new(),
+            },
+        );
+    }
+
+    /// Add a host with the given ID and labels to the lattice, replacing any host with the same ID
+    /// and its inventory
+    pub fn add_host_with_labels(&self, host_id: &str, labels: BTreeMap<String, String>) {
+        self.add_host(Host {
+            id: host_id.to_string(),
+            friendly_name: host_id.to_string(),
+            labels,
+            ..Default::default()
+        });
+    }
+
+    /// Remove a host from the lattice, as if it had stopped without notice. Returns true if the
+    /// host existed
+    pub fn remove_host(&self, host_id: &str) -> bool {
+        self.state().hosts.remove(host_id).is_some()
+    }
+
+    /// Set the claims returned by [`Client::get_claims`]
+    pub fn set_claims(&self, claims: Vec<HashMap<String, String>>) {
+        self.state().claims = claims;
+    }
+
+    /// Get all hosts in the lattice, ordered by ID
+    #[must_use]
+    pub fn hosts(&self) -> Vec<Host> {
+        self.state()
+            .hosts
+            .values()
+            .map(|h| h.host.clone())
+            .collect()
+    }
+
+    /// Get the inventory of a host, if it is part of the lattice
+    #[must_use]
+    pub fn inventory(&self, host_id: &str) -> Option<HostInventory> {
+        self.state().hosts.get(host_id).map(MockHost::inventory)
+    }
+
+    /// Get all links in the lattice
+    #[must_use]
+    pub fn links(&self) -> Vec<Link> {
+        self.state().links.clone()
+    }
+
+    /// Get a named config, if it exists
+    #[must_use]
+    pub fn config(&self, name: &str) -> Option<HashMap<String, String>> {
+        self.state().configs.get(name).cloned()
+    }
+
+    /// Get the values of a named config scoped to a host, if they exist
+    #[must_use]
+    pub fn host_config(&self, host_id: &str, name: &str) -> Option<HashMap<String, String>> {
+        self.state()
+            .host_configs
+            .get(&(host_id.to_string(), name.to_string()))
+            .cloned()
+    }
+
+    /// Get the subjects of all requests received so far, in the order they were received
+    #[must_use]
+    pub fn requests(&self) -> Vec<String> {
+        self.state().requests.clone()
+    }
+
+    /// Get the number of active subscriptions to `subject`
+    #[must_use]
+    pub fn subscriptions(&self, subject: &str) -> usize {
+        self.state()
+            .subscribers
+            .iter()
+            .filter(|(sub, tx)| sub == subject && !tx.is_closed())
+            .count()
+    }
+
+    /// Publish a lattice event, e.g. a `host_heartbeat`, to all subscribers of the event type.
+    ///
+    /// `ty` is the event type without the `com.wasmcloud.lattice.` namespace and `source` the ID
+    /// of the host that emitted it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event could not be built
+    pub fn publish_event(&self, ty: &str, source: &str, data: serde_json::Value) -> Result<()> {
+        let event = EventBuilderV10::new()
+            .id(format!("{ty}-{}", self.state().requests.len()))
+            .source(source)
+            .ty(format!("com.wasmcloud.lattice.{ty}"))
+            .data("application/json", data)
+            .build()?;
+        let subject = format!("wasmbus.evt.{}.{ty}", self.lattice);
+        let payload = Bytes::from(json_serialize(event)?);
+        self.state().subscribers.retain(|(sub, tx)| {
+            sub != &subject
+                || tx
+                    .unbounded_send(TransportMessage::new(subject.clone(), payload.clone()))
+                    .is_ok()
+        });
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, LatticeState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Handle a request, returning the replies along with the events to publish once the state
+    /// lock has been released
+    fn handle(&self, subject: &str, payload: &[u8]) -> Result<(Reply, Vec<PendingEvent>)> {
+        let op = [ProtocolVersion::V1, ProtocolVersion::V2]
+            .into_iter()
+            .find_map(|version| {
+                let (_, rest) = subject.split_once(&format!(".{version}."))?;
+                let (lattice, op) = rest.split_once('.')?;
+                (lattice == &*self.lattice).then(|| broker::parse_operation(version, op))
+            })
+            .ok_or_else(|| format!("no responders on {subject}"))?;
+        let (resource, action, arg) = (op.resource, op.action, &*op.arg);
+
+        let mut state = self.state();
+        state.requests.push(subject.to_string());
+        let mut events = Vec::new();
+        let reply = match (resource, action) {
+            ("host", "ping") => Reply::Many(
+                state
+                    .hosts
+                    .values()
+                    .map(|h| json_serialize(CtlResponse::ok(h.host.clone())))
+                    .collect::<Result<_>>()?,
+            ),
+            ("host", "get") if payload.is_empty() => ok(state.host(arg)?.inventory())?,
+            ("host", "get") => {
+                let request: InventoryPageRequest = json_deserialize(payload)?;
+                ok(state.host(arg)?.inventory().into_page(&request))?
+            }
+            ("host", "stop") => {
+                let _: StopHostCommand = json_deserialize(payload)?;
+                let host = state
+                    .hosts
+                    .remove(arg)
+                    .ok_or_else(|| no_responders(subject))?;
+                events.push(PendingEvent::new(
+                    "host_stopped",
+                    arg,
+                    json!({ "labels": host.host.labels }),
+                ));
+                success()?
+            }
+            ("host", "drain") => {
+                let cmd: DrainHostCommand = json_deserialize(payload)?;
+                state.host(arg)?;
+                events.push(PendingEvent::new(
+                    "host_draining",
+                    arg,
+                    json!({
+                        "host_id": arg,
+                        "timeout_ms": cmd.options.timeout_ms,
+                        "force_stop": cmd.options.force_stop,
+                    }),
+                ));
+                success()?
+            }
+            ("host", "prefetch") => {
+                let cmd: PrefetchImagesCommand = json_deserialize(payload)?;
+                let host = state.host_mut(arg)?;
+                for image_ref in cmd.image_refs {
+                    host.prefetched_images.insert(
+                        image_ref.clone(),
+                        PrefetchedImage::new(&image_ref, PrefetchStatus::Cached),
+                    );
+                }
+                success()?
+            }
+            ("component", "profile") => {
+                let cmd: ProfileComponentCommand = json_deserialize(payload)?;
+                if state.host(arg)?.components.contains_key(&cmd.component_id) {
+                    ok(ComponentProfile {
+                        component_id: cmd.component_id,
+                        host_id: arg.to_string(),
+                        duration_ms: cmd.duration_ms,
+                        ..Default::default()
+                    })?
+                } else {
+                    error(&format!("component {} not found", cmd.component_id))?
+                }
+            }
+            ("component", "memory") => {
+                let query: TopMemoryQuery = json_deserialize(payload)?;
+                let usages = state.host(arg)?.components.values().map(|component| {
+                    ComponentMemoryUsage::builder()
+                        .component_id(component.id.clone())
+                        .image_ref(component.image_ref.clone())
+                        .max_instances(component.max_instances)
+                        .build()
+                });
+                ok(top_memory_consumers(usages, query.limit as usize))?
+            }
+            ("host", "cleanup") => {
+                let _: CleanupHostDataCommand = json_deserialize(payload)?;
+                state.host(arg)?;
+                error("host does not manage a data directory")?
+            }
+            ("host", "tracing") => {
+                let _: UpdateHostTracingCommand = json_deserialize(payload)?;
+                state.host(arg)?;
+                ok(())?
+            }
+            ("component", "auction") => {
+                let req: ComponentAuctionRequest = json_deserialize(payload)?;
+                Reply::Many(
+                    state
+                        .hosts
+                        .values()
+                        .filter(|h| matches_constraints(&h.host, &req.constraints))
+                        .map(|h| {
+                            json_serialize(CtlResponse::ok(ComponentAuctionAck {
+                                component_ref: req.component_ref.clone(),
+                                component_id: req.component_id.clone(),
+                                host_id: h.host.id.clone(),
+                                constraints: req.constraints.clone(),
+                                hints: h.auction_hints(),
+                            }))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            ("provider", "auction") => {
+                let req: ProviderAuctionRequest = json_deserialize(payload)?;
+                Reply::Many(
+                    state
+                        .hosts
+                        .values()
+                        .filter(|h| {
+                            !h.providers.contains_key(&req.provider_id)
+                                && matches_constraints(&h.host, &req.constraints)
+                        })
+                        .map(|h| {
+                            json_serialize(CtlResponse::ok(ProviderAuctionAck {
+                                host_id: h.host.id.clone(),
+                                provider_ref: req.provider_ref.clone(),
+                                provider_id: req.provider_id.clone(),
+                                constraints: req.constraints.clone(),
+                                hints: h.auction_hints(),
+                            }))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            ("component", "scale") => {
+                let cmd: ScaleComponentCommand = json_deserialize(payload)?;
+                let host = state.host_mut(arg)?;
+                let annotations = cmd.annotations.clone().unwrap_or_default();
+                match host.components.get(&cmd.component_id) {
+                    Some(existing)
+                        if existing.image_ref != cmd.component_ref && !cmd.allow_update =>
+                    {
+                        return Ok((
+                            error(&format!(
+                                "component {} is already running with a different image reference",
+                                cmd.component_id
+                            ))?,
+                            events,
+                        ));
+                    }
+                    _ => {}
+                }
+                if cmd.max_instances == 0 {
+                    host.components.remove(&cmd.component_id);
+                } else {
+                    host.components.insert(
+                        cmd.component_id.clone(),
+                        ComponentDescription {
+                            id: cmd.component_id.clone(),
+                            image_ref: cmd.component_ref.clone(),
+                            name: None,
+                            annotations: cmd.annotations.clone(),
+                            revision: 0,
+                            max_instances: cmd.max_instances,
+                            limits: cmd.component_limits.clone(),
+                            memory_bytes: None,
+                            running_instances: None,
+                        },
+                    );
+                }
+                events.push(PendingEvent::new(
+                    "component_scaled",
+                    arg,
+                    json!({
+                        "annotations": annotations,
+                        "host_id": arg,
+                        "image_ref": cmd.component_ref,
+                        "max_instances": cmd.max_instances,
+                        "component_id": cmd.component_id,
+                    }),
+                ));
+                success()?
+            }
+            ("component", "stop") => {
+                let cmd: StopComponentsCommand = json_deserialize(payload)?;
+                if cmd.annotations.is_empty() {
+                    return Ok((error("at least one annotation is required")?, events));
+                }
+                let host = state.host_mut(arg)?;
+                let stopped: Vec<_> = host
+                    .components
+                    .values()
+                    .filter(|c| cmd.matches(c.annotations.as_ref().unwrap_or(&BTreeMap::new())))
+                    .map(|c| c.id.clone())
+                    .collect();
+                for component_id in &stopped {
+                    let Some(component) = host.components.remove(component_id) else {
+                        continue;
+                    };
+                    events.push(PendingEvent::new(
+                        "component_scaled",
+                        arg,
+                        json!({
+                            "annotations": component.annotations.unwrap_or_default(),
+                            "host_id": arg,
+                            "image_ref": component.image_ref,
+                            "max_instances": 0,
+                            "component_id": component_id,
+                        }),
+                    ));
+                }
+                ok(stopped)?
+            }
+            ("component", "update") => {
+                let cmd: UpdateComponentCommand = json_deserialize(payload)?;
+                let host = state.host_mut(arg)?;
+                let Some(component) = host.components.get_mut(&cmd.component_id) else {
+                    return Ok((
+                        error(&format!("component {} not found", cmd.component_id))?,
+                        events,
+                    ));
+                };
+                component.image_ref.clone_from(&cmd.new_component_ref);
+                if cmd.annotations.is_some() {
+                    component.annotations.clone_from(&cmd.annotations);
+                }
+                events.push(PendingEvent::new(
+                    "component_scaled",
+                    arg,
+                    json!({
+                        "annotations": component.annotations.clone().unwrap_or_default(),
+                        "host_id": arg,
+                        "image_ref": cmd.new_component_ref,
+                        "max_instances": component.max_instances,
+                        "component_id": cmd.component_id,
+                    }),
+                ));
+                success()?
+            }
+            ("provider", "start") => {
+                let cmd: StartProviderCommand = json_deserialize(payload)?;
+                let host = state.host_mut(arg)?;
+                if !host.providers.contains_key(cmd.provider_id()) {
+                    host.providers.insert(
+                        cmd.provider_id().to_string(),
+                        ProviderDescription {
+                            id: cmd.provider_id().to_string(),
+                            image_ref: Some(cmd.provider_ref().to_string()),
+                            name: None,
+                            revision: 0,
+                            annotations: cmd.annotations().cloned(),
+                        },
+                    );
+                    events.push(PendingEvent::new(
+                        "provider_started",
+                        arg,
+                        json!({
+                            "host_id": arg,
+                            "image_ref": cmd.provider_ref(),
+                            "provider_id": cmd.provider_id(),
+                            "annotations": cmd.annotations().cloned().unwrap_or_default(),
+                        }),
+                    ));
+                }
+                success()?
+            }
+            ("provider", "update") => {
+                let cmd: UpdateProviderConfigCommand = json_deserialize(payload)?;
+                let missing = cmd.config.iter().find(|name| {
+                    !name.starts_with("SECRET_") && !state.configs.contains_key(*name)
+                });
+                if let Some(name) = missing {
+                    let message = format!("Configuration {name} not found in config store");
+                    return Ok((error(&message)?, events));
+                }
+                let host = state.host_mut(arg)?;
+                if !host.providers.contains_key(&cmd.provider_id) {
+                    return Ok((
+                        error(&format!("provider {} not found", cmd.provider_id))?,
+                        events,
+                    ));
+                }
+                events.push(PendingEvent::new(
+                    "provider_config_updated",
+                    arg,
+                    json!({
+                        "host_id": arg,
+                        "provider_id": cmd.provider_id,
+                        "config": cmd.config,
+                    }),
+                ));
+                success()?
+            }
+            ("provider", "stop") => {
+                let cmd: StopProviderCommand = json_deserialize(payload)?;
+                let host = state.host_mut(arg)?;
+                let Some(provider) = host.providers.remove(&cmd.provider_id) else {
+                    return Ok((
+                        error(&format!("provider {} not found", cmd.provider_id))?,
+                        events,
+                    ));
+                };
+                events.push(PendingEvent::new(
+                    "provider_stopped",
+                    arg,
+                    json!({
+                        "host_id": arg,
+                        "provider_id": cmd.provider_id,
+                        "annotations": provider.annotations.unwrap_or_default(),
+                        "reason": "stop",
+                    }),
+                ));
+                success()?
+            }
+            ("label", "put" | "del" | "put_many" | "del_many") => {
+                let host = state.host_mut(arg)?;
+                let labels = &mut host.host.labels;
+                match action {
+                    "put" => {
+                        let HostLabel { key, value } = json_deserialize(payload)?;
+                        labels.insert(key, value);
+                    }
+                    "del" => {
+                        let HostLabelIdentifier { key } = json_deserialize(payload)?;
+                        labels.remove(&key);
+                    }
+                    "put_many" => {
+                        let HostLabels { labels: new } = json_deserialize(payload)?;
+                        labels.extend(new);
+                    }
+                    _ => {
+                        let HostLabelIdentifiers { keys } = json_deserialize(payload)?;
+                        for key in keys {
+                            labels.remove(&key);
+                        }
+                    }
+                }
+                events.push(PendingEvent::new(
+                    "labels_changed",
+                    arg,
+                    json!({ "host_id": arg, "labels": labels }),
+                ));
+                success()?
+            }
+            ("link", "get") => Reply::One(json_serialize(
+                CtlResponse::ok(state.links.clone()).with_revision(state.revision),
+            )?),
+            ("link", "put") => {
+                let req: PutLinkRequest = json_deserialize(payload)?;
+                let link = req.link;
+                let existing = state.links.iter().position(|l| {
+                    l.source_id == link.source_id
+                        && l.name == link.name
+                        && l.wit_namespace == link.wit_namespace
+                        && l.wit_package == link.wit_package
+                });
+                match existing {
+                    Some(_) if req.if_absent => {
+                        return Ok((error("link already exists")?, events));
+                    }
+                    Some(idx) if state.links[idx].target != link.target && !req.force => {
+                        return Ok((
+                            error(&format!(
+                                "link already exists with a different target [{}]",
+                                state.links[idx].target
+                            ))?,
+                            events,
+                        ));
+                    }
+                    Some(idx) => state.links[idx] = link.clone(),
+                    None => state.links.push(link.clone()),
+                }
+                state.revision += 1;
+                events.push(PendingEvent::new(
+                    "linkdef_set",
+                    &self.lattice,
+                    json!({
+                        "source_id": link.source_id,
+                        "target": link.target,
+                        "name": link.name,
+                        "wit_namespace": link.wit_namespace,
+                        "wit_package": link.wit_package,
+                        "interfaces": link.interfaces,
+                        "source_config": link.source_config,
+                        "target_config": link.target_config,
+                    }),
+                ));
+                success()?
+            }
+            ("link", "del") => {
+                let req: DeleteInterfaceLinkDefinitionRequest = json_deserialize(payload)?;
+                state.links.retain(|l| {
+                    l.source_id != req.source_id
+                        || l.name != req.name
+                        || l.wit_namespace != req.wit_namespace
+                        || l.wit_package != req.wit_package
+                });
+                state.revision += 1;
+                events.push(PendingEvent::new(
+                    "linkdef_deleted",
+                    &self.lattice,
+                    json!({
+                        "source_id": req.source_id,
+                        "name": req.name,
+                        "wit_namespace": req.wit_namespace,
+                        "wit_package": req.wit_package,
+                    }),
+                ));
+                success()?
+            }
+            ("config", "get") => Reply::One(json_serialize(CtlResponse {
+                success: true,
+                message: String::new(),
+                response: state.configs.get(arg).cloned(),
+                revision: Some(state.revision),
+                code: None,
+                retry_after_ms: None,
+            })?),
+            ("config", "get_many") => {
+                let ConfigNames { names } = json_deserialize(payload)?;
+                let configs: ConfigsByName = names
+                    .into_iter()
+                    .map(|name| {
+                        let config = state.configs.get(&name).cloned();
+                        (name, config)
+                    })
+                    .collect();
+                ok(configs)?
+            }
+            ("config", "put") => {
+                let config: HashMap<String, String> = json_deserialize(payload)?;
+                state.configs.insert(arg.to_string(), config);
+                state.revision += 1;
+                events.push(PendingEvent::new(
+                    "config_set",
+                    &self.lattice,
+                    json!({ "config_name": arg }),
+                ));
+                success()?
+            }
+            ("config", "del") => {
+                state.configs.remove(arg);
+                state.revision += 1;
+                events.push(PendingEvent::new(
+                    "config_deleted",
+                    &self.lattice,
+                    json!({ "config_name": arg }),
+                ));
+                success()?
+            }
+            ("config", "get_host") => {
+                let key = host_config_key(arg, subject)?;
+                ok(state.host_configs.get(&key).cloned())?
+            }
+            ("config", "put_host") => {
+                let key = host_config_key(arg, subject)?;
+                let config: HashMap<String, String> = json_deserialize(payload)?;
+                events.push(PendingEvent::new(
+                    "config_set",
+                    &self.lattice,
+                    json!({ "config_name": key.1, "host_id": key.0 }),
+                ));
+                state.host_configs.insert(key, config);
+                state.revision += 1;
+                success()?
+            }
+            ("config", "del_host") => {
+                let key = host_config_key(arg, subject)?;
+                events.push(PendingEvent::new(
+                    "config_deleted",
+                    &self.lattice,
+                    json!({ "config_name": key.1, "host_id": key.0 }),
+                ));
+                state.host_configs.remove(&key);
+                state.revision += 1;
+                success()?
+            }
+            ("claims", "get") => Reply::One(json_serialize(
+                CtlResponse::ok(state.claims.clone()).with_revision(state.revision),
+            )?),
+            ("registry", "put") => success()?,
+            _ => return Err(no_responders(subject)),
+        };
+        Ok((reply, events))
+    }
+
+    /// Handle a request and publish the resulting events
+    fn respond(&self, subject: &str, payload: &[u8]) -> Result<Reply> {
+        let (reply, events) = self.handle(subject, payload)?;
+        for PendingEvent { ty, source, data } in events {
+            self.publish_event(ty, &source, data)?;
+        }
+        Ok(reply)
+    }
+}
+
+impl LatticeState {
+    fn host(&self, host_id: &str) -> Result<&MockHost> {
+        self.hosts
+            .get(host_id)
+            .ok_or_else(|| no_responders(host_id))
+    }
+
+    fn host_mut(&mut self, host_id: &str) -> Result<&mut MockHost> {
+        self.hosts
+            .get_mut(host_id)
+            .ok_or_else(|| no_responders(host_id))
+    }
+}
+
+/// An event to publish once a request has been handled
+struct PendingEvent {
+    ty: &'static str,
+    source: String,
+    data: serde_json::Value,
+}
+
+impl PendingEvent {
+    fn new(ty: &'static str, source: &str, data: serde_json::Value) -> Self {
+        Self {
+            ty,
+            source: source.to_string(),
+            data,
+        }
+    }
+}
+
+fn matches_constraints(host: &Host, constraints: &BTreeMap<String, String>) -> bool {
+    constraints
+        .iter()
+        .all(|(k, v)| host.labels.get(k) == Some(v))
+}
+
+/// Encode a JSON reply with the encoding accepted by the request, like a host would
+fn encoded_reply(subject: String, reply: Vec<u8>, encoding: Encoding) -> Result<TransportMessage> {
+    let mut msg = TransportMessage::new(subject, encoding.from_json(reply.into())?);
+    encoding.insert_reply_headers(&mut msg.headers);
+    Ok(msg)
+}
+
+fn no_responders(target: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    NoResponders::new(target).into()
+}
+
+fn ok<T: Serialize>(response: T) -> Result<Reply> {
+    json_serialize(CtlResponse::ok(response)).map(Reply::One)
+}
+
+fn success() -> Result<Reply> {
+    json_serialize(CtlResponse::<()>::success(String::new())).map(Reply::One)
+}
+
+fn error(message: &str) -> Result<Reply> {
+    json_serialize(CtlResponse::<()>::error(message)).map(Reply::One)
+}
+
+/// Split the `<host_id>.<config_name>` argument of a host-scoped config subject
+fn host_config_key(arg: &str, subject: &str) -> Result<(String, String)> {
+    let (host_id, name) = arg.split_once('.').ok_or_else(|| no_responders(subject))?;
+    Ok((host_id.to_string(), name.to_string()))
+}
+
+impl ControlTransport for MockLattice {
+    fn request(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+        _timeout: Duration,
+    ) -> BoxFuture<'_, Result<TransportMessage>> {
+        async move {
+            let encoding = Encoding::accepted(Some(&headers));
+            match self.respond(&subject, &payload)? {
+                Reply::One(reply) => encoded_reply(subject, reply, encoding),
+                Reply::Many(replies) => replies
+                    .into_iter()
+                    .next()
+                    .map(|reply| encoded_reply(subject.clone(), reply, encoding))
+                    .ok_or_else(|| no_responders(&subject))?,
+            }
+        }
+        .boxed()
+    }
+
+    fn request_many(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+        async move {
+            let encoding = Encoding::accepted(Some(&headers));
+            let replies = match self.respond(&subject, &payload)? {
+                Reply::One(reply) => vec![reply],
+                Reply::Many(replies) => replies,
+            };
+            let replies = replies
+                .into_iter()
+                .map(|reply| encoded_reply(subject.clone(), reply, encoding))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(stream::iter(replies).boxed())
+        }
+        .boxed()
+    }
+
+    fn publish(
+        &self,
+        subject: String,
+        _headers: HeaderMap,
+        payload: Bytes,
+    ) -> BoxFuture<'_, Result<()>> {
+        async move { self.respond(&subject, &payload).map(|_| ()) }.boxed()
+    }
+
+    fn subscribe(
+        &self,
+        subject: String,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+        async move {
+            let (tx, rx) = mpsc::unbounded();
+            self.state().subscribers.push((subject, tx));
+            Ok(rx.boxed())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::collections::{BTreeMap, HashMap};
+
+    use cloudevents::AttributesReader as _;
+
+    use super::MockLattice;
+    use crate::{
+        ComponentProfile, DataCategory, Host, HostNotFound, InventoryPageRequest, Link,
+        PrefetchStatus, Result, UpdateHostTracingCommand,
+    };
+
+    fn host(id: &str, zone: &str) -> Host {
+        Host {
+            id: id.into(),
+            labels: BTreeMap::from([("zone".into(), zone.into())]),
+            version: Some("1.10.0".into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_lattice_handles_ctl_requests() -> Result<()> {
+        let lattice = MockLattice::default();
+        lattice.add_host(host("host-a", "east"));
+        lattice.add_host(host("host-b", "west"));
+        let client = lattice.client();
+
+        let hosts = client.get_hosts().await?;
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(client.get_hosts_by_label("zone=west").await?.len(), 1);
+
+        let acks = client
+            .perform_component_auction(
+                "ghcr.io/wasmcloud/echo:0.1.0",
+                "echo",
+                BTreeMap::from([("zone".to_string(), "west".to_string())]),
+            )
+            .await?;
+        assert_eq!(acks.len(), 1);
+        let host_id = acks[0].data().expect("ack").host_id().to_string();
+        assert_eq!(host_id, "host-b");
+        let hints = acks[0].data().expect("ack").hints();
+        assert_eq!(hints.component_count(), Some(0));
+        assert_eq!(hints.labels().get("zone").map(String::as_str), Some("west"));
+
+        let mut events = client
+            .events_receiver(vec!["component_scaled".into()])
+            .await?;
+        let ack = client
+            .scale_component(
+                &host_id,
+                "ghcr.io/wasmcloud/echo:0.1.0",
+                "echo",
+                5,
+                None,
+                vec![],
+            )
+            .await?;
+        assert!(ack.succeeded());
+        let evt = events.recv().await.expect("component_scaled event");
+        assert_eq!(evt.ty(), "com.wasmcloud.lattice.component_scaled");
+        let inventory = client.get_host_inventory(&host_id).await?;
+        let components = inventory.data().expect("inventory").components();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].max_instances(), 5);
+
+        let ack = client
+            .scale_component(
+                &host_id,
+                "ghcr.io/wasmcloud/other:0.1.0",
+                "echo",
+                1,
+                None,
+                vec![],
+            )
+            .await?;
+        assert!(!ack.succeeded(), "image reference changes need an update");
+
+        let app = BTreeMap::from([("app".to_string(), "shop".to_string())]);
+        assert!(client
+            .scale_component(
+                &host_id,
+                "ghcr.io/wasmcloud/cart:0.1.0",
+                "cart",
+                1,
+                Some((&app).into()),
+                vec![],
+            )
+            .await?
+            .succeeded());
+        let stopped = client.stop_components_matching(&host_id, app).await?;
+        assert_eq!(stopped.data(), Some(&vec!["cart".to_string()]));
+        let inventory = client.get_host_inventory(&host_id).await?;
+        let components = inventory.data().expect("inventory").components();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].id(), "echo");
+        let profile = client
+            .profile_component(&host_id, "echo", Duration::from_millis(10))
+            .await?;
+        assert_eq!(
+            profile.data().map(ComponentProfile::duration),
+            Some(Duration::from_millis(10))
+        );
+        assert!(!client
+            .profile_component(&host_id, "cart", Duration::from_millis(10))
+            .await?
+            .succeeded());
+        let top = client.get_top_memory_consumers(&host_id, 5).await?;
+        assert_eq!(
+            top.data()
+                .map(|usages| usages.iter().map(|u| u.component_id()).collect::<Vec<_>>()),
+            Some(vec!["echo"])
+        );
+        assert!(!client
+            .stop_components_matching(&host_id, BTreeMap::new())
+            .await?
+            .succeeded());
+
+        assert!(client
+            .start_provider(
+                "host-a",
+                "ghcr.io/wasmcloud/http:0.1.0",
+                "http",
+                None,
+                vec![]
+            )
+            .await?
+            .succeeded());
+        assert!(client
+            .update_provider_config("host-a", "http", vec![])
+            .await?
+            .succeeded());
+        assert!(!client
+            .update_provider_config("host-a", "http", vec!["missing".into()])
+            .await?
+            .succeeded());
+        assert!(client.stop_provider("host-a", "http").await?.succeeded());
+        assert!(!client.stop_provider("host-a", "http").await?.succeeded());
+        assert!(!client
+            .update_provider_config("host-a", "http", vec![])
+            .await?
+            .succeeded());
+        assert!(client.get_host_inventory("host-c").await.is_err());
+        let err = client
+            .scale_component("host-c", "echo", "echo", 1, None, vec![])
+            .await
+            .expect_err("scaling on an unknown host should fail");
+        let not_found = err
+            .downcast_ref::<HostNotFound>()
+            .expect("unknown hosts should be reported as not found");
+        assert_eq!(not_found.host_id(), "host-c");
+        assert_eq!(not_found.operation(), "scale_component");
+
+        let link = Link::builder()
+            .source_id("echo")
+            .target("http")
+            .name("default")
+            .wit_namespace("wasi")
+            .wit_package("http")
+            .interfaces(vec!["incoming-handler".into()])
+            .build()?;
+        assert!(client.put_link(link.clone()).await?.succeeded());
+        assert!(!client.put_link_if_absent(link).await?.succeeded());
+        assert_eq!(
+            client.get_links().await?.into_data().map(|l| l.len()),
+            Some(1)
+        );
+        let links = client.get_links_at(1).await?;
+        assert_eq!(links.revision(), Some(1));
+        assert!(client.get_links_at(2).await.is_err());
+        assert!(client
+            .delete_link("echo", "default", "wasi", "http")
+            .await?
+            .succeeded());
+        assert!(lattice.links().is_empty());
+
+        client
+            .put_config("cfg", HashMap::from([("k".to_string(), "v".to_string())]))
+            .await?;
+        assert_eq!(
+            client.get_config("cfg").await?.data(),
+            lattice.config("cfg").as_ref()
+        );
+        assert_eq!(client.get_config_at("cfg", 3).await?.revision(), Some(3));
+        let configs = client
+            .get_configs(vec!["cfg".to_string(), "missing".to_string()])
+            .await?
+            .into_data()
+            .expect("configs");
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs["cfg"], lattice.config("cfg"));
+        assert_eq!(configs["missing"], None);
+        client.delete_config("cfg").await?;
+        assert_eq!(client.get_config("cfg").await?.data(), None);
+
+        client
+            .put_host_config(
+                "host-a",
+                "cfg",
+                HashMap::from([("k".to_string(), "local".to_string())]),
+            )
+            .await?;
+        assert_eq!(
+            client.get_host_config("host-a", "cfg").await?.data(),
+            lattice.host_config("host-a", "cfg").as_ref()
+        );
+        assert_eq!(lattice.host_config("host-b", "cfg"), None);
+        client.delete_host_config("host-a", "cfg").await?;
+        assert_eq!(client.get_host_config("host-a", "cfg").await?.data(), None);
+
+        client.put_label("host-a", "gpu", "true").await?;
+        assert_eq!(
+            lattice.hosts()[0].labels().get("gpu").map(String::as_str),
+            Some("true")
+        );
+        assert!(client
+            .prefetch_images("host-a", vec!["ghcr.io/wasmcloud/echo:0.1.0".into()])
+            .await?
+            .succeeded());
+        let inventory = client.get_host_inventory("host-a").await?;
+        let prefetched = inventory.data().expect("inventory").prefetched_images();
+        assert_eq!(prefetched[0].image_ref(), "ghcr.io/wasmcloud/echo:0.1.0");
+        assert_eq!(prefetched[0].status(), PrefetchStatus::Cached);
+        assert!(!client
+            .cleanup_host_data("host-a", vec![DataCategory::Artifacts])
+            .await?
+            .succeeded());
+        assert!(client
+            .update_host_tracing(&UpdateHostTracingCommand::new("host-a").with_trace_level("debug"))
+            .await?
+            .succeeded());
+        let inventory = inventory.into_data().expect("inventory");
+        let mut paged = Vec::new();
+        let mut request = InventoryPageRequest::new(1);
+        loop {
+            let page = client
+                .get_host_inventory_page("host-a", &request)
+                .await?
+                .into_data()
+                .expect("inventory page");
+            assert_eq!(page.inventory().host_id(), "host-a");
+            paged.extend(
+                page.inventory()
+                    .components()
+                    .iter()
+                    .map(|c| c.id().to_string()),
+            );
+            paged.extend(
+                page.inventory()
+                    .providers()
+                    .iter()
+                    .map(|p| p.id().to_string()),
+            );
+            let Some(cursor) = page.next_cursor() else {
+                break;
+            };
+            request = request.with_cursor(cursor);
+        }
+        assert_eq!(
+            paged.len(),
+            inventory.components().len() + inventory.providers().len()
+        );
+        assert!(client.stop_host("host-a", None).await?.succeeded());
+        assert_eq!(lattice.hosts().len(), 1);
+        assert!(!lattice.requests().is_empty());
+        Ok(())
+    }
+}
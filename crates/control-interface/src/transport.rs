@@ -0,0 +1,373 @@
+//! Transports used by the [`Client`](crate::Client) to exchange control interface messages with
+//! hosts.
+//!
+//! The default transport is an [`async_nats::Client`]. Alternative implementations of
+//! [`ControlTransport`] can be provided with
+//! [`ClientBuilder::with_transport`](crate::ClientBuilder::with_transport), for example an
+//! in-memory transport to unit test controllers built on this crate.
+
+use core::fmt::{self, Debug};
+use core::time::Duration;
+
+use async_nats::HeaderMap;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt as _, StreamExt as _};
+use tracing::error;
+
+use crate::{otel, Result};
+
+/// A message received from a [`ControlTransport`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TransportMessage {
+    /// The subject the message was received on
+    pub subject: String,
+    /// Headers received along with the message
+    pub headers: HeaderMap,
+    /// The message payload
+    pub payload: Bytes,
+}
+
+impl TransportMessage {
+    /// Create a new [`TransportMessage`] without headers
+    #[must_use]
+    pub fn new(subject: impl Into<String>, payload: impl Into<Bytes>) -> Self {
+        Self {
+            subject: subject.into(),
+            headers: HeaderMap::new(),
+            payload: payload.into(),
+        }
+    }
+
+    /// Set the headers of the message
+    #[must_use]
+    pub fn with_headers(self, headers: HeaderMap) -> Self {
+        Self { headers, ..self }
+    }
+
+    /// Returns the trace context propagated in the headers of the message, e.g. the span of the
+    /// host that processed a request when this message is its reply.
+    ///
+    /// The [`Client`](crate::Client) links the spans of its operations to the spans of all
+    /// replies, consumers of raw messages can use this to parent their own spans instead.
+    #[must_use]
+    pub fn span_context(&self) -> Option<opentelemetry::trace::SpanContext> {
+        otel::HeaderExtractor::new(&self.headers).extract_span_context()
+    }
+}
+
+impl From<async_nats::Message> for TransportMessage {
+    fn from(msg: async_nats::Message) -> Self {
+        Self {
+            subject: msg.subject.to_string(),
+            headers: msg.headers.unwrap_or_default(),
+            payload: msg.payload,
+        }
+    }
+}
+
+/// Error returned by [`ControlTransport::request`] when nothing is subscribed to the subject of a
+/// request, e.g. because the targeted host does not exist.
+///
+/// Transports return this error rather than waiting for the timeout where the messaging system
+/// reports missing subscribers, which allows the [`Client`](crate::Client) to tell unknown hosts
+/// apart from hosts that are slow to reply.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct NoResponders {
+    /// The subject of the request
+    pub subject: String,
+}
+
+impl NoResponders {
+    /// Create a new [`NoResponders`] error for a request on the given subject
+    #[must_use]
+    pub fn new(subject: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+        }
+    }
+}
+
+impl fmt::Display for NoResponders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no responders for {}", self.subject)
+    }
+}
+
+impl std::error::Error for NoResponders {}
+
+/// Returns whether a transport error was caused by nothing being subscribed to the subject
+pub(crate) fn is_no_responders(error: &(dyn std::error::Error + 'static)) -> bool {
+    if error.is::<NoResponders>() {
+        return true;
+    }
+    if let Some(error) = error.downcast_ref::<async_nats::RequestError>() {
+        return error.kind() == async_nats::RequestErrorKind::NoResponders;
+    }
+    false
+}
+
+/// The messaging operations the control interface [`Client`](crate::Client) relies on.
+///
+/// Subjects use the NATS subject syntax of the control interface regardless of the transport.
+pub trait ControlTransport: Debug + Send + Sync + 'static {
+    /// Send a request and wait up to `timeout` for a single reply
+    fn request(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+        timeout: Duration,
+    ) -> BoxFuture<'_, Result<TransportMessage>>;
+
+    /// Send a request that any number of hosts may reply to, returning the stream of replies.
+    ///
+    /// The stream does not need to end on its own, callers stop reading once they have waited
+    /// long enough for replies.
+    fn request_many(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>>;
+
+    /// Publish a message without waiting for a reply
+    fn publish(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> BoxFuture<'_, Result<()>>;
+
+    /// Subscribe to all messages published on a subject
+    fn subscribe(
+        &self,
+        subject: String,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>>;
+
+    /// Returns whether the transport is currently connected.
+    ///
+    /// Used to detect reconnects, during which subscribers may have missed messages. Transports
+    /// that cannot lose their connection can rely on the default, which is always connected.
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
+
+impl ControlTransport for async_nats::Client {
+    fn request(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+        timeout: Duration,
+    ) -> BoxFuture<'_, Result<TransportMessage>> {
+        async move {
+            match tokio::time::timeout(
+                timeout,
+                self.request_with_headers(
+                    subject.clone(),
+                    otel::HeaderInjector::new_with_span(headers).into(),
+                    payload,
+                ),
+            )
+            .await
+            {
+                Err(_) => {
+                    Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "timed out").into())
+                }
+                Ok(Ok(message)) => Ok(message.into()),
+                Ok(Err(e)) if e.kind() == async_nats::RequestErrorKind::NoResponders => {
+                    Err(NoResponders::new(subject).into())
+                }
+                Ok(Err(e)) => Err(e.into()),
+            }
+        }
+        .boxed()
+    }
+
+    fn request_many(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+        async move {
+            let reply = self.new_inbox();
+            let sub = self.subscribe(reply.clone()).await?;
+            self.publish_with_reply_and_headers(
+                subject,
+                reply,
+                otel::HeaderInjector::new_with_span(headers).into(),
+                payload,
+            )
+            .await?;
+            let nc = self.clone();
+            tokio::spawn(async move {
+                if let Err(error) = nc.flush().await {
+                    error!(%error, "flush after publish");
+                }
+            });
+            Ok(sub.map(TransportMessage::from).boxed())
+        }
+        .boxed()
+    }
+
+    fn publish(
+        &self,
+        subject: String,
+        headers: HeaderMap,
+        payload: Bytes,
+    ) -> BoxFuture<'_, Result<()>> {
+        async move {
+            self.publish_with_headers(
+                subject,
+                otel::HeaderInjector::new_with_span(headers).into(),
+                payload,
+            )
+            .await
+            .map_err(Into::into)
+        }
+        .boxed()
+    }
+
+    fn subscribe(
+        &self,
+        subject: String,
+    ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+        async move {
+            let sub = async_nats::Client::subscribe(self, subject).await?;
+            Ok(sub.map(TransportMessage::from).boxed())
+        }
+        .boxed()
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connection_state() == async_nats::connection::State::Connected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use async_nats::HeaderMap;
+    use bytes::Bytes;
+    use futures::future::BoxFuture;
+    use futures::stream::{self, BoxStream};
+    use futures::{FutureExt as _, StreamExt as _};
+
+    use super::{ControlTransport, TransportMessage};
+    use crate::{ClientBuilder, CtlResponse, Host, Link, Result};
+
+    /// Transport that answers host pings and link queries from memory
+    #[derive(Debug)]
+    struct StaticTransport;
+
+    impl ControlTransport for StaticTransport {
+        fn request(
+            &self,
+            subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+            _timeout: Duration,
+        ) -> BoxFuture<'_, Result<TransportMessage>> {
+            async move {
+                if !subject.ends_with(".link.get") {
+                    return Err(format!("no responders on {subject}").into());
+                }
+                let links = vec![Link::default()];
+                let payload = serde_json::to_vec(&CtlResponse::ok(links))?;
+                Ok(TransportMessage::new(subject, payload))
+            }
+            .boxed()
+        }
+
+        fn request_many(
+            &self,
+            subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            async move {
+                if subject.ends_with(".link.get") {
+                    let links = vec![Link::default()];
+                    let payload = serde_json::to_vec(&CtlResponse::ok(links))?;
+                    return Ok(stream::iter([TransportMessage::new(subject, payload)]).boxed());
+                }
+                let replies = ["host-a", "host-b"].map(|id| {
+                    let host = Host {
+                        id: id.to_string(),
+                        ..Default::default()
+                    };
+                    let payload = serde_json::to_vec(&CtlResponse::ok(host))
+                        .expect("failed to serialize host");
+                    TransportMessage::new(subject.clone(), payload)
+                });
+                Ok(stream::iter(replies).boxed())
+            }
+            .boxed()
+        }
+
+        fn publish(
+            &self,
+            _subject: String,
+            _headers: HeaderMap,
+            _payload: Bytes,
+        ) -> BoxFuture<'_, Result<()>> {
+            async { Ok(()) }.boxed()
+        }
+
+        fn subscribe(
+            &self,
+            _subject: String,
+        ) -> BoxFuture<'_, Result<BoxStream<'static, TransportMessage>>> {
+            async { Ok(stream::pending().boxed()) }.boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn client_uses_custom_transport() -> Result<()> {
+        let client = ClientBuilder::with_transport(StaticTransport).build();
+
+        let hosts = client.get_hosts().await?;
+        let ids: Vec<_> = hosts
+            .iter()
+            .filter_map(|res| res.data().map(Host::id))
+            .collect();
+        assert_eq!(ids, ["host-a", "host-b"]);
+
+        let links = client.get_links().await?;
+        assert_eq!(links.data().map(Vec::len), Some(1));
+
+        assert!(client.get_claims().await.is_err());
+        assert!(client.get_aliases().await.is_err(), "no NATS connection");
+        Ok(())
+    }
+
+    #[test]
+    fn test_span_context_is_extracted_from_headers() {
+        let message = TransportMessage::new("reply", Vec::new());
+        assert!(message.span_context().is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        );
+        let span_context = message
+            .with_headers(headers)
+            .span_context()
+            .expect("span context should be extracted");
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert_eq!(span_context.span_id().to_string(), "00f067aa0ba902b7");
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+    }
+}
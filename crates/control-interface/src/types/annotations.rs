@@ -0,0 +1,345 @@
+//! Annotations placed on components and providers by the tools that manage them
+
+use core::fmt;
+
+use std::collections::{btree_map, BTreeMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Annotation identifying the tool that manages a workload, e.g. `wadm`
+pub const MANAGED_BY_ANNOTATION: &str = "wasmcloud.dev/managed-by";
+/// Annotation holding the name of the application a workload belongs to
+pub const APP_NAME_ANNOTATION: &str = "wasmcloud.dev/appspec";
+/// Annotation holding the version of the application a workload belongs to
+pub const APP_VERSION_ANNOTATION: &str = "wasmcloud.dev/version";
+/// Annotation holding the revision of the application a workload belongs to
+pub const APP_REVISION_ANNOTATION: &str = "wasmcloud.dev/revision";
+
+/// Maximum length of the name segment of an annotation key
+const MAX_KEY_NAME_LEN: usize = 63;
+/// Maximum length of the prefix segment of an annotation key
+const MAX_KEY_PREFIX_LEN: usize = 253;
+
+/// Annotations of a component or provider.
+///
+/// This serializes as a plain map of strings, and any map can be converted into [`Annotations`]
+/// so that annotations set by other tools are preserved. Well-known annotations used by wadm and
+/// wash are available through typed accessors, and are validated when set through
+/// [`Annotations::insert`] or an [`AnnotationsBuilder`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Annotations(BTreeMap<String, String>);
+
+impl Annotations {
+    /// Create a new, empty set of annotations
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn builder() -> AnnotationsBuilder {
+        AnnotationsBuilder::default()
+    }
+
+    /// Get the tool that manages the workload
+    #[must_use]
+    pub fn managed_by(&self) -> Option<&str> {
+        self.get(MANAGED_BY_ANNOTATION)
+    }
+
+    /// Get the name of the application the workload belongs to
+    #[must_use]
+    pub fn app_name(&self) -> Option<&str> {
+        self.get(APP_NAME_ANNOTATION)
+    }
+
+    /// Get the version of the application the workload belongs to
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.get(APP_VERSION_ANNOTATION)
+    }
+
+    /// Get the revision of the application the workload belongs to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the revision annotation is set but is not a non-negative integer
+    pub fn revision(&self) -> Result<Option<u64>> {
+        self.get(APP_REVISION_ANNOTATION)
+            .map(|rev| {
+                rev.parse()
+                    .map_err(|_| format!("invalid revision annotation [{rev}]").into())
+            })
+            .transpose()
+    }
+
+    /// Get the value of an annotation
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Set an annotation, returning the previous value if there was one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key is invalid, or the value is invalid for a well-known key
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Option<String>> {
+        let key = key.into();
+        let value = value.into();
+        validate_annotation(&key, &value)?;
+        Ok(self.0.insert(key, value))
+    }
+
+    /// Remove an annotation, returning its value if it was set
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+
+    /// Returns true if the workload is managed by the given tool
+    #[must_use]
+    pub fn is_managed_by(&self, manager: &str) -> bool {
+        self.managed_by() == Some(manager)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over all annotations in key order
+    pub fn iter(&self) -> btree_map::Iter<'_, String, String> {
+        self.0.iter()
+    }
+
+    /// Check that all annotation keys are valid and that well-known annotations have valid values
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first invalid annotation
+    pub fn validate(&self) -> Result<()> {
+        self.0
+            .iter()
+            .try_for_each(|(key, value)| validate_annotation(key, value))
+    }
+
+    /// Get the underlying map of annotations
+    #[must_use]
+    pub fn as_map(&self) -> &BTreeMap<String, String> {
+        &self.0
+    }
+
+    /// Convert into the underlying map of annotations
+    #[must_use]
+    pub fn into_inner(self) -> BTreeMap<String, String> {
+        self.0
+    }
+}
+
+impl From<BTreeMap<String, String>> for Annotations {
+    fn from(annotations: BTreeMap<String, String>) -> Self {
+        Self(annotations)
+    }
+}
+
+impl From<&BTreeMap<String, String>> for Annotations {
+    fn from(annotations: &BTreeMap<String, String>) -> Self {
+        Self(annotations.clone())
+    }
+}
+
+impl From<Annotations> for BTreeMap<String, String> {
+    fn from(annotations: Annotations) -> Self {
+        annotations.0
+    }
+}
+
+impl FromIterator<(String, String)> for Annotations {
+    fn from_iter<T: IntoIterator<Item = (String, String)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for Annotations {
+    type Item = (String, String);
+    type IntoIter = btree_map::IntoIter<String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Annotations {
+    type Item = (&'a String, &'a String);
+    type IntoIter = btree_map::Iter<'a, String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for Annotations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (key, value) in &self.0 {
+            if !first {
+                f.write_str(",")?;
+            }
+            write!(f, "{key}={value}")?;
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct AnnotationsBuilder {
+    annotations: BTreeMap<String, String>,
+}
+
+impl AnnotationsBuilder {
+    /// Set the tool that manages the workload
+    #[must_use]
+    pub fn managed_by(self, v: impl Into<String>) -> Self {
+        self.annotation(MANAGED_BY_ANNOTATION, v)
+    }
+
+    /// Set the name of the application the workload belongs to
+    #[must_use]
+    pub fn app_name(self, v: impl Into<String>) -> Self {
+        self.annotation(APP_NAME_ANNOTATION, v)
+    }
+
+    /// Set the version of the application the workload belongs to
+    #[must_use]
+    pub fn version(self, v: impl Into<String>) -> Self {
+        self.annotation(APP_VERSION_ANNOTATION, v)
+    }
+
+    /// Set the revision of the application the workload belongs to
+    #[must_use]
+    pub fn revision(self, v: u64) -> Self {
+        self.annotation(APP_REVISION_ANNOTATION, v.to_string())
+    }
+
+    /// Set an arbitrary annotation
+    #[must_use]
+    pub fn annotation(mut self, k: impl Into<String>, v: impl Into<String>) -> Self {
+        self.annotations.insert(k.into(), v.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Annotations> {
+        let annotations = Annotations(self.annotations);
+        annotations.validate()?;
+        Ok(annotations)
+    }
+}
+
+/// Ensure an annotation key is well-formed and, for well-known annotations, that the value is
+/// valid.
+///
+/// Keys consist of an optional DNS-style prefix followed by `/` and a name made of ASCII
+/// alphanumerics, `-`, `_` and `.`, e.g. `wasmcloud.dev/appspec`.
+fn validate_annotation(key: &str, value: &str) -> Result<()> {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    let valid_chars = |s: &str, extra: &[char]| {
+        s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || extra.contains(&c))
+    };
+    if name.is_empty() || name.len() > MAX_KEY_NAME_LEN || !valid_chars(name, &['-', '_', '.']) {
+        return Err(format!(
+            "invalid annotation key [{key}], names must be 1-{MAX_KEY_NAME_LEN} ASCII letters, digits, `-`, `_` or `.`"
+        )
+        .into());
+    }
+    if let Some(prefix) = prefix {
+        if prefix.is_empty()
+            || prefix.len() > MAX_KEY_PREFIX_LEN
+            || !valid_chars(prefix, &['-', '.'])
+        {
+            return Err(format!(
+                "invalid annotation key [{key}], prefixes must be DNS subdomains of at most {MAX_KEY_PREFIX_LEN} characters"
+            )
+            .into());
+        }
+    }
+    match key {
+        MANAGED_BY_ANNOTATION | APP_NAME_ANNOTATION | APP_VERSION_ANNOTATION
+            if value.trim().is_empty() =>
+        {
+            Err(format!("annotation [{key}] cannot be empty").into())
+        }
+        APP_REVISION_ANNOTATION if value.parse::<u64>().is_err() => {
+            Err(format!("annotation [{key}] must be a non-negative integer, got [{value}]").into())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{Annotations, APP_NAME_ANNOTATION, APP_REVISION_ANNOTATION};
+
+    #[test]
+    fn annotations_accessors_and_validation() {
+        let annotations = Annotations::builder()
+            .managed_by("wadm")
+            .app_name("rust-echo")
+            .version("v0.1.0")
+            .revision(3)
+            .annotation("team", "edge")
+            .build()
+            .expect("valid annotations");
+        assert!(annotations.is_managed_by("wadm"));
+        assert_eq!(annotations.app_name(), Some("rust-echo"));
+        assert_eq!(annotations.version(), Some("v0.1.0"));
+        assert_eq!(annotations.revision().unwrap(), Some(3));
+        assert_eq!(annotations.get("team"), Some("edge"));
+
+        // Annotations are serialized as a plain map
+        let json = serde_json::to_value(&annotations).unwrap();
+        assert_eq!(json[APP_NAME_ANNOTATION], "rust-echo");
+        let map: BTreeMap<String, String> = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(Annotations::from(map), annotations);
+        assert_eq!(
+            serde_json::from_value::<Annotations>(json).unwrap(),
+            annotations
+        );
+
+        assert!(Annotations::builder().app_name(" ").build().is_err());
+        assert!(Annotations::builder()
+            .annotation(APP_REVISION_ANNOTATION, "latest")
+            .build()
+            .is_err());
+        let mut annotations = Annotations::new();
+        assert!(annotations.insert("has space", "value").is_err());
+        assert!(annotations.insert("/name", "value").is_err());
+        assert!(annotations.insert("example.com/", "value").is_err());
+        assert!(annotations.insert("example.com/name", "value").is_ok());
+
+        // Maps from the wire are accepted as-is, but can be checked explicitly
+        let annotations =
+            Annotations::from_iter([(APP_REVISION_ANNOTATION.to_string(), "x".to_string())]);
+        assert!(annotations.revision().is_err());
+        assert!(annotations.validate().is_err());
+    }
+}
@@ -38,6 +38,14 @@ pub struct ComponentDescription {
     /// The collective resource constraints for this component, such as memory limits and maximum execution time
     #[serde(default)]
     pub(crate) limits: Option<HashMap<String, String>>,
+
+    /// Linear memory, in bytes, currently held by running instances of this component
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) memory_bytes: Option<u64>,
+
+    /// The number of instances of this component currently running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) running_instances: Option<u64>,
 }
 
 #[derive(Default, Clone, PartialEq, Eq)]
@@ -50,6 +58,8 @@ pub struct ComponentDescriptionBuilder {
     revision: Option<i32>,
     max_instances: Option<u32>,
     limits: Option<HashMap<String, String>>,
+    memory_bytes: Option<u64>,
+    running_instances: Option<u64>,
 }
 
 impl ComponentDescriptionBuilder {
@@ -90,8 +100,20 @@ impl ComponentDescriptionBuilder {
     }
 
     #[must_use]
-    pub fn annotations(mut self, v: BTreeMap<String, String>) -> Self {
-        self.annotations = Some(v);
+    pub fn annotations(mut self, v: impl Into<BTreeMap<String, String>>) -> Self {
+        self.annotations = Some(v.into());
+        self
+    }
+
+    #[must_use]
+    pub fn memory_bytes(mut self, v: u64) -> Self {
+        self.memory_bytes = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn running_instances(mut self, v: u64) -> Self {
+        self.running_instances = Some(v);
         self
     }
 
@@ -106,6 +128,8 @@ impl ComponentDescriptionBuilder {
             max_instances: self.max_instances.unwrap_or_default(),
             annotations: self.annotations,
             limits: self.limits,
+            memory_bytes: self.memory_bytes,
+            running_instances: self.running_instances,
         })
     }
 }
@@ -145,6 +169,17 @@ impl ComponentDescription {
         self.limits.clone()
     }
 
+    /// Get the linear memory, in bytes, currently held by running instances of the component,
+    /// if reported by the host
+    pub fn memory_bytes(&self) -> Option<u64> {
+        self.memory_bytes
+    }
+
+    /// Get the number of instances of the component currently running, if reported by the host
+    pub fn running_instances(&self) -> Option<u64> {
+        self.running_instances
+    }
+
     #[must_use]
     pub fn builder() -> ComponentDescriptionBuilder {
         ComponentDescriptionBuilder::default()
@@ -244,8 +279,8 @@ impl ComponentInstanceBuilder {
     }
 
     #[must_use]
-    pub fn annotations(mut self, v: BTreeMap<String, String>) -> Self {
-        self.annotations = Some(v);
+    pub fn annotations(mut self, v: impl Into<BTreeMap<String, String>>) -> Self {
+        self.annotations = Some(v.into());
         self
     }
 
@@ -281,6 +316,8 @@ mod tests {
                 revision: 0,
                 max_instances: 1,
                 limits: None,
+                memory_bytes: None,
+                running_instances: None,
             },
             ComponentDescription::builder()
                 .id("id".into())
@@ -0,0 +1,389 @@
+//! Data types used when managing named configuration on a wasmCloud lattice
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest as _, Sha256};
+
+use crate::{IdentifierKind, Result};
+
+/// Prefix of the digest of a [`ConfigRef`] pinned to the content of a config
+const CONFIG_DIGEST_PREFIX: &str = "sha256:";
+
+/// A single observed revision of a named configuration stored in the lattice config bucket.
+///
+/// A revision without data indicates that the configuration was deleted.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ConfigRevision {
+    /// Name of the configuration
+    pub(crate) name: String,
+    /// Revision of the configuration in the backing store. Revisions increase monotonically.
+    pub(crate) revision: u64,
+    /// Contents of the configuration, or `None` if the configuration was deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) config: Option<HashMap<String, String>>,
+}
+
+impl ConfigRevision {
+    /// Create a [`ConfigRevision`] from a name, revision and (optional) config contents
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        revision: u64,
+        config: Option<HashMap<String, String>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            revision,
+            config,
+        }
+    }
+
+    /// Get the name of the configuration
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the revision of the configuration in the backing store
+    #[must_use]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Get the contents of the configuration, if it was not deleted
+    #[must_use]
+    pub fn config(&self) -> Option<&HashMap<String, String>> {
+        self.config.as_ref()
+    }
+
+    /// Whether this revision represents the deletion of the configuration
+    #[must_use]
+    pub fn is_deleted(&self) -> bool {
+        self.config.is_none()
+    }
+
+    /// Take the contents of the configuration
+    #[must_use]
+    pub fn into_config(self) -> Option<HashMap<String, String>> {
+        self.config
+    }
+}
+
+/// A set of config names to fetch in a single request
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ConfigNames {
+    /// Names of the configs to fetch
+    pub(crate) names: Vec<String>,
+}
+
+impl ConfigNames {
+    /// Create a [`ConfigNames`] from a list of config names
+    pub fn from_names(names: Vec<String>) -> Self {
+        Self { names }
+    }
+
+    /// Get the names of the configs to fetch
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+/// A change to the lattice config bucket, as observed by
+/// [`Client::config_watcher`](crate::Client::config_watcher)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ConfigChange {
+    /// A config was created or updated
+    Put {
+        /// Name of the config
+        name: String,
+        /// Revision of the config in the config bucket
+        revision: u64,
+        /// Contents of the config
+        config: HashMap<String, String>,
+    },
+    /// A config was deleted
+    Delete {
+        /// Name of the config
+        name: String,
+        /// Revision of the deletion in the config bucket
+        revision: u64,
+    },
+}
+
+impl ConfigChange {
+    /// Get the name of the changed config
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Put { name, .. } | Self::Delete { name, .. } => name,
+        }
+    }
+
+    /// Get the revision of the change in the config bucket
+    #[must_use]
+    pub fn revision(&self) -> u64 {
+        match self {
+            Self::Put { revision, .. } | Self::Delete { revision, .. } => *revision,
+        }
+    }
+}
+
+/// The version of a named config a [`ConfigRef`] is pinned to
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigVersion {
+    /// A revision of the config in the lattice config bucket
+    Revision(u64),
+    /// The hex-encoded SHA-256 digest of the config content, see [`ConfigRef::digest_of`]
+    Digest(String),
+}
+
+/// A reference to a named config, optionally pinned to a version of the config.
+///
+/// References are written as `name`, `name@<revision>` or `name@sha256:<digest>`. A pinned
+/// reference only resolves if the config matches the pinned version, which allows rolling out
+/// workloads against exactly the configuration that was reviewed.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConfigRef {
+    name: String,
+    version: Option<ConfigVersion>,
+}
+
+impl ConfigRef {
+    /// Create a reference to the current version of the named config
+    pub fn new(name: impl AsRef<str>) -> Result<Self> {
+        let name = IdentifierKind::is_config_name(name)?;
+        if name.contains(['.', '>', '*', '@']) || name.contains(char::is_whitespace) {
+            return Err(format!("invalid config name `{name}`").into());
+        }
+        Ok(Self {
+            name,
+            version: None,
+        })
+    }
+
+    /// Parse a reference of the form `name`, `name@<revision>` or `name@sha256:<digest>`
+    pub fn parse(value: impl AsRef<str>) -> Result<Self> {
+        let value = value.as_ref().trim();
+        let Some((name, version)) = value.split_once('@') else {
+            return Self::new(value);
+        };
+        let config_ref = Self::new(name)?;
+        if let Some(digest) = version.strip_prefix(CONFIG_DIGEST_PREFIX) {
+            config_ref.with_digest(digest)
+        } else {
+            let revision = version
+                .parse()
+                .map_err(|_| format!("invalid version `{version}` of config `{name}`"))?;
+            Ok(config_ref.with_revision(revision))
+        }
+    }
+
+    /// Pin the reference to a revision of the config in the lattice config bucket
+    #[must_use]
+    pub fn with_revision(self, revision: u64) -> Self {
+        Self {
+            version: Some(ConfigVersion::Revision(revision)),
+            ..self
+        }
+    }
+
+    /// Pin the reference to the content of the config, identified by its hex-encoded SHA-256
+    /// digest
+    pub fn with_digest(self, digest: impl AsRef<str>) -> Result<Self> {
+        let digest = digest.as_ref().to_ascii_lowercase();
+        if digest.len() != 64 || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("invalid digest `{digest}` of config `{}`", self.name).into());
+        }
+        Ok(Self {
+            version: Some(ConfigVersion::Digest(digest)),
+            ..self
+        })
+    }
+
+    /// Compute the digest of the content of a config, which is the SHA-256 digest of the config
+    /// serialized as a JSON object with sorted keys
+    #[must_use]
+    pub fn digest_of(config: &HashMap<String, String>) -> String {
+        let sorted: BTreeMap<_, _> = config.iter().collect();
+        let json = serde_json::to_vec(&sorted).unwrap_or_default();
+        hex::encode(Sha256::digest(json))
+    }
+
+    /// Get the name of the referenced config
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the version the reference is pinned to, if any
+    #[must_use]
+    pub fn version(&self) -> Option<&ConfigVersion> {
+        self.version.as_ref()
+    }
+}
+
+impl Display for ConfigRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version {
+            None => f.write_str(&self.name),
+            Some(ConfigVersion::Revision(revision)) => write!(f, "{}@{revision}", self.name),
+            Some(ConfigVersion::Digest(digest)) => {
+                write!(f, "{}@{CONFIG_DIGEST_PREFIX}{digest}", self.name)
+            }
+        }
+    }
+}
+
+impl FromStr for ConfigRef {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl Serialize for ConfigRef {
+    fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigRef {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::parse(value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Named configs passed to [`Client::scale_component`](crate::Client::scale_component) and
+/// [`Client::start_provider`](crate::Client::start_provider).
+///
+/// Config refs can be created from a list of [`ConfigRef`]s, or, for compatibility, from a list
+/// of config refs in their string form, which are validated when they are used. Plain config
+/// names are valid config refs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConfigRefs(Vec<String>);
+
+impl ConfigRefs {
+    /// Validate the config refs
+    pub fn parse(&self) -> Result<Vec<ConfigRef>> {
+        self.0.iter().map(ConfigRef::parse).collect()
+    }
+}
+
+impl From<Vec<String>> for ConfigRefs {
+    fn from(refs: Vec<String>) -> Self {
+        Self(refs)
+    }
+}
+
+impl From<&[ConfigRef]> for ConfigRefs {
+    fn from(refs: &[ConfigRef]) -> Self {
+        refs.iter().cloned().collect()
+    }
+}
+
+impl<const N: usize> From<[ConfigRef; N]> for ConfigRefs {
+    fn from(refs: [ConfigRef; N]) -> Self {
+        refs.into_iter().collect()
+    }
+}
+
+impl FromIterator<ConfigRef> for ConfigRefs {
+    fn from_iter<T: IntoIterator<Item = ConfigRef>>(iter: T) -> Self {
+        Self(iter.into_iter().map(|r| r.to_string()).collect())
+    }
+}
+
+/// Named configs fetched in a single request, keyed by config name. Configs that do not exist are
+/// present with a value of `None`.
+pub type ConfigsByName = HashMap<String, Option<HashMap<String, String>>>;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{ConfigRef, ConfigRefs, ConfigRevision, ConfigVersion};
+
+    #[test]
+    fn config_revision_roundtrip() {
+        let revision = ConfigRevision::new(
+            "my-config",
+            3,
+            Some(HashMap::from([("key".to_string(), "value".to_string())])),
+        );
+        let json = serde_json::to_string(&revision).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ConfigRevision>(&json).unwrap(),
+            revision
+        );
+        assert!(!revision.is_deleted());
+
+        let deleted: ConfigRevision =
+            serde_json::from_str(r#"{"name":"my-config","revision":4}"#).unwrap();
+        assert!(deleted.is_deleted());
+        assert_eq!(deleted.revision(), 4);
+    }
+
+    #[test]
+    fn config_ref_parse() {
+        let name = ConfigRef::parse("my-config").unwrap();
+        assert_eq!(name.name(), "my-config");
+        assert_eq!(name.version(), None);
+
+        let pinned = ConfigRef::parse("my-config@3").unwrap();
+        assert_eq!(pinned.version(), Some(&ConfigVersion::Revision(3)));
+        assert_eq!(pinned.to_string(), "my-config@3");
+
+        let config = HashMap::from([("key".to_string(), "value".to_string())]);
+        let digest = ConfigRef::digest_of(&config);
+        let pinned = ConfigRef::parse(format!("my-config@sha256:{digest}")).unwrap();
+        assert_eq!(
+            pinned,
+            ConfigRef::new("my-config")
+                .unwrap()
+                .with_digest(&digest)
+                .unwrap()
+        );
+        assert_eq!(
+            serde_json::from_str::<ConfigRef>(&serde_json::to_string(&pinned).unwrap()).unwrap(),
+            pinned
+        );
+
+        assert!(ConfigRef::parse("").is_err());
+        assert!(ConfigRef::parse("my.config").is_err());
+        assert!(ConfigRef::parse("my-config@").is_err());
+        assert!(ConfigRef::parse("my-config@sha256:abc").is_err());
+
+        let refs = ConfigRefs::from(vec!["a".to_string(), "b@1".to_string()]);
+        assert_eq!(refs.parse().unwrap().len(), 2);
+        assert!(ConfigRefs::from(vec!["a@b".to_string()]).parse().is_err());
+    }
+
+    #[test]
+    fn config_digest_is_stable() {
+        let a = HashMap::from([
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string()),
+        ]);
+        let b = HashMap::from([
+            ("b".to_string(), "2".to_string()),
+            ("a".to_string(), "1".to_string()),
+        ]);
+        assert_eq!(ConfigRef::digest_of(&a), ConfigRef::digest_of(&b));
+        assert_eq!(ConfigRef::digest_of(&a).len(), 64);
+        assert_ne!(
+            ConfigRef::digest_of(&a),
+            ConfigRef::digest_of(&HashMap::new())
+        );
+    }
+}
@@ -1,11 +1,68 @@
 //! Data types used when interacting with the control interface of a wasmCloud lattice
 
 use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
+use nkeys::KeyPair;
 use serde::{Deserialize, Serialize};
 
+use crate::types::config::ConfigRef;
+use crate::types::host::DataCategory;
 use crate::Result;
 
+/// Header carrying the public nkey identifying the sender of a request, which hosts use to rate
+/// limit requests per sender. Hosts only honour it if the request carries a valid
+/// [SENDER_SIGNATURE_HEADER], other requests are identified by their reply subject
+pub const SENDER_HEADER: &str = "Wasmcloud-Sender";
+
+/// Header carrying the signature of a request by the key in its [SENDER_HEADER], see [sign_sender]
+pub const SENDER_SIGNATURE_HEADER: &str = "Wasmcloud-Sender-Signature";
+
+/// Header carrying the ID of a control interface command, a ULID in the format produced by
+/// `wasmcloud_core::id`. Hosts log the handling of the command under this ID, so that it can be
+/// correlated with the logs of the client that sent it
+pub const COMMAND_ID_HEADER: &str = "Wasmcloud-Command-Id";
+
+/// Sign the request with the [COMMAND_ID_HEADER] `command_id` sent on `subject` as `key`,
+/// returning the value of its [SENDER_SIGNATURE_HEADER]
+pub fn sign_sender(key: &KeyPair, subject: &str, command_id: &str) -> Result<String> {
+    let signature = key.sign(&sender_signature_data(subject, command_id))?;
+    Ok(hex::encode(signature))
+}
+
+/// Returns the sender of a request received on `subject`, if its [SENDER_HEADER] is signed by the
+/// key of the sender (see [sign_sender])
+#[must_use]
+pub fn verified_sender<'a>(subject: &str, headers: &'a async_nats::HeaderMap) -> Option<&'a str> {
+    let sender = headers.get(SENDER_HEADER)?.as_str();
+    let command_id = headers.get(COMMAND_ID_HEADER)?.as_str();
+    let signature = hex::decode(headers.get(SENDER_SIGNATURE_HEADER)?.as_str()).ok()?;
+    KeyPair::from_public_key(sender)
+        .ok()?
+        .verify(&sender_signature_data(subject, command_id), &signature)
+        .ok()?;
+    Some(sender)
+}
+
+/// Data signed by the sender of a request. Command IDs are unique, so signatures can't be reused
+/// for other requests
+fn sender_signature_data(subject: &str, command_id: &str) -> Vec<u8> {
+    format!("{subject}\n{command_id}").into_bytes()
+}
+
+/// Machine-readable reason of an unsuccessful [`CtlResponse`]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum CtlErrorCode {
+    /// The host refused the request because the sender exceeded its rate limit. The request may be
+    /// retried after [`CtlResponse::retry_after`]
+    RateLimited,
+    /// A code not known to this version of the control interface
+    #[serde(other)]
+    Unknown,
+}
+
 /// A control interface response that wraps a response payload, a success flag, and a message
 /// with additional context if necessary.
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -18,6 +75,16 @@ pub struct CtlResponse<T> {
     /// The response data, if any
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) response: Option<T>,
+    /// Revision of the lattice KV bucket the response data reflects, if the data was read from
+    /// one. The data reflects at least all changes up to and including this revision
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) revision: Option<u64>,
+    /// Machine-readable reason of the failure, if the request failed for a well-known reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) code: Option<CtlErrorCode>,
+    /// Milliseconds after which a refused request may be retried
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) retry_after_ms: Option<u64>,
 }
 
 impl<T> CtlResponse<T> {
@@ -28,6 +95,29 @@ impl<T> CtlResponse<T> {
             success: true,
             message: String::new(),
             response: Some(response),
+            revision: None,
+            code: None,
+            retry_after_ms: None,
+        }
+    }
+
+    /// Mark the response as unsuccessful with the given message while keeping its data, e.g. to
+    /// report the parts of a request that succeeded along with the ones that failed
+    #[must_use]
+    pub fn with_error(self, message: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            message: message.into(),
+            ..self
+        }
+    }
+
+    /// Set the revision of the lattice KV bucket the response data reflects
+    #[must_use]
+    pub fn with_revision(self, revision: u64) -> Self {
+        Self {
+            revision: Some(revision),
+            ..self
         }
     }
 
@@ -54,6 +144,30 @@ impl<T> CtlResponse<T> {
     pub fn into_data(self) -> Option<T> {
         self.response
     }
+
+    /// Get the revision of the lattice KV bucket the response data reflects, if any
+    #[must_use]
+    pub fn revision(&self) -> Option<u64> {
+        self.revision
+    }
+
+    /// Get the machine-readable reason of the failure, if any
+    #[must_use]
+    pub fn code(&self) -> Option<CtlErrorCode> {
+        self.code
+    }
+
+    /// Get whether the host refused the request because the sender exceeded its rate limit
+    #[must_use]
+    pub fn is_rate_limited(&self) -> bool {
+        self.code == Some(CtlErrorCode::RateLimited)
+    }
+
+    /// Get the duration after which a refused request may be retried, if the host indicated one
+    #[must_use]
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after_ms.map(Duration::from_millis)
+    }
 }
 
 impl CtlResponse<()> {
@@ -65,6 +179,9 @@ impl CtlResponse<()> {
             success: true,
             message,
             response: None,
+            revision: None,
+            code: None,
+            retry_after_ms: None,
         }
     }
 
@@ -77,8 +194,52 @@ impl CtlResponse<()> {
             success: false,
             message: message.to_string(),
             response: None,
+            revision: None,
+            code: None,
+            retry_after_ms: None,
         }
     }
+
+    /// Helper function to return an unsuccessful response refusing a request of a sender, which
+    /// exceeded its rate limit. The request may be retried after `retry_after`
+    #[must_use]
+    pub fn rate_limited(retry_after: Duration) -> Self {
+        let retry_after_ms = u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX);
+        CtlResponse {
+            success: false,
+            message: format!("rate limit exceeded, retry after {retry_after_ms}ms"),
+            response: None,
+            revision: None,
+            code: Some(CtlErrorCode::RateLimited),
+            retry_after_ms: Some(retry_after_ms),
+        }
+    }
+}
+
+/// A request for data read from a lattice KV bucket, which must reflect at least all changes up to
+/// and including the given revision of the bucket.
+///
+/// This allows controllers to reason about causality between their reads and the changes they
+/// made or the events they observed, even if the host serving the read lags behind.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct RevisionQuery {
+    /// Minimum revision of the bucket the data must reflect
+    #[serde(default)]
+    pub(crate) min_revision: u64,
+}
+
+impl RevisionQuery {
+    /// Create a [`RevisionQuery`] for data at or after the given revision
+    #[must_use]
+    pub fn at_or_after(min_revision: u64) -> Self {
+        Self { min_revision }
+    }
+
+    #[must_use]
+    pub fn min_revision(&self) -> u64 {
+        self.min_revision
+    }
 }
 
 /// Command a host to scale a component
@@ -112,6 +273,10 @@ pub struct ScaleComponentCommand {
     /// 6}
     #[serde(default)]
     pub(crate) config: Vec<String>,
+    /// Configs of `config` that are pinned to a version. Hosts refuse the command unless the
+    /// configs they read match the pinned versions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) pinned_config: Vec<ConfigRef>,
     #[serde(default)]
     /// Whether to perform an update if the details of the component (ex. component ID) change as
     /// part of the scale request.
@@ -142,6 +307,11 @@ impl ScaleComponentCommand {
         &self.config
     }
 
+    #[must_use]
+    pub fn pinned_config(&self) -> &[ConfigRef] {
+        &self.pinned_config
+    }
+
     #[must_use]
     pub fn annotations(&self) -> Option<&BTreeMap<String, String>> {
         self.annotations.as_ref()
@@ -179,6 +349,7 @@ pub struct ScaleComponentCommandBuilder {
     component_limits: Option<HashMap<String, String>>,
     host_id: Option<String>,
     config: Option<Vec<String>>,
+    pinned_config: Option<Vec<ConfigRef>>,
     allow_update: Option<bool>,
 }
 
@@ -230,6 +401,12 @@ impl ScaleComponentCommandBuilder {
         self
     }
 
+    #[must_use]
+    pub fn pinned_config(mut self, v: Vec<ConfigRef>) -> Self {
+        self.pinned_config = Some(v);
+        self
+    }
+
     #[must_use]
     pub fn allow_update(mut self, v: bool) -> Self {
         self.allow_update = Some(v);
@@ -251,6 +428,7 @@ impl ScaleComponentCommandBuilder {
                 .host_id
                 .ok_or_else(|| "host id is required for scaling hosts host".to_string())?,
             config: self.config.unwrap_or_default(),
+            pinned_config: self.pinned_config.unwrap_or_default(),
             allow_update: self.allow_update.unwrap_or_default(),
         })
     }
@@ -276,6 +454,10 @@ pub struct StartProviderCommand {
     /// 6}
     #[serde(default)]
     config: Vec<String>,
+    /// Configs of `config` that are pinned to a version. Hosts refuse the command unless the
+    /// configs they read match the pinned versions
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pinned_config: Vec<ConfigRef>,
     /// Optional set of annotations used to describe the nature of this provider start command. For
     /// example, autonomous agents may wish to "tag" start requests as part of a given deployment
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -303,6 +485,11 @@ impl StartProviderCommand {
         &self.config
     }
 
+    #[must_use]
+    pub fn pinned_config(&self) -> &[ConfigRef] {
+        &self.pinned_config
+    }
+
     #[must_use]
     pub fn annotations(&self) -> Option<&BTreeMap<String, String>> {
         self.annotations.as_ref()
@@ -323,6 +510,7 @@ pub struct StartProviderCommandBuilder {
     provider_ref: Option<String>,
     annotations: Option<BTreeMap<String, String>>,
     config: Option<Vec<String>>,
+    pinned_config: Option<Vec<ConfigRef>>,
 }
 
 impl StartProviderCommandBuilder {
@@ -361,6 +549,12 @@ impl StartProviderCommandBuilder {
         self
     }
 
+    #[must_use]
+    pub fn pinned_config(mut self, v: Vec<ConfigRef>) -> Self {
+        self.pinned_config = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<StartProviderCommand> {
         Ok(StartProviderCommand {
             provider_ref: self
@@ -374,6 +568,7 @@ impl StartProviderCommandBuilder {
                 .host_id
                 .ok_or_else(|| "host id is required for starting providers".to_string())?,
             config: self.config.unwrap_or_default(),
+            pinned_config: self.pinned_config.unwrap_or_default(),
         })
     }
 }
@@ -388,6 +583,10 @@ pub struct StopHostCommand {
     /// An optional timeout, in seconds
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub(crate) timeout: Option<u64>,
+    /// Whether the host is stopped permanently, publishing a decommission record and removing
+    /// the data it keeps about itself
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub(crate) decommission: bool,
 }
 
 impl StopHostCommand {
@@ -401,6 +600,11 @@ impl StopHostCommand {
         self.timeout
     }
 
+    #[must_use]
+    pub fn decommission(&self) -> bool {
+        self.decommission
+    }
+
     #[must_use]
     pub fn builder() -> StopHostCommandBuilder {
         StopHostCommandBuilder::default()
@@ -412,6 +616,7 @@ impl StopHostCommand {
 pub struct StopHostCommandBuilder {
     host_id: Option<String>,
     timeout: Option<u64>,
+    decommission: bool,
 }
 
 impl StopHostCommandBuilder {
@@ -432,16 +637,302 @@ impl StopHostCommandBuilder {
         self
     }
 
+    #[must_use]
+    pub fn decommission(mut self, v: bool) -> Self {
+        self.decommission = v;
+        self
+    }
+
     pub fn build(self) -> Result<StopHostCommand> {
         Ok(StopHostCommand {
             host_id: self
                 .host_id
                 .ok_or_else(|| "host id is required for stopping host".to_string())?,
             timeout: self.timeout,
+            decommission: self.decommission,
         })
     }
 }
 
+/// Options controlling how a host is drained, see [`Client::drain_host`](crate::Client::drain_host)
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct DrainOptions {
+    /// Amount of time, in milliseconds, to wait for workloads to be rescheduled elsewhere before
+    /// the drain deadline passes. When unset, the host waits indefinitely.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout_ms: Option<u64>,
+    /// Whether to stop the components and providers still running on the host once the deadline
+    /// passes. Otherwise they are left running, while the host keeps declining new workloads.
+    #[serde(default)]
+    pub(crate) force_stop: bool,
+}
+
+impl DrainOptions {
+    #[must_use]
+    pub fn timeout_ms(&self) -> Option<u64> {
+        self.timeout_ms
+    }
+
+    #[must_use]
+    pub fn force_stop(&self) -> bool {
+        self.force_stop
+    }
+
+    #[must_use]
+    pub fn builder() -> DrainOptionsBuilder {
+        DrainOptionsBuilder::default()
+    }
+}
+
+/// Builder for [`DrainOptions`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DrainOptionsBuilder {
+    timeout_ms: Option<u64>,
+    force_stop: bool,
+}
+
+impl DrainOptionsBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn timeout_ms(mut self, v: u64) -> Self {
+        self.timeout_ms = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn force_stop(mut self, v: bool) -> Self {
+        self.force_stop = v;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> DrainOptions {
+        DrainOptions {
+            timeout_ms: self.timeout_ms,
+            force_stop: self.force_stop,
+        }
+    }
+}
+
+/// A command sent to request that the given host stops accepting new workloads and drains its
+/// running components and providers
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct DrainHostCommand {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Options controlling the drain
+    #[serde(flatten)]
+    pub(crate) options: DrainOptions,
+}
+
+impl DrainHostCommand {
+    /// Create a [`DrainHostCommand`] for the given host
+    #[must_use]
+    pub fn new(host_id: &str, options: DrainOptions) -> Self {
+        Self {
+            host_id: host_id.into(),
+            options,
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn options(&self) -> &DrainOptions {
+        &self.options
+    }
+}
+
+/// A command sent to request that the given host prefetches images into its artifact cache and
+/// keeps them warm, so that the first workloads started from them do not pay for a registry pull
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct PrefetchImagesCommand {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// OCI references of the images to prefetch
+    #[serde(default)]
+    pub(crate) image_refs: Vec<String>,
+}
+
+impl PrefetchImagesCommand {
+    /// Create a [`PrefetchImagesCommand`] for the given host and images
+    #[must_use]
+    pub fn new(host_id: &str, image_refs: Vec<String>) -> Self {
+        Self {
+            host_id: host_id.into(),
+            image_refs,
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn image_refs(&self) -> &[String] {
+        &self.image_refs
+    }
+}
+
+/// A command sent to a host requesting that every component whose annotations match the given
+/// annotations is scaled to zero
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct StopComponentsCommand {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Annotations that components must all have, with equal values, to be stopped
+    #[serde(default)]
+    pub(crate) annotations: BTreeMap<String, String>,
+}
+
+impl StopComponentsCommand {
+    /// Create a [`StopComponentsCommand`] for the given host and annotation selector
+    #[must_use]
+    pub fn new(host_id: &str, annotations: BTreeMap<String, String>) -> Self {
+        Self {
+            host_id: host_id.into(),
+            annotations,
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn annotations(&self) -> &BTreeMap<String, String> {
+        &self.annotations
+    }
+
+    /// Returns whether a component with the given annotations is selected by this command
+    #[must_use]
+    pub fn matches(&self, annotations: &BTreeMap<String, String>) -> bool {
+        self.annotations
+            .iter()
+            .all(|(k, v)| annotations.get(k) == Some(v))
+    }
+}
+
+/// A command sent to request that the given host removes data from its data directory, e.g. to
+/// reclaim disk space
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct CleanupHostDataCommand {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Categories of data to remove, all categories if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) categories: Vec<DataCategory>,
+}
+
+impl CleanupHostDataCommand {
+    /// Create a [`CleanupHostDataCommand`] for the given host and categories of data
+    #[must_use]
+    pub fn new(host_id: &str, categories: Vec<DataCategory>) -> Self {
+        Self {
+            host_id: host_id.into(),
+            categories,
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn categories(&self) -> &[DataCategory] {
+        &self.categories
+    }
+}
+
+/// A command sent to request that the given host changes the level and sampler of the traces it
+/// emits, without restarting. Settings that are not set are left unchanged
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct UpdateHostTracingCommand {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Level of the traces to emit, e.g. `debug`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) trace_level: Option<String>,
+    /// Sampler of the traces to emit, e.g. `parentbased_traceidratio`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) traces_sampler: Option<String>,
+    /// Argument of the sampler, e.g. the ratio of traces to sample
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) traces_sampler_arg: Option<String>,
+}
+
+impl UpdateHostTracingCommand {
+    /// Create an [`UpdateHostTracingCommand`] for the given host, leaving all settings unchanged
+    #[must_use]
+    pub fn new(host_id: &str) -> Self {
+        Self {
+            host_id: host_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the level of the traces to emit
+    #[must_use]
+    pub fn with_trace_level(mut self, trace_level: impl Into<String>) -> Self {
+        self.trace_level = Some(trace_level.into());
+        self
+    }
+
+    /// Set the sampler of the traces to emit, along with its argument if any
+    #[must_use]
+    pub fn with_traces_sampler(
+        mut self,
+        traces_sampler: impl Into<String>,
+        traces_sampler_arg: Option<String>,
+    ) -> Self {
+        self.traces_sampler = Some(traces_sampler.into());
+        self.traces_sampler_arg = traces_sampler_arg;
+        self
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn trace_level(&self) -> Option<&str> {
+        self.trace_level.as_deref()
+    }
+
+    #[must_use]
+    pub fn traces_sampler(&self) -> Option<&str> {
+        self.traces_sampler.as_deref()
+    }
+
+    #[must_use]
+    pub fn traces_sampler_arg(&self) -> Option<&str> {
+        self.traces_sampler_arg.as_deref()
+    }
+}
+
 /// A request to stop the given provider on the indicated host
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[non_exhaustive]
@@ -509,6 +1000,50 @@ impl StopProviderCommandBuilder {
     }
 }
 
+/// A command instructing a specific host to change the named configs consumed by a running
+/// capability provider, without restarting it
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct UpdateProviderConfigCommand {
+    /// The host ID on which the provider is running
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Unique identifier of the provider to update
+    #[serde(default)]
+    pub(crate) provider_id: String,
+    /// The new list of named configs to use for this provider, merged in the same way as the
+    /// configs given when starting the provider. An empty list removes all named configs.
+    #[serde(default)]
+    pub(crate) config: Vec<String>,
+}
+
+impl UpdateProviderConfigCommand {
+    /// Create an [`UpdateProviderConfigCommand`] for the given provider
+    #[must_use]
+    pub fn new(host_id: &str, provider_id: &str, config: Vec<String>) -> Self {
+        Self {
+            host_id: host_id.into(),
+            provider_id: provider_id.into(),
+            config,
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn provider_id(&self) -> &str {
+        &self.provider_id
+    }
+
+    #[must_use]
+    pub fn config(&self) -> &Vec<String> {
+        &self.config
+    }
+}
+
 /// A command instructing a specific host to perform a live update
 /// on the indicated component by supplying a new image reference. Note that
 /// live updates are only possible through image references
@@ -618,10 +1153,53 @@ impl UpdateComponentCommandBuilder {
 mod tests {
     use std::collections::BTreeMap;
 
+    use std::time::Duration;
+
+    use nkeys::KeyPair;
+
     use super::{
+        sign_sender, verified_sender, CtlErrorCode, CtlResponse, DrainHostCommand, DrainOptions,
         ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
-        UpdateComponentCommand,
+        UpdateComponentCommand, COMMAND_ID_HEADER, SENDER_HEADER, SENDER_SIGNATURE_HEADER,
     };
+    use crate::types::config::ConfigRef;
+
+    #[test]
+    fn sender_signatures() {
+        const SUBJECT: &str = "wasmbus.ctl.v1.default.host.ping";
+        let key = KeyPair::new_user();
+        let headers = |sender: &str, subject: &str, command_id: &str| {
+            let mut headers = async_nats::HeaderMap::new();
+            headers.insert(SENDER_HEADER, sender);
+            headers.insert(COMMAND_ID_HEADER, "command");
+            let signature = sign_sender(&key, subject, command_id).expect("failed to sign");
+            headers.insert(SENDER_SIGNATURE_HEADER, signature.as_str());
+            headers
+        };
+        let sender = key.public_key();
+        assert_eq!(
+            verified_sender(SUBJECT, &headers(&sender, SUBJECT, "command")),
+            Some(sender.as_str())
+        );
+        // Signatures only hold for the sender, subject and command they were made for
+        let other = KeyPair::new_user().public_key();
+        assert_eq!(
+            verified_sender(SUBJECT, &headers(&other, SUBJECT, "command")),
+            None
+        );
+        assert_eq!(
+            verified_sender("other", &headers(&sender, SUBJECT, "command")),
+            None
+        );
+        assert_eq!(
+            verified_sender(SUBJECT, &headers(&sender, SUBJECT, "other")),
+            None
+        );
+        let mut unsigned = async_nats::HeaderMap::new();
+        unsigned.insert(SENDER_HEADER, sender.as_str());
+        unsigned.insert(COMMAND_ID_HEADER, "command");
+        assert_eq!(verified_sender(SUBJECT, &unsigned), None);
+    }
 
     #[test]
     fn scale_component_command_builder() {
@@ -631,6 +1209,7 @@ mod tests {
                 component_id: "component_id".into(),
                 host_id: "host_id".into(),
                 config: vec!["c".into()],
+                pinned_config: vec![ConfigRef::parse("c@1").unwrap()],
                 allow_update: true,
                 annotations: Some(BTreeMap::from([("a".into(), "b".into())])),
                 max_instances: 1,
@@ -641,6 +1220,7 @@ mod tests {
                 .component_id("component_id")
                 .host_id("host_id")
                 .config(vec!["c".into()])
+                .pinned_config(vec![ConfigRef::parse("c@1").unwrap()])
                 .allow_update(true)
                 .annotations(BTreeMap::from([("a".into(), "b".into())]))
                 .max_instances(1)
@@ -657,6 +1237,7 @@ mod tests {
                 provider_ref: "provider_ref".into(),
                 host_id: "host_id".into(),
                 config: vec!["p".into()],
+                pinned_config: vec![ConfigRef::parse("p@1").unwrap()],
                 annotations: Some(BTreeMap::from([("a".into(), "b".into())])),
             },
             StartProviderCommand::builder()
@@ -664,6 +1245,7 @@ mod tests {
                 .provider_ref("provider_ref")
                 .host_id("host_id")
                 .config(vec!["p".into()])
+                .pinned_config(vec![ConfigRef::parse("p@1").unwrap()])
                 .annotations(BTreeMap::from([("a".into(), "b".into())]))
                 .build()
                 .unwrap()
@@ -676,13 +1258,63 @@ mod tests {
             StopHostCommand {
                 host_id: "host_id".into(),
                 timeout: Some(1),
+                decommission: false,
             },
             StopHostCommand::builder()
                 .host_id("host_id")
                 .timeout(1)
                 .build()
                 .unwrap()
-        )
+        );
+        let decommission = StopHostCommand::builder()
+            .host_id("host_id")
+            .decommission(true)
+            .build()
+            .unwrap();
+        assert!(decommission.decommission());
+        assert_eq!(
+            serde_json::to_value(&decommission).unwrap(),
+            serde_json::json!({ "host_id": "host_id", "decommission": true })
+        );
+        // Hosts that do not know about decommissioning receive the same command as before
+        assert_eq!(
+            serde_json::to_value(
+                StopHostCommand::builder()
+                    .host_id("host_id")
+                    .build()
+                    .unwrap()
+            )
+            .unwrap(),
+            serde_json::json!({ "host_id": "host_id" })
+        );
+    }
+
+    #[test]
+    fn drain_host_command_serde() {
+        let command = DrainHostCommand::new(
+            "host_id",
+            DrainOptions::builder()
+                .timeout_ms(1000)
+                .force_stop(true)
+                .build(),
+        );
+        let json = serde_json::to_value(&command).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "host_id": "host_id",
+                "timeout_ms": 1000,
+                "force_stop": true,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<DrainHostCommand>(json).unwrap(),
+            command
+        );
+        assert_eq!(
+            serde_json::from_str::<DrainHostCommand>(r#"{"host_id":"host_id"}"#).unwrap(),
+            DrainHostCommand::new("host_id", DrainOptions::default())
+        );
     }
 
     #[test]
@@ -718,4 +1350,31 @@ mod tests {
                 .unwrap()
         )
     }
+
+    #[test]
+    fn rate_limited_response_serde() {
+        let json = serde_json::to_value(CtlResponse::rate_limited(Duration::from_millis(250)))
+            .expect("failed to serialize response");
+        assert_eq!(json["success"], false);
+        assert_eq!(json["code"], "RATE_LIMITED");
+        assert_eq!(json["retry_after_ms"], 250);
+
+        // The response carries no data, so it can be decoded as the response of any request
+        let resp: CtlResponse<Vec<String>> =
+            serde_json::from_value(json).expect("failed to deserialize response");
+        assert!(!resp.succeeded());
+        assert!(resp.is_rate_limited());
+        assert_eq!(resp.retry_after(), Some(Duration::from_millis(250)));
+
+        let resp: CtlResponse<()> =
+            serde_json::from_str(r#"{"success":false,"message":"","code":"FROM_THE_FUTURE"}"#)
+                .expect("failed to deserialize response with unknown code");
+        assert_eq!(resp.code(), Some(CtlErrorCode::Unknown));
+        assert!(
+            serde_json::from_str::<CtlResponse<()>>(r#"{"success":true,"message":""}"#)
+                .expect("failed to deserialize response without code")
+                .code()
+                .is_none()
+        );
+    }
 }
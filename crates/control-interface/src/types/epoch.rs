@@ -0,0 +1,101 @@
+//! Lattice epochs, which fence off the commands of controllers that are no longer the leader
+
+use serde::{Deserialize, Serialize};
+
+/// Key in the lattice data bucket that stores the current [`LatticeEpoch`]
+pub const LATTICE_EPOCH_KEY: &str = "EPOCH_lattice";
+
+/// Header carrying the epoch of the controller that sent a control interface command. Hosts
+/// reject commands carrying an epoch older than the current epoch of the lattice
+pub const EPOCH_HEADER: &str = "wasmcloud-lattice-epoch";
+
+/// The current epoch of a lattice, advanced by a controller whenever it becomes the leader.
+///
+/// Commands sent by a [`Client`](crate::Client) that advanced or was configured with an epoch
+/// carry that epoch, and hosts reject commands with an epoch older than the current one, so that
+/// a controller that lost its leadership cannot interfere with the new leader.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LatticeEpoch {
+    /// The epoch, starting at 1 for the first leader
+    pub(crate) epoch: u64,
+    /// Identifier of the controller that advanced the lattice to this epoch, if provided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) holder: Option<String>,
+    /// When the epoch was advanced, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub(crate) advanced_at: u64,
+}
+
+impl LatticeEpoch {
+    /// Create a new [`LatticeEpoch`]
+    #[must_use]
+    pub fn new(epoch: u64, holder: Option<String>, advanced_at: u64) -> Self {
+        Self {
+            epoch,
+            holder,
+            advanced_at,
+        }
+    }
+
+    /// Get the epoch
+    #[must_use]
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Get the identifier of the controller that advanced the lattice to this epoch
+    #[must_use]
+    pub fn holder(&self) -> Option<&str> {
+        self.holder.as_deref()
+    }
+
+    /// Get when the epoch was advanced, in milliseconds since the Unix epoch
+    #[must_use]
+    pub fn advanced_at(&self) -> u64 {
+        self.advanced_at
+    }
+
+    /// Returns the epoch following this one, held by `holder`
+    #[must_use]
+    pub fn next(&self, holder: Option<String>, advanced_at: u64) -> Self {
+        Self::new(self.epoch.saturating_add(1), holder, advanced_at)
+    }
+}
+
+/// Returns the epoch carried by the [`EPOCH_HEADER`] of a control interface command, if any
+#[must_use]
+pub fn epoch_from_headers(headers: &async_nats::HeaderMap) -> Option<u64> {
+    headers.get(EPOCH_HEADER)?.as_str().trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_advances_and_roundtrips() {
+        let first = LatticeEpoch::default().next(Some("wadm-1".into()), 1000);
+        assert_eq!(first.epoch(), 1);
+        assert_eq!(first.holder(), Some("wadm-1"));
+        let second = first.next(None, 2000);
+        assert_eq!(second.epoch(), 2);
+        assert_eq!(second.holder(), None);
+
+        let json = serde_json::to_vec(&second).expect("failed to serialize");
+        assert_eq!(
+            serde_json::from_slice::<LatticeEpoch>(&json).expect("failed to deserialize"),
+            second
+        );
+    }
+
+    #[test]
+    fn epoch_from_headers_parses_header() {
+        let mut headers = async_nats::HeaderMap::new();
+        assert_eq!(epoch_from_headers(&headers), None);
+        headers.insert(EPOCH_HEADER, "42");
+        assert_eq!(epoch_from_headers(&headers), Some(42));
+        headers.insert(EPOCH_HEADER, "not-a-number");
+        assert_eq!(epoch_from_headers(&headers), None);
+    }
+}
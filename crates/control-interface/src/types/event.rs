@@ -0,0 +1,339 @@
+//! Matching of lattice events, used to wait for the outcome of control interface commands
+
+use core::fmt;
+
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use cloudevents::{AttributesReader as _, Data, Event, EventBuilder as _, EventBuilderV10};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Predicate applied to a field of the event data
+type FieldPredicate = Arc<dyn Fn(&serde_json::Value) -> bool + Send + Sync>;
+
+/// Matcher for lattice events, used with [`Client::wait_for_event`](crate::Client::wait_for_event).
+///
+/// All conditions of a matcher must hold for an event to match, e.g. the following matches the
+/// first `component_scaled` event for component `echo` on a given host that scaled it to 1
+/// instance:
+///
+/// ```rust
+/// use wasmcloud_control_interface::EventMatcher;
+///
+/// let matcher = EventMatcher::new("component_scaled")
+///     .host("NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC")
+///     .component("echo")
+///     .field("max_instances", 1);
+/// ```
+#[derive(Clone, Default)]
+#[must_use]
+pub struct EventMatcher {
+    event_types: BTreeSet<String>,
+    host_id: Option<String>,
+    fields: Vec<(String, FieldPredicate)>,
+}
+
+impl fmt::Debug for EventMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventMatcher")
+            .field("event_types", &self.event_types)
+            .field("host_id", &self.host_id)
+            .field(
+                "fields",
+                &self.fields.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl EventMatcher {
+    /// Create a matcher for events of the given type, without the CloudEvents namespace, e.g.
+    /// `component_scaled`
+    pub fn new(event_type: impl Into<String>) -> Self {
+        Self::default().or_event_type(event_type)
+    }
+
+    /// Additionally match events of the given type
+    pub fn or_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_types.insert(event_type.into());
+        self
+    }
+
+    /// Only match events emitted by the given host
+    pub fn host(mut self, host_id: impl Into<String>) -> Self {
+        self.host_id = Some(host_id.into());
+        self
+    }
+
+    /// Only match events about the given component, i.e. with a `component_id` data field
+    pub fn component(self, component_id: impl Into<String>) -> Self {
+        self.field("component_id", component_id.into())
+    }
+
+    /// Only match events about the given provider, i.e. with a `provider_id` data field
+    pub fn provider(self, provider_id: impl Into<String>) -> Self {
+        self.field("provider_id", provider_id.into())
+    }
+
+    /// Only match events whose data field at `path` equals `value`. Nested fields are separated
+    /// by dots, e.g. `annotations.app`
+    pub fn field(self, path: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        let value = value.into();
+        self.field_matches(path, move |field| *field == value)
+    }
+
+    /// Only match events whose data has a field at `path` satisfying `predicate`. Nested fields are
+    /// separated by dots, e.g. `annotations.app`
+    pub fn field_matches(
+        mut self,
+        path: impl Into<String>,
+        predicate: impl Fn(&serde_json::Value) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.fields.push((path.into(), Arc::new(predicate)));
+        self
+    }
+
+    /// Get the event types matched by this matcher
+    #[must_use]
+    pub fn event_types(&self) -> &BTreeSet<String> {
+        &self.event_types
+    }
+
+    /// Returns whether the given event matches
+    #[must_use]
+    pub fn matches(&self, event: &LatticeEvent) -> bool {
+        self.event_types.contains(event.event_type())
+            && self
+                .host_id
+                .as_ref()
+                .is_none_or(|host_id| host_id == event.source())
+            && self
+                .fields
+                .iter()
+                .all(|(path, predicate)| event.field(path).is_some_and(|value| predicate(value)))
+    }
+}
+
+/// A lattice event with JSON data, as returned by
+/// [`Client::wait_for_event`](crate::Client::wait_for_event)
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LatticeEvent {
+    pub(crate) event_type: String,
+    pub(crate) data: serde_json::Value,
+    pub(crate) event: Event,
+}
+
+impl LatticeEvent {
+    /// Get the event type without the CloudEvents namespace, e.g. `component_scaled`
+    #[must_use]
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// Get the source of the event, which is the ID of the host that emitted it
+    #[must_use]
+    pub fn source(&self) -> &str {
+        self.event.source().as_str()
+    }
+
+    /// Get the event data
+    #[must_use]
+    pub fn data(&self) -> &serde_json::Value {
+        &self.data
+    }
+
+    /// Get the data field at `path`, with nested fields separated by dots
+    #[must_use]
+    pub fn field(&self, path: &str) -> Option<&serde_json::Value> {
+        path.split('.')
+            .try_fold(&self.data, |value, key| value.get(key))
+    }
+
+    /// Deserialize the event data into `T`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data does not match `T`
+    pub fn data_as<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_value(self.data.clone())?)
+    }
+
+    /// Get a reference to the underlying CloudEvent
+    #[must_use]
+    pub fn as_event(&self) -> &Event {
+        &self.event
+    }
+
+    /// Get the underlying CloudEvent
+    #[must_use]
+    pub fn into_event(self) -> Event {
+        self.event
+    }
+}
+
+impl TryFrom<Event> for LatticeEvent {
+    type Error = Event;
+
+    /// Convert a CloudEvent with JSON data, returning the event back if it has none
+    fn try_from(event: Event) -> core::result::Result<Self, Event> {
+        let data = match event.data() {
+            Some(Data::Json(data)) => data.clone(),
+            _ => return Err(event),
+        };
+        let event_type = event
+            .ty()
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self {
+            event_type,
+            data,
+            event,
+        })
+    }
+}
+
+/// Default name of the JetStream stream retaining the lattice events replayed by
+/// [`Client::replay_events`](crate::Client::replay_events), which is created by
+/// [`Client::create_event_stream`](crate::Client::create_event_stream)
+pub const DEFAULT_EVENT_STREAM: &str = "wasmbus_events";
+
+/// Event type of the [`EventStreamGap`] markers emitted by
+/// [`Client::events_receiver`](crate::Client::events_receiver)
+pub const EVENT_STREAM_GAP_TYPE: &str = "com.wasmcloud.lattice.event_stream_gap";
+
+/// Source of [`EventStreamGap`] markers, which are emitted by the client rather than a host
+pub const EVENT_STREAM_GAP_SOURCE: &str = "wasmcloud-control-interface";
+
+/// Counter used to give every gap marker emitted by this process a unique event ID
+static EVENT_STREAM_GAP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Why lattice events may have been missed, see [`EventStreamGap`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum EventStreamGapReason {
+    /// The connection to the lattice was lost and has been re-established
+    Reconnected,
+    /// The event subscription ended and was re-established
+    Resubscribed,
+}
+
+/// Synthetic marker inserted into the stream returned by
+/// [`Client::events_receiver`](crate::Client::events_receiver) when lattice events may have been
+/// missed, e.g. after a NATS reconnect.
+///
+/// Consumers that derive state from events should treat a gap as a signal to re-query the lattice
+/// rather than rely on the events they have seen so far.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct EventStreamGap {
+    /// The lattice the event stream belongs to
+    pub lattice: String,
+    /// Why events may have been missed
+    pub reason: EventStreamGapReason,
+}
+
+impl EventStreamGap {
+    /// Create a new gap marker for the given lattice
+    #[must_use]
+    pub fn new(lattice: impl Into<String>, reason: EventStreamGapReason) -> Self {
+        Self {
+            lattice: lattice.into(),
+            reason,
+        }
+    }
+
+    /// Convert the marker to a CloudEvent of type [`EVENT_STREAM_GAP_TYPE`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event could not be built
+    pub fn to_event(&self) -> Result<Event> {
+        let seq = EVENT_STREAM_GAP_SEQ.fetch_add(1, Ordering::Relaxed);
+        Ok(EventBuilderV10::new()
+            .id(format!("event-stream-gap-{seq}"))
+            .source(EVENT_STREAM_GAP_SOURCE)
+            .ty(EVENT_STREAM_GAP_TYPE)
+            .data("application/json", serde_json::to_value(self)?)
+            .build()?)
+    }
+
+    /// Parse a gap marker from a CloudEvent, returning `None` if the event is not a gap marker
+    #[must_use]
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.ty() != EVENT_STREAM_GAP_TYPE {
+            return None;
+        }
+        match event.data() {
+            Some(Data::Json(data)) => serde_json::from_value(data.clone()).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use std::collections::BTreeMap;
+
+    use super::EventMatcher;
+    use crate::testing::MockLattice;
+    use crate::Result;
+
+    const HOST_ID: &str = "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC";
+    const COMPONENT_REF: &str = "ghcr.io/wasmcloud/components/http-hello-world-rust:0.1.0";
+
+    #[tokio::test]
+    async fn wait_for_matching_event() -> Result<()> {
+        let lattice = MockLattice::new("default");
+        lattice.add_host_with_labels(HOST_ID, BTreeMap::new());
+        let client = lattice.client();
+
+        let matcher = EventMatcher::new("component_scaled")
+            .host(HOST_ID)
+            .component("echo")
+            .field("max_instances", 2)
+            .field_matches("image_ref", |v| v.as_str() == Some(COMPONENT_REF));
+        let wait = client.wait_for_event(matcher, Duration::from_secs(5));
+        let scale = async {
+            // Events for other components or instance counts must not match
+            client
+                .scale_component(HOST_ID, COMPONENT_REF, "other", 2, None, vec![])
+                .await?;
+            client
+                .scale_component(HOST_ID, COMPONENT_REF, "echo", 1, None, vec![])
+                .await?;
+            client
+                .scale_component(HOST_ID, COMPONENT_REF, "echo", 2, None, vec![])
+                .await
+        };
+        let (event, ack) = tokio::join!(wait, scale);
+        assert!(ack?.succeeded());
+        let event = event?;
+        assert_eq!(event.event_type(), "component_scaled");
+        assert_eq!(event.source(), HOST_ID);
+        assert_eq!(event.data_as::<serde_json::Value>()?["max_instances"], 2);
+        assert_eq!(
+            event.field("component_id").and_then(|v| v.as_str()),
+            Some("echo")
+        );
+
+        let err = client
+            .wait_for_event(
+                EventMatcher::new("component_scaled").host("NOTAHOST"),
+                Duration::from_millis(50),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        Ok(())
+    }
+}
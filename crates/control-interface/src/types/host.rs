@@ -243,6 +243,14 @@ pub struct HostInventory {
     /// The host uptime in seconds
     #[serde(default)]
     pub(crate) uptime_seconds: u64,
+
+    /// Images the host prefetches and keeps warm in its artifact cache
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) prefetched_images: Vec<PrefetchedImage>,
+
+    /// Disk usage of the host data directory, if the host manages one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) data_dir: Option<DataDirUsage>,
 }
 
 impl HostInventory {
@@ -286,6 +294,16 @@ impl HostInventory {
         self.uptime_seconds
     }
 
+    /// Get the images the host prefetches, along with the status of their prefetch
+    pub fn prefetched_images(&self) -> &Vec<PrefetchedImage> {
+        &self.prefetched_images
+    }
+
+    /// Get the disk usage of the host data directory, if the host manages one
+    pub fn data_dir(&self) -> Option<&DataDirUsage> {
+        self.data_dir.as_ref()
+    }
+
     #[must_use]
     pub fn builder() -> HostInventoryBuilder {
         HostInventoryBuilder::default()
@@ -303,6 +321,8 @@ pub struct HostInventoryBuilder {
     version: Option<String>,
     uptime_human: Option<String>,
     uptime_seconds: Option<u64>,
+    prefetched_images: Option<Vec<PrefetchedImage>>,
+    data_dir: Option<DataDirUsage>,
 }
 
 impl HostInventoryBuilder {
@@ -354,6 +374,18 @@ impl HostInventoryBuilder {
         self
     }
 
+    #[must_use]
+    pub fn prefetched_images(mut self, v: Vec<PrefetchedImage>) -> Self {
+        self.prefetched_images = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn data_dir(mut self, v: DataDirUsage) -> Self {
+        self.data_dir = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<HostInventory> {
         Ok(HostInventory {
             components: self.components.unwrap_or_default(),
@@ -374,10 +406,332 @@ impl HostInventoryBuilder {
             uptime_seconds: self
                 .uptime_seconds
                 .ok_or_else(|| "uptime_seconds is required".to_string())?,
+            prefetched_images: self.prefetched_images.unwrap_or_default(),
+            data_dir: self.data_dir,
         })
     }
 }
 
+/// Status of an image that a host prefetches into its artifact cache
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PrefetchStatus {
+    /// The image has not been fetched yet
+    #[default]
+    Pending,
+    /// The image is being fetched
+    Fetching,
+    /// The image is in the artifact cache
+    Cached,
+    /// The last attempt to fetch the image failed
+    Failed,
+}
+
+/// An image that a host prefetches and keeps warm in its artifact cache, as reported in its
+/// [`HostInventory`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct PrefetchedImage {
+    /// OCI reference of the image
+    pub(crate) image_ref: String,
+    /// Status of the prefetch
+    #[serde(default)]
+    pub(crate) status: PrefetchStatus,
+    /// Error of the last failed attempt to fetch the image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+}
+
+impl PrefetchedImage {
+    /// Create a [`PrefetchedImage`] for the given image reference and status
+    #[must_use]
+    pub fn new(image_ref: &str, status: PrefetchStatus) -> Self {
+        Self {
+            image_ref: image_ref.into(),
+            status,
+            error: None,
+        }
+    }
+
+    /// Set the error of the last failed attempt to fetch the image
+    #[must_use]
+    pub fn with_error(self, error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..self
+        }
+    }
+
+    /// Get the OCI reference of the image
+    #[must_use]
+    pub fn image_ref(&self) -> &str {
+        &self.image_ref
+    }
+
+    /// Get the status of the prefetch
+    #[must_use]
+    pub fn status(&self) -> PrefetchStatus {
+        self.status
+    }
+
+    /// Get the error of the last failed attempt to fetch the image, if any
+    #[must_use]
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// A category of data a host keeps in its data directory
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DataCategory {
+    /// Components and providers fetched from OCI registries
+    Artifacts,
+    /// Output of capability provider processes
+    ProviderLogs,
+    /// Invocation recordings captured for troubleshooting
+    FlightRecordings,
+    /// Components compiled ahead of time for the host runtime
+    Precompiled,
+}
+
+impl DataCategory {
+    /// All data categories
+    pub const ALL: [Self; 4] = [
+        Self::Artifacts,
+        Self::ProviderLogs,
+        Self::FlightRecordings,
+        Self::Precompiled,
+    ];
+
+    /// Get the name of the category, which is also the name of its directory
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Artifacts => "artifacts",
+            Self::ProviderLogs => "provider_logs",
+            Self::FlightRecordings => "flight_recordings",
+            Self::Precompiled => "precompiled",
+        }
+    }
+}
+
+impl core::fmt::Display for DataCategory {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl core::str::FromStr for DataCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s.replace('-', "_"))
+            .ok_or_else(|| format!("unknown data category [{s}]"))
+    }
+}
+
+/// Disk usage of a single [`DataCategory`] in a host data directory
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct DataCategoryUsage {
+    /// The category of data
+    pub(crate) category: DataCategory,
+    /// Total size of the files of the category in bytes
+    #[serde(default)]
+    pub(crate) bytes: u64,
+    /// Number of files of the category
+    #[serde(default)]
+    pub(crate) files: u64,
+}
+
+impl DataCategoryUsage {
+    /// Create a [`DataCategoryUsage`] for the given category
+    #[must_use]
+    pub fn new(category: DataCategory, bytes: u64, files: u64) -> Self {
+        Self {
+            category,
+            bytes,
+            files,
+        }
+    }
+
+    /// Get the category of data
+    #[must_use]
+    pub fn category(&self) -> DataCategory {
+        self.category
+    }
+
+    /// Get the total size of the files of the category in bytes
+    #[must_use]
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Get the number of files of the category
+    #[must_use]
+    pub fn files(&self) -> u64 {
+        self.files
+    }
+}
+
+/// Disk usage of the data directory of a host, as reported in its [`HostInventory`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct DataDirUsage {
+    /// Path of the data directory on the host
+    pub(crate) path: String,
+    /// Version of the layout of the data directory
+    #[serde(default)]
+    pub(crate) layout_version: u32,
+    /// Disk usage of each category of data
+    #[serde(default)]
+    pub(crate) categories: Vec<DataCategoryUsage>,
+}
+
+impl DataDirUsage {
+    /// Create a [`DataDirUsage`] for the data directory at the given path
+    #[must_use]
+    pub fn new(
+        path: impl Into<String>,
+        layout_version: u32,
+        categories: Vec<DataCategoryUsage>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            layout_version,
+            categories,
+        }
+    }
+
+    /// Get the path of the data directory on the host
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the version of the layout of the data directory
+    #[must_use]
+    pub fn layout_version(&self) -> u32 {
+        self.layout_version
+    }
+
+    /// Get the disk usage of each category of data
+    #[must_use]
+    pub fn categories(&self) -> &[DataCategoryUsage] {
+        &self.categories
+    }
+
+    /// Get the total size of all data in bytes
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.categories.iter().map(DataCategoryUsage::bytes).sum()
+    }
+}
+
+/// Prefix of keys in the lattice data bucket that store [`HostDecommission`] records
+pub(crate) const HOST_DECOMMISSION_KEY_PREFIX: &str = "HOST_DECOMMISSION_";
+
+/// Record of a host that was stopped permanently, published by the host to the lattice data
+/// bucket and with the `host_decommissioned` event before it stops
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct HostDecommission {
+    /// ID of the decommissioned host
+    pub(crate) host_id: String,
+    /// Human-friendly name of the host
+    #[serde(default)]
+    pub(crate) friendly_name: String,
+    /// Labels of the host when it was decommissioned
+    #[serde(default)]
+    pub(crate) labels: BTreeMap<String, String>,
+    /// Version of the host
+    #[serde(default)]
+    pub(crate) version: String,
+    /// When the host was decommissioned, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub(crate) decommissioned_at: u64,
+    /// Categories of the data directory of the host that were removed
+    #[serde(default)]
+    pub(crate) removed_data: Vec<DataCategory>,
+}
+
+impl HostDecommission {
+    /// Create a record of the decommissioning of a host
+    #[must_use]
+    pub fn new(
+        host_id: impl Into<String>,
+        friendly_name: impl Into<String>,
+        labels: BTreeMap<String, String>,
+        version: impl Into<String>,
+        decommissioned_at: u64,
+    ) -> Self {
+        Self {
+            host_id: host_id.into(),
+            friendly_name: friendly_name.into(),
+            labels,
+            version: version.into(),
+            decommissioned_at,
+            removed_data: Vec::new(),
+        }
+    }
+
+    /// Set the categories of the data directory of the host that were removed
+    #[must_use]
+    pub fn with_removed_data(self, removed_data: Vec<DataCategory>) -> Self {
+        Self {
+            removed_data,
+            ..self
+        }
+    }
+
+    /// Get the key of this record in the lattice data bucket
+    #[must_use]
+    pub fn key(&self) -> String {
+        format!("{HOST_DECOMMISSION_KEY_PREFIX}{}", self.host_id)
+    }
+
+    /// Get the ID of the decommissioned host
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    /// Get the human-friendly name of the host
+    #[must_use]
+    pub fn friendly_name(&self) -> &str {
+        &self.friendly_name
+    }
+
+    /// Get the labels of the host when it was decommissioned
+    #[must_use]
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// Get the version of the host
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Get when the host was decommissioned, in milliseconds since the Unix epoch
+    #[must_use]
+    pub fn decommissioned_at(&self) -> u64 {
+        self.decommissioned_at
+    }
+
+    /// Get the categories of the data directory of the host that were removed
+    #[must_use]
+    pub fn removed_data(&self) -> &[DataCategory] {
+        &self.removed_data
+    }
+}
+
 /// A label on a given host (ex. "arch=amd64")
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[non_exhaustive]
@@ -429,13 +783,195 @@ impl HostLabelIdentifier {
     }
 }
 
+/// A set of labels to put on a given host in a single, atomic operation
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct HostLabels {
+    /// Labels to put on the host, keyed by label key
+    pub(crate) labels: BTreeMap<String, String>,
+}
+
+impl HostLabels {
+    /// Create a [`HostLabels`] from a map of label keys to values
+    pub fn from_map(labels: BTreeMap<String, String>) -> Self {
+        Self { labels }
+    }
+
+    /// Get the labels to put on the host
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+}
+
+/// A set of label keys to remove from a given host in a single, atomic operation
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct HostLabelIdentifiers {
+    /// Keys of the labels to remove
+    pub(crate) keys: Vec<String>,
+}
+
+impl HostLabelIdentifiers {
+    /// Create a [`HostLabelIdentifiers`] from a list of label keys
+    pub fn from_keys(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// Get the keys of the labels to remove
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+}
+
+/// Prefix of inventory cursors pointing after a component
+const COMPONENT_CURSOR_PREFIX: &str = "component:";
+/// Prefix of inventory cursors pointing after a provider
+const PROVIDER_CURSOR_PREFIX: &str = "provider:";
+
+/// Parameters of a paged host inventory query
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct InventoryPageRequest {
+    /// Cursor returned with the previous page, or `None` to start from the first page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) cursor: Option<String>,
+    /// Maximum number of components and providers, combined, to return in the page
+    pub(crate) limit: u32,
+}
+
+impl InventoryPageRequest {
+    /// Create a request for the first page of at most `limit` components and providers
+    #[must_use]
+    pub fn new(limit: u32) -> Self {
+        Self {
+            cursor: None,
+            limit,
+        }
+    }
+
+    /// Request the page following the one that returned `cursor`
+    #[must_use]
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Get the cursor of the previous page, if any
+    #[must_use]
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    /// Get the maximum number of components and providers to return
+    #[must_use]
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+}
+
+/// A page of a [`HostInventory`].
+///
+/// Every page carries the full host metadata, but only a slice of the components and providers
+/// running on the host. Components are returned before providers, each ordered by ID. Pages are
+/// serialized like a [`HostInventory`], so the complete inventory returned by hosts that do not
+/// support paging is read as a single, last page.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct HostInventoryPage {
+    /// The host inventory, restricted to the components and providers of this page
+    #[serde(flatten)]
+    pub(crate) inventory: HostInventory,
+    /// Opaque cursor to request the next page with, or `None` if this is the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) next_cursor: Option<String>,
+}
+
+impl HostInventoryPage {
+    /// Get the inventory of this page
+    #[must_use]
+    pub fn inventory(&self) -> &HostInventory {
+        &self.inventory
+    }
+
+    /// Take the inventory of this page
+    #[must_use]
+    pub fn into_inventory(self) -> HostInventory {
+        self.inventory
+    }
+
+    /// Get the cursor to request the next page with, or `None` if this is the last page
+    #[must_use]
+    pub fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+}
+
+impl HostInventory {
+    /// Restrict the inventory to the page described by `request`
+    #[must_use]
+    pub fn into_page(mut self, request: &InventoryPageRequest) -> HostInventoryPage {
+        self.components.sort_by(|a, b| a.id.cmp(&b.id));
+        self.providers.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let (components_from, providers_from) = match request.cursor() {
+            None => (0, 0),
+            Some(cursor) => {
+                if let Some(id) = cursor.strip_prefix(COMPONENT_CURSOR_PREFIX) {
+                    (self.components.partition_point(|c| c.id.as_str() <= id), 0)
+                } else if let Some(id) = cursor.strip_prefix(PROVIDER_CURSOR_PREFIX) {
+                    (
+                        self.components.len(),
+                        self.providers.partition_point(|p| p.id.as_str() <= id),
+                    )
+                } else {
+                    (self.components.len(), self.providers.len())
+                }
+            }
+        };
+        let limit = usize::try_from(request.limit.max(1)).unwrap_or(usize::MAX);
+        let (total_components, total_providers) = (self.components.len(), self.providers.len());
+
+        let components: Vec<_> = self
+            .components
+            .drain(..)
+            .skip(components_from)
+            .take(limit)
+            .collect();
+        let providers: Vec<_> = self
+            .providers
+            .drain(..)
+            .skip(providers_from)
+            .take(limit - components.len())
+            .collect();
+        let more = components_from + components.len() < total_components
+            || providers_from + providers.len() < total_providers;
+        let next_cursor = match (providers.last(), components.last()) {
+            _ if !more => None,
+            (Some(provider), _) => Some(format!("{PROVIDER_CURSOR_PREFIX}{}", provider.id)),
+            (None, Some(component)) => Some(format!("{COMPONENT_CURSOR_PREFIX}{}", component.id)),
+            (None, None) => None,
+        };
+        HostInventoryPage {
+            inventory: Self {
+                components,
+                providers,
+                ..self
+            },
+            next_cursor,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
 
     use crate::{ComponentDescription, ProviderDescription};
 
-    use super::{Host, HostInventory};
+    use super::{
+        DataCategory, DataCategoryUsage, DataDirUsage, Host, HostInventory, HostInventoryPage,
+        InventoryPageRequest, PrefetchStatus, PrefetchedImage,
+    };
 
     #[test]
     fn host_builder() {
@@ -479,7 +1015,16 @@ mod tests {
                 labels: BTreeMap::from([("a".into(), "b".into())]),
                 version: "1.0.0".into(),
                 uptime_human: "t".into(),
-                uptime_seconds: 1
+                uptime_seconds: 1,
+                prefetched_images: Vec::from([PrefetchedImage::new(
+                    "ghcr.io/wasmcloud/echo:0.1.0",
+                    PrefetchStatus::Cached
+                )]),
+                data_dir: Some(DataDirUsage::new(
+                    "/var/lib/wasmcloud",
+                    1,
+                    Vec::from([DataCategoryUsage::new(DataCategory::Artifacts, 1024, 2)]),
+                )),
             },
             HostInventory::builder()
                 .components(Vec::from([ComponentDescription::default()]))
@@ -490,8 +1035,91 @@ mod tests {
                 .version("1.0.0".into())
                 .uptime_human("t".into())
                 .uptime_seconds(1)
+                .prefetched_images(Vec::from([PrefetchedImage::new(
+                    "ghcr.io/wasmcloud/echo:0.1.0",
+                    PrefetchStatus::Cached
+                )]))
+                .data_dir(DataDirUsage::new(
+                    "/var/lib/wasmcloud",
+                    1,
+                    Vec::from([DataCategoryUsage::new(DataCategory::Artifacts, 1024, 2)]),
+                ))
                 .build()
                 .unwrap()
         )
     }
+
+    #[test]
+    fn host_inventory_pages() {
+        let inventory = HostInventory {
+            components: ["c", "a", "b"]
+                .into_iter()
+                .map(|id| ComponentDescription {
+                    id: id.into(),
+                    ..Default::default()
+                })
+                .collect(),
+            providers: ["q", "p"]
+                .into_iter()
+                .map(|id| ProviderDescription {
+                    id: id.into(),
+                    ..Default::default()
+                })
+                .collect(),
+            host_id: "host_id".into(),
+            ..Default::default()
+        };
+        let ids = |page: &HostInventoryPage| {
+            let inventory = page.inventory();
+            inventory
+                .components()
+                .iter()
+                .map(|c| c.id().to_string())
+                .chain(inventory.providers().iter().map(|p| p.id().to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        let mut pages = Vec::new();
+        let mut request = InventoryPageRequest::new(2);
+        loop {
+            let page = inventory.clone().into_page(&request);
+            assert_eq!(page.inventory().host_id(), "host_id");
+            pages.push(ids(&page));
+            let Some(cursor) = page.next_cursor() else {
+                break;
+            };
+            request = request.with_cursor(cursor);
+        }
+        assert_eq!(pages, [vec!["a", "b"], vec!["c", "p"], vec!["q"]]);
+
+        let page = inventory.clone().into_page(&InventoryPageRequest::new(3));
+        assert_eq!(page.next_cursor(), Some("component:c"));
+        let page = inventory
+            .clone()
+            .into_page(&InventoryPageRequest::new(3).with_cursor("component:c"));
+        assert_eq!(ids(&page), ["p", "q"]);
+        assert_eq!(page.next_cursor(), None);
+
+        // Inventories of hosts that do not support paging are read as a single page
+        let page: HostInventoryPage =
+            serde_json::from_value(serde_json::to_value(&inventory).unwrap()).unwrap();
+        assert_eq!(page.inventory(), &inventory);
+        assert_eq!(page.next_cursor(), None);
+    }
+
+    #[test]
+    fn data_category_names() {
+        for category in DataCategory::ALL {
+            assert_eq!(category.as_str().parse::<DataCategory>(), Ok(category));
+            assert_eq!(
+                serde_json::to_value(category).unwrap(),
+                serde_json::json!(category.as_str())
+            );
+        }
+        assert_eq!(
+            "provider-logs".parse::<DataCategory>(),
+            Ok(DataCategory::ProviderLogs)
+        );
+        assert!("logs".parse::<DataCategory>().is_err());
+    }
 }
@@ -0,0 +1,207 @@
+//! Validated identifiers of lattice resources.
+//!
+//! Identifiers are validated when they are constructed, so that malformed identifiers are caught
+//! where they are created rather than by the first control interface call using them. [`Client`]
+//! methods accept any type implementing [`IntoId`], which includes plain strings for
+//! compatibility. Strings are validated when the method is called.
+//!
+//! [`Client`]: crate::Client
+
+use core::borrow::Borrow;
+use core::fmt::{self, Display};
+use core::ops::Deref;
+use core::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{IdentifierKind, Result};
+
+/// Conversion into a validated identifier of type `T`
+pub trait IntoId<T> {
+    /// Convert into the identifier, failing if it is malformed
+    fn into_id(self) -> Result<T>;
+}
+
+macro_rules! identifier {
+    ($(#[$meta:meta])* $name:ident, $validate:path) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            /// Validate an identifier. Leading and trailing whitespace is removed.
+            pub fn parse(value: impl AsRef<str>) -> Result<Self> {
+                $validate(value).map(Self)
+            }
+
+            #[must_use]
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            #[must_use]
+            pub fn into_string(self) -> String {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Box<dyn std::error::Error + Send + Sync>;
+
+            fn from_str(s: &str) -> Result<Self> {
+                Self::parse(s)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Box<dyn std::error::Error + Send + Sync>;
+
+            fn try_from(value: &str) -> Result<Self> {
+                Self::parse(value)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Box<dyn std::error::Error + Send + Sync>;
+
+            fn try_from(value: String) -> Result<Self> {
+                Self::parse(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> core::result::Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Self::parse(value).map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl IntoId<$name> for $name {
+            fn into_id(self) -> Result<$name> {
+                Ok(self)
+            }
+        }
+
+        impl IntoId<$name> for &$name {
+            fn into_id(self) -> Result<$name> {
+                Ok(self.clone())
+            }
+        }
+
+        impl IntoId<$name> for &str {
+            fn into_id(self) -> Result<$name> {
+                $name::parse(self)
+            }
+        }
+
+        impl IntoId<$name> for String {
+            fn into_id(self) -> Result<$name> {
+                $name::parse(self)
+            }
+        }
+
+        impl IntoId<$name> for &String {
+            fn into_id(self) -> Result<$name> {
+                $name::parse(self)
+            }
+        }
+    };
+}
+
+identifier!(
+    /// The ID of a host, i.e. its public key
+    HostId,
+    IdentifierKind::is_host_id
+);
+
+identifier!(
+    /// The unique ID of a component in a lattice
+    ComponentId,
+    IdentifierKind::is_component_id
+);
+
+identifier!(
+    /// The OCI reference of a capability provider, e.g. `ghcr.io/wasmcloud/http-server:0.23.0`
+    ProviderRef,
+    IdentifierKind::is_provider_ref
+);
+
+identifier!(
+    /// The name of a link, e.g. `default`
+    LinkName,
+    IdentifierKind::is_link_name
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentId, HostId, IntoId, LinkName};
+
+    #[test]
+    fn identifiers_are_validated() {
+        let host_id = HostId::parse("  NHOST ").expect("failed to parse host ID");
+        assert_eq!(host_id, "NHOST");
+        assert_eq!(host_id.to_string(), "NHOST");
+        assert!(HostId::parse(" ").is_err());
+        assert!("".parse::<ComponentId>().is_err());
+        assert!(LinkName::try_from(String::from("default")).is_ok());
+
+        let from_str: HostId = "NHOST".into_id().expect("failed to convert host ID");
+        assert_eq!(from_str, host_id);
+        assert!(IntoId::<HostId>::into_id("").is_err());
+
+        assert_eq!(
+            serde_json::to_string(&host_id).expect("failed to serialize"),
+            "\"NHOST\""
+        );
+        assert!(serde_json::from_str::<ComponentId>("\"  \"").is_err());
+        assert_eq!(
+            serde_json::from_str::<ComponentId>("\"echo\"").expect("failed to deserialize"),
+            "echo"
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Label selectors used to query hosts by their labels, and changes of host labels
+
+use core::fmt;
+use core::str::FromStr;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// A single requirement of a [`LabelSelector`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum LabelRequirement {
+    /// `key=value` or `key==value`, the label must be set to the value
+    Equals { key: String, value: String },
+    /// `key!=value`, the label must be unset or set to a different value
+    NotEquals { key: String, value: String },
+    /// `key in (a,b)`, the label must be set to one of the values
+    In {
+        key: String,
+        values: BTreeSet<String>,
+    },
+    /// `key notin (a,b)`, the label must be unset or set to none of the values
+    NotIn {
+        key: String,
+        values: BTreeSet<String>,
+    },
+    /// `key`, the label must be set
+    Exists { key: String },
+    /// `!key`, the label must be unset
+    DoesNotExist { key: String },
+}
+
+impl LabelRequirement {
+    /// Get the label key this requirement applies to
+    #[must_use]
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Equals { key, .. }
+            | Self::NotEquals { key, .. }
+            | Self::In { key, .. }
+            | Self::NotIn { key, .. }
+            | Self::Exists { key }
+            | Self::DoesNotExist { key } => key,
+        }
+    }
+
+    /// Returns true if the given labels satisfy this requirement
+    #[must_use]
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let label = labels.get(self.key());
+        match self {
+            Self::Equals { value, .. } => label == Some(value),
+            Self::NotEquals { value, .. } => label != Some(value),
+            Self::In { values, .. } => label.is_some_and(|v| values.contains(v)),
+            Self::NotIn { values, .. } => !label.is_some_and(|v| values.contains(v)),
+            Self::Exists { .. } => label.is_some(),
+            Self::DoesNotExist { .. } => label.is_none(),
+        }
+    }
+}
+
+impl fmt::Display for LabelRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |values: &BTreeSet<String>| values.iter().cloned().collect::<Vec<_>>().join(",");
+        match self {
+            Self::Equals { key, value } => write!(f, "{key}={value}"),
+            Self::NotEquals { key, value } => write!(f, "{key}!={value}"),
+            Self::In { key, values } => write!(f, "{key} in ({})", join(values)),
+            Self::NotIn { key, values } => write!(f, "{key} notin ({})", join(values)),
+            Self::Exists { key } => write!(f, "{key}"),
+            Self::DoesNotExist { key } => write!(f, "!{key}"),
+        }
+    }
+}
+
+impl FromStr for LabelRequirement {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let key = |key: &str| -> Result<String> {
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(format!("invalid label key in requirement [{s}]").into());
+            }
+            Ok(key.to_string())
+        };
+
+        if let Some(open) = s.find('(') {
+            let Some(list) = s[open + 1..].strip_suffix(')') else {
+                return Err(format!("unterminated value list in requirement [{s}]").into());
+            };
+            let values: BTreeSet<String> = list
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(ToString::to_string)
+                .collect();
+            if values.is_empty() {
+                return Err(format!("empty value list in requirement [{s}]").into());
+            }
+            let head = s[..open].trim_end();
+            return match head.rsplit_once(char::is_whitespace) {
+                Some((k, "in")) => Ok(Self::In {
+                    key: key(k)?,
+                    values,
+                }),
+                Some((k, "notin")) => Ok(Self::NotIn {
+                    key: key(k)?,
+                    values,
+                }),
+                _ => Err(format!("expected `in` or `notin` in requirement [{s}]").into()),
+            };
+        }
+
+        if let Some((k, value)) = s.split_once("!=") {
+            return Ok(Self::NotEquals {
+                key: key(k)?,
+                value: value.trim().to_string(),
+            });
+        }
+        if let Some((k, value)) = s.split_once("==").or_else(|| s.split_once('=')) {
+            return Ok(Self::Equals {
+                key: key(k)?,
+                value: value.trim().to_string(),
+            });
+        }
+        if let Some(k) = s.strip_prefix('!') {
+            return Ok(Self::DoesNotExist { key: key(k)? });
+        }
+        Ok(Self::Exists { key: key(s)? })
+    }
+}
+
+/// A set of requirements on host labels, all of which must be satisfied for a host to match.
+///
+/// Selectors are parsed from a comma-separated list of requirements, supporting both equality
+/// (`arch=amd64`, `zone!=us-east-1`) and set-based (`zone in (a,b)`, `tier notin (edge)`,
+/// `gpu`, `!gpu`) expressions. An empty selector matches every host.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct LabelSelector {
+    pub(crate) requirements: Vec<LabelRequirement>,
+}
+
+impl LabelSelector {
+    /// Create a [`LabelSelector`] from a list of requirements
+    #[must_use]
+    pub fn from_requirements(requirements: Vec<LabelRequirement>) -> Self {
+        Self { requirements }
+    }
+
+    /// Get the requirements of this selector
+    #[must_use]
+    pub fn requirements(&self) -> &[LabelRequirement] {
+        &self.requirements
+    }
+
+    /// Returns true if the given labels satisfy every requirement of this selector
+    #[must_use]
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.requirements.iter().all(|r| r.matches(labels))
+    }
+}
+
+impl fmt::Display for LabelSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let requirements: Vec<_> = self.requirements.iter().map(ToString::to_string).collect();
+        write!(f, "{}", requirements.join(","))
+    }
+}
+
+impl FromStr for LabelSelector {
+    type Err = Box<dyn std::error::Error + Send + Sync>;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Commas separate requirements, except inside the value list of set-based requirements
+        let mut requirements = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| format!("unbalanced parentheses in selector [{s}]"))?;
+                }
+                ',' if depth == 0 => {
+                    requirements.push(&s[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if depth != 0 {
+            return Err(format!("unbalanced parentheses in selector [{s}]").into());
+        }
+        requirements.push(&s[start..]);
+
+        let requirements = requirements
+            .into_iter()
+            .filter(|r| !r.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<_>>()?;
+        Ok(Self { requirements })
+    }
+}
+
+/// A change of the labels of a host, as yielded by
+/// [`Client::label_events`](crate::Client::label_events)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LabelChange {
+    /// A label was added to a host, or the value of an existing label changed
+    Put {
+        /// ID of the host
+        host_id: String,
+        /// Key of the label
+        key: String,
+        /// New value of the label
+        value: String,
+        /// Previous value of the label, if the label was already set
+        previous: Option<String>,
+    },
+    /// A label was removed from a host
+    Delete {
+        /// ID of the host
+        host_id: String,
+        /// Key of the label
+        key: String,
+        /// Value of the label before it was removed
+        value: String,
+    },
+}
+
+impl LabelChange {
+    /// Get the ID of the host whose labels changed
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        match self {
+            Self::Put { host_id, .. } | Self::Delete { host_id, .. } => host_id,
+        }
+    }
+
+    /// Get the key of the changed label
+    #[must_use]
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Put { key, .. } | Self::Delete { key, .. } => key,
+        }
+    }
+}
+
+/// Compute the changes between two sets of labels of a host
+pub(crate) fn diff_labels(
+    host_id: &str,
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<LabelChange> {
+    let deleted = old
+        .iter()
+        .filter(|(key, _)| !new.contains_key(*key))
+        .map(|(key, value)| LabelChange::Delete {
+            host_id: host_id.to_string(),
+            key: key.clone(),
+            value: value.clone(),
+        });
+    let put = new
+        .iter()
+        .filter(|(key, value)| old.get(*key) != Some(*value))
+        .map(|(key, value)| LabelChange::Put {
+            host_id: host_id.to_string(),
+            key: key.clone(),
+            value: value.clone(),
+            previous: old.get(key).cloned(),
+        });
+    deleted.chain(put).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{diff_labels, LabelChange, LabelRequirement, LabelSelector};
+
+    #[test]
+    fn parses_and_matches_selectors() {
+        let labels = BTreeMap::from([
+            ("arch".to_string(), "amd64".to_string()),
+            ("zone".to_string(), "us-east-1".to_string()),
+            ("gpu".to_string(), String::new()),
+        ]);
+
+        let matching = [
+            "",
+            "arch=amd64",
+            "arch==amd64",
+            "arch!=arm64",
+            "tier!=edge",
+            "zone in (us-east-1, us-west-2)",
+            "zone notin (eu-west-1)",
+            "tier notin (edge)",
+            "gpu",
+            "!tier",
+            "arch=amd64, zone in (us-east-1,us-west-2), !tier",
+        ];
+        for selector in matching {
+            let parsed: LabelSelector = selector.parse().expect("failed to parse selector");
+            assert!(parsed.matches(&labels), "[{selector}] should match");
+        }
+
+        let not_matching = [
+            "arch=arm64",
+            "arch!=amd64",
+            "zone in (eu-west-1)",
+            "zone notin (us-east-1)",
+            "tier",
+            "!gpu",
+            "arch=amd64,tier",
+        ];
+        for selector in not_matching {
+            let parsed: LabelSelector = selector.parse().expect("failed to parse selector");
+            assert!(!parsed.matches(&labels), "[{selector}] should not match");
+        }
+
+        let selector: LabelSelector = "zone in (b,a),!tier".parse().unwrap();
+        assert_eq!(
+            selector.requirements()[1],
+            LabelRequirement::DoesNotExist {
+                key: "tier".to_string()
+            }
+        );
+        assert_eq!(selector.to_string(), "zone in (a,b),!tier");
+
+        for invalid in [
+            "zone in (a",
+            "zone in ()",
+            "zone within (a)",
+            "=value",
+            "a b",
+        ] {
+            assert!(
+                invalid.parse::<LabelSelector>().is_err(),
+                "[{invalid}] should fail to parse"
+            );
+        }
+    }
+
+    #[test]
+    fn label_diff() {
+        let old = BTreeMap::from([
+            ("arch".to_string(), "amd64".to_string()),
+            ("zone".to_string(), "us-east-1".to_string()),
+        ]);
+        let new = BTreeMap::from([
+            ("arch".to_string(), "amd64".to_string()),
+            ("zone".to_string(), "us-west-2".to_string()),
+            ("gpu".to_string(), "true".to_string()),
+        ]);
+        assert_eq!(
+            diff_labels("host", &old, &new),
+            vec![
+                LabelChange::Put {
+                    host_id: "host".into(),
+                    key: "gpu".into(),
+                    value: "true".into(),
+                    previous: None,
+                },
+                LabelChange::Put {
+                    host_id: "host".into(),
+                    key: "zone".into(),
+                    value: "us-west-2".into(),
+                    previous: Some("us-east-1".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            diff_labels("host", &new, &old),
+            vec![
+                LabelChange::Delete {
+                    host_id: "host".into(),
+                    key: "gpu".into(),
+                    value: "true".into(),
+                },
+                LabelChange::Put {
+                    host_id: "host".into(),
+                    key: "zone".into(),
+                    value: "us-east-1".into(),
+                    previous: Some("us-west-2".into()),
+                },
+            ]
+        );
+        assert!(diff_labels("host", &old, &old).is_empty());
+    }
+}
@@ -170,6 +170,77 @@ impl LinkBuilder {
 }
 
 /// Helper function to provide a default link name
+/// Prefix of keys in the lattice data bucket that store component specifications, which hold the
+/// links of the component
+pub(crate) const COMPONENT_SPEC_KEY_PREFIX: &str = "COMPONENT_";
+
+/// A change to the links of the lattice, as observed by
+/// [`Client::link_watcher`](crate::Client::link_watcher)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum LinkChange {
+    /// A link was created or updated
+    Put {
+        /// Revision of the source component specification in the lattice data bucket
+        revision: u64,
+        /// The link
+        link: Link,
+    },
+    /// A link was deleted
+    Delete {
+        /// Revision of the source component specification in the lattice data bucket
+        revision: u64,
+        /// The deleted link
+        link: Link,
+    },
+}
+
+impl LinkChange {
+    /// Get the changed link
+    #[must_use]
+    pub fn link(&self) -> &Link {
+        match self {
+            Self::Put { link, .. } | Self::Delete { link, .. } => link,
+        }
+    }
+
+    /// Get the revision of the change in the lattice data bucket
+    #[must_use]
+    pub fn revision(&self) -> u64 {
+        match self {
+            Self::Put { revision, .. } | Self::Delete { revision, .. } => *revision,
+        }
+    }
+}
+
+/// Compute the changes between two sets of links of a source component. Links are identified by
+/// their target, name and WIT namespace and package, so modifying other fields of a link results
+/// in a [`LinkChange::Put`].
+pub(crate) fn diff_links(old: &[Link], new: &[Link], revision: u64) -> Vec<LinkChange> {
+    let same_link = |a: &Link, b: &Link| {
+        a.target == b.target
+            && a.name == b.name
+            && a.wit_namespace == b.wit_namespace
+            && a.wit_package == b.wit_package
+    };
+    let deleted = old
+        .iter()
+        .filter(|link| !new.iter().any(|other| same_link(link, other)))
+        .map(|link| LinkChange::Delete {
+            revision,
+            link: link.clone(),
+        });
+    let put = new
+        .iter()
+        .filter(|link| !old.contains(link))
+        .map(|link| LinkChange::Put {
+            revision,
+            link: link.clone(),
+        });
+    deleted.chain(put).collect()
+}
+
 pub(crate) fn default_link_name() -> String {
     "default".to_string()
 }
@@ -177,7 +248,7 @@ pub(crate) fn default_link_name() -> String {
 #[cfg(test)]
 mod tests {
 
-    use super::Link;
+    use super::{diff_links, Link, LinkChange};
 
     #[test]
     fn link_builder() {
@@ -205,4 +276,40 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn diff_links_by_identity() {
+        let link = |target: &str, interfaces: &[&str]| Link {
+            source_id: "source".into(),
+            target: target.into(),
+            name: "default".into(),
+            wit_namespace: "wasi".into(),
+            wit_package: "keyvalue".into(),
+            interfaces: interfaces.iter().map(ToString::to_string).collect(),
+            ..Default::default()
+        };
+        let old = vec![link("redis", &["store"]), link("nats", &["store"])];
+        let new = vec![
+            link("redis", &["store", "atomics"]),
+            link("vault", &["store"]),
+        ];
+        assert_eq!(
+            diff_links(&old, &new, 7),
+            vec![
+                LinkChange::Delete {
+                    revision: 7,
+                    link: link("nats", &["store"]),
+                },
+                LinkChange::Put {
+                    revision: 7,
+                    link: link("redis", &["store", "atomics"]),
+                },
+                LinkChange::Put {
+                    revision: 7,
+                    link: link("vault", &["store"]),
+                },
+            ]
+        );
+        assert!(diff_links(&new, &new, 8).is_empty());
+    }
 }
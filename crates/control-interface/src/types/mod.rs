@@ -1,9 +1,19 @@
 //! Collection of types that are commonly used/necessary in control interface operations
 
+pub mod annotations;
 pub mod component;
+pub mod config;
 pub mod ctl;
+pub mod epoch;
+pub mod event;
 pub mod host;
+pub mod id;
+pub mod label;
 pub mod link;
+pub mod naming;
+pub mod profile;
 pub mod provider;
 pub mod registry;
+pub mod route;
 pub mod rpc;
+pub mod snapshot;
@@ -0,0 +1,141 @@
+//! Human-friendly aliases for hosts and components, stored in lattice metadata
+
+use core::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Prefix of keys in the lattice data bucket that store aliases
+pub(crate) const ALIAS_KEY_PREFIX: &str = "ALIAS_";
+
+/// The entity an alias refers to
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AliasTarget {
+    /// A host, identified by its ID
+    Host(String),
+    /// A component, identified by its ID
+    Component(String),
+}
+
+impl AliasTarget {
+    /// Get the ID of the aliased entity
+    #[must_use]
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Host(id) | Self::Component(id) => id,
+        }
+    }
+
+    /// Get the ID of the aliased host, if this alias refers to a host
+    #[must_use]
+    pub fn host_id(&self) -> Option<&str> {
+        match self {
+            Self::Host(id) => Some(id),
+            Self::Component(_) => None,
+        }
+    }
+
+    /// Get the ID of the aliased component, if this alias refers to a component
+    #[must_use]
+    pub fn component_id(&self) -> Option<&str> {
+        match self {
+            Self::Component(id) => Some(id),
+            Self::Host(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AliasTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Host(id) => write!(f, "host {id}"),
+            Self::Component(id) => write!(f, "component {id}"),
+        }
+    }
+}
+
+/// A friendly name (e.g. `edge-van-3`) registered for a host or component on the lattice
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LatticeAlias {
+    /// The alias
+    pub(crate) name: String,
+    /// The entity the alias refers to
+    #[serde(flatten)]
+    pub(crate) target: AliasTarget,
+}
+
+impl LatticeAlias {
+    /// Create a new [`LatticeAlias`], validating the name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name is not a valid alias or the target ID is empty
+    pub fn new(name: &str, target: AliasTarget) -> Result<Self> {
+        let name = validate_alias_name(name)?;
+        if target.id().trim().is_empty() {
+            return Err(format!("alias [{name}] must refer to a non-empty ID").into());
+        }
+        Ok(Self { name, target })
+    }
+
+    /// Get the alias
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the entity the alias refers to
+    #[must_use]
+    pub fn target(&self) -> &AliasTarget {
+        &self.target
+    }
+}
+
+/// Ensure a name is usable as an alias, returning it normalized to lowercase.
+///
+/// Aliases are case-insensitive and may only contain ASCII alphanumerics, `-` and `_`, so that
+/// they can be stored as keys in the lattice data bucket.
+pub(crate) fn validate_alias_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("alias cannot be empty".into());
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid alias [{name}], aliases may only contain ASCII letters, digits, `-` and `_`"
+        )
+        .into());
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AliasTarget, LatticeAlias};
+
+    #[test]
+    fn alias_serde_and_validation() {
+        let alias =
+            LatticeAlias::new("Edge-Van-3", AliasTarget::Host("NABC".into())).expect("valid alias");
+        assert_eq!(alias.name(), "edge-van-3");
+
+        let json = serde_json::to_value(&alias).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "name": "edge-van-3", "kind": "host", "id": "NABC" })
+        );
+        assert_eq!(serde_json::from_value::<LatticeAlias>(json).unwrap(), alias);
+
+        assert!(LatticeAlias::new("edge van", AliasTarget::Host("NABC".into())).is_err());
+        assert!(LatticeAlias::new("edge.van", AliasTarget::Host("NABC".into())).is_err());
+        assert!(LatticeAlias::new("", AliasTarget::Component("echo".into())).is_err());
+        assert!(LatticeAlias::new("echo", AliasTarget::Component(" ".into())).is_err());
+    }
+}
@@ -0,0 +1,495 @@
+//! Data types used when profiling components running on a host and reporting the memory they use
+
+use core::time::Duration;
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// A command sent to a host requesting that it profiles a running component for a period of time
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ProfileComponentCommand {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// The ID of the component to profile
+    pub(crate) component_id: String,
+    /// How long to profile the component for, in milliseconds
+    pub(crate) duration_ms: u64,
+}
+
+impl ProfileComponentCommand {
+    /// Create a [`ProfileComponentCommand`] profiling a component for `duration`
+    #[must_use]
+    pub fn new(host_id: &str, component_id: &str, duration: Duration) -> Self {
+        Self {
+            host_id: host_id.into(),
+            component_id: component_id.into(),
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn component_id(&self) -> &str {
+        &self.component_id
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+/// Distribution of the latencies of component invocations, in nanoseconds
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LatencySummary {
+    /// Number of invocations
+    pub(crate) count: u64,
+    /// Number of invocations that failed
+    pub(crate) errors: u64,
+    pub(crate) min_ns: u64,
+    pub(crate) max_ns: u64,
+    pub(crate) mean_ns: u64,
+    pub(crate) p50_ns: u64,
+    pub(crate) p90_ns: u64,
+    pub(crate) p99_ns: u64,
+}
+
+impl LatencySummary {
+    /// Summarize the latencies of a set of invocations, `errors` of which failed
+    #[must_use]
+    pub fn from_samples(mut latencies_ns: Vec<u64>, errors: u64) -> Self {
+        latencies_ns.sort_unstable();
+        let count = latencies_ns.len() as u64;
+        let percentile = |p: u64| {
+            let Some(last) = latencies_ns.len().checked_sub(1) else {
+                return 0;
+            };
+            // Nearest-rank percentile
+            let rank = usize::try_from((count * p).div_ceil(100)).unwrap_or(usize::MAX);
+            latencies_ns[rank.saturating_sub(1).min(last)]
+        };
+        let total: u128 = latencies_ns.iter().map(|ns| u128::from(*ns)).sum();
+        Self {
+            count,
+            errors,
+            min_ns: latencies_ns.first().copied().unwrap_or_default(),
+            max_ns: latencies_ns.last().copied().unwrap_or_default(),
+            mean_ns: total
+                .checked_div(u128::from(count))
+                .and_then(|mean| u64::try_from(mean).ok())
+                .unwrap_or_default(),
+            p50_ns: percentile(50),
+            p90_ns: percentile(90),
+            p99_ns: percentile(99),
+        }
+    }
+
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    #[must_use]
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    #[must_use]
+    pub fn min(&self) -> Duration {
+        Duration::from_nanos(self.min_ns)
+    }
+
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max_ns)
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        Duration::from_nanos(self.mean_ns)
+    }
+
+    #[must_use]
+    pub fn p50(&self) -> Duration {
+        Duration::from_nanos(self.p50_ns)
+    }
+
+    #[must_use]
+    pub fn p90(&self) -> Duration {
+        Duration::from_nanos(self.p90_ns)
+    }
+
+    #[must_use]
+    pub fn p99(&self) -> Duration {
+        Duration::from_nanos(self.p99_ns)
+    }
+}
+
+/// Report of profiling a component with [`ProfileComponentCommand`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ComponentProfile {
+    /// The ID of the profiled component
+    pub(crate) component_id: String,
+    /// The ID of the host the component was profiled on
+    pub(crate) host_id: String,
+    /// How long the component was profiled for, in milliseconds
+    pub(crate) duration_ms: u64,
+    /// Latencies of the invocations handled while profiling
+    pub(crate) invocations: LatencySummary,
+    /// Time spent executing guest code while profiling, in nanoseconds
+    pub(crate) cpu_time_ns: u64,
+    /// Linear memory allocated or grown by instances of the component while profiling
+    pub(crate) memory_allocated_bytes: u64,
+    /// Largest linear memory of a single instance of the component observed while profiling
+    pub(crate) peak_memory_bytes: u64,
+    /// Guest stack samples in folded format, i.e. `outer;inner` frames mapped to the number of
+    /// samples. Empty if the host does not sample guest stacks.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) stack_samples: BTreeMap<String, u64>,
+}
+
+impl ComponentProfile {
+    #[must_use]
+    pub fn component_id(&self) -> &str {
+        &self.component_id
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+
+    #[must_use]
+    pub fn invocations(&self) -> &LatencySummary {
+        &self.invocations
+    }
+
+    #[must_use]
+    pub fn cpu_time(&self) -> Duration {
+        Duration::from_nanos(self.cpu_time_ns)
+    }
+
+    /// Fraction of the profile duration spent executing guest code. Values above 1 indicate that
+    /// several instances executed concurrently.
+    #[must_use]
+    pub fn cpu_utilization(&self) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        self.cpu_time().as_secs_f64() / self.duration().as_secs_f64()
+    }
+
+    #[must_use]
+    pub fn memory_allocated_bytes(&self) -> u64 {
+        self.memory_allocated_bytes
+    }
+
+    #[must_use]
+    pub fn peak_memory_bytes(&self) -> u64 {
+        self.peak_memory_bytes
+    }
+
+    #[must_use]
+    pub fn stack_samples(&self) -> &BTreeMap<String, u64> {
+        &self.stack_samples
+    }
+
+    #[must_use]
+    pub fn builder() -> ComponentProfileBuilder {
+        ComponentProfileBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ComponentProfileBuilder {
+    profile: ComponentProfile,
+}
+
+impl ComponentProfileBuilder {
+    #[must_use]
+    pub fn component_id(mut self, v: String) -> Self {
+        self.profile.component_id = v;
+        self
+    }
+
+    #[must_use]
+    pub fn host_id(mut self, v: String) -> Self {
+        self.profile.host_id = v;
+        self
+    }
+
+    #[must_use]
+    pub fn duration(mut self, v: Duration) -> Self {
+        self.profile.duration_ms = u64::try_from(v.as_millis()).unwrap_or(u64::MAX);
+        self
+    }
+
+    #[must_use]
+    pub fn invocations(mut self, v: LatencySummary) -> Self {
+        self.profile.invocations = v;
+        self
+    }
+
+    #[must_use]
+    pub fn cpu_time(mut self, v: Duration) -> Self {
+        self.profile.cpu_time_ns = u64::try_from(v.as_nanos()).unwrap_or(u64::MAX);
+        self
+    }
+
+    #[must_use]
+    pub fn memory_allocated_bytes(mut self, v: u64) -> Self {
+        self.profile.memory_allocated_bytes = v;
+        self
+    }
+
+    #[must_use]
+    pub fn peak_memory_bytes(mut self, v: u64) -> Self {
+        self.profile.peak_memory_bytes = v;
+        self
+    }
+
+    #[must_use]
+    pub fn stack_samples(mut self, v: BTreeMap<String, u64>) -> Self {
+        self.profile.stack_samples = v;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> ComponentProfile {
+        self.profile
+    }
+}
+
+/// A query sent to a host for the components holding the most linear memory
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TopMemoryQuery {
+    /// The ID of the target host
+    #[serde(default)]
+    pub(crate) host_id: String,
+    /// Maximum number of components to report
+    pub(crate) limit: u32,
+}
+
+impl TopMemoryQuery {
+    /// Create a [`TopMemoryQuery`] for the `limit` components holding the most memory
+    #[must_use]
+    pub fn new(host_id: &str, limit: u32) -> Self {
+        Self {
+            host_id: host_id.into(),
+            limit,
+        }
+    }
+
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    #[must_use]
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+}
+
+/// Linear memory held by a component running on a host, as reported for a [`TopMemoryQuery`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ComponentMemoryUsage {
+    /// The ID of the component
+    pub(crate) component_id: String,
+    /// Image reference of the component
+    #[serde(default)]
+    pub(crate) image_ref: String,
+    /// Linear memory currently held by running instances of the component
+    pub(crate) resident_bytes: u64,
+    /// Largest linear memory of a single instance of the component observed since it started
+    #[serde(default)]
+    pub(crate) peak_bytes: u64,
+    /// Number of instances of the component currently running
+    #[serde(default)]
+    pub(crate) running_instances: u64,
+    /// Maximum number of instances of the component that can run concurrently
+    #[serde(default)]
+    pub(crate) max_instances: u32,
+}
+
+impl ComponentMemoryUsage {
+    #[must_use]
+    pub fn component_id(&self) -> &str {
+        &self.component_id
+    }
+
+    #[must_use]
+    pub fn image_ref(&self) -> &str {
+        &self.image_ref
+    }
+
+    #[must_use]
+    pub fn resident_bytes(&self) -> u64 {
+        self.resident_bytes
+    }
+
+    #[must_use]
+    pub fn peak_bytes(&self) -> u64 {
+        self.peak_bytes
+    }
+
+    #[must_use]
+    pub fn running_instances(&self) -> u64 {
+        self.running_instances
+    }
+
+    #[must_use]
+    pub fn max_instances(&self) -> u32 {
+        self.max_instances
+    }
+
+    #[must_use]
+    pub fn builder() -> ComponentMemoryUsageBuilder {
+        ComponentMemoryUsageBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ComponentMemoryUsageBuilder {
+    usage: ComponentMemoryUsage,
+}
+
+impl ComponentMemoryUsageBuilder {
+    #[must_use]
+    pub fn component_id(mut self, v: String) -> Self {
+        self.usage.component_id = v;
+        self
+    }
+
+    #[must_use]
+    pub fn image_ref(mut self, v: String) -> Self {
+        self.usage.image_ref = v;
+        self
+    }
+
+    #[must_use]
+    pub fn resident_bytes(mut self, v: u64) -> Self {
+        self.usage.resident_bytes = v;
+        self
+    }
+
+    #[must_use]
+    pub fn peak_bytes(mut self, v: u64) -> Self {
+        self.usage.peak_bytes = v;
+        self
+    }
+
+    #[must_use]
+    pub fn running_instances(mut self, v: u64) -> Self {
+        self.usage.running_instances = v;
+        self
+    }
+
+    #[must_use]
+    pub fn max_instances(mut self, v: u32) -> Self {
+        self.usage.max_instances = v;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> ComponentMemoryUsage {
+        self.usage
+    }
+}
+
+/// Returns the `limit` components holding the most resident memory, largest first. Ties are
+/// ordered by peak memory, then by component ID.
+#[must_use]
+pub fn top_memory_consumers(
+    usages: impl IntoIterator<Item = ComponentMemoryUsage>,
+    limit: usize,
+) -> Vec<ComponentMemoryUsage> {
+    let mut usages: Vec<_> = usages.into_iter().collect();
+    usages.sort_by(|a, b| {
+        b.resident_bytes
+            .cmp(&a.resident_bytes)
+            .then(b.peak_bytes.cmp(&a.peak_bytes))
+            .then_with(|| a.component_id.cmp(&b.component_id))
+    });
+    usages.truncate(limit);
+    usages
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::{top_memory_consumers, ComponentMemoryUsage, ComponentProfile, LatencySummary};
+
+    #[test]
+    fn latency_summary() {
+        let summary = LatencySummary::from_samples((1..=100).rev().collect(), 3);
+        assert_eq!(summary.count(), 100);
+        assert_eq!(summary.errors(), 3);
+        assert_eq!(summary.min(), Duration::from_nanos(1));
+        assert_eq!(summary.max(), Duration::from_nanos(100));
+        assert_eq!(summary.mean(), Duration::from_nanos(50));
+        assert_eq!(summary.p50(), Duration::from_nanos(50));
+        assert_eq!(summary.p90(), Duration::from_nanos(90));
+        assert_eq!(summary.p99(), Duration::from_nanos(99));
+
+        assert_eq!(
+            LatencySummary::from_samples(Vec::new(), 0),
+            LatencySummary::default()
+        );
+        let single = LatencySummary::from_samples(vec![7], 0);
+        assert_eq!(single.p99(), Duration::from_nanos(7));
+    }
+
+    #[test]
+    fn component_profile_builder() {
+        let profile = ComponentProfile::builder()
+            .component_id("echo".into())
+            .host_id("host".into())
+            .duration(Duration::from_secs(2))
+            .cpu_time(Duration::from_secs(1))
+            .build();
+        assert_eq!(profile.component_id(), "echo");
+        assert!((profile.cpu_utilization() - 0.5).abs() < f64::EPSILON);
+        assert!(profile.stack_samples().is_empty());
+    }
+    #[test]
+    fn top_memory_consumers_by_resident_memory() {
+        let usage = |id: &str, resident, peak| {
+            ComponentMemoryUsage::builder()
+                .component_id(id.into())
+                .resident_bytes(resident)
+                .peak_bytes(peak)
+                .build()
+        };
+        let top = top_memory_consumers(
+            [
+                usage("idle", 0, 4096),
+                usage("small", 1024, 1024),
+                usage("large", 8192, 8192),
+                usage("spiky", 1024, 65536),
+            ],
+            3,
+        );
+        let ids: Vec<_> = top.iter().map(ComponentMemoryUsage::component_id).collect();
+        assert_eq!(ids, ["large", "spiky", "small"]);
+    }
+}
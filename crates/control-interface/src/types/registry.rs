@@ -1,5 +1,7 @@
 //! Data types used when managing credentials on a wasmCloud host or during operation
 
+use core::fmt;
+
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
@@ -7,7 +9,10 @@ use serde::{Deserialize, Serialize};
 ///
 /// While this is usually a docker image registry, other registries may be supported
 /// in the future.
-#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+///
+/// The password and token are redacted from debug output, so that credentials can be logged
+/// safely. They are serialized as they are, as they have to reach the host.
+#[derive(Clone, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[non_exhaustive]
 pub struct RegistryCredential {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -67,6 +72,18 @@ impl RegistryCredential {
     }
 }
 
+impl fmt::Debug for RegistryCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redact = |value: &Option<String>| value.as_ref().map(|_| "[redacted]");
+        f.debug_struct("RegistryCredential")
+            .field("password", &redact(&self.password))
+            .field("token", &redact(&self.token))
+            .field("username", &self.username)
+            .field("registry_type", &self.registry_type)
+            .finish()
+    }
+}
+
 /// Helper for creating the default registry type
 fn default_registry_type() -> String {
     "oci".to_string()
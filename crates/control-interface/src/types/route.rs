@@ -0,0 +1,260 @@
+//! Weighted traffic splits between components linked under a single target and the inbound HTTP
+//! ingress routing table, both stored in lattice metadata
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{IdentifierKind, Result};
+
+/// Prefix of keys in the lattice data bucket that store traffic splits
+pub const TRAFFIC_SPLIT_KEY_PREFIX: &str = "ROUTE_";
+
+/// Prefix of keys in the lattice data bucket that store ingress routes
+pub const INGRESS_ROUTE_KEY_PREFIX: &str = "INGRESS_";
+
+/// A weighted split of the invocations sent to a link target between several components, e.g. to
+/// incrementally roll out a new version of a component.
+///
+/// Hosts route every invocation of the target to one of the components of the split, in
+/// proportion to their weights. Components keep their own IDs, so the previous version of a
+/// component can be kept running under the target ID while a new version is started alongside it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TrafficSplit {
+    /// The link target whose invocations are split
+    pub(crate) target: String,
+    /// The weight of each component receiving invocations, keyed by component ID
+    pub(crate) weights: BTreeMap<String, u32>,
+}
+
+impl TrafficSplit {
+    /// Create a new [`TrafficSplit`] of the invocations of `target`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target or a component ID is invalid, or if all weights are zero
+    pub fn new(
+        target: impl AsRef<str>,
+        weights: impl IntoIterator<Item = (String, u32)>,
+    ) -> Result<Self> {
+        let target = IdentifierKind::is_component_id(target)?;
+        let weights = weights
+            .into_iter()
+            .map(|(id, weight)| Ok((IdentifierKind::is_component_id(id)?, weight)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        if weights.values().all(|weight| *weight == 0) {
+            return Err(format!(
+                "traffic split of [{target}] must give a non-zero weight to at least one component"
+            )
+            .into());
+        }
+        Ok(Self { target, weights })
+    }
+
+    /// Parse a traffic split of `target` from a comma-separated list of `component=weight` pairs,
+    /// e.g. `echo-v1=90,echo-v2=10`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the list is malformed or the split is invalid, see [`TrafficSplit::new`]
+    pub fn parse(target: impl AsRef<str>, split: &str) -> Result<Self> {
+        let weights = split
+            .split(',')
+            .map(|pair| {
+                let (id, weight) = pair.split_once('=').ok_or_else(|| {
+                    format!("invalid split [{pair}], expected `component=weight`")
+                })?;
+                let weight = weight
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid weight [{weight}] of component [{id}]"))?;
+                Ok((id.trim().to_string(), weight))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Self::new(target, weights)
+    }
+
+    /// Get the link target whose invocations are split
+    #[must_use]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Get the weight of each component receiving invocations, keyed by component ID
+    #[must_use]
+    pub fn weights(&self) -> &BTreeMap<String, u32> {
+        &self.weights
+    }
+
+    /// Get the sum of all weights
+    #[must_use]
+    pub fn total_weight(&self) -> u64 {
+        self.weights.values().map(|weight| u64::from(*weight)).sum()
+    }
+
+    /// Select the component receiving an invocation, given a position in `0..total_weight()`.
+    /// Positions past the total weight wrap around.
+    #[must_use]
+    pub fn select(&self, position: u64) -> &str {
+        let mut position = position % self.total_weight().max(1);
+        for (id, weight) in &self.weights {
+            let weight = u64::from(*weight);
+            if position < weight {
+                return id;
+            }
+            position -= weight;
+        }
+        // Only reachable if all weights are zero, which `new` rejects
+        &self.target
+    }
+}
+
+/// An entry of the lattice ingress routing table, which maps inbound HTTP requests to the
+/// component handling them by hostname and path.
+///
+/// HTTP server providers running in `ingress` routing mode serve the routing table of their
+/// lattice, so every provider on every host routes requests consistently without duplicating the
+/// routes in their configuration. A request is routed to the route with the longest matching path
+/// prefix, preferring routes for its hostname over routes for any hostname.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct IngressRoute {
+    /// Name of the route, which identifies it in the routing table
+    pub(crate) name: String,
+    /// Hostname the route applies to, or `None` if it applies to requests for any hostname
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) hostname: Option<String>,
+    /// Path prefix the route applies to, e.g. `/api`
+    pub(crate) path_prefix: String,
+    /// ID of the component handling the requests matching the route
+    pub(crate) component_id: String,
+}
+
+impl IngressRoute {
+    /// Create a new [`IngressRoute`] sending requests for `hostname` with paths starting with
+    /// `path_prefix` to `component_id`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name, hostname, path prefix or component ID is invalid
+    pub fn new(
+        name: &str,
+        hostname: Option<&str>,
+        path_prefix: &str,
+        component_id: impl AsRef<str>,
+    ) -> Result<Self> {
+        let name = validate_route_name(name)?;
+        let hostname = hostname
+            .map(|hostname| {
+                let hostname = hostname.trim().to_ascii_lowercase();
+                if hostname.is_empty() || hostname.contains(['/', ' ']) {
+                    Err(format!(
+                        "invalid hostname [{hostname}] of ingress route [{name}]"
+                    ))
+                } else {
+                    Ok(hostname)
+                }
+            })
+            .transpose()?;
+        let path_prefix = path_prefix.trim();
+        if !path_prefix.starts_with('/') {
+            return Err(format!(
+                "invalid path prefix [{path_prefix}] of ingress route [{name}], it must start with `/`"
+            )
+            .into());
+        }
+        let component_id = IdentifierKind::is_component_id(component_id)?;
+        Ok(Self {
+            name,
+            hostname,
+            path_prefix: path_prefix.to_string(),
+            component_id,
+        })
+    }
+
+    /// Get the name of the route
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the hostname the route applies to, if it is restricted to one
+    #[must_use]
+    pub fn hostname(&self) -> Option<&str> {
+        self.hostname.as_deref()
+    }
+
+    /// Get the path prefix the route applies to
+    #[must_use]
+    pub fn path_prefix(&self) -> &str {
+        &self.path_prefix
+    }
+
+    /// Get the ID of the component handling the requests matching the route
+    #[must_use]
+    pub fn component_id(&self) -> &str {
+        &self.component_id
+    }
+}
+
+/// Ensure a name is usable as the name of an ingress route, returning it normalized to lowercase.
+///
+/// Like aliases, route names may only contain ASCII alphanumerics, `-` and `_`, so that they can be
+/// stored as keys in the lattice data bucket.
+pub(crate) fn validate_route_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid ingress route name [{name}], names may only contain ASCII letters, digits, `-` and `_`"
+        )
+        .into());
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IngressRoute, TrafficSplit};
+
+    #[test]
+    fn traffic_split_parse_and_select() {
+        let split = TrafficSplit::parse("echo", "echo-v1=90, echo-v2=10").unwrap();
+        assert_eq!(split.target(), "echo");
+        assert_eq!(split.total_weight(), 100);
+        let selected: Vec<_> = (0..100).map(|i| split.select(i)).collect();
+        assert_eq!(selected.iter().filter(|id| **id == "echo-v1").count(), 90);
+        assert_eq!(selected.iter().filter(|id| **id == "echo-v2").count(), 10);
+        assert_eq!(split.select(100), split.select(0));
+
+        let json = serde_json::to_string(&split).unwrap();
+        assert_eq!(serde_json::from_str::<TrafficSplit>(&json).unwrap(), split);
+
+        assert!(TrafficSplit::parse("echo", "echo-v1").is_err());
+        assert!(TrafficSplit::parse("echo", "echo-v1=ten").is_err());
+        assert!(TrafficSplit::parse("echo", "echo-v1=0,echo-v2=0").is_err());
+        assert!(TrafficSplit::parse("", "echo-v1=1").is_err());
+    }
+
+    #[test]
+    fn ingress_route_validation() {
+        let route = IngressRoute::new("API", Some("Api.Example.com"), "/v1", "api").unwrap();
+        assert_eq!(route.name(), "api");
+        assert_eq!(route.hostname(), Some("api.example.com"));
+        assert_eq!(route.path_prefix(), "/v1");
+        assert_eq!(route.component_id(), "api");
+
+        let json = serde_json::to_string(&route).unwrap();
+        assert_eq!(serde_json::from_str::<IngressRoute>(&json).unwrap(), route);
+
+        assert!(IngressRoute::new("any", None, "/", "api").is_ok());
+        assert!(IngressRoute::new("a.b", None, "/", "api").is_err());
+        assert!(IngressRoute::new("api", None, "v1", "api").is_err());
+        assert!(IngressRoute::new("api", Some("a/b"), "/", "api").is_err());
+        assert!(IngressRoute::new("api", None, "/", "").is_err());
+    }
+}
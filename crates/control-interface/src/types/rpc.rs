@@ -1,9 +1,10 @@
 //! Data types used in RPC calls (usually control-related) on a wasmCloud lattice
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use serde::{Deserialize, Serialize};
 
+use crate::types::link::Link;
 use crate::Result;
 
 /// A host response to a request to start a component.
@@ -24,6 +25,9 @@ pub struct ComponentAuctionAck {
     /// Constraints that were used in the auction
     #[serde(default)]
     pub(crate) constraints: BTreeMap<String, String>,
+    /// Capacity hints reported by the bidding host
+    #[serde(default, skip_serializing_if = "AuctionHints::is_empty")]
+    pub(crate) hints: AuctionHints,
 }
 
 impl ComponentAuctionAck {
@@ -39,6 +43,7 @@ impl ComponentAuctionAck {
             component_id: component_id.into(),
             host_id: host_id.into(),
             constraints: constraints.into(),
+            hints: AuctionHints::default(),
         }
     }
 
@@ -66,6 +71,12 @@ impl ComponentAuctionAck {
         &self.constraints
     }
 
+    /// Get the capacity hints reported by the bidding host
+    #[must_use]
+    pub fn hints(&self) -> &AuctionHints {
+        &self.hints
+    }
+
     pub fn builder() -> ComponentAuctionAckBuilder {
         ComponentAuctionAckBuilder::default()
     }
@@ -77,6 +88,7 @@ pub struct ComponentAuctionAckBuilder {
     component_id: Option<String>,
     host_id: Option<String>,
     constraints: Option<BTreeMap<String, String>>,
+    hints: Option<AuctionHints>,
 }
 
 impl ComponentAuctionAckBuilder {
@@ -104,6 +116,12 @@ impl ComponentAuctionAckBuilder {
         self
     }
 
+    #[must_use]
+    pub fn hints(mut self, v: AuctionHints) -> Self {
+        self.hints = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ComponentAuctionAck> {
         Ok(ComponentAuctionAck {
             component_ref: self
@@ -116,10 +134,258 @@ impl ComponentAuctionAckBuilder {
                 .host_id
                 .ok_or_else(|| "host_id is required".to_string())?,
             constraints: self.constraints.unwrap_or_default(),
+            hints: self.hints.unwrap_or_default(),
         })
     }
 }
 
+/// Capacity hints a host attaches to its auction acknowledgements.
+///
+/// Schedulers can use these to choose between bidders instead of taking the first responder, see
+/// [`rank_auction_results`](crate::client::rank_auction_results). Every hint is optional; hosts
+/// older than 1.10.0 send none.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct AuctionHints {
+    /// Number of components currently running on the host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) component_count: Option<u64>,
+    /// Number of providers currently running on the host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) provider_count: Option<u64>,
+    /// Estimate of the memory available on the host, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) free_memory_bytes: Option<u64>,
+    /// Number of seconds the host has been running
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) uptime_seconds: Option<u64>,
+    /// Snapshot of the host labels at the time of the bid
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) labels: BTreeMap<String, String>,
+}
+
+impl AuctionHints {
+    /// Get the number of components running on the host
+    #[must_use]
+    pub fn component_count(&self) -> Option<u64> {
+        self.component_count
+    }
+
+    /// Get the number of providers running on the host
+    #[must_use]
+    pub fn provider_count(&self) -> Option<u64> {
+        self.provider_count
+    }
+
+    /// Get the estimated free memory on the host, in bytes
+    #[must_use]
+    pub fn free_memory_bytes(&self) -> Option<u64> {
+        self.free_memory_bytes
+    }
+
+    /// Get the host uptime in seconds
+    #[must_use]
+    pub fn uptime_seconds(&self) -> Option<u64> {
+        self.uptime_seconds
+    }
+
+    /// Get the host labels at the time of the bid
+    #[must_use]
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// Returns true if the host reported no hints
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.component_count.is_none()
+            && self.provider_count.is_none()
+            && self.free_memory_bytes.is_none()
+            && self.uptime_seconds.is_none()
+            && self.labels.is_empty()
+    }
+
+    #[must_use]
+    pub fn builder() -> AuctionHintsBuilder {
+        AuctionHintsBuilder::default()
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct AuctionHintsBuilder {
+    component_count: Option<u64>,
+    provider_count: Option<u64>,
+    free_memory_bytes: Option<u64>,
+    uptime_seconds: Option<u64>,
+    labels: BTreeMap<String, String>,
+}
+
+impl AuctionHintsBuilder {
+    #[must_use]
+    pub fn component_count(mut self, v: u64) -> Self {
+        self.component_count = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn provider_count(mut self, v: u64) -> Self {
+        self.provider_count = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn free_memory_bytes(mut self, v: u64) -> Self {
+        self.free_memory_bytes = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn uptime_seconds(mut self, v: u64) -> Self {
+        self.uptime_seconds = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn labels(mut self, v: BTreeMap<String, String>) -> Self {
+        self.labels = v;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> AuctionHints {
+        AuctionHints {
+            component_count: self.component_count,
+            provider_count: self.provider_count,
+            free_memory_bytes: self.free_memory_bytes,
+            uptime_seconds: self.uptime_seconds,
+            labels: self.labels,
+        }
+    }
+}
+
+/// An auction acknowledgement that can be ranked by
+/// [`rank_auction_results`](crate::client::rank_auction_results)
+pub trait AuctionBid {
+    /// Get the capacity hints reported by the bidding host
+    fn hints(&self) -> &AuctionHints;
+
+    /// Get the number of workloads of the auctioned kind already running on the bidding host
+    fn running_count(&self) -> Option<u64>;
+}
+
+impl AuctionBid for ComponentAuctionAck {
+    fn hints(&self) -> &AuctionHints {
+        &self.hints
+    }
+
+    fn running_count(&self) -> Option<u64> {
+        self.hints.component_count
+    }
+}
+
+impl AuctionBid for ProviderAuctionAck {
+    fn hints(&self) -> &AuctionHints {
+        &self.hints
+    }
+
+    fn running_count(&self) -> Option<u64> {
+        self.hints.provider_count
+    }
+}
+
+/// Resources that a host must be able to provide to bid on an auction.
+///
+/// Hosts that cannot satisfy every requirement decline the auction. Hosts older than 1.10.0 ignore
+/// requirements entirely.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ResourceRequirements {
+    /// Minimum amount of memory, in bytes, that must be available on the host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) min_memory_bytes: Option<u64>,
+    /// Maximum number of workloads of the auctioned kind (components or providers) that may
+    /// already be running on the host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_running: Option<u32>,
+    /// Features the host must support, e.g. `wasi-nn`. A host supports a feature if it is an
+    /// enabled experimental feature of the host or the host has a `feature.<name>=true` label.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub(crate) features: BTreeSet<String>,
+}
+
+impl ResourceRequirements {
+    /// Get the minimum amount of memory, in bytes, that must be available on the host
+    #[must_use]
+    pub fn min_memory_bytes(&self) -> Option<u64> {
+        self.min_memory_bytes
+    }
+
+    /// Get the maximum number of workloads of the auctioned kind already running on the host
+    #[must_use]
+    pub fn max_running(&self) -> Option<u32> {
+        self.max_running
+    }
+
+    /// Get the features the host must support
+    #[must_use]
+    pub fn features(&self) -> &BTreeSet<String> {
+        &self.features
+    }
+
+    /// Returns true if no requirements are set
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.min_memory_bytes.is_none() && self.max_running.is_none() && self.features.is_empty()
+    }
+
+    #[must_use]
+    pub fn builder() -> ResourceRequirementsBuilder {
+        ResourceRequirementsBuilder::default()
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct ResourceRequirementsBuilder {
+    min_memory_bytes: Option<u64>,
+    max_running: Option<u32>,
+    features: BTreeSet<String>,
+}
+
+impl ResourceRequirementsBuilder {
+    #[must_use]
+    pub fn min_memory_bytes(mut self, v: u64) -> Self {
+        self.min_memory_bytes = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn max_running(mut self, v: u32) -> Self {
+        self.max_running = Some(v);
+        self
+    }
+
+    #[must_use]
+    pub fn feature(mut self, v: impl Into<String>) -> Self {
+        self.features.insert(v.into());
+        self
+    }
+
+    #[must_use]
+    pub fn features(mut self, v: impl IntoIterator<Item = String>) -> Self {
+        self.features.extend(v);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> ResourceRequirements {
+        ResourceRequirements {
+            min_memory_bytes: self.min_memory_bytes,
+            max_running: self.max_running,
+            features: self.features,
+        }
+    }
+}
+
 /// A request to locate suitable hosts for a given component
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 #[non_exhaustive]
@@ -133,6 +399,9 @@ pub struct ComponentAuctionRequest {
     pub(crate) component_id: String,
     /// The set of constraints that must match the labels of a suitable target host
     pub(crate) constraints: BTreeMap<String, String>,
+    /// Resources a suitable target host must be able to provide
+    #[serde(default, skip_serializing_if = "ResourceRequirements::is_empty")]
+    pub(crate) requirements: ResourceRequirements,
 }
 
 impl ComponentAuctionRequest {
@@ -154,6 +423,12 @@ impl ComponentAuctionRequest {
         &self.constraints
     }
 
+    /// Get the resource requirements for the auction request
+    #[must_use]
+    pub fn requirements(&self) -> &ResourceRequirements {
+        &self.requirements
+    }
+
     pub fn builder() -> ComponentAuctionRequestBuilder {
         ComponentAuctionRequestBuilder::default()
     }
@@ -164,6 +439,7 @@ pub struct ComponentAuctionRequestBuilder {
     component_ref: Option<String>,
     component_id: Option<String>,
     constraints: Option<BTreeMap<String, String>>,
+    requirements: Option<ResourceRequirements>,
 }
 
 impl ComponentAuctionRequestBuilder {
@@ -185,6 +461,12 @@ impl ComponentAuctionRequestBuilder {
         self
     }
 
+    #[must_use]
+    pub fn requirements(mut self, v: ResourceRequirements) -> Self {
+        self.requirements = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ComponentAuctionRequest> {
         Ok(ComponentAuctionRequest {
             component_ref: self
@@ -194,6 +476,7 @@ impl ComponentAuctionRequestBuilder {
                 .component_id
                 .ok_or_else(|| "component_id is required".to_string())?,
             constraints: self.constraints.unwrap_or_default(),
+            requirements: self.requirements.unwrap_or_default(),
         })
     }
 }
@@ -218,6 +501,9 @@ pub struct ProviderAuctionAck {
     /// The constraints provided for the auction
     #[serde(default)]
     pub(crate) constraints: BTreeMap<String, String>,
+    /// Capacity hints reported by the bidding host
+    #[serde(default, skip_serializing_if = "AuctionHints::is_empty")]
+    pub(crate) hints: AuctionHints,
 }
 
 impl ProviderAuctionAck {
@@ -245,6 +531,12 @@ impl ProviderAuctionAck {
         &self.constraints
     }
 
+    /// Get the capacity hints reported by the bidding host
+    #[must_use]
+    pub fn hints(&self) -> &AuctionHints {
+        &self.hints
+    }
+
     #[must_use]
     pub fn builder() -> ProviderAuctionAckBuilder {
         ProviderAuctionAckBuilder::default()
@@ -257,6 +549,7 @@ pub struct ProviderAuctionAckBuilder {
     provider_ref: Option<String>,
     provider_id: Option<String>,
     constraints: Option<BTreeMap<String, String>>,
+    hints: Option<AuctionHints>,
 }
 
 impl ProviderAuctionAckBuilder {
@@ -284,6 +577,12 @@ impl ProviderAuctionAckBuilder {
         self
     }
 
+    #[must_use]
+    pub fn hints(mut self, v: AuctionHints) -> Self {
+        self.hints = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ProviderAuctionAck> {
         Ok(ProviderAuctionAck {
             provider_ref: self
@@ -296,6 +595,7 @@ impl ProviderAuctionAckBuilder {
                 .host_id
                 .ok_or_else(|| "host_id is required".to_string())?,
             constraints: self.constraints.unwrap_or_default(),
+            hints: self.hints.unwrap_or_default(),
         })
     }
 }
@@ -316,6 +616,10 @@ pub struct ProviderAuctionRequest {
 
     /// The set of constraints that must match the labels of a suitable target host
     pub(crate) constraints: BTreeMap<String, String>,
+
+    /// Resources a suitable target host must be able to provide
+    #[serde(default, skip_serializing_if = "ResourceRequirements::is_empty")]
+    pub(crate) requirements: ResourceRequirements,
 }
 
 impl ProviderAuctionRequest {
@@ -337,6 +641,12 @@ impl ProviderAuctionRequest {
         &self.constraints
     }
 
+    /// Get the resource requirements for the auction request
+    #[must_use]
+    pub fn requirements(&self) -> &ResourceRequirements {
+        &self.requirements
+    }
+
     /// Build a new [`ProviderAuctionRequest`]
     #[must_use]
     pub fn builder() -> ProviderAuctionRequestBuilder {
@@ -349,6 +659,7 @@ pub struct ProviderAuctionRequestBuilder {
     provider_ref: Option<String>,
     provider_id: Option<String>,
     constraints: Option<BTreeMap<String, String>>,
+    requirements: Option<ResourceRequirements>,
 }
 
 impl ProviderAuctionRequestBuilder {
@@ -370,6 +681,12 @@ impl ProviderAuctionRequestBuilder {
         self
     }
 
+    #[must_use]
+    pub fn requirements(mut self, v: ResourceRequirements) -> Self {
+        self.requirements = Some(v);
+        self
+    }
+
     pub fn build(self) -> Result<ProviderAuctionRequest> {
         Ok(ProviderAuctionRequest {
             provider_ref: self
@@ -379,6 +696,7 @@ impl ProviderAuctionRequestBuilder {
                 .provider_id
                 .ok_or_else(|| "provider_id is required".to_string())?,
             constraints: self.constraints.unwrap_or_default(),
+            requirements: self.requirements.unwrap_or_default(),
         })
     }
 }
@@ -491,19 +809,120 @@ impl DeleteInterfaceLinkDefinitionRequestBuilder {
     }
 }
 
+/// A request to put a link into the lattice, along with options controlling how a link that
+/// already exists for the same source, WIT interface and link name is treated.
+///
+/// The options are flattened next to the link fields on the wire, so hosts that don't know about
+/// them still see a plain [`Link`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct PutLinkRequest {
+    /// The link to put
+    #[serde(flatten)]
+    pub(crate) link: Link,
+
+    /// Replace an existing link on the same source, WIT interface and link name even if it points
+    /// to a different target
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) force: bool,
+
+    /// Reject the link if any link already exists on the same source, WIT interface and link
+    /// name, even if it points to the same target
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub(crate) if_absent: bool,
+}
+
+impl PutLinkRequest {
+    /// Get the link to put
+    #[must_use]
+    pub fn link(&self) -> &Link {
+        &self.link
+    }
+
+    /// Whether conflicting links with a different target should be replaced
+    #[must_use]
+    pub fn force(&self) -> bool {
+        self.force
+    }
+
+    /// Whether the link should only be put if no link exists for the same source, WIT interface
+    /// and link name
+    #[must_use]
+    pub fn if_absent(&self) -> bool {
+        self.if_absent
+    }
+
+    #[must_use]
+    pub fn builder() -> PutLinkRequestBuilder {
+        PutLinkRequestBuilder::default()
+    }
+}
+
+impl From<Link> for PutLinkRequest {
+    fn from(link: Link) -> Self {
+        Self {
+            link,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct PutLinkRequestBuilder {
+    link: Option<Link>,
+    force: bool,
+    if_absent: bool,
+}
+
+impl PutLinkRequestBuilder {
+    pub fn link(mut self, v: Link) -> Self {
+        self.link = Some(v);
+        self
+    }
+
+    pub fn force(mut self, v: bool) -> Self {
+        self.force = v;
+        self
+    }
+
+    pub fn if_absent(mut self, v: bool) -> Self {
+        self.if_absent = v;
+        self
+    }
+
+    pub fn build(self) -> Result<PutLinkRequest> {
+        if self.force && self.if_absent {
+            return Err("force and if_absent cannot both be set".into());
+        }
+        Ok(PutLinkRequest {
+            link: self.link.ok_or_else(|| "link is required".to_string())?,
+            force: self.force,
+            if_absent: self.if_absent,
+        })
+    }
+}
+
 /// Helper function to provide a default link name
 fn default_link_name() -> String {
     "default".to_string()
 }
 
+/// Helper function to skip serializing unset flags
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by serde
+fn is_false(v: &bool) -> bool {
+    !v
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
 
     use super::{
-        ComponentAuctionAck, ComponentAuctionRequest, DeleteInterfaceLinkDefinitionRequest,
-        ProviderAuctionAck, ProviderAuctionRequest,
+        AuctionHints, ComponentAuctionAck, ComponentAuctionRequest,
+        DeleteInterfaceLinkDefinitionRequest, ProviderAuctionAck, ProviderAuctionRequest,
+        PutLinkRequest, ResourceRequirements,
     };
+    use crate::Link;
 
     #[test]
     fn component_auction_ack_builder() {
@@ -512,7 +931,8 @@ mod tests {
                 component_ref: "component_ref".into(),
                 component_id: "component_id".into(),
                 host_id: "host_id".into(),
-                constraints: BTreeMap::from([("a".into(), "b".into())])
+                constraints: BTreeMap::from([("a".into(), "b".into())]),
+                hints: AuctionHints::default(),
             },
             ComponentAuctionAck::builder()
                 .component_ref("component_ref".into())
@@ -530,7 +950,8 @@ mod tests {
             ComponentAuctionRequest {
                 component_ref: "component_ref".into(),
                 component_id: "component_id".into(),
-                constraints: BTreeMap::from([("a".into(), "b".into())])
+                constraints: BTreeMap::from([("a".into(), "b".into())]),
+                requirements: ResourceRequirements::default(),
             },
             ComponentAuctionRequest::builder()
                 .component_ref("component_ref".into())
@@ -541,6 +962,96 @@ mod tests {
         )
     }
 
+    #[test]
+    fn auction_request_requirements() {
+        let requirements = ResourceRequirements::builder()
+            .min_memory_bytes(512 * 1024 * 1024)
+            .max_running(10)
+            .feature("wasi-nn")
+            .build();
+        assert!(!requirements.is_empty());
+        assert!(ResourceRequirements::default().is_empty());
+
+        let req = ProviderAuctionRequest::builder()
+            .provider_ref("provider_ref".into())
+            .provider_id("provider_id".into())
+            .requirements(requirements.clone())
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json["requirements"],
+            serde_json::json!({
+                "min_memory_bytes": 536870912,
+                "max_running": 10,
+                "features": ["wasi-nn"],
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<ProviderAuctionRequest>(json)
+                .unwrap()
+                .requirements(),
+            &requirements
+        );
+
+        // Requests without requirements are unchanged on the wire for older hosts
+        let req = ComponentAuctionRequest::builder()
+            .component_ref("component_ref".into())
+            .component_id("component_id".into())
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&req).unwrap();
+        assert!(json.get("requirements").is_none());
+        assert!(serde_json::from_value::<ComponentAuctionRequest>(json)
+            .unwrap()
+            .requirements()
+            .is_empty());
+    }
+
+    #[test]
+    fn auction_ack_hints() {
+        let hints = AuctionHints::builder()
+            .provider_count(3)
+            .free_memory_bytes(1024)
+            .uptime_seconds(60)
+            .labels(BTreeMap::from([("zone".into(), "east".into())]))
+            .build();
+        let ack = ProviderAuctionAck::builder()
+            .provider_ref("provider_ref".into())
+            .provider_id("provider_id".into())
+            .host_id("host_id".into())
+            .hints(hints.clone())
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&ack).unwrap();
+        assert_eq!(
+            json["hints"],
+            serde_json::json!({
+                "provider_count": 3,
+                "free_memory_bytes": 1024,
+                "uptime_seconds": 60,
+                "labels": { "zone": "east" },
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<ProviderAuctionAck>(json)
+                .unwrap()
+                .hints(),
+            &hints
+        );
+
+        // Acks from hosts that send no hints still deserialize
+        let ack: ComponentAuctionAck = serde_json::from_value(serde_json::json!({
+            "component_ref": "component_ref",
+            "component_id": "component_id",
+            "host_id": "host_id",
+            "constraints": {},
+        }))
+        .unwrap();
+        assert!(ack.hints().is_empty());
+        assert!(serde_json::to_value(&ack).unwrap().get("hints").is_none());
+    }
+
     #[test]
     fn provider_auction_ack_builder() {
         assert_eq!(
@@ -548,7 +1059,8 @@ mod tests {
                 provider_ref: "provider_ref".into(),
                 provider_id: "provider_id".into(),
                 host_id: "host_id".into(),
-                constraints: BTreeMap::from([("a".into(), "b".into())])
+                constraints: BTreeMap::from([("a".into(), "b".into())]),
+                hints: AuctionHints::default(),
             },
             ProviderAuctionAck::builder()
                 .provider_ref("provider_ref".into())
@@ -566,7 +1078,8 @@ mod tests {
             ProviderAuctionRequest {
                 provider_ref: "provider_ref".into(),
                 provider_id: "provider_id".into(),
-                constraints: BTreeMap::from([("a".into(), "b".into())])
+                constraints: BTreeMap::from([("a".into(), "b".into())]),
+                requirements: ResourceRequirements::default(),
             },
             ProviderAuctionRequest::builder()
                 .provider_ref("provider_ref".into())
@@ -595,4 +1108,41 @@ mod tests {
                 .unwrap()
         )
     }
+
+    #[test]
+    fn put_link_request_wire_format() {
+        let link = Link::builder()
+            .source_id("source_id")
+            .target("target")
+            .name("default")
+            .wit_namespace("wasi")
+            .wit_package("keyvalue")
+            .interfaces(vec!["store".into()])
+            .build()
+            .unwrap();
+
+        // Without options the request serializes to a plain link
+        let plain = serde_json::to_value(PutLinkRequest::from(link.clone())).unwrap();
+        assert_eq!(plain, serde_json::to_value(&link).unwrap());
+
+        let forced = PutLinkRequest::builder()
+            .link(link.clone())
+            .force(true)
+            .build()
+            .unwrap();
+        let json = serde_json::to_vec(&forced).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<PutLinkRequest>(&json).unwrap(),
+            forced
+        );
+        // Hosts that only understand links can still read the request
+        assert_eq!(serde_json::from_slice::<Link>(&json).unwrap(), link);
+
+        assert!(PutLinkRequest::builder()
+            .link(link)
+            .force(true)
+            .if_absent(true)
+            .build()
+            .is_err());
+    }
 }
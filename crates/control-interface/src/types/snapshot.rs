@@ -0,0 +1,530 @@
+//! Point-in-time snapshots of the state of a lattice, and the changes between two snapshots
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ComponentDescription, HostInventory, Link, ProviderDescription};
+
+/// The state of a lattice at a point in time, as captured by
+/// [`Client::snapshot`](crate::Client::snapshot).
+///
+/// Snapshots only contain the desired and observed state of the lattice, not volatile data like
+/// host uptimes, so that two snapshots of an unchanged lattice compare equal. Snapshots can be
+/// serialized to e.g. keep an audit trail of a lattice, and compared with [`LatticeSnapshot::diff`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LatticeSnapshot {
+    /// The lattice the snapshot was taken of
+    pub(crate) lattice: String,
+    /// When the snapshot was taken, in milliseconds since the Unix epoch
+    pub(crate) taken_at: u64,
+    /// The hosts of the lattice, keyed by host ID
+    #[serde(default)]
+    pub(crate) hosts: BTreeMap<String, HostSnapshot>,
+    /// The links of the lattice, sorted by source ID
+    #[serde(default)]
+    pub(crate) links: Vec<Link>,
+    /// The named configs of the lattice, keyed by name
+    #[serde(default)]
+    pub(crate) configs: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// The state of a host in a [`LatticeSnapshot`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct HostSnapshot {
+    /// The human-readable friendly name of the host
+    #[serde(default)]
+    pub(crate) friendly_name: String,
+    /// The version of the host
+    #[serde(default)]
+    pub(crate) version: String,
+    /// The labels of the host
+    #[serde(default)]
+    pub(crate) labels: BTreeMap<String, String>,
+    /// The components running on the host, keyed by component ID
+    #[serde(default)]
+    pub(crate) components: BTreeMap<String, ComponentDescription>,
+    /// The providers running on the host, keyed by provider ID
+    #[serde(default)]
+    pub(crate) providers: BTreeMap<String, ProviderDescription>,
+}
+
+impl From<HostInventory> for HostSnapshot {
+    fn from(inventory: HostInventory) -> Self {
+        Self {
+            friendly_name: inventory.friendly_name,
+            version: inventory.version,
+            labels: inventory.labels,
+            components: inventory
+                .components
+                .into_iter()
+                .map(|component| (component.id.clone(), component))
+                .collect(),
+            providers: inventory
+                .providers
+                .into_iter()
+                .map(|provider| (provider.id.clone(), provider))
+                .collect(),
+        }
+    }
+}
+
+impl HostSnapshot {
+    /// Get the human-readable friendly name of the host
+    #[must_use]
+    pub fn friendly_name(&self) -> &str {
+        &self.friendly_name
+    }
+
+    /// Get the version of the host
+    #[must_use]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Get the labels of the host
+    #[must_use]
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.labels
+    }
+
+    /// Get the components running on the host, keyed by component ID
+    #[must_use]
+    pub fn components(&self) -> &BTreeMap<String, ComponentDescription> {
+        &self.components
+    }
+
+    /// Get the providers running on the host, keyed by provider ID
+    #[must_use]
+    pub fn providers(&self) -> &BTreeMap<String, ProviderDescription> {
+        &self.providers
+    }
+
+    /// Get whether the host itself changed, ignoring the workloads running on it
+    fn host_changed(&self, other: &Self) -> bool {
+        self.friendly_name != other.friendly_name
+            || self.version != other.version
+            || self.labels != other.labels
+    }
+}
+
+impl LatticeSnapshot {
+    /// Create a new [`LatticeSnapshot`] of `lattice` taken at `taken_at` milliseconds since the
+    /// Unix epoch
+    #[must_use]
+    pub fn new(
+        lattice: impl Into<String>,
+        taken_at: u64,
+        hosts: BTreeMap<String, HostSnapshot>,
+        mut links: Vec<Link>,
+        configs: BTreeMap<String, BTreeMap<String, String>>,
+    ) -> Self {
+        links.sort_by_cached_key(link_key);
+        Self {
+            lattice: lattice.into(),
+            taken_at,
+            hosts,
+            links,
+            configs,
+        }
+    }
+
+    /// Get the lattice the snapshot was taken of
+    #[must_use]
+    pub fn lattice(&self) -> &str {
+        &self.lattice
+    }
+
+    /// Get when the snapshot was taken, in milliseconds since the Unix epoch
+    #[must_use]
+    pub fn taken_at(&self) -> u64 {
+        self.taken_at
+    }
+
+    /// Get the hosts of the lattice, keyed by host ID
+    #[must_use]
+    pub fn hosts(&self) -> &BTreeMap<String, HostSnapshot> {
+        &self.hosts
+    }
+
+    /// Get the links of the lattice
+    #[must_use]
+    pub fn links(&self) -> &Vec<Link> {
+        &self.links
+    }
+
+    /// Get the named configs of the lattice, keyed by name
+    #[must_use]
+    pub fn configs(&self) -> &BTreeMap<String, BTreeMap<String, String>> {
+        &self.configs
+    }
+
+    /// Compute the changes from this snapshot to `other`, e.g. from an older snapshot to a newer
+    /// one.
+    ///
+    /// Components and providers are compared per host, so a component moving between hosts is
+    /// reported as removed from one host and added to the other. Links are identified by their
+    /// source, target, name and WIT namespace and package.
+    #[must_use]
+    pub fn diff(&self, other: &LatticeSnapshot) -> LatticeDiff {
+        let mut diff = LatticeDiff {
+            hosts: diff_maps(None, &self.hosts, &other.hosts, HostSnapshot::host_changed),
+            ..Default::default()
+        };
+        // Workloads are reported separately from the hosts they run on
+        for change in &mut diff.hosts {
+            change.change.strip_workloads();
+        }
+        let empty = HostSnapshot::default();
+        let host_ids: BTreeSet<_> = self.hosts.keys().chain(other.hosts.keys()).collect();
+        for host_id in host_ids {
+            let before = self.hosts.get(host_id).unwrap_or(&empty);
+            let after = other.hosts.get(host_id).unwrap_or(&empty);
+            diff.components.extend(diff_maps(
+                Some(host_id),
+                &before.components,
+                &after.components,
+                |a, b| a != b,
+            ));
+            diff.providers.extend(diff_maps(
+                Some(host_id),
+                &before.providers,
+                &after.providers,
+                |a, b| a != b,
+            ));
+        }
+        let links = |snapshot: &LatticeSnapshot| {
+            snapshot
+                .links
+                .iter()
+                .map(|link| (link_key(link), link.clone()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        diff.links = diff_maps(None, &links(self), &links(other), |a, b| a != b);
+        diff.configs = diff_maps(None, &self.configs, &other.configs, |a, b| a != b);
+        diff
+    }
+}
+
+/// Key identifying a link, used to order links and match them between snapshots
+fn link_key(link: &Link) -> String {
+    format!(
+        "{}/{}:{}/{}/{}",
+        link.source_id, link.wit_namespace, link.wit_package, link.name, link.target
+    )
+}
+
+/// Compute the changes between two maps, calling `changed` to compare values present in both
+fn diff_maps<T: Clone>(
+    host_id: Option<&String>,
+    before: &BTreeMap<String, T>,
+    after: &BTreeMap<String, T>,
+    changed: impl Fn(&T, &T) -> bool,
+) -> Vec<ResourceChange<T>> {
+    let mut changes = Vec::new();
+    for (id, old) in before {
+        let change = match after.get(id) {
+            None => Change::Removed {
+                before: old.clone(),
+            },
+            Some(new) if changed(old, new) => Change::Modified {
+                before: old.clone(),
+                after: new.clone(),
+            },
+            Some(_) => continue,
+        };
+        changes.push(ResourceChange {
+            host_id: host_id.cloned(),
+            id: id.clone(),
+            change,
+        });
+    }
+    for (id, new) in after {
+        if !before.contains_key(id) {
+            changes.push(ResourceChange {
+                host_id: host_id.cloned(),
+                id: id.clone(),
+                change: Change::Added { after: new.clone() },
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.id.cmp(&b.id));
+    changes
+}
+
+/// A change to a single resource between two [`LatticeSnapshot`]s
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Change<T> {
+    /// The resource was added
+    Added {
+        /// The added resource
+        after: T,
+    },
+    /// The resource was removed
+    Removed {
+        /// The removed resource
+        before: T,
+    },
+    /// The resource was modified
+    Modified {
+        /// The resource before the change
+        before: T,
+        /// The resource after the change
+        after: T,
+    },
+}
+
+impl<T> Change<T> {
+    /// Get the resource before the change, if it existed
+    #[must_use]
+    pub fn before(&self) -> Option<&T> {
+        match self {
+            Self::Removed { before } | Self::Modified { before, .. } => Some(before),
+            Self::Added { .. } => None,
+        }
+    }
+
+    /// Get the resource after the change, if it still exists
+    #[must_use]
+    pub fn after(&self) -> Option<&T> {
+        match self {
+            Self::Added { after } | Self::Modified { after, .. } => Some(after),
+            Self::Removed { .. } => None,
+        }
+    }
+}
+
+impl Change<HostSnapshot> {
+    /// Remove the workloads of the host snapshots, which are reported separately
+    fn strip_workloads(&mut self) {
+        let strip = |host: &mut HostSnapshot| {
+            host.components.clear();
+            host.providers.clear();
+        };
+        match self {
+            Self::Added { after: host } | Self::Removed { before: host } => strip(host),
+            Self::Modified { before, after } => {
+                strip(before);
+                strip(after);
+            }
+        }
+    }
+}
+
+/// A [`Change`] of an identified resource
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ResourceChange<T> {
+    /// The host the resource runs on, for components and providers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) host_id: Option<String>,
+    /// The ID of the resource, i.e. the host ID, component ID, provider ID, link key or config
+    /// name
+    pub(crate) id: String,
+    /// The change
+    #[serde(flatten)]
+    pub(crate) change: Change<T>,
+}
+
+impl<T> ResourceChange<T> {
+    /// Get the host the resource runs on, for components and providers
+    #[must_use]
+    pub fn host_id(&self) -> Option<&str> {
+        self.host_id.as_deref()
+    }
+
+    /// Get the ID of the resource. Links are identified by a key of the form
+    /// `<source_id>/<wit_namespace>:<wit_package>/<name>/<target>`
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Get the change
+    #[must_use]
+    pub fn change(&self) -> &Change<T> {
+        &self.change
+    }
+}
+
+/// The changes between two [`LatticeSnapshot`]s, as computed by [`LatticeSnapshot::diff`].
+///
+/// Changes of hosts only cover the hosts themselves, i.e. their name, version and labels, while
+/// the components and providers running on them are reported in [`LatticeDiff::components`] and
+/// [`LatticeDiff::providers`].
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LatticeDiff {
+    /// Changes of hosts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) hosts: Vec<ResourceChange<HostSnapshot>>,
+    /// Changes of components
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) components: Vec<ResourceChange<ComponentDescription>>,
+    /// Changes of providers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) providers: Vec<ResourceChange<ProviderDescription>>,
+    /// Changes of links
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) links: Vec<ResourceChange<Link>>,
+    /// Changes of named configs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) configs: Vec<ResourceChange<BTreeMap<String, String>>>,
+}
+
+impl LatticeDiff {
+    /// Get the changes of hosts
+    #[must_use]
+    pub fn hosts(&self) -> &Vec<ResourceChange<HostSnapshot>> {
+        &self.hosts
+    }
+
+    /// Get the changes of components
+    #[must_use]
+    pub fn components(&self) -> &Vec<ResourceChange<ComponentDescription>> {
+        &self.components
+    }
+
+    /// Get the changes of providers
+    #[must_use]
+    pub fn providers(&self) -> &Vec<ResourceChange<ProviderDescription>> {
+        &self.providers
+    }
+
+    /// Get the changes of links
+    #[must_use]
+    pub fn links(&self) -> &Vec<ResourceChange<Link>> {
+        &self.links
+    }
+
+    /// Get the changes of named configs
+    #[must_use]
+    pub fn configs(&self) -> &Vec<ResourceChange<BTreeMap<String, String>>> {
+        &self.configs
+    }
+
+    /// Get the total number of changes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+            + self.components.len()
+            + self.providers.len()
+            + self.links.len()
+            + self.configs.len()
+    }
+
+    /// Get whether the snapshots were equivalent
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{Change, HostSnapshot, LatticeSnapshot};
+    use crate::{ComponentDescription, Link};
+
+    fn host(components: &[(&str, u32)]) -> HostSnapshot {
+        HostSnapshot {
+            version: "1.0.0".into(),
+            components: components
+                .iter()
+                .map(|(id, max_instances)| {
+                    (
+                        id.to_string(),
+                        ComponentDescription {
+                            id: id.to_string(),
+                            max_instances: *max_instances,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn link(source_id: &str, target: &str) -> Link {
+        Link {
+            source_id: source_id.into(),
+            target: target.into(),
+            name: "default".into(),
+            wit_namespace: "wasi".into(),
+            wit_package: "http".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lattice_snapshot_diff() {
+        let before = LatticeSnapshot::new(
+            "default",
+            1,
+            BTreeMap::from([
+                ("host-a".into(), host(&[("echo", 1), ("kv", 1)])),
+                ("host-b".into(), host(&[])),
+            ]),
+            vec![link("http", "echo")],
+            BTreeMap::from([("cfg".into(), BTreeMap::from([("a".into(), "1".into())]))]),
+        );
+        assert!(before.diff(&before).is_empty());
+
+        let mut upgraded = host(&[]);
+        upgraded.version = "1.1.0".into();
+        let after = LatticeSnapshot::new(
+            "default",
+            2,
+            BTreeMap::from([
+                ("host-a".into(), host(&[("echo", 10)])),
+                ("host-c".into(), upgraded),
+            ]),
+            vec![link("http", "echo-v2")],
+            BTreeMap::from([("cfg".into(), BTreeMap::from([("a".into(), "2".into())]))]),
+        );
+        let diff = before.diff(&after);
+        assert_eq!(diff.len(), 7);
+
+        let hosts: Vec<_> = diff.hosts().iter().map(|c| c.id()).collect();
+        assert_eq!(hosts, ["host-b", "host-c"]);
+        assert!(matches!(diff.hosts()[0].change(), Change::Removed { .. }));
+        assert_eq!(
+            diff.hosts()[1].change().after().map(HostSnapshot::version),
+            Some("1.1.0")
+        );
+
+        assert_eq!(diff.components().len(), 2);
+        assert_eq!(diff.components()[0].host_id(), Some("host-a"));
+        assert_eq!(diff.components()[0].id(), "echo");
+        assert!(matches!(
+            diff.components()[0].change(),
+            Change::Modified { before, after }
+                if before.max_instances == 1 && after.max_instances == 10
+        ));
+        assert_eq!(diff.components()[1].id(), "kv");
+        assert!(matches!(
+            diff.components()[1].change(),
+            Change::Removed { .. }
+        ));
+
+        assert_eq!(diff.links().len(), 2);
+        assert_eq!(diff.configs().len(), 1);
+
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(
+            serde_json::from_str::<super::LatticeDiff>(&json).unwrap(),
+            diff
+        );
+        let json = serde_json::to_string(&after).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LatticeSnapshot>(&json).unwrap(),
+            after
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Tracking of host versions observed on the lattice, used to reject requests that a target host
+//! is too old to understand instead of waiting for a request to time out.
+
+use core::fmt;
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use semver::Version;
+use tracing::trace;
+
+use crate::Result;
+
+/// Minimum host version required for control interface operations, keyed by the name of the
+/// [`Client`](crate::Client) method. Operations not listed here are not gated.
+const MINIMUM_HOST_VERSIONS: &[(&str, Version)] = &[
+    ("get_host_inventory", Version::new(1, 0, 0)),
+    ("scale_component", Version::new(1, 0, 0)),
+    ("update_component", Version::new(1, 0, 0)),
+    ("stop_components_matching", Version::new(1, 10, 0)),
+    ("start_provider", Version::new(1, 0, 0)),
+    ("stop_provider", Version::new(1, 0, 0)),
+    ("update_provider_config", Version::new(1, 10, 0)),
+    ("stop_host", Version::new(1, 0, 0)),
+    ("decommission_host", Version::new(1, 10, 0)),
+    ("drain_host", Version::new(1, 10, 0)),
+    ("prefetch_images", Version::new(1, 10, 0)),
+    ("cleanup_host_data", Version::new(1, 10, 0)),
+    ("update_host_tracing", Version::new(1, 10, 0)),
+    ("profile_component", Version::new(1, 10, 0)),
+    ("get_top_memory_consumers", Version::new(1, 10, 0)),
+    ("put_link_if_absent", Version::new(1, 10, 0)),
+    ("put_label", Version::new(1, 0, 0)),
+    ("delete_label", Version::new(1, 0, 0)),
+    ("put_labels", Version::new(1, 10, 0)),
+    ("delete_labels", Version::new(1, 10, 0)),
+    ("put_host_config", Version::new(1, 10, 0)),
+    ("get_host_config", Version::new(1, 10, 0)),
+    ("delete_host_config", Version::new(1, 10, 0)),
+];
+
+/// Minimum host version that serves the `v2` control interface subjects, see
+/// [`ProtocolVersion`](crate::ProtocolVersion)
+const MINIMUM_V2_HOST_VERSION: Version = Version::new(1, 10, 0);
+
+/// Returns the minimum host version required to handle the given operation, if any
+pub(crate) fn minimum_host_version(operation: &str) -> Option<&'static Version> {
+    MINIMUM_HOST_VERSIONS
+        .iter()
+        .find_map(|(op, version)| (*op == operation).then_some(version))
+}
+
+/// Pre-release builds of a version are expected to support that version's APIs, so they are
+/// compared as the release they precede
+fn release(version: &Version) -> Version {
+    Version::new(version.major, version.minor, version.patch)
+}
+
+/// Error returned when an operation targets a host whose version is known to not support it.
+///
+/// Since the client returns boxed errors, callers can detect this case with
+/// [`downcast_ref`](std::error::Error::downcast_ref).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnsupportedByHost {
+    pub(crate) host_id: String,
+    pub(crate) host_version: Version,
+    pub(crate) operation: String,
+    pub(crate) minimum_version: Version,
+}
+
+impl UnsupportedByHost {
+    /// Get the ID of the host that does not support the operation
+    #[must_use]
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    /// Get the version last reported by the host
+    #[must_use]
+    pub fn host_version(&self) -> &Version {
+        &self.host_version
+    }
+
+    /// Get the name of the unsupported operation
+    #[must_use]
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Get the minimum host version that supports the operation
+    #[must_use]
+    pub fn minimum_version(&self) -> &Version {
+        &self.minimum_version
+    }
+}
+
+impl fmt::Display for UnsupportedByHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "host {} is running version {}, but `{}` requires version {} or later",
+            self.host_id, self.host_version, self.operation, self.minimum_version
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedByHost {}
+
+/// Cache of host versions observed in host listings and heartbeats
+#[derive(Clone, Debug, Default)]
+pub(crate) struct HostVersions(Arc<RwLock<HashMap<String, Version>>>);
+
+impl HostVersions {
+    /// Record the version reported by a host. Versions that cannot be parsed are ignored
+    pub(crate) fn observe(&self, host_id: &str, version: &str) {
+        let Ok(version) = Version::parse(version.trim_start_matches('v')) else {
+            trace!(host_id, version, "ignoring unparseable host version");
+            return;
+        };
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(host_id.to_string(), version);
+    }
+
+    /// Get the last version observed for the given host
+    pub(crate) fn get(&self, host_id: &str) -> Option<Version> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(host_id)
+            .cloned()
+    }
+
+    /// Ensure that the given host supports the operation. Hosts whose version has not been
+    /// observed are assumed to support every operation.
+    pub(crate) fn check(&self, host_id: &str, operation: &str) -> Result<()> {
+        let (Some(minimum_version), Some(host_version)) =
+            (minimum_host_version(operation), self.get(host_id))
+        else {
+            return Ok(());
+        };
+        if release(&host_version) < *minimum_version {
+            return Err(UnsupportedByHost {
+                host_id: host_id.to_string(),
+                host_version,
+                operation: operation.to_string(),
+                minimum_version: minimum_version.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Whether the given host is expected to serve `v2` subjects. Hosts whose version has not been
+    /// observed are assumed to serve them, requests to hosts that don't fall back to `v1` subjects.
+    pub(crate) fn serves_v2(&self, host_id: &str) -> bool {
+        self.get(host_id)
+            .is_none_or(|version| release(&version) >= MINIMUM_V2_HOST_VERSION)
+    }
+
+    /// Whether every host observed on the lattice serves `v2` subjects. Lattice-wide requests can't
+    /// tell whether a host ignored them, so this is false until at least one host was observed.
+    pub(crate) fn lattice_serves_v2(&self) -> bool {
+        let versions = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        !versions.is_empty()
+            && versions
+                .values()
+                .all(|version| release(version) >= MINIMUM_V2_HOST_VERSION)
+    }
+
+    /// Ensure that all hosts observed on the lattice support the operation, for requests that any
+    /// host of the lattice may answer
+    pub(crate) fn check_lattice(&self, operation: &str) -> Result<()> {
+        let host_ids: Vec<_> = self
+            .0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+        host_ids
+            .iter()
+            .try_for_each(|host_id| self.check(host_id, operation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gates_on_observed_version() {
+        let versions = HostVersions::default();
+        // Unknown hosts are not gated
+        assert!(versions.check("host", "scale_component").is_ok());
+
+        versions.observe("host", "0.82.0");
+        let err = versions
+            .check("host", "scale_component")
+            .expect_err("old host should be rejected");
+        let err = err
+            .downcast_ref::<UnsupportedByHost>()
+            .expect("error should be UnsupportedByHost");
+        assert_eq!(err.host_id(), "host");
+        assert_eq!(err.minimum_version(), &Version::new(1, 0, 0));
+
+        versions.observe("host", "v1.0.0-rc.1");
+        assert!(versions.check("host", "scale_component").is_ok());
+
+        // Unparseable versions do not replace known ones
+        versions.observe("host", "not-a-version");
+        assert_eq!(
+            versions.get("host"),
+            Some(Version::parse("1.0.0-rc.1").unwrap())
+        );
+
+        // Operations without a minimum are never gated
+        versions.observe("host", "0.1.0");
+        assert!(versions.check("host", "get_links").is_ok());
+    }
+
+    #[test]
+    fn rejects_hosts_released_before_operation() {
+        let versions = HostVersions::default();
+        versions.observe("host", "1.9.0");
+        for (operation, minimum) in MINIMUM_HOST_VERSIONS {
+            if *minimum < Version::new(1, 10, 0) {
+                continue;
+            }
+            let err = versions
+                .check("host", operation)
+                .expect_err("1.9.0 host should be rejected");
+            assert_eq!(
+                err.downcast_ref::<UnsupportedByHost>()
+                    .map(UnsupportedByHost::operation),
+                Some(*operation)
+            );
+        }
+        for operation in [
+            "stop_components_matching",
+            "update_provider_config",
+            "decommission_host",
+            "drain_host",
+            "prefetch_images",
+            "cleanup_host_data",
+            "update_host_tracing",
+            "profile_component",
+            "get_top_memory_consumers",
+            "put_link_if_absent",
+            "put_labels",
+            "delete_labels",
+            "put_host_config",
+            "get_host_config",
+            "delete_host_config",
+        ] {
+            assert!(
+                versions.check("host", operation).is_err(),
+                "{operation} should be gated"
+            );
+        }
+        assert!(versions.check("host", "scale_component").is_ok());
+
+        versions.observe("host", "1.10.0-rc.1");
+        assert!(versions.check("host", "drain_host").is_ok());
+    }
+
+    #[test]
+    fn lattice_operations_require_every_host_to_support_them() {
+        let versions = HostVersions::default();
+        assert!(versions.check_lattice("put_link_if_absent").is_ok());
+
+        versions.observe("new", "1.10.0");
+        versions.observe("old", "1.9.0");
+        let err = versions
+            .check_lattice("put_link_if_absent")
+            .expect_err("old host should be rejected");
+        assert_eq!(
+            err.downcast_ref::<UnsupportedByHost>()
+                .map(UnsupportedByHost::host_id),
+            Some("old")
+        );
+        assert!(versions.check_lattice("get_links").is_ok());
+
+        versions.observe("old", "1.10.1");
+        assert!(versions.check_lattice("put_link_if_absent").is_ok());
+    }
+
+    #[test]
+    fn v2_subjects_require_every_host_to_serve_them() {
+        let versions = HostVersions::default();
+        assert!(versions.serves_v2("new"));
+        assert!(!versions.lattice_serves_v2());
+
+        versions.observe("new", "1.10.0-rc.1");
+        assert!(versions.serves_v2("new"));
+        assert!(versions.lattice_serves_v2());
+
+        versions.observe("old", "1.9.0");
+        assert!(!versions.serves_v2("old"));
+        assert!(!versions.lattice_serves_v2());
+    }
+}
@@ -0,0 +1,184 @@
+//! Content-addressed references from named config values to blobs of a lattice [NATS JetStream
+//! object store][os].
+//!
+//! Large config values, such as certificates or schema files, do not fit in the `CONFIGDATA` KV
+//! bucket. Instead, the value is stored in the object store returned by [`config_blob_bucket`]
+//! and the config value is set to a [`ConfigBlobRef`], e.g. `blob+sha256:2c26b46b…`.
+//! [`resolve_config_refs`] replaces references by the content of the blob they point to.
+//!
+//! [os]: https://docs.nats.io/nats-concepts/jetstream/obj_store
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+use std::collections::HashMap;
+
+use anyhow::{bail, ensure, Context as _};
+use async_nats::jetstream::object_store::ObjectStore;
+use sha2::{Digest as _, Sha256};
+use tokio::io::AsyncReadExt as _;
+use tracing::{debug, instrument};
+
+/// Prefix of config values that reference a blob of the config object store
+pub const CONFIG_BLOB_REF_PREFIX: &str = "blob+sha256:";
+
+/// Get the name of the object store holding config blobs for a lattice
+#[must_use]
+pub fn config_blob_bucket(lattice: &str) -> String {
+    format!("CONFIGBLOBS_{lattice}")
+}
+
+/// A reference to a config blob, identified by the SHA-256 digest of its content
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ConfigBlobRef {
+    digest: String,
+}
+
+impl ConfigBlobRef {
+    /// Create the reference to a blob with the given content
+    #[must_use]
+    pub fn for_content(content: &[u8]) -> Self {
+        Self {
+            digest: hex::encode(Sha256::digest(content)),
+        }
+    }
+
+    /// Parse a config value as a blob reference, returning `None` if the value is a plain value
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+
+    /// Get the hex-encoded SHA-256 digest of the blob content
+    #[must_use]
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+
+    /// Get the name of the blob in the config object store
+    #[must_use]
+    pub fn object_name(&self) -> String {
+        format!("sha256-{}", self.digest)
+    }
+
+    /// Verify that `content` is the content of the referenced blob
+    pub fn verify(&self, content: &[u8]) -> anyhow::Result<()> {
+        let actual = Self::for_content(content);
+        ensure!(
+            actual == *self,
+            "config blob digest mismatch, expected {} but got {}",
+            self.digest,
+            actual.digest
+        );
+        Ok(())
+    }
+}
+
+impl Display for ConfigBlobRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{CONFIG_BLOB_REF_PREFIX}{}", self.digest)
+    }
+}
+
+impl FromStr for ConfigBlobRef {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(digest) = s.strip_prefix(CONFIG_BLOB_REF_PREFIX) else {
+            bail!("config blob reference must start with `{CONFIG_BLOB_REF_PREFIX}`");
+        };
+        ensure!(
+            digest.len() == 64
+                && digest
+                    .bytes()
+                    .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)),
+            "config blob digest must be 64 lowercase hex characters"
+        );
+        Ok(Self {
+            digest: digest.to_string(),
+        })
+    }
+}
+
+/// Store `content` in the config object store, returning the reference to use as a config value.
+///
+/// Blobs are content-addressed, so storing the same content twice is a no-op.
+#[instrument(level = "debug", skip_all)]
+pub async fn put_config_blob(store: &ObjectStore, content: &[u8]) -> anyhow::Result<ConfigBlobRef> {
+    let blob = ConfigBlobRef::for_content(content);
+    let name = blob.object_name();
+    if store.info(&name).await.is_ok() {
+        debug!(%blob, "config blob already exists");
+        return Ok(blob);
+    }
+    store
+        .put(name.as_str(), &mut &*content)
+        .await
+        .with_context(|| format!("failed to store config blob `{blob}`"))?;
+    Ok(blob)
+}
+
+/// Fetch the content of a blob from the config object store, verifying its digest
+#[instrument(level = "debug", skip(store))]
+pub async fn get_config_blob(store: &ObjectStore, blob: &ConfigBlobRef) -> anyhow::Result<Vec<u8>> {
+    let mut object = store
+        .get(blob.object_name())
+        .await
+        .with_context(|| format!("failed to get config blob `{blob}`"))?;
+    let mut content = Vec::new();
+    object
+        .read_to_end(&mut content)
+        .await
+        .with_context(|| format!("failed to read config blob `{blob}`"))?;
+    blob.verify(&content)?;
+    Ok(content)
+}
+
+/// Replace every value of `config` that is a [`ConfigBlobRef`] by the content of the referenced
+/// blob, which must be valid UTF-8. Plain values are returned unchanged.
+pub async fn resolve_config_refs(
+    store: &ObjectStore,
+    mut config: HashMap<String, String>,
+) -> anyhow::Result<HashMap<String, String>> {
+    for (key, value) in &mut config {
+        let Some(blob) = ConfigBlobRef::parse(value) else {
+            continue;
+        };
+        let content = get_config_blob(store, &blob)
+            .await
+            .with_context(|| format!("failed to resolve config key `{key}`"))?;
+        *value = String::from_utf8(content)
+            .with_context(|| format!("config blob `{blob}` of key `{key}` is not valid UTF-8"))?;
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_blob_ref() {
+        let blob = ConfigBlobRef::for_content(b"foo");
+        assert_eq!(
+            blob.to_string(),
+            "blob+sha256:2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+        assert_eq!(ConfigBlobRef::parse(&blob.to_string()), Some(blob.clone()));
+        assert_eq!(
+            blob.object_name(),
+            "sha256-2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae"
+        );
+        assert!(blob.verify(b"foo").is_ok());
+        assert!(blob.verify(b"bar").is_err());
+
+        assert_eq!(ConfigBlobRef::parse("foo"), None);
+        assert_eq!(ConfigBlobRef::parse("blob+sha256:2c26b46b"), None);
+        assert_eq!(
+            ConfigBlobRef::parse(
+                "blob+sha256:2C26B46B68FFC68FF99B453C1D30413413422D706483BFA0F98A5E886266E7AE"
+            ),
+            None
+        );
+    }
+}
@@ -8,10 +8,10 @@ use secrecy::zeroize::{Zeroize, ZeroizeOnDrop};
 use serde::{Deserialize, Serialize};
 
 use crate::link::InterfaceLinkDefinition;
-use crate::logging::Level;
+use crate::logging::{Level, LogDirectives};
 use crate::otel::OtelConfig;
 use crate::secrets::SecretValue;
-use crate::wit::{deserialize_wit_map, serialize_wit_map, WitMap};
+use crate::wit::WitMap;
 
 /// Environment settings for initializing a capability provider
 pub type HostEnvValues = WitMap<String>;
@@ -33,10 +33,6 @@ pub struct HostData {
     pub lattice_rpc_url: String,
     #[serde(default)]
     pub provider_key: String,
-    #[serde(
-        serialize_with = "serialize_wit_map",
-        deserialize_with = "deserialize_wit_map"
-    )]
     pub env_values: HostEnvValues,
     #[serde(default)]
     pub instance_id: String,
@@ -58,7 +54,12 @@ pub struct HostData {
     #[serde(default)]
     pub provider_xkey_private_key: String,
     /// Host-wide default RPC timeout for rpc messages, in milliseconds.  Defaults to 2000.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    /// Also accepts human-readable durations like `"2s"`.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::units::option_millis"
+    )]
     pub default_rpc_timeout_ms: Option<u64>,
     /// True if structured logging is enabled for the host. Providers should use the same setting as the host.
     #[serde(default)]
@@ -66,6 +67,9 @@ pub struct HostData {
     /// The log level providers should log at
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub log_level: Option<Level>,
+    /// Per-target log filtering directives providers should apply on top of the log level
+    #[serde(default, skip_serializing_if = "LogDirectives::is_empty")]
+    pub log_directives: LogDirectives,
     #[serde(default)]
     pub otel_config: OtelConfig,
 }
@@ -0,0 +1,524 @@
+//! Typed configuration of a wasmCloud host
+//!
+//! [`HostConfig`] models the settings of a host (NATS connections, lattice, OCI, OpenTelemetry
+//! and resource limits) together with their defaults. It is shared by the host binary, `wash` and
+//! tests, so that all of them agree on the defaults and on which combinations of settings are
+//! valid. [`HostConfig::validate`] reports all invalid settings at once rather than failing on the
+//! first one.
+
+use core::fmt;
+use core::time::Duration;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::otel::OtelConfig;
+use crate::secrets::SecretString;
+use crate::units::{ByteSize, HumanDuration};
+
+/// Default lattice of a host
+pub const DEFAULT_LATTICE: &str = "default";
+
+/// Default host of the NATS server a host connects to
+pub const DEFAULT_NATS_HOST: &str = "127.0.0.1";
+
+/// Default port of the NATS server a host connects to
+pub const DEFAULT_NATS_PORT: u16 = 4222;
+
+/// Default prefix of the control interface topics
+pub const DEFAULT_CTL_TOPIC_PREFIX: &str = "wasmbus.ctl";
+
+/// Default timeout of RPC calls
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default maximum execution time of a component invocation
+pub const DEFAULT_MAX_EXECUTION_TIME: Duration = Duration::from_secs(10 * 60);
+
+/// Default maximum amount of linear memory a component can allocate, in bytes (256 MiB)
+pub const DEFAULT_MAX_LINEAR_MEMORY: u32 = 256 * 1024 * 1024;
+
+/// Default maximum size of a component binary that can be loaded, in bytes (50 MiB)
+pub const DEFAULT_MAX_COMPONENT_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Default maximum number of components that can run simultaneously
+pub const DEFAULT_MAX_COMPONENTS: u32 = 10_000;
+
+/// Default maximum number of core instances per component
+pub const DEFAULT_MAX_CORE_INSTANCES_PER_COMPONENT: u32 = 30;
+
+/// Configuration of a wasmCloud host. Settings missing when deserializing keep their defaults.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HostConfig {
+    /// The lattice the host belongs to
+    pub lattice: String,
+    /// Labels of the host
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+    /// Connection to the NATS server, used for both control interface and RPC messages unless
+    /// overridden in `ctl` or `rpc`
+    pub nats: HostNatsConfig,
+    /// Overrides of the NATS connection used for control interface messages
+    pub ctl: HostNatsOverrides,
+    /// Prefix of the control interface topics
+    pub ctl_topic_prefix: String,
+    /// Overrides of the NATS connection used for RPC messages
+    pub rpc: HostNatsOverrides,
+    /// Timeout of RPC calls
+    pub rpc_timeout: HumanDuration,
+    /// Pulling of artifacts from OCI registries
+    pub oci: HostOciConfig,
+    /// OpenTelemetry configuration of the host
+    pub otel: OtelConfig,
+    /// Resource limits of the components run by the host
+    pub limits: HostLimits,
+}
+
+impl Default for HostConfig {
+    fn default() -> Self {
+        Self {
+            lattice: DEFAULT_LATTICE.to_string(),
+            labels: HashMap::default(),
+            nats: HostNatsConfig::default(),
+            ctl: HostNatsOverrides::default(),
+            ctl_topic_prefix: DEFAULT_CTL_TOPIC_PREFIX.to_string(),
+            rpc: HostNatsOverrides::default(),
+            rpc_timeout: DEFAULT_RPC_TIMEOUT.into(),
+            oci: HostOciConfig::default(),
+            otel: OtelConfig::default(),
+            limits: HostLimits::default(),
+        }
+    }
+}
+
+/// Connection of a host to the NATS server
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HostNatsConfig {
+    /// Host of the NATS server
+    pub host: String,
+    /// Port of the NATS server
+    pub port: u16,
+    /// User JWT to authenticate with, requires `seed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt: Option<String>,
+    /// Seed nkey to authenticate with, requires `jwt`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<SecretString>,
+    /// Credentials file containing the JWT and seed to authenticate with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creds: Option<PathBuf>,
+    /// JetStream domain of the NATS server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub js_domain: Option<String>,
+}
+
+impl Default for HostNatsConfig {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_NATS_HOST.to_string(),
+            port: DEFAULT_NATS_PORT,
+            jwt: None,
+            seed: None,
+            creds: None,
+            js_domain: None,
+        }
+    }
+}
+
+/// Overrides of the [`HostNatsConfig`] for the control interface or RPC connection of a host
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HostNatsOverrides {
+    /// Host of the NATS server, defaults to the host of the shared connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Port of the NATS server, defaults to the port of the shared connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// User JWT to authenticate with, requires `seed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt: Option<String>,
+    /// Seed nkey to authenticate with, requires `jwt`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<SecretString>,
+    /// Credentials file containing the JWT and seed to authenticate with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creds: Option<PathBuf>,
+    /// Whether the connection requires TLS
+    pub tls: bool,
+}
+
+/// Pulling of artifacts from OCI registries by a host
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HostOciConfig {
+    /// Whether images tagged `latest` may be pulled
+    pub allow_latest: bool,
+    /// Registries to which insecure (non-TLS) connections are allowed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_insecure: Vec<String>,
+    /// Registry for which `user` and `password` are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// User to authenticate to `registry` with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Password to authenticate to `registry` with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<SecretString>,
+}
+
+/// Resource limits of the components run by a host
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct HostLimits {
+    /// Maximum execution time of a component invocation
+    pub max_execution_time: HumanDuration,
+    /// Maximum amount of linear memory a component can allocate
+    pub max_linear_memory: ByteSize,
+    /// Maximum size of a component binary that can be loaded
+    pub max_component_size: ByteSize,
+    /// Maximum number of components that can run simultaneously
+    pub max_components: u32,
+    /// Maximum number of core instances per component
+    pub max_core_instances_per_component: u32,
+}
+
+impl Default for HostLimits {
+    fn default() -> Self {
+        Self {
+            max_execution_time: DEFAULT_MAX_EXECUTION_TIME.into(),
+            max_linear_memory: u64::from(DEFAULT_MAX_LINEAR_MEMORY).into(),
+            max_component_size: DEFAULT_MAX_COMPONENT_SIZE.into(),
+            max_components: DEFAULT_MAX_COMPONENTS,
+            max_core_instances_per_component: DEFAULT_MAX_CORE_INSTANCES_PER_COMPONENT,
+        }
+    }
+}
+
+/// An invalid setting of a [`HostConfig`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HostConfigError {
+    /// Path of the invalid setting, e.g. `nats.port`
+    pub field: String,
+    /// Why the setting is invalid
+    pub message: String,
+}
+
+impl fmt::Display for HostConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.field, self.message)
+    }
+}
+
+/// All invalid settings of a [`HostConfig`], as returned by [`HostConfig::validate`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HostConfigErrors(pub Vec<HostConfigError>);
+
+impl HostConfigErrors {
+    fn push(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.0.push(HostConfigError {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Returns true if the invalid settings include `field`
+    #[must_use]
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.iter().any(|err| err.field == field)
+    }
+}
+
+impl fmt::Display for HostConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid host configuration")?;
+        for err in &self.0 {
+            write!(f, "\n  - {err}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HostConfigErrors {}
+
+impl HostConfig {
+    /// Reads the configuration from a JSON file. Settings missing from the file keep their
+    /// defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not contain a valid configuration
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read(path)
+            .with_context(|| format!("failed to read host config `{}`", path.display()))?;
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("invalid host config `{}`", path.display()))
+    }
+
+    /// Returns the URL of the NATS server used for control interface messages
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host and port do not form a valid URL
+    pub fn ctl_nats_url(&self) -> anyhow::Result<Url> {
+        self.nats_url(&self.ctl)
+            .context("failed to construct a valid control interface NATS URL")
+    }
+
+    /// Returns the URL of the NATS server used for RPC messages
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host and port do not form a valid URL
+    pub fn rpc_nats_url(&self) -> anyhow::Result<Url> {
+        self.nats_url(&self.rpc)
+            .context("failed to construct a valid RPC NATS URL")
+    }
+
+    fn nats_url(&self, overrides: &HostNatsOverrides) -> anyhow::Result<Url> {
+        let host = overrides.host.as_deref().unwrap_or(&self.nats.host);
+        let port = overrides.port.unwrap_or(self.nats.port);
+        Ok(Url::parse(&format!("nats://{host}:{port}"))?)
+    }
+
+    /// Checks the configuration for invalid settings and combinations of settings
+    ///
+    /// # Errors
+    ///
+    /// Returns all invalid settings found, rather than only the first
+    pub fn validate(&self) -> Result<(), HostConfigErrors> {
+        let mut errors = HostConfigErrors::default();
+
+        if self.lattice.is_empty() {
+            errors.push("lattice", "must not be empty");
+        } else if !is_subject_token(&self.lattice) {
+            errors.push(
+                "lattice",
+                "must not contain whitespace, `.`, `*` or `>`, as it is part of NATS subjects",
+            );
+        }
+        for key in self.labels.keys() {
+            if key.is_empty() {
+                errors.push("labels", "label keys must not be empty");
+            } else if key.to_lowercase().starts_with("hostcore.") {
+                errors.push(
+                    format!("labels.{key}"),
+                    "`hostcore.*` labels are set by the host and cannot be set manually",
+                );
+            }
+        }
+
+        if self.nats.host.is_empty() {
+            errors.push("nats.host", "must not be empty");
+        }
+        if self.nats.port == 0 {
+            errors.push("nats.port", "must not be 0");
+        }
+        validate_credentials(
+            &mut errors,
+            "nats",
+            self.nats.jwt.as_ref(),
+            self.nats.seed.as_ref(),
+            self.nats.creds.as_ref(),
+        );
+        if self.nats.js_domain.as_deref() == Some("") {
+            errors.push("nats.js_domain", "must not be empty if set");
+        }
+        for (section, overrides) in [("ctl", &self.ctl), ("rpc", &self.rpc)] {
+            if overrides.host.as_deref() == Some("") {
+                errors.push(format!("{section}.host"), "must not be empty if set");
+            }
+            if overrides.port == Some(0) {
+                errors.push(format!("{section}.port"), "must not be 0");
+            }
+            validate_credentials(
+                &mut errors,
+                section,
+                overrides.jwt.as_ref(),
+                overrides.seed.as_ref(),
+                overrides.creds.as_ref(),
+            );
+        }
+        if let Err(err) = self.ctl_nats_url() {
+            errors.push("ctl", format!("{err:#}"));
+        }
+        if let Err(err) = self.rpc_nats_url() {
+            errors.push("rpc", format!("{err:#}"));
+        }
+        if self.ctl_topic_prefix.is_empty()
+            || !self.ctl_topic_prefix.split('.').all(is_subject_token)
+        {
+            errors.push("ctl_topic_prefix", "must be a valid NATS subject");
+        }
+        if self.rpc_timeout.0.is_zero() {
+            errors.push("rpc_timeout", "must be greater than 0");
+        }
+
+        let registry_credentials = [
+            self.oci.registry.is_some(),
+            self.oci.user.is_some(),
+            self.oci.password.is_some(),
+        ];
+        if registry_credentials.contains(&true) && registry_credentials.contains(&false) {
+            errors.push(
+                "oci",
+                "`registry`, `user` and `password` must be set together",
+            );
+        }
+        if self.oci.allowed_insecure.iter().any(String::is_empty) {
+            errors.push("oci.allowed_insecure", "must not contain empty registries");
+        }
+
+        for (field, endpoint) in [
+            (
+                "otel.observability_endpoint",
+                &self.otel.observability_endpoint,
+            ),
+            ("otel.traces_endpoint", &self.otel.traces_endpoint),
+            ("otel.metrics_endpoint", &self.otel.metrics_endpoint),
+            ("otel.logs_endpoint", &self.otel.logs_endpoint),
+        ] {
+            if let Some(Err(err)) = endpoint.as_deref().map(Url::parse) {
+                errors.push(field, format!("invalid URL: {err}"));
+            }
+        }
+
+        if self.limits.max_execution_time.0.is_zero() {
+            errors.push("limits.max_execution_time", "must be greater than 0");
+        }
+        match self.limits.max_linear_memory.0 {
+            0 => errors.push("limits.max_linear_memory", "must be greater than 0"),
+            bytes if u32::try_from(bytes).is_err() => errors.push(
+                "limits.max_linear_memory",
+                format!("must not exceed {} bytes", u32::MAX),
+            ),
+            _ => {}
+        }
+        if self.limits.max_component_size.0 == 0 {
+            errors.push("limits.max_component_size", "must be greater than 0");
+        }
+        if self.limits.max_components == 0 {
+            errors.push("limits.max_components", "must be greater than 0");
+        }
+        if self.limits.max_core_instances_per_component == 0 {
+            errors.push(
+                "limits.max_core_instances_per_component",
+                "must be greater than 0",
+            );
+        }
+
+        if errors.0.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Returns true if `token` can be used as a single token of a NATS subject
+fn is_subject_token(token: &str) -> bool {
+    !token.is_empty()
+        && !token
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '.' | '*' | '>'))
+}
+
+/// Checks that a JWT and seed are given together and not together with a credentials file
+fn validate_credentials(
+    errors: &mut HostConfigErrors,
+    section: &str,
+    jwt: Option<&String>,
+    seed: Option<&SecretString>,
+    creds: Option<&PathBuf>,
+) {
+    if jwt.is_some() != seed.is_some() {
+        errors.push(section, "`jwt` and `seed` must be set together");
+    }
+    if creds.is_some() && (jwt.is_some() || seed.is_some()) {
+        errors.push(
+            section,
+            "`creds` cannot be set together with `jwt` and `seed`",
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid() {
+        let config: HostConfig = serde_json::from_str("{}").expect("failed to parse config");
+        assert_eq!(config.lattice, DEFAULT_LATTICE);
+        assert_eq!(config.rpc_timeout.0, DEFAULT_RPC_TIMEOUT);
+        assert_eq!(config.limits, HostLimits::default());
+        assert_eq!(
+            config.rpc_nats_url().expect("invalid RPC URL").as_str(),
+            "nats://127.0.0.1:4222"
+        );
+        config.validate().expect("default config is invalid");
+    }
+
+    #[test]
+    fn test_human_readable_values() {
+        let config: HostConfig = serde_json::from_str(
+            r#"{
+                "lattice": "prod",
+                "rpc": { "host": "rpc.example.com" },
+                "rpc_timeout": "5s",
+                "limits": { "max_linear_memory": "512MiB", "max_execution_time": "1m" }
+            }"#,
+        )
+        .expect("failed to parse config");
+        assert_eq!(config.rpc_timeout.0, Duration::from_secs(5));
+        assert_eq!(config.limits.max_linear_memory.0, 512 * 1024 * 1024);
+        assert_eq!(config.limits.max_execution_time.0, Duration::from_secs(60));
+        assert_eq!(
+            config.rpc_nats_url().expect("invalid RPC URL").as_str(),
+            "nats://rpc.example.com:4222"
+        );
+        assert_eq!(
+            config.ctl_nats_url().expect("invalid CTL URL").as_str(),
+            "nats://127.0.0.1:4222"
+        );
+    }
+
+    #[test]
+    fn test_validate_aggregates_errors() {
+        let mut config = HostConfig {
+            lattice: "my.lattice".to_string(),
+            ..Default::default()
+        };
+        config.nats.jwt = Some("jwt".to_string());
+        config.rpc.port = Some(0);
+        config.oci.registry = Some("ghcr.io".to_string());
+        config.otel.traces_endpoint = Some("not a url".to_string());
+        config.limits.max_components = 0;
+        config.limits.max_linear_memory = ByteSize(u64::from(u32::MAX) + 1);
+        config
+            .labels
+            .insert("hostcore.os".to_string(), "linux".to_string());
+
+        let errors = config.validate().expect_err("config should be invalid");
+        for field in [
+            "lattice",
+            "nats",
+            "rpc.port",
+            "oci",
+            "otel.traces_endpoint",
+            "limits.max_components",
+            "limits.max_linear_memory",
+            "labels.hostcore.os",
+        ] {
+            assert!(errors.contains(field), "missing error for `{field}`");
+        }
+        assert_eq!(errors.0.len(), 8);
+    }
+}
@@ -16,6 +16,7 @@
 use core::fmt;
 use core::ops::Deref;
 use core::str::FromStr;
+use core::time::Duration;
 
 use std::collections::HashMap;
 use std::io::ErrorKind;
@@ -29,6 +30,8 @@ use serde::{de, de::Deserializer, de::Visitor, Deserialize, Serialize};
 use tracing::{instrument, trace};
 use unicase::UniCase;
 
+use crate::units::parse_duration_or;
+
 const CORS_ALLOWED_ORIGINS: &[&str] = &[];
 const CORS_ALLOWED_METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE", "HEAD", "OPTIONS"];
 const CORS_ALLOWED_HEADERS: &[&str] = &[
@@ -60,6 +63,9 @@ pub struct ServiceSettings {
     pub cors_allowed_headers: Option<AllowedHeaders>,
     pub cors_allowed_methods: Option<AllowedMethods>,
     pub cors_exposed_headers: Option<ExposedHeaders>,
+    /// How long (seconds) browsers may cache CORS preflight responses. Also accepts
+    /// human-readable durations like `"5m"`
+    #[serde(default, with = "crate::units::option_secs")]
     pub cors_max_age_secs: Option<u64>,
     // tls config
     #[serde(default)]
@@ -69,8 +75,9 @@ pub struct ServiceSettings {
     pub tls_priv_key_file: Option<String>,
     /// Rpc timeout - how long (milliseconds) to wait for component's response
     /// before returning a status 503 to the http client
-    /// If not set, uses the system-wide rpc timeout
-    #[serde(default)]
+    /// If not set, uses the system-wide rpc timeout. Also accepts human-readable durations like
+    /// `"30s"`
+    #[serde(default, with = "crate::units::option_millis")]
     pub timeout_ms: Option<u64>,
     // DEPRECATED due to the nested struct being poorly supported by wasmCloud config
     #[deprecated(since = "0.22.0", note = "Use top-level fields instead")]
@@ -151,7 +158,7 @@ impl ServiceSettings {
                         errors.push(format!(
                             "missing tls_{} '{}'{}",
                             f.0,
-                            &path.display(),
+                            path.display(),
                             if path.is_absolute() {
                                 ""
                             } else {
@@ -255,9 +262,12 @@ pub fn load_settings(
     if let Some(readonly_mode) = values.get(&UniCase::new("readonly_mode")) {
         settings.readonly_mode = Some(readonly_mode.to_string().parse().unwrap_or(false));
     }
-    // accept timeout_ms flag
-    if let Some(Ok(timeout_ms)) = values.get(&UniCase::new("timeout_ms")).map(|s| s.parse()) {
-        settings.timeout_ms = Some(timeout_ms)
+    // accept timeout_ms flag, in milliseconds or as a human-readable duration
+    if let Some(Ok(timeout)) = values
+        .get(&UniCase::new("timeout_ms"))
+        .map(|s| parse_duration_or(s, Duration::from_millis(1)))
+    {
+        settings.timeout_ms = Some(u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX))
     }
 
     // TLS
@@ -290,10 +300,11 @@ pub fn load_settings(
         settings.cors_exposed_headers = Some(ExposedHeaders(headers));
     }
     if let Some(cors_max_age_secs) = values.get(&UniCase::new("cors_max_age_secs")) {
-        let max_age_secs: u64 = cors_max_age_secs.parse().map_err(|_| {
-            HttpServerError::InvalidParameter("Invalid cors_max_age_secs".to_string())
-        })?;
-        settings.cors_max_age_secs = Some(max_age_secs);
+        let max_age =
+            parse_duration_or(cors_max_age_secs, Duration::from_secs(1)).map_err(|_| {
+                HttpServerError::InvalidParameter("Invalid cors_max_age_secs".to_string())
+            })?;
+        settings.cors_max_age_secs = Some(max_age.as_secs());
     }
     if let Some(disable_keepalive) = values.get(&UniCase::new("disable_keepalive")) {
         settings.disable_keepalive = Some(disable_keepalive.parse().unwrap_or(false));
@@ -316,6 +327,7 @@ pub struct Cors {
     pub allowed_headers: Option<AllowedHeaders>,
     pub allowed_methods: Option<AllowedMethods>,
     pub exposed_headers: Option<ExposedHeaders>,
+    #[serde(default, with = "crate::units::option_secs")]
     pub max_age_secs: Option<u64>,
 }
 
@@ -527,9 +539,10 @@ where
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
     use std::str::FromStr;
 
-    use super::{CorsOrigin, ServiceSettings};
+    use super::{load_settings, CorsOrigin, ServiceSettings};
 
     const GOOD_ORIGINS: &[&str] = &[
         // origins that should be parsed correctly
@@ -592,6 +605,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn settings_human_readable_durations() {
+        let s = ServiceSettings::from_json(r#"{"timeout_ms":"30s","cors_max_age_secs":"5m"}"#)
+            .expect("parse_json");
+        assert_eq!(s.timeout_ms, Some(30_000));
+        assert_eq!(s.cors_max_age_secs, Some(300));
+
+        let values = HashMap::from([
+            ("timeout_ms".to_string(), "1.5s".to_string()),
+            ("cors_max_age_secs".to_string(), "120".to_string()),
+        ]);
+        let s = load_settings(None, &values).expect("load_settings");
+        assert_eq!(s.timeout_ms, Some(1_500));
+        assert_eq!(s.cors_max_age_secs, Some(120));
+    }
+
     #[test]
     fn origins_deserialize() {
         // test CorsOrigin
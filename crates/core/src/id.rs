@@ -0,0 +1,120 @@
+//! Generation of the IDs used to correlate work across the host, control interface clients and
+//! providers.
+//!
+//! Every ID is a [ULID](https://github.com/ulid/spec), whose first bits encode the time it was
+//! generated at. Invocation, command and event IDs are 26 character, lexicographically sortable
+//! ULID strings. Invocation and command IDs are sent along with the invocation or command in
+//! [`INVOCATION_ID_HEADER`] and `Wasmcloud-Command-Id` respectively, so that the sender and the
+//! receiver log under the same ID. Event IDs are the `id` of the CloudEvents published to the
+//! lattice.
+//!
+//! Policy request IDs have always been UUIDs, which policy servers may rely on. They are ULIDs in
+//! the UUID encoding, e.g. `0190c8b4-6d2e-7f3a-9b1c-2d4e6f8a0b1c`, so that they remain valid UUIDs
+//! while still carrying the time they were generated at. [`is_valid`] and [`timestamp`] accept
+//! both encodings.
+
+use std::time::SystemTime;
+
+use ulid::Ulid;
+use uuid::Uuid;
+
+/// Header carrying the ID of a wRPC invocation, generated by the caller with [`invocation_id`]
+pub const INVOCATION_ID_HEADER: &str = "wasmcloud-invocation-id";
+
+/// Generate a new, unique ID
+#[must_use]
+pub fn generate() -> String {
+    Ulid::new().to_string()
+}
+
+/// Generate a new, unique ID in the UUID encoding, for IDs whose consumers expect UUIDs
+#[must_use]
+pub fn generate_uuid() -> String {
+    Uuid::from_u128(Ulid::new().into()).to_string()
+}
+
+/// Generate the ID of an invocation of a component or provider, sent by the caller in
+/// [`INVOCATION_ID_HEADER`]
+#[must_use]
+pub fn invocation_id() -> String {
+    generate()
+}
+
+/// Generate the ID of a control interface command, sent along with the command so that the host
+/// handling it can log under the same ID as the client that sent it
+#[must_use]
+pub fn command_id() -> String {
+    generate()
+}
+
+/// Generate the ID of a lattice event, used as the `id` of its CloudEvent
+#[must_use]
+pub fn event_id() -> String {
+    generate()
+}
+
+/// Generate the ID of a request to a policy server, in the UUID encoding
+#[must_use]
+pub fn policy_request_id() -> String {
+    generate_uuid()
+}
+
+fn parse(id: &str) -> Option<Ulid> {
+    Ulid::from_string(id)
+        .ok()
+        .or_else(|| Uuid::parse_str(id).ok().map(|id| Ulid::from(id.as_u128())))
+}
+
+/// Returns whether `id` is a valid ID as produced by this module
+#[must_use]
+pub fn is_valid(id: &str) -> bool {
+    parse(id).is_some()
+}
+
+/// Returns the time `id` was generated at, or `None` if `id` is not a valid ID
+#[must_use]
+pub fn timestamp(id: &str) -> Option<SystemTime> {
+    parse(id).map(|id| id.datetime())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn ids_are_unique_and_sortable() {
+        let first = command_id();
+        std::thread::sleep(Duration::from_millis(2));
+        let second = invocation_id();
+        assert_eq!(first.len(), 26);
+        assert_eq!(event_id().len(), 26);
+        assert!(is_valid(&first));
+        assert!(first < second);
+        assert_ne!(invocation_id(), invocation_id());
+    }
+
+    #[test]
+    fn timestamps_are_recovered() {
+        let before = SystemTime::now() - Duration::from_millis(1);
+        let id = generate();
+        let generated = timestamp(&id).expect("id should be valid");
+        assert!(generated >= before);
+        assert!(generated <= SystemTime::now());
+        assert_eq!(timestamp("not-an-id"), None);
+    }
+
+    #[test]
+    fn policy_ids_are_uuids() {
+        let before = SystemTime::now() - Duration::from_millis(1);
+        let id = policy_request_id();
+        assert_eq!(id.len(), 36);
+        assert!(Uuid::parse_str(&id).is_ok());
+        assert!(is_valid(&id));
+        let generated = timestamp(&id).expect("id should be valid");
+        assert!(generated >= before);
+        assert!(generated <= SystemTime::now());
+        assert!(!is_valid("not-an-id"));
+    }
+}
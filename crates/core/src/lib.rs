@@ -1,8 +1,14 @@
 #![forbid(clippy::unwrap_used)]
 
+pub mod config_ref;
+pub mod host_config;
+pub mod id;
 pub mod logging;
+pub mod metrics;
+pub mod migration;
 pub mod nats;
 pub mod tls;
+pub mod units;
 
 pub mod host;
 pub use host::*;
@@ -44,6 +50,10 @@ pub mod http_client;
 /// The 1.0 version of the wasmCloud control API, used in topic strings for the control API
 pub const CTL_API_VERSION_1: &str = "v1";
 
+/// The 2.0 version of the wasmCloud control API, which groups host-targeted operations under
+/// `host.{host_id}` in topic strings
+pub const CTL_API_VERSION_2: &str = "v2";
+
 /// Identifier of one or more entities on the lattice used for addressing. May take many forms, such as:
 /// - component public key
 /// - provider public key
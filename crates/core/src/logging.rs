@@ -4,13 +4,19 @@
 //!
 //! [wasi-logging]: <https://github.com/WebAssembly/wasi-logging>
 
+use core::fmt;
+use core::str::FromStr;
+
+use anyhow::{bail, Context as _};
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
+#[derive(Default)]
 pub enum Level {
     Error,
     Warn,
+    #[default]
     Info,
     Debug,
     Trace,
@@ -29,8 +35,208 @@ impl From<tracing::Level> for Level {
     }
 }
 
-impl Default for Level {
-    fn default() -> Self {
-        Self::Info
+impl FromStr for Level {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "error" => Ok(Self::Error),
+            "warn" | "warning" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            "critical" => Ok(Self::Critical),
+            _ => bail!("invalid log level `{s}`"),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+/// A single log filtering directive, setting the level of a target (e.g. `async_nats=warn`) or
+/// the default level of all targets (e.g. `debug`)
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogDirective {
+    /// Target the directive applies to, which is a module path such as `wasmcloud_host::wasmbus`,
+    /// or `None` for all targets
+    pub target: Option<String>,
+    /// Level of the logs emitted for the target
+    pub level: Level,
+}
+
+impl FromStr for LogDirective {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s = s.trim();
+        let Some((target, level)) = s.split_once('=') else {
+            let level = s.parse()?;
+            return Ok(Self {
+                target: None,
+                level,
+            });
+        };
+        let target = target.trim();
+        if target.is_empty()
+            || target.split("::").any(|segment| {
+                segment.is_empty()
+                    || !segment
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            })
+        {
+            bail!("invalid log target `{target}`");
+        }
+        let level = level
+            .parse()
+            .with_context(|| format!("invalid log directive `{s}`"))?;
+        Ok(Self {
+            target: Some(target.to_string()),
+            level,
+        })
+    }
+}
+
+impl fmt::Display for LogDirective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.target {
+            Some(target) => write!(f, "{target}={}", self.level),
+            None => write!(f, "{}", self.level),
+        }
+    }
+}
+
+/// Per-target log filtering directives in the format of `RUST_LOG`, e.g.
+/// `wasmcloud_host=debug,async_nats=warn`.
+///
+/// Directives are applied in order on top of the log level, so later directives for the same
+/// target take precedence.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LogDirectives(Vec<LogDirective>);
+
+impl LogDirectives {
+    /// Returns whether there are no directives
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the directives, in the order they should be applied
+    pub fn iter(&self) -> impl Iterator<Item = &LogDirective> {
+        self.0.iter()
+    }
+}
+
+impl FromStr for LogDirectives {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        s.split(',')
+            .filter(|directive| !directive.trim().is_empty())
+            .map(str::parse)
+            .collect::<anyhow::Result<_>>()
+            .map(Self)
+    }
+}
+
+impl TryFrom<String> for LogDirectives {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> anyhow::Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<LogDirectives> for String {
+    fn from(directives: LogDirectives) -> Self {
+        directives.to_string()
+    }
+}
+
+impl fmt::Display for LogDirectives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, directive) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{directive}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<LogDirective> for LogDirectives {
+    fn from_iter<T: IntoIterator<Item = LogDirective>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_directives() {
+        let directives: LogDirectives = "wasmcloud_host=debug, async_nats=WARN,warn,a::b-c=trace"
+            .parse()
+            .expect("failed to parse directives");
+        assert_eq!(
+            directives.iter().collect::<Vec<_>>(),
+            [
+                &LogDirective {
+                    target: Some("wasmcloud_host".into()),
+                    level: Level::Debug,
+                },
+                &LogDirective {
+                    target: Some("async_nats".into()),
+                    level: Level::Warn,
+                },
+                &LogDirective {
+                    target: None,
+                    level: Level::Warn,
+                },
+                &LogDirective {
+                    target: Some("a::b-c".into()),
+                    level: Level::Trace,
+                },
+            ]
+        );
+        assert_eq!(
+            directives.to_string(),
+            "wasmcloud_host=debug,async_nats=warn,warn,a::b-c=trace"
+        );
+        assert!("".parse::<LogDirectives>().expect("empty").is_empty());
+
+        for invalid in [
+            "loud",
+            "=debug",
+            "a:b=debug",
+            "a::=debug",
+            "a b=info",
+            "a=loud",
+        ] {
+            assert!(
+                invalid.parse::<LogDirectives>().is_err(),
+                "`{invalid}` should be rejected"
+            );
+        }
+
+        let json = serde_json::to_string(&directives).expect("failed to serialize");
+        assert_eq!(
+            serde_json::from_str::<LogDirectives>(&json).expect("failed to deserialize"),
+            directives
+        );
     }
 }
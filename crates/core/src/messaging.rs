@@ -19,6 +19,8 @@ pub struct ConsumerConfig {
     pub stream: Box<str>,
     pub consumer: Box<str>,
     pub max_messages: Option<usize>,
+    /// Also accepts human-readable sizes like `"64MiB"`
+    #[serde(default, with = "crate::units::option_bytes")]
     pub max_bytes: Option<usize>,
 }
 
@@ -54,8 +56,8 @@ pub struct ConnectionConfig {
     #[serde(default)]
     pub tls_ca_file: Option<Box<str>>,
 
-    /// Ping interval in seconds
-    #[serde(default)]
+    /// Ping interval in seconds. Also accepts human-readable durations like `"2m"`
+    #[serde(default, with = "crate::units::option_secs")]
     pub ping_interval_sec: Option<u16>,
 
     /// Inbox prefix to use (by default
@@ -0,0 +1,172 @@
+//! Standard names, units and attributes of the metrics recorded for wRPC invocations
+//!
+//! Hosts record these metrics for the invocations of components and the provider SDK for the
+//! invocations of capability providers, so that dashboards and alerts built on them work for any
+//! workload, regardless of which crate recorded the metrics.
+
+/// Counter of invocations, incremented once per invocation
+pub const INVOCATIONS: &str = "wasmcloud.rpc.invocations";
+
+/// Counter of failed invocations
+pub const INVOCATION_ERRORS: &str = "wasmcloud.rpc.invocation.errors";
+
+/// Histogram of the duration of served invocations, in [`DURATION_UNIT`]
+pub const INVOCATION_DURATION: &str = "wasmcloud.rpc.invocation.duration";
+
+/// Histogram of the size of the encoded parameters sent with invocations, in [`SIZE_UNIT`]
+pub const INVOCATION_PARAMS_SIZE: &str = "wasmcloud.rpc.invocation.params.size";
+
+/// Unit of durations, following the OpenTelemetry semantic conventions
+pub const DURATION_UNIT: &str = "s";
+
+/// Unit of sizes, following the OpenTelemetry semantic conventions
+pub const SIZE_UNIT: &str = "By";
+
+/// Attribute holding the lattice of the invocation
+pub const LATTICE_ATTRIBUTE: &str = "wasmcloud.lattice";
+
+/// Attribute holding the ID of the host running the workload
+pub const HOST_ID_ATTRIBUTE: &str = "wasmcloud.host.id";
+
+/// Attribute holding the [`WorkloadKind`] of the workload recording the metric
+pub const WORKLOAD_KIND_ATTRIBUTE: &str = "wasmcloud.workload.kind";
+
+/// Attribute holding the ID of the component or provider recording the metric
+pub const WORKLOAD_ID_ATTRIBUTE: &str = "wasmcloud.workload.id";
+
+/// Attribute holding the [`InvocationDirection`] of the invocation
+pub const DIRECTION_ATTRIBUTE: &str = "wasmcloud.rpc.direction";
+
+/// Attribute holding the invoked operation as `<instance>/<function>`, e.g.
+/// `wasi:http/incoming-handler/handle`
+pub const OPERATION_ATTRIBUTE: &str = "wasmcloud.rpc.operation";
+
+/// Attribute holding the ID of the other side of the invocation, i.e. the source of served
+/// invocations and the target of sent ones, if known
+pub const PEER_ID_ATTRIBUTE: &str = "wasmcloud.rpc.peer.id";
+
+/// Kind of workload recording invocation metrics
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WorkloadKind {
+    /// A WebAssembly component
+    Component,
+    /// A capability provider
+    Provider,
+}
+
+impl WorkloadKind {
+    /// Returns the value of the [`WORKLOAD_KIND_ATTRIBUTE`]
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Component => "component",
+            Self::Provider => "provider",
+        }
+    }
+}
+
+/// Whether an invocation was served or sent by the workload recording the metric
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvocationDirection {
+    /// The invocation was served by the workload
+    Incoming,
+    /// The invocation was sent by the workload
+    Outgoing,
+}
+
+impl InvocationDirection {
+    /// Returns the value of the [`DIRECTION_ATTRIBUTE`]
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Incoming => "incoming",
+            Self::Outgoing => "outgoing",
+        }
+    }
+}
+
+/// Attributes of the metrics recorded for an invocation
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvocationMetricAttributes<'a> {
+    /// The lattice of the invocation
+    pub lattice: &'a str,
+    /// The ID of the host running the workload
+    pub host_id: &'a str,
+    /// The kind of workload recording the metric
+    pub workload_kind: WorkloadKind,
+    /// The ID of the component or provider recording the metric
+    pub workload_id: &'a str,
+    /// Whether the invocation was served or sent
+    pub direction: InvocationDirection,
+    /// The invoked instance, e.g. `wasi:http/incoming-handler`
+    pub instance: &'a str,
+    /// The invoked function, e.g. `handle`
+    pub func: &'a str,
+    /// The ID of the other side of the invocation, if known
+    pub peer_id: Option<&'a str>,
+}
+
+impl InvocationMetricAttributes<'_> {
+    /// Returns the attributes as key-value pairs, omitting the peer ID if unknown
+    #[must_use]
+    pub fn to_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = vec![
+            (LATTICE_ATTRIBUTE, self.lattice.to_string()),
+            (HOST_ID_ATTRIBUTE, self.host_id.to_string()),
+            (
+                WORKLOAD_KIND_ATTRIBUTE,
+                self.workload_kind.as_str().to_string(),
+            ),
+            (WORKLOAD_ID_ATTRIBUTE, self.workload_id.to_string()),
+            (DIRECTION_ATTRIBUTE, self.direction.as_str().to_string()),
+            (
+                OPERATION_ATTRIBUTE,
+                format!("{}/{}", self.instance, self.func),
+            ),
+        ];
+        if let Some(peer_id) = self.peer_id {
+            pairs.push((PEER_ID_ATTRIBUTE, peer_id.to_string()));
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_are_standard_pairs() {
+        let mut attributes = InvocationMetricAttributes {
+            lattice: "default",
+            host_id: "NHOST",
+            workload_kind: WorkloadKind::Provider,
+            workload_id: "http-server",
+            direction: InvocationDirection::Outgoing,
+            instance: "wasi:http/incoming-handler",
+            func: "handle",
+            peer_id: Some("echo"),
+        };
+        assert_eq!(
+            attributes.to_pairs(),
+            vec![
+                (LATTICE_ATTRIBUTE, "default".to_string()),
+                (HOST_ID_ATTRIBUTE, "NHOST".to_string()),
+                (WORKLOAD_KIND_ATTRIBUTE, "provider".to_string()),
+                (WORKLOAD_ID_ATTRIBUTE, "http-server".to_string()),
+                (DIRECTION_ATTRIBUTE, "outgoing".to_string()),
+                (
+                    OPERATION_ATTRIBUTE,
+                    "wasi:http/incoming-handler/handle".to_string()
+                ),
+                (PEER_ID_ATTRIBUTE, "echo".to_string()),
+            ]
+        );
+
+        attributes.peer_id = None;
+        assert!(attributes
+            .to_pairs()
+            .iter()
+            .all(|(key, _)| *key != PEER_ID_ATTRIBUTE));
+    }
+}
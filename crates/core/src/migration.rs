@@ -0,0 +1,560 @@
+//! Versioned schema migrations for lattice [NATS JetStream KV][kv] buckets (e.g. `LATTICEDATA`
+//! and `CONFIGDATA`).
+//!
+//! A [`Migrator`] holds an ordered list of [`MigrationStep`]s, each of which rewrites the
+//! entries of a bucket from one layout version to the next. The schema version of a bucket is
+//! recorded under [`SCHEMA_VERSION_KEY`], and hosts coordinate through [`MIGRATION_LOCK_KEY`] so
+//! that only a single host in the lattice applies pending steps at a time.
+//!
+//! [kv]: https://docs.nats.io/nats-concepts/jetstream/key-value-store
+
+use core::time::Duration;
+
+use std::collections::BTreeMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, ensure, Context as _};
+use async_nats::jetstream::kv::{CreateErrorKind, Operation, Store};
+use bytes::Bytes;
+use futures::TryStreamExt as _;
+use tracing::{debug, info, instrument, warn};
+
+/// Prefix of keys reserved for migration bookkeeping, which are never passed to a [`MigrationStep`]
+pub const MIGRATION_KEY_PREFIX: &str = "MIGRATION_";
+
+/// Key under which the current schema version of a bucket is stored
+pub const SCHEMA_VERSION_KEY: &str = "MIGRATION_version";
+
+/// Key used as a lock by the host applying migrations to a bucket
+pub const MIGRATION_LOCK_KEY: &str = "MIGRATION_lock";
+
+/// Default duration after which a migration lock is considered abandoned
+pub const DEFAULT_MIGRATION_LOCK_TTL: Duration = Duration::from_secs(60);
+
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Fraction of the lock TTL after which a held migration lock is renewed
+const LOCK_RENEW_DIVISOR: u32 = 3;
+
+/// A change to a single key of a bucket
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KeyChange {
+    /// Set the key to the value
+    Put { key: String, value: Bytes },
+    /// Delete the key
+    Delete { key: String },
+}
+
+impl KeyChange {
+    /// Get the key this change applies to
+    #[must_use]
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Put { key, .. } | Self::Delete { key } => key,
+        }
+    }
+}
+
+/// A single versioned migration of a bucket layout
+pub trait MigrationStep: Send + Sync {
+    /// The schema version of the bucket after this step has been applied. Versions start at 1,
+    /// a bucket without a recorded version is considered to be at version 0
+    fn version(&self) -> u64;
+
+    /// Human-readable description of this step, used in reports
+    fn description(&self) -> &str;
+
+    /// Compute the changes required to migrate a single entry. Changes may touch keys other
+    /// than `key`, e.g. to rename an entry. Returning no changes leaves the entry untouched.
+    fn migrate_entry(&self, key: &str, value: &[u8]) -> anyhow::Result<Vec<KeyChange>>;
+}
+
+/// Outcome of a single [`MigrationStep`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StepReport {
+    /// Schema version reached by the step
+    pub version: u64,
+    /// Description of the step
+    pub description: String,
+    /// Changes made (or, for a dry run, planned) by the step
+    pub changes: Vec<KeyChange>,
+}
+
+/// Outcome of running a [`Migrator`] against a bucket
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MigrationReport {
+    /// Name of the bucket
+    pub bucket: String,
+    /// Schema version of the bucket before migrating
+    pub from_version: u64,
+    /// Schema version of the bucket after migrating
+    pub to_version: u64,
+    /// Whether the changes were only planned and not written to the bucket
+    pub dry_run: bool,
+    /// Reports for each step, in the order they were applied
+    pub steps: Vec<StepReport>,
+}
+
+impl MigrationReport {
+    /// Returns true if no steps were pending
+    #[must_use]
+    pub fn is_noop(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Total number of key changes across all steps
+    #[must_use]
+    pub fn change_count(&self) -> usize {
+        self.steps.iter().map(|step| step.changes.len()).sum()
+    }
+}
+
+/// Applies ordered [`MigrationStep`]s to a JetStream KV bucket
+pub struct Migrator {
+    holder: String,
+    lock_ttl: Duration,
+    steps: Vec<Box<dyn MigrationStep>>,
+}
+
+impl Migrator {
+    /// Create a new [`Migrator`] without any steps. `holder` identifies the caller, normally a
+    /// host ID, in the migration lock
+    #[must_use]
+    pub fn new(holder: impl Into<String>) -> Self {
+        Self {
+            holder: holder.into(),
+            lock_ttl: DEFAULT_MIGRATION_LOCK_TTL,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Add a step to this migrator. Steps may be added in any order, they are applied in order
+    /// of their version
+    #[must_use]
+    pub fn with_step(mut self, step: impl MigrationStep + 'static) -> Self {
+        self.steps.push(Box::new(step));
+        self.steps.sort_by_key(|step| step.version());
+        self
+    }
+
+    /// Set the duration after which a lock held by another (presumably crashed) host is taken over
+    #[must_use]
+    pub fn with_lock_ttl(self, lock_ttl: Duration) -> Self {
+        Self { lock_ttl, ..self }
+    }
+
+    /// The schema version of a bucket after all steps have been applied
+    #[must_use]
+    pub fn latest_version(&self) -> u64 {
+        self.steps.last().map_or(0, |step| step.version())
+    }
+
+    /// Steps that need to be applied to a bucket at `version`
+    pub fn pending(&self, version: u64) -> impl Iterator<Item = &dyn MigrationStep> {
+        self.steps
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(move |step| step.version() > version)
+    }
+
+    /// Ensure that no two steps share a version
+    fn validate(&self) -> anyhow::Result<()> {
+        for pair in self.steps.windows(2) {
+            ensure!(
+                pair[0].version() != pair[1].version(),
+                "duplicate migration step version {}",
+                pair[0].version()
+            );
+        }
+        Ok(())
+    }
+
+    /// Compute the step reports for migrating `entries` from `version`, applying the changes of
+    /// each step to `entries` so that subsequent steps observe them
+    fn plan_entries(
+        &self,
+        entries: &mut BTreeMap<String, Bytes>,
+        version: u64,
+    ) -> anyhow::Result<Vec<StepReport>> {
+        self.validate()?;
+        self.pending(version)
+            .map(|step| plan_step(step, entries))
+            .collect()
+    }
+
+    /// Compute the pending changes to `store` without writing them
+    #[instrument(level = "debug", skip_all, fields(bucket = %store.name))]
+    pub async fn dry_run(&self, store: &Store) -> anyhow::Result<MigrationReport> {
+        let from_version = schema_version(store).await?;
+        let mut entries = if self.pending(from_version).next().is_some() {
+            load_entries(store).await?
+        } else {
+            BTreeMap::default()
+        };
+        let steps = self.plan_entries(&mut entries, from_version)?;
+        Ok(MigrationReport {
+            bucket: store.name.clone(),
+            from_version,
+            to_version: from_version.max(self.latest_version()),
+            dry_run: true,
+            steps,
+        })
+    }
+
+    /// Apply all pending steps to `store`.
+    ///
+    /// If another host is currently migrating the bucket, this waits for it to finish and
+    /// re-evaluates the pending steps afterwards. The schema version is recorded after every step,
+    /// so an interrupted migration resumes at the first incomplete step. The lock is renewed while
+    /// migrating, and the migration is aborted if another host took over the lock regardless.
+    #[instrument(level = "debug", skip_all, fields(bucket = %store.name, holder = %self.holder))]
+    pub async fn migrate(&self, store: &Store) -> anyhow::Result<MigrationReport> {
+        let mut from_version = schema_version(store).await?;
+        if self.pending(from_version).next().is_none() {
+            return Ok(MigrationReport {
+                bucket: store.name.clone(),
+                from_version,
+                to_version: from_version,
+                ..Default::default()
+            });
+        }
+
+        let mut lock = self.acquire_lock(store).await?;
+        let res = async {
+            // Another host may have completed the migration while we waited for the lock
+            from_version = schema_version(store).await?;
+            self.validate()?;
+            let mut entries = load_entries(store).await?;
+            let mut steps = Vec::new();
+            for step in self.pending(from_version) {
+                let report = plan_step(step, &mut entries)?;
+                for change in &report.changes {
+                    self.renew_lock(store, &mut lock).await?;
+                    match change {
+                        KeyChange::Put { key, value } => store
+                            .put(key, value.clone())
+                            .await
+                            .with_context(|| format!("failed to put key `{key}`"))?,
+                        KeyChange::Delete { key } => store
+                            .delete(key)
+                            .await
+                            .map(|()| 0)
+                            .with_context(|| format!("failed to delete key `{key}`"))?,
+                    };
+                }
+                self.renew_lock(store, &mut lock).await?;
+                store
+                    .put(SCHEMA_VERSION_KEY, step.version().to_string().into())
+                    .await
+                    .context("failed to record schema version")?;
+                info!(
+                    version = step.version(),
+                    description = step.description(),
+                    changes = report.changes.len(),
+                    "applied bucket migration step"
+                );
+                steps.push(report);
+            }
+            anyhow::Ok(steps)
+        }
+        .await;
+        self.release_lock(store, lock.revision).await;
+
+        let steps = res?;
+        Ok(MigrationReport {
+            bucket: store.name.clone(),
+            from_version,
+            to_version: steps.last().map_or(from_version, |step| step.version),
+            dry_run: false,
+            steps,
+        })
+    }
+
+    /// Value of the migration lock entry, identifying the holder and when the lock was last renewed
+    fn lock_value(&self) -> Bytes {
+        Bytes::from(format!("{};{}", self.holder, unix_millis()))
+    }
+
+    /// Acquire the migration lock of `store`
+    async fn acquire_lock(&self, store: &Store) -> anyhow::Result<HeldLock> {
+        loop {
+            let value = self.lock_value();
+            match store.create(MIGRATION_LOCK_KEY, value.clone()).await {
+                Ok(revision) => return Ok(HeldLock::new(revision)),
+                Err(err) if err.kind() == CreateErrorKind::AlreadyExists => {}
+                Err(err) => return Err(anyhow::anyhow!(err).context("failed to acquire lock")),
+            }
+
+            let Some(entry) = store
+                .entry(MIGRATION_LOCK_KEY)
+                .await
+                .context("failed to read migration lock")?
+            else {
+                continue;
+            };
+            if matches!(entry.operation, Operation::Delete | Operation::Purge) {
+                continue;
+            }
+            let acquired_at = std::str::from_utf8(&entry.value)
+                .ok()
+                .and_then(|v| v.rsplit_once(';'))
+                .and_then(|(_, at)| at.parse::<u128>().ok());
+            let expired = acquired_at
+                .is_none_or(|at| unix_millis().saturating_sub(at) > self.lock_ttl.as_millis());
+            if expired {
+                warn!(
+                    lock = %String::from_utf8_lossy(&entry.value),
+                    "taking over expired migration lock"
+                );
+                match store
+                    .update(MIGRATION_LOCK_KEY, value, entry.revision)
+                    .await
+                {
+                    Ok(revision) => return Ok(HeldLock::new(revision)),
+                    Err(err) => debug!(?err, "lost race to take over migration lock"),
+                }
+            } else {
+                debug!(
+                    lock = %String::from_utf8_lossy(&entry.value),
+                    "waiting for migration lock"
+                );
+            }
+            tokio::time::sleep(LOCK_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Renew the migration lock once a fraction of its TTL has passed, so that other hosts do not
+    /// take over the lock of a long-running migration. Fails if the lock was taken over already,
+    /// in which case the migration must not continue
+    async fn renew_lock(&self, store: &Store, lock: &mut HeldLock) -> anyhow::Result<()> {
+        if !lock.renewal_due(self.lock_ttl) {
+            return Ok(());
+        }
+        let revision = store
+            .update(MIGRATION_LOCK_KEY, self.lock_value(), lock.revision)
+            .await
+            .context("lost migration lock, another host may have taken it over")?;
+        *lock = HeldLock::new(revision);
+        Ok(())
+    }
+
+    async fn release_lock(&self, store: &Store, revision: u64) {
+        if let Err(err) = store
+            .delete_expect_revision(MIGRATION_LOCK_KEY, Some(revision))
+            .await
+        {
+            warn!(?err, "failed to release migration lock");
+        }
+    }
+}
+
+/// A migration lock held by this host
+#[derive(Debug)]
+struct HeldLock {
+    /// Revision of the lock entry written by this host
+    revision: u64,
+    renewed_at: Instant,
+}
+
+impl HeldLock {
+    fn new(revision: u64) -> Self {
+        Self {
+            revision,
+            renewed_at: Instant::now(),
+        }
+    }
+
+    /// Returns true if the lock should be renewed to not be considered abandoned
+    fn renewal_due(&self, lock_ttl: Duration) -> bool {
+        self.renewed_at.elapsed() >= lock_ttl / LOCK_RENEW_DIVISOR
+    }
+}
+
+/// Compute the changes of a single step and apply them to `entries`
+fn plan_step(
+    step: &dyn MigrationStep,
+    entries: &mut BTreeMap<String, Bytes>,
+) -> anyhow::Result<StepReport> {
+    let mut changes = Vec::new();
+    for (key, value) in entries.iter() {
+        changes.extend(
+            step.migrate_entry(key, value).with_context(|| {
+                format!("migration step {} failed on key `{key}`", step.version())
+            })?,
+        );
+    }
+    for change in &changes {
+        ensure!(
+            !change.key().starts_with(MIGRATION_KEY_PREFIX),
+            "migration step {} attempted to modify reserved key `{}`",
+            step.version(),
+            change.key()
+        );
+        match change {
+            KeyChange::Put { key, value } => {
+                entries.insert(key.clone(), value.clone());
+            }
+            KeyChange::Delete { key } => {
+                entries.remove(key);
+            }
+        }
+    }
+    Ok(StepReport {
+        version: step.version(),
+        description: step.description().to_string(),
+        changes,
+    })
+}
+
+/// Read the schema version recorded in `store`, returning 0 if none is recorded
+pub async fn schema_version(store: &Store) -> anyhow::Result<u64> {
+    let Some(value) = store
+        .get(SCHEMA_VERSION_KEY)
+        .await
+        .context("failed to read schema version")?
+    else {
+        return Ok(0);
+    };
+    let Ok(version) = std::str::from_utf8(&value)
+        .map_err(anyhow::Error::from)
+        .and_then(|v| v.trim().parse().map_err(anyhow::Error::from))
+    else {
+        bail!(
+            "invalid schema version `{}` in bucket `{}`",
+            String::from_utf8_lossy(&value),
+            store.name
+        );
+    };
+    Ok(version)
+}
+
+async fn load_entries(store: &Store) -> anyhow::Result<BTreeMap<String, Bytes>> {
+    let keys: Vec<String> = store
+        .keys()
+        .await
+        .context("failed to list keys")?
+        .try_collect()
+        .await
+        .context("failed to list keys")?;
+    let mut entries = BTreeMap::new();
+    for key in keys {
+        if key.starts_with(MIGRATION_KEY_PREFIX) {
+            continue;
+        }
+        if let Some(value) = store
+            .get(&key)
+            .await
+            .with_context(|| format!("failed to get key `{key}`"))?
+        {
+            entries.insert(key, value);
+        }
+    }
+    Ok(entries)
+}
+
+fn unix_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use anyhow::Result;
+    use bytes::Bytes;
+
+    use core::time::Duration;
+
+    use std::time::Instant;
+
+    use super::{HeldLock, KeyChange, MigrationStep, Migrator};
+
+    struct Rename(u64, &'static str, &'static str);
+
+    impl MigrationStep for Rename {
+        fn version(&self) -> u64 {
+            self.0
+        }
+
+        fn description(&self) -> &str {
+            "rename key prefix"
+        }
+
+        fn migrate_entry(&self, key: &str, value: &[u8]) -> Result<Vec<KeyChange>> {
+            let Some(id) = key.strip_prefix(self.1) else {
+                return Ok(Vec::new());
+            };
+            Ok(vec![
+                KeyChange::Put {
+                    key: format!("{}{id}", self.2),
+                    value: Bytes::copy_from_slice(value),
+                },
+                KeyChange::Delete {
+                    key: key.to_string(),
+                },
+            ])
+        }
+    }
+
+    #[test]
+    fn plans_pending_steps_in_order() -> Result<()> {
+        let migrator = Migrator::new("test")
+            .with_step(Rename(2, "B_", "C_"))
+            .with_step(Rename(1, "A_", "B_"));
+        assert_eq!(migrator.latest_version(), 2);
+
+        let mut entries = BTreeMap::from([
+            ("A_foo".to_string(), Bytes::from("foo")),
+            ("OTHER_bar".to_string(), Bytes::from("bar")),
+        ]);
+        let steps = migrator.plan_entries(&mut entries, 0)?;
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].version, 1);
+        assert_eq!(steps[1].changes[0].key(), "C_foo");
+        assert_eq!(
+            entries,
+            BTreeMap::from([
+                ("C_foo".to_string(), Bytes::from("foo")),
+                ("OTHER_bar".to_string(), Bytes::from("bar")),
+            ])
+        );
+
+        // Only the second step is pending for a bucket at version 1
+        let mut entries = BTreeMap::from([("B_baz".to_string(), Bytes::from("baz"))]);
+        let steps = migrator.plan_entries(&mut entries, 1)?;
+        assert_eq!(steps.len(), 1);
+        assert!(entries.contains_key("C_baz"));
+
+        assert!(migrator.plan_entries(&mut entries, 2)?.is_empty());
+
+        let duplicate = Migrator::new("test")
+            .with_step(Rename(1, "A_", "B_"))
+            .with_step(Rename(1, "B_", "C_"));
+        assert!(duplicate.plan_entries(&mut entries, 0).is_err());
+
+        let reserved = Migrator::new("test").with_step(Rename(1, "A_", "MIGRATION_"));
+        let mut entries = BTreeMap::from([("A_foo".to_string(), Bytes::from("foo"))]);
+        assert!(reserved.plan_entries(&mut entries, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn locks_are_renewed_well_before_they_expire() {
+        let ttl = Duration::from_secs(60);
+        let lock = HeldLock::new(1);
+        assert!(!lock.renewal_due(ttl));
+
+        let Some(renewed_at) = Instant::now().checked_sub(Duration::from_secs(25)) else {
+            return;
+        };
+        let lock = HeldLock {
+            revision: 1,
+            renewed_at,
+        };
+        assert!(lock.renewal_due(ttl));
+        assert!(!lock.renewal_due(Duration::from_secs(90)));
+    }
+}
@@ -266,6 +266,31 @@ impl OciFetcher {
             .with_context(|| format!("failed to read `{}`", path.display()))
     }
 
+    /// Fetch a component or provider from OCI into the artifact cache without reading it, so that
+    /// later fetches of the same reference are cache hits. Returns whether the artifact was
+    /// already cached and up to date
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if fetching or caching the artifact fails
+    pub async fn prefetch(&self, oci_ref: impl AsRef<str>) -> anyhow::Result<CacheResult> {
+        let (_, cache) = self
+            .fetch_path(
+                oci_cache_dir().await?,
+                oci_ref,
+                vec![
+                    WASM_MEDIA_TYPE,
+                    OCI_MEDIA_TYPE,
+                    WASM_LAYER_MEDIA_TYPE,
+                    PROVIDER_ARCHIVE_MEDIA_TYPE,
+                ],
+                OciArtifactCacheUpdate::Update,
+            )
+            .await
+            .context("failed to fetch OCI path")?;
+        Ok(cache)
+    }
+
     /// Used to set additional CA paths that will be used as part of fetching components and providers
     pub fn with_additional_ca_paths(mut self, paths: &[impl AsRef<Path>]) -> Self {
         self.additional_ca_paths = paths.iter().map(AsRef::as_ref).map(PathBuf::from).collect();
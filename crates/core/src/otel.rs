@@ -2,13 +2,18 @@
 //!
 //! [otel]: https://opentelemetry.io
 
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
 
-use anyhow::bail;
+use anyhow::{bail, Context as _};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use crate::{logging::Level, wit::WitMap};
+use crate::{logging::Level, secrets::SecretString, wit::WitMap};
 
 /// Configuration values for OpenTelemetry
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -40,6 +45,67 @@ pub struct OtelConfig {
     /// Determines whether http or grpc will be used for exporting the telemetry.
     #[serde(default)]
     pub protocol: OtelProtocol,
+    /// Overrides the protocol used for exporting traces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub traces_protocol: Option<OtelProtocol>,
+    /// Overrides the protocol used for exporting metrics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics_protocol: Option<OtelProtocol>,
+    /// Overrides the protocol used for exporting logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs_protocol: Option<OtelProtocol>,
+    /// Determines where the enabled telemetry is exported to. Defaults to an OTLP collector, but
+    /// telemetry can also be written to stdout or a file to inspect it without running one.
+    #[serde(default)]
+    pub exporter: OtelExporter,
+    /// Headers to include in the requests exporting all signals, e.g. API keys required by hosted
+    /// observability backends.
+    ///
+    /// Values prefixed with `env:` are read from the named environment variable and values
+    /// prefixed with `file:` are read from the file at the given path, so that secrets do not have
+    /// to be part of the configuration itself.
+    ///
+    /// Values are redacted from debug output, but serialized as they are, as the configuration is
+    /// forwarded to providers.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        with = "crate::secrets::expose_map"
+    )]
+    pub headers: HashMap<String, SecretString>,
+    /// Headers to include in the requests exporting traces, overriding the matching `headers`.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        with = "crate::secrets::expose_map"
+    )]
+    pub traces_headers: HashMap<String, SecretString>,
+    /// Headers to include in the requests exporting metrics, overriding the matching `headers`.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        with = "crate::secrets::expose_map"
+    )]
+    pub metrics_headers: HashMap<String, SecretString>,
+    /// Headers to include in the requests exporting logs, overriding the matching `headers`.
+    #[serde(
+        default,
+        skip_serializing_if = "HashMap::is_empty",
+        with = "crate::secrets::expose_map"
+    )]
+    pub logs_headers: HashMap<String, SecretString>,
+    /// Overrides the `service.name` resource attribute attached to all exported telemetry, which
+    /// otherwise defaults to the name of the exporting service, e.g. `wasmcloud-host`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+    /// The `service.namespace` resource attribute attached to all exported telemetry, e.g. to
+    /// distinguish the hosts of different clusters.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_namespace: Option<String>,
+    /// Additional resource attributes attached to all exported telemetry. These take precedence
+    /// over attributes set via `OTEL_RESOURCE_ATTRIBUTES`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub resource_attributes: HashMap<String, String>,
     /// Additional CAs to include in the OpenTelemetry client configuration
     #[serde(default)]
     pub additional_ca_paths: Vec<PathBuf>,
@@ -68,9 +134,322 @@ pub struct OtelConfig {
     /// variables.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub concurrent_exports: Option<usize>,
+    /// Determines what happens to ended spans while the queue configured by
+    /// `max_batch_queue_size` is full. Defaults to dropping them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_full_policy: Option<OtelQueueFullPolicy>,
+    /// The maximum number of times an export of telemetry to a collector is retried after it
+    /// failed, e.g. because the collector is unavailable. Defaults to 0, i.e. failed exports are
+    /// dropped right away.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_max_retries: Option<u32>,
+    /// The delay in milliseconds before the first retry of a failed export, which doubles with
+    /// every further retry. Defaults to 1000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_initial_backoff_ms: Option<u64>,
+    /// The maximum delay in milliseconds between retries of a failed export. Defaults to 30000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_max_backoff_ms: Option<u64>,
+    /// The maximum number of events recorded per exported span. Events beyond the limit are
+    /// dropped and counted as such, which bounds the size of spans emitted by runaway
+    /// instrumentation. If not set, the default of the underlying SDK is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_events_per_span: Option<u32>,
+    /// The maximum length in bytes of string attribute values of exported spans and their events
+    /// and links. Longer values are truncated. If not set, values are exported in full.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_attribute_length: Option<usize>,
+    /// Keys of span, event and link attributes whose values are redacted before export, e.g.
+    /// `authorization`. Keys are matched case-insensitively, either in full or as the last
+    /// `.`-separated segment of an attribute key, so that `authorization` also redacts
+    /// `http.request.header.authorization`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redacted_attributes: Vec<String>,
+    /// Determine whether the configuration is forwarded to the capability providers started by
+    /// the host, so that they export telemetry as well. Defaults to true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_to_providers: Option<bool>,
+    /// Overrides of the configuration forwarded to individual capability providers, keyed by
+    /// provider ID, e.g. to only export the traces of a single provider.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub provider_overrides: HashMap<String, ProviderOtelOverrides>,
+}
+
+/// Overrides of the telemetry configuration forwarded to a capability provider, see
+/// [`OtelConfig::for_provider`]
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ProviderOtelOverrides {
+    /// Overrides whether the configuration is forwarded to the provider.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward: Option<bool>,
+    /// Overrides whether the provider exports traces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_traces: Option<bool>,
+    /// Overrides whether the provider exports metrics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_metrics: Option<bool>,
+    /// Overrides whether the provider exports logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_logs: Option<bool>,
+    /// Overrides the level of tracing of the provider.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<Level>,
 }
 
 impl OtelConfig {
+    /// Builds the configuration from the OpenTelemetry section of a config file and the standard
+    /// `OTEL_*` environment variables of the process.
+    ///
+    /// Settings are resolved in the following order, where later sources take precedence:
+    /// 1. the defaults of [`OtelConfig`]
+    /// 2. the config file at `path`, if given (see [`OtelConfig::from_file`])
+    /// 3. the environment variables supported by [`OtelConfig::apply_env`]
+    /// 4. programmatic overrides, e.g. command line flags, applied with [`OtelConfig::merge`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config file cannot be read or parsed, or if an environment
+    /// variable has an invalid value
+    pub fn from_env_and_file(path: Option<impl AsRef<Path>>) -> anyhow::Result<Self> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        config.apply_env(std::env::vars())?;
+        Ok(config)
+    }
+
+    /// Reads the configuration from a JSON file, which either contains the configuration itself or
+    /// a config file with the configuration in its `otel` section. Settings missing from the file
+    /// keep their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not contain a valid configuration
+    pub fn from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read(path)
+            .with_context(|| format!("failed to read otel config `{}`", path.display()))?;
+        let mut value: serde_json::Value = serde_json::from_slice(&contents)
+            .with_context(|| format!("failed to parse otel config `{}`", path.display()))?;
+        if let Some(section) = value.get_mut("otel") {
+            value = section.take();
+        }
+        serde_json::from_value(value)
+            .with_context(|| format!("invalid otel config `{}`", path.display()))
+    }
+
+    /// Applies the standard OpenTelemetry environment variables among `vars`, ignoring all other
+    /// variables. Names are matched case-insensitively, so that the same variables can be passed
+    /// as e.g. provider configuration.
+    ///
+    /// Supported are the `OTEL_EXPORTER_OTLP_ENDPOINT`, `OTEL_EXPORTER_OTLP_PROTOCOL` and
+    /// `OTEL_EXPORTER_OTLP_HEADERS` variables and their signal-specific variants such as
+    /// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`, as well as `OTEL_SERVICE_NAME`,
+    /// `OTEL_TRACES_SAMPLER`, `OTEL_TRACES_SAMPLER_ARG`, `OTEL_BSP_MAX_QUEUE_SIZE` and
+    /// `OTEL_BSP_MAX_CONCURRENT_EXPORTS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error on the first variable with an invalid value. Variables preceding it are
+    /// applied
+    pub fn apply_env<K, V>(&mut self, vars: impl IntoIterator<Item = (K, V)>) -> anyhow::Result<()>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (name, value) in vars {
+            let (name, value) = (name.as_ref(), value.as_ref());
+            let name = name.to_ascii_uppercase();
+            let parse_usize = || {
+                value
+                    .parse::<usize>()
+                    .with_context(|| format!("invalid value `{value}` of `{name}`"))
+            };
+            match name.as_str() {
+                "OTEL_EXPORTER_OTLP_ENDPOINT" => self.observability_endpoint = Some(value.into()),
+                "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT" => self.traces_endpoint = Some(value.into()),
+                "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT" => self.metrics_endpoint = Some(value.into()),
+                "OTEL_EXPORTER_OTLP_LOGS_ENDPOINT" => self.logs_endpoint = Some(value.into()),
+                "OTEL_EXPORTER_OTLP_PROTOCOL" => self.protocol = parse_env_protocol(&name, value)?,
+                "OTEL_EXPORTER_OTLP_TRACES_PROTOCOL" => {
+                    self.traces_protocol = Some(parse_env_protocol(&name, value)?);
+                }
+                "OTEL_EXPORTER_OTLP_METRICS_PROTOCOL" => {
+                    self.metrics_protocol = Some(parse_env_protocol(&name, value)?);
+                }
+                "OTEL_EXPORTER_OTLP_LOGS_PROTOCOL" => {
+                    self.logs_protocol = Some(parse_env_protocol(&name, value)?);
+                }
+                "OTEL_EXPORTER_OTLP_HEADERS" => {
+                    self.headers.extend(parse_env_headers(&name, value)?);
+                }
+                "OTEL_EXPORTER_OTLP_TRACES_HEADERS" => {
+                    self.traces_headers.extend(parse_env_headers(&name, value)?);
+                }
+                "OTEL_EXPORTER_OTLP_METRICS_HEADERS" => {
+                    self.metrics_headers
+                        .extend(parse_env_headers(&name, value)?);
+                }
+                "OTEL_EXPORTER_OTLP_LOGS_HEADERS" => {
+                    self.logs_headers.extend(parse_env_headers(&name, value)?);
+                }
+                "OTEL_SERVICE_NAME" => self.service_name = Some(value.into()),
+                "OTEL_TRACES_SAMPLER" => self.traces_sampler = Some(value.into()),
+                "OTEL_TRACES_SAMPLER_ARG" => self.traces_sampler_arg = Some(value.into()),
+                "OTEL_BSP_MAX_QUEUE_SIZE" => self.max_batch_queue_size = Some(parse_usize()?),
+                "OTEL_BSP_MAX_CONCURRENT_EXPORTS" => {
+                    self.concurrent_exports = Some(parse_usize()?);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies programmatic overrides, such as command line flags, on top of this configuration.
+    ///
+    /// Optional settings of `overrides` take precedence if they are set and other settings if
+    /// they differ from their defaults. Observability is enabled if it is enabled by either
+    /// configuration. Headers and resource attributes are merged, with `overrides` taking
+    /// precedence for the same key, and CA paths and redacted attributes are combined.
+    #[must_use]
+    pub fn merge(self, overrides: OtelConfig) -> Self {
+        let default = OtelConfig::default();
+        let mut headers = self.headers;
+        headers.extend(overrides.headers);
+        let mut traces_headers = self.traces_headers;
+        traces_headers.extend(overrides.traces_headers);
+        let mut metrics_headers = self.metrics_headers;
+        metrics_headers.extend(overrides.metrics_headers);
+        let mut logs_headers = self.logs_headers;
+        logs_headers.extend(overrides.logs_headers);
+        let mut resource_attributes = self.resource_attributes;
+        resource_attributes.extend(overrides.resource_attributes);
+        let mut additional_ca_paths = self.additional_ca_paths;
+        for path in overrides.additional_ca_paths {
+            if !additional_ca_paths.contains(&path) {
+                additional_ca_paths.push(path);
+            }
+        }
+        let mut redacted_attributes = self.redacted_attributes;
+        for key in overrides.redacted_attributes {
+            if !redacted_attributes.contains(&key) {
+                redacted_attributes.push(key);
+            }
+        }
+        Self {
+            enable_observability: self.enable_observability || overrides.enable_observability,
+            enable_traces: overrides.enable_traces.or(self.enable_traces),
+            enable_metrics: overrides.enable_metrics.or(self.enable_metrics),
+            enable_logs: overrides.enable_logs.or(self.enable_logs),
+            observability_endpoint: overrides
+                .observability_endpoint
+                .or(self.observability_endpoint),
+            traces_endpoint: overrides.traces_endpoint.or(self.traces_endpoint),
+            metrics_endpoint: overrides.metrics_endpoint.or(self.metrics_endpoint),
+            logs_endpoint: overrides.logs_endpoint.or(self.logs_endpoint),
+            protocol: if overrides.protocol == default.protocol {
+                self.protocol
+            } else {
+                overrides.protocol
+            },
+            traces_protocol: overrides.traces_protocol.or(self.traces_protocol),
+            metrics_protocol: overrides.metrics_protocol.or(self.metrics_protocol),
+            logs_protocol: overrides.logs_protocol.or(self.logs_protocol),
+            exporter: if overrides.exporter == default.exporter {
+                self.exporter
+            } else {
+                overrides.exporter
+            },
+            headers,
+            traces_headers,
+            metrics_headers,
+            logs_headers,
+            service_name: overrides.service_name.or(self.service_name),
+            service_namespace: overrides.service_namespace.or(self.service_namespace),
+            resource_attributes,
+            additional_ca_paths,
+            trace_level: if overrides.trace_level == default.trace_level {
+                self.trace_level
+            } else {
+                overrides.trace_level
+            },
+            traces_sampler: overrides.traces_sampler.or(self.traces_sampler),
+            traces_sampler_arg: overrides.traces_sampler_arg.or(self.traces_sampler_arg),
+            max_batch_queue_size: overrides.max_batch_queue_size.or(self.max_batch_queue_size),
+            concurrent_exports: overrides.concurrent_exports.or(self.concurrent_exports),
+            queue_full_policy: overrides.queue_full_policy.or(self.queue_full_policy),
+            export_max_retries: overrides.export_max_retries.or(self.export_max_retries),
+            export_initial_backoff_ms: overrides
+                .export_initial_backoff_ms
+                .or(self.export_initial_backoff_ms),
+            export_max_backoff_ms: overrides
+                .export_max_backoff_ms
+                .or(self.export_max_backoff_ms),
+            max_events_per_span: overrides.max_events_per_span.or(self.max_events_per_span),
+            max_attribute_length: overrides.max_attribute_length.or(self.max_attribute_length),
+            redacted_attributes,
+            forward_to_providers: overrides.forward_to_providers.or(self.forward_to_providers),
+            provider_overrides: {
+                let mut provider_overrides = self.provider_overrides;
+                provider_overrides.extend(overrides.provider_overrides);
+                provider_overrides
+            },
+        }
+    }
+
+    /// Returns the configuration forwarded to the capability provider with the given ID.
+    ///
+    /// Providers report their own service name, but share the other settings of the host, with
+    /// the matching [`ProviderOtelOverrides`] applied. If the configuration is not forwarded to
+    /// the provider, the returned configuration disables observability.
+    pub fn for_provider(&self, provider_id: &str) -> OtelConfig {
+        let overrides = self
+            .provider_overrides
+            .get(provider_id)
+            .cloned()
+            .unwrap_or_default();
+        if !overrides
+            .forward
+            .unwrap_or(self.forward_to_providers.unwrap_or(true))
+        {
+            return OtelConfig::default();
+        }
+        OtelConfig {
+            enable_observability: self.enable_observability,
+            enable_traces: overrides.enable_traces.or(self.enable_traces),
+            enable_metrics: overrides.enable_metrics.or(self.enable_metrics),
+            enable_logs: overrides.enable_logs.or(self.enable_logs),
+            observability_endpoint: self.observability_endpoint.clone(),
+            traces_endpoint: self.traces_endpoint.clone(),
+            metrics_endpoint: self.metrics_endpoint.clone(),
+            logs_endpoint: self.logs_endpoint.clone(),
+            protocol: self.protocol,
+            traces_protocol: self.traces_protocol,
+            metrics_protocol: self.metrics_protocol,
+            logs_protocol: self.logs_protocol,
+            exporter: self.exporter.clone(),
+            headers: self.headers.clone(),
+            traces_headers: self.traces_headers.clone(),
+            metrics_headers: self.metrics_headers.clone(),
+            logs_headers: self.logs_headers.clone(),
+            service_namespace: self.service_namespace.clone(),
+            resource_attributes: self.resource_attributes.clone(),
+            additional_ca_paths: self.additional_ca_paths.clone(),
+            trace_level: overrides
+                .trace_level
+                .unwrap_or_else(|| self.trace_level.clone()),
+            max_events_per_span: self.max_events_per_span,
+            max_attribute_length: self.max_attribute_length,
+            redacted_attributes: self.redacted_attributes.clone(),
+            export_max_retries: self.export_max_retries,
+            export_initial_backoff_ms: self.export_initial_backoff_ms,
+            export_max_backoff_ms: self.export_max_backoff_ms,
+            ..Default::default()
+        }
+    }
+
     pub fn logs_endpoint(&self) -> String {
         self.resolve_endpoint(OtelSignal::Logs, self.logs_endpoint.clone())
     }
@@ -83,6 +462,57 @@ impl OtelConfig {
         self.resolve_endpoint(OtelSignal::Traces, self.traces_endpoint.clone())
     }
 
+    pub fn logs_protocol(&self) -> OtelProtocol {
+        self.logs_protocol.unwrap_or(self.protocol)
+    }
+
+    pub fn metrics_protocol(&self) -> OtelProtocol {
+        self.metrics_protocol.unwrap_or(self.protocol)
+    }
+
+    pub fn traces_protocol(&self) -> OtelProtocol {
+        self.traces_protocol.unwrap_or(self.protocol)
+    }
+
+    /// Returns the headers to export logs with, with all `env:` and `file:` values resolved
+    pub fn logs_headers(&self) -> anyhow::Result<HashMap<String, String>> {
+        self.resolve_headers(&self.logs_headers)
+    }
+
+    /// Returns the headers to export metrics with, with all `env:` and `file:` values resolved
+    pub fn metrics_headers(&self) -> anyhow::Result<HashMap<String, String>> {
+        self.resolve_headers(&self.metrics_headers)
+    }
+
+    /// Returns the headers to export traces with, with all `env:` and `file:` values resolved
+    pub fn traces_headers(&self) -> anyhow::Result<HashMap<String, String>> {
+        self.resolve_headers(&self.traces_headers)
+    }
+
+    /// Returns the policy for retrying failed exports, with defaults applied
+    pub fn export_retry_policy(&self) -> OtelRetryPolicy {
+        let default = OtelRetryPolicy::default();
+        OtelRetryPolicy {
+            max_retries: self.export_max_retries.unwrap_or(default.max_retries),
+            initial_backoff: self
+                .export_initial_backoff_ms
+                .map_or(default.initial_backoff, Duration::from_millis),
+            max_backoff: self
+                .export_max_backoff_ms
+                .map_or(default.max_backoff, Duration::from_millis),
+        }
+    }
+
+    /// Returns true if the value of the attribute with the given key must be redacted before export
+    pub fn is_redacted_attribute(&self, key: &str) -> bool {
+        self.redacted_attributes.iter().any(|redacted| {
+            key.eq_ignore_ascii_case(redacted)
+                || key
+                    .rsplit_once('.')
+                    .is_some_and(|(_, last)| last.eq_ignore_ascii_case(redacted))
+        })
+    }
+
     pub fn logs_enabled(&self) -> bool {
         self.enable_logs.unwrap_or(self.enable_observability)
     }
@@ -109,19 +539,40 @@ impl OtelConfig {
         if let Some(endpoint) = signal_endpoint_override {
             return endpoint;
         }
+        let protocol = match signal {
+            OtelSignal::Traces => self.traces_protocol(),
+            OtelSignal::Metrics => self.metrics_protocol(),
+            OtelSignal::Logs => self.logs_protocol(),
+        };
         if let Some(endpoint) = self.observability_endpoint.clone() {
-            return match self.protocol {
+            return match protocol {
                 OtelProtocol::Grpc => self.resolve_grpc_endpoint(endpoint),
                 OtelProtocol::Http => self.resolve_http_endpoint(signal, endpoint),
             };
         }
         // Set sensible defaults if nothing is provided
-        match self.protocol {
+        match protocol {
             OtelProtocol::Grpc => "http://127.0.0.1:4317".to_string(),
             OtelProtocol::Http => format!("http://127.0.0.1:4318{signal}"),
         }
     }
 
+    // Signal-specific headers take precedence over the headers configured for all signals.
+    fn resolve_headers(
+        &self,
+        signal_headers: &HashMap<String, SecretString>,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        self.headers
+            .iter()
+            .chain(signal_headers)
+            .map(|(name, value)| {
+                let value = resolve_header_value(value.expose())
+                    .with_context(|| format!("failed to resolve value of header `{name}`"))?;
+                Ok((name.clone(), value))
+            })
+            .collect()
+    }
+
     // opentelemetry-otlp expects the gRPC endpoint to not have path components
     // configured, so we're just clearing them out and returning the base url.
     fn resolve_grpc_endpoint(&self, endpoint: String) -> String {
@@ -154,18 +605,157 @@ impl OtelConfig {
     }
 }
 
+/// Parses the value of an `OTEL_EXPORTER_OTLP_*PROTOCOL` environment variable
+fn parse_env_protocol(name: &str, value: &str) -> anyhow::Result<OtelProtocol> {
+    match value.trim() {
+        "grpc" => Ok(OtelProtocol::Grpc),
+        "http/protobuf" | "http" => Ok(OtelProtocol::Http),
+        protocol => bail!(
+            "unsupported protocol `{protocol}` of `{name}`, did you mean 'grpc' or 'http/protobuf'?"
+        ),
+    }
+}
+
+/// Parses the comma-separated `key=value` pairs of an `OTEL_EXPORTER_OTLP_*HEADERS` environment
+/// variable
+fn parse_env_headers(name: &str, value: &str) -> anyhow::Result<HashMap<String, SecretString>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').with_context(|| {
+                format!("invalid header `{pair}` of `{name}`, expected key=value")
+            })?;
+            Ok((key.trim().to_string(), value.trim().into()))
+        })
+        .collect()
+}
+
+/// Resolves a header value, reading it from an environment variable if prefixed with `env:` or from
+/// a file if prefixed with `file:`
+fn resolve_header_value(value: &str) -> anyhow::Result<String> {
+    if let Some(name) = value.strip_prefix("env:") {
+        std::env::var(name).with_context(|| format!("failed to read environment variable `{name}`"))
+    } else if let Some(path) = value.strip_prefix("file:") {
+        let value =
+            std::fs::read_to_string(path).with_context(|| format!("failed to read `{path}`"))?;
+        Ok(value.trim().to_string())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
 // TODO(joonas): In a future release we should enable this renaming once we
 // are comfortable with the fact there are no providers being used that have
 // the case sensitive handling still in place.
 // #[serde(rename_all = "lowercase")]
+#[derive(Default)]
 pub enum OtelProtocol {
     #[serde(alias = "grpc", alias = "Grpc")]
     Grpc,
     #[serde(alias = "http", alias = "Http")]
+    #[default]
     Http,
 }
 
+/// What happens to ended spans while the export queue is full
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtelQueueFullPolicy {
+    /// Drop the spans, so that instrumented code is never slowed down by exporting
+    #[default]
+    Drop,
+    /// Block the thread ending a span until the queue has room again, so that no spans are lost
+    /// at the expense of latency while the collector is slow or unavailable. Spans are still
+    /// dropped once exporting them failed after all retries
+    Block,
+}
+
+impl FromStr for OtelQueueFullPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop" => Ok(Self::Drop),
+            "block" => Ok(Self::Block),
+            policy => {
+                bail!("unsupported queue full policy: {policy:?}, did you mean 'drop' or 'block'?")
+            }
+        }
+    }
+}
+
+/// Policy for retrying failed exports of telemetry with exponential backoff, see
+/// [`OtelConfig::export_retry_policy`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OtelRetryPolicy {
+    /// The maximum number of retries of a failed export
+    pub max_retries: u32,
+    /// The delay before the first retry
+    pub initial_backoff: Duration,
+    /// The maximum delay between retries
+    pub max_backoff: Duration,
+}
+
+impl Default for OtelRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl OtelRetryPolicy {
+    /// Returns the delay before the given retry, counting from 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    /// Returns the total delay of all retries of a failed export
+    pub fn total_backoff(&self) -> Duration {
+        (0..self.max_retries)
+            .map(|retry| self.backoff(retry))
+            .fold(Duration::ZERO, Duration::saturating_add)
+    }
+}
+
+/// Destination of exported telemetry
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum OtelExporter {
+    /// Export telemetry to an OpenTelemetry collector using the configured endpoints and protocols
+    #[default]
+    Otlp,
+    /// Write telemetry to stdout as newline-delimited OTLP-JSON
+    Stdout,
+    /// Append telemetry to the file at `path` as newline-delimited OTLP-JSON, which can later be
+    /// replayed to a collector
+    File { path: PathBuf },
+}
+
+impl FromStr for OtelExporter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "otlp" => Ok(Self::Otlp),
+            "stdout" => Ok(Self::Stdout),
+            exporter => match exporter.strip_prefix("file:") {
+                Some(path) if !path.is_empty() => Ok(Self::File { path: path.into() }),
+                _ => bail!(
+                    "unsupported exporter: {exporter:?}, did you mean 'otlp', 'stdout' or 'file:<path>'?"
+                ),
+            },
+        }
+    }
+}
+
 // Represents https://opentelemetry.io/docs/concepts/signals/
 enum OtelSignal {
     Traces,
@@ -187,12 +777,6 @@ impl std::fmt::Display for OtelSignal {
     }
 }
 
-impl Default for OtelProtocol {
-    fn default() -> Self {
-        Self::Http
-    }
-}
-
 impl FromStr for OtelProtocol {
     type Err = anyhow::Error;
 
@@ -210,9 +794,144 @@ impl FromStr for OtelProtocol {
 /// Environment settings for initializing a capability provider
 pub type TraceContext = WitMap<String>;
 
+/// Key of the W3C `traceparent` entry of a [`TraceContext`]
+pub const TRACEPARENT_KEY: &str = "traceparent";
+
+/// Key of the W3C `tracestate` entry of a [`TraceContext`]
+pub const TRACESTATE_KEY: &str = "tracestate";
+
+/// Key of the W3C `baggage` entry of a [`TraceContext`], which carries application-defined
+/// metadata such as tenant or request IDs alongside the trace
+pub const BAGGAGE_KEY: &str = "baggage";
+
+/// Typed accessors of the W3C trace context and baggage entries of a [`TraceContext`]
+pub trait TraceContextHeaders {
+    /// Returns the value of the entry with the given key
+    fn header(&self, key: &str) -> Option<&str>;
+
+    /// Sets the entry with the given key, replacing any previous value
+    fn set_header(&mut self, key: &str, value: String);
+
+    /// Returns the `traceparent` entry, which identifies the trace and the parent span
+    fn traceparent(&self) -> Option<&str> {
+        self.header(TRACEPARENT_KEY)
+    }
+
+    /// Returns the `tracestate` entry, which carries vendor-specific trace data
+    fn tracestate(&self) -> Option<&str> {
+        self.header(TRACESTATE_KEY)
+    }
+
+    /// Returns the decoded members of the `baggage` entry, in order. Properties of members are
+    /// ignored and malformed members are skipped
+    fn baggage(&self) -> Vec<(String, String)> {
+        self.header(BAGGAGE_KEY)
+            .map(parse_baggage)
+            .unwrap_or_default()
+    }
+
+    /// Returns the decoded value of the baggage member with the given key
+    fn baggage_item(&self, key: &str) -> Option<String> {
+        self.baggage()
+            .into_iter()
+            .find_map(|(k, v)| (k == key).then_some(v))
+    }
+
+    /// Sets the baggage member with the given key, replacing any previous value of the member
+    fn set_baggage_item(&mut self, key: &str, value: &str) {
+        let mut baggage = self.baggage();
+        baggage.retain(|(k, _)| k != key);
+        baggage.push((key.to_string(), value.to_string()));
+        self.set_header(BAGGAGE_KEY, format_baggage(&baggage));
+    }
+}
+
+impl TraceContextHeaders for TraceContext {
+    fn header(&self, key: &str) -> Option<&str> {
+        self.iter()
+            .find_map(|(k, v)| k.eq_ignore_ascii_case(key).then_some(v.as_str()))
+    }
+
+    fn set_header(&mut self, key: &str, value: String) {
+        let existing = self
+            .keys()
+            .find(|k| k.eq_ignore_ascii_case(key))
+            .map(str::to_string);
+        match existing.and_then(|k| self.get_mut(&k)) {
+            Some(v) => *v = value,
+            None => {
+                self.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Parses the members of a W3C `baggage` header, decoding their values
+pub fn parse_baggage(header: &str) -> Vec<(String, String)> {
+    header
+        .split(',')
+        .filter_map(|member| {
+            // Properties following the value are not supported and dropped
+            let member = member.split(';').next()?;
+            let (key, value) = member.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some((key.to_string(), percent_decode(value.trim())?))
+        })
+        .collect()
+}
+
+/// Formats members as a W3C `baggage` header, encoding their values
+pub fn format_baggage<K: AsRef<str>, V: AsRef<str>>(members: &[(K, V)]) -> String {
+    members
+        .iter()
+        .map(|(key, value)| format!("{}={}", key.as_ref(), percent_encode(value.as_ref())))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Percent-encodes all bytes of `value` that are not allowed in baggage values unencoded
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for b in value.bytes() {
+        // `baggage-octet` of the W3C baggage specification, excluding `%` used for encoding
+        if matches!(b, 0x21 | 0x23..=0x24 | 0x26..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E) {
+            encoded.push(char::from(b));
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Decodes a percent-encoded baggage value, returning `None` if it is malformed
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.bytes();
+    while let Some(b) = input.next() {
+        if b == b'%' {
+            let hex = [input.next()?, input.next()?];
+            let hex = std::str::from_utf8(&hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        } else {
+            bytes.push(b);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{OtelConfig, OtelProtocol};
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use super::{
+        OtelConfig, OtelExporter, OtelProtocol, OtelQueueFullPolicy, ProviderOtelOverrides,
+        TraceContext, TraceContextHeaders,
+    };
+    use crate::logging::Level;
 
     #[test]
     fn test_grpc_resolves_to_defaults_without_overrides() {
@@ -312,4 +1031,278 @@ mod tests {
         assert_eq!(expected_metrics, config.metrics_endpoint());
         assert_eq!(expected_logs, config.logs_endpoint());
     }
+
+    #[test]
+    fn test_signal_specific_protocols_override_protocol() {
+        let config = OtelConfig {
+            protocol: OtelProtocol::Http,
+            traces_protocol: Some(OtelProtocol::Grpc),
+            observability_endpoint: Some(String::from("https://example.com:4318")),
+            ..Default::default()
+        };
+
+        assert_eq!(OtelProtocol::Grpc, config.traces_protocol());
+        assert_eq!(OtelProtocol::Http, config.metrics_protocol());
+        assert_eq!(OtelProtocol::Http, config.logs_protocol());
+        assert_eq!("https://example.com:4318", config.traces_endpoint());
+        assert_eq!(
+            "https://example.com:4318/v1/metrics",
+            config.metrics_endpoint()
+        );
+        assert_eq!("https://example.com:4318/v1/logs", config.logs_endpoint());
+
+        let config = OtelConfig {
+            protocol: OtelProtocol::Grpc,
+            metrics_protocol: Some(OtelProtocol::Http),
+            ..Default::default()
+        };
+
+        assert_eq!("http://127.0.0.1:4317", config.traces_endpoint());
+        assert_eq!(
+            "http://127.0.0.1:4318/v1/metrics",
+            config.metrics_endpoint()
+        );
+        assert_eq!("http://127.0.0.1:4317", config.logs_endpoint());
+    }
+
+    #[test]
+    fn test_signal_specific_headers_override_headers() {
+        let key_path =
+            std::env::temp_dir().join(format!("wasmcloud-otel-api-key-{}", std::process::id()));
+        std::fs::write(&key_path, "secret-from-file\n").expect("failed to write key");
+
+        let config = OtelConfig {
+            headers: HashMap::from([
+                ("x-api-key".to_string(), "shared".into()),
+                ("x-team".to_string(), "wasmcloud".into()),
+            ]),
+            traces_headers: HashMap::from([(
+                "x-api-key".to_string(),
+                format!("file:{}", key_path.display()).into(),
+            )]),
+            ..Default::default()
+        };
+
+        let traces = config.traces_headers().expect("failed to resolve headers");
+        assert_eq!(traces["x-api-key"], "secret-from-file");
+        assert_eq!(traces["x-team"], "wasmcloud");
+        let metrics = config.metrics_headers().expect("failed to resolve headers");
+        assert_eq!(metrics["x-api-key"], "shared");
+        assert_eq!(metrics["x-team"], "wasmcloud");
+
+        let config = OtelConfig {
+            logs_headers: HashMap::from([(
+                "x-api-key".to_string(),
+                "env:WASMCLOUD_TEST_OTEL_HEADER_DOES_NOT_EXIST".into(),
+            )]),
+            ..Default::default()
+        };
+        assert!(config.logs_headers().is_err());
+        assert!(config.traces_headers().expect("no headers").is_empty());
+
+        let _ = std::fs::remove_file(key_path);
+    }
+
+    #[test]
+    fn test_provider_config_applies_overrides() {
+        let config = OtelConfig {
+            enable_observability: true,
+            service_name: Some("host".into()),
+            trace_level: Level::Debug,
+            forward_to_providers: Some(false),
+            provider_overrides: HashMap::from([(
+                "http-server".into(),
+                ProviderOtelOverrides {
+                    forward: Some(true),
+                    enable_logs: Some(false),
+                    trace_level: Some(Level::Warn),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        assert!(!config.for_provider("keyvalue").enable_observability);
+
+        let provider = config.for_provider("http-server");
+        assert!(provider.traces_enabled());
+        assert!(!provider.logs_enabled());
+        assert_eq!(provider.trace_level, Level::Warn);
+        assert_eq!(provider.service_name, None);
+        assert!(provider.provider_overrides.is_empty());
+
+        let config = OtelConfig {
+            forward_to_providers: None,
+            ..config
+        };
+        let provider = config.for_provider("keyvalue");
+        assert!(provider.logs_enabled());
+        assert_eq!(provider.trace_level, Level::Debug);
+    }
+
+    #[test]
+    fn test_config_layers_resolve_in_order() {
+        let dir = std::env::temp_dir().join(format!("otel-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create dir");
+        let path = dir.join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"otel":{"enable_observability":true,"protocol":"Grpc","service_name":"file","traces_sampler":"always_on"}}"#,
+        )
+        .expect("failed to write config");
+
+        let mut config = OtelConfig::from_file(&path).expect("failed to read config");
+        assert!(config.enable_observability);
+        assert_eq!(config.protocol, OtelProtocol::Grpc);
+        assert_eq!(config.service_name.as_deref(), Some("file"));
+
+        config
+            .apply_env([
+                ("OTEL_SERVICE_NAME", "env"),
+                ("otel_exporter_otlp_headers", "api-key=secret, tenant=acme"),
+                ("OTEL_EXPORTER_OTLP_LOGS_PROTOCOL", "http/protobuf"),
+                ("UNRELATED", "value"),
+            ])
+            .expect("failed to apply env");
+        assert_eq!(config.service_name.as_deref(), Some("env"));
+        assert_eq!(
+            config
+                .headers
+                .get("tenant")
+                .map(|value| value.expose().as_str()),
+            Some("acme")
+        );
+        assert_eq!(config.logs_protocol(), OtelProtocol::Http);
+        assert!(config
+            .apply_env([("OTEL_BSP_MAX_QUEUE_SIZE", "many")])
+            .is_err());
+
+        let config = config.merge(OtelConfig {
+            service_name: Some("flag".into()),
+            ..Default::default()
+        });
+        assert_eq!(config.service_name.as_deref(), Some("flag"));
+        // Settings left at their defaults do not override lower layers
+        assert!(config.enable_observability);
+        assert_eq!(config.protocol, OtelProtocol::Grpc);
+        assert_eq!(config.traces_sampler.as_deref(), Some("always_on"));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_trace_context_baggage_roundtrips() {
+        let mut context = TraceContext::from(vec![
+            (
+                "traceparent".into(),
+                "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01".into(),
+            ),
+            (
+                "baggage".into(),
+                "tenant=acme;ttl=30, bad, region=us%20east".into(),
+            ),
+        ]);
+        assert_eq!(
+            context.traceparent(),
+            Some("00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")
+        );
+        assert_eq!(context.tracestate(), None);
+        assert_eq!(
+            context.baggage(),
+            vec![
+                ("tenant".to_string(), "acme".to_string()),
+                ("region".to_string(), "us east".to_string()),
+            ]
+        );
+
+        context.set_baggage_item("tenant", "globex, inc");
+        context.set_baggage_item("request-id", "42");
+        assert_eq!(
+            context.baggage_item("tenant").as_deref(),
+            Some("globex, inc")
+        );
+        assert_eq!(context.baggage_item("request-id").as_deref(), Some("42"));
+        assert_eq!(
+            context.header("baggage"),
+            Some("region=us%20east,tenant=globex%2C%20inc,request-id=42")
+        );
+        assert_eq!(context.len(), 2);
+    }
+
+    #[test]
+    fn test_redacted_attributes_match_keys_and_last_segments() {
+        let config = OtelConfig {
+            redacted_attributes: vec!["authorization".into(), "db.statement".into()],
+            ..Default::default()
+        };
+        assert!(config.is_redacted_attribute("authorization"));
+        assert!(config.is_redacted_attribute("Authorization"));
+        assert!(config.is_redacted_attribute("http.request.header.authorization"));
+        assert!(config.is_redacted_attribute("db.statement"));
+        assert!(!config.is_redacted_attribute("authorization.scheme"));
+        assert!(!config.is_redacted_attribute("http.request.method"));
+        assert!(!OtelConfig::default().is_redacted_attribute("authorization"));
+    }
+
+    #[test]
+    fn test_exporter_parses_and_defaults_to_otlp() {
+        assert_eq!(OtelExporter::Otlp, OtelConfig::default().exporter);
+        assert_eq!(OtelExporter::Otlp, "otlp".parse().expect("failed to parse"));
+        assert_eq!(
+            OtelExporter::Stdout,
+            "stdout".parse().expect("failed to parse")
+        );
+        assert_eq!(
+            OtelExporter::File {
+                path: "/tmp/telemetry.jsonl".into()
+            },
+            "file:/tmp/telemetry.jsonl"
+                .parse()
+                .expect("failed to parse")
+        );
+        assert!("file:".parse::<OtelExporter>().is_err());
+        assert!("jaeger".parse::<OtelExporter>().is_err());
+
+        let config: OtelConfig =
+            serde_json::from_str(r#"{"exporter":{"type":"file","path":"/tmp/telemetry.jsonl"}}"#)
+                .expect("failed to deserialize");
+        assert_eq!(
+            OtelExporter::File {
+                path: "/tmp/telemetry.jsonl".into()
+            },
+            config.exporter
+        );
+        let config: OtelConfig = serde_json::from_str("{}").expect("failed to deserialize");
+        assert_eq!(OtelExporter::Otlp, config.exporter);
+    }
+
+    #[test]
+    fn test_export_retry_policy_backs_off_exponentially() {
+        let policy = OtelConfig::default().export_retry_policy();
+        assert_eq!(0, policy.max_retries);
+        assert_eq!(Duration::ZERO, policy.total_backoff());
+
+        let config: OtelConfig = serde_json::from_str(
+            r#"{"export_max_retries":4,"export_initial_backoff_ms":500,"export_max_backoff_ms":3000,"queue_full_policy":"block"}"#,
+        )
+        .expect("failed to deserialize");
+        assert_eq!(Some(OtelQueueFullPolicy::Block), config.queue_full_policy);
+        let policy = config.export_retry_policy();
+        assert_eq!(Duration::from_millis(500), policy.backoff(0));
+        assert_eq!(Duration::from_millis(1000), policy.backoff(1));
+        assert_eq!(Duration::from_millis(2000), policy.backoff(2));
+        assert_eq!(Duration::from_millis(3000), policy.backoff(3));
+        assert_eq!(Duration::from_millis(3000), policy.backoff(u32::MAX));
+        assert_eq!(Duration::from_millis(6500), policy.total_backoff());
+
+        // Retries are forwarded to providers, which export to the same collectors
+        let provider = config.for_provider("provider");
+        assert_eq!(config.export_retry_policy(), provider.export_retry_policy());
+        assert_eq!(None, provider.queue_full_policy);
+
+        assert_eq!(
+            OtelQueueFullPolicy::Drop,
+            "drop".parse().expect("failed to parse")
+        );
+        assert!("wait".parse::<OtelQueueFullPolicy>().is_err());
+    }
 }
@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use anyhow::{Context as _, Result};
 
+use crate::secrets::SecretString;
+
 /// The type of a registry
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 #[non_exhaustive]
@@ -16,9 +18,9 @@ pub enum RegistryType {
 #[non_exhaustive]
 pub enum RegistryAuth {
     /// HTTP Basic authentication (username and password)
-    Basic(String, String),
+    Basic(String, SecretString),
     /// token authentication
-    Token(String),
+    Token(SecretString),
     /// No authentication
     #[default]
     Anonymous,
@@ -27,7 +29,7 @@ pub enum RegistryAuth {
 impl From<(Option<String>, Option<String>)> for RegistryAuth {
     fn from((maybe_username, maybe_password): (Option<String>, Option<String>)) -> Self {
         match (maybe_username, maybe_password) {
-            (Some(username), Some(password)) => Self::Basic(username, password),
+            (Some(username), Some(password)) => Self::Basic(username, password.into()),
             _ => Self::Anonymous,
         }
     }
@@ -38,7 +40,7 @@ impl From<&RegistryAuth> for oci_client::secrets::RegistryAuth {
     fn from(auth: &crate::RegistryAuth) -> Self {
         match auth {
             crate::RegistryAuth::Basic(username, password) => {
-                Self::Basic(username.clone(), password.clone())
+                Self::Basic(username.clone(), password.expose().clone())
             }
             _ => Self::Anonymous,
         }
@@ -49,14 +51,16 @@ impl From<&RegistryAuth> for oci_client::secrets::RegistryAuth {
 impl From<RegistryAuth> for oci_client::secrets::RegistryAuth {
     fn from(auth: crate::RegistryAuth) -> Self {
         match auth {
-            crate::RegistryAuth::Basic(username, password) => Self::Basic(username, password),
+            crate::RegistryAuth::Basic(username, password) => {
+                Self::Basic(username, password.into_inner())
+            }
             _ => Self::Anonymous,
         }
     }
 }
 
 /// Credentials for a registry containing wasmCloud artifacts
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 pub struct RegistryConfig {
     /// The type of the registry (only OCI is supported at this time)
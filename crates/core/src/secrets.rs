@@ -44,3 +44,197 @@ impl Debug for SecretValue {
         }
     }
 }
+
+/// Placeholder written in place of redacted values
+pub const REDACTED: &str = "[redacted]";
+
+/// A value, such as a password, credential or API key, that must not end up in logs or other
+/// output.
+///
+/// The [`Debug`] and [`Display`](std::fmt::Display) implementations as well as the [`Serialize`]
+/// implementation write [`REDACTED`] instead of the value, while deserializing reads the value
+/// itself. Data that has to carry the value, such as configuration forwarded to providers, opts in
+/// to serializing it with the [`expose`], [`expose_option`] and [`expose_map`] serde helpers.
+#[derive(Clone, Default, Deserialize, Eq, Hash, PartialEq)]
+#[serde(transparent)]
+pub struct Redacted<T>(T);
+
+/// A string that must not end up in logs or other output
+pub type SecretString = Redacted<String>;
+
+impl<T> Redacted<T> {
+    /// Wraps the value
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns a reference to the value. Take care not to log or otherwise output it
+    #[must_use]
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    /// Returns the value. Take care not to log or otherwise output it
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Redacted<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<T> Debug for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> std::fmt::Display for Redacted<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> Serialize for Redacted<T> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+/// Serde helper serializing the value of a [`Redacted`] instead of [`REDACTED`], e.g.
+/// `#[serde(with = "wasmcloud_core::secrets::expose")]`
+pub mod expose {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Redacted;
+
+    /// Deserialize the value
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Redacted<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Redacted::deserialize(deserializer)
+    }
+
+    /// Serialize the value itself
+    pub fn serialize<S, T>(value: &Redacted<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        value.expose().serialize(serializer)
+    }
+}
+
+/// Serde helper serializing the value of an optional [`Redacted`] instead of [`REDACTED`], e.g.
+/// `#[serde(default, with = "wasmcloud_core::secrets::expose_option")]`
+pub mod expose_option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Redacted;
+
+    /// Deserialize the value, if any
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<Redacted<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        Option::deserialize(deserializer)
+    }
+
+    /// Serialize the value itself, if any
+    pub fn serialize<S, T>(value: &Option<Redacted<T>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Serialize,
+    {
+        value.as_ref().map(Redacted::expose).serialize(serializer)
+    }
+}
+
+/// Serde helper serializing the values of a map of [`Redacted`] instead of [`REDACTED`], e.g.
+/// `#[serde(default, with = "wasmcloud_core::secrets::expose_map")]`
+pub mod expose_map {
+    use std::collections::HashMap;
+    use std::hash::Hash;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Redacted;
+
+    /// Deserialize the map
+    pub fn deserialize<'de, D, K, T>(deserializer: D) -> Result<HashMap<K, Redacted<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Eq + Hash,
+        T: Deserialize<'de>,
+    {
+        HashMap::deserialize(deserializer)
+    }
+
+    /// Serialize the map with the values themselves
+    pub fn serialize<S, K, T>(
+        value: &HashMap<K, Redacted<T>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        K: Serialize,
+        T: Serialize,
+    {
+        serializer.collect_map(value.iter().map(|(k, v)| (k, v.expose())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Credentials {
+        user: String,
+        password: SecretString,
+        #[serde(with = "expose")]
+        token: SecretString,
+        #[serde(with = "expose_map")]
+        headers: HashMap<String, SecretString>,
+    }
+
+    #[test]
+    fn test_redacted_is_masked() {
+        let credentials: Credentials = serde_json::from_str(
+            r#"{"user":"admin","password":"hunter2","token":"t0k3n","headers":{"api-key":"s3cr3t"}}"#,
+        )
+        .expect("failed to parse credentials");
+        assert_eq!(credentials.password.expose(), "hunter2");
+        assert_eq!(credentials.password.to_string(), REDACTED);
+
+        let debug = format!("{credentials:?}");
+        assert!(debug.contains("admin"));
+        for secret in ["hunter2", "t0k3n", "s3cr3t"] {
+            assert!(!debug.contains(secret), "{secret} was logged");
+        }
+
+        let json = serde_json::to_value(&credentials).expect("failed to serialize credentials");
+        assert_eq!(json["password"], REDACTED);
+        assert_eq!(json["token"], "t0k3n");
+        assert_eq!(json["headers"]["api-key"], "s3cr3t");
+    }
+}
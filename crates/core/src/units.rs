@@ -0,0 +1,518 @@
+//! Human-readable durations (e.g. `30s`, `5m`, `1h30m`) and sizes (e.g. `512MiB`, `10MB`) for
+//! host and provider configuration.
+//!
+//! [`HumanDuration`] and [`ByteSize`] parse and format these values and serialize as strings that
+//! round-trip. Existing configuration fields that store plain integers in a fixed unit accept
+//! human-readable values through the `option_*` serde helpers, e.g.
+//! `#[serde(default, with = "crate::units::option_millis")]`, while still serializing as integers
+//! so that older hosts and providers can read them.
+
+use core::fmt;
+use core::str::FromStr;
+use core::time::Duration;
+
+use anyhow::{bail, ensure, Context as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Units accepted in durations
+const DURATION_UNITS: &[(&str, Duration)] = &[
+    ("ns", Duration::from_nanos(1)),
+    ("us", Duration::from_micros(1)),
+    ("µs", Duration::from_micros(1)),
+    ("ms", Duration::from_millis(1)),
+    ("s", Duration::from_secs(1)),
+    ("m", Duration::from_secs(60)),
+    ("h", Duration::from_secs(60 * 60)),
+    ("d", Duration::from_secs(24 * 60 * 60)),
+];
+
+/// Units used to format durations, largest first
+const DURATION_FORMAT_UNITS: &[(&str, u128)] = &[
+    ("d", 24 * 60 * 60 * 1_000_000_000),
+    ("h", 60 * 60 * 1_000_000_000),
+    ("m", 60 * 1_000_000_000),
+    ("s", 1_000_000_000),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+/// Binary size units, largest first
+const BINARY_SIZE_UNITS: &[(&str, u64)] = &[
+    ("PiB", 1 << 50),
+    ("TiB", 1 << 40),
+    ("GiB", 1 << 30),
+    ("MiB", 1 << 20),
+    ("KiB", 1 << 10),
+];
+
+/// Decimal size units, largest first
+const DECIMAL_SIZE_UNITS: &[(&str, u64)] = &[
+    ("PB", 1_000_000_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("GB", 1_000_000_000),
+    ("MB", 1_000_000),
+    ("KB", 1_000),
+];
+
+/// Parse a human-readable duration like `30s`, `250ms` or `1h30m`.
+///
+/// A duration is one or more numbers, each followed by one of the units `ns`, `us`, `ms`, `s`,
+/// `m`, `h` or `d`. Numbers may have a fractional part, e.g. `1.5s`.
+///
+/// # Errors
+///
+/// Returns an error if the duration is empty, has a component without a unit, or overflows
+pub fn parse_duration(value: &str) -> anyhow::Result<Duration> {
+    let value = value.trim();
+    ensure!(!value.is_empty(), "duration cannot be empty");
+    let mut rest = value;
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .with_context(|| {
+                format!("invalid duration `{value}`, `{rest}` has no unit (e.g. `{rest}s`)")
+            })?;
+        ensure!(
+            number_len > 0,
+            "invalid duration `{value}`, expected a number"
+        );
+        let (number, tail) = rest.split_at(number_len);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let Some((_, unit)) = DURATION_UNITS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(unit.trim()))
+        else {
+            bail!("invalid duration `{value}`, unknown unit `{unit}`");
+        };
+        let component = if number.contains('.') {
+            let number: f64 = number
+                .parse()
+                .with_context(|| format!("invalid duration `{value}`"))?;
+            Duration::try_from_secs_f64(number * unit.as_secs_f64())
+                .with_context(|| format!("invalid duration `{value}`"))?
+        } else {
+            let number: u64 = number
+                .parse()
+                .with_context(|| format!("invalid duration `{value}`"))?;
+            checked_mul(*unit, number)
+                .with_context(|| format!("duration `{value}` is too large"))?
+        };
+        total = total
+            .checked_add(component)
+            .with_context(|| format!("duration `{value}` is too large"))?;
+        rest = tail;
+    }
+    Ok(total)
+}
+
+/// Parse a duration like [`parse_duration`], but interpret a plain number as a multiple of
+/// `unit`. This keeps configuration values that used to be plain integers working, e.g. a
+/// `timeout_ms` of `500` and `500ms` are the same duration.
+///
+/// # Errors
+///
+/// Returns an error if the value is neither a number nor a valid duration
+pub fn parse_duration_or(value: &str, unit: Duration) -> anyhow::Result<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(number) => {
+            checked_mul(unit, number).with_context(|| format!("duration `{value}` is too large"))
+        }
+        Err(_) => parse_duration(value),
+    }
+}
+
+/// Multiply a duration by an integer, returning `None` on overflow
+fn checked_mul(unit: Duration, count: u64) -> Option<Duration> {
+    let nanos = unit.as_nanos().checked_mul(u128::from(count))?;
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    // The remainder is always less than one second, so it fits in a `u32`
+    let subsec_nanos = u32::try_from(nanos % 1_000_000_000).ok()?;
+    Some(Duration::new(secs, subsec_nanos))
+}
+
+/// Format a duration so that [`parse_duration`] returns the same duration, e.g. `1h30m` or
+/// `250ms`
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let mut nanos = duration.as_nanos();
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for (name, unit) in DURATION_FORMAT_UNITS {
+        let count = nanos / unit;
+        if count > 0 {
+            out.push_str(&format!("{count}{name}"));
+            nanos %= unit;
+        }
+    }
+    out
+}
+
+/// Parse a human-readable size like `512MiB`, `10MB` or `1024`.
+///
+/// Binary units (`KiB`, `MiB`, `GiB`, `TiB`, `PiB`) are powers of 1024, decimal units (`KB`,
+/// `MB`, `GB`, `TB`, `PB`) are powers of 1000, and a number without a unit or with `B` is a
+/// number of bytes. Units are case-insensitive and numbers may have a fractional part, e.g.
+/// `1.5GiB`.
+///
+/// # Errors
+///
+/// Returns an error if the size is empty, has an unknown unit, or overflows
+pub fn parse_size(value: &str) -> anyhow::Result<u64> {
+    let value = value.trim();
+    let number_len = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    ensure!(number_len > 0, "invalid size `{value}`, expected a number");
+    let (number, unit) = value.split_at(number_len);
+    let unit = unit.trim();
+    let multiplier = if unit.is_empty() || unit.eq_ignore_ascii_case("B") {
+        1
+    } else if let Some((_, multiplier)) = BINARY_SIZE_UNITS
+        .iter()
+        .chain(DECIMAL_SIZE_UNITS)
+        .find(|(name, _)| name.eq_ignore_ascii_case(unit))
+    {
+        *multiplier
+    } else {
+        bail!("invalid size `{value}`, unknown unit `{unit}`");
+    };
+    if number.contains('.') {
+        let number: f64 = number
+            .parse()
+            .with_context(|| format!("invalid size `{value}`"))?;
+        let bytes = (number * multiplier as f64).round();
+        ensure!(bytes < u64::MAX as f64, "size `{value}` is too large");
+        Ok(bytes as u64)
+    } else {
+        let number: u64 = number
+            .parse()
+            .with_context(|| format!("invalid size `{value}`"))?;
+        number
+            .checked_mul(multiplier)
+            .with_context(|| format!("size `{value}` is too large"))
+    }
+}
+
+/// Format a size so that [`parse_size`] returns the same size, using the largest binary or
+/// decimal unit the size is a whole multiple of, e.g. `512MiB`, `10MB` or `1023B`
+#[must_use]
+pub fn format_size(bytes: u64) -> String {
+    if bytes == 0 {
+        return "0B".to_string();
+    }
+    BINARY_SIZE_UNITS
+        .iter()
+        .chain(DECIMAL_SIZE_UNITS)
+        .find(|(_, multiplier)| bytes.is_multiple_of(*multiplier))
+        .map_or_else(
+            || format!("{bytes}B"),
+            |(name, multiplier)| format!("{}{name}", bytes / multiplier),
+        )
+}
+
+/// A [`Duration`] that is parsed from and formatted as a human-readable string like `30s`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct HumanDuration(pub Duration);
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(HumanDuration(duration): HumanDuration) -> Self {
+        duration
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_duration(s).map(Self)
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_duration(self.0))
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A size in bytes that is parsed from and formatted as a human-readable string like `512MiB`
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ByteSize(pub u64);
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ByteSize> for u64 {
+    fn from(ByteSize(bytes): ByteSize) -> Self {
+        bytes
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_size(s).map(Self)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_size(self.0))
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match IntegerOrString::deserialize(deserializer)? {
+            IntegerOrString::Integer(bytes) => Ok(Self(bytes)),
+            IntegerOrString::String(value) => value.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// A configuration value that is either a plain integer or a human-readable string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IntegerOrString {
+    Integer(u64),
+    String(String),
+}
+
+/// Deserialize an optional integer field in a fixed unit, which may also be given as a
+/// human-readable string
+fn deserialize_option<'de, D, T>(
+    deserializer: D,
+    parse: impl FnOnce(&str) -> anyhow::Result<u64>,
+) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    let value = match Option::<IntegerOrString>::deserialize(deserializer)? {
+        None => return Ok(None),
+        Some(IntegerOrString::Integer(value)) => value,
+        Some(IntegerOrString::String(value)) => parse(&value).map_err(serde::de::Error::custom)?,
+    };
+    T::try_from(value)
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("value `{value}` is out of range")))
+}
+
+/// Serialize an optional integer field as a plain integer
+fn serialize_option<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: TryInto<u64> + Copy,
+{
+    match value.map(TryInto::try_into) {
+        None => serializer.serialize_none(),
+        Some(Ok(value)) => serializer.serialize_some(&value),
+        Some(Err(_)) => Err(serde::ser::Error::custom("value is out of range")),
+    }
+}
+
+macro_rules! option_duration_module {
+    ($name:ident, $unit:literal, $from:expr, $as:ident) => {
+        #[doc = concat!("Serde helpers for optional integer durations in ", $unit, ", which also accept human-readable durations like `30s`")]
+        pub mod $name {
+            use serde::{Deserializer, Serializer};
+
+            /// Deserialize the duration from an integer or a human-readable string
+            pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+            where
+                D: Deserializer<'de>,
+                T: TryFrom<u64>,
+            {
+                super::deserialize_option(deserializer, |value| {
+                    let duration = super::parse_duration_or(value, $from)?;
+                    u64::try_from(duration.$as()).map_err(|_| {
+                        anyhow::anyhow!("duration `{value}` is too large")
+                    })
+                })
+            }
+
+            /// Serialize the duration as an integer
+            pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+                T: TryInto<u64> + Copy,
+            {
+                super::serialize_option(value, serializer)
+            }
+        }
+    };
+}
+
+option_duration_module!(
+    option_millis,
+    "milliseconds",
+    core::time::Duration::from_millis(1),
+    as_millis
+);
+option_duration_module!(
+    option_secs,
+    "seconds",
+    core::time::Duration::from_secs(1),
+    as_secs
+);
+
+/// Serde helpers for optional integer sizes in bytes, which also accept human-readable sizes
+/// like `512MiB`
+pub mod option_bytes {
+    use serde::{Deserializer, Serializer};
+
+    /// Deserialize the size from an integer or a human-readable string
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<u64>,
+    {
+        super::deserialize_option(deserializer, super::parse_size)
+    }
+
+    /// Serialize the size as an integer
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: TryInto<u64> + Copy,
+    {
+        super::serialize_option(value, serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format_duration() {
+        assert_eq!(
+            parse_duration("30s").expect("should be valid"),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            parse_duration("5m").expect("should be valid"),
+            Duration::from_secs(300)
+        );
+        assert_eq!(
+            parse_duration("250ms").expect("should be valid"),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            parse_duration("1h30m").expect("should be valid"),
+            Duration::from_secs(5400)
+        );
+        assert_eq!(
+            parse_duration("1.5s").expect("should be valid"),
+            Duration::from_millis(1500)
+        );
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("30 parsecs").is_err());
+        assert_eq!(
+            parse_duration_or("500", Duration::from_millis(1)).expect("should be valid"),
+            Duration::from_millis(500)
+        );
+
+        for value in ["0s", "30s", "5m", "1h30m", "250ms", "1d2h3m4s5ms6us7ns"] {
+            assert_eq!(
+                format_duration(parse_duration(value).expect("should be valid")),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_and_format_size() {
+        assert_eq!(parse_size("512MiB").expect("should be valid"), 512 << 20);
+        assert_eq!(parse_size("10mb").expect("should be valid"), 10_000_000);
+        assert_eq!(parse_size("1024").expect("should be valid"), 1024);
+        assert_eq!(parse_size("1.5KiB").expect("should be valid"), 1536);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("12 parsecs").is_err());
+        assert!(parse_size("99999999PiB").is_err());
+
+        for value in ["0B", "512MiB", "10MB", "1023B", "1GiB"] {
+            assert_eq!(
+                format_size(parse_size(value).expect("should be valid")),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_serde_helpers() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Config {
+            #[serde(default, with = "option_millis")]
+            timeout_ms: Option<u64>,
+            #[serde(default, with = "option_secs")]
+            interval_sec: Option<u16>,
+            #[serde(default, with = "option_bytes")]
+            max_bytes: Option<usize>,
+            limit: Option<ByteSize>,
+            period: Option<HumanDuration>,
+        }
+
+        let config: Config = serde_json::from_str(
+            r#"{"timeout_ms":"2s","interval_sec":"1m","max_bytes":"1KiB","limit":"512MiB","period":"1h30m"}"#,
+        )
+        .expect("should be valid");
+        assert_eq!(
+            config,
+            Config {
+                timeout_ms: Some(2000),
+                interval_sec: Some(60),
+                max_bytes: Some(1024),
+                limit: Some(ByteSize(512 << 20)),
+                period: Some(HumanDuration(Duration::from_secs(5400))),
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&config).expect("should be valid"),
+            r#"{"timeout_ms":2000,"interval_sec":60,"max_bytes":1024,"limit":"512MiB","period":"1h30m"}"#
+        );
+        let config: Config =
+            serde_json::from_str(r#"{"timeout_ms":2000,"max_bytes":1024,"limit":10}"#)
+                .expect("should be valid");
+        assert_eq!(config.timeout_ms, Some(2000));
+        assert_eq!(config.interval_sec, None);
+        assert_eq!(config.limit, Some(ByteSize(10)));
+        assert!(serde_json::from_str::<Config>(r#"{"interval_sec":"1000d"}"#).is_err());
+    }
+}
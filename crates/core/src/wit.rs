@@ -2,43 +2,250 @@
 //!
 //! [wit]: <https://github.com/WebAssembly/component-model/blob/main/design/mvp/WIT.md>
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::BuildHasher;
+use std::ops::Deref;
 
 use anyhow::{bail, Context as _, Result};
 use semver::Version;
+use serde::de::{MapAccess, Visitor};
 use serde::ser::SerializeMap;
-use serde::{Deserialize, Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{WitFunction, WitInterface, WitNamespace, WitPackage};
 
 /// Representation of maps (AKA associative arrays) that are usable from WIT
 ///
 /// This representation is required because WIT does not natively
-/// have support for a map type, so we must use a list of tuples
-pub type WitMap<T> = Vec<(String, T)>;
-
-pub(crate) fn serialize_wit_map<S: Serializer, T>(
-    map: &WitMap<T>,
-    serializer: S,
-) -> std::result::Result<S::Ok, S::Error>
-where
-    T: Serialize,
-{
-    let mut seq = serializer.serialize_map(Some(map.len()))?;
-    for (key, val) in map {
-        seq.serialize_entry(key, val)?;
-    }
-    seq.end()
+/// have support for a map type, so we must use a list of tuples. The entries are kept sorted by
+/// key without duplicates, so that [`get`](WitMap::get) and [`insert`](WitMap::insert) use binary
+/// search, while the map still dereferences to the list of tuples that is passed across WIT.
+///
+/// When building a map from entries with duplicate keys, e.g. with [`FromIterator`] or
+/// [`Extend`], the last value of a key wins, like it does for [`HashMap`].
+///
+/// Maps (de)serialize as maps, e.g. JSON objects.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct WitMap<T>(Vec<(String, T)>);
+
+impl<T> Default for WitMap<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> WitMap<T> {
+    /// Creates an empty map
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty map with space for at least `capacity` entries
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    fn position(&self, key: &str) -> std::result::Result<usize, usize> {
+        self.0.binary_search_by(|(k, _)| k.as_str().cmp(key))
+    }
+
+    /// Returns the value of the given key
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&T> {
+        let idx = self.position(key).ok()?;
+        Some(&self.0[idx].1)
+    }
+
+    /// Returns a mutable reference to the value of the given key
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut T> {
+        let idx = self.position(key).ok()?;
+        Some(&mut self.0[idx].1)
+    }
+
+    /// Returns true if the map contains the given key
+    #[must_use]
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_ok()
+    }
+
+    /// Sets the value of the given key, returning the previous value, if any
+    pub fn insert(&mut self, key: impl Into<String>, value: T) -> Option<T> {
+        let key = key.into();
+        match self.position(&key) {
+            Ok(idx) => Some(std::mem::replace(&mut self.0[idx].1, value)),
+            Err(idx) => {
+                self.0.insert(idx, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes the given key, returning its value, if any
+    pub fn remove(&mut self, key: &str) -> Option<T> {
+        let idx = self.position(key).ok()?;
+        Some(self.0.remove(idx).1)
+    }
+
+    /// Returns an iterator over the keys, in order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|(k, _)| k.as_str())
+    }
+
+    /// Returns an iterator over the values, in the order of their keys
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.0.iter().map(|(_, v)| v)
+    }
+
+    /// Returns an iterator over the entries with mutable references to the values, in order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut T)> {
+        self.0.iter_mut().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Returns the entries as a list of tuples sorted by key
+    #[must_use]
+    pub fn into_inner(self) -> Vec<(String, T)> {
+        self.0
+    }
+
+    /// Restores the invariant of entries being sorted by key without duplicates, keeping the last
+    /// value of duplicate keys
+    fn normalize(&mut self) {
+        // The sort is stable, so duplicates stay in insertion order
+        self.0.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.0.dedup_by(|next, prev| {
+            if next.0 == prev.0 {
+                std::mem::swap(next, prev);
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+impl<T> Deref for WitMap<T> {
+    type Target = [(String, T)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> From<Vec<(String, T)>> for WitMap<T> {
+    fn from(entries: Vec<(String, T)>) -> Self {
+        let mut map = Self(entries);
+        map.normalize();
+        map
+    }
 }
 
-pub(crate) fn deserialize_wit_map<'de, D: serde::Deserializer<'de>, T>(
-    deserializer: D,
-) -> std::result::Result<WitMap<T>, D::Error>
-where
-    T: Deserialize<'de>,
-{
-    let values = HashMap::<String, T>::deserialize(deserializer)?;
-    Ok(values.into_iter().collect())
+impl<T, S: BuildHasher> From<HashMap<String, T, S>> for WitMap<T> {
+    fn from(entries: HashMap<String, T, S>) -> Self {
+        let mut map = Self(entries.into_iter().collect());
+        // Keys of a `HashMap` are unique, so sorting suffices
+        map.0.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        map
+    }
+}
+
+impl<T> From<BTreeMap<String, T>> for WitMap<T> {
+    fn from(entries: BTreeMap<String, T>) -> Self {
+        // Entries of a `BTreeMap` are already sorted and unique
+        Self(entries.into_iter().collect())
+    }
+}
+
+impl<T> From<WitMap<T>> for Vec<(String, T)> {
+    fn from(map: WitMap<T>) -> Self {
+        map.0
+    }
+}
+
+impl<T> From<WitMap<T>> for HashMap<String, T> {
+    fn from(map: WitMap<T>) -> Self {
+        map.0.into_iter().collect()
+    }
+}
+
+impl<T> From<WitMap<T>> for BTreeMap<String, T> {
+    fn from(map: WitMap<T>) -> Self {
+        map.0.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, T> FromIterator<(K, T)> for WitMap<T> {
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(iter: I) -> Self {
+        iter.into_iter()
+            .map(|(k, v)| (k.into(), v))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl<K: Into<String>, T> Extend<(K, T)> for WitMap<T> {
+    fn extend<I: IntoIterator<Item = (K, T)>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(|(k, v)| (k.into(), v)));
+        self.normalize();
+    }
+}
+
+impl<T> IntoIterator for WitMap<T> {
+    type Item = (String, T);
+    type IntoIter = std::vec::IntoIter<(String, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a WitMap<T> {
+    type Item = &'a (String, T);
+    type IntoIter = std::slice::Iter<'a, (String, T)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<T: Serialize> Serialize for WitMap<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, val) in &self.0 {
+            map.serialize_entry(key, val)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for WitMap<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct WitMapVisitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for WitMapVisitor<T> {
+            type Value = WitMap<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(
+                self,
+                mut access: A,
+            ) -> std::result::Result<Self::Value, A::Error> {
+                let mut entries: Vec<(String, T)> =
+                    Vec::with_capacity(access.size_hint().unwrap_or_default());
+                while let Some(entry) = access.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(entries.into())
+            }
+        }
+
+        deserializer.deserialize_map(WitMapVisitor(std::marker::PhantomData))
+    }
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
@@ -263,9 +470,41 @@ pub fn parse_wit_package_name(p: impl AsRef<str>) -> Result<WitInformationTuple>
 // TODO(joonas): Remove these once doctests are run as part of CI.
 #[cfg(test)]
 mod test {
+    use std::collections::{BTreeMap, HashMap};
+
     use semver::Version;
 
-    use super::parse_wit_package_name;
+    use super::{parse_wit_package_name, WitMap};
+
+    #[test]
+    fn test_wit_map() {
+        let mut map: WitMap<u32> = [("b", 1), ("a", 2), ("b", 3)].into_iter().collect();
+        assert_eq!(
+            map.iter().cloned().collect::<Vec<_>>(),
+            vec![("a".to_string(), 2), ("b".to_string(), 3)]
+        );
+        assert_eq!(map.get("b"), Some(&3));
+        assert_eq!(map.get("c"), None);
+
+        assert_eq!(map.insert("c", 4), None);
+        assert_eq!(map.insert("a", 5), Some(2));
+        map.extend([("0", 6), ("c", 7)]);
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["0", "a", "b", "c"]);
+        assert_eq!(map.get("c"), Some(&7));
+        assert_eq!(map.remove("0"), Some(6));
+        assert!(!map.contains_key("0"));
+
+        assert_eq!(
+            WitMap::from(HashMap::from([("x".to_string(), 1), ("y".to_string(), 2)])),
+            WitMap::from(BTreeMap::from([("y".to_string(), 2), ("x".to_string(), 1)]))
+        );
+
+        let json = serde_json::to_string(&map).expect("failed to serialize map");
+        assert_eq!(json, r#"{"a":5,"b":3,"c":7}"#);
+        let map: WitMap<u32> =
+            serde_json::from_str(r#"{"z":1,"y":2}"#).expect("failed to deserialize map");
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["y", "z"]);
+    }
     #[test]
     fn test_parse_wit_package_name() {
         let (ns, packages, interfaces, func, version) =
@@ -5,6 +5,16 @@ use tokio::sync::watch::{self, Receiver};
 
 use crate::store::{DefaultStore, StoreManager};
 
+/// Prefix of the keys under which host-scoped config is stored in the config store
+pub const HOST_CONFIG_PREFIX: &str = "HOSTCONFIG";
+
+/// Returns the key under which the values of the named config scoped to the given host are
+/// stored in the config store
+#[must_use]
+pub fn host_config_key(host_id: &str, config_name: &str) -> String {
+    format!("{HOST_CONFIG_PREFIX}_{host_id}_{config_name}")
+}
+
 #[async_trait::async_trait]
 /// A trait for managing a config store which can be watched to receive updates to the config
 pub trait ConfigManager: StoreManager {
@@ -20,6 +30,24 @@ pub trait ConfigManager: StoreManager {
         };
         Ok(watch::channel(config).1)
     }
+
+    /// Watches a config by name like [`watch`](ConfigManager::watch), but starts with an empty
+    /// config rather than failing if the config does not exist (yet). This is used for optional
+    /// config, such as host-scoped overrides of named config.
+    ///
+    /// The default implementation returns a receiver that will never receive any updates.
+    async fn watch_optional(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<Receiver<HashMap<String, String>>> {
+        let config = match self.get(name).await {
+            Ok(Some(data)) => serde_json::from_slice(&data)
+                .context("Data corruption error, unable to decode data from store")?,
+            Ok(None) => HashMap::new(),
+            Err(e) => return Err(anyhow::anyhow!("Error fetching config {}: {}", name, e)),
+        };
+        Ok(watch::channel(config).1)
+    }
 }
 
 /// A default implementation of the config manager that does not watch for updates
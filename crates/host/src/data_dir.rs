@@ -0,0 +1,294 @@
+//! The data directory in which a host keeps local state that outlives a single host process, such
+//! as fetched artifacts and provider logs.
+//!
+//! The directory has a versioned layout with one subdirectory per [`DataCategory`]:
+//!
+//! ```text
+//! <root>/
+//! ├── layout.json
+//! ├── artifacts/
+//! ├── provider_logs/
+//! ├── flight_recordings/
+//! └── precompiled/
+//! ```
+//!
+//! [`DataDir::open`] creates missing directories and checks the integrity of existing ones before
+//! the host uses them, so that a misconfigured directory fails host startup instead of individual
+//! workloads later on.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Context as _};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use tracing::{debug, info, instrument, warn};
+use wasmcloud_control_interface::{DataCategory, DataCategoryUsage, DataDirUsage};
+
+/// Version of the data directory layout written by this host
+pub const LAYOUT_VERSION: u32 = 1;
+
+/// Name of the file recording the layout of the data directory
+const LAYOUT_FILE: &str = "layout.json";
+
+/// Name of the file used to check that directories are writable
+const PROBE_FILE: &str = ".probe";
+
+/// Contents of the layout file
+#[derive(Debug, Deserialize, Serialize)]
+struct Layout {
+    version: u32,
+}
+
+/// A host data directory, see the [module documentation](self)
+#[derive(Clone, Debug)]
+pub struct DataDir {
+    root: PathBuf,
+}
+
+impl DataDir {
+    /// Open the data directory at `root`, creating it with the current layout if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory uses a newer layout than this host supports, or if it or
+    /// one of its category directories is not a directory, is not writable, or is owned by
+    /// another user
+    #[instrument(level = "debug", skip_all, fields(root = %root.as_ref().display()))]
+    pub async fn open(root: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        create_dir(&root).await?;
+        let dir = Self { root };
+        dir.check_layout().await?;
+        let owner = dir.check_writable(&dir.root).await?;
+        for category in DataCategory::ALL {
+            let path = dir.path(category);
+            create_dir(&path).await?;
+            let category_owner = dir.check_writable(&path).await?;
+            ensure!(
+                category_owner == owner,
+                "{} is owned by a different user than the data directory",
+                path.display()
+            );
+        }
+        info!(root = %dir.root.display(), "opened host data directory");
+        Ok(dir)
+    }
+
+    /// Get the root of the data directory
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the directory holding the data of a category
+    #[must_use]
+    pub fn path(&self, category: DataCategory) -> PathBuf {
+        self.root.join(category.as_str())
+    }
+
+    /// Compute the disk usage of every category of data
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a category directory cannot be read
+    pub async fn usage(&self) -> anyhow::Result<DataDirUsage> {
+        let mut categories = Vec::with_capacity(DataCategory::ALL.len());
+        for category in DataCategory::ALL {
+            let (bytes, files) = dir_usage(&self.path(category)).await?;
+            categories.push(DataCategoryUsage::new(category, bytes, files));
+        }
+        Ok(DataDirUsage::new(
+            self.root.to_string_lossy(),
+            LAYOUT_VERSION,
+            categories,
+        ))
+    }
+
+    /// Remove all data of the given categories, or of all categories if none are given, returning
+    /// the number of bytes freed. Entries that cannot be removed, e.g. because they are in use,
+    /// are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a category directory cannot be read
+    #[instrument(level = "debug", skip(self))]
+    pub async fn cleanup(&self, categories: &[DataCategory]) -> anyhow::Result<u64> {
+        let categories = if categories.is_empty() {
+            &DataCategory::ALL[..]
+        } else {
+            categories
+        };
+        let mut freed = 0;
+        for category in categories {
+            let path = self.path(*category);
+            let mut entries = fs::read_dir(&path)
+                .await
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let (bytes, _) = dir_usage(&path).await.unwrap_or_default();
+                let res = if entry.file_type().await?.is_dir() {
+                    fs::remove_dir_all(&path).await
+                } else {
+                    fs::remove_file(&path).await
+                };
+                match res {
+                    Ok(()) => freed += bytes,
+                    Err(error) => warn!(path = %path.display(), ?error, "failed to remove data"),
+                }
+            }
+        }
+        info!(freed, ?categories, "cleaned up host data directory");
+        Ok(freed)
+    }
+
+    /// Check the layout version of the directory, writing the current version to new directories
+    async fn check_layout(&self) -> anyhow::Result<()> {
+        let path = self.root.join(LAYOUT_FILE);
+        match fs::read(&path).await {
+            Ok(layout) => {
+                let Layout { version } = serde_json::from_slice(&layout)
+                    .with_context(|| format!("failed to parse {}", path.display()))?;
+                if version > LAYOUT_VERSION {
+                    bail!(
+                        "data directory {} uses layout version {version}, but this host only supports version {LAYOUT_VERSION} or earlier",
+                        self.root.display()
+                    );
+                }
+                debug!(version, "found data directory layout");
+                // Version 1 is the first layout, later versions migrate older layouts here
+                Ok(())
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                let layout = serde_json::to_vec(&Layout {
+                    version: LAYOUT_VERSION,
+                })
+                .context("failed to serialize data directory layout")?;
+                fs::write(&path, layout)
+                    .await
+                    .with_context(|| format!("failed to write {}", path.display()))
+            }
+            Err(error) => Err(error).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// Check that a directory is writable by writing a probe file, returning the owner of the
+    /// probe file, i.e. the user the host runs as
+    async fn check_writable(&self, dir: &Path) -> anyhow::Result<Option<u32>> {
+        let probe = dir.join(PROBE_FILE);
+        fs::write(&probe, b"")
+            .await
+            .with_context(|| format!("{} is not writable", dir.display()))?;
+        let owner = owner(&fs::metadata(&probe).await?);
+        fs::remove_file(&probe)
+            .await
+            .with_context(|| format!("failed to remove {}", probe.display()))?;
+        let dir_owner = owner_of(dir).await?;
+        ensure!(
+            dir_owner == owner,
+            "{} is not owned by the user running the host",
+            dir.display()
+        );
+        Ok(owner)
+    }
+}
+
+/// Create a directory if it does not exist, failing if the path exists but is not a directory
+async fn create_dir(path: &Path) -> anyhow::Result<()> {
+    match fs::symlink_metadata(path).await {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        Ok(_) => bail!("{} exists, but is not a directory", path.display()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => fs::create_dir_all(path)
+            .await
+            .with_context(|| format!("failed to create {}", path.display())),
+        Err(error) => Err(error).with_context(|| format!("failed to access {}", path.display())),
+    }
+}
+
+/// Compute the total size and number of files under a path
+async fn dir_usage(path: &Path) -> anyhow::Result<(u64, u64)> {
+    let mut bytes = 0;
+    let mut files = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata = fs::symlink_metadata(&path)
+            .await
+            .with_context(|| format!("failed to access {}", path.display()))?;
+        if metadata.is_dir() {
+            let mut entries = fs::read_dir(&path)
+                .await
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            while let Some(entry) = entries.next_entry().await? {
+                pending.push(entry.path());
+            }
+        } else {
+            bytes += metadata.len();
+            files += 1;
+        }
+    }
+    Ok((bytes, files))
+}
+
+async fn owner_of(path: &Path) -> anyhow::Result<Option<u32>> {
+    let metadata = fs::metadata(path)
+        .await
+        .with_context(|| format!("failed to access {}", path.display()))?;
+    Ok(owner(&metadata))
+}
+
+#[cfg(unix)]
+fn owner(metadata: &std::fs::Metadata) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt as _;
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn owner(_: &std::fs::Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DataDir, LAYOUT_FILE, LAYOUT_VERSION};
+
+    use tokio::fs;
+    use wasmcloud_control_interface::DataCategory;
+
+    #[tokio::test]
+    async fn data_dir_layout_usage_and_cleanup() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let root = tmp.path().join("data");
+        let dir = DataDir::open(&root).await?;
+        for category in DataCategory::ALL {
+            assert!(dir.path(category).is_dir());
+        }
+
+        fs::write(dir.path(DataCategory::Artifacts).join("a.wasm"), [0; 100]).await?;
+        fs::create_dir(dir.path(DataCategory::ProviderLogs).join("http")).await?;
+        fs::write(
+            dir.path(DataCategory::ProviderLogs)
+                .join("http")
+                .join("out.log"),
+            [0; 10],
+        )
+        .await?;
+        let usage = dir.usage().await?;
+        assert_eq!(usage.layout_version(), LAYOUT_VERSION);
+        assert_eq!(usage.total_bytes(), 110);
+
+        assert_eq!(dir.cleanup(&[DataCategory::ProviderLogs]).await?, 10);
+        assert_eq!(dir.usage().await?.total_bytes(), 100);
+        assert_eq!(dir.cleanup(&[]).await?, 100);
+        assert_eq!(dir.usage().await?.total_bytes(), 0);
+
+        // Reopening keeps the layout, newer layouts are rejected
+        DataDir::open(&root).await?;
+        fs::write(root.join(LAYOUT_FILE), r#"{"version":999}"#).await?;
+        assert!(DataDir::open(&root).await.is_err());
+
+        let file = tmp.path().join("file");
+        fs::write(&file, b"").await?;
+        assert!(DataDir::open(&file).await.is_err());
+        Ok(())
+    }
+}
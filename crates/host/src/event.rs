@@ -1,8 +1,15 @@
+use core::fmt::{self, Display};
+use core::str::FromStr;
+use core::time::Duration;
+
 use std::collections::{BTreeMap, HashMap};
 
+use anyhow::bail;
 use serde_json::json;
 use wascap::jwt;
-use wasmcloud_control_interface::Link;
+use wasmcloud_control_interface::{HostDecommission, Link};
+
+use crate::nats::health::NatsHealthIssue;
 
 /// A trait for publishing wasmbus events. This can be implemented by any transport or bus
 /// implementation that can send the serialized event to the appropriate destination.
@@ -29,6 +36,88 @@ pub trait EventPublisher: Send + Sync {
 pub struct DefaultEventPublisher {}
 impl EventPublisher for DefaultEventPublisher {}
 
+/// Version of the schema of event payloads.
+///
+/// When the payload format changes, hosts can publish every event in both the old and the new
+/// schema, each on its own subject, for a transition window. This allows consumers such as wadm
+/// to migrate to the new schema without all hosts and consumers being upgraded at once.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EventSchemaVersion {
+    /// The original schema, published on `wasmbus.evt.{lattice}.{event}`. Payloads contain
+    /// fields kept for compatibility with the OTP host, such as `public_key` and `link_name`.
+    #[default]
+    V1,
+    /// The schema without the fields kept for compatibility with the OTP host, published on
+    /// `wasmbus.evt.v2.{lattice}.{event}`. The `public_key` of the claims is moved to
+    /// `claims.subject`.
+    V2,
+}
+
+/// Top-level payload fields that are only published with [`EventSchemaVersion::V1`]
+const V1_ONLY_FIELDS: [&str; 3] = ["public_key", "instance_id", "link_name"];
+
+impl EventSchemaVersion {
+    /// Get the string representation of this version, e.g. `v1`
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "v1",
+            Self::V2 => "v2",
+        }
+    }
+
+    /// Get the subject events named `event_name` are published on in this schema version
+    #[must_use]
+    pub fn subject(self, lattice: &str, event_name: &str) -> String {
+        match self {
+            Self::V1 => format!("wasmbus.evt.{lattice}.{event_name}"),
+            Self::V2 => format!("wasmbus.evt.v2.{lattice}.{event_name}"),
+        }
+    }
+
+    /// Convert an event payload, which is always produced in the [`EventSchemaVersion::V1`]
+    /// schema, to this schema version
+    #[must_use]
+    pub fn convert(self, data: serde_json::Value) -> serde_json::Value {
+        match (self, data) {
+            (Self::V2, serde_json::Value::Object(mut data)) => {
+                let public_key = data.remove("public_key");
+                for field in V1_ONLY_FIELDS {
+                    data.remove(field);
+                }
+                if let Some(serde_json::Value::Object(claims)) = data.get_mut("claims") {
+                    if claims.get("tags").is_some_and(serde_json::Value::is_null) {
+                        claims.remove("tags");
+                    }
+                    if let Some(public_key) = public_key {
+                        claims.entry("subject").or_insert(public_key);
+                    }
+                }
+                serde_json::Value::Object(data)
+            }
+            (_, data) => data,
+        }
+    }
+}
+
+impl Display for EventSchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventSchemaVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &*s.trim().to_ascii_lowercase() {
+            "v1" | "1" => Ok(Self::V1),
+            "v2" | "2" => Ok(Self::V2),
+            _ => bail!("unknown event schema version `{s}`, expected `v1` or `v2`"),
+        }
+    }
+}
+
 fn format_component_claims(claims: &jwt::Claims<jwt::Component>) -> serde_json::Value {
     let issuer = &claims.issuer;
     let not_before_human = claims
@@ -337,6 +426,56 @@ pub fn provider_stopped(
     })
 }
 
+/// Generates an event payload for when the named configs consumed by a running provider changed
+///
+/// # Arguments
+/// * `host_id` - ID of the host where the provider is running
+/// * `provider_id` - Unique identifier for the provider
+/// * `config` - New list of named configs of the provider
+///
+/// # Returns
+/// JSON object containing provider config update details
+pub fn provider_config_updated(
+    host_id: impl AsRef<str>,
+    provider_id: impl AsRef<str>,
+    config: &[String],
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "provider_id": provider_id.as_ref(),
+        "config": config,
+    })
+}
+
+/// Generates an event payload for when a component has finished stopping, after in-flight
+/// invocations were either drained or cancelled
+///
+/// # Arguments
+/// * `annotations` - Key-value pairs of metadata annotations
+/// * `host_id` - ID of the host where the component stopped
+/// * `image_ref` - Reference to the component image
+/// * `component_id` - Unique identifier for the component
+/// * `reason` - Reason for stopping the component, `stop` if in-flight invocations completed
+///   within the termination grace period and `forced_stop` otherwise
+///
+/// # Returns
+/// JSON object containing component stop details
+pub fn component_stopped(
+    annotations: &BTreeMap<String, String>,
+    host_id: impl AsRef<str>,
+    image_ref: impl AsRef<str>,
+    component_id: impl AsRef<str>,
+    reason: impl AsRef<str>,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "image_ref": image_ref.as_ref(),
+        "component_id": component_id.as_ref(),
+        "annotations": annotations,
+        "reason": reason.as_ref(),
+    })
+}
+
 /// Generates an event payload for provider health checks
 ///
 /// # Arguments
@@ -381,6 +520,42 @@ pub fn config_deleted(config_name: impl AsRef<str>) -> serde_json::Value {
     })
 }
 
+/// Generates an event payload for when a host-scoped config is set
+///
+/// # Arguments
+/// * `host_id` - ID of the host the configuration is scoped to
+/// * `config_name` - Name of the configuration being set
+///
+/// # Returns
+/// JSON object containing host-scoped config set details
+pub fn host_config_set(
+    host_id: impl AsRef<str>,
+    config_name: impl AsRef<str>,
+) -> serde_json::Value {
+    json!({
+        "config_name": config_name.as_ref(),
+        "host_id": host_id.as_ref(),
+    })
+}
+
+/// Generates an event payload for when a host-scoped config is deleted
+///
+/// # Arguments
+/// * `host_id` - ID of the host the configuration is scoped to
+/// * `config_name` - Name of the configuration being deleted
+///
+/// # Returns
+/// JSON object containing host-scoped config deletion details
+pub fn host_config_deleted(
+    host_id: impl AsRef<str>,
+    config_name: impl AsRef<str>,
+) -> serde_json::Value {
+    json!({
+        "config_name": config_name.as_ref(),
+        "host_id": host_id.as_ref(),
+    })
+}
+
 /// Generates an event payload for when host labels are changed
 ///
 /// # Arguments
@@ -398,3 +573,178 @@ pub fn labels_changed(
         "labels": labels.into(),
     })
 }
+
+/// Generates an event payload for when a host is decommissioned, i.e. stopped permanently
+///
+/// # Arguments
+/// * `record` - The decommission record written by the host
+///
+/// # Returns
+/// JSON object containing the decommission record
+pub fn host_decommissioned(record: &HostDecommission) -> serde_json::Value {
+    json!({
+        "host_id": record.host_id(),
+        "friendly_name": record.friendly_name(),
+        "labels": record.labels(),
+        "version": record.version(),
+        "decommissioned_at": record.decommissioned_at(),
+        "removed_data": record.removed_data(),
+    })
+}
+
+/// Generates an event payload for when a host starts draining
+///
+/// # Arguments
+/// * `host_id` - ID of the host being drained
+/// * `timeout_ms` - Amount of time the host waits for workloads to be rescheduled, if bounded
+/// * `force_stop` - Whether remaining workloads are stopped once the timeout elapses
+///
+/// # Returns
+/// JSON object containing host drain details
+pub fn host_draining(
+    host_id: impl AsRef<str>,
+    timeout_ms: Option<u64>,
+    force_stop: bool,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "timeout_ms": timeout_ms,
+        "force_stop": force_stop,
+    })
+}
+
+/// Generates an event payload for when a host finished draining
+///
+/// # Arguments
+/// * `host_id` - ID of the drained host
+/// * `stopped_components` - IDs of components force-stopped by the drain
+/// * `stopped_providers` - IDs of providers force-stopped by the drain
+/// * `remaining` - Number of workloads left running on the host after the drain
+///
+/// # Returns
+/// JSON object containing host drain results
+pub fn host_drained(
+    host_id: impl AsRef<str>,
+    stopped_components: &[String],
+    stopped_providers: &[String],
+    remaining: usize,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "stopped_components": stopped_components,
+        "stopped_providers": stopped_providers,
+        "remaining": remaining,
+    })
+}
+
+/// Generates an event payload for when the health of a host NATS connection changes
+///
+/// # Arguments
+/// * `host_id` - ID of the host observing the issue
+/// * `issue` - The threshold that was crossed, or is no longer crossed
+///
+/// # Returns
+/// JSON object containing NATS health details
+pub fn nats_health(host_id: impl AsRef<str>, issue: &NatsHealthIssue) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "kind": issue.kind,
+        "subject": issue.subject,
+        "value": issue.value,
+        "threshold": issue.threshold,
+    })
+}
+
+/// Generates an event payload for when the host watchdog escalates the recovery of a stalled
+/// subsystem
+///
+/// # Arguments
+/// * `host_id` - ID of the host
+/// * `subsystem` - Name of the stalled subsystem, e.g. `data_watch`
+/// * `action` - Recovery action taken, e.g. `resubscribe` or `exit`
+/// * `stalled_for` - How long the subsystem has not made progress
+/// * `diagnostics` - State of all subsystems monitored by the watchdog
+///
+/// # Returns
+/// JSON object containing watchdog escalation details
+pub fn host_watchdog_escalated(
+    host_id: impl AsRef<str>,
+    subsystem: &str,
+    action: &str,
+    stalled_for: Duration,
+    diagnostics: serde_json::Value,
+) -> serde_json::Value {
+    json!({
+        "host_id": host_id.as_ref(),
+        "subsystem": subsystem,
+        "action": action,
+        "stalled_for_ms": u64::try_from(stalled_for.as_millis()).unwrap_or(u64::MAX),
+        "diagnostics": diagnostics,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde_json::json;
+    use wasmcloud_control_interface::{DataCategory, HostDecommission};
+
+    use super::{host_decommissioned, EventSchemaVersion};
+
+    #[test]
+    fn event_schema_versions() {
+        let data = json!({
+            "host_id": "host",
+            "provider_id": "provider",
+            "public_key": "provider",
+            "instance_id": "provider",
+            "link_name": "default",
+            "claims": { "issuer": "issuer", "tags": null },
+        });
+        assert_eq!(EventSchemaVersion::V1.convert(data.clone()), data);
+        assert_eq!(
+            EventSchemaVersion::V2.convert(data),
+            json!({
+                "host_id": "host",
+                "provider_id": "provider",
+                "claims": { "issuer": "issuer", "subject": "provider" },
+            })
+        );
+        assert_eq!(
+            EventSchemaVersion::V2.convert(json!({ "config_name": "foo" })),
+            json!({ "config_name": "foo" })
+        );
+
+        assert_eq!(
+            EventSchemaVersion::V1.subject("default", "host_started"),
+            "wasmbus.evt.default.host_started"
+        );
+        assert_eq!(
+            EventSchemaVersion::V2.subject("default", "host_started"),
+            "wasmbus.evt.v2.default.host_started"
+        );
+        assert_eq!(
+            "V2".parse::<EventSchemaVersion>()
+                .expect("failed to parse version"),
+            EventSchemaVersion::V2
+        );
+        assert!("v3".parse::<EventSchemaVersion>().is_err());
+    }
+
+    #[test]
+    fn host_decommissioned_event_is_a_record() {
+        let record = HostDecommission::new(
+            "host",
+            "friendly-host",
+            BTreeMap::from([("zone".into(), "us-east-1".into())]),
+            "1.9.0",
+            1_700_000_000_000,
+        )
+        .with_removed_data(vec![DataCategory::FlightRecordings]);
+        let parsed: HostDecommission = serde_json::from_value(host_decommissioned(&record))
+            .expect("failed to parse event data as a record");
+        assert_eq!(parsed, record);
+        assert_eq!(record.key(), "HOST_DECOMMISSION_host");
+    }
+}
@@ -14,6 +14,9 @@ pub mod event;
 /// [crate::store::StoreManager] traits for the wasmCloud host.
 pub mod nats;
 
+/// [crate::data_dir::DataDir] managing the local state a host keeps on disk
+pub mod data_dir;
+
 /// Implementation of OpenTelemetry metrics for wasmCloud, primarily using [wasmcloud_tracing]
 pub mod metrics;
 
@@ -137,6 +140,41 @@ impl ResourceRef<'_> {
     }
 }
 
+/// Build the [`OciFetcher`] for an OCI reference, using the registry configuration of its
+/// authority if there is one and anonymous access otherwise
+fn oci_fetcher(
+    oci_ref: &ResourceRef<'_>,
+    default_config: &oci::Config,
+    registry_config: &HashMap<String, RegistryConfig>,
+) -> OciFetcher {
+    oci_ref
+        .authority()
+        .and_then(|authority| registry_config.get(authority))
+        .map(OciFetcher::from)
+        .unwrap_or_else(|| {
+            OciFetcher::from(
+                RegistryConfig::builder()
+                    .reg_type(RegistryType::Oci)
+                    .additional_ca_paths(default_config.additional_ca_paths.clone())
+                    .allow_latest(default_config.allow_latest)
+                    .allow_insecure(
+                        oci_ref
+                            .authority()
+                            .map(|authority| {
+                                default_config
+                                    .allowed_insecure
+                                    .contains(&authority.to_string())
+                            })
+                            .unwrap_or(false),
+                    )
+                    .auth(RegistryAuth::Anonymous)
+                    .build()
+                    .unwrap_or_default(),
+            )
+        })
+        .with_additional_ca_paths(&default_config.additional_ca_paths)
+}
+
 /// Fetch an component from a reference.
 #[instrument(level = "debug", skip(default_config, registry_config))]
 pub async fn fetch_component(
@@ -155,37 +193,14 @@ pub async fn fetch_component(
                 .await
                 .context("failed to read component")
         }
-        ref oci_ref @ ResourceRef::Oci(component_ref) => oci_ref
-            .authority()
-            .and_then(|authority| registry_config.get(authority))
-            .map(OciFetcher::from)
-            .unwrap_or_else(|| {
-                OciFetcher::from(
-                    RegistryConfig::builder()
-                        .reg_type(RegistryType::Oci)
-                        .additional_ca_paths(default_config.additional_ca_paths.clone())
-                        .allow_latest(default_config.allow_latest)
-                        .allow_insecure(
-                            oci_ref
-                                .authority()
-                                .map(|authority| {
-                                    default_config
-                                        .allowed_insecure
-                                        .contains(&authority.to_string())
-                                })
-                                .unwrap_or(false),
-                        )
-                        .auth(RegistryAuth::Anonymous)
-                        .build()
-                        .unwrap_or_default(),
-                )
-            })
-            .with_additional_ca_paths(&default_config.additional_ca_paths)
-            .fetch_component(component_ref)
-            .await
-            .with_context(|| {
-                format!("failed to fetch component under OCI reference `{component_ref}`")
-            }),
+        ref oci_ref @ ResourceRef::Oci(component_ref) => {
+            oci_fetcher(oci_ref, default_config, registry_config)
+                .fetch_component(component_ref)
+                .await
+                .with_context(|| {
+                    format!("failed to fetch component under OCI reference `{component_ref}`")
+                })
+        }
         ResourceRef::Builtin(..) => bail!("nothing to fetch for a builtin"),
     }
 }
@@ -214,41 +229,41 @@ pub(crate) async fn fetch_provider(
             .await
             .context("failed to read provider")
         }
-        oci_ref @ ResourceRef::Oci(provider_ref) => oci_ref
-            .authority()
-            .and_then(|authority| registry_config.get(authority))
-            .map(OciFetcher::from)
-            .unwrap_or_else(|| {
-                OciFetcher::from(
-                    RegistryConfig::builder()
-                        .reg_type(RegistryType::Oci)
-                        .additional_ca_paths(default_config.additional_ca_paths.clone())
-                        .allow_latest(default_config.allow_latest)
-                        .allow_insecure(
-                            oci_ref
-                                .authority()
-                                .map(|authority| {
-                                    default_config
-                                        .allowed_insecure
-                                        .contains(&authority.to_string())
-                                })
-                                .unwrap_or(false),
-                        )
-                        .auth(RegistryAuth::Anonymous)
-                        .build()
-                        .unwrap_or_default(),
-                )
-            })
-            .with_additional_ca_paths(&default_config.additional_ca_paths)
-            .fetch_provider(provider_ref, host_id)
-            .await
-            .with_context(|| {
-                format!("failed to fetch provider under OCI reference `{provider_ref}`")
-            }),
+        oci_ref @ ResourceRef::Oci(provider_ref) => {
+            oci_fetcher(oci_ref, default_config, registry_config)
+                .fetch_provider(provider_ref, host_id)
+                .await
+                .with_context(|| {
+                    format!("failed to fetch provider under OCI reference `{provider_ref}`")
+                })
+        }
         ResourceRef::Builtin(..) => bail!("nothing to fetch for a builtin"),
     }
 }
 
+/// Prefetch a component or provider from an OCI reference into the artifact cache.
+#[instrument(level = "debug", skip(default_config, registry_config))]
+pub(crate) async fn prefetch_artifact(
+    image_ref: &str,
+    default_config: &oci::Config,
+    registry_config: &HashMap<String, RegistryConfig>,
+) -> anyhow::Result<()> {
+    match ResourceRef::try_from(image_ref)? {
+        ref oci_ref @ ResourceRef::Oci(image_ref) => {
+            oci_fetcher(oci_ref, default_config, registry_config)
+                .prefetch(image_ref)
+                .await
+                .with_context(|| {
+                    format!("failed to prefetch artifact under OCI reference `{image_ref}`")
+                })?;
+            Ok(())
+        }
+        ResourceRef::File(..) | ResourceRef::Builtin(..) => {
+            bail!("only OCI references can be prefetched")
+        }
+    }
+}
+
 #[test]
 fn parse_references() -> anyhow::Result<()> {
     // file:// URL
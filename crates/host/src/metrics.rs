@@ -1,12 +1,18 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::Duration;
 
 use sysinfo::System;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
+use wasmcloud_core::metrics::{InvocationDirection, InvocationMetricAttributes, WorkloadKind};
+use wasmcloud_runtime::scheduling::CpuTime;
 use wasmcloud_tracing::{
-    Counter, Gauge, Histogram, KeyValue, Meter, ObservableGauge, UpDownCounter,
+    Counter, Gauge, Histogram, InvocationMetrics, KeyValue, Meter, ObservableCounter,
+    ObservableGauge, UpDownCounter,
 };
 
+use crate::nats::health::NatsHealthSnapshot;
+
 const DEFAULT_REFRESH_TIME: Duration = Duration::from_secs(5);
 
 /// `HostMetrics` encapsulates the set of metrics emitted by the wasmcloud host
@@ -23,6 +29,12 @@ pub struct HostMetrics {
     pub component_active_instances: UpDownCounter<i64>,
     /// The maximum number of instances of a component.
     pub component_max_instances: Gauge<u64>,
+    /// The time spent executing guest code of a component in seconds.
+    pub component_cpu_time: Counter<f64>,
+    /// The linear memory held by the live instances of a component in bytes.
+    pub component_resident_memory_bytes: Gauge<u64>,
+    /// The standard invocation metrics, which are shared with capability providers.
+    pub invocations: InvocationMetrics,
 
     /// The total amount of available system memory in bytes.
     pub system_total_memory_bytes: ObservableGauge<u64>,
@@ -31,6 +43,17 @@ pub struct HostMetrics {
     /// The total cpu usage.
     pub system_cpu_usage: ObservableGauge<f64>,
 
+    /// Whether each NATS connection of the host is connected.
+    pub nats_connected: ObservableGauge<u64>,
+    /// The number of times each NATS connection of the host reconnected.
+    pub nats_reconnects: ObservableCounter<u64>,
+    /// The number of slow consumer notifications received by the host NATS clients.
+    pub nats_slow_consumers: ObservableCounter<u64>,
+    /// The bytes of control interface requests received but not yet answered.
+    pub nats_pending_bytes: ObservableGauge<u64>,
+    /// The number of entries left to process by each KV bucket watch.
+    pub nats_kv_watch_lag: ObservableGauge<u64>,
+
     /// The host's ID.
     // TODO this is actually configured as an InstrumentationScope attribute on the global meter,
     // but we don't really have a way of getting at those. We should figure out a way to get at that
@@ -41,6 +64,9 @@ pub struct HostMetrics {
     // removed or metrics will need to be scoped per-lattice.
     pub lattice_id: String,
 
+    // Latest NATS health sample, observed by the NATS gauges.
+    nats_health: Arc<watch::Sender<NatsHealthSnapshot>>,
+
     // Task handle for dropping when the metrics are no longer needed.
     _refresh_task_handle: Arc<RefreshWrapper>,
 }
@@ -104,6 +130,18 @@ impl HostMetrics {
             .with_description("Maximum number of component instances")
             .build();
 
+        let component_cpu_time = meter
+            .f64_counter("wasmcloud_host.component.cpu_time")
+            .with_description("Time spent executing component code")
+            .with_unit("seconds")
+            .build();
+
+        let component_resident_memory_bytes = meter
+            .u64_gauge("wasmcloud_host.component.memory.resident.bytes")
+            .with_description("Linear memory held by live component instances")
+            .with_unit("bytes")
+            .build();
+
         let mut system = System::new();
         // Get the initial metrics
         system.refresh_memory();
@@ -171,21 +209,99 @@ impl HostMetrics {
             })
             .build();
 
+        let (nats_health, nats_health_rx) = watch::channel(NatsHealthSnapshot::default());
+
+        let nats_connected = meter
+            .u64_observable_gauge("wasmcloud_host.nats.connected")
+            .with_description("Whether the NATS connection is connected")
+            .with_callback({
+                let rx = nats_health_rx.clone();
+                move |observer| {
+                    for (name, conn) in &rx.borrow().connections {
+                        observer.observe(
+                            u64::from(conn.connected),
+                            &[KeyValue::new("connection", *name)],
+                        );
+                    }
+                }
+            })
+            .build();
+
+        let nats_reconnects = meter
+            .u64_observable_counter("wasmcloud_host.nats.reconnects")
+            .with_description("Number of times the NATS connection reconnected")
+            .with_callback({
+                let rx = nats_health_rx.clone();
+                move |observer| {
+                    for (name, conn) in &rx.borrow().connections {
+                        observer.observe(conn.reconnects, &[KeyValue::new("connection", *name)]);
+                    }
+                }
+            })
+            .build();
+
+        let nats_slow_consumers = meter
+            .u64_observable_counter("wasmcloud_host.nats.slow_consumers")
+            .with_description("Number of slow consumer notifications of NATS subscriptions")
+            .with_callback({
+                let rx = nats_health_rx.clone();
+                move |observer| {
+                    observer.observe(rx.borrow().slow_consumers, &[]);
+                }
+            })
+            .build();
+
+        let nats_pending_bytes = meter
+            .u64_observable_gauge("wasmcloud_host.nats.pending.bytes")
+            .with_description("Bytes of control interface requests not yet answered")
+            .with_unit("bytes")
+            .with_callback({
+                let rx = nats_health_rx.clone();
+                move |observer| {
+                    observer.observe(rx.borrow().pending_bytes, &[]);
+                }
+            })
+            .build();
+
+        let nats_kv_watch_lag = meter
+            .u64_observable_gauge("wasmcloud_host.nats.kv_watch.lag")
+            .with_description("Number of entries left to process by the KV bucket watch")
+            .with_callback(move |observer| {
+                for (bucket, lag) in &nats_health_rx.borrow().kv_watch_lag {
+                    observer.observe(*lag, &[KeyValue::new("bucket", bucket.clone())]);
+                }
+            })
+            .build();
+
         Ok(Self {
             handle_rpc_message_duration_ns: wasmcloud_host_handle_rpc_message_duration_ns,
             component_invocations: component_invocation_count,
             component_errors: component_error_count,
             component_active_instances,
             component_max_instances,
+            component_cpu_time,
+            component_resident_memory_bytes,
+            invocations: InvocationMetrics::new(meter),
             system_total_memory_bytes: system_memory_total_bytes,
             system_used_memory_bytes: system_memory_used_bytes,
             system_cpu_usage,
+            nats_connected,
+            nats_reconnects,
+            nats_slow_consumers,
+            nats_pending_bytes,
+            nats_kv_watch_lag,
             host_id,
             lattice_id,
+            nats_health: Arc::new(nats_health),
             _refresh_task_handle: Arc::new(RefreshWrapper(refresh_task_handle)),
         })
     }
 
+    /// Record the latest health sample of the host NATS connections.
+    pub(crate) fn record_nats_health(&self, snapshot: NatsHealthSnapshot) {
+        self.nats_health.send_replace(snapshot);
+    }
+
     /// Increment the number of active instances of a component.
     pub(crate) fn increment_active_instance(&self, attributes: &[KeyValue]) {
         self.component_active_instances.add(1, attributes);
@@ -201,11 +317,40 @@ impl HostMetrics {
         self.component_max_instances.record(max, attributes);
     }
 
+    /// Set the linear memory held by the live instances of a component.
+    pub(crate) fn set_component_resident_memory(&self, bytes: u64, attributes: &[KeyValue]) {
+        self.component_resident_memory_bytes
+            .record(bytes, attributes);
+    }
+
+    /// Record the time a component spent executing guest code since it was last recorded.
+    ///
+    /// `reported` holds the total time recorded so far, which is shared by all invocations of the
+    /// component.
+    pub(crate) fn record_component_cpu_time(
+        &self,
+        cpu_time: &CpuTime,
+        reported: &Mutex<Duration>,
+        attributes: &[KeyValue],
+    ) {
+        let total = cpu_time.get();
+        let mut reported = reported.lock().unwrap_or_else(PoisonError::into_inner);
+        let delta = total.saturating_sub(*reported);
+        *reported = total;
+        drop(reported);
+        if !delta.is_zero() {
+            self.component_cpu_time.add(delta.as_secs_f64(), attributes);
+        }
+    }
+
     /// Record the result of invoking a component, including the elapsed time, any attributes, and whether the invocation resulted in an error.
+    ///
+    /// `invocation_attributes` are the attributes of the standard invocation metrics.
     pub(crate) fn record_component_invocation(
         &self,
         elapsed: u64,
         attributes: &[KeyValue],
+        invocation_attributes: &[KeyValue],
         error: bool,
     ) {
         self.handle_rpc_message_duration_ns
@@ -214,5 +359,53 @@ impl HostMetrics {
         if error {
             self.component_errors.add(1, attributes);
         }
+        self.invocations.record_incoming(
+            invocation_attributes,
+            Duration::from_nanos(elapsed),
+            error,
+        );
     }
+
+    /// Returns the attributes of the standard invocation metrics of an invocation of `instance` and
+    /// `func` served or sent by a component.
+    pub(crate) fn component_invocation_attributes(
+        &self,
+        component_id: &str,
+        direction: InvocationDirection,
+        instance: &str,
+        func: &str,
+        peer_id: Option<&str>,
+    ) -> Vec<KeyValue> {
+        InvocationMetrics::attributes(&InvocationMetricAttributes {
+            lattice: &self.lattice_id,
+            host_id: &self.host_id,
+            workload_kind: WorkloadKind::Component,
+            workload_id: component_id,
+            direction,
+            instance,
+            func,
+            peer_id,
+        })
+    }
+}
+
+/// Returns the attributes of the standard invocation metrics of an invocation of `instance` and
+/// `func` served by a component, e.g. on behalf of a builtin provider
+pub(crate) fn served_invocation_attributes(
+    lattice_id: &str,
+    host_id: &str,
+    component_id: &str,
+    instance: &str,
+    func: &str,
+) -> Vec<KeyValue> {
+    InvocationMetrics::attributes(&InvocationMetricAttributes {
+        lattice: lattice_id,
+        host_id,
+        workload_kind: WorkloadKind::Component,
+        workload_id: component_id,
+        direction: InvocationDirection::Incoming,
+        instance,
+        func,
+        peer_id: None,
+    })
 }
@@ -13,10 +13,10 @@ use serde::Deserialize;
 use serde_json::json;
 use tracing::{debug, error, instrument};
 use wasmcloud_control_interface::RegistryCredential;
-use wasmcloud_core::RegistryConfig;
+use wasmcloud_core::{config_ref::config_blob_bucket, migration::Migrator, RegistryConfig};
 
 use crate::{
-    event::EventPublisher,
+    event::{EventPublisher, EventSchemaVersion},
     nats::{event::NatsEventPublisher, policy::NatsPolicyManager, secrets::NatsSecretsManager},
     oci,
     registry::{merge_registry_config, RegistryCredentialExt as _, SupplementalConfig},
@@ -28,7 +28,10 @@ use crate::{
 
 const DEFAULT_CTL_TOPIC_PREFIX: &str = "wasmbus.ctl";
 
-use super::{create_bucket, ctl::NatsControlInterfaceServer};
+use super::{
+    create_bucket, create_object_store, ctl::NatsControlInterfaceServer, health::NatsHealth,
+    migrate_bucket, rate_limit::CtlRateLimits, store::BlobResolvingConfigStore,
+};
 
 /// Opinionated [crate::wasmbus::HostBuilder] that uses NATS as the primary transport and implementations
 /// for the [crate::wasmbus::Host] extension traits.
@@ -52,7 +55,10 @@ pub struct NatsHostBuilder {
     data_store: Store,
     policy_manager: Option<Arc<dyn PolicyManager>>,
     secrets_manager: Option<Arc<dyn SecretsManager>>,
-    event_publisher: Option<Arc<dyn EventPublisher>>,
+    event_source: Option<String>,
+    event_schema_versions: Vec<EventSchemaVersion>,
+    ctl_rate_limits: Option<CtlRateLimits>,
+    nats_health: Arc<NatsHealth>,
 }
 
 impl NatsHostBuilder {
@@ -79,6 +85,13 @@ impl NatsHostBuilder {
 
         let config_bucket = format!("CONFIGDATA_{lattice}");
         let config_data = create_bucket(&ctl_jetstream, &config_bucket).await?;
+        let config_blobs =
+            create_object_store(&ctl_jetstream, &config_blob_bucket(&lattice)).await?;
+
+        // No layout changes have been made to either bucket yet, new steps are added here
+        let migration_holder = format!("nats-client-{}", ctl_nats.server_info().client_id);
+        migrate_bucket(&data_store, &Migrator::new(&migration_holder)).await?;
+        migrate_bucket(&config_data, &Migrator::new(&migration_holder)).await?;
 
         let supplemental_config = if config_service_enabled {
             load_supplemental_config(&ctl_nats, &lattice, &labels).await?
@@ -92,7 +105,10 @@ impl NatsHostBuilder {
             merge_registry_config(&mut registry_config, oci_opts).await;
         }
 
-        let config_generator = BundleGenerator::new(Arc::new(config_data.clone()));
+        let config_generator = BundleGenerator::new(Arc::new(BlobResolvingConfigStore::new(
+            config_data.clone(),
+            config_blobs,
+        )));
 
         Ok(Self {
             ctl_nats,
@@ -105,7 +121,10 @@ impl NatsHostBuilder {
             data_store,
             policy_manager: None,
             secrets_manager: None,
-            event_publisher: None,
+            event_source: None,
+            event_schema_versions: Vec::new(),
+            ctl_rate_limits: None,
+            nats_health: Arc::default(),
             enable_component_auction,
             enable_provider_auction,
         })
@@ -163,11 +182,39 @@ impl NatsHostBuilder {
     /// recommended to use the host's public key as the source, as this will allow tracing
     /// events back to the host that published them.
     pub fn with_event_publisher(self, source: String) -> Self {
-        let event_publisher =
-            NatsEventPublisher::new(source, self.lattice.clone(), self.ctl_nats.clone());
+        NatsHostBuilder {
+            event_source: Some(source),
+            ..self
+        }
+    }
+
+    /// Set the schema versions events are published in, see [`EventSchemaVersion`]. Events are
+    /// published in [`EventSchemaVersion::V1`] by default.
+    pub fn with_event_schema_versions(
+        self,
+        event_schema_versions: impl IntoIterator<Item = EventSchemaVersion>,
+    ) -> Self {
+        NatsHostBuilder {
+            event_schema_versions: event_schema_versions.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Limit the rate of control interface requests accepted from a single sender, see
+    /// [`CtlRateLimits`]. Requests are not rate limited by default.
+    pub fn with_ctl_rate_limits(self, ctl_rate_limits: Option<CtlRateLimits>) -> Self {
+        NatsHostBuilder {
+            ctl_rate_limits,
+            ..self
+        }
+    }
 
+    /// Record the health of the host NATS connections in `nats_health`. The control connection
+    /// passed to [`NatsHostBuilder::new`] should be created with the same [`NatsHealth`], see
+    /// [`connect_nats_with_health`](super::connect_nats_with_health).
+    pub fn with_nats_health(self, nats_health: Arc<NatsHealth>) -> Self {
         NatsHostBuilder {
-            event_publisher: Some(Arc::new(event_publisher)),
+            nats_health,
             ..self
         }
     }
@@ -177,22 +224,31 @@ impl NatsHostBuilder {
         self,
         config: WasmbusHostConfig,
     ) -> anyhow::Result<(HostBuilder, NatsControlInterfaceServer)> {
+        let event_publisher = self.event_source.map(|source| {
+            Arc::new(
+                NatsEventPublisher::new(source, self.lattice.clone(), self.ctl_nats.clone())
+                    .with_schema_versions(self.event_schema_versions),
+            ) as Arc<dyn EventPublisher>
+        });
         Ok((
             HostBuilder::from(config)
                 .with_registry_config(self.registry_config)
-                .with_event_publisher(self.event_publisher)
+                .with_event_publisher(event_publisher)
                 .with_policy_manager(self.policy_manager)
                 .with_secrets_manager(self.secrets_manager)
                 .with_bundle_generator(Some(self.config_generator))
                 .with_config_store(Some(self.config_store))
-                .with_data_store(Some(Arc::new(self.data_store.clone()))),
+                .with_data_store(Some(Arc::new(self.data_store.clone())))
+                .with_nats_health(Some(Arc::clone(&self.nats_health))),
             NatsControlInterfaceServer::new(
                 self.ctl_nats,
                 self.data_store,
                 self.ctl_topic_prefix,
                 self.enable_component_auction,
                 self.enable_provider_auction,
-            ),
+            )
+            .with_rate_limits(self.ctl_rate_limits)
+            .with_nats_health(self.nats_health),
         ))
     }
 }
@@ -5,19 +5,24 @@ use async_nats::jetstream::kv::Store;
 use bytes::Bytes;
 use futures::future::Either;
 use futures::stream::SelectAll;
-use futures::{Stream, StreamExt, TryFutureExt as _};
+use futures::{FutureExt as _, Stream, StreamExt};
 use serde::Serialize;
+use std::panic::AssertUnwindSafe;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::task::JoinSet;
 use tracing::{error, instrument, trace, warn};
-use wasmcloud_control_interface::CtlResponse;
-use wasmcloud_core::CTL_API_VERSION_1;
+use wasmcloud_control_interface::{
+    chunking, epoch_from_headers, CtlResponse, Encoding, COMMAND_ID_HEADER,
+};
+use wasmcloud_core::{CTL_API_VERSION_1, CTL_API_VERSION_2};
 use wasmcloud_tracing::context::TraceContextInjector;
 
 use crate::wasmbus::injector_to_headers;
 
+use super::health::{self, NatsHealth, NatsHealthThresholds, DEFAULT_NATS_HEALTH_INTERVAL};
+use super::rate_limit::{self, CtlRateLimiter, CtlRateLimits};
 use super::store::data_watch;
 
 #[derive(Debug)]
@@ -43,21 +48,38 @@ impl Queue {
         component_auction: bool,
         provider_auction: bool,
     ) -> anyhow::Result<Self> {
-        let mut subs = vec![
-            Either::Left(nats.subscribe(format!(
-                "{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.registry.put",
-            ))),
-            Either::Left(nats.subscribe(format!(
-                "{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.host.ping",
-            ))),
-            Either::Right(nats.queue_subscribe(
-                format!("{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.link.*"),
-                format!("{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.link",),
-            )),
-            Either::Right(nats.queue_subscribe(
-                format!("{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.claims.get"),
-                format!("{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.claims"),
-            )),
+        let mut subs = Vec::new();
+        // Lattice-wide subjects have the same shape in both versions
+        for version in [CTL_API_VERSION_1, CTL_API_VERSION_2] {
+            let prefix = format!("{topic_prefix}.{version}.{lattice}");
+            subs.extend([
+                Either::Left(nats.subscribe(format!("{prefix}.registry.put"))),
+                Either::Left(nats.subscribe(format!("{prefix}.host.ping"))),
+                Either::Right(
+                    nats.queue_subscribe(format!("{prefix}.link.*"), format!("{prefix}.link")),
+                ),
+                Either::Right(
+                    nats.queue_subscribe(
+                        format!("{prefix}.claims.get"),
+                        format!("{prefix}.claims"),
+                    ),
+                ),
+                Either::Right(
+                    nats.queue_subscribe(format!("{prefix}.config.>"), format!("{prefix}.config")),
+                ),
+            ]);
+            if component_auction {
+                subs.push(Either::Left(
+                    nats.subscribe(format!("{prefix}.component.auction")),
+                ));
+            }
+            if provider_auction {
+                subs.push(Either::Left(
+                    nats.subscribe(format!("{prefix}.provider.auction")),
+                ));
+            }
+        }
+        subs.extend([
             Either::Left(nats.subscribe(format!(
                 "{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.component.*.{host_id}"
             ))),
@@ -70,21 +92,11 @@ impl Queue {
             Either::Left(nats.subscribe(format!(
                 "{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.host.*.{host_id}"
             ))),
-            Either::Right(nats.queue_subscribe(
-                format!("{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.config.>"),
-                format!("{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.config"),
-            )),
-        ];
-        if component_auction {
-            subs.push(Either::Left(nats.subscribe(format!(
-                "{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.component.auction",
-            ))));
-        }
-        if provider_auction {
-            subs.push(Either::Left(nats.subscribe(format!(
-                "{topic_prefix}.{CTL_API_VERSION_1}.{lattice}.provider.auction",
-            ))));
-        }
+            // `v2` subjects group all operations targeting this host under a single prefix
+            Either::Left(nats.subscribe(format!(
+                "{topic_prefix}.{CTL_API_VERSION_2}.{lattice}.host.{host_id}.>"
+            ))),
+        ]);
         let streams = futures::future::join_all(subs)
             .await
             .into_iter()
@@ -97,7 +109,11 @@ impl Queue {
 }
 
 impl crate::wasmbus::Host {
-    #[instrument(level = "trace", skip_all, fields(subject = %message.subject))]
+    #[instrument(
+        level = "trace",
+        skip_all,
+        fields(subject = %message.subject, command_id = tracing::field::Empty)
+    )]
     pub(crate) async fn handle_ctl_message(
         self: Arc<Self>,
         message: async_nats::Message,
@@ -107,16 +123,31 @@ impl crate::wasmbus::Host {
         // disabled. In most cases that's fine, since we aren't aware of any control interface
         // requests including a trace context
         opentelemetry_nats::attach_span_context(&message);
+        // Log under the ID the client sent, so that its logs and ours can be joined. Older clients
+        // don't send one, in which case the request gets a fresh ID
+        let command_id = message
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(COMMAND_ID_HEADER))
+            .map(|id| id.as_str().to_string())
+            .unwrap_or_else(wasmcloud_core::id::command_id);
+        tracing::Span::current().record("command_id", command_id.as_str());
+        // `v2` subjects name the same operations as their `v1` counterparts, in a different order
+        let subject = wasmcloud_control_interface::v1_subject(
+            &Some(ctl_subject_prefix.to_string()),
+            self.lattice(),
+            &message.subject,
+        )
+        .map_or(message.subject, async_nats::Subject::from);
         // Skip the topic prefix, the version, and the lattice
         // e.g. `wasmbus.ctl.v1.{prefix}`
-        let subject = message.subject;
         let mut parts = subject
             .trim()
             .trim_start_matches(ctl_subject_prefix)
             .trim_start_matches('.')
             .split('.')
             .skip(2);
-        trace!(%subject, "handling control interface request");
+        trace!(%subject, %command_id, "handling control interface request");
 
         // This response is a wrapped Result<Option<Result<Vec<u8>>>> for a good reason.
         // The outer Result is for reporting protocol errors in handling the request, e.g. failing to
@@ -126,7 +157,47 @@ impl crate::wasmbus::Host {
         // The inner Result is purely for the success or failure of serializing the [CtlResponse], which
         //    should never fail but it's a result we must handle.
         // And finally, the Vec<u8> is the serialized [CtlResponse] that we'll send back to the client
-        let ctl_response = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        let operation = (parts.next(), parts.next(), parts.next(), parts.next());
+        if self.is_read_only() && is_mutating_operation(operation.0, operation.1) {
+            // Auctions are declined silently, like any other auction the host can't satisfy
+            if operation.1 == Some("auction") {
+                trace!(%subject, "declining auction in read-only mode");
+                return None;
+            }
+            warn!(
+                %subject,
+                %command_id,
+                "refusing mutating control interface request in read-only mode"
+            );
+            return serde_json::to_vec(&CtlResponse::<()>::error(
+                "host is running in read-only mode and does not accept mutating commands",
+            ))
+            .ok()
+            .map(Into::into);
+        }
+        // Queries are answered regardless of their epoch, since they can't conflict with the leader
+        let epoch = message.headers.as_ref().and_then(epoch_from_headers);
+        let checked = if is_mutating_operation(operation.0, operation.1) {
+            self.check_lattice_epoch(epoch)
+        } else {
+            Ok(())
+        };
+        if let Err(current) = checked {
+            // Auctions are declined silently, so that a stale controller never places workloads
+            if operation.1 == Some("auction") {
+                trace!(%subject, ?epoch, current, "declining auction with stale lattice epoch");
+                return None;
+            }
+            warn!(%subject, %command_id, ?epoch, current, "refusing control interface request with stale lattice epoch");
+            return serde_json::to_vec(&CtlResponse::<()>::error(&format!(
+                "request carries lattice epoch {} but the lattice is at epoch {current}",
+                epoch.unwrap_or_default()
+            )))
+            .ok()
+            .map(Into::into);
+        }
+
+        let ctl_response = match operation {
             // Component commands
             (Some("component"), Some("auction"), None, None) => self
                 .handle_auction_component(message.payload)
@@ -142,6 +213,21 @@ impl crate::wasmbus::Host {
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
+            (Some("component"), Some("stop"), Some(host_id), None) => self
+                .handle_stop_components(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("component"), Some("profile"), Some(host_id), None) => self
+                .handle_profile_component(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("component"), Some("memory"), Some(host_id), None) => self
+                .handle_top_memory_consumers(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             // Provider commands
             (Some("provider"), Some("auction"), None, None) => self
                 .handle_auction_provider(message.payload)
@@ -151,12 +237,23 @@ impl crate::wasmbus::Host {
                 .handle_start_provider(message.payload)
                 .await
                 .map(serialize_ctl_response),
+            (Some("provider"), Some("update"), Some(_host_id), None) => self
+                .handle_update_provider_config(message.payload)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             (Some("provider"), Some("stop"), Some(_host_id), None) => self
                 .handle_stop_provider(message.payload)
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
             // Host commands
+            (Some("host"), Some("get"), Some(_host_id), None) if !message.payload.is_empty() => {
+                self.handle_inventory_page(message.payload)
+                    .await
+                    .map(Some)
+                    .map(serialize_ctl_response)
+            }
             (Some("host"), Some("get"), Some(_host_id), None) => self
                 .handle_inventory()
                 .await
@@ -172,9 +269,29 @@ impl crate::wasmbus::Host {
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
+            (Some("host"), Some("drain"), Some(host_id), None) => Arc::clone(&self)
+                .handle_drain_host(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("host"), Some("prefetch"), Some(host_id), None) => Arc::clone(&self)
+                .handle_prefetch_images(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("host"), Some("cleanup"), Some(host_id), None) => Arc::clone(&self)
+                .handle_cleanup_host_data(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("host"), Some("tracing"), Some(host_id), None) => Arc::clone(&self)
+                .handle_update_host_tracing(message.payload, host_id)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             // Claims commands
             (Some("claims"), Some("get"), None, None) => self
-                .handle_claims()
+                .handle_claims(message.payload)
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
@@ -186,7 +303,9 @@ impl crate::wasmbus::Host {
                 .map(serialize_ctl_response),
             (Some("link"), Some("get"), None, None) => {
                 // Explicitly returning a Vec<u8> for non-cloning efficiency within handle_links
-                self.handle_links().await.map(|bytes| Some(Ok(bytes)))
+                self.handle_links(message.payload)
+                    .await
+                    .map(|bytes| Some(Ok(bytes)))
             }
             (Some("link"), Some("put"), None, None) => self
                 .handle_link_put(message.payload)
@@ -204,6 +323,16 @@ impl crate::wasmbus::Host {
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
+            (Some("label"), Some("del_many"), Some(host_id), None) => self
+                .handle_labels_del(host_id, message.payload)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("label"), Some("put_many"), Some(host_id), None) => self
+                .handle_labels_put(host_id, message.payload)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             // Registry commands
             (Some("registry"), Some("put"), None, None) => self
                 .handle_registries_put(message.payload)
@@ -212,9 +341,14 @@ impl crate::wasmbus::Host {
                 .map(serialize_ctl_response),
             // Config commands
             (Some("config"), Some("get"), Some(config_name), None) => self
-                .handle_config_get(config_name)
+                .handle_config_get(config_name, message.payload)
                 .await
                 .map(|bytes| Some(Ok(bytes))),
+            (Some("config"), Some("get_many"), None, None) => self
+                .handle_config_get_many(message.payload)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             (Some("config"), Some("put"), Some(config_name), None) => self
                 .handle_config_put(config_name, message.payload)
                 .await
@@ -225,6 +359,20 @@ impl crate::wasmbus::Host {
                 .await
                 .map(Some)
                 .map(serialize_ctl_response),
+            (Some("config"), Some("get_host"), Some(host_id), Some(config_name)) => self
+                .handle_host_config_get(host_id, config_name)
+                .await
+                .map(|bytes| Some(Ok(bytes))),
+            (Some("config"), Some("put_host"), Some(host_id), Some(config_name)) => self
+                .handle_host_config_put(host_id, config_name, message.payload)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
+            (Some("config"), Some("del_host"), Some(host_id), Some(config_name)) => self
+                .handle_host_config_delete(host_id, config_name)
+                .await
+                .map(Some)
+                .map(serialize_ctl_response),
             // Topic fallback
             _ => {
                 warn!(%subject, "received control interface request on unsupported subject");
@@ -235,9 +383,9 @@ impl crate::wasmbus::Host {
         };
 
         if let Err(err) = &ctl_response {
-            error!(%subject, ?err, "failed to handle control interface request");
+            error!(%subject, %command_id, ?err, "failed to handle control interface request");
         } else {
-            trace!(%subject, "handled control interface request");
+            trace!(%subject, %command_id, "handled control interface request");
         }
 
         match ctl_response {
@@ -272,6 +420,9 @@ pub struct NatsControlInterfaceServer {
     ctl_topic_prefix: String,
     enable_component_auction: bool,
     enable_provider_auction: bool,
+    health: Arc<NatsHealth>,
+    health_thresholds: NatsHealthThresholds,
+    rate_limiter: Option<Arc<CtlRateLimiter>>,
 }
 
 impl NatsControlInterfaceServer {
@@ -296,6 +447,35 @@ impl NatsControlInterfaceServer {
             ctl_topic_prefix,
             enable_component_auction,
             enable_provider_auction,
+            health: Arc::default(),
+            health_thresholds: NatsHealthThresholds::default(),
+            rate_limiter: None,
+        }
+    }
+
+    /// Set the thresholds above which the host reports its NATS connections as degraded.
+    #[must_use]
+    pub fn with_health_thresholds(self, health_thresholds: NatsHealthThresholds) -> Self {
+        Self {
+            health_thresholds,
+            ..self
+        }
+    }
+
+    /// Record the health of the NATS connections of the host in `health` instead of a state private
+    /// to this server, e.g. to share it with the connections of the host.
+    #[must_use]
+    pub fn with_nats_health(self, health: Arc<NatsHealth>) -> Self {
+        Self { health, ..self }
+    }
+
+    /// Limit the rate of control interface requests accepted from a single sender, see
+    /// [`rate_limit`]. Requests are not rate limited by default.
+    #[must_use]
+    pub fn with_rate_limits(self, rate_limits: Option<CtlRateLimits>) -> Self {
+        Self {
+            rate_limiter: rate_limits.map(|limits| Arc::new(CtlRateLimiter::new(limits))),
+            ..self
         }
     }
 
@@ -318,47 +498,120 @@ impl NatsControlInterfaceServer {
         .context("failed to initialize queue")?;
 
         let mut tasks = JoinSet::new();
-        data_watch(&mut tasks, self.data_store, host.clone())
-            .await
-            .context("failed to start data watch")?;
+        data_watch(
+            &mut tasks,
+            self.data_store,
+            host.clone(),
+            Arc::clone(&self.health),
+        )
+        .await
+        .context("failed to start data watch")?;
+
+        tasks.spawn(health::monitor(
+            Arc::clone(&host),
+            Arc::clone(&self.ctl_nats),
+            Arc::clone(&self.health),
+            self.health_thresholds,
+            DEFAULT_NATS_HEALTH_INTERVAL,
+        ));
 
         tasks.spawn({
             let ctl_nats = Arc::clone(&self.ctl_nats);
             let host = Arc::clone(&host);
+            let health = Arc::clone(&self.health);
+            let rate_limiter = self.rate_limiter.clone();
             let ctl_subject_prefix = Arc::new(self.ctl_topic_prefix.clone());
             async move {
                 queue
                     .for_each_concurrent(None, {
                         let host = Arc::clone(&host);
                         let ctl_nats = Arc::clone(&ctl_nats);
+                        let health = Arc::clone(&health);
+                        let rate_limiter = rate_limiter.clone();
                         let ctl_subject_prefix = Arc::clone(&ctl_subject_prefix);
                         move |msg| {
                             let host = Arc::clone(&host);
                             let ctl_nats = Arc::clone(&ctl_nats);
+                            let health = Arc::clone(&health);
+                            let rate_limiter = rate_limiter.clone();
                             let ctl_subject_prefix = Arc::clone(&ctl_subject_prefix);
                             async move {
+                                let _pending = health.pending_request(msg.payload.len());
                                 let msg_subject = msg.subject.clone();
                                 let msg_reply = msg.reply.clone();
-                                let payload = host.handle_ctl_message(msg, &ctl_subject_prefix).await;
+                                let accepts_chunks = chunking::accepts_chunks(msg.headers.as_ref());
+                                let encoding = Encoding::accepted(msg.headers.as_ref());
+                                let limited = rate_limiter
+                                    .as_ref()
+                                    .and_then(|limiter| limiter.check(rate_limit::sender(&msg)).err());
+                                let payload = if let Some(retry_after) = limited {
+                                    // Auctions are declined silently, like any other auction the host can't satisfy
+                                    if msg_subject.ends_with(".auction") {
+                                        None
+                                    } else {
+                                        serde_json::to_vec(&CtlResponse::rate_limited(retry_after))
+                                            .ok()
+                                            .map(Bytes::from)
+                                    }
+                                } else {
+                                    // A panic while handling a (possibly malformed) request must not take down
+                                    // the control interface, so it is reported to the client as an error instead
+                                    match AssertUnwindSafe(host.handle_ctl_message(msg, &ctl_subject_prefix))
+                                        .catch_unwind()
+                                        .await
+                                    {
+                                        Ok(payload) => payload,
+                                        Err(_) => {
+                                            error!(%msg_subject, "panicked while handling control interface request");
+                                            serde_json::to_vec(&CtlResponse::error("internal error while handling request"))
+                                                .ok()
+                                                .map(Bytes::from)
+                                        }
+                                    }
+                                };
                                 if let Some(reply) = msg_reply {
-                                    let headers = injector_to_headers(&TraceContextInjector::default_with_span());
+                                    let mut headers = injector_to_headers(&TraceContextInjector::default_with_span());
                                     if let Some(payload) = payload {
+                                        let payload = match encoding.from_json(payload.clone()) {
+                                            Ok(encoded) => {
+                                                encoding.insert_reply_headers(&mut headers);
+                                                encoded
+                                            }
+                                            Err(err) => {
+                                                warn!(%msg_subject, %err, "failed to encode control interface reply, replying with JSON");
+                                                payload
+                                            }
+                                        };
                                         let max_payload = ctl_nats.server_info().max_payload;
-                                        if payload.len() > max_payload {
-                                            warn!(
-                                                size = payload.len(),
-                                                max_size = max_payload,
-                                                "ctl response payload is too large to publish and may fail",
-                                            );
+                                        let chunks = if accepts_chunks {
+                                            chunking::split(payload, max_payload)
+                                        } else {
+                                            if payload.len() > max_payload {
+                                                warn!(
+                                                    size = payload.len(),
+                                                    max_size = max_payload,
+                                                    "ctl response payload is too large to publish and may fail",
+                                                );
+                                            }
+                                            vec![(async_nats::HeaderMap::new(), payload)]
+                                        };
+                                        for (chunk_headers, chunk) in chunks {
+                                            let mut headers = headers.clone();
+                                            for (name, values) in chunk_headers.iter() {
+                                                for value in values {
+                                                    headers.append(name.clone(), value.clone());
+                                                }
+                                            }
+                                            if let Err(err) = ctl_nats
+                                                .publish_with_headers(reply.clone(), headers, chunk)
+                                                .await
+                                            {
+                                                tracing::error!(%msg_subject, ?err, "failed to publish reply to control interface request");
+                                                break;
+                                            }
                                         }
-                                        if let Err(err) =
-                                            ctl_nats
-                                            .publish_with_headers(reply.clone(), headers, payload)
-                                            .err_into::<anyhow::Error>()
-                                            .and_then(|()| ctl_nats.flush().err_into::<anyhow::Error>())
-                                            .await
-                                        {
-                                            tracing::error!(%msg_subject, ?err, "failed to publish reply to control interface request");
+                                        if let Err(err) = ctl_nats.flush().await {
+                                            tracing::error!(%msg_subject, ?err, "failed to flush reply to control interface request");
                                         }
                                     }
                                 }
@@ -377,9 +630,69 @@ impl NatsControlInterfaceServer {
     }
 }
 
+/// Returns true if the control interface operation on the given resource would mutate the host or
+/// the lattice, which read-only hosts refuse
+fn is_mutating_operation(resource: Option<&str>, operation: Option<&str>) -> bool {
+    !matches!(
+        (resource, operation),
+        (Some("host"), Some("get" | "ping"))
+            | (Some("claims" | "link" | "config"), Some("get"))
+            | (Some("config"), Some("get_many" | "get_host"))
+            | (Some("component"), Some("profile" | "memory"))
+    )
+}
+
 /// Helper function to serialize `CtlResponse`<T> into a Vec<u8> if the response is Some
 fn serialize_ctl_response<T: Serialize>(
     ctl_response: Option<CtlResponse<T>>,
 ) -> Option<anyhow::Result<Vec<u8>>> {
     ctl_response.map(|resp| serde_json::to_vec(&resp).map_err(anyhow::Error::from))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_mutating_operation;
+
+    #[test]
+    fn queries_are_not_mutating() {
+        for (resource, operation) in [
+            ("host", "get"),
+            ("host", "ping"),
+            ("claims", "get"),
+            ("link", "get"),
+            ("config", "get"),
+            ("config", "get_many"),
+            ("config", "get_host"),
+            ("component", "profile"),
+            ("component", "memory"),
+        ] {
+            assert!(
+                !is_mutating_operation(Some(resource), Some(operation)),
+                "{resource}.{operation} should be a query"
+            );
+        }
+    }
+
+    #[test]
+    fn commands_and_unknown_operations_are_mutating() {
+        for (resource, operation) in [
+            (Some("component"), Some("scale")),
+            (Some("component"), Some("auction")),
+            (Some("provider"), Some("start")),
+            (Some("provider"), Some("auction")),
+            (Some("link"), Some("put")),
+            (Some("link"), Some("del")),
+            (Some("config"), Some("put")),
+            (Some("host"), Some("stop")),
+            (Some("label"), Some("put")),
+            (Some("component"), Some("get")),
+            (Some("host"), None),
+            (None, None),
+        ] {
+            assert!(
+                is_mutating_operation(resource, operation),
+                "{resource:?}.{operation:?} should be refused by read-only hosts"
+            );
+        }
+    }
+}
@@ -4,17 +4,19 @@ use anyhow::Context;
 use cloudevents::{EventBuilder, EventBuilderV10};
 use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 use tracing::{instrument, warn};
-use ulid::Ulid;
-use uuid::Uuid;
 
-use crate::event::EventPublisher;
+use crate::event::{EventPublisher, EventSchemaVersion};
 
 /// NATS implementation of the wasmCloud [crate::event::EventPublisher] extension trait,
 /// sending events to the NATS message bus with a CloudEvents payload envelope.
+///
+/// Every event is published once per configured [`EventSchemaVersion`], on the subject of that
+/// version.
 pub struct NatsEventPublisher {
     event_builder: EventBuilderV10,
     lattice: String,
     ctl_nats: async_nats::Client,
+    schema_versions: Vec<EventSchemaVersion>,
 }
 
 impl NatsEventPublisher {
@@ -30,41 +32,85 @@ impl NatsEventPublisher {
             event_builder: EventBuilderV10::new().source(source),
             lattice,
             ctl_nats,
+            schema_versions: vec![EventSchemaVersion::default()],
         }
     }
-}
 
-#[async_trait::async_trait]
-impl EventPublisher for NatsEventPublisher {
-    #[instrument(skip(self, data))]
-    async fn publish_event(&self, name: &str, data: serde_json::Value) -> anyhow::Result<()> {
-        let now = OffsetDateTime::now_utc()
-            .format(&Rfc3339)
-            .context("failed to format current time")?;
-        let ev = self
+    /// Publish events in each of the given schema versions. Publishing in several versions allows
+    /// consumers to migrate to a new schema during a transition window.
+    ///
+    /// Events are published in [`EventSchemaVersion::V1`] if `schema_versions` is empty.
+    #[must_use]
+    pub fn with_schema_versions(
+        mut self,
+        schema_versions: impl IntoIterator<Item = EventSchemaVersion>,
+    ) -> Self {
+        let mut schema_versions: Vec<_> = schema_versions.into_iter().collect();
+        schema_versions.sort_unstable();
+        schema_versions.dedup();
+        if !schema_versions.is_empty() {
+            self.schema_versions = schema_versions;
+        }
+        self
+    }
+
+    async fn publish_version(
+        &self,
+        version: EventSchemaVersion,
+        name: &str,
+        id: &str,
+        time: &str,
+        data: serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let mut builder = self
             .event_builder
             .clone()
             .ty(format!("com.wasmcloud.lattice.{name}"))
-            .id(Uuid::from_u128(Ulid::new().into()).to_string())
-            .time(now)
-            .data("application/json", data)
-            .build()
-            .context("failed to build cloud event")?;
+            .id(id)
+            .time(time)
+            .data("application/json", version.convert(data));
+        if version != EventSchemaVersion::V1 {
+            builder = builder.extension("schemaversion", version.as_str());
+        }
+        let ev = builder.build().context("failed to build cloud event")?;
         let ev = serde_json::to_vec(&ev).context("failed to serialize event")?;
         let max_payload = self.ctl_nats.server_info().max_payload;
-        let lattice = &self.lattice;
         if ev.len() > max_payload {
             warn!(
                 size = ev.len(),
                 max_size = max_payload,
                 event = name,
-                lattice = &lattice,
+                lattice = &self.lattice,
+                %version,
                 "event payload is too large to publish and may fail",
             );
         }
         self.ctl_nats
-            .publish(format!("wasmbus.evt.{lattice}.{name}"), ev.into())
+            .publish(version.subject(&self.lattice, name), ev.into())
             .await
-            .with_context(|| format!("failed to publish `{name}` event"))
+            .with_context(|| format!("failed to publish `{name}` event in schema {version}"))
+    }
+}
+
+#[async_trait::async_trait]
+impl EventPublisher for NatsEventPublisher {
+    #[instrument(skip(self, data))]
+    async fn publish_event(&self, name: &str, data: serde_json::Value) -> anyhow::Result<()> {
+        let now = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .context("failed to format current time")?;
+        // All versions of an event share the same ID, so that consumers can correlate them
+        let id = wasmcloud_core::id::event_id();
+        let mut result = Ok(());
+        for version in &self.schema_versions {
+            if let Err(err) = self
+                .publish_version(*version, name, &id, &now, data.clone())
+                .await
+            {
+                warn!(?err, %version, event = name, "failed to publish event");
+                result = Err(err);
+            }
+        }
+        result
     }
 }
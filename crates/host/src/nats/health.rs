@@ -0,0 +1,375 @@
+//! Health of the NATS connections used by the host.
+//!
+//! Degraded control plane connectivity otherwise goes unnoticed until control interface requests
+//! start timing out. [monitor] samples the client statistics of the host connections, exports them
+//! through [crate::metrics::HostMetrics] and publishes `nats_health_degraded` and
+//! `nats_health_recovered` events whenever a [NatsHealthThresholds] is crossed.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use async_nats::connection::State;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, warn};
+
+use crate::event;
+use crate::wasmbus::Host;
+
+/// Interval at which [monitor] samples the health of the host connections
+pub const DEFAULT_NATS_HEALTH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Values above which a NATS connection is reported as degraded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NatsHealthThresholds {
+    /// Reconnects of a single connection between two samples
+    pub reconnects: u64,
+    /// Slow consumer notifications between two samples
+    pub slow_consumers: u64,
+    /// Bytes of control interface requests received but not yet answered
+    pub pending_bytes: u64,
+    /// Entries a KV bucket watch has yet to process
+    pub kv_watch_lag: u64,
+}
+
+impl Default for NatsHealthThresholds {
+    fn default() -> Self {
+        Self {
+            reconnects: 1,
+            slow_consumers: 1,
+            pending_bytes: 8 * 1024 * 1024,
+            kv_watch_lag: 1000,
+        }
+    }
+}
+
+/// State of a single NATS connection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NatsConnectionHealth {
+    /// Whether the client is currently connected
+    pub connected: bool,
+    /// Number of times the client reconnected since it was created
+    pub reconnects: u64,
+}
+
+/// Sample of the health of the host NATS connections
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NatsHealthSnapshot {
+    /// Connection state keyed by connection name, i.e. `ctl` or `rpc`
+    pub connections: BTreeMap<&'static str, NatsConnectionHealth>,
+    /// Number of slow consumer notifications received so far
+    pub slow_consumers: u64,
+    /// Bytes of control interface requests received but not yet answered
+    pub pending_bytes: u64,
+    /// Entries left to process by KV bucket watches, keyed by bucket name
+    pub kv_watch_lag: BTreeMap<String, u64>,
+}
+
+/// A threshold crossed by a [NatsHealthSnapshot]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NatsHealthIssue {
+    /// Kind of the issue, e.g. `disconnected` or `kv_watch_lag`
+    pub kind: &'static str,
+    /// The connection, subscription or bucket affected by the issue
+    pub subject: String,
+    /// The observed value
+    pub value: u64,
+    /// The threshold that was crossed
+    pub threshold: u64,
+}
+
+impl NatsHealthIssue {
+    fn new(kind: &'static str, subject: impl Into<String>, value: u64, threshold: u64) -> Self {
+        Self {
+            kind,
+            subject: subject.into(),
+            value,
+            threshold,
+        }
+    }
+
+    fn key(&self) -> (&'static str, String) {
+        (self.kind, self.subject.clone())
+    }
+}
+
+impl NatsHealthSnapshot {
+    /// Returns the issues observed in this snapshot, comparing counters against the `previous` one
+    #[must_use]
+    pub fn issues(
+        &self,
+        previous: &Self,
+        thresholds: &NatsHealthThresholds,
+    ) -> Vec<NatsHealthIssue> {
+        let mut issues = Vec::new();
+        for (name, conn) in &self.connections {
+            if !conn.connected {
+                issues.push(NatsHealthIssue::new("disconnected", *name, 0, 0));
+            }
+            let reconnects = conn.reconnects.saturating_sub(
+                previous
+                    .connections
+                    .get(name)
+                    .map_or(0, |conn| conn.reconnects),
+            );
+            if reconnects >= thresholds.reconnects && reconnects > 0 {
+                issues.push(NatsHealthIssue::new(
+                    "reconnects",
+                    *name,
+                    reconnects,
+                    thresholds.reconnects,
+                ));
+            }
+        }
+        let slow_consumers = self.slow_consumers.saturating_sub(previous.slow_consumers);
+        if slow_consumers >= thresholds.slow_consumers && slow_consumers > 0 {
+            issues.push(NatsHealthIssue::new(
+                "slow_consumers",
+                "host",
+                slow_consumers,
+                thresholds.slow_consumers,
+            ));
+        }
+        if self.pending_bytes >= thresholds.pending_bytes {
+            issues.push(NatsHealthIssue::new(
+                "pending_bytes",
+                "ctl",
+                self.pending_bytes,
+                thresholds.pending_bytes,
+            ));
+        }
+        for (bucket, lag) in &self.kv_watch_lag {
+            if *lag >= thresholds.kv_watch_lag {
+                issues.push(NatsHealthIssue::new(
+                    "kv_watch_lag",
+                    bucket,
+                    *lag,
+                    thresholds.kv_watch_lag,
+                ));
+            }
+        }
+        issues
+    }
+}
+
+/// Host-side NATS state that is not tracked by the client statistics
+#[derive(Debug, Default)]
+pub struct NatsHealth {
+    slow_consumers: AtomicU64,
+    pending_bytes: AtomicU64,
+    kv_watch_lag: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Tracks the bytes of a control interface request until it is answered
+pub(crate) struct PendingRequest<'a> {
+    health: &'a NatsHealth,
+    bytes: u64,
+}
+
+impl Drop for PendingRequest<'_> {
+    fn drop(&mut self) {
+        self.health
+            .pending_bytes
+            .fetch_sub(self.bytes, Ordering::Relaxed);
+    }
+}
+
+impl NatsHealth {
+    /// Event callback installed on clients created with [super::connect_nats_with_health]
+    pub(crate) fn handle_client_event(&self, event: async_nats::Event) {
+        match event {
+            async_nats::Event::SlowConsumer(sid) => {
+                self.slow_consumers.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    sid,
+                    "NATS subscription is a slow consumer, messages were dropped"
+                );
+            }
+            async_nats::Event::Disconnected => warn!("NATS client disconnected"),
+            event => debug!(%event, "NATS client event"),
+        }
+    }
+
+    /// Record a control interface request of `bytes` bytes as pending until the returned guard is
+    /// dropped
+    pub(crate) fn pending_request(&self, bytes: usize) -> PendingRequest<'_> {
+        let bytes = bytes as u64;
+        self.pending_bytes.fetch_add(bytes, Ordering::Relaxed);
+        PendingRequest {
+            health: self,
+            bytes,
+        }
+    }
+
+    /// Record the number of entries left to process by the watch of `bucket`
+    pub(crate) fn record_kv_watch_lag(&self, bucket: &str, lag: u64) {
+        self.kv_watch_lag
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(bucket.to_string(), lag);
+    }
+
+    /// Take a [NatsHealthSnapshot] of the named connections
+    #[must_use]
+    pub fn snapshot(
+        &self,
+        connections: &[(&'static str, &async_nats::Client)],
+    ) -> NatsHealthSnapshot {
+        NatsHealthSnapshot {
+            connections: connections
+                .iter()
+                .map(|(name, client)| {
+                    let connects = client.statistics().connects.load(Ordering::Relaxed);
+                    (
+                        *name,
+                        NatsConnectionHealth {
+                            connected: client.connection_state() == State::Connected,
+                            reconnects: connects.saturating_sub(1),
+                        },
+                    )
+                })
+                .collect(),
+            slow_consumers: self.slow_consumers.load(Ordering::Relaxed),
+            pending_bytes: self.pending_bytes.load(Ordering::Relaxed),
+            kv_watch_lag: self
+                .kv_watch_lag
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+}
+
+/// Periodically sample the health of the host NATS connections, record it as metrics and publish
+/// events when issues start or stop being observed
+pub(crate) async fn monitor(
+    host: Arc<Host>,
+    ctl_nats: Arc<async_nats::Client>,
+    health: Arc<NatsHealth>,
+    thresholds: NatsHealthThresholds,
+    interval: Duration,
+) -> anyhow::Result<()> {
+    let sample = || health.snapshot(&[("ctl", &ctl_nats), ("rpc", host.rpc_nats())]);
+    let mut previous = sample();
+    let mut active = BTreeMap::new();
+    let mut interval = tokio::time::interval(interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        let current = sample();
+        host.metrics().record_nats_health(current.clone());
+
+        let issues: BTreeMap<_, _> = current
+            .issues(&previous, &thresholds)
+            .into_iter()
+            .map(|issue| (issue.key(), issue))
+            .collect();
+        for (key, issue) in &issues {
+            if !active.contains_key(key) {
+                warn!(
+                    kind = issue.kind,
+                    subject = issue.subject,
+                    value = issue.value,
+                    threshold = issue.threshold,
+                    "NATS connection degraded"
+                );
+                publish(&host, "nats_health_degraded", issue).await;
+            }
+        }
+        for (key, issue) in &active {
+            if !issues.contains_key(key) {
+                publish(&host, "nats_health_recovered", issue).await;
+            }
+        }
+        active = issues;
+        previous = current;
+    }
+}
+
+async fn publish(host: &Host, event_name: &str, issue: &NatsHealthIssue) {
+    if let Err(err) = host
+        .event_publisher
+        .publish_event(event_name, event::nats_health(host.id(), issue))
+        .await
+    {
+        error!(?err, event_name, "failed to publish NATS health event");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(connected: bool, reconnects: u64) -> NatsHealthSnapshot {
+        NatsHealthSnapshot {
+            connections: BTreeMap::from([(
+                "ctl",
+                NatsConnectionHealth {
+                    connected,
+                    reconnects,
+                },
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nats_health_issues() {
+        let thresholds = NatsHealthThresholds::default();
+        let healthy = snapshot(true, 2);
+        assert!(healthy.issues(&healthy, &thresholds).is_empty());
+
+        let issues = snapshot(false, 4).issues(&healthy, &thresholds);
+        assert_eq!(
+            issues,
+            [
+                NatsHealthIssue::new("disconnected", "ctl", 0, 0),
+                NatsHealthIssue::new("reconnects", "ctl", 2, 1),
+            ]
+        );
+
+        let mut lagging = healthy.clone();
+        lagging.slow_consumers = 3;
+        lagging.pending_bytes = thresholds.pending_bytes;
+        lagging
+            .kv_watch_lag
+            .insert("LATTICEDATA_default".into(), thresholds.kv_watch_lag + 1);
+        lagging.kv_watch_lag.insert("CONFIGDATA_default".into(), 0);
+        let issues = lagging.issues(&healthy, &thresholds);
+        assert_eq!(
+            issues,
+            [
+                NatsHealthIssue::new("slow_consumers", "host", 3, 1),
+                NatsHealthIssue::new("pending_bytes", "ctl", thresholds.pending_bytes, 8388608),
+                NatsHealthIssue::new("kv_watch_lag", "LATTICEDATA_default", 1001, 1000),
+            ]
+        );
+        // Counters only count as issues while they increase
+        assert_eq!(lagging.issues(&lagging, &thresholds).len(), 2);
+    }
+
+    #[test]
+    fn nats_health_pending_requests() {
+        let health = NatsHealth::default();
+        let first = health.pending_request(100);
+        let second = health.pending_request(50);
+        assert_eq!(health.snapshot(&[]).pending_bytes, 150);
+        drop(first);
+        assert_eq!(health.snapshot(&[]).pending_bytes, 50);
+        drop(second);
+        assert_eq!(health.snapshot(&[]).pending_bytes, 0);
+    }
+
+    #[test]
+    fn nats_health_slow_consumers() {
+        let health = NatsHealth::default();
+        let other = NatsHealth::default();
+        health.handle_client_event(async_nats::Event::SlowConsumer(1));
+        health.handle_client_event(async_nats::Event::Connected);
+        assert_eq!(health.snapshot(&[]).slow_consumers, 1);
+        assert_eq!(other.snapshot(&[]).slow_consumers, 0);
+    }
+}
@@ -4,8 +4,10 @@ use std::{sync::Arc, time::Duration};
 
 use anyhow::{bail, Context as _};
 use async_nats::jetstream::kv::Store;
+use async_nats::jetstream::object_store::ObjectStore;
 use nkeys::KeyPair;
 use tracing::{info, instrument};
+use wasmcloud_core::migration::Migrator;
 
 use crate::workload_identity::{
     setup_workload_identity_nats_connect_options, WorkloadIdentityConfig,
@@ -17,6 +19,9 @@ pub mod builder;
 /// NATS implementation of the wasmCloud control interface
 pub mod ctl;
 
+/// Health metrics and events of the NATS connections used by the host
+pub mod health;
+
 /// NATS implementation of the wasmCloud [crate::event::EventPublisher] extension trait,
 /// sending events to the NATS message bus with a CloudEvents payload envelope.
 pub mod event;
@@ -28,6 +33,9 @@ pub mod policy;
 /// which sends provider commands over the NATS message bus.
 pub mod provider;
 
+/// Per-sender rate limiting of control interface requests
+pub mod rate_limit;
+
 /// NATS implementation of the [crate::secrets::SecretsManager] extension trait
 /// for fetching encrypted secrets from a secret store.
 pub mod secrets;
@@ -54,6 +62,35 @@ pub async fn connect_nats(
     require_tls: bool,
     request_timeout: Option<Duration>,
     workload_identity_config: Option<WorkloadIdentityConfig>,
+) -> anyhow::Result<async_nats::Client> {
+    connect_nats_with_health(
+        addr,
+        jwt,
+        key,
+        require_tls,
+        request_timeout,
+        workload_identity_config,
+        Arc::default(),
+    )
+    .await
+}
+
+/// Same as [connect_nats], but records the client events, like slow consumer notifications, in the
+/// [NatsHealth](health::NatsHealth) of the host the client belongs to.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Only one of JWT or seed is specified, as we cannot authenticate with only one of them
+/// - Connection fails
+pub async fn connect_nats_with_health(
+    addr: impl async_nats::ToServerAddrs,
+    jwt: Option<&String>,
+    key: Option<Arc<KeyPair>>,
+    require_tls: bool,
+    request_timeout: Option<Duration>,
+    workload_identity_config: Option<WorkloadIdentityConfig>,
+    health: Arc<health::NatsHealth>,
 ) -> anyhow::Result<async_nats::Client> {
     let opts = match (jwt, key, workload_identity_config) {
         (Some(jwt), Some(key), None) => {
@@ -76,7 +113,10 @@ pub async fn connect_nats(
     } else {
         opts
     };
-    let opts = opts.require_tls(require_tls);
+    let opts = opts.require_tls(require_tls).event_callback(move |event| {
+        let health = Arc::clone(&health);
+        async move { health.handle_client_event(event) }
+    });
     opts.connect(addr)
         .await
         .context("failed to connect to NATS")
@@ -109,3 +149,53 @@ pub(crate) async fn create_bucket(
         }
     }
 }
+
+#[instrument(level = "debug", skip_all)]
+pub(crate) async fn create_object_store(
+    jetstream: &async_nats::jetstream::Context,
+    bucket: &str,
+) -> anyhow::Result<ObjectStore> {
+    // Don't create the object store if it already exists
+    if let Ok(store) = jetstream.get_object_store(bucket).await {
+        info!(%bucket, "object store already exists. Skipping creation.");
+        return Ok(store);
+    }
+
+    match jetstream
+        .create_object_store(async_nats::jetstream::object_store::Config {
+            bucket: bucket.to_string(),
+            ..Default::default()
+        })
+        .await
+    {
+        Ok(store) => {
+            info!(%bucket, "created object store with 1 replica");
+            Ok(store)
+        }
+        Err(err) => {
+            Err(anyhow::anyhow!(err).context(format!("failed to create object store '{bucket}'")))
+        }
+    }
+}
+
+/// Bring a lattice bucket up to the latest schema version known to this host, coordinating with
+/// the other hosts of the lattice so that pending migration steps are only applied once.
+///
+/// Layout changes of `LATTICEDATA` and `CONFIGDATA` are registered as steps of the migrator
+/// passed by [builder::NatsHostBuilder].
+#[instrument(level = "debug", skip_all, fields(bucket = %store.name))]
+pub(crate) async fn migrate_bucket(store: &Store, migrator: &Migrator) -> anyhow::Result<()> {
+    let report = migrator
+        .migrate(store)
+        .await
+        .with_context(|| format!("failed to migrate bucket '{}'", store.name))?;
+    if !report.is_noop() {
+        info!(
+            from_version = report.from_version,
+            to_version = report.to_version,
+            changes = report.change_count(),
+            "migrated bucket"
+        );
+    }
+    Ok(())
+}
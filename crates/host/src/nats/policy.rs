@@ -14,8 +14,6 @@ use futures::{
 use tokio::spawn;
 use tokio::sync::RwLock;
 use tracing::{debug, error, instrument, trace, warn};
-use ulid::Ulid;
-use uuid::Uuid;
 use wascap::jwt;
 
 use crate::policy::{
@@ -107,7 +105,7 @@ impl NatsPolicyManager {
             return Ok(entry.clone());
         }
 
-        let request_id = Uuid::from_u128(Ulid::new().into()).to_string();
+        let request_id = wasmcloud_core::id::policy_request_id();
         trace!(?cache_key, "requesting policy decision");
         let payload = serde_json::to_vec(&Request {
             request_id: request_id.clone(),
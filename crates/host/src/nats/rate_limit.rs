@@ -0,0 +1,257 @@
+//! Per-sender rate limiting of control interface requests.
+//!
+//! Every host in a lattice receives the requests of every controller, so a single runaway
+//! controller could otherwise saturate the control plane of all hosts at once. [CtlRateLimiter]
+//! keeps a token bucket per sender and refuses requests of senders that exhausted their bucket
+//! with a [`CtlResponse::rate_limited`](wasmcloud_control_interface::CtlResponse::rate_limited)
+//! reply.
+//!
+//! NATS does not tell subscribers which connection or account published a message, so senders
+//! are identified by the reply subject of their requests: single requests are answered on an
+//! inbox unique to their connection, e.g. `_INBOX.{connection}.{request}`, and accounts commonly
+//! use dedicated inbox prefixes. Requests answered by many hosts use an inbox of their own, e.g.
+//! `_INBOX.{request}`, which is used as is. Requests signed by the key in their
+//! [SENDER_HEADER](wasmcloud_control_interface::SENDER_HEADER), which clients set with
+//! [`ClientBuilder::sender`](wasmcloud_control_interface::ClientBuilder::sender), are identified by
+//! that key instead. Unsigned sender headers are ignored, as any client could set them.
+
+use core::num::NonZeroU32;
+use core::time::Duration;
+
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use tokio::time::Instant;
+use tracing::warn;
+use wasmcloud_control_interface::verified_sender;
+
+/// Number of senders tracked by default, see [CtlRateLimits::max_senders]
+pub const DEFAULT_MAX_SENDERS: usize = 10_000;
+
+/// Sender of requests without a reply subject or verified sender, which share one bucket
+const ANONYMOUS_SENDER: &str = "anonymous";
+
+/// Limits on the rate of control interface requests accepted from a single sender
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CtlRateLimits {
+    /// Sustained number of requests per second accepted from a sender
+    pub requests_per_second: NonZeroU32,
+    /// Number of requests a sender may send at once after having been idle
+    pub burst: NonZeroU32,
+    /// Maximum number of senders tracked at once. Once reached, senders whose buckets are full
+    /// are forgotten, and new senders share a single bucket if all buckets are in use
+    pub max_senders: usize,
+}
+
+impl CtlRateLimits {
+    /// Create limits accepting `requests_per_second` requests per second per sender, with a burst
+    /// of as many requests
+    #[must_use]
+    pub fn per_second(requests_per_second: NonZeroU32) -> Self {
+        Self {
+            requests_per_second,
+            burst: requests_per_second,
+            max_senders: DEFAULT_MAX_SENDERS,
+        }
+    }
+
+    /// Set the number of requests a sender may send at once after having been idle
+    #[must_use]
+    pub fn with_burst(self, burst: NonZeroU32) -> Self {
+        Self { burst, ..self }
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// Whether the last request of the sender was refused, used to only log once per episode
+    limited: bool,
+}
+
+/// Token buckets of the senders of control interface requests
+#[derive(Debug)]
+pub(crate) struct CtlRateLimiter {
+    limits: CtlRateLimits,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl CtlRateLimiter {
+    pub(crate) fn new(limits: CtlRateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Take a token from the bucket of `sender`, returning the time after which a token will be
+    /// available if the bucket is empty
+    pub(crate) fn check(&self, sender: &str) -> Result<(), Duration> {
+        self.check_at(sender, Instant::now())
+    }
+
+    fn check_at(&self, sender: &str, now: Instant) -> Result<(), Duration> {
+        let rate = f64::from(self.limits.requests_per_second.get());
+        let burst = f64::from(self.limits.burst.get());
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if !buckets.contains_key(sender) && buckets.len() >= self.limits.max_senders {
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * rate < burst
+            });
+        }
+        let sender = if buckets.contains_key(sender) || buckets.len() < self.limits.max_senders {
+            sender
+        } else {
+            ANONYMOUS_SENDER
+        };
+        let bucket = buckets.entry(sender.to_string()).or_insert(Bucket {
+            tokens: burst,
+            updated: now,
+            limited: false,
+        });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.limited = false;
+            return Ok(());
+        }
+        if !bucket.limited {
+            bucket.limited = true;
+            warn!(
+                sender,
+                requests_per_second = rate,
+                burst,
+                "sender exceeded control interface rate limit, refusing its requests"
+            );
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+    }
+}
+
+/// Identify the sender of a control interface request
+pub(crate) fn sender(message: &async_nats::Message) -> &str {
+    if let Some(sender) = message
+        .headers
+        .as_ref()
+        .and_then(|headers| verified_sender(&message.subject, headers))
+    {
+        return sender;
+    }
+    match message.reply.as_deref() {
+        // Strip the request-specific token of `{prefix}.{connection}.{request}` inboxes, which
+        // differs for every request of a connection. Inboxes of a single token following the
+        // prefix are not specific to a connection and identify the sender as a whole
+        Some(reply) => match reply.rsplit_once('.') {
+            Some((inbox, _)) if inbox.contains('.') => inbox,
+            _ => reply,
+        },
+        None => ANONYMOUS_SENDER,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nkeys::KeyPair;
+    use wasmcloud_control_interface::{
+        sign_sender, COMMAND_ID_HEADER, SENDER_HEADER, SENDER_SIGNATURE_HEADER,
+    };
+
+    use super::*;
+
+    fn limits(requests_per_second: u32, burst: u32) -> CtlRateLimits {
+        CtlRateLimits::per_second(
+            NonZeroU32::new(requests_per_second).expect("rate must be non-zero"),
+        )
+        .with_burst(NonZeroU32::new(burst).expect("burst must be non-zero"))
+    }
+
+    #[test]
+    fn limits_senders_independently() {
+        let limiter = CtlRateLimiter::new(limits(10, 2));
+        let now = Instant::now();
+        assert_eq!(limiter.check_at("a", now), Ok(()));
+        assert_eq!(limiter.check_at("a", now), Ok(()));
+        assert_eq!(limiter.check_at("a", now), Err(Duration::from_millis(100)));
+        assert_eq!(limiter.check_at("b", now), Ok(()));
+
+        // Tokens are refilled at the configured rate, up to the burst
+        assert_eq!(
+            limiter.check_at("a", now + Duration::from_millis(100)),
+            Ok(())
+        );
+        assert!(limiter
+            .check_at("a", now + Duration::from_millis(100))
+            .is_err());
+        let later = now + Duration::from_secs(60);
+        assert_eq!(limiter.check_at("a", later), Ok(()));
+        assert_eq!(limiter.check_at("a", later), Ok(()));
+        assert!(limiter.check_at("a", later).is_err());
+    }
+
+    #[test]
+    fn bounds_tracked_senders() {
+        let limiter = CtlRateLimiter {
+            limits: CtlRateLimits {
+                max_senders: 2,
+                ..limits(1, 1)
+            },
+            buckets: Mutex::default(),
+        };
+        let now = Instant::now();
+        assert_eq!(limiter.check_at("a", now), Ok(()));
+        assert_eq!(limiter.check_at("b", now), Ok(()));
+        // All buckets are in use, so new senders share the anonymous bucket
+        assert_eq!(limiter.check_at("c", now), Ok(()));
+        assert!(limiter.check_at("d", now).is_err());
+        // Once full again, buckets of idle senders are forgotten
+        let later = now + Duration::from_secs(10);
+        assert_eq!(limiter.check_at("c", later), Ok(()));
+        assert_eq!(limiter.buckets.lock().expect("lock poisoned").len(), 1);
+    }
+
+    #[test]
+    fn identifies_senders() {
+        const SUBJECT: &str = "wasmbus.ctl.v1.default.host.ping";
+        let message =
+            |reply: Option<&str>, headers: Option<async_nats::HeaderMap>| async_nats::Message {
+                subject: SUBJECT.into(),
+                reply: reply.map(Into::into),
+                payload: Default::default(),
+                headers,
+                status: None,
+                description: None,
+                length: 0,
+            };
+        assert_eq!(sender(&message(Some("_INBOX.abc.1"), None)), "_INBOX.abc");
+        // Inboxes of requests answered by many hosts are not shared by all senders
+        assert_eq!(sender(&message(Some("_INBOX.abc"), None)), "_INBOX.abc");
+        assert_eq!(sender(&message(None, None)), ANONYMOUS_SENDER);
+
+        let key = KeyPair::new_user();
+        let sender_headers = |signature: Option<String>| {
+            let mut headers = async_nats::HeaderMap::new();
+            headers.insert(SENDER_HEADER, key.public_key().as_str());
+            headers.insert(COMMAND_ID_HEADER, "command");
+            if let Some(signature) = signature {
+                headers.insert(SENDER_SIGNATURE_HEADER, signature.as_str());
+            }
+            headers
+        };
+        let signature = sign_sender(&key, SUBJECT, "command").expect("failed to sign sender");
+        assert_eq!(
+            sender(&message(
+                Some("_INBOX.abc.1"),
+                Some(sender_headers(Some(signature)))
+            )),
+            key.public_key()
+        );
+        // Unsigned sender headers can't be trusted
+        assert_eq!(
+            sender(&message(Some("_INBOX.abc.1"), Some(sender_headers(None)))),
+            "_INBOX.abc"
+        );
+    }
+}
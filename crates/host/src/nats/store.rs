@@ -4,16 +4,19 @@ use std::{collections::HashMap, sync::Arc};
 
 use anyhow::{anyhow, ensure, Context as _};
 use async_nats::jetstream::kv::{Entry as KvEntry, Operation, Store};
+use async_nats::jetstream::object_store::ObjectStore;
 use bytes::Bytes;
 use futures::{StreamExt as _, TryStreamExt as _};
 use tokio::{
     sync::watch::{self, Receiver},
     task::JoinSet,
 };
-use tracing::{debug, error, instrument, warn};
+use tracing::{debug, error, instrument, trace, warn};
+use wasmcloud_core::config_ref::resolve_config_refs;
 
 use crate::{
     config::ConfigManager,
+    nats::health::NatsHealth,
     store::StoreManager,
     wasmbus::{
         claims::{Claims, StoredClaims},
@@ -44,6 +47,38 @@ impl StoreManager for Store {
             .await
             .map_err(|err| anyhow::anyhow!("Failed to delete config: {}", err))
     }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn revision(&self) -> anyhow::Result<Option<u64>> {
+        let info = self
+            .stream
+            .get_info()
+            .await
+            .map_err(|err| anyhow::anyhow!("Failed to get config bucket info: {}", err))?;
+        Ok(Some(info.state.last_sequence))
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn get_with_revision(&self, key: &str) -> anyhow::Result<Option<(Bytes, Option<u64>)>> {
+        let entry = self
+            .entry(key)
+            .await
+            .map_err(|err| anyhow::anyhow!("Failed to get config: {}", err))?;
+        Ok(entry
+            .filter(|entry| entry.operation == Operation::Put)
+            .map(|entry| (entry.value, Some(entry.revision))))
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn keys(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        self.keys()
+            .await
+            .map_err(|err| anyhow::anyhow!("Failed to list keys: {}", err))?
+            .try_filter(|key| futures::future::ready(key.starts_with(prefix)))
+            .try_collect()
+            .await
+            .map_err(|err| anyhow::anyhow!("Failed to list keys: {}", err))
+    }
 }
 
 #[async_trait::async_trait]
@@ -57,56 +92,180 @@ impl ConfigManager for Store {
             Ok(None) => return Err(anyhow::anyhow!("Config {} does not exist", name)),
             Err(e) => return Err(anyhow::anyhow!("Error fetching config {}: {}", name, e)),
         };
+        watch_config(self, name, config).await
+    }
 
-        let (tx, rx) = watch::channel(config);
-        // Since we're starting a task, we need to own this data
-        let name = name.to_owned();
-        let mut watcher = self.watch(&name).await.context("Failed to watch config")?;
+    /// Watch the key in the JetStream bucket for changes like [`ConfigManager::watch`], starting
+    /// with an empty config if the key does not exist yet.
+    async fn watch_optional(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<Receiver<HashMap<String, String>>> {
+        let config: HashMap<String, String> = match self.get(name).await {
+            Ok(Some(data)) => serde_json::from_slice(&data)
+                .context("Data corruption error, unable to decode data from store")?,
+            Ok(None) => HashMap::new(),
+            Err(e) => return Err(anyhow::anyhow!("Error fetching config {}: {}", name, e)),
+        };
+        watch_config(self, name, config).await
+    }
+}
 
-        tokio::spawn(async move {
-            loop {
-                if tx.is_closed() {
-                    warn!(%name, "config watch channel closed, aborting watch");
+/// Watch the key in the JetStream bucket for changes, returning a channel that starts with
+/// `config` and receives updates to the config as they happen.
+async fn watch_config(
+    store: &Store,
+    name: &str,
+    config: HashMap<String, String>,
+) -> anyhow::Result<Receiver<HashMap<String, String>>> {
+    let (tx, rx) = watch::channel(config);
+    // Since we're starting a task, we need to own this data
+    let name = name.to_owned();
+    let mut watcher = store.watch(&name).await.context("Failed to watch config")?;
+
+    tokio::spawn(async move {
+        loop {
+            if tx.is_closed() {
+                warn!(%name, "config watch channel closed, aborting watch");
+                return;
+            }
+
+            match watcher.try_next().await {
+                Ok(Some(entry))
+                    if matches!(entry.operation, Operation::Delete | Operation::Purge) =>
+                {
+                    // NOTE(thomastaylor312): We should probably do something and notify something up
+                    // the chain if we get a delete or purge event of a config that is still being used.
+                    // For now we just zero it out
+                    tx.send_replace(HashMap::new());
+                }
+                Ok(Some(entry)) => {
+                    let config: HashMap<String, String> = match serde_json::from_slice(&entry.value)
+                    {
+                        Ok(config) => config,
+                        Err(e) => {
+                            error!(%name, error = %e, "Error decoding config from store during watch");
+                            continue;
+                        }
+                    };
+                    tx.send_if_modified(|current| {
+                        if current == &config {
+                            false
+                        } else {
+                            *current = config;
+                            true
+                        }
+                    });
+                }
+                Ok(None) => {
+                    error!(%name, "Watcher for config has closed");
                     return;
                 }
+                Err(e) => {
+                    error!(%name, error = %e, "Error reading from watcher for config. Will wait for next entry");
+                    continue;
+                }
+            }
+        }
+    });
 
-                match watcher.try_next().await {
-                    Ok(Some(entry))
-                        if matches!(entry.operation, Operation::Delete | Operation::Purge) =>
-                    {
-                        // NOTE(thomastaylor312): We should probably do something and notify something up
-                        // the chain if we get a delete or purge event of a config that is still being used.
-                        // For now we just zero it out
-                        tx.send_replace(HashMap::new());
-                    }
-                    Ok(Some(entry)) => {
-                        let config: HashMap<String, String> = match serde_json::from_slice(
-                            &entry.value,
-                        ) {
-                            Ok(config) => config,
-                            Err(e) => {
-                                error!(%name, error = %e, "Error decoding config from store during watch");
-                                continue;
-                            }
-                        };
-                        tx.send_if_modified(|current| {
-                            if current == &config {
-                                false
-                            } else {
-                                *current = config;
-                                true
-                            }
-                        });
-                    }
-                    Ok(None) => {
-                        error!(%name, "Watcher for config has closed");
-                        return;
+    Ok(rx)
+}
+
+/// A [ConfigManager] backed by a JetStream KV bucket, which resolves config values referencing
+/// blobs of the lattice config object store.
+///
+/// See [wasmcloud_core::config_ref] for the format of the references.
+#[derive(Clone)]
+pub struct BlobResolvingConfigStore {
+    config: Store,
+    blobs: ObjectStore,
+}
+
+impl BlobResolvingConfigStore {
+    /// Create a config store resolving references of `config` values to blobs of `blobs`
+    pub fn new(config: Store, blobs: ObjectStore) -> Self {
+        Self { config, blobs }
+    }
+}
+
+#[async_trait::async_trait]
+impl StoreManager for BlobResolvingConfigStore {
+    async fn get(&self, key: &str) -> anyhow::Result<Option<Bytes>> {
+        StoreManager::get(&self.config, key).await
+    }
+
+    async fn put(&self, key: &str, value: Bytes) -> anyhow::Result<()> {
+        StoreManager::put(&self.config, key, value).await
+    }
+
+    async fn del(&self, key: &str) -> anyhow::Result<()> {
+        StoreManager::del(&self.config, key).await
+    }
+
+    async fn revision(&self) -> anyhow::Result<Option<u64>> {
+        StoreManager::revision(&self.config).await
+    }
+
+    async fn get_with_revision(&self, key: &str) -> anyhow::Result<Option<(Bytes, Option<u64>)>> {
+        StoreManager::get_with_revision(&self.config, key).await
+    }
+
+    async fn keys(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        StoreManager::keys(&self.config, prefix).await
+    }
+}
+
+#[async_trait::async_trait]
+impl ConfigManager for BlobResolvingConfigStore {
+    /// Watch the key in the JetStream bucket for changes, resolving blob references of every
+    /// update before it is sent to the returned channel.
+    async fn watch(&self, name: &str) -> anyhow::Result<Receiver<HashMap<String, String>>> {
+        let updates = ConfigManager::watch(&self.config, name).await?;
+        self.resolve_updates(name, updates).await
+    }
+
+    /// Watch the key in the JetStream bucket for changes like [`ConfigManager::watch`], starting
+    /// with an empty config if the key does not exist yet.
+    async fn watch_optional(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<Receiver<HashMap<String, String>>> {
+        let updates = ConfigManager::watch_optional(&self.config, name).await?;
+        self.resolve_updates(name, updates).await
+    }
+}
+
+impl BlobResolvingConfigStore {
+    /// Resolve blob references of the config received from `updates` and of every update to it
+    async fn resolve_updates(
+        &self,
+        name: &str,
+        mut updates: Receiver<HashMap<String, String>>,
+    ) -> anyhow::Result<Receiver<HashMap<String, String>>> {
+        let config = updates.borrow_and_update().clone();
+        let config = resolve_config_refs(&self.blobs, config)
+            .await
+            .with_context(|| format!("failed to resolve config {name}"))?;
+
+        let (tx, rx) = watch::channel(config);
+        let name = name.to_owned();
+        let blobs = self.blobs.clone();
+        tokio::spawn(async move {
+            while updates.changed().await.is_ok() {
+                let config = updates.borrow_and_update().clone();
+                match resolve_config_refs(&blobs, config).await {
+                    Ok(config) => {
+                        tx.send_replace(config);
                     }
                     Err(e) => {
-                        error!(%name, error = %e, "Error reading from watcher for config. Will wait for next entry");
-                        continue;
+                        error!(%name, error = ?e, "Error resolving config blobs during watch, keeping previous config");
                     }
                 }
+                if tx.is_closed() {
+                    warn!(%name, "config watch channel closed, aborting watch");
+                    return;
+                }
             }
         });
 
@@ -148,6 +307,31 @@ impl crate::wasmbus::Host {
             (Operation::Delete, Some(("CLAIMS", pubkey))) => {
                 self.process_claims_delete(pubkey, value).await
             }
+            (Operation::Put, Some(("ROUTE", target))) => {
+                self.process_traffic_split_put(target, value).await
+            }
+            (Operation::Delete, Some(("ROUTE", target))) => {
+                self.process_traffic_split_delete(target).await
+            }
+            (operation, Some(("INGRESS", name))) => {
+                // Ingress routes are served by HTTP server providers in `ingress` routing mode
+                trace!(?operation, name, "ignoring ingress route entry");
+                Ok(())
+            }
+            (Operation::Put, Some(("EPOCH", _))) => self.process_lattice_epoch_put(value),
+            (operation, Some(("EPOCH", key))) => {
+                // Epochs only ever advance, so removing the entry does not lower the fence
+                trace!(?operation, key, "ignoring lattice epoch removal");
+                Ok(())
+            }
+            (operation, Some(("ALIAS", name))) => {
+                trace!(?operation, name, "ignoring lattice alias entry");
+                Ok(())
+            }
+            (operation, Some(("MIGRATION", key))) => {
+                trace!(?operation, key, "ignoring migration bookkeeping entry");
+                Ok(())
+            }
             (operation, Some(("REFMAP", id))) => {
                 // TODO: process REFMAP entries
                 debug!(?operation, id, "ignoring REFMAP entry");
@@ -243,6 +427,7 @@ pub async fn data_watch(
     tasks: &mut JoinSet<anyhow::Result<()>>,
     store: Store,
     host: Arc<crate::wasmbus::Host>,
+    health: Arc<NatsHealth>,
 ) -> anyhow::Result<()> {
     tasks.spawn({
         let host = Arc::clone(&host);
@@ -253,6 +438,14 @@ pub async fn data_watch(
                 .watch_all()
                 .await
                 .context("failed to watch lattice data bucket")?;
+            // Every change up to this revision is either processed below or delivered by the watch
+            let initial_revision = data
+                .stream
+                .get_info()
+                .await
+                .context("failed to get info of lattice data bucket")?
+                .state
+                .last_sequence;
 
             // Process existing data without emitting events
             data.keys()
@@ -273,22 +466,49 @@ pub async fn data_watch(
                     }
                 })
                 .await;
-            data_watch
-                .for_each({
-                    let host = Arc::clone(&host);
-                    move |entry| {
-                        let host = Arc::clone(&host);
-                        async move {
-                            match entry {
-                                Err(error) => {
-                                    error!("failed to watch lattice data bucket: {error}");
-                                }
-                                Ok(entry) => host.process_entry(entry).await,
+            host.set_data_revision(initial_revision);
+            let mut data_watch = data_watch;
+            loop {
+                let entry = tokio::select! {
+                    entry = data_watch.next() => entry,
+                    () = host.watchdog().resubscribe_requested() => {
+                        // Changes applied so far are kept, the watch resumes after them
+                        let revision = host.data_revision();
+                        warn!(revision, "resubscribing to lattice data bucket");
+                        match data.watch_all_from_revision(revision + 1).await {
+                            Ok(watch) => {
+                                data_watch = watch;
+                                host.watchdog().data_watch().beat();
+                            }
+                            Err(error) => {
+                                error!("failed to resubscribe to lattice data bucket: {error}");
                             }
                         }
+                        continue;
                     }
-                })
-                .await;
+                };
+                let Some(entry) = entry else {
+                    break;
+                };
+                match entry {
+                    Err(error) => {
+                        error!("failed to watch lattice data bucket: {error}");
+                    }
+                    Ok(entry) => {
+                        health.record_kv_watch_lag(&entry.bucket, entry.delta);
+                        let heartbeat = host.watchdog().data_watch();
+                        let (revision, delta) = (entry.revision, entry.delta);
+                        // The entry being processed counts towards the backlog, so that the
+                        // watchdog notices if processing it gets stuck
+                        heartbeat.beat();
+                        heartbeat.set_backlog(delta.saturating_add(1));
+                        host.process_entry(entry).await;
+                        host.set_data_revision(revision);
+                        heartbeat.set_backlog(delta);
+                        heartbeat.beat();
+                    }
+                }
+            }
             let deadline = { *host.stop_rx.borrow() };
             host.stop_tx.send_replace(deadline);
             Ok(())
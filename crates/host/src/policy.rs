@@ -3,7 +3,6 @@ use std::hash::Hash;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
 use wascap::jwt;
 
 // NOTE: All requests will be v1 until the schema changes, at which point we can change the version
@@ -23,7 +22,7 @@ pub trait PolicyManager: Send + Sync {
         _claims: Option<&jwt::Claims<jwt::Component>>,
     ) -> anyhow::Result<Response> {
         Ok(Response {
-            request_id: Uuid::new_v4().to_string(),
+            request_id: wasmcloud_core::id::policy_request_id(),
             permitted: true,
             message: None,
         })
@@ -38,7 +37,7 @@ pub trait PolicyManager: Send + Sync {
         _claims: Option<&jwt::Claims<jwt::CapabilityProvider>>,
     ) -> anyhow::Result<Response> {
         Ok(Response {
-            request_id: Uuid::new_v4().to_string(),
+            request_id: wasmcloud_core::id::policy_request_id(),
             permitted: true,
             message: None,
         })
@@ -55,7 +54,7 @@ pub trait PolicyManager: Send + Sync {
         _function: String,
     ) -> anyhow::Result<Response> {
         Ok(Response {
-            request_id: Uuid::new_v4().to_string(),
+            request_id: wasmcloud_core::id::policy_request_id(),
             permitted: true,
             message: None,
         })
@@ -16,6 +16,24 @@ pub trait StoreManager: Send + Sync {
 
     /// Deletes a key from the config store.
     async fn del(&self, key: &str) -> anyhow::Result<()>;
+
+    /// Returns the revision of the last change to any key of the store, if the store tracks
+    /// revisions.
+    async fn revision(&self) -> anyhow::Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Retrieves a value from the store by key along with the revision of the store it was last
+    /// changed at, if the store tracks revisions.
+    async fn get_with_revision(&self, key: &str) -> anyhow::Result<Option<(Bytes, Option<u64>)>> {
+        Ok(self.get(key).await?.map(|value| (value, None)))
+    }
+
+    /// Lists the keys of the store starting with `prefix`. Stores that can't list their keys
+    /// return none.
+    async fn keys(&self, _prefix: &str) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
 }
 
 /// A struct that implements the StoreManager trait, storing data in an in-memory HashMap.
@@ -42,4 +60,16 @@ impl StoreManager for DefaultStore {
         self.store.write().await.remove(key);
         Ok(())
     }
+
+    #[instrument(skip(self))]
+    async fn keys(&self, prefix: &str) -> anyhow::Result<Vec<String>> {
+        Ok(self
+            .store
+            .read()
+            .await
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
 }
@@ -193,6 +193,13 @@ impl From<StoredClaims> for HashMap<String, String> {
     }
 }
 
+/// Serialize component claims in the format the host stores them in under `CLAIMS_{subject}` in
+/// the lattice data bucket
+pub fn serialize_component_claims(claims: jwt::Claims<jwt::Component>) -> anyhow::Result<Vec<u8>> {
+    let claims = StoredClaims::try_from(Claims::Component(claims))?;
+    serde_json::to_vec(&claims).context("failed to serialize claims")
+}
+
 #[allow(clippy::large_enum_variant)] // Without this clippy complains component is at least 0 bytes while provider is at least 280 bytes. That doesn't make sense
 pub(crate) enum Claims {
     Component(jwt::Claims<jwt::Component>),
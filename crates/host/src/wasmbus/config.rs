@@ -9,7 +9,7 @@ use tokio::sync::{
 };
 use tracing::{error, warn, Instrument};
 
-use crate::config::ConfigManager;
+use crate::config::{host_config_key, ConfigManager};
 
 type LockedConfig = Arc<RwLock<HashMap<String, String>>>;
 /// A cache of named config mapped to an existing receiver
@@ -19,6 +19,8 @@ type WatchCache = Arc<RwLock<HashMap<String, Receiver<HashMap<String, String>>>>
 struct ConfigReceiver {
     pub name: String,
     pub receiver: Receiver<HashMap<String, String>>,
+    /// Whether this receiver watches the host-scoped overrides of the named config
+    pub host_override: bool,
 }
 
 /// Helper struct that aborts on drop so we don't abort them when something is cloned in an arc. It
@@ -106,7 +108,11 @@ impl ConfigBundle {
         let changed_notifier = Arc::new(changed_notifier);
         let mut bundle = ConfigBundle {
             merged_config: Arc::default(),
-            config_names: receivers.iter().map(|r| r.name.clone()).collect(),
+            config_names: receivers
+                .iter()
+                .filter(|r| !r.host_override)
+                .map(|r| r.name.clone())
+                .collect(),
             changed_receiver,
             _changed_notifier: changed_notifier.clone(),
             _handles: Arc::new(AbortHandles {
@@ -117,7 +123,10 @@ impl ConfigBundle {
             Arc::new(receivers.iter().map(|r| r.receiver.clone()).collect());
         update_merge(&bundle.merged_config, &changed_notifier, &ordered_configs).await;
         // Move all the receivers into spawned tasks to update the config
-        for ConfigReceiver { name, mut receiver } in receivers {
+        for ConfigReceiver {
+            name, mut receiver, ..
+        } in receivers
+        {
             // SAFETY: We know we have the right amount of registrations because we just created
             // them using the len above
             let reg = registrations
@@ -195,6 +204,8 @@ impl ConfigBundle {
 pub struct BundleGenerator {
     store: Arc<dyn ConfigManager>,
     watch_cache: WatchCache,
+    /// ID of the host whose host-scoped config overrides are merged over named config
+    host_id: Option<String>,
 }
 
 impl BundleGenerator {
@@ -204,39 +215,79 @@ impl BundleGenerator {
         Self {
             store,
             watch_cache: Arc::default(),
+            host_id: None,
+        }
+    }
+
+    /// Merge the host-scoped config overrides of the given host over each named config in the
+    /// generated bundles
+    #[must_use]
+    pub fn with_host_id(self, host_id: impl Into<String>) -> Self {
+        Self {
+            host_id: Some(host_id.into()),
+            ..self
         }
     }
 
     /// Generate a new config bundle. Will return an error if any of the configs do not exist or if
     /// there was an error fetching the initial config
+    ///
+    /// If the generator was created with a host ID, the host-scoped overrides of each named config
+    /// are merged directly after it, so they take precedence over the lattice-wide values of the
+    /// same config but not over config named later in the list
     pub async fn generate(&self, config_names: Vec<String>) -> anyhow::Result<ConfigBundle> {
-        let receivers: Vec<ConfigReceiver> =
-            futures::future::join_all(config_names.into_iter().map(|name| self.get_receiver(name)))
-                .await
+        let receivers: Vec<ConfigReceiver> = futures::future::join_all(
+            config_names
                 .into_iter()
-                .collect::<anyhow::Result<_>>()?;
+                .map(|name| self.get_receivers(name)),
+        )
+        .await
+        .into_iter()
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect();
         Ok(ConfigBundle::new(receivers).await)
     }
 
-    async fn get_receiver(&self, name: String) -> anyhow::Result<ConfigReceiver> {
+    async fn get_receivers(&self, name: String) -> anyhow::Result<Vec<ConfigReceiver>> {
+        let mut receivers = vec![self.get_receiver(name.clone(), false).await?];
+        if self.host_id.is_some() {
+            receivers.push(self.get_receiver(name, true).await?);
+        }
+        Ok(receivers)
+    }
+
+    async fn get_receiver(
+        &self,
+        name: String,
+        host_override: bool,
+    ) -> anyhow::Result<ConfigReceiver> {
+        let key = match (&self.host_id, host_override) {
+            (Some(host_id), true) => host_config_key(host_id, &name),
+            _ => name.clone(),
+        };
         // First check the cache to see if we already have a receiver for this config
-        if let Some(receiver) = self.watch_cache.read().await.get(&name) {
+        if let Some(receiver) = self.watch_cache.read().await.get(&key) {
             return Ok(ConfigReceiver {
                 name,
                 receiver: receiver.clone(),
+                host_override,
             });
         }
 
-        let receiver = self
-            .store
-            .watch(&name)
-            .await
-            .context(format!("error setting up watcher for {name}"))?;
-        self.watch_cache
-            .write()
-            .await
-            .insert(name.clone(), receiver.clone());
-        Ok(ConfigReceiver { name, receiver })
+        let receiver = if host_override {
+            self.store.watch_optional(&key).await
+        } else {
+            self.store.watch(&key).await
+        }
+        .context(format!("error setting up watcher for {key}"))?;
+        self.watch_cache.write().await.insert(key, receiver.clone());
+        Ok(ConfigReceiver {
+            name,
+            receiver,
+            host_override,
+        })
     }
 }
 
@@ -279,14 +330,17 @@ mod tests {
             ConfigReceiver {
                 name: "foo".to_string(),
                 receiver: foo_rx,
+                host_override: false,
             },
             ConfigReceiver {
                 name: "bar".to_string(),
                 receiver: bar_rx,
+                host_override: false,
             },
             ConfigReceiver {
                 name: "baz".to_string(),
                 receiver: baz_rx,
+                host_override: false,
             },
         ])
         .await;
@@ -349,4 +403,60 @@ mod tests {
             ]),
         );
     }
+
+    #[tokio::test]
+    async fn test_config_bundle_host_override() {
+        let (_foo_tx, foo_rx) = watch::channel(HashMap::from([
+            ("foo".to_string(), "bar".to_string()),
+            ("cache".to_string(), "small".to_string()),
+        ]));
+        let (override_tx, override_rx) = watch::channel(HashMap::new());
+        let (_bar_tx, bar_rx) =
+            watch::channel(HashMap::from([("foo".to_string(), "baz".to_string())]));
+
+        let mut bundle = ConfigBundle::new(vec![
+            ConfigReceiver {
+                name: "foo".to_string(),
+                receiver: foo_rx,
+                host_override: false,
+            },
+            ConfigReceiver {
+                name: "foo".to_string(),
+                receiver: override_rx,
+                host_override: true,
+            },
+            ConfigReceiver {
+                name: "bar".to_string(),
+                receiver: bar_rx,
+                host_override: false,
+            },
+        ])
+        .await;
+
+        // Host overrides should not show up as separate named config
+        assert_eq!(
+            bundle.config_names(),
+            &vec!["foo".to_string(), "bar".to_string()]
+        );
+        let _ = tokio::time::timeout(Duration::from_millis(50), bundle.changed())
+            .await
+            .expect("Should have received a config");
+
+        // Host overrides win over the named config, but not over config named later
+        override_tx.send_replace(HashMap::from([
+            ("foo".to_string(), "host".to_string()),
+            ("cache".to_string(), "large".to_string()),
+        ]));
+        let conf = tokio::time::timeout(Duration::from_millis(50), bundle.changed())
+            .await
+            .expect("conf should have been present")
+            .expect("Should have received a config");
+        assert_eq!(
+            *conf,
+            HashMap::from([
+                ("foo".to_string(), "baz".to_string()),
+                ("cache".to_string(), "large".to_string())
+            ]),
+        );
+    }
 }
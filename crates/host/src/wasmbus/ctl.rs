@@ -1,32 +1,50 @@
+use core::future::Future;
 use core::sync::atomic::Ordering;
 
 use std::collections::btree_map::Entry as BTreeMapEntry;
-use std::collections::{hash_map, HashMap};
+use std::collections::{hash_map, BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{anyhow, bail, Context as _};
+use anyhow::{anyhow, bail, ensure, Context as _};
 use bytes::Bytes;
 use futures::join;
 use serde_json::json;
+use sysinfo::System;
 use tokio::spawn;
-use tokio::time::Instant;
+use tokio::sync::{oneshot, RwLock};
+use tokio::time::{sleep, Instant};
 use tracing::{debug, error, info, instrument, trace, warn};
 use wasmcloud_control_interface::{
-    ComponentAuctionAck, ComponentAuctionRequest, CtlResponse,
-    DeleteInterfaceLinkDefinitionRequest, HostInventory, HostLabel, HostLabelIdentifier, Link,
-    ProviderAuctionAck, ProviderAuctionRequest, RegistryCredential, ScaleComponentCommand,
-    StartProviderCommand, StopHostCommand, StopProviderCommand, UpdateComponentCommand,
+    top_memory_consumers, AuctionHints, CleanupHostDataCommand, ComponentAuctionAck,
+    ComponentAuctionRequest, ComponentMemoryUsage, ComponentProfile, ConfigNames, ConfigsByName,
+    CtlResponse, DataCategory, DataDirUsage, DeleteInterfaceLinkDefinitionRequest,
+    DrainHostCommand, HostInventory, HostInventoryPage, HostLabel, HostLabelIdentifier,
+    HostLabelIdentifiers, HostLabels, InventoryPageRequest, Link, PrefetchImagesCommand,
+    ProfileComponentCommand, ProviderAuctionAck, ProviderAuctionRequest, PutLinkRequest,
+    RegistryCredential, ResourceRequirements, RevisionQuery, ScaleComponentCommand,
+    StartProviderCommand, StopComponentsCommand, StopHostCommand, StopProviderCommand,
+    TopMemoryQuery, UpdateComponentCommand, UpdateHostTracingCommand, UpdateProviderConfigCommand,
 };
+use wasmcloud_core::logging::Level;
 use wasmcloud_core::shutdown_subject;
 use wasmcloud_tracing::context::TraceContextInjector;
 
+use crate::config::host_config_key;
 use crate::registry::RegistryCredentialExt;
+use crate::wasmbus::providers::ConfigNamesUpdate;
 use crate::wasmbus::{
-    human_friendly_uptime, injector_to_headers, Annotations, Claims, Host, Provider, StoredClaims,
+    human_friendly_uptime, injector_to_headers, termination_grace_period, Annotations, Claims,
+    Features, Host, Provider, StoredClaims, STOP_REASON_FORCED, STOP_REASON_GRACEFUL,
 };
 use crate::ResourceRef;
 
+/// Interval at which a draining host checks whether its workloads have been rescheduled
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest time a component can be profiled for with a single request
+const MAX_PROFILE_DURATION: Duration = Duration::from_secs(600);
+
 /// Implementation for the server-side handling of control interface requests.
 ///
 /// This trait is not a part of the `wasmcloud_control_interface` crate yet to allow
@@ -51,6 +69,34 @@ pub trait ControlInterfaceServer: Send + Sync {
     /// or failure.
     async fn handle_stop_host(&self, request: StopHostCommand) -> anyhow::Result<CtlResponse<()>>;
 
+    /// Handle a request to drain the host. The host should stop accepting new workloads and return
+    /// a response indicating success or failure before the drain completes.
+    async fn handle_drain_host(
+        self: Arc<Self>,
+        request: DrainHostCommand,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
+    /// Handle a request to prefetch images into the artifact cache of the host. This method should
+    /// return a response indicating success or failure before the images are fetched.
+    async fn handle_prefetch_images(
+        self: Arc<Self>,
+        request: PrefetchImagesCommand,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
+    /// Handle a request to remove data from the data directory of the host. This method should
+    /// return the disk usage of the data directory after the cleanup.
+    async fn handle_cleanup_host_data(
+        self: Arc<Self>,
+        request: CleanupHostDataCommand,
+    ) -> anyhow::Result<CtlResponse<DataDirUsage>>;
+
+    /// Handle a request to change the level and sampler of the traces emitted by the host. This
+    /// method should return a response indicating success or failure.
+    async fn handle_update_host_tracing(
+        self: Arc<Self>,
+        request: UpdateHostTracingCommand,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
     /// Handle a request to scale a component. This method should return a response indicating success
     /// or failure.
     async fn handle_scale_component(
@@ -65,6 +111,27 @@ pub trait ControlInterfaceServer: Send + Sync {
         request: UpdateComponentCommand,
     ) -> anyhow::Result<CtlResponse<()>>;
 
+    /// Handle a request to profile a component for a period of time. This method should return a
+    /// response containing the profile once it is complete.
+    async fn handle_profile_component(
+        &self,
+        request: ProfileComponentCommand,
+    ) -> anyhow::Result<CtlResponse<ComponentProfile>>;
+
+    /// Handle a query for the components holding the most linear memory. This method should return
+    /// a response containing their memory usage, largest first.
+    async fn handle_top_memory_consumers(
+        &self,
+        request: TopMemoryQuery,
+    ) -> anyhow::Result<CtlResponse<Vec<ComponentMemoryUsage>>>;
+
+    /// Handle a request to scale all components matching an annotation selector to zero. This
+    /// method should return a response containing the IDs of the stopped components.
+    async fn handle_stop_components(
+        &self,
+        request: StopComponentsCommand,
+    ) -> anyhow::Result<CtlResponse<Vec<String>>>;
+
     /// Handle a request to start a provider. This method should return a response indicating success
     /// or failure.
     async fn handle_start_provider(
@@ -72,6 +139,13 @@ pub trait ControlInterfaceServer: Send + Sync {
         request: StartProviderCommand,
     ) -> anyhow::Result<Option<CtlResponse<()>>>;
 
+    /// Handle a request to change the named configs consumed by a running provider. This method
+    /// should return a response indicating success or failure.
+    async fn handle_update_provider_config(
+        &self,
+        request: UpdateProviderConfigCommand,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
     /// Handle a request to stop a provider. This method should return a response indicating success
     /// or failure.
     async fn handle_stop_provider(
@@ -83,22 +157,99 @@ pub trait ControlInterfaceServer: Send + Sync {
     /// the host inventory.
     async fn handle_inventory(&self) -> anyhow::Result<CtlResponse<HostInventory>>;
 
+    /// Handle a request to get a page of the host inventory. This method should return a response
+    /// containing the requested slice of the components and providers running on the host.
+    async fn handle_inventory_page(
+        &self,
+        request: InventoryPageRequest,
+    ) -> anyhow::Result<CtlResponse<HostInventoryPage>>;
+
     /// Handle a request to get the claims for all components and providers. This method should return
     /// a response containing the claims.
     async fn handle_claims(&self) -> anyhow::Result<CtlResponse<Vec<HashMap<String, String>>>>;
 
+    /// Handle a request to get the claims for all components and providers, reflecting at least the
+    /// revision of the lattice data bucket requested by `query`.
+    ///
+    /// The default implementation ignores `query` and returns a response without a revision, which
+    /// clients treat as not supporting reads pinned to a revision.
+    async fn handle_claims_at_revision(
+        &self,
+        query: RevisionQuery,
+    ) -> anyhow::Result<CtlResponse<Vec<HashMap<String, String>>>> {
+        let _ = query;
+        self.handle_claims().await
+    }
+
     /// Handle a request to get the links for all components. This method should return a response containing
     /// the links.
     async fn handle_links(&self) -> anyhow::Result<Vec<u8>>;
 
+    /// Handle a request to get the links for all components, reflecting at least the revision of
+    /// the lattice data bucket requested by `query`.
+    ///
+    /// The default implementation ignores `query`, like [`Self::handle_claims_at_revision`].
+    async fn handle_links_at_revision(&self, query: RevisionQuery) -> anyhow::Result<Vec<u8>> {
+        let _ = query;
+        self.handle_links().await
+    }
+
     /// Handle a request to get the configuration for a specific key. This method should return a response
     /// containing the configuration.
     async fn handle_config_get(&self, config_name: &str) -> anyhow::Result<Vec<u8>>;
 
+    /// Handle a request to get the configuration for a specific key, reflecting at least the
+    /// revision of the config bucket requested by `query`.
+    ///
+    /// The default implementation ignores `query`, like [`Self::handle_claims_at_revision`].
+    async fn handle_config_get_at_revision(
+        &self,
+        config_name: &str,
+        query: RevisionQuery,
+    ) -> anyhow::Result<Vec<u8>> {
+        let _ = query;
+        self.handle_config_get(config_name).await
+    }
+
+    /// Handle a request to get several configurations at once. This method should return a response
+    /// containing an entry for every requested name, which is `None` if the configuration does not
+    /// exist.
+    async fn handle_config_get_many(
+        &self,
+        request: ConfigNames,
+    ) -> anyhow::Result<CtlResponse<ConfigsByName>>;
+
     /// Handle a request to delete the configuration for a specific key. This method should return a response
     /// indicating success or failure.
     async fn handle_config_delete(&self, config_name: &str) -> anyhow::Result<CtlResponse<()>>;
 
+    /// Handle a request to get the configuration of a specific key scoped to a host. This method
+    /// should return a response containing the host-scoped configuration, or a successful response
+    /// without data if the host has no overrides for the configuration.
+    async fn handle_host_config_get(
+        &self,
+        host_id: &str,
+        config_name: &str,
+    ) -> anyhow::Result<Vec<u8>>;
+
+    /// Handle a request to put the configuration of a specific key scoped to a host. The values are
+    /// merged over the lattice-wide configuration of the same name on that host. This method should
+    /// return a response indicating success or failure.
+    async fn handle_host_config_put(
+        &self,
+        host_id: &str,
+        config_name: &str,
+        data: Bytes,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
+    /// Handle a request to delete the configuration of a specific key scoped to a host. This method
+    /// should return a response indicating success or failure.
+    async fn handle_host_config_delete(
+        &self,
+        host_id: &str,
+        config_name: &str,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
     /// Handle a request to put a label on the host. This method should return a response indicating success
     /// or failure.
     async fn handle_label_put(
@@ -115,9 +266,26 @@ pub trait ControlInterfaceServer: Send + Sync {
         host_id: &str,
     ) -> anyhow::Result<CtlResponse<()>>;
 
-    /// Handle a request to put a link on a component. This method should return a response indicating success
-    /// or failure.
-    async fn handle_link_put(&self, request: Link) -> anyhow::Result<CtlResponse<()>>;
+    /// Handle a request to put several labels on the host at once. Either all labels must be applied
+    /// or none. This method should return a response indicating success or failure.
+    async fn handle_labels_put(
+        &self,
+        request: HostLabels,
+        host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
+    /// Handle a request to delete several labels from the host at once. This method should return a
+    /// response indicating success or failure.
+    async fn handle_labels_del(
+        &self,
+        request: HostLabelIdentifiers,
+        host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>>;
+
+    /// Handle a request to put a link on a component. Unless the request is forced, a link that conflicts
+    /// with an existing link to a different target must be rejected. This method should return a response
+    /// indicating success or failure.
+    async fn handle_link_put(&self, request: PutLinkRequest) -> anyhow::Result<CtlResponse<()>>;
 
     /// Handle a request to delete a link from a component. This method should return a response indicating success
     /// or failure.
@@ -166,7 +334,21 @@ impl ControlInterfaceServer for Host {
             "handling auction for component"
         );
 
+        if self.draining.load(Ordering::Relaxed) {
+            debug!(component_id, "declining component auction while draining");
+            return Ok(None);
+        }
+
         let host_labels = self.labels.read().await;
+        if let Some(reason) = unmet_requirement(
+            request.requirements(),
+            &host_labels,
+            &self.experimental_features,
+            self.components.read().await.len(),
+        ) {
+            debug!(component_id, reason, "declining component auction");
+            return Ok(None);
+        }
         let constraints_satisfied = constraints
             .iter()
             .all(|(k, v)| host_labels.get(k).is_some_and(|hv| hv == v));
@@ -174,13 +356,21 @@ impl ControlInterfaceServer for Host {
 
         // This host can run the component if all constraints are satisfied and the component is not already running
         if constraints_satisfied && !component_id_running {
+            let hints = auction_hints(
+                self.components.read().await.len(),
+                self.providers.read().await.len(),
+                self.start_at.elapsed(),
+                &host_labels,
+            );
             Ok(Some(CtlResponse::ok(
-                ComponentAuctionAck::from_component_host_and_constraints(
-                    component_ref,
-                    component_id,
-                    &self.host_key.public_key(),
-                    constraints.clone(),
-                ),
+                ComponentAuctionAck::builder()
+                    .component_ref(component_ref.into())
+                    .component_id(component_id.into())
+                    .constraints(constraints.clone())
+                    .host_id(self.host_key.public_key())
+                    .hints(hints)
+                    .build()
+                    .map_err(|e| anyhow!("failed to build component auction ack: {e}"))?,
             )))
         } else {
             Ok(None)
@@ -203,19 +393,40 @@ impl ControlInterfaceServer for Host {
             "handling auction for provider"
         );
 
+        if self.draining.load(Ordering::Relaxed) {
+            debug!(provider_id, "declining provider auction while draining");
+            return Ok(None);
+        }
+
         let host_labels = self.labels.read().await;
+        if let Some(reason) = unmet_requirement(
+            request.requirements(),
+            &host_labels,
+            &self.experimental_features,
+            self.providers.read().await.len(),
+        ) {
+            debug!(provider_id, reason, "declining provider auction");
+            return Ok(None);
+        }
         let constraints_satisfied = constraints
             .iter()
             .all(|(k, v)| host_labels.get(k).is_some_and(|hv| hv == v));
         let providers = self.providers.read().await;
         let provider_running = providers.contains_key(provider_id);
         if constraints_satisfied && !provider_running {
+            let hints = auction_hints(
+                self.components.read().await.len(),
+                providers.len(),
+                self.start_at.elapsed(),
+                &host_labels,
+            );
             Ok(Some(CtlResponse::ok(
                 ProviderAuctionAck::builder()
                     .provider_ref(provider_ref.into())
                     .provider_id(provider_id.into())
                     .constraints(constraints.clone())
                     .host_id(self.host_key.public_key())
+                    .hints(hints)
                     .build()
                     .map_err(|e| anyhow!("failed to build provider auction ack: {e}"))?,
             )))
@@ -227,8 +438,17 @@ impl ControlInterfaceServer for Host {
     #[instrument(level = "debug", skip_all)]
     async fn handle_stop_host(&self, request: StopHostCommand) -> anyhow::Result<CtlResponse<()>> {
         let timeout = request.timeout();
+        let decommission = request.decommission();
+
+        info!(?timeout, decommission, "handling stop host");
 
-        info!(?timeout, "handling stop host");
+        if decommission {
+            let record = self
+                .decommission()
+                .await
+                .context("failed to decommission host")?;
+            info!(removed_data = ?record.removed_data(), "decommissioned host");
+        }
 
         self.ready.store(false, Ordering::Relaxed);
         self.heartbeat.abort();
@@ -240,6 +460,220 @@ impl ControlInterfaceServer for Host {
             "successfully handled stop host".into(),
         ))
     }
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_drain_host(
+        self: Arc<Self>,
+        request: DrainHostCommand,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let timeout_ms = request.options().timeout_ms();
+        let force_stop = request.options().force_stop();
+
+        info!(?timeout_ms, force_stop, "handling drain host");
+
+        if self.draining.swap(true, Ordering::Relaxed) {
+            return Ok(CtlResponse::error("host is already draining"));
+        }
+        let host_id = self.host_key.public_key();
+        self.event_publisher
+            .publish_event(
+                "host_draining",
+                crate::event::host_draining(&host_id, timeout_ms, force_stop),
+            )
+            .await?;
+
+        let deadline = timeout_ms
+            .and_then(|timeout| Instant::now().checked_add(Duration::from_millis(timeout)));
+        spawn(async move {
+            // Wait for the scheduler to move workloads to other hosts, which scales them down here
+            loop {
+                let remaining =
+                    self.components.read().await.len() + self.providers.read().await.len();
+                if remaining == 0 || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    break;
+                }
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+
+            let mut stopped_components = Vec::new();
+            let mut stopped_providers = Vec::new();
+            if force_stop {
+                let components: Vec<_> = self.components.write().await.drain().collect();
+                for (component_id, component) in components {
+                    if let Err(err) = self.stop_component(&component, &host_id).await {
+                        error!(%component_id, ?err, "failed to stop component while draining");
+                        continue;
+                    }
+                    if let Err(err) = self
+                        .event_publisher
+                        .publish_event(
+                            "component_scaled",
+                            crate::event::component_scaled(
+                                component.claims(),
+                                &component.annotations,
+                                &host_id,
+                                0_usize,
+                                &component.image_reference,
+                                &component.id,
+                            ),
+                        )
+                        .await
+                    {
+                        error!(%component_id, ?err, "failed to publish component scaled event");
+                    }
+                    stopped_components.push(component_id.to_string());
+                }
+
+                let provider_ids: Vec<_> = self.providers.read().await.keys().cloned().collect();
+                for provider_id in provider_ids {
+                    let stop = StopProviderCommand::builder()
+                        .host_id(&host_id)
+                        .provider_id(&provider_id)
+                        .build()
+                        .map_err(|e| anyhow!(e));
+                    match stop {
+                        Ok(stop) => match <Self as ControlInterfaceServer>::handle_stop_provider(
+                            &self, stop,
+                        )
+                        .await
+                        {
+                            Ok(res) if res.succeeded() => stopped_providers.push(provider_id),
+                            Ok(res) => {
+                                warn!(
+                                    provider_id,
+                                    message = res.message(),
+                                    "failed to stop provider while draining"
+                                );
+                            }
+                            Err(err) => {
+                                error!(provider_id, ?err, "failed to stop provider while draining");
+                            }
+                        },
+                        Err(err) => {
+                            error!(provider_id, ?err, "failed to build stop provider command")
+                        }
+                    }
+                }
+            }
+
+            let remaining = self.components.read().await.len() + self.providers.read().await.len();
+            info!(
+                stopped_components = stopped_components.len(),
+                stopped_providers = stopped_providers.len(),
+                remaining,
+                "host drained"
+            );
+            if let Err(err) = self
+                .event_publisher
+                .publish_event(
+                    "host_drained",
+                    crate::event::host_drained(
+                        &host_id,
+                        &stopped_components,
+                        &stopped_providers,
+                        remaining,
+                    ),
+                )
+                .await
+            {
+                error!(?err, "failed to publish host drained event");
+            }
+        });
+
+        Ok(CtlResponse::<()>::success(
+            "successfully started draining host".into(),
+        ))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_prefetch_images(
+        self: Arc<Self>,
+        request: PrefetchImagesCommand,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let image_refs = request.image_refs().to_vec();
+
+        info!(?image_refs, "handling prefetch images");
+
+        if image_refs.is_empty() {
+            return Ok(CtlResponse::error("no images to prefetch"));
+        }
+        self.prefetch_images(image_refs).await;
+
+        Ok(CtlResponse::<()>::success(
+            "successfully queued images for prefetch".into(),
+        ))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_cleanup_host_data(
+        self: Arc<Self>,
+        request: CleanupHostDataCommand,
+    ) -> anyhow::Result<CtlResponse<DataDirUsage>> {
+        let categories = request.categories();
+
+        info!(?categories, "handling cleanup host data");
+
+        let Some(data_dir) = &self.data_dir else {
+            bail!("host does not manage a data directory");
+        };
+        let freed = data_dir
+            .cleanup(categories)
+            .await
+            .context("failed to clean up data directory")?;
+        debug!(freed, "cleaned up data directory");
+        if categories.is_empty() || categories.contains(&DataCategory::Artifacts) {
+            // Keep the images that were requested to be prefetched warm
+            self.refresh_prefetched_images().await;
+        }
+        let usage = data_dir
+            .usage()
+            .await
+            .context("failed to compute data directory usage")?;
+        Ok(CtlResponse::ok(usage))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_update_host_tracing(
+        self: Arc<Self>,
+        request: UpdateHostTracingCommand,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        info!(
+            trace_level = ?request.trace_level(),
+            traces_sampler = ?request.traces_sampler(),
+            traces_sampler_arg = ?request.traces_sampler_arg(),
+            "handling update host tracing"
+        );
+
+        let Some(handle) = wasmcloud_tracing::reload_handle() else {
+            bail!("host tracing cannot be updated at runtime");
+        };
+        let mut otel_config = handle.otel_config();
+        if let Some(trace_level) = request.trace_level() {
+            let Ok(level) = serde_json::from_value::<Level>(serde_json::Value::String(
+                trace_level.to_ascii_lowercase(),
+            )) else {
+                return Ok(CtlResponse::error(&format!(
+                    "invalid trace level `{trace_level}`"
+                )));
+            };
+            otel_config.trace_level = level;
+        }
+        if let Some(traces_sampler) = request.traces_sampler() {
+            otel_config.traces_sampler = Some(traces_sampler.into());
+        }
+        if request.traces_sampler().is_some() || request.traces_sampler_arg().is_some() {
+            otel_config.traces_sampler_arg = request.traces_sampler_arg().map(Into::into);
+        }
+        if let Err(err) = handle.update(&otel_config) {
+            return Ok(CtlResponse::error(&format!(
+                "failed to update host tracing: {err:#}"
+            )));
+        }
+
+        Ok(CtlResponse::<()>::success(
+            "successfully updated host tracing".into(),
+        ))
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn handle_scale_component(
         self: Arc<Self>,
@@ -282,6 +716,17 @@ impl ControlInterfaceServer for Host {
                 .unwrap_or_else(|| (None, false))
         };
 
+        // A draining host may still scale down or update its components, but not start new ones
+        if original_ref.is_none() && max_instances > 0 && self.draining.load(Ordering::Relaxed) {
+            return Ok(CtlResponse::error(
+                "host is draining and does not accept new components",
+            ));
+        }
+
+        if let Err(e) = self.verify_pinned_config(request.pinned_config()).await {
+            return Ok(CtlResponse::error(&e.to_string()));
+        }
+
         let mut perform_post_update: bool = false;
         let message = match (allow_update, original_ref, ref_changed) {
             // Updates are not allowed, original ref changed
@@ -399,6 +844,147 @@ impl ControlInterfaceServer for Host {
         Ok(CtlResponse::<()>::success(message))
     }
 
+    #[instrument(level = "debug", skip_all, fields(component_id = %request.component_id()))]
+    async fn handle_profile_component(
+        &self,
+        request: ProfileComponentCommand,
+    ) -> anyhow::Result<CtlResponse<ComponentProfile>> {
+        let duration = request.duration();
+
+        info!(?duration, "handling profile component");
+
+        ensure!(
+            !duration.is_zero() && duration <= MAX_PROFILE_DURATION,
+            "profile duration must be positive and at most {}s",
+            MAX_PROFILE_DURATION.as_secs()
+        );
+        let Some(component) = self
+            .components
+            .read()
+            .await
+            .get(request.component_id())
+            .cloned()
+        else {
+            bail!(
+                "component `{}` is not running on this host",
+                request.component_id()
+            );
+        };
+
+        let memory = component.memory_usage();
+        memory.reset_peak();
+        let allocated = memory.allocated_bytes();
+        let cpu_time = component.cpu_time().get();
+        component.profile.start()?;
+        sleep(duration).await;
+        let invocations = component.profile.finish();
+
+        Ok(CtlResponse::ok(
+            ComponentProfile::builder()
+                .component_id(request.component_id().into())
+                .host_id(self.host_key.public_key())
+                .duration(duration)
+                .invocations(invocations)
+                .cpu_time(component.cpu_time().get().saturating_sub(cpu_time))
+                .memory_allocated_bytes(memory.allocated_bytes().saturating_sub(allocated))
+                .peak_memory_bytes(memory.peak_bytes())
+                .build(),
+        ))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_top_memory_consumers(
+        &self,
+        request: TopMemoryQuery,
+    ) -> anyhow::Result<CtlResponse<Vec<ComponentMemoryUsage>>> {
+        trace!(limit = request.limit(), "handling top memory consumers");
+
+        let components = self.components.read().await;
+        let usages = components.iter().map(|(id, component)| {
+            let memory = component.memory_usage();
+            ComponentMemoryUsage::builder()
+                .component_id(id.to_string())
+                .image_ref(component.image_reference.to_string())
+                .resident_bytes(memory.resident_bytes())
+                .peak_bytes(memory.peak_bytes())
+                .running_instances(memory.instances())
+                .max_instances(component.max_instances.get().try_into().unwrap_or(u32::MAX))
+                .build()
+        });
+        Ok(CtlResponse::ok(top_memory_consumers(
+            usages,
+            request.limit().try_into().unwrap_or(usize::MAX),
+        )))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_stop_components(
+        &self,
+        request: StopComponentsCommand,
+    ) -> anyhow::Result<CtlResponse<Vec<String>>> {
+        let annotations = request.annotations();
+
+        info!(?annotations, "handling stop components");
+
+        ensure!(
+            !annotations.is_empty(),
+            "at least one annotation is required to select components to stop"
+        );
+        let host_id = self.host_key.public_key();
+        let selected: Vec<_> = self
+            .components
+            .read()
+            .await
+            .iter()
+            .filter(|(_, component)| request.matches(&component.annotations))
+            .map(|(component_id, component)| (component_id.clone(), Arc::clone(component)))
+            .collect();
+
+        let (stopped, failed) = stop_selected_components(&self.components, selected, |component| {
+            let host_id = &host_id;
+            async move {
+                self.stop_component(&component, host_id).await?;
+                if let Err(err) = self
+                    .event_publisher
+                    .publish_event(
+                        "component_scaled",
+                        crate::event::component_scaled(
+                            component.claims(),
+                            &component.annotations,
+                            host_id,
+                            0_usize,
+                            &component.image_reference,
+                            &component.id,
+                        ),
+                    )
+                    .await
+                {
+                    error!(
+                        component_id = %component.id,
+                        ?err,
+                        "failed to publish component scaled event"
+                    );
+                }
+                Ok(())
+            }
+        })
+        .await;
+
+        info!(?stopped, "stopped components matching annotations");
+        if failed.is_empty() {
+            return Ok(CtlResponse::ok(stopped));
+        }
+        let failures = failed
+            .iter()
+            .map(|(component_id, err)| {
+                error!(%component_id, ?err, "failed to stop component");
+                format!("{component_id}: {err:#}")
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        Ok(CtlResponse::ok(stopped).with_error(format!("failed to stop components: {failures}")))
+    }
+
     // TODO(#1548): With component IDs, new component references, configuration, etc, we're going to need to do some
     // design thinking around how update component should work. Should it be limited to a single host or latticewide?
     // Should it also update configuration, or is that separate? Should scaling be done via an update?
@@ -470,6 +1056,11 @@ impl ControlInterfaceServer for Host {
         self: Arc<Self>,
         request: StartProviderCommand,
     ) -> anyhow::Result<Option<CtlResponse<()>>> {
+        if self.draining.load(Ordering::Relaxed) {
+            return Ok(Some(CtlResponse::error(
+                "host is draining and does not accept new providers",
+            )));
+        }
         if self
             .providers
             .read()
@@ -501,6 +1092,10 @@ impl ControlInterfaceServer for Host {
             }
         }
 
+        if let Err(e) = self.verify_pinned_config(request.pinned_config()).await {
+            return Ok(Some(CtlResponse::error(&e.to_string())));
+        }
+
         // NOTE: We log at info since starting providers can take a while
         info!(
             provider_ref = request.provider_ref(),
@@ -549,6 +1144,67 @@ impl ControlInterfaceServer for Host {
         )))
     }
 
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_update_provider_config(
+        &self,
+        request: UpdateProviderConfigCommand,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let provider_id = request.provider_id();
+        let config = request.config();
+
+        debug!(provider_id, ?config, "handling update provider config");
+
+        if let Err(e) = self.validate_config(config).await {
+            return Ok(CtlResponse::error(&e.to_string()));
+        }
+        let config_updates = {
+            let providers = self.providers.read().await;
+            let Some(provider) = providers.get(provider_id) else {
+                return Ok(CtlResponse::error("provider with that ID is not running"));
+            };
+            let Some(config_updates) = provider.config_updates.clone() else {
+                return Ok(CtlResponse::error(
+                    "builtin providers do not support configuration updates",
+                ));
+            };
+            config_updates
+        };
+        // Only acknowledge the update once the provider supervisor delivered the new config
+        let (delivered_tx, delivered_rx) = oneshot::channel();
+        let update = ConfigNamesUpdate {
+            names: config.clone(),
+            delivered: delivered_tx,
+        };
+        if config_updates.send(update).await.is_err() {
+            return Ok(CtlResponse::error("provider is no longer running"));
+        }
+        match delivered_rx.await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                return Ok(CtlResponse::error(&format!(
+                    "failed to deliver provider config: {e:#}"
+                )));
+            }
+            Err(_) => return Ok(CtlResponse::error("provider is no longer running")),
+        }
+
+        info!(provider_id, ?config, "updated provider config");
+        self.event_publisher
+            .publish_event(
+                "provider_config_updated",
+                crate::event::provider_config_updated(
+                    self.host_key.public_key(),
+                    provider_id,
+                    config,
+                ),
+            )
+            .await?;
+
+        Ok(CtlResponse::<()>::success(
+            "successfully updated provider config".into(),
+        ))
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn handle_stop_provider(
         &self,
@@ -578,16 +1234,19 @@ impl ControlInterfaceServer for Host {
         // prevents restarting the provider but does not stop the provider process.
         shutdown.store(true, Ordering::Relaxed);
 
-        // Send a request to the provider, requesting a graceful shutdown
+        // Send a request to the provider, requesting a graceful shutdown. The provider responds once
+        // it finished its in-flight work, which it is given the termination grace period to do.
+        let grace_period =
+            termination_grace_period(annotations).or(self.host_config.provider_shutdown_delay);
         let req = serde_json::to_vec(&json!({ "host_id": host_id }))
             .context("failed to encode provider stop request")?;
         let req = async_nats::Request::new()
             .payload(req.into())
-            .timeout(self.host_config.provider_shutdown_delay)
+            .timeout(grace_period)
             .headers(injector_to_headers(
                 &TraceContextInjector::default_with_span(),
             ));
-        if let Err(e) = self
+        let reason = if let Err(e) = self
             .rpc_nats
             .send_request(
                 shutdown_subject(&self.host_config.lattice, provider_id, "default"),
@@ -598,11 +1257,15 @@ impl ControlInterfaceServer for Host {
             warn!(
                 ?e,
                 provider_id,
+                ?grace_period,
                 "provider did not gracefully shut down in time, shutting down forcefully"
             );
             // NOTE: The provider child process is spawned with [tokio::process::Command::kill_on_drop],
             // so dropping the task will send a SIGKILL to the provider process.
-        }
+            STOP_REASON_FORCED
+        } else {
+            STOP_REASON_GRACEFUL
+        };
 
         // Stop the provider and health check / config changes tasks
         tasks.abort_all();
@@ -611,7 +1274,7 @@ impl ControlInterfaceServer for Host {
         self.event_publisher
             .publish_event(
                 "provider_stopped",
-                crate::event::provider_stopped(annotations, host_id, provider_id, "stop"),
+                crate::event::provider_stopped(annotations, host_id, provider_id, reason),
             )
             .await?;
         Ok(CtlResponse::<()>::success(
@@ -626,9 +1289,30 @@ impl ControlInterfaceServer for Host {
         Ok(CtlResponse::ok(inventory))
     }
 
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_inventory_page(
+        &self,
+        request: InventoryPageRequest,
+    ) -> anyhow::Result<CtlResponse<HostInventoryPage>> {
+        trace!(cursor = ?request.cursor(), limit = request.limit(), "handling inventory page");
+        let inventory = self.inventory().await;
+        Ok(CtlResponse::ok(inventory.into_page(&request)))
+    }
+
     #[instrument(level = "trace", skip_all)]
     async fn handle_claims(&self) -> anyhow::Result<CtlResponse<Vec<HashMap<String, String>>>> {
-        trace!("handling claims");
+        self.handle_claims_at_revision(RevisionQuery::default())
+            .await
+    }
+
+    #[instrument(level = "trace", skip_all)]
+    async fn handle_claims_at_revision(
+        &self,
+        query: RevisionQuery,
+    ) -> anyhow::Result<CtlResponse<Vec<HashMap<String, String>>>> {
+        trace!(min_revision = query.min_revision(), "handling claims");
+
+        let revision = self.wait_for_data_revision(query.min_revision()).await?;
 
         let (component_claims, provider_claims) =
             join!(self.component_claims.read(), self.provider_claims.read());
@@ -639,30 +1323,56 @@ impl ControlInterfaceServer for Host {
             .flat_map(TryFrom::try_from)
             .collect();
 
-        Ok(CtlResponse::ok(
-            claims.into_iter().map(std::convert::Into::into).collect(),
-        ))
+        Ok(
+            CtlResponse::ok(claims.into_iter().map(std::convert::Into::into).collect())
+                .with_revision(revision),
+        )
     }
 
     #[instrument(level = "trace", skip_all)]
-    // TODO: Vec<&Link> return?
     async fn handle_links(&self) -> anyhow::Result<Vec<u8>> {
-        trace!("handling links");
+        self.handle_links_at_revision(RevisionQuery::default())
+            .await
+    }
 
+    #[instrument(level = "trace", skip_all)]
+    // TODO: Vec<&Link> return?
+    async fn handle_links_at_revision(&self, query: RevisionQuery) -> anyhow::Result<Vec<u8>> {
+        trace!(min_revision = query.min_revision(), "handling links");
+
+        let revision = self.wait_for_data_revision(query.min_revision()).await?;
         let links = self.links.read().await;
         let links: Vec<&Link> = links.values().flatten().collect();
-        let res =
-            serde_json::to_vec(&CtlResponse::ok(links)).context("failed to serialize response")?;
+        let res = serde_json::to_vec(&CtlResponse::ok(links).with_revision(revision))
+            .context("failed to serialize response")?;
         Ok(res)
     }
 
     #[instrument(level = "trace", skip(self))]
     async fn handle_config_get(&self, config_name: &str) -> anyhow::Result<Vec<u8>> {
-        trace!(%config_name, "handling get config");
+        self.handle_config_get_at_revision(config_name, RevisionQuery::default())
+            .await
+    }
+
+    #[instrument(level = "trace", skip(self))]
+    async fn handle_config_get_at_revision(
+        &self,
+        config_name: &str,
+        query: RevisionQuery,
+    ) -> anyhow::Result<Vec<u8>> {
+        trace!(%config_name, min_revision = query.min_revision(), "handling get config");
+        // The revision of an entry is the revision of the bucket it was last written at, so the
+        // requested revision is compared to the bucket's, which any key read afterwards reflects
+        let revision = self.wait_for_config_revision(query.min_revision()).await?;
         if let Some(config_bytes) = self.config_store.get(config_name).await? {
             let config_map: HashMap<String, String> = serde_json::from_slice(&config_bytes)
                 .context("config data should be a map of string -> string")?;
-            serde_json::to_vec(&CtlResponse::ok(config_map)).map_err(anyhow::Error::from)
+            let response = CtlResponse::ok(config_map);
+            let response = match revision {
+                Some(revision) => response.with_revision(revision),
+                None => response,
+            };
+            serde_json::to_vec(&response).map_err(anyhow::Error::from)
         } else {
             serde_json::to_vec(&CtlResponse::<()>::success(
                 "Configuration not found".into(),
@@ -671,6 +1381,27 @@ impl ControlInterfaceServer for Host {
         }
     }
 
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_config_get_many(
+        &self,
+        request: ConfigNames,
+    ) -> anyhow::Result<CtlResponse<ConfigsByName>> {
+        trace!(names = ?request.names(), "handling get many configs");
+        let mut configs = ConfigsByName::with_capacity(request.names().len());
+        for name in request.names() {
+            let config = match self.config_store.get(name).await? {
+                Some(config_bytes) => Some(
+                    serde_json::from_slice::<HashMap<String, String>>(&config_bytes).with_context(
+                        || format!("config data of `{name}` should be a map of string -> string"),
+                    )?,
+                ),
+                None => None,
+            };
+            configs.insert(name.clone(), config);
+        }
+        Ok(CtlResponse::ok(configs))
+    }
+
     #[instrument(level = "debug", skip_all, fields(%config_name))]
     async fn handle_config_delete(&self, config_name: &str) -> anyhow::Result<CtlResponse<()>> {
         debug!("handle config entry deletion");
@@ -689,6 +1420,78 @@ impl ControlInterfaceServer for Host {
         ))
     }
 
+    #[instrument(level = "trace", skip(self))]
+    async fn handle_host_config_get(
+        &self,
+        host_id: &str,
+        config_name: &str,
+    ) -> anyhow::Result<Vec<u8>> {
+        trace!("handling get host config");
+        let key = host_config_key(host_id, config_name);
+        if let Some(config_bytes) = self.config_store.get(&key).await? {
+            let config_map: HashMap<String, String> = serde_json::from_slice(&config_bytes)
+                .context("config data should be a map of string -> string")?;
+            serde_json::to_vec(&CtlResponse::ok(config_map)).map_err(anyhow::Error::from)
+        } else {
+            serde_json::to_vec(&CtlResponse::<()>::success(
+                "Host configuration not found".into(),
+            ))
+            .map_err(anyhow::Error::from)
+        }
+    }
+
+    #[instrument(level = "debug", skip(self, data))]
+    async fn handle_host_config_put(
+        &self,
+        host_id: &str,
+        config_name: &str,
+        data: Bytes,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        debug!("handle host config entry put");
+        serde_json::from_slice::<HashMap<String, String>>(&data)
+            .context("config data should be a map of string -> string")?;
+        self.config_store
+            .put(&host_config_key(host_id, config_name), data)
+            .await
+            .context("unable to store host config data")?;
+        // The targeted host picks up the overrides through its config watches
+        self.event_publisher
+            .publish_event(
+                "config_set",
+                crate::event::host_config_set(host_id, config_name),
+            )
+            .await?;
+
+        Ok(CtlResponse::<()>::success(
+            "successfully put host config".into(),
+        ))
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    async fn handle_host_config_delete(
+        &self,
+        host_id: &str,
+        config_name: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        debug!("handle host config entry deletion");
+
+        self.config_store
+            .del(&host_config_key(host_id, config_name))
+            .await
+            .context("Unable to delete host config data")?;
+
+        self.event_publisher
+            .publish_event(
+                "config_deleted",
+                crate::event::host_config_deleted(host_id, config_name),
+            )
+            .await?;
+
+        Ok(CtlResponse::<()>::success(
+            "successfully deleted host config".into(),
+        ))
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn handle_label_put(
         &self,
@@ -755,10 +1558,86 @@ impl ControlInterfaceServer for Host {
         ))
     }
 
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_labels_put(
+        &self,
+        request: HostLabels,
+        host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        // Validate every label before applying any of them, so the update is all-or-nothing
+        if let Some(key) = request
+            .labels()
+            .keys()
+            .find(|key| key.to_lowercase().starts_with("hostcore."))
+        {
+            bail!("hostcore.* labels cannot be set dynamically (got [{key}])");
+        }
+
+        let mut labels = self.labels.write().await;
+        for (key, value) in request.labels() {
+            if let Some(previous) = labels.insert(key.clone(), value.clone()) {
+                if previous != *value {
+                    info!(key, value, "updated label");
+                }
+            } else {
+                info!(key, value, "set label");
+            }
+        }
+
+        self.event_publisher
+            .publish_event(
+                "labels_changed",
+                crate::event::labels_changed(host_id, HashMap::from_iter(labels.clone())),
+            )
+            .await
+            .context("failed to publish labels_changed event")?;
+
+        Ok(CtlResponse::<()>::success("successfully put labels".into()))
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    async fn handle_labels_del(
+        &self,
+        request: HostLabelIdentifiers,
+        host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let mut labels = self.labels.write().await;
+        let mut removed = 0;
+        for key in request.keys() {
+            if labels.remove(key).is_some() {
+                info!(key, "removed label");
+                removed += 1;
+            } else {
+                warn!(key, "could not remove unset label");
+            }
+        }
+
+        if removed == 0 {
+            return Ok(CtlResponse::<()>::success(
+                "successfully deleted labels (no such labels)".into(),
+            ));
+        }
+
+        self.event_publisher
+            .publish_event(
+                "labels_changed",
+                crate::event::labels_changed(host_id, HashMap::from_iter(labels.clone())),
+            )
+            .await
+            .context("failed to publish labels_changed event")?;
+
+        Ok(CtlResponse::<()>::success(
+            "successfully deleted labels".into(),
+        ))
+    }
+
     /// Handle a new link by modifying the relevant source [crate::wasmbus::ComponentSpecification].
     #[instrument(level = "debug", skip_all)]
-    async fn handle_link_put(&self, request: Link) -> anyhow::Result<CtlResponse<()>> {
-        let link_set_result: anyhow::Result<()> = async {
+    async fn handle_link_put(&self, request: PutLinkRequest) -> anyhow::Result<CtlResponse<()>> {
+        let force = request.force();
+        let if_absent = request.if_absent();
+        let request = request.link();
+        let link_set_result: anyhow::Result<Vec<Link>> = async {
             let source_id = request.source_id();
             let target = request.target();
             let wit_namespace = request.wit_namespace();
@@ -782,51 +1661,16 @@ impl ControlInterfaceServer for Host {
                     .source_config()
                     .clone()
                     .iter()
-                    .chain(request.target_config())
-            ).await?;
+                    .chain(request.target_config()),
+            )
+            .await?;
 
             let mut component_spec = self
                 .get_component_spec(source_id)
                 .await?
                 .unwrap_or_default();
 
-            // If the link is defined from this source on the same interface and link name, but to a different target,
-            // we need to reject this link and suggest deleting the existing link or using a different link name.
-            if let Some(existing_conflict_link) = component_spec.links.iter().find(|link| {
-                link.source_id() == source_id
-                    && link.wit_namespace() == wit_namespace
-                    && link.wit_package() == wit_package
-                    && link.name() == name
-                    // Check if interfaces have no intersection
-                    && link.interfaces().iter().any(|i| interfaces.contains(i))
-                    && link.target() != target
-            }) {
-                error!(
-                    source_id,
-                    desired_target = target,
-                    existing_target = existing_conflict_link.target(),
-                    ns_and_package,
-                    name,
-                    "link already exists with different target, consider deleting the existing link or using a different link name"
-                );
-                bail!("link already exists with different target, consider deleting the existing link or using a different link name");
-            }
-
-            // If we can find an existing link with the same source, target, namespace, package, and name, update it.
-            // Otherwise, add the new link to the component specification.
-            if let Some(existing_link_index) = component_spec.links.iter().position(|link| {
-                link.source_id() == source_id
-                    && link.target() == target
-                    && link.wit_namespace() == wit_namespace
-                    && link.wit_package() == wit_package
-                    && link.name() == name
-            }) {
-                if let Some(existing_link) = component_spec.links.get_mut(existing_link_index) {
-                    *existing_link = request.clone();
-                }
-            } else {
-                component_spec.links.push(request.clone());
-            };
+            let replaced_links = put_link(&mut component_spec.links, request, force, if_absent)?;
 
             // Update component specification with the new link
             self.store_component_spec(&source_id, &component_spec)
@@ -834,26 +1678,47 @@ impl ControlInterfaceServer for Host {
             self.update_host_with_spec(&source_id, &component_spec)
                 .await?;
 
-            self.put_backwards_compat_provider_link(&request)
-                .await?;
+            for replaced_link in &replaced_links {
+                self.del_provider_link(replaced_link).await?;
+            }
+            self.put_backwards_compat_provider_link(request).await?;
 
-            Ok(())
+            Ok(replaced_links)
         }
         .await;
 
-        if let Err(e) = link_set_result {
-            self.event_publisher
-                .publish_event(
-                    "linkdef_set_failed",
-                    crate::event::linkdef_set_failed(&request, &e),
-                )
-                .await?;
-            Ok(CtlResponse::error(e.to_string().as_ref()))
-        } else {
-            self.event_publisher
-                .publish_event("linkdef_set", crate::event::linkdef_set(&request))
-                .await?;
-            Ok(CtlResponse::<()>::success("successfully set link".into()))
+        match link_set_result {
+            Err(e) => {
+                self.event_publisher
+                    .publish_event(
+                        "linkdef_set_failed",
+                        crate::event::linkdef_set_failed(request, &e),
+                    )
+                    .await?;
+                Ok(CtlResponse::error(e.to_string().as_ref()))
+            }
+            Ok(replaced_links) => {
+                for replaced_link in &replaced_links {
+                    let replaced_target = replaced_link.target().to_string();
+                    self.event_publisher
+                        .publish_event(
+                            "linkdef_deleted",
+                            crate::event::linkdef_deleted(
+                                replaced_link.source_id(),
+                                Some(&replaced_target),
+                                replaced_link.name(),
+                                replaced_link.wit_namespace(),
+                                replaced_link.wit_package(),
+                                Some(replaced_link.interfaces()),
+                            ),
+                        )
+                        .await?;
+                }
+                self.event_publisher
+                    .publish_event("linkdef_set", crate::event::linkdef_set(request))
+                    .await?;
+                Ok(CtlResponse::<()>::success("successfully set link".into()))
+            }
         }
     }
 
@@ -1016,3 +1881,342 @@ impl ControlInterfaceServer for Host {
         Ok(CtlResponse::ok(host))
     }
 }
+
+/// Label that, when set to `true`, advertises support for a feature named in auction
+/// [`ResourceRequirements`], e.g. `feature.wasi-nn=true`
+const FEATURE_LABEL_PREFIX: &str = "feature.";
+
+/// Put `link` into the `links` of its source component, returning the links it replaced.
+///
+/// Links overlap if they are from the same source on the same WIT package, link name and any of
+/// the same interfaces. Unless `force` is set, an overlapping link to a different target is
+/// rejected, and with `if_absent` any overlapping link is rejected.
+fn put_link(
+    links: &mut Vec<Link>,
+    link: &Link,
+    force: bool,
+    if_absent: bool,
+) -> anyhow::Result<Vec<Link>> {
+    let source_id = link.source_id();
+    let target = link.target();
+    let wit_namespace = link.wit_namespace();
+    let wit_package = link.wit_package();
+    let name = link.name();
+    let ns_and_package = format!("{wit_namespace}:{wit_package}");
+
+    let overlaps = |existing: &Link| {
+        existing.source_id() == source_id
+            && existing.wit_namespace() == wit_namespace
+            && existing.wit_package() == wit_package
+            && existing.name() == name
+            // Check if interfaces have no intersection
+            && existing
+                .interfaces()
+                .iter()
+                .any(|i| link.interfaces().contains(i))
+    };
+
+    if if_absent {
+        if let Some(existing_link) = links.iter().find(|existing| overlaps(existing)) {
+            error!(
+                source_id,
+                existing_target = existing_link.target(),
+                ns_and_package,
+                name,
+                "link already exists and was only requested if absent"
+            );
+            bail!("link already exists for this source, interface and link name");
+        }
+    }
+
+    // If the link is defined from this source on the same interface and link name, but to a different target,
+    // we need to reject this link and suggest deleting the existing link or using a different link name,
+    // unless the caller asked to replace it.
+    let mut replaced_links = Vec::new();
+    if force {
+        let (replaced, kept) = links
+            .drain(..)
+            .partition(|existing| overlaps(existing) && existing.target() != target);
+        replaced_links = replaced;
+        *links = kept;
+        for replaced_link in &replaced_links {
+            warn!(
+                source_id,
+                desired_target = target,
+                existing_target = replaced_link.target(),
+                ns_and_package,
+                name,
+                "replacing existing link with different target"
+            );
+        }
+    } else if let Some(existing_conflict_link) = links
+        .iter()
+        .find(|existing| overlaps(existing) && existing.target() != target)
+    {
+        error!(
+            source_id,
+            desired_target = target,
+            existing_target = existing_conflict_link.target(),
+            ns_and_package,
+            name,
+            "link already exists with different target, consider deleting the existing link or using a different link name"
+        );
+        bail!("link already exists with different target, consider deleting the existing link, using a different link name or forcing the update");
+    }
+
+    // If we can find an existing link with the same source, target, namespace, package, and name, update it.
+    // Otherwise, add the new link to the component specification.
+    if let Some(existing_link) = links.iter_mut().find(|existing| {
+        existing.source_id() == source_id
+            && existing.target() == target
+            && existing.wit_namespace() == wit_namespace
+            && existing.wit_package() == wit_package
+            && existing.name() == name
+    }) {
+        *existing_link = link.clone();
+    } else {
+        links.push(link.clone());
+    }
+    Ok(replaced_links)
+}
+
+/// Build the capacity hints attached to auction acks so that schedulers can rank bidding hosts
+fn auction_hints(
+    components: usize,
+    providers: usize,
+    uptime: Duration,
+    labels: &BTreeMap<String, String>,
+) -> AuctionHints {
+    let mut system = System::new();
+    system.refresh_memory();
+    AuctionHints::builder()
+        .component_count(components as u64)
+        .provider_count(providers as u64)
+        .free_memory_bytes(system.available_memory())
+        .uptime_seconds(uptime.as_secs())
+        .labels(labels.clone())
+        .build()
+}
+
+/// Stop every selected component with `stop`, removing a component from `components` only once it
+/// stopped, so that components failing to stop remain in the inventory. Returns the IDs of the
+/// stopped components and the errors of the ones that failed to stop
+async fn stop_selected_components<T, Fut>(
+    components: &RwLock<HashMap<String, Arc<T>>>,
+    selected: Vec<(String, Arc<T>)>,
+    mut stop: impl FnMut(Arc<T>) -> Fut,
+) -> (Vec<String>, Vec<(String, anyhow::Error)>)
+where
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let mut stopped = Vec::with_capacity(selected.len());
+    let mut failed = Vec::new();
+    for (component_id, component) in selected {
+        match stop(Arc::clone(&component)).await {
+            Ok(()) => {
+                let mut components = components.write().await;
+                // Leave components alone that were replaced while stopping, e.g. by a scale request
+                if components
+                    .get(&component_id)
+                    .is_some_and(|current| Arc::ptr_eq(current, &component))
+                {
+                    components.remove(&component_id);
+                }
+                stopped.push(component_id);
+            }
+            Err(err) => failed.push((component_id, err)),
+        }
+    }
+    (stopped, failed)
+}
+
+/// Check whether this host can satisfy the resource requirements of an auction, where `running`
+/// is the number of workloads of the auctioned kind already running. Returns a description of the
+/// first unmet requirement, if any.
+fn unmet_requirement(
+    requirements: &ResourceRequirements,
+    labels: &BTreeMap<String, String>,
+    features: &Features,
+    running: usize,
+) -> Option<String> {
+    if let Some(max) = requirements.max_running() {
+        if running >= max as usize {
+            return Some(format!(
+                "{running} workloads already running, maximum is {max}"
+            ));
+        }
+    }
+    if let Some(feature) = requirements.features().iter().find(|feature| {
+        !features.is_enabled(feature)
+            && labels
+                .get(&format!("{FEATURE_LABEL_PREFIX}{feature}"))
+                .is_none_or(|v| !v.eq_ignore_ascii_case("true"))
+    }) {
+        return Some(format!("feature `{feature}` is not supported"));
+    }
+    if let Some(min) = requirements.min_memory_bytes() {
+        let mut system = System::new();
+        system.refresh_memory();
+        let available = system.available_memory();
+        if available < min {
+            return Some(format!(
+                "{available} bytes of memory available, {min} bytes required"
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(target: &str, interfaces: &[&str], target_config: &[&str]) -> Link {
+        Link::builder()
+            .source_id("source")
+            .target(target)
+            .wit_namespace("wasi")
+            .wit_package("keyvalue")
+            .interfaces(interfaces.iter().map(ToString::to_string).collect())
+            .name("default")
+            .target_config(target_config.iter().map(ToString::to_string).collect())
+            .build()
+            .expect("failed to build link")
+    }
+
+    #[test]
+    fn put_link_updates_links_to_the_same_target() -> anyhow::Result<()> {
+        let mut links = vec![link("redis", &["store"], &[])];
+        let updated = link("redis", &["store"], &["redis-url"]);
+        assert!(put_link(&mut links, &updated, false, false)?.is_empty());
+        assert_eq!(links, [updated]);
+
+        let other_name = Link::builder()
+            .source_id("source")
+            .target("vault")
+            .wit_namespace("wasi")
+            .wit_package("keyvalue")
+            .interfaces(vec!["store".into()])
+            .name("secret")
+            .build()
+            .expect("failed to build link");
+        assert!(put_link(&mut links, &other_name, false, false)?.is_empty());
+        assert_eq!(links.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn put_link_rejects_conflicting_targets_unless_forced() -> anyhow::Result<()> {
+        let existing = link("redis", &["store", "atomics"], &[]);
+        let unrelated = link("nats", &["batch"], &[]);
+        let mut links = vec![existing.clone(), unrelated.clone()];
+
+        let conflicting = link("vault", &["store"], &[]);
+        assert!(put_link(&mut links, &conflicting, false, false).is_err());
+        assert_eq!(links, [existing.clone(), unrelated.clone()]);
+
+        let replaced = put_link(&mut links, &conflicting, true, false)?;
+        assert_eq!(replaced, [existing]);
+        assert_eq!(links, [unrelated, conflicting]);
+        Ok(())
+    }
+
+    #[test]
+    fn put_link_if_absent_rejects_any_overlapping_link() -> anyhow::Result<()> {
+        let existing = link("redis", &["store"], &[]);
+        let mut links = vec![existing.clone()];
+
+        // Even an update of the link to the same target is rejected
+        let err = put_link(
+            &mut links,
+            &link("redis", &["store"], &["redis-url"]),
+            false,
+            true,
+        )
+        .expect_err("existing link should be rejected");
+        assert!(err.to_string().contains("link already exists"));
+        assert!(put_link(&mut links, &link("vault", &["store"], &[]), false, true).is_err());
+        assert_eq!(links, std::slice::from_ref(&existing));
+
+        let absent = link("vault", &["atomics"], &[]);
+        assert!(put_link(&mut links, &absent, false, true)?.is_empty());
+        assert_eq!(links, [existing, absent]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stop_selected_components_keeps_failed_components() {
+        let components = RwLock::new(HashMap::from([
+            ("ok".to_string(), Arc::new("ok")),
+            ("broken".to_string(), Arc::new("broken")),
+            ("unselected".to_string(), Arc::new("unselected")),
+        ]));
+        let selected: Vec<_> = components
+            .read()
+            .await
+            .iter()
+            .filter(|(id, _)| *id != "unselected")
+            .map(|(id, component)| (id.clone(), Arc::clone(component)))
+            .collect();
+
+        let (stopped, failed) =
+            stop_selected_components(&components, selected, |component| async move {
+                if *component == "broken" {
+                    bail!("component refused to stop")
+                }
+                Ok(())
+            })
+            .await;
+
+        assert_eq!(stopped, ["ok"]);
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, "broken");
+        assert_eq!(failed[0].1.to_string(), "component refused to stop");
+        let mut remaining: Vec<_> = components.read().await.keys().cloned().collect();
+        remaining.sort();
+        assert_eq!(remaining, ["broken", "unselected"]);
+    }
+
+    #[test]
+    fn unmet_requirement_checks_each_requirement() {
+        let labels = BTreeMap::from([
+            ("feature.wasi-nn".to_string(), "TRUE".to_string()),
+            ("feature.gpu".to_string(), "false".to_string()),
+        ]);
+        let features = Features::from("builtin-http-server");
+
+        assert_eq!(
+            unmet_requirement(&ResourceRequirements::default(), &labels, &features, 100),
+            None
+        );
+
+        let max_running = ResourceRequirements::builder().max_running(2).build();
+        assert_eq!(unmet_requirement(&max_running, &labels, &features, 1), None);
+        assert_eq!(
+            unmet_requirement(&max_running, &labels, &features, 2).as_deref(),
+            Some("2 workloads already running, maximum is 2")
+        );
+
+        // Features are supported if enabled on the host or advertised with a label
+        let supported = ResourceRequirements::builder()
+            .feature("wasi-nn")
+            .feature("builtin_http_server")
+            .build();
+        assert_eq!(unmet_requirement(&supported, &labels, &features, 0), None);
+        for feature in ["gpu", "tpu"] {
+            let unsupported = ResourceRequirements::builder().feature(feature).build();
+            assert_eq!(
+                unmet_requirement(&unsupported, &labels, &features, 0),
+                Some(format!("feature `{feature}` is not supported"))
+            );
+        }
+
+        let memory = ResourceRequirements::builder()
+            .min_memory_bytes(u64::MAX)
+            .build();
+        assert!(unmet_requirement(&memory, &labels, &features, 0)
+            .is_some_and(|reason| reason.ends_with(&format!("{} bytes required", u64::MAX))));
+        let memory = ResourceRequirements::builder().min_memory_bytes(0).build();
+        assert_eq!(unmet_requirement(&memory, &labels, &features, 0), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! Fencing of control interface commands by the lattice epoch stored in the lattice data bucket,
+//! so that only the commands of the current leader among the lattice controllers are honored.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Context as _;
+use tracing::{debug, instrument};
+use wasmcloud_control_interface::LatticeEpoch;
+
+/// The highest lattice epoch observed by the host, either in the lattice data bucket or carried
+/// by a command. An epoch of 0 means that no controller has advanced the epoch yet
+#[derive(Debug, Default)]
+pub(crate) struct EpochFence(AtomicU64);
+
+impl EpochFence {
+    /// Returns the current epoch
+    pub(crate) fn current(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Raise the current epoch to `epoch`, if it is higher
+    pub(crate) fn observe(&self, epoch: u64) {
+        let previous = self.0.fetch_max(epoch, Ordering::Relaxed);
+        if epoch > previous {
+            debug!(epoch, previous, "advanced lattice epoch");
+        }
+    }
+
+    /// Check whether a command carrying `epoch` is honored, raising the current epoch if the
+    /// command carries a newer one.
+    ///
+    /// Commands carrying no epoch are always admitted, so that controllers not using epochs
+    /// keep working. Commands carrying an epoch older than the current one are rejected
+    pub(crate) fn admit(&self, epoch: Option<u64>) -> bool {
+        let Some(epoch) = epoch else {
+            return true;
+        };
+        let previous = self.0.fetch_max(epoch, Ordering::Relaxed);
+        epoch >= previous
+    }
+}
+
+impl super::Host {
+    /// Check whether a control interface command carrying `epoch` is honored, returning the
+    /// current lattice epoch if the command carries a stale one
+    pub(crate) fn check_lattice_epoch(&self, epoch: Option<u64>) -> Result<(), u64> {
+        if self.epoch_fence.admit(epoch) {
+            Ok(())
+        } else {
+            Err(self.epoch_fence.current())
+        }
+    }
+
+    /// Process the lattice epoch being put into the lattice data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) fn process_lattice_epoch_put(&self, value: impl AsRef<[u8]>) -> anyhow::Result<()> {
+        let epoch: LatticeEpoch =
+            serde_json::from_slice(value.as_ref()).context("failed to decode lattice epoch")?;
+        self.epoch_fence.observe(epoch.epoch());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EpochFence;
+
+    #[test]
+    fn rejects_stale_epochs() {
+        let fence = EpochFence::default();
+        assert!(fence.admit(None));
+        assert!(fence.admit(Some(0)));
+
+        fence.observe(3);
+        assert_eq!(fence.current(), 3);
+        assert!(!fence.admit(Some(2)));
+        assert!(fence.admit(Some(3)));
+        assert!(fence.admit(None));
+
+        // Commands of a newer leader are honored before the host observes the new epoch in the
+        // data bucket, and fence off the previous leader right away
+        assert!(fence.admit(Some(4)));
+        assert_eq!(fence.current(), 4);
+        assert!(!fence.admit(Some(3)));
+
+        fence.observe(2);
+        assert_eq!(fence.current(), 4);
+    }
+}
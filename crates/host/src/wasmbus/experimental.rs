@@ -101,6 +101,21 @@ impl Features {
     pub fn rpc_interface_enabled(&self) -> bool {
         self.rpc_interface
     }
+
+    /// Check if a feature is enabled by name, accepting the same names as the `From<&str>`
+    /// conversion. Unknown names are never enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match &*name.to_ascii_lowercase().replace('-', "_") {
+            "builtin_http_client" => self.builtin_http_client,
+            "builtin_http_server" => self.builtin_http_server,
+            "builtin_messaging_nats" => self.builtin_messaging_nats,
+            "wasmcloud_messaging_v3" => self.wasmcloud_messaging_v3,
+            "workload_identity_auth" => self.workload_identity_auth,
+            "workload_identity_interface" => self.workload_identity_interface,
+            "rpc_interface" => self.rpc_interface,
+            _ => false,
+        }
+    }
 }
 
 /// This enables unioning feature flags together
@@ -15,7 +15,8 @@ use spire_api::{
     selectors::Selector, DelegateAttestationRequest::Selectors, DelegatedIdentityClient,
 };
 use tokio::sync::RwLock;
-use tracing::{error, instrument, warn};
+use tracing::{error, instrument, trace, warn};
+use wasmcloud_core::metrics::InvocationDirection;
 use wasmcloud_runtime::capability::logging::logging;
 use wasmcloud_runtime::capability::secrets::store::SecretValue;
 use wasmcloud_runtime::capability::{
@@ -30,7 +31,9 @@ use wasmcloud_tracing::context::TraceContextInjector;
 use wrpc_transport::InvokeExt as _;
 
 use super::config::ConfigBundle;
+use super::routing::TrafficRoutes;
 use super::{injector_to_headers, Features};
+use crate::metrics::HostMetrics;
 
 // The key used to represent a wasmCloud-specific selector:
 // https://github.com/spiffe/spire-api-sdk/blob/3c6b1447f3d82210b91462d003f6c2774ffbe472/proto/spire/api/types/selector.proto#L6-L8
@@ -72,12 +75,16 @@ pub struct Handler {
     pub instance_links: Arc<RwLock<HashMap<Box<str>, HashMap<Box<str>, Box<str>>>>>,
     /// Link name -> messaging client
     pub messaging_links: Arc<RwLock<HashMap<Box<str>, async_nats::Client>>>,
+    /// Traffic splits of link targets, used to route invocations between component versions
+    pub traffic_routes: Arc<TrafficRoutes>,
 
     pub invocation_timeout: Duration,
     /// Experimental features enabled in the host for gating handler functionality
     pub experimental_features: Features,
     /// Labels associated with the wasmCloud Host the component is running on
     pub host_labels: Arc<RwLock<BTreeMap<String, String>>>,
+    /// Metrics of the host, used to record the invocations sent by the component
+    pub metrics: Arc<HostMetrics>,
 }
 
 impl Handler {
@@ -93,9 +100,11 @@ impl Handler {
             targets: Arc::default(),
             instance_links: self.instance_links.clone(),
             messaging_links: self.messaging_links.clone(),
+            traffic_routes: self.traffic_routes.clone(),
             invocation_timeout: self.invocation_timeout,
             experimental_features: self.experimental_features,
             host_labels: self.host_labels.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 }
@@ -238,21 +247,42 @@ impl wrpc_transport::Invoke for Handler {
             format!("failed to call `{func}` in instance `{instance}` (failed to find a configured link with name `{link_name}` from component `{id}`, please check your configuration)", id = self.component_id)
         }).map_err(Error::LinkNotFound)?;
 
+        // Route the invocation to one of the components of the target's traffic split, if any
+        let id = self.traffic_routes.resolve(id).await;
+
         let mut headers = injector_to_headers(&TraceContextInjector::default_with_span());
         headers.insert("source-id", &*self.component_id);
         headers.insert("link-name", link_name);
+        let invocation_id = wasmcloud_core::id::invocation_id();
+        headers.insert(
+            wasmcloud_core::id::INVOCATION_ID_HEADER,
+            invocation_id.as_str(),
+        );
+        trace!(%id, instance, func, %invocation_id, "invoking link target");
         let nats = wrpc_transport_nats::Client::new(
             Arc::clone(&self.nats),
-            format!("{}.{id}", &self.lattice),
+            format!("{}.{id}", self.lattice),
             None,
         )
         .await
         .map_err(Error::Handler)?;
-        let (tx, rx) = nats
+        let params_size = params.len();
+        let res = nats
             .timeout(self.invocation_timeout)
             .invoke(Some(headers), instance, func, params, paths)
-            .await
-            .map_err(Error::Handler)?;
+            .await;
+        self.metrics.invocations.record_outgoing(
+            &self.metrics.component_invocation_attributes(
+                &self.component_id,
+                InvocationDirection::Outgoing,
+                instance,
+                func,
+                Some(&*id),
+            ),
+            params_size,
+            res.is_err(),
+        );
+        let (tx, rx) = res.map_err(Error::Handler)?;
         Ok((tx, rx))
     }
 }
@@ -594,7 +624,7 @@ impl MessagingHostMessage0_3 for Message {
                     .iter()
                     // NOTE(brooksmtownsend): The funky construction here is to provide a concrete type
                     // to the `as_ref()` call, which is necessary to satisfy the type inference on Windows.
-                    .filter(|(k, ..)| (<&async_nats::HeaderName as AsRef<str>>::as_ref(k) != key))
+                    .filter(|(k, ..)| <&async_nats::HeaderName as AsRef<str>>::as_ref(k) != key)
                     .flat_map(|(k, vs)| zip(repeat(k.clone()), vs.iter().cloned()))
                     .collect();
                 Ok(())
@@ -3,17 +3,20 @@ use crate::OciConfig;
 use core::net::SocketAddr;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
 use nkeys::KeyPair;
 use url::Url;
-use wasmcloud_core::{logging::Level as LogLevel, OtelConfig};
+use wasmcloud_core::logging::{Level as LogLevel, LogDirectives};
+use wasmcloud_core::OtelConfig;
 use wasmcloud_runtime::{
     DEFAULT_MAX_CORE_INSTANCES_PER_COMPONENT, MAX_COMPONENTS, MAX_COMPONENT_SIZE, MAX_LINEAR_MEMORY,
 };
 
 use crate::wasmbus::experimental::Features;
+use crate::wasmbus::WatchdogConfig;
 
 /// wasmCloud Host configuration
 #[allow(clippy::struct_excessive_bools)]
@@ -47,6 +50,9 @@ pub struct Host {
     pub enable_structured_logging: bool,
     /// Log level to pass to capability providers to use. Should be parsed from a [`tracing::Level`]
     pub log_level: LogLevel,
+    /// Per-target log filtering directives to pass to capability providers, applied on top of
+    /// the log level
+    pub log_directives: LogDirectives,
     /// Whether to enable loading supplemental configuration
     pub config_service_enabled: bool,
     /// configuration for OpenTelemetry tracing
@@ -74,6 +80,28 @@ pub struct Host {
     pub enable_component_auction: bool,
     /// Whether capability provider auctions are enabled
     pub enable_provider_auction: bool,
+    /// Whether the host refuses all control interface commands that would mutate it or the
+    /// lattice, only answering queries. Auctions are silently declined.
+    ///
+    /// Links and named configs are still watched in the lattice data bucket, so that the
+    /// workloads of a read-only host follow changes made through other hosts of the lattice.
+    pub read_only: bool,
+    /// Path to a [`WorkloadManifest`](crate::wasmbus::WorkloadManifest) of components and
+    /// providers to start when the host starts
+    pub workload_manifest: Option<PathBuf>,
+    /// OCI references of components and providers to prefetch into the artifact cache when the
+    /// host starts, so that the first workloads started from them do not pay for a registry pull
+    pub prefetch_images: Vec<String>,
+    /// Interval at which all prefetched images, including those prefetched via the control
+    /// interface, are prefetched again to keep them warm. Images are only prefetched once if not
+    /// set
+    pub prefetch_refresh_interval: Option<Duration>,
+    /// Path to a [`DataDir`](crate::data_dir::DataDir) in which the host keeps local state, such
+    /// as provider logs, across restarts
+    pub data_dir: Option<PathBuf>,
+    /// Configuration of the watchdog detecting and recovering stalled host subsystems. The
+    /// watchdog is disabled if not set
+    pub watchdog: Option<WatchdogConfig>,
 }
 
 /// Configuration for wasmCloud policy service
@@ -105,6 +133,7 @@ impl Default for Host {
             allow_file_load: false,
             enable_structured_logging: false,
             log_level: LogLevel::Info,
+            log_directives: LogDirectives::default(),
             config_service_enabled: false,
             otel_config: OtelConfig::default(),
             version: env!("CARGO_PKG_VERSION").to_string(),
@@ -120,6 +149,12 @@ impl Default for Host {
             http_admin: None,
             enable_component_auction: true,
             enable_provider_auction: true,
+            read_only: false,
+            workload_manifest: None,
+            prefetch_images: Vec::new(),
+            prefetch_refresh_interval: None,
+            data_dir: None,
+            watchdog: None,
         }
     }
 }
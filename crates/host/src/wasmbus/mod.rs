@@ -12,7 +12,7 @@ use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{anyhow, bail, ensure, Context as _};
 use bytes::{BufMut, Bytes, BytesMut};
@@ -27,43 +27,61 @@ use serde_json::json;
 use sysinfo::System;
 use tokio::io::AsyncWrite;
 use tokio::net::TcpListener;
-use tokio::spawn;
 use tokio::sync::{mpsc, watch, RwLock, Semaphore};
 use tokio::task::{JoinHandle, JoinSet};
-use tokio::time::{interval_at, timeout, Instant};
+use tokio::time::{interval_at, sleep, timeout, timeout_at, Instant};
+use tokio::{select, spawn};
 use tokio_stream::wrappers::IntervalStream;
 use tracing::{debug, debug_span, error, info, instrument, trace, warn, Instrument as _};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use wascap::jwt;
 use wasmcloud_control_interface::{
-    ComponentAuctionAck, ComponentAuctionRequest, ComponentDescription, CtlResponse,
-    DeleteInterfaceLinkDefinitionRequest, HostInventory, HostLabel, HostLabelIdentifier, Link,
-    ProviderAuctionAck, ProviderAuctionRequest, ProviderDescription, RegistryCredential,
-    ScaleComponentCommand, StartProviderCommand, StopHostCommand, StopProviderCommand,
-    UpdateComponentCommand,
+    CleanupHostDataCommand, ComponentAuctionAck, ComponentAuctionRequest, ComponentDescription,
+    ComponentMemoryUsage, ComponentProfile, ConfigNames, ConfigRef, ConfigVersion, ConfigsByName,
+    CtlResponse, DataCategory, DataDirUsage, DeleteInterfaceLinkDefinitionRequest,
+    DrainHostCommand, DrainOptions, HostDecommission, HostInventory, HostInventoryPage, HostLabel,
+    HostLabelIdentifier, HostLabelIdentifiers, HostLabels, InventoryPageRequest, Link,
+    PrefetchImagesCommand, PrefetchStatus, PrefetchedImage, ProfileComponentCommand,
+    ProviderAuctionAck, ProviderAuctionRequest, ProviderDescription, PutLinkRequest,
+    RegistryCredential, RevisionQuery, ScaleComponentCommand, StartProviderCommand,
+    StopComponentsCommand, StopHostCommand, StopProviderCommand, TopMemoryQuery,
+    UpdateComponentCommand, UpdateHostTracingCommand, UpdateProviderConfigCommand,
 };
+use wasmcloud_core::metrics::InvocationDirection;
 use wasmcloud_core::ComponentId;
 use wasmcloud_runtime::capability::secrets::store::SecretValue;
 use wasmcloud_runtime::component::{from_string_map, Limits, WrpcServeEvent};
+use wasmcloud_runtime::scheduling::PriorityClass;
 use wasmcloud_runtime::Runtime;
 use wasmcloud_secrets_types::SECRET_PREFIX;
 use wasmcloud_tracing::context::TraceContextInjector;
 use wasmcloud_tracing::{global, InstrumentationScope, KeyValue};
 
+use crate::config::host_config_key;
+use crate::data_dir::DataDir;
 use crate::event::{DefaultEventPublisher, EventPublisher};
 use crate::metrics::HostMetrics;
-use crate::nats::connect_nats;
+use crate::nats::connect_nats_with_health;
+use crate::nats::health::NatsHealth;
 use crate::nats::provider::NatsProviderManager;
 use crate::policy::DefaultPolicyManager;
 use crate::secrets::{DefaultSecretsManager, SecretsManager};
 use crate::store::{DefaultStore, StoreManager};
 use crate::wasmbus::ctl::ControlInterfaceServer;
+use crate::wasmbus::epoch::EpochFence;
+use crate::wasmbus::profile::InvocationRecorder;
+use crate::wasmbus::routing::TrafficRoutes;
+use crate::wasmbus::watchdog::{Watchdog, WatchedEventPublisher};
 use crate::workload_identity::WorkloadIdentityConfig;
 use crate::{fetch_component, PolicyManager, PolicyResponse, RegistryConfig, ResourceRef};
 
 mod component_spec;
+mod epoch;
 mod experimental;
 mod handler;
+mod profile;
+mod routing;
+mod watchdog;
 
 pub(crate) mod claims;
 pub(crate) mod providers;
@@ -77,8 +95,16 @@ pub mod config;
 /// wasmCloud host configuration
 pub mod host_config;
 
+/// Workloads started from a local manifest
+pub mod workload_manifest;
+
+pub use self::claims::serialize_component_claims;
 pub use self::experimental::Features;
 pub use self::host_config::Host as HostConfig;
+pub use self::watchdog::{
+    WatchdogConfig, WatchdogSubsystem, DEFAULT_WATCHDOG_INTERVAL, DEFAULT_WATCHDOG_STALL_TIMEOUT,
+};
+pub use self::workload_manifest::WorkloadManifest;
 pub use component_spec::ComponentSpecification;
 pub use providers::ProviderManager;
 
@@ -88,6 +114,21 @@ use self::handler::Handler;
 const MAX_INVOCATION_CHANNEL_SIZE: usize = 5000;
 const MIN_INVOCATION_CHANNEL_SIZE: usize = 256;
 
+/// Maximum time a read pinned to a revision waits for the host to reach the revision
+const REVISION_WAIT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interval at which the revision of the config bucket is polled by a read pinned to a revision
+const REVISION_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Parse the optional [`RevisionQuery`] of a read request, which older clients send empty
+fn revision_query(payload: impl AsRef<[u8]>) -> anyhow::Result<RevisionQuery> {
+    let payload = payload.as_ref();
+    if payload.is_empty() {
+        return Ok(RevisionQuery::default());
+    }
+    serde_json::from_slice(payload).context("failed to deserialize revision query")
+}
+
 #[derive(Clone, Default)]
 struct AsyncBytesMut(Arc<std::sync::Mutex<BytesMut>>);
 
@@ -131,6 +172,74 @@ impl TryFrom<AsyncBytesMut> for Vec<u8> {
 
 type Annotations = BTreeMap<String, String>;
 
+/// Annotation used to configure how long a component or provider is given to finish in-flight
+/// work when stopped, before it is stopped forcefully
+pub const TERMINATION_GRACE_ANNOTATION: &str = "wasmcloud.dev/termination-grace-seconds";
+
+/// Annotation used to configure the scheduling priority of a component, one of `low`, `normal`
+/// or `high`. Higher priority components run for longer time slices before yielding to other
+/// components on the host.
+pub const PRIORITY_ANNOTATION: &str = "wasmcloud.dev/priority";
+
+/// Stop reason used in events when a workload finished in-flight work within its grace period
+pub(crate) const STOP_REASON_GRACEFUL: &str = "stop";
+
+/// Stop reason used in events when a workload was stopped once its grace period elapsed
+pub(crate) const STOP_REASON_FORCED: &str = "forced_stop";
+
+/// Returns the termination grace period configured via [`TERMINATION_GRACE_ANNOTATION`], if any.
+/// Invalid values are ignored.
+pub(crate) fn termination_grace_period(annotations: &Annotations) -> Option<Duration> {
+    let value = annotations.get(TERMINATION_GRACE_ANNOTATION)?;
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(err) => {
+            warn!(
+                value,
+                ?err,
+                "ignoring invalid `{TERMINATION_GRACE_ANNOTATION}` annotation"
+            );
+            None
+        }
+    }
+}
+
+/// Wait up to `grace_period` for the in-flight invocations of a component to finish, cancelling
+/// them via `force_stop` otherwise, and return the stop reason to report. Every in-flight
+/// invocation holds one of the `max_instances` permits, so all permits are available once drained.
+async fn drain_invocations(
+    permits: &Semaphore,
+    max_instances: u32,
+    grace_period: Duration,
+    force_stop: &watch::Sender<bool>,
+) -> &'static str {
+    if timeout(grace_period, permits.acquire_many(max_instances))
+        .await
+        .is_ok()
+    {
+        STOP_REASON_GRACEFUL
+    } else {
+        force_stop.send_replace(true);
+        STOP_REASON_FORCED
+    }
+}
+
+/// Returns the scheduling priority configured via [`PRIORITY_ANNOTATION`], defaulting to
+/// [`PriorityClass::Normal`]. Invalid values are ignored.
+pub(crate) fn priority_class(annotations: &Annotations) -> PriorityClass {
+    let Some(value) = annotations.get(PRIORITY_ANNOTATION) else {
+        return PriorityClass::default();
+    };
+    value.parse().unwrap_or_else(|err| {
+        warn!(
+            value,
+            ?err,
+            "ignoring invalid `{PRIORITY_ANNOTATION}` annotation"
+        );
+        PriorityClass::default()
+    })
+}
+
 #[derive(Debug)]
 struct Component {
     component: wasmcloud_runtime::Component<Handler>,
@@ -145,6 +254,10 @@ struct Component {
     image_reference: Arc<str>,
     events: mpsc::Sender<WrpcServeEvent<<WrpcServer as wrpc_transport::Serve>::Context>>,
     permits: Arc<Semaphore>,
+    /// Set to `true` to cancel in-flight invocations once the termination grace period elapsed
+    force_stop: watch::Sender<bool>,
+    /// Records invocations while the component is being profiled
+    profile: Arc<InvocationRecorder>,
 }
 
 impl Deref for Component {
@@ -169,6 +282,8 @@ struct WrpcServer {
 struct InvocationContext {
     start_at: Instant,
     attributes: Vec<KeyValue>,
+    /// Attributes of the standard invocation metrics
+    invocation_attributes: Vec<KeyValue>,
     span: tracing::Span,
 }
 
@@ -222,11 +337,18 @@ impl wrpc_transport::Serve for WrpcServer {
             let instance = Arc::clone(&instance);
             let metrics = Arc::clone(&metrics);
             let policy_manager = Arc::clone(&policy_manager);
-            let span = tracing::info_span!("component_invocation", func = %func, id = %id, instance = %instance);
+            // Log under the ID the caller sent, so that its logs and ours can be joined. Callers
+            // predating invocation IDs don't send one, in which case the invocation gets a new ID
+            let invocation_id = cx
+                .as_ref()
+                .and_then(|cx| cx.get(wasmcloud_core::id::INVOCATION_ID_HEADER))
+                .map(|id| id.as_str().to_string())
+                .unwrap_or_else(wasmcloud_core::id::invocation_id);
+            let span = tracing::info_span!("component_invocation", func = %func, id = %id, instance = %instance, %invocation_id);
             async move {
                 if let Some(ref cx) = cx {
-                    // Coerce the HashMap<String, Vec<String>> into a Vec<(String, String)> by
-                    // flattening the values
+                    // Coerce the HashMap<String, Vec<String>> into a TraceContext by flattening
+                    // the values
                     let trace_context = cx
                         .iter()
                         .flat_map(|(key, value)| {
@@ -235,7 +357,7 @@ impl wrpc_transport::Serve for WrpcServer {
                                 .map(|v| (key.to_string(), v.to_string()))
                                 .collect::<Vec<_>>()
                         })
-                        .collect::<Vec<(String, String)>>();
+                        .collect::<wasmcloud_core::TraceContext>();
                     span.set_parent(wasmcloud_tracing::context::get_span_context(&trace_context));
                 }
 
@@ -259,6 +381,15 @@ impl wrpc_transport::Serve for WrpcServer {
                         "policy denied request to invoke component `{request_id}`: `{message:?}`",
                     );
 
+                let invocation_attributes = metrics.component_invocation_attributes(
+                    &id,
+                    InvocationDirection::Incoming,
+                    &instance,
+                    &func,
+                    cx.as_ref()
+                        .and_then(|cx| cx.get("source-id"))
+                        .map(async_nats::header::HeaderValue::as_str),
+                );
                 Ok((
                     InvocationContext{
                         start_at: Instant::now(),
@@ -269,6 +400,7 @@ impl wrpc_transport::Serve for WrpcServer {
                             KeyValue::new("host", metrics.host_id.clone()),
                             KeyValue::new("operation", format!("{instance}/{func}")),
                         ],
+                        invocation_attributes,
                         span,
                     },
                     tx,
@@ -319,6 +451,21 @@ pub struct Host {
     /// Indicates whether the host is ready to process requests.
     ready: Arc<AtomicBool>,
 
+    /// Indicates whether the host is draining and declines new workloads.
+    draining: AtomicBool,
+
+    /// Highest lattice epoch observed, below which control interface commands are rejected.
+    epoch_fence: EpochFence,
+
+    /// Revision of the lattice data bucket up to which all changes were applied to the host.
+    data_revision: watch::Sender<u64>,
+
+    /// Images prefetched into the artifact cache, keyed by their OCI reference.
+    prefetched_images: RwLock<BTreeMap<String, PrefetchedImage>>,
+
+    /// The data directory in which the host keeps local state, if configured.
+    data_dir: Option<DataDir>,
+
     /// The encryption key used to secure secrets when transmitting over NATS.
     secrets_xkey: Arc<XKey>,
 
@@ -335,14 +482,18 @@ pub struct Host {
     messaging_links:
         Arc<RwLock<HashMap<Arc<str>, Arc<RwLock<HashMap<Box<str>, async_nats::Client>>>>>>,
 
+    /// Traffic splits of link targets, shared with component handlers to route invocations.
+    traffic_routes: Arc<TrafficRoutes>,
+
     /// A map of providers managed by the host, keyed by their identifiers.
     providers: RwLock<HashMap<String, Provider>>,
 
     /// A map of claims associated with capability providers, keyed by their identifiers.
     provider_claims: Arc<RwLock<HashMap<String, jwt::Claims<jwt::CapabilityProvider>>>>,
 
-    /// The runtime environment used by the host for executing tasks.
-    runtime: Runtime,
+    /// The runtime environment used by the host for executing tasks, which the watchdog may
+    /// recreate.
+    runtime: std::sync::RwLock<Runtime>,
 
     /// Optional overrides for registry configuration settings.
     registry_config: RwLock<HashMap<String, RegistryConfig>>,
@@ -359,6 +510,9 @@ pub struct Host {
     /// The event publisher used for emitting events from the host.
     pub(crate) event_publisher: Arc<dyn EventPublisher>,
 
+    /// Heartbeats of the subsystems monitored by the watchdog.
+    watchdog: Arc<Watchdog>,
+
     /// The policy manager used for evaluating policy decisions.
     policy_manager: Arc<dyn PolicyManager>,
 
@@ -395,6 +549,8 @@ pub struct HostBuilder {
     data_store: Option<Arc<dyn StoreManager>>,
     /// The event publisher to use for sending events
     event_publisher: Option<Arc<dyn EventPublisher>>,
+    /// The health of the NATS connections of the host
+    nats_health: Option<Arc<NatsHealth>>,
     /// The policy manager to use for evaluating policy decisions
     policy_manager: Option<Arc<dyn PolicyManager>>,
     /// The secrets manager to use for managing secrets
@@ -486,6 +642,15 @@ impl HostBuilder {
         Self { data_store, ..self }
     }
 
+    /// Initialize the host with the given NATS connection health, shared with the control
+    /// interface server of the host
+    pub fn with_nats_health(self, nats_health: Option<Arc<NatsHealth>>) -> Self {
+        Self {
+            nats_health,
+            ..self
+        }
+    }
+
     /// Initialize the host with the given configuration watching bundle
     pub fn with_bundle_generator(self, bundle_generator: Option<BundleGenerator>) -> Self {
         Self {
@@ -536,13 +701,14 @@ impl HostBuilder {
             "connecting to NATS RPC server"
         );
         let rpc_nats = Arc::new(
-            connect_nats(
+            connect_nats_with_health(
                 self.config.rpc_nats_url.as_str(),
                 self.config.rpc_jwt.as_ref(),
                 self.config.rpc_key.clone(),
                 self.config.rpc_tls,
                 Some(self.config.rpc_timeout),
                 workload_identity_config.clone(),
+                self.nats_health.clone().unwrap_or_default(),
             )
             .await
             .context("failed to establish NATS RPC server connection")?,
@@ -550,15 +716,7 @@ impl HostBuilder {
 
         let (stop_tx, stop_rx) = watch::channel(None);
 
-        let (runtime, _epoch) = Runtime::builder()
-            .max_execution_time(self.config.max_execution_time)
-            .max_linear_memory(self.config.max_linear_memory)
-            .max_components(self.config.max_components)
-            .max_core_instances_per_component(self.config.max_core_instances_per_component)
-            .max_component_size(self.config.max_component_size)
-            .experimental_features(self.config.experimental_features.into())
-            .build()
-            .context("failed to build runtime")?;
+        let runtime = build_runtime(&self.config)?;
 
         let scope = InstrumentationScope::builder("wasmcloud-host")
             .with_version(self.config.version.clone())
@@ -661,6 +819,28 @@ impl HostBuilder {
             });
         }
 
+        let data_dir = if let Some(path) = &self.config.data_dir {
+            let dir = DataDir::open(path)
+                .await
+                .with_context(|| format!("failed to open data directory {}", path.display()))?;
+            Some(dir)
+        } else {
+            None
+        };
+
+        let watchdog = Arc::new(Watchdog::default());
+        let event_publisher = self
+            .event_publisher
+            .unwrap_or_else(|| Arc::new(DefaultEventPublisher::default()));
+        let event_publisher = if self.config.watchdog.is_some() {
+            Arc::new(WatchedEventPublisher::new(
+                event_publisher,
+                Arc::clone(&watchdog),
+            )) as Arc<dyn EventPublisher>
+        } else {
+            event_publisher
+        };
+
         let (heartbeat_abort, heartbeat_abort_reg) = AbortHandle::new_pair();
         let start_at = Instant::now();
 
@@ -674,7 +854,7 @@ impl HostBuilder {
             secrets_xkey: Arc::new(XKey::new()),
             labels: Arc::new(RwLock::new(labels)),
             experimental_features: self.config.experimental_features,
-            runtime,
+            runtime: std::sync::RwLock::new(runtime),
             start_at,
             stop_rx,
             stop_tx,
@@ -684,14 +864,19 @@ impl HostBuilder {
             metrics: Arc::new(metrics),
             max_execution_time: self.config.max_execution_time,
             messaging_links: Arc::default(),
+            traffic_routes: Arc::default(),
             ready: Arc::clone(&ready),
+            draining: AtomicBool::new(false),
+            epoch_fence: EpochFence::default(),
+            data_revision: watch::Sender::new(0),
+            prefetched_images: RwLock::default(),
+            data_dir,
             tasks,
             rpc_nats: Arc::clone(&rpc_nats),
             registry_config: RwLock::new(self.registry_config),
             // Extension traits that we fallback to defaults for
-            event_publisher: self
-                .event_publisher
-                .unwrap_or_else(|| Arc::new(DefaultEventPublisher::default())),
+            event_publisher,
+            watchdog,
             policy_manager: self
                 .policy_manager
                 .unwrap_or_else(|| Arc::new(DefaultPolicyManager)),
@@ -706,7 +891,8 @@ impl HostBuilder {
                 .unwrap_or_else(|| Arc::new(DefaultStore::default())),
             config_generator: self
                 .bundle_generator
-                .unwrap_or_else(|| BundleGenerator::new(Arc::new(DefaultStore::default()))),
+                .unwrap_or_else(|| BundleGenerator::new(Arc::new(DefaultStore::default())))
+                .with_host_id(self.config.host_key.public_key()),
             // TODO(#4407): This trait abstraction isn't actually abstracted since all capability
             // providers are NATS based. As we revise communication with providers, we can update
             // this to be a trait object from the builder instead.
@@ -767,6 +953,8 @@ impl HostBuilder {
             }
         });
 
+        host.start_watchdog()?;
+
         let start_evt = json!({
             "id": host.host_key.public_key(),
             "friendly_name": host.friendly_name,
@@ -782,11 +970,44 @@ impl HostBuilder {
             host_id = host.host_key.public_key(),
             "wasmCloud host started"
         );
+        if host.host_config.read_only {
+            info!("host is running in read-only mode, mutating control interface commands will be refused");
+        }
+
+        if !host.host_config.prefetch_images.is_empty() {
+            host.prefetch_images(host.host_config.prefetch_images.clone())
+                .await;
+        }
+        host.start_prefetch_refresh();
+
+        if let Some(path) = &host.host_config.workload_manifest {
+            let manifest = WorkloadManifest::load(path).await?;
+            host.apply_workload_manifest(manifest)
+                .await
+                .context("failed to start workloads from manifest")?;
+        }
 
         Ok((Arc::clone(&host), async move {
             ready.store(false, Ordering::Relaxed);
             heartbeat_abort.abort();
             heartbeat.await.context("failed to await heartbeat")?;
+            // Give the components their termination grace period to finish in-flight
+            // invocations, bounded by the deadline of the stop command
+            let deadline = *host.stop_rx.borrow();
+            let components: Vec<_> = host.components.write().await.drain().collect();
+            let host_id = host.host_key.public_key();
+            let drained = futures::future::join_all(
+                components
+                    .iter()
+                    .map(|(_, component)| host.begin_stop_component(component, &host_id)),
+            );
+            if let Some(deadline) = deadline {
+                if timeout_at(deadline, drained).await.is_err() {
+                    warn!("components did not stop before the stop deadline");
+                }
+            } else {
+                drained.await;
+            }
             host.event_publisher
                 .publish_event(
                     "host_stopped",
@@ -807,6 +1028,20 @@ impl HostBuilder {
     }
 }
 
+/// Build the runtime used to start components with the limits of `config`
+fn build_runtime(config: &HostConfig) -> anyhow::Result<Runtime> {
+    let (runtime, _epoch) = Runtime::builder()
+        .max_execution_time(config.max_execution_time)
+        .max_linear_memory(config.max_linear_memory)
+        .max_components(config.max_components)
+        .max_core_instances_per_component(config.max_core_instances_per_component)
+        .max_component_size(config.max_component_size)
+        .experimental_features(config.experimental_features.into())
+        .build()
+        .context("failed to build runtime")?;
+    Ok(runtime)
+}
+
 impl From<HostConfig> for HostBuilder {
     fn from(config: HostConfig) -> Self {
         HostBuilder {
@@ -851,6 +1086,93 @@ impl Host {
         &self.host_config.lattice
     }
 
+    /// Returns the revision of the lattice data bucket up to which all changes were applied
+    pub(crate) fn data_revision(&self) -> u64 {
+        *self.data_revision.borrow()
+    }
+
+    /// Record that all changes up to `revision` of the lattice data bucket were applied
+    pub(crate) fn set_data_revision(&self, revision: u64) {
+        self.data_revision.send_if_modified(|current| {
+            if revision > *current {
+                *current = revision;
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Wait until all changes up to `min_revision` of the lattice data bucket were applied,
+    /// returning the revision applied at that point
+    async fn wait_for_data_revision(&self, min_revision: u64) -> anyhow::Result<u64> {
+        let mut revision = self.data_revision.subscribe();
+        let reached = timeout(
+            REVISION_WAIT_TIMEOUT,
+            revision.wait_for(|revision| *revision >= min_revision),
+        )
+        .await
+        .map(|reached| reached.map(|revision| *revision));
+        match reached {
+            Ok(Ok(revision)) => Ok(revision),
+            Ok(Err(_)) => bail!("lattice data bucket is no longer watched"),
+            Err(_) => bail!(
+                "lattice data bucket revision {min_revision} not reached, host is at revision {}",
+                *revision.borrow()
+            ),
+        }
+    }
+
+    /// Wait until the config bucket reached `min_revision`, returning the revision of the bucket at
+    /// that point or `None` if the config store does not track revisions.
+    ///
+    /// Unlike the lattice data bucket, the config bucket is not watched as a whole, so its
+    /// revision is polled. Reads may be served by a lagging replica of the bucket.
+    async fn wait_for_config_revision(&self, min_revision: u64) -> anyhow::Result<Option<u64>> {
+        let deadline = Instant::now() + REVISION_WAIT_TIMEOUT;
+        loop {
+            match self.config_store.revision().await? {
+                None if min_revision > 0 => {
+                    bail!("config store does not support reads pinned to a revision")
+                }
+                None => return Ok(None),
+                Some(revision) if revision >= min_revision => return Ok(Some(revision)),
+                Some(revision) if Instant::now() >= deadline => bail!(
+                    "config bucket revision {min_revision} not reached, host is at revision {revision}"
+                ),
+                Some(_) => sleep(REVISION_RETRY_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Returns the runtime used to start components
+    fn runtime(&self) -> Runtime {
+        self.runtime
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the heartbeats of the subsystems monitored by the watchdog
+    pub(crate) fn watchdog(&self) -> &Watchdog {
+        &self.watchdog
+    }
+
+    /// Returns the NATS client used for making RPC calls
+    pub(crate) fn rpc_nats(&self) -> &async_nats::Client {
+        &self.rpc_nats
+    }
+
+    /// Returns the metrics emitted by the host
+    pub(crate) fn metrics(&self) -> &HostMetrics {
+        &self.metrics
+    }
+
+    /// Returns true if the host refuses mutating control interface commands
+    pub fn is_read_only(&self) -> bool {
+        self.host_config.read_only
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn inventory(&self) -> HostInventory {
         trace!("generating host inventory");
@@ -861,9 +1183,17 @@ impl Host {
                     let mut description = ComponentDescription::builder()
                         .id(id.into())
                         .image_ref(component.image_reference.to_string())
-                        .annotations(component.annotations.clone().into_iter().collect())
+                        .annotations(
+                            component
+                                .annotations
+                                .clone()
+                                .into_iter()
+                                .collect::<BTreeMap<_, _>>(),
+                        )
                         .max_instances(component.max_instances.get().try_into().unwrap_or(u32::MAX))
                         .limits(component.limits.map(|limits| limits.to_string_map()))
+                        .memory_bytes(component.memory_usage().resident_bytes())
+                        .running_instances(component.memory_usage().instances())
                         .revision(
                             component
                                 .claims()
@@ -936,8 +1266,19 @@ impl Host {
             )
             .collect();
 
+        let data_dir = match &self.data_dir {
+            Some(dir) => match dir.usage().await {
+                Ok(usage) => Some(usage),
+                Err(err) => {
+                    warn!(?err, "failed to compute data directory usage");
+                    None
+                }
+            },
+            None => None,
+        };
+
         let uptime = self.start_at.elapsed();
-        HostInventory::builder()
+        let inventory = HostInventory::builder()
             .components(components)
             .providers(providers)
             .friendly_name(self.friendly_name.clone())
@@ -946,8 +1287,20 @@ impl Host {
             .uptime_seconds(uptime.as_secs())
             .version(self.host_config.version.clone())
             .host_id(self.host_key.public_key())
-            .build()
-            .expect("failed to build host inventory")
+            .prefetched_images(
+                self.prefetched_images
+                    .read()
+                    .await
+                    .values()
+                    .cloned()
+                    .collect(),
+            );
+        let inventory = if let Some(usage) = data_dir {
+            inventory.data_dir(usage)
+        } else {
+            inventory
+        };
+        inventory.build().expect("failed to build host inventory")
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -977,13 +1330,17 @@ impl Host {
 
         let max_execution_time = self.max_execution_time; // TODO: Needs approval to go ahead.
         component.set_max_execution_time(max_execution_time);
+        component.set_priority(priority_class(annotations));
+        let cpu_time = component.cpu_time().clone();
+        let reported_cpu_time = Arc::new(std::sync::Mutex::new(Duration::ZERO));
+        let memory_usage = component.memory_usage().clone();
 
         let (events_tx, mut events_rx) = mpsc::channel(
             max_instances
                 .get()
                 .clamp(MIN_INVOCATION_CHANNEL_SIZE, MAX_INVOCATION_CHANNEL_SIZE),
         );
-        let prefix = Arc::from(format!("{}.{id}", &self.host_config.lattice));
+        let prefix = Arc::from(format!("{}.{id}", self.host_config.lattice));
         let nats = wrpc_transport_nats::Client::new(
             Arc::clone(&self.rpc_nats),
             Arc::clone(&prefix),
@@ -1008,6 +1365,7 @@ impl Host {
         let permits = Arc::new(Semaphore::new(
             usize::from(max_instances).min(Semaphore::MAX_PERMITS),
         ));
+        let (force_stop, force_stop_rx) = watch::channel(false);
         let component_attributes = Arc::new(vec![
             KeyValue::new("component.id", id.to_string()),
             KeyValue::new("component.ref", image_reference.to_string()),
@@ -1018,12 +1376,15 @@ impl Host {
             .set_max_instances(max_instances.get() as u64, &component_attributes);
 
         let metrics = Arc::clone(&self.metrics);
+        let profile = Arc::<InvocationRecorder>::default();
         Ok(Arc::new(Component {
             component,
             id: Arc::clone(&id),
             handler,
             events: events_tx,
             permits: Arc::clone(&permits),
+            force_stop,
+            profile: Arc::clone(&profile),
             exports: spawn(async move {
                 // Since we are joining two `move` closures, we need two separate `Arc`s
                 let metrics_left = Arc::clone(&metrics);
@@ -1035,6 +1396,10 @@ impl Host {
                             let metrics_left = Arc::clone(&metrics_left);
                             let component_attributes = Arc::clone(&component_attributes);
                             let permits = Arc::clone(&permits);
+                            let cpu_time = cpu_time.clone();
+                            let reported_cpu_time = Arc::clone(&reported_cpu_time);
+                            let memory_usage = memory_usage.clone();
+                            let mut force_stop = force_stop_rx.clone();
                             if let Some(fut) = exports.next().await {
                                 match fut {
                                     Ok(fut) => {
@@ -1048,10 +1413,28 @@ impl Host {
                                             let _permit = permit;
                                             debug!("handling invocation");
                                             // Awaiting this future drives the execution of the component
-                                            let result = timeout(max_execution_time, fut).await;
+                                            let result = select! {
+                                                result = timeout(max_execution_time, fut) => Some(result),
+                                                _ = force_stop.wait_for(|stop| *stop) => None,
+                                            };
                                             metrics_left
                                                 .decrement_active_instance(&component_attributes);
-
+                                            metrics_left.record_component_cpu_time(
+                                                &cpu_time,
+                                                &reported_cpu_time,
+                                                &component_attributes,
+                                            );
+                                            metrics_left.set_component_resident_memory(
+                                                memory_usage.resident_bytes(),
+                                                &component_attributes,
+                                            );
+
+                                            let Some(result) = result else {
+                                                warn!("component invocation cancelled, component was stopped forcefully");
+                                                return Err(anyhow::anyhow!(
+                                                    "component invocation cancelled"
+                                                ));
+                                            };
                                             match result {
                                                 Ok(Ok(())) => {
                                                     debug!("successfully handled invocation");
@@ -1085,6 +1468,7 @@ impl Host {
                                         InvocationContext {
                                             start_at,
                                             ref attributes,
+                                            ref invocation_attributes,
                                             ..
                                         },
                                     success,
@@ -1094,6 +1478,7 @@ impl Host {
                                         InvocationContext {
                                             start_at,
                                             ref attributes,
+                                            ref invocation_attributes,
                                             ..
                                         },
                                     success,
@@ -1103,15 +1488,21 @@ impl Host {
                                         InvocationContext {
                                             start_at,
                                             ref attributes,
+                                            ref invocation_attributes,
                                             ..
                                         },
                                     success,
-                                } => metrics_right.record_component_invocation(
-                                    u64::try_from(start_at.elapsed().as_nanos())
-                                        .unwrap_or_default(),
-                                    attributes,
-                                    !success,
-                                ),
+                                } => {
+                                    let elapsed = u64::try_from(start_at.elapsed().as_nanos())
+                                        .unwrap_or_default();
+                                    metrics_right.record_component_invocation(
+                                        elapsed,
+                                        attributes,
+                                        invocation_attributes,
+                                        !success,
+                                    );
+                                    profile.record(elapsed, !success);
+                                }
                             }
                         }
                         debug!("serving event stream is done");
@@ -1169,11 +1560,13 @@ impl Host {
                 let mut links = self.messaging_links.write().await;
                 Arc::clone(links.entry(Arc::clone(&component_id)).or_default())
             },
+            traffic_routes: Arc::clone(&self.traffic_routes),
             invocation_timeout: Duration::from_secs(10), // TODO: Make this configurable
             experimental_features: self.experimental_features,
             host_labels: Arc::clone(&self.labels),
+            metrics: Arc::clone(&self.metrics),
         };
-        let component = wasmcloud_runtime::Component::new(&self.runtime, wasm, limits)?;
+        let component = wasmcloud_runtime::Component::new(&self.runtime(), wasm, limits)?;
         let component = self
             .instantiate_component(
                 annotations,
@@ -1205,13 +1598,65 @@ impl Host {
         Ok(entry.insert(component))
     }
 
+    /// Stop accepting invocations for a component and wait for its in-flight invocations to
+    /// finish. Invocations still running once the termination grace period elapsed (by default,
+    /// the maximum execution time) are cancelled.
     #[instrument(level = "debug", skip_all)]
-    async fn stop_component(&self, component: &Component, _host_id: &str) -> anyhow::Result<()> {
+    async fn stop_component(&self, component: &Component, host_id: &str) -> anyhow::Result<()> {
+        self.begin_stop_component(component, host_id).await;
+        Ok(())
+    }
+
+    /// Stop accepting invocations for a component, returning a future that drains its in-flight
+    /// invocations like [`Host::stop_component`]. Used where the caller must not wait for the
+    /// drain, e.g. while holding the lock of the components map.
+    fn begin_stop_component(
+        &self,
+        component: &Component,
+        host_id: &str,
+    ) -> impl Future<Output = ()> + Send + 'static {
         trace!(component_id = %component.id, "stopping component");
 
         component.exports.abort();
 
-        Ok(())
+        let grace_period =
+            termination_grace_period(&component.annotations).unwrap_or(self.max_execution_time);
+        let permits = Arc::clone(&component.permits);
+        let max_instances = u32::try_from(component.max_instances.get()).unwrap_or(u32::MAX);
+        let force_stop = component.force_stop.clone();
+        let event_publisher = Arc::clone(&self.event_publisher);
+        let annotations = component.annotations.clone();
+        let image_reference = Arc::clone(&component.image_reference);
+        let component_id = Arc::clone(&component.id);
+        let host_id = host_id.to_string();
+        async move {
+            let reason =
+                drain_invocations(&permits, max_instances, grace_period, &force_stop).await;
+            if reason == STOP_REASON_GRACEFUL {
+                debug!(%component_id, "component stopped gracefully");
+            } else {
+                warn!(
+                    %component_id,
+                    ?grace_period,
+                    "component did not finish in-flight invocations in time, stopped forcefully"
+                );
+            }
+            if let Err(err) = event_publisher
+                .publish_event(
+                    "component_stopped",
+                    crate::event::component_stopped(
+                        &annotations,
+                        &host_id,
+                        &image_reference,
+                        &component_id,
+                        reason,
+                    ),
+                )
+                .await
+            {
+                warn!(%component_id, ?err, "failed to publish component stopped event");
+            }
+        }
     }
 
     #[instrument(level = "trace", skip_all)]
@@ -1227,6 +1672,151 @@ impl Host {
         .context("failed to fetch component")
     }
 
+    /// Prefetch images into the artifact cache in the background, tracking their status for the
+    /// host inventory. Images that are already tracked are fetched again, which refreshes the
+    /// cached artifact if the reference now points to a different digest.
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) async fn prefetch_images(self: &Arc<Self>, image_refs: Vec<String>) {
+        {
+            let mut prefetched_images = self.prefetched_images.write().await;
+            for image_ref in &image_refs {
+                prefetched_images.insert(
+                    image_ref.clone(),
+                    PrefetchedImage::new(image_ref, PrefetchStatus::Pending),
+                );
+            }
+        }
+        let host = Arc::clone(self);
+        spawn(async move {
+            for image_ref in image_refs {
+                host.set_prefetch_status(PrefetchedImage::new(
+                    &image_ref,
+                    PrefetchStatus::Fetching,
+                ))
+                .await;
+                // Fetching can take a while, which must not block updates of the registry config
+                let registry_config = host.registry_config.read().await.clone();
+                let res = crate::prefetch_artifact(
+                    &image_ref,
+                    &host.host_config.oci_opts,
+                    &registry_config,
+                )
+                .await;
+                let status = match res {
+                    Ok(()) => {
+                        info!(image_ref, "prefetched image");
+                        PrefetchedImage::new(&image_ref, PrefetchStatus::Cached)
+                    }
+                    Err(err) => {
+                        warn!(image_ref, ?err, "failed to prefetch image");
+                        PrefetchedImage::new(&image_ref, PrefetchStatus::Failed)
+                            .with_error(format!("{err:#}"))
+                    }
+                };
+                host.set_prefetch_status(status).await;
+            }
+        });
+    }
+
+    /// Prefetch all tracked images again, e.g. to restore images evicted from the artifact cache
+    pub(crate) async fn refresh_prefetched_images(self: &Arc<Self>) {
+        let image_refs: Vec<_> = self
+            .prefetched_images
+            .read()
+            .await
+            .keys()
+            .cloned()
+            .collect();
+        if !image_refs.is_empty() {
+            debug!(?image_refs, "refreshing prefetched images");
+            self.prefetch_images(image_refs).await;
+        }
+    }
+
+    /// Periodically refresh the prefetched images if configured via
+    /// [`HostConfig::prefetch_refresh_interval`]
+    fn start_prefetch_refresh(self: &Arc<Self>) {
+        let Some(period) = self.host_config.prefetch_refresh_interval else {
+            return;
+        };
+        let host = Arc::downgrade(self);
+        spawn(async move {
+            let mut interval = interval_at(Instant::now() + period, period);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let Some(host) = host.upgrade() else {
+                    return;
+                };
+                host.refresh_prefetched_images().await;
+            }
+        });
+    }
+
+    async fn set_prefetch_status(&self, image: PrefetchedImage) {
+        self.prefetched_images
+            .write()
+            .await
+            .insert(image.image_ref().to_string(), image);
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_prefetch_images(
+        self: Arc<Self>,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let cmd = serde_json::from_slice::<PrefetchImagesCommand>(payload.as_ref())
+            .context("failed to deserialize prefetch images command")?;
+        let host_id = cmd.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_prefetch_images(self, cmd).await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_cleanup_host_data(
+        self: Arc<Self>,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<DataDirUsage>> {
+        let cmd = serde_json::from_slice::<CleanupHostDataCommand>(payload.as_ref())
+            .context("failed to deserialize cleanup host data command")?;
+        let host_id = cmd.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_cleanup_host_data(self, cmd).await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_update_host_tracing(
+        self: Arc<Self>,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let cmd = serde_json::from_slice::<UpdateHostTracingCommand>(payload.as_ref())
+            .context("failed to deserialize update host tracing command")?;
+        let host_id = cmd.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_update_host_tracing(self, cmd).await
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub(crate) async fn handle_auction_component(
         &self,
@@ -1247,6 +1837,92 @@ impl Host {
         <Self as ControlInterfaceServer>::handle_auction_provider(self, request).await
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_drain_host(
+        self: Arc<Self>,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let cmd = if payload.as_ref().is_empty() {
+            DrainHostCommand::new(transport_host_id, DrainOptions::default())
+        } else {
+            serde_json::from_slice::<DrainHostCommand>(payload.as_ref())
+                .context("failed to deserialize drain command")?
+        };
+        let host_id = cmd.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_drain_host(self, cmd).await
+    }
+
+    /// Record that this host is stopped permanently and remove the data it keeps about itself, so
+    /// that it stops appearing in tooling once it is gone
+    #[instrument(level = "debug", skip_all)]
+    async fn decommission(&self) -> anyhow::Result<HostDecommission> {
+        /// Data that is only of use for troubleshooting this host
+        const HOST_DATA: [DataCategory; 2] =
+            [DataCategory::ProviderLogs, DataCategory::FlightRecordings];
+
+        // Decline pending and future auctions, the host won't be around to run their workloads
+        self.draining.store(true, Ordering::Relaxed);
+
+        let host_id = self.host_key.public_key();
+        if let Err(err) = remove_host_config(self.config_store.as_ref(), &host_id).await {
+            warn!(?err, "failed to remove host config while decommissioning");
+        }
+        let removed_data = match &self.data_dir {
+            Some(data_dir) => match data_dir.cleanup(&HOST_DATA).await {
+                Ok(freed) => {
+                    debug!(freed, "removed host data");
+                    HOST_DATA.to_vec()
+                }
+                Err(err) => {
+                    warn!(?err, "failed to remove host data while decommissioning");
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        let decommissioned_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis().try_into().unwrap_or(u64::MAX))
+            .unwrap_or_default();
+        let labels = core::mem::take(&mut *self.labels.write().await);
+        self.event_publisher
+            .publish_event(
+                "labels_changed",
+                crate::event::labels_changed(&host_id, HashMap::new()),
+            )
+            .await
+            .context("failed to publish labels_changed event")?;
+        let record = HostDecommission::new(
+            &host_id,
+            &self.friendly_name,
+            labels,
+            &self.host_config.version,
+            decommissioned_at,
+        )
+        .with_removed_data(removed_data);
+        let value =
+            serde_json::to_vec(&record).context("failed to serialize decommission record")?;
+        self.data_store
+            .put(&record.key(), value.into())
+            .await
+            .context("failed to write decommission record")?;
+        self.event_publisher
+            .publish_event(
+                "host_decommissioned",
+                crate::event::host_decommissioned(&record),
+            )
+            .await?;
+        Ok(record)
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub(crate) async fn handle_stop_host(
         &self,
@@ -1254,8 +1930,8 @@ impl Host {
         transport_host_id: &str,
     ) -> anyhow::Result<CtlResponse<()>> {
         // Allow an empty payload to be used for stopping hosts
-        let timeout = if payload.as_ref().is_empty() {
-            None
+        let (timeout, decommission) = if payload.as_ref().is_empty() {
+            (None, false)
         } else {
             let cmd = serde_json::from_slice::<StopHostCommand>(payload.as_ref())
                 .context("failed to deserialize stop command")?;
@@ -1270,7 +1946,7 @@ impl Host {
                     "invalid host_id [{host_id}]"
                 );
             }
-            timeout
+            (timeout, cmd.decommission())
         };
 
         // It *should* be impossible for the transport-derived host ID to not match at this point
@@ -1279,7 +1955,9 @@ impl Host {
             "invalid host_id [{transport_host_id}]"
         );
 
-        let mut stop_command = StopHostCommand::builder().host_id(transport_host_id);
+        let mut stop_command = StopHostCommand::builder()
+            .host_id(transport_host_id)
+            .decommission(decommission);
         if let Some(timeout) = timeout {
             stop_command = stop_command.timeout(timeout);
         }
@@ -1303,6 +1981,63 @@ impl Host {
         <Self as ControlInterfaceServer>::handle_scale_component(self, request).await
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_stop_components(
+        &self,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<Vec<String>>> {
+        let cmd = serde_json::from_slice::<StopComponentsCommand>(payload.as_ref())
+            .context("failed to deserialize stop components command")?;
+        let host_id = cmd.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_stop_components(self, cmd).await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_profile_component(
+        &self,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<ComponentProfile>> {
+        let cmd = serde_json::from_slice::<ProfileComponentCommand>(payload.as_ref())
+            .context("failed to deserialize profile component command")?;
+        let host_id = cmd.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_profile_component(self, cmd).await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_top_memory_consumers(
+        &self,
+        payload: impl AsRef<[u8]>,
+        transport_host_id: &str,
+    ) -> anyhow::Result<CtlResponse<Vec<ComponentMemoryUsage>>> {
+        let query = serde_json::from_slice::<TopMemoryQuery>(payload.as_ref())
+            .context("failed to deserialize top memory consumers query")?;
+        let host_id = query.host_id();
+        if !host_id.is_empty() {
+            anyhow::ensure!(host_id == transport_host_id, "invalid host_id [{host_id}]");
+        }
+        anyhow::ensure!(
+            transport_host_id == self.host_key.public_key(),
+            "invalid host_id [{transport_host_id}]"
+        );
+        <Self as ControlInterfaceServer>::handle_top_memory_consumers(self, query).await
+    }
+
     #[instrument(level = "debug", skip_all)]
     /// Handles scaling an component to a supplied number of `max` concurrently executing instances.
     /// Supplying `0` will result in stopping that component instance.
@@ -1426,9 +2161,9 @@ impl Host {
             // Component is running and we requested to scale to zero instances, stop component
             (hash_map::Entry::Occupied(entry), None) => {
                 let component = entry.remove();
-                self.stop_component(&component, host_id)
-                    .await
-                    .context("failed to stop component in response to scale to zero")?;
+                // The components map stays locked while this task runs, so the drain must not be
+                // awaited here
+                spawn(self.begin_stop_component(&component, host_id));
 
                 info!(?component_ref, "component stopped");
                 crate::event::component_scaled(
@@ -1485,9 +2220,7 @@ impl Host {
                         .await
                         .context("failed to instantiate component")?;
                     let component = entry.insert(instance);
-                    self.stop_component(&component, host_id)
-                        .await
-                        .context("failed to stop component after scaling")?;
+                    spawn(self.begin_stop_component(&component, host_id));
 
                     info!(?component_ref, ?max, "component scaled");
                 } else {
@@ -1541,7 +2274,7 @@ impl Host {
 
             let new_component = self.fetch_component(&new_component_ref).await?;
             let new_component = wasmcloud_runtime::Component::new(
-                &self.runtime,
+                &self.runtime(),
                 &new_component,
                 existing_component.limits,
             )
@@ -1706,6 +2439,8 @@ impl Host {
                 )
                 .await?;
             let config_bundle = Arc::new(RwLock::new(config_bundle));
+            let (config_updates_tx, config_updates_rx) = mpsc::channel(1);
+            let is_binary = path.is_some();
             // Used by provider child tasks (health check, config watch, process restarter) to
             // know when to shutdown.
             let shutdown = Arc::new(AtomicBool::new(false));
@@ -1720,6 +2455,7 @@ impl Host {
                             provider_id,
                             // Arguments to allow regenerating configuration later
                             config_names.to_vec(),
+                            config_updates_rx,
                             claims_token.clone(),
                             annotations.clone(),
                             shutdown.clone(),
@@ -1778,6 +2514,7 @@ impl Host {
                 image_ref: provider_ref.as_ref().to_string(),
                 xkey,
                 shutdown,
+                config_updates: is_binary.then_some(config_updates_tx),
             });
         } else {
             bail!("provider is already running with that ID")
@@ -1786,6 +2523,16 @@ impl Host {
         Ok(())
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_update_provider_config(
+        &self,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let cmd = serde_json::from_slice::<UpdateProviderConfigCommand>(payload.as_ref())
+            .context("failed to deserialize provider config update command")?;
+        <Self as ControlInterfaceServer>::handle_update_provider_config(self, cmd).await
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub(crate) async fn handle_stop_provider(
         &self,
@@ -1801,21 +2548,50 @@ impl Host {
         <Self as ControlInterfaceServer>::handle_inventory(self).await
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_inventory_page(
+        &self,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<CtlResponse<HostInventoryPage>> {
+        let request = serde_json::from_slice::<InventoryPageRequest>(payload.as_ref())
+            .context("failed to deserialize inventory page request")?;
+        <Self as ControlInterfaceServer>::handle_inventory_page(self, request).await
+    }
+
     #[instrument(level = "trace", skip_all)]
     pub(crate) async fn handle_claims(
         &self,
+        payload: impl AsRef<[u8]>,
     ) -> anyhow::Result<CtlResponse<Vec<HashMap<String, String>>>> {
-        <Self as ControlInterfaceServer>::handle_claims(self).await
+        let query = revision_query(payload)?;
+        <Self as ControlInterfaceServer>::handle_claims_at_revision(self, query).await
     }
 
     #[instrument(level = "trace", skip_all)]
-    pub(crate) async fn handle_links(&self) -> anyhow::Result<Vec<u8>> {
-        <Self as ControlInterfaceServer>::handle_links(self).await
+    pub(crate) async fn handle_links(&self, payload: impl AsRef<[u8]>) -> anyhow::Result<Vec<u8>> {
+        let query = revision_query(payload)?;
+        <Self as ControlInterfaceServer>::handle_links_at_revision(self, query).await
     }
 
-    #[instrument(level = "trace", skip(self))]
-    pub(crate) async fn handle_config_get(&self, config_name: &str) -> anyhow::Result<Vec<u8>> {
-        <Self as ControlInterfaceServer>::handle_config_get(self, config_name).await
+    #[instrument(level = "trace", skip(self, payload))]
+    pub(crate) async fn handle_config_get(
+        &self,
+        config_name: &str,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<Vec<u8>> {
+        let query = revision_query(payload)?;
+        <Self as ControlInterfaceServer>::handle_config_get_at_revision(self, config_name, query)
+            .await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_config_get_many(
+        &self,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<CtlResponse<ConfigsByName>> {
+        let request = serde_json::from_slice::<ConfigNames>(payload.as_ref())
+            .context("failed to deserialize get many configs request")?;
+        <Self as ControlInterfaceServer>::handle_config_get_many(self, request).await
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -1840,6 +2616,28 @@ impl Host {
         <Self as ControlInterfaceServer>::handle_label_del(self, label, host_id).await
     }
 
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_labels_put(
+        &self,
+        host_id: &str,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let host_labels = serde_json::from_slice::<HostLabels>(payload.as_ref())
+            .context("failed to deserialize put labels request")?;
+        <Self as ControlInterfaceServer>::handle_labels_put(self, host_labels, host_id).await
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn handle_labels_del(
+        &self,
+        host_id: &str,
+        payload: impl AsRef<[u8]>,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        let labels = serde_json::from_slice::<HostLabelIdentifiers>(payload.as_ref())
+            .context("failed to deserialize delete labels request")?;
+        <Self as ControlInterfaceServer>::handle_labels_del(self, labels, host_id).await
+    }
+
     /// Handle a new link by modifying the relevant source [ComponentSpecification]. Once
     /// the change is written to the LATTICEDATA store, each host in the lattice (including this one)
     /// will handle the new specification and update their own internal link maps via [process_component_spec_put].
@@ -1848,9 +2646,9 @@ impl Host {
         &self,
         payload: impl AsRef<[u8]>,
     ) -> anyhow::Result<CtlResponse<()>> {
-        let link: Link = serde_json::from_slice(payload.as_ref())
+        let request: PutLinkRequest = serde_json::from_slice(payload.as_ref())
             .context("failed to deserialize wrpc link definition")?;
-        <Self as ControlInterfaceServer>::handle_link_put(self, link).await
+        <Self as ControlInterfaceServer>::handle_link_put(self, request).await
     }
 
     #[instrument(level = "debug", skip_all)]
@@ -1895,6 +2693,36 @@ impl Host {
         <Self as ControlInterfaceServer>::handle_config_delete(self, config_name).await
     }
 
+    #[instrument(level = "trace", skip(self))]
+    pub(crate) async fn handle_host_config_get(
+        &self,
+        host_id: &str,
+        config_name: &str,
+    ) -> anyhow::Result<Vec<u8>> {
+        <Self as ControlInterfaceServer>::handle_host_config_get(self, host_id, config_name).await
+    }
+
+    #[instrument(level = "debug", skip(self, data))]
+    pub(crate) async fn handle_host_config_put(
+        &self,
+        host_id: &str,
+        config_name: &str,
+        data: Bytes,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        <Self as ControlInterfaceServer>::handle_host_config_put(self, host_id, config_name, data)
+            .await
+    }
+
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) async fn handle_host_config_delete(
+        &self,
+        host_id: &str,
+        config_name: &str,
+    ) -> anyhow::Result<CtlResponse<()>> {
+        <Self as ControlInterfaceServer>::handle_host_config_delete(self, host_id, config_name)
+            .await
+    }
+
     #[instrument(level = "debug", skip_all)]
     pub(crate) async fn handle_ping_hosts(
         &self,
@@ -2004,7 +2832,7 @@ impl Host {
             .context("failed to publish provider link definition delete")
     }
 
-    async fn fetch_config_and_secrets(
+    pub(crate) async fn fetch_config_and_secrets(
         &self,
         config_names: &[String],
         entity_jwt: Option<&String>,
@@ -2034,7 +2862,7 @@ impl Host {
     ///
     /// For any configuration that starts with `SECRET_`, the configuration is expected to be a secret reference.
     /// For any other configuration, the configuration is expected to be a [`HashMap<String, String>`].
-    async fn validate_config<I>(&self, config_names: I) -> anyhow::Result<()>
+    pub(crate) async fn validate_config<I>(&self, config_names: I) -> anyhow::Result<()>
     where
         I: IntoIterator<Item: AsRef<str>>,
     {
@@ -2073,6 +2901,12 @@ impl Host {
         Ok(())
     }
 
+    /// Verifies that the configs pinned to a version by a command match the version of the config
+    /// read from the store.
+    pub(crate) async fn verify_pinned_config(&self, pinned: &[ConfigRef]) -> anyhow::Result<()> {
+        verify_pinned_config(self.config_store.as_ref(), pinned).await
+    }
+
     /// Transform a [`wasmcloud_control_interface::Link`] into a [`wasmcloud_core::InterfaceLinkDefinition`]
     /// by fetching the source and target configurations and secrets, and encrypting the secrets.
     async fn resolve_link_config(
@@ -2188,6 +3022,65 @@ fn component_import_links(links: &[Link]) -> HashMap<Box<str>, HashMap<Box<str>,
     m
 }
 
+/// Remove the config overrides scoped to `host_id` from `config_store`, returning the number of
+/// removed configs
+async fn remove_host_config(
+    config_store: &dyn StoreManager,
+    host_id: &str,
+) -> anyhow::Result<usize> {
+    let keys = config_store
+        .keys(&host_config_key(host_id, ""))
+        .await
+        .context("failed to list host config")?;
+    for key in &keys {
+        config_store
+            .del(key)
+            .await
+            .with_context(|| format!("failed to remove host config [{key}]"))?;
+    }
+    Ok(keys.len())
+}
+
+/// Verify that the configs pinned to a version match the version of the config read from
+/// `config_store`
+async fn verify_pinned_config(
+    config_store: &dyn StoreManager,
+    pinned: &[ConfigRef],
+) -> anyhow::Result<()> {
+    for config_ref in pinned {
+        let name = config_ref.name();
+        let Some((data, revision)) = config_store
+            .get_with_revision(name)
+            .await
+            .with_context(|| format!("failed to get config `{name}`"))?
+        else {
+            bail!("Config `{name}` does not exist");
+        };
+        match config_ref.version() {
+            Some(ConfigVersion::Revision(pinned)) => {
+                let revision = revision.with_context(|| {
+                    format!("config store does not track revisions to verify config `{config_ref}`")
+                })?;
+                ensure!(
+                    revision == *pinned,
+                    "Config `{name}` is at revision {revision}, but revision {pinned} was requested"
+                );
+            }
+            Some(ConfigVersion::Digest(pinned)) => {
+                let config: HashMap<String, String> = serde_json::from_slice(&data)
+                    .with_context(|| format!("failed to decode config `{name}`"))?;
+                let digest = ConfigRef::digest_of(&config);
+                ensure!(
+                    digest == *pinned,
+                    "Config `{name}` has digest `{digest}`, which does not match the pinned digest `{pinned}`"
+                );
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 fn human_friendly_uptime(uptime: Duration) -> String {
     // strip sub-seconds, then convert to human-friendly format
     humantime::format_duration(
@@ -2354,4 +3247,151 @@ mod test {
 
         assert_eq!(links_map, expected_result);
     }
+
+    #[test]
+    fn parses_termination_grace_annotation() {
+        use std::collections::BTreeMap;
+        use std::time::Duration;
+
+        use super::{termination_grace_period, TERMINATION_GRACE_ANNOTATION};
+
+        let annotations = |value: &str| {
+            BTreeMap::from([(TERMINATION_GRACE_ANNOTATION.to_string(), value.to_string())])
+        };
+        assert_eq!(termination_grace_period(&BTreeMap::new()), None);
+        assert_eq!(
+            termination_grace_period(&annotations("30")),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            termination_grace_period(&annotations("0")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(termination_grace_period(&annotations("30s")), None);
+        assert_eq!(termination_grace_period(&annotations("-1")), None);
+    }
+
+    #[tokio::test]
+    async fn drains_in_flight_invocations_within_grace_period() {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        use tokio::sync::{watch, Semaphore};
+        use tokio::time::Instant;
+
+        use super::{drain_invocations, STOP_REASON_FORCED, STOP_REASON_GRACEFUL};
+
+        let permits = Arc::new(Semaphore::new(2));
+        let (force_stop, force_stopped) = watch::channel(false);
+
+        // An invocation finishing within the grace period is awaited
+        let invocation = Arc::clone(&permits)
+            .acquire_owned()
+            .await
+            .expect("semaphore closed");
+        let started = Instant::now();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            drop(invocation);
+        });
+        let reason = drain_invocations(&permits, 2, Duration::from_secs(5), &force_stop).await;
+        assert_eq!(reason, STOP_REASON_GRACEFUL);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(!*force_stopped.borrow());
+
+        // An invocation outliving the grace period is cancelled
+        let _invocation = Arc::clone(&permits)
+            .acquire_owned()
+            .await
+            .expect("semaphore closed");
+        let started = Instant::now();
+        let reason = drain_invocations(&permits, 2, Duration::from_millis(100), &force_stop).await;
+        assert_eq!(reason, STOP_REASON_FORCED);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert!(*force_stopped.borrow());
+    }
+
+    #[tokio::test]
+    async fn removes_host_config() -> anyhow::Result<()> {
+        use crate::config::host_config_key;
+        use crate::store::{DefaultStore, StoreManager};
+
+        use super::remove_host_config;
+
+        let store = DefaultStore::default();
+        for key in [
+            host_config_key("NHOST", "cache"),
+            host_config_key("NHOST", "endpoint"),
+            host_config_key("NHOSTOTHER", "cache"),
+            "cache".to_string(),
+        ] {
+            store.put(&key, "{}".into()).await?;
+        }
+        assert_eq!(remove_host_config(&store, "NHOST").await?, 2);
+        assert_eq!(store.get(&host_config_key("NHOST", "cache")).await?, None);
+        assert_eq!(
+            store.get(&host_config_key("NHOST", "endpoint")).await?,
+            None
+        );
+        // Config of other hosts and lattice-wide config is left alone
+        assert!(store
+            .get(&host_config_key("NHOSTOTHER", "cache"))
+            .await?
+            .is_some());
+        assert!(store.get("cache").await?.is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verifies_pinned_config() -> anyhow::Result<()> {
+        use std::collections::HashMap;
+
+        use wasmcloud_control_interface::ConfigRef;
+
+        use crate::store::{DefaultStore, StoreManager};
+
+        use super::verify_pinned_config;
+
+        let store = DefaultStore::default();
+        let config = HashMap::from([("size".to_string(), "1024".to_string())]);
+        store
+            .put("cache", serde_json::to_vec(&config)?.into())
+            .await?;
+
+        let digest = ConfigRef::digest_of(&config);
+        let pinned =
+            ConfigRef::parse(format!("cache@sha256:{digest}")).map_err(|e| anyhow::anyhow!(e))?;
+        verify_pinned_config(&store, std::slice::from_ref(&pinned)).await?;
+
+        // The pin no longer matches once the config changed
+        store.put("cache", r#"{"size":"2048"}"#.into()).await?;
+        assert!(verify_pinned_config(&store, &[pinned]).await.is_err());
+
+        // Revisions can't be verified against a store that does not track them
+        let pinned = ConfigRef::parse("cache@1").map_err(|e| anyhow::anyhow!(e))?;
+        assert!(verify_pinned_config(&store, &[pinned]).await.is_err());
+        let missing =
+            ConfigRef::parse(format!("missing@sha256:{digest}")).map_err(|e| anyhow::anyhow!(e))?;
+        assert!(verify_pinned_config(&store, &[missing]).await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parses_priority_annotation() {
+        use std::collections::BTreeMap;
+
+        use wasmcloud_runtime::scheduling::PriorityClass;
+
+        use super::{priority_class, PRIORITY_ANNOTATION};
+
+        let annotations =
+            |value: &str| BTreeMap::from([(PRIORITY_ANNOTATION.to_string(), value.to_string())]);
+        assert_eq!(priority_class(&BTreeMap::new()), PriorityClass::Normal);
+        assert_eq!(priority_class(&annotations("low")), PriorityClass::Low);
+        assert_eq!(priority_class(&annotations(" High ")), PriorityClass::High);
+        assert_eq!(
+            priority_class(&annotations("urgent")),
+            PriorityClass::Normal
+        );
+    }
 }
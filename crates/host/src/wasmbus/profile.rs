@@ -0,0 +1,84 @@
+//! Recording of component invocations while a component is being profiled.
+
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::ensure;
+use wasmcloud_control_interface::LatencySummary;
+
+/// Maximum number of invocation latencies kept per profile, further invocations are only counted
+const MAX_PROFILE_SAMPLES: usize = 1_000_000;
+
+#[derive(Debug, Default)]
+struct Recording {
+    latencies_ns: Vec<u64>,
+    errors: u64,
+}
+
+/// Records the invocations of a component while a profile is active
+#[derive(Debug, Default)]
+pub(crate) struct InvocationRecorder {
+    recording: Mutex<Option<Recording>>,
+}
+
+impl InvocationRecorder {
+    /// Start recording invocations, failing if a profile is already active
+    pub(crate) fn start(&self) -> anyhow::Result<()> {
+        let mut recording = self
+            .recording
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        ensure!(recording.is_none(), "component is already being profiled");
+        *recording = Some(Recording::default());
+        Ok(())
+    }
+
+    /// Record an invocation that took `elapsed_ns`, if a profile is active
+    pub(crate) fn record(&self, elapsed_ns: u64, error: bool) {
+        let mut recording = self
+            .recording
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Some(recording) = recording.as_mut() else {
+            return;
+        };
+        if recording.latencies_ns.len() < MAX_PROFILE_SAMPLES {
+            recording.latencies_ns.push(elapsed_ns);
+        }
+        if error {
+            recording.errors += 1;
+        }
+    }
+
+    /// Stop recording and summarize the recorded invocations
+    pub(crate) fn finish(&self) -> LatencySummary {
+        let recording = self
+            .recording
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap_or_default();
+        LatencySummary::from_samples(recording.latencies_ns, recording.errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InvocationRecorder;
+
+    #[test]
+    fn invocation_recorder() {
+        let recorder = InvocationRecorder::default();
+        recorder.record(10, false);
+        recorder.start().expect("failed to start profile");
+        assert!(recorder.start().is_err());
+        recorder.record(20, false);
+        recorder.record(40, true);
+        let summary = recorder.finish();
+        assert_eq!(summary.count(), 2);
+        assert_eq!(summary.errors(), 1);
+        assert_eq!(summary.max().as_nanos(), 40);
+        // Invocations are not recorded once the profile finished
+        recorder.record(30, false);
+        assert_eq!(recorder.finish().count(), 0);
+    }
+}
@@ -254,6 +254,13 @@ impl Provider {
                                     KeyValue::new("lattice", Arc::clone(&lattice_id)),
                                     KeyValue::new("host", Arc::clone(&host_id)),
                                 ],
+                                invocation_attributes: crate::metrics::served_invocation_attributes(
+                                    &lattice_id,
+                                    &host_id,
+                                    &component.id,
+                                    "wasi:http/incoming-handler",
+                                    "handle",
+                                ),
                             },
                             req,
                         )
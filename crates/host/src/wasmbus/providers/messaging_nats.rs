@@ -124,9 +124,16 @@ async fn handle_message(
                 start_at: Instant::now(),
                 attributes: vec![
                     KeyValue::new("component.ref", Arc::clone(&component.image_reference)),
-                    KeyValue::new("lattice", lattice_id),
-                    KeyValue::new("host", host_id),
+                    KeyValue::new("lattice", Arc::clone(&lattice_id)),
+                    KeyValue::new("host", Arc::clone(&host_id)),
                 ],
+                invocation_attributes: crate::metrics::served_invocation_attributes(
+                    &lattice_id,
+                    &host_id,
+                    &component.id,
+                    "wasmcloud:messaging/handler",
+                    "handle-message",
+                ),
             },
             wrpc::wasmcloud::messaging0_2_0::types::BrokerMessage {
                 subject: msg.subject.into_string(),
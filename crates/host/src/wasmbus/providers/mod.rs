@@ -19,13 +19,13 @@ use futures::{stream, Future, StreamExt};
 use nkeys::XKey;
 use tokio::io::AsyncWriteExt;
 use tokio::process;
-use tokio::sync::RwLock;
+use tokio::select;
+use tokio::sync::{mpsc, oneshot, RwLock};
 use tokio::task::JoinSet;
 use tracing::{error, instrument, trace, warn};
-use uuid::Uuid;
 use wascap::jwt::{CapabilityProvider, Token};
 use wasmcloud_core::{
-    health_subject, provider_config_update_subject, HealthCheckResponse, HostData, OtelConfig,
+    health_subject, provider_config_update_subject, HealthCheckResponse, HostData, HostEnvValues,
 };
 use wasmcloud_runtime::capability::secrets::store::SecretValue;
 use wasmcloud_tracing::context::TraceContextInjector;
@@ -72,6 +72,18 @@ pub(crate) struct Provider {
     pub(crate) shutdown: Arc<AtomicBool>,
     /// Tasks running the provider, health check, and config watcher
     pub(crate) tasks: JoinSet<()>,
+    /// Updates of the named configs consumed by the provider, which are applied by the provider
+    /// supervisor. Builtin providers do not watch for configuration updates and have no sender.
+    pub(crate) config_updates: Option<mpsc::Sender<ConfigNamesUpdate>>,
+}
+
+/// A request to change the named configs consumed by a running binary provider
+#[derive(Debug)]
+pub(crate) struct ConfigNamesUpdate {
+    /// New list of named configs, replacing the previous list
+    pub(crate) names: Vec<String>,
+    /// Receives the outcome once the newly merged configuration was delivered to the provider
+    pub(crate) delivered: oneshot::Sender<anyhow::Result<()>>,
 }
 
 impl Host {
@@ -169,20 +181,7 @@ impl Host {
                 .try_into()
                 .context("failed to convert rpc_timeout to u64")?,
         );
-        let otel_config = OtelConfig {
-            enable_observability: self.host_config.otel_config.enable_observability,
-            enable_traces: self.host_config.otel_config.enable_traces,
-            enable_metrics: self.host_config.otel_config.enable_metrics,
-            enable_logs: self.host_config.otel_config.enable_logs,
-            observability_endpoint: self.host_config.otel_config.observability_endpoint.clone(),
-            traces_endpoint: self.host_config.otel_config.traces_endpoint.clone(),
-            metrics_endpoint: self.host_config.otel_config.metrics_endpoint.clone(),
-            logs_endpoint: self.host_config.otel_config.logs_endpoint.clone(),
-            protocol: self.host_config.otel_config.protocol,
-            additional_ca_paths: self.host_config.otel_config.additional_ca_paths.clone(),
-            trace_level: self.host_config.otel_config.trace_level.clone(),
-            ..Default::default()
-        };
+        let otel_config = self.host_config.otel_config.for_provider(provider_id);
 
         // The provider itself needs to know its private key
         let provider_xkey_private_key = if let Ok(seed) = provider_xkey.seed() {
@@ -200,8 +199,8 @@ impl Host {
             lattice_rpc_user_jwt: self.host_config.rpc_jwt.clone().unwrap_or_default(),
             lattice_rpc_user_seed: lattice_rpc_user_seed.unwrap_or_default(),
             lattice_rpc_url: self.host_config.rpc_nats_url.to_string(),
-            env_values: vec![],
-            instance_id: Uuid::new_v4().to_string(),
+            env_values: HostEnvValues::default(),
+            instance_id: wasmcloud_core::id::generate_uuid(),
             provider_key: provider_id.to_string(),
             link_definitions,
             config: host_config,
@@ -211,6 +210,7 @@ impl Host {
             cluster_issuers: vec![],
             default_rpc_timeout_ms,
             log_level: Some(self.host_config.log_level.clone()),
+            log_directives: self.host_config.log_directives.clone(),
             structured_logging: self.host_config.enable_structured_logging,
             otel_config,
         };
@@ -228,6 +228,7 @@ impl Host {
         provider_xkey: XKey,
         provider_id: &str,
         config_names: Vec<String>,
+        config_updates: mpsc::Receiver<ConfigNamesUpdate>,
         claims_token: Option<Token<CapabilityProvider>>,
         annotations: BTreeMap<String, String>,
         shutdown: Arc<AtomicBool>,
@@ -247,6 +248,7 @@ impl Host {
                     provider_xkey,
                     provider_id.to_string(),
                     config_names,
+                    config_updates,
                     claims_token,
                     annotations,
                     shutdown.clone(),
@@ -275,7 +277,8 @@ impl Host {
         config_bundle: Arc<RwLock<ConfigBundle>>,
         provider_xkey: XKey,
         provider_id: String,
-        config_names: Vec<String>,
+        mut config_names: Vec<String>,
+        mut config_updates: mpsc::Receiver<ConfigNamesUpdate>,
         claims_token: Option<Token<CapabilityProvider>>,
         annotations: BTreeMap<String, String>,
         shutdown: Arc<AtomicBool>,
@@ -303,7 +306,64 @@ impl Host {
             ));
             loop {
                 let mut child = child.write().await;
-                match child.wait().await {
+                let mut update = None;
+                let status = select! {
+                    status = child.wait() => Some(status),
+                    Some(next) = config_updates.recv() => {
+                        update = Some(next);
+                        None
+                    }
+                };
+                let Some(status) = status else {
+                    drop(child);
+                    let Some(ConfigNamesUpdate { names, delivered }) = update else {
+                        continue;
+                    };
+                    trace!(?provider_id, ?names, "provider config names changed");
+                    let res = async {
+                        let (mut bundle, _) = self
+                            .fetch_config_and_secrets(
+                                &names,
+                                claims_token.as_ref().map(|t| &t.jwt),
+                                annotations.get("wasmcloud.dev/appspec"),
+                            )
+                            .await?;
+                        let rpc_nats = &self.rpc_nats;
+                        let subject = provider_config_update_subject(&lattice, &provider_id);
+                        deliver_config(&mut bundle, |bytes| async move {
+                            rpc_nats
+                                .publish(subject, bytes)
+                                .await
+                                .context("failed to publish configuration update")?;
+                            rpc_nats
+                                .flush()
+                                .await
+                                .context("failed to flush configuration update")
+                        })
+                        .await?;
+                        anyhow::Ok(bundle)
+                    }
+                    .await
+                    .map(|bundle| {
+                        // The merged config was delivered already, so the new watcher only
+                        // publishes subsequent changes
+                        config_task.abort_all();
+                        config_task.spawn(watch_config(
+                            Arc::clone(&self.rpc_nats),
+                            Arc::new(RwLock::new(bundle)),
+                            Arc::clone(&lattice),
+                            provider_id.clone(),
+                        ));
+                        config_names = names;
+                    });
+                    if let Err(err) = &res {
+                        error!(?err, ?provider_id, "failed to update provider config");
+                    }
+                    // The requester may have stopped waiting for the outcome already
+                    let _ = delivered.send(res);
+                    continue;
+                };
+                match status {
                     Ok(status) => {
                         // When the provider is shutting down, don't restart it
                         if shutdown.load(Ordering::Relaxed) {
@@ -544,6 +604,20 @@ fn check_health(
 ///
 /// Returns a future that continually checks provider config changes
 /// until the config receiver gets a message
+/// Publishes the merged config of a newly generated config bundle using `publish`, consuming the
+/// initial change notification of the bundle so that its watcher does not deliver it again
+async fn deliver_config<F, Fut>(bundle: &mut ConfigBundle, publish: F) -> anyhow::Result<()>
+where
+    F: FnOnce(Bytes) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let bytes = {
+        let config = bundle.changed().await?;
+        serde_json::to_vec(&*config).context("failed to serialize configuration update")?
+    };
+    publish(Bytes::from(bytes)).await
+}
+
 fn watch_config(
     rpc_nats: Arc<Client>,
     config: Arc<RwLock<ConfigBundle>>,
@@ -574,3 +648,70 @@ fn watch_config(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::store::{DefaultStore, StoreManager as _};
+    use crate::wasmbus::config::BundleGenerator;
+
+    #[tokio::test]
+    async fn config_updates_are_delivered_once() -> anyhow::Result<()> {
+        let store = Arc::new(DefaultStore::default());
+        store
+            .put("foo", Bytes::from_static(br#"{"star":"wars"}"#))
+            .await?;
+        store
+            .put(
+                "bar",
+                Bytes::from_static(br#"{"star":"trek","ship":"enterprise"}"#),
+            )
+            .await?;
+        let mut bundle = BundleGenerator::new(store)
+            .generate(vec!["foo".to_string(), "bar".to_string()])
+            .await?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        deliver_config(&mut bundle, |bytes| async move {
+            tx.send(bytes)?;
+            Ok(())
+        })
+        .await?;
+        let delivered: HashMap<String, String> =
+            serde_json::from_slice(&rx.recv().await.context("config should be delivered")?)?;
+        assert_eq!(
+            delivered,
+            HashMap::from([
+                ("star".to_string(), "trek".to_string()),
+                ("ship".to_string(), "enterprise".to_string()),
+            ])
+        );
+
+        // The config watcher of the bundle must not deliver the same config again
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), bundle.changed())
+                .await
+                .is_err(),
+            "delivered config should not be reported as changed"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_config_deliveries_are_reported() -> anyhow::Result<()> {
+        let store = Arc::new(DefaultStore::default());
+        store.put("foo", Bytes::from_static(b"{}")).await?;
+        let mut bundle = BundleGenerator::new(store)
+            .generate(vec!["foo".to_string()])
+            .await?;
+
+        let err = deliver_config(&mut bundle, |_| async { bail!("no responders") })
+            .await
+            .expect_err("delivery should fail");
+        assert!(err.to_string().contains("no responders"));
+        Ok(())
+    }
+}
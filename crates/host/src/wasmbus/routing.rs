@@ -0,0 +1,108 @@
+//! Weighted routing of invocations between the components sharing a link target, configured with
+//! [`TrafficSplit`]s stored in the lattice data bucket.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use std::collections::HashMap;
+
+use anyhow::{ensure, Context as _};
+use tokio::sync::RwLock;
+use tracing::{debug, instrument};
+use wasmcloud_control_interface::TrafficSplit;
+
+/// A traffic split along with the number of invocations routed by it so far
+#[derive(Debug)]
+struct Route {
+    split: TrafficSplit,
+    invocations: AtomicU64,
+}
+
+/// Traffic splits of link targets, keyed by the target
+#[derive(Debug, Default)]
+pub(crate) struct TrafficRoutes(RwLock<HashMap<Box<str>, Route>>);
+
+impl TrafficRoutes {
+    /// Resolve the lattice ID an invocation of `target` is sent to.
+    ///
+    /// Invocations are distributed deterministically in proportion to the weights of the split,
+    /// so that e.g. a 90/10 split sends exactly 10 out of every 100 invocations to the second
+    /// component. Targets without a split are returned unchanged.
+    pub(crate) async fn resolve(&self, target: &str) -> Box<str> {
+        let routes = self.0.read().await;
+        let Some(Route { split, invocations }) = routes.get(target) else {
+            return target.into();
+        };
+        let position = invocations.fetch_add(1, Ordering::Relaxed);
+        split.select(position).into()
+    }
+
+    /// Set the traffic split of a target, replacing any previous split
+    pub(crate) async fn put(&self, split: TrafficSplit) {
+        debug!(target = split.target(), weights = ?split.weights(), "updating traffic split");
+        self.0.write().await.insert(
+            split.target().into(),
+            Route {
+                split,
+                invocations: AtomicU64::new(0),
+            },
+        );
+    }
+
+    /// Remove the traffic split of a target
+    pub(crate) async fn remove(&self, target: &str) {
+        debug!(target, "removing traffic split");
+        self.0.write().await.remove(target);
+    }
+}
+
+impl super::Host {
+    /// Process a traffic split being put into the lattice data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn process_traffic_split_put(
+        &self,
+        target: impl AsRef<str>,
+        value: impl AsRef<[u8]>,
+    ) -> anyhow::Result<()> {
+        let target = target.as_ref();
+        let split: TrafficSplit =
+            serde_json::from_slice(value.as_ref()).context("failed to decode traffic split")?;
+        ensure!(split.target() == target, "traffic split target mismatch");
+        self.traffic_routes.put(split).await;
+        Ok(())
+    }
+
+    /// Process a traffic split being deleted from the lattice data bucket
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) async fn process_traffic_split_delete(
+        &self,
+        target: impl AsRef<str>,
+    ) -> anyhow::Result<()> {
+        self.traffic_routes.remove(target.as_ref()).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TrafficRoutes;
+
+    use wasmcloud_control_interface::TrafficSplit;
+
+    #[tokio::test]
+    async fn routes_invocations_by_weight() {
+        let routes = TrafficRoutes::default();
+        assert_eq!(&*routes.resolve("api").await, "api");
+
+        let split = TrafficSplit::parse("api", "api=3,api-v2=1").expect("valid split");
+        routes.put(split).await;
+        let mut resolved = Vec::new();
+        for _ in 0..8 {
+            resolved.push(routes.resolve("api").await);
+        }
+        assert_eq!(resolved.iter().filter(|id| &***id == "api-v2").count(), 2);
+        assert_eq!(&*routes.resolve("other").await, "other");
+
+        routes.remove("api").await;
+        assert_eq!(&*routes.resolve("api").await, "api");
+    }
+}
@@ -0,0 +1,502 @@
+//! Self-monitoring of host subsystems that can get stuck without failing, such as the lattice data
+//! watch or the executor running components.
+//!
+//! Monitored subsystems report their progress to a [`Heartbeat`]. The watchdog checks the
+//! heartbeats from a dedicated thread, so that it keeps working when the async executor is stuck,
+//! and escalates the recovery of a stalled subsystem in stages: first a recovery specific to the
+//! subsystem, such as resubscribing to the lattice data bucket or recreating the wasmtime engine,
+//! then a controlled exit of the process with an exit code identifying the subsystem, so that the
+//! host is restarted by its supervisor. A `host_watchdog_escalated` event with diagnostics is
+//! published before each escalation.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, PoisonError};
+use std::time::Instant;
+
+use anyhow::Context as _;
+use serde_json::json;
+use tokio::sync::futures::Notified;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+use crate::event::{self, EventPublisher};
+
+/// Default interval at which the watchdog checks the host subsystems
+pub const DEFAULT_WATCHDOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default duration without progress after which a subsystem is considered stalled
+pub const DEFAULT_WATCHDOG_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum time to wait for the `host_watchdog_escalated` event to be published before exiting
+const EXIT_EVENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Configuration of the host watchdog
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchdogConfig {
+    /// Interval at which the subsystems are checked
+    pub interval: Duration,
+    /// Duration without progress after which a subsystem with pending work is considered
+    /// stalled. This is also the time given to each recovery stage before escalating further
+    pub stall_timeout: Duration,
+    /// Whether the process exits once all other recovery stages failed. If disabled, the
+    /// watchdog keeps reporting the stall instead
+    pub exit_on_stall: bool,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_WATCHDOG_INTERVAL,
+            stall_timeout: DEFAULT_WATCHDOG_STALL_TIMEOUT,
+            exit_on_stall: true,
+        }
+    }
+}
+
+/// Host subsystems monitored by the watchdog
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WatchdogSubsystem {
+    /// Publishing of lattice events, stalled if published events are not sent
+    EventPublisher,
+    /// Watch of the lattice data bucket, stalled if the watch lags behind without progressing
+    DataWatch,
+    /// Executor running components, stalled if a heartbeat task scheduled on it stops running,
+    /// e.g. because components do not yield
+    Runtime,
+}
+
+impl WatchdogSubsystem {
+    const ALL: [Self; 3] = [Self::EventPublisher, Self::DataWatch, Self::Runtime];
+
+    /// Get the name of the subsystem, e.g. `data_watch`
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::EventPublisher => "event_publisher",
+            Self::DataWatch => "data_watch",
+            Self::Runtime => "runtime",
+        }
+    }
+
+    /// Get the code the process exits with when the watchdog gives up on this subsystem
+    #[must_use]
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::EventPublisher => 71,
+            Self::DataWatch => 72,
+            Self::Runtime => 73,
+        }
+    }
+
+    /// Returns the recovery actions for this subsystem, in order of escalation
+    fn escalation(self) -> &'static [WatchdogAction] {
+        match self {
+            Self::EventPublisher => &[WatchdogAction::Exit],
+            Self::DataWatch => &[WatchdogAction::Resubscribe, WatchdogAction::Exit],
+            Self::Runtime => &[WatchdogAction::RecreateEngine, WatchdogAction::Exit],
+        }
+    }
+}
+
+/// Recovery action taken by the watchdog for a stalled subsystem
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum WatchdogAction {
+    /// Resubscribe to the lattice data bucket from the last applied revision
+    Resubscribe,
+    /// Recreate the wasmtime engine used to start components
+    RecreateEngine,
+    /// Exit the process with the exit code of the subsystem
+    Exit,
+}
+
+impl WatchdogAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Resubscribe => "resubscribe",
+            Self::RecreateEngine => "recreate_engine",
+            Self::Exit => "exit",
+        }
+    }
+}
+
+/// Progress of a monitored subsystem
+#[derive(Debug)]
+pub(crate) struct Heartbeat {
+    start: Instant,
+    /// Milliseconds since `start` at which the subsystem last made progress
+    last_beat_ms: AtomicU64,
+    /// Units of work the subsystem has yet to process
+    backlog: AtomicU64,
+    /// Whether the subsystem is expected to beat even without a backlog
+    continuous: bool,
+}
+
+/// Tracks a unit of work in the backlog of a [`Heartbeat`] until it is dropped
+pub(crate) struct BacklogGuard<'a>(&'a Heartbeat);
+
+impl Drop for BacklogGuard<'_> {
+    fn drop(&mut self) {
+        self.0.backlog.fetch_sub(1, Ordering::Relaxed);
+        self.0.beat();
+    }
+}
+
+impl Heartbeat {
+    fn new(start: Instant, continuous: bool) -> Self {
+        Self {
+            start,
+            last_beat_ms: AtomicU64::new(0),
+            backlog: AtomicU64::new(0),
+            continuous,
+        }
+    }
+
+    fn elapsed_ms(&self, now: Instant) -> u64 {
+        u64::try_from(now.saturating_duration_since(self.start).as_millis()).unwrap_or(u64::MAX)
+    }
+
+    /// Record that the subsystem made progress
+    pub(crate) fn beat(&self) {
+        self.last_beat_ms
+            .store(self.elapsed_ms(Instant::now()), Ordering::Relaxed);
+    }
+
+    /// Record the units of work the subsystem has yet to process
+    pub(crate) fn set_backlog(&self, backlog: u64) {
+        self.backlog.store(backlog, Ordering::Relaxed);
+    }
+
+    /// Record a unit of work in the backlog until the returned guard is dropped
+    pub(crate) fn begin(&self) -> BacklogGuard<'_> {
+        // Time spent idle before the work started does not count towards a stall
+        if self.backlog.fetch_add(1, Ordering::Relaxed) == 0 {
+            self.beat();
+        }
+        BacklogGuard(self)
+    }
+
+    /// Returns for how long the subsystem has not made progress while it was expected to
+    fn stalled_for(&self, now: Instant) -> Option<Duration> {
+        if !self.continuous && self.backlog.load(Ordering::Relaxed) == 0 {
+            return None;
+        }
+        let last_beat_ms = self.last_beat_ms.load(Ordering::Relaxed);
+        Some(Duration::from_millis(
+            self.elapsed_ms(now).saturating_sub(last_beat_ms),
+        ))
+    }
+}
+
+/// Escalation state of a single subsystem
+#[derive(Debug, Default)]
+struct Escalation {
+    /// Index of the next recovery action to take
+    stage: usize,
+    /// When the last recovery action was taken
+    last: Option<Instant>,
+}
+
+impl Escalation {
+    /// Returns the recovery action to take for `subsystem`, given for how long it has not made
+    /// progress. Each stage is given `stall_timeout` to recover before escalating to the next
+    fn next(
+        &mut self,
+        subsystem: WatchdogSubsystem,
+        stalled_for: Option<Duration>,
+        now: Instant,
+        stall_timeout: Duration,
+    ) -> Option<WatchdogAction> {
+        if stalled_for.is_none_or(|stalled_for| stalled_for < stall_timeout) {
+            *self = Self::default();
+            return None;
+        }
+        if self
+            .last
+            .is_some_and(|last| now.saturating_duration_since(last) < stall_timeout)
+        {
+            return None;
+        }
+        let actions = subsystem.escalation();
+        let action = actions[self.stage.min(actions.len() - 1)];
+        self.stage = self.stage.saturating_add(1);
+        self.last = Some(now);
+        Some(action)
+    }
+}
+
+/// Heartbeats of the monitored subsystems and the recovery requests sent to them
+#[derive(Debug)]
+pub(crate) struct Watchdog {
+    event_publisher: Heartbeat,
+    data_watch: Heartbeat,
+    runtime: Heartbeat,
+    resubscribe: Notify,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        let start = Instant::now();
+        Self {
+            event_publisher: Heartbeat::new(start, false),
+            data_watch: Heartbeat::new(start, false),
+            runtime: Heartbeat::new(start, true),
+            resubscribe: Notify::new(),
+        }
+    }
+}
+
+impl Watchdog {
+    fn heartbeat(&self, subsystem: WatchdogSubsystem) -> &Heartbeat {
+        match subsystem {
+            WatchdogSubsystem::EventPublisher => &self.event_publisher,
+            WatchdogSubsystem::DataWatch => &self.data_watch,
+            WatchdogSubsystem::Runtime => &self.runtime,
+        }
+    }
+
+    /// Returns the heartbeat of the lattice data bucket watch
+    pub(crate) fn data_watch(&self) -> &Heartbeat {
+        &self.data_watch
+    }
+
+    /// Completes when the watchdog requests the lattice data bucket watch to resubscribe
+    pub(crate) fn resubscribe_requested(&self) -> Notified<'_> {
+        self.resubscribe.notified()
+    }
+
+    /// Returns the state of all heartbeats, included in the diagnostics of escalations
+    fn diagnostics(&self, now: Instant) -> serde_json::Value {
+        WatchdogSubsystem::ALL
+            .into_iter()
+            .map(|subsystem| {
+                let heartbeat = self.heartbeat(subsystem);
+                (
+                    subsystem.as_str().to_string(),
+                    json!({
+                        "backlog": heartbeat.backlog.load(Ordering::Relaxed),
+                        "ms_since_last_beat": heartbeat
+                            .elapsed_ms(now)
+                            .saturating_sub(heartbeat.last_beat_ms.load(Ordering::Relaxed)),
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+}
+
+/// [`EventPublisher`] recording in-flight events in the backlog of the event publisher heartbeat
+pub(crate) struct WatchedEventPublisher {
+    inner: Arc<dyn EventPublisher>,
+    watchdog: Arc<Watchdog>,
+}
+
+impl WatchedEventPublisher {
+    pub(crate) fn new(inner: Arc<dyn EventPublisher>, watchdog: Arc<Watchdog>) -> Self {
+        Self { inner, watchdog }
+    }
+}
+
+#[async_trait::async_trait]
+impl EventPublisher for WatchedEventPublisher {
+    async fn publish_event(&self, name: &str, data: serde_json::Value) -> anyhow::Result<()> {
+        let _pending = self.watchdog.event_publisher.begin();
+        self.inner.publish_event(name, data).await
+    }
+}
+
+impl super::Host {
+    /// Start the watchdog thread and the runtime heartbeat task, if the watchdog is enabled
+    pub(crate) fn start_watchdog(self: &Arc<Self>) -> anyhow::Result<()> {
+        let Some(config) = self.host_config.watchdog else {
+            return Ok(());
+        };
+
+        let host = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.interval);
+            loop {
+                interval.tick().await;
+                let Some(host) = host.upgrade() else {
+                    return;
+                };
+                host.watchdog.runtime.beat();
+            }
+        });
+
+        let handle = tokio::runtime::Handle::current();
+        let host = Arc::downgrade(self);
+        std::thread::Builder::new()
+            .name("wasmcloud-watchdog".into())
+            .spawn(move || {
+                let mut escalations = BTreeMap::<_, Escalation>::new();
+                loop {
+                    std::thread::sleep(config.interval);
+                    let Some(host) = host.upgrade() else {
+                        return;
+                    };
+                    let now = Instant::now();
+                    for subsystem in WatchdogSubsystem::ALL {
+                        let stalled_for = host.watchdog.heartbeat(subsystem).stalled_for(now);
+                        if let Some(action) = escalations.entry(subsystem).or_default().next(
+                            subsystem,
+                            stalled_for,
+                            now,
+                            config.stall_timeout,
+                        ) {
+                            host.escalate(
+                                &handle,
+                                &config,
+                                subsystem,
+                                action,
+                                stalled_for.unwrap_or_default(),
+                            );
+                        }
+                    }
+                }
+            })
+            .context("failed to spawn watchdog thread")?;
+        info!(
+            interval = ?config.interval,
+            stall_timeout = ?config.stall_timeout,
+            "started host watchdog"
+        );
+        Ok(())
+    }
+
+    /// Take the recovery `action` for the stalled `subsystem`, after reporting diagnostics
+    fn escalate(
+        &self,
+        handle: &tokio::runtime::Handle,
+        config: &WatchdogConfig,
+        subsystem: WatchdogSubsystem,
+        action: WatchdogAction,
+        stalled_for: Duration,
+    ) {
+        let diagnostics = self.watchdog.diagnostics(Instant::now());
+        error!(
+            subsystem = subsystem.as_str(),
+            action = action.as_str(),
+            ?stalled_for,
+            %diagnostics,
+            "host subsystem stalled, escalating recovery"
+        );
+
+        // The event is published on the executor, which may be the one that is stuck, so this
+        // thread only waits for it before exiting and gives up after a timeout
+        let (published_tx, published_rx) = std::sync::mpsc::channel();
+        let event_publisher = Arc::clone(&self.event_publisher);
+        let data = event::host_watchdog_escalated(
+            self.host_key.public_key(),
+            subsystem.as_str(),
+            action.as_str(),
+            stalled_for,
+            diagnostics,
+        );
+        handle.spawn(async move {
+            if let Err(err) = event_publisher
+                .publish_event("host_watchdog_escalated", data)
+                .await
+            {
+                error!(?err, "failed to publish host watchdog escalation");
+            }
+            let _ = published_tx.send(());
+        });
+
+        match action {
+            WatchdogAction::Resubscribe => self.watchdog.resubscribe.notify_one(),
+            WatchdogAction::RecreateEngine => match self.recreate_runtime() {
+                Ok(()) => warn!("recreated wasmtime engine, components started from now on use it"),
+                Err(err) => error!(?err, "failed to recreate wasmtime engine"),
+            },
+            WatchdogAction::Exit if config.exit_on_stall => {
+                let _ = published_rx.recv_timeout(EXIT_EVENT_TIMEOUT);
+                error!(
+                    subsystem = subsystem.as_str(),
+                    exit_code = subsystem.exit_code(),
+                    "host subsystem did not recover, exiting"
+                );
+                std::process::exit(subsystem.exit_code());
+            }
+            WatchdogAction::Exit => {
+                warn!(
+                    subsystem = subsystem.as_str(),
+                    "host subsystem did not recover, exiting on stalls is disabled"
+                );
+            }
+        }
+    }
+
+    /// Replace the wasmtime engine used to start components with a new one
+    fn recreate_runtime(&self) -> anyhow::Result<()> {
+        let runtime = super::build_runtime(&self.host_config)?;
+        *self.runtime.write().unwrap_or_else(PoisonError::into_inner) = runtime;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_stalled_subsystems() {
+        const TIMEOUT: Duration = Duration::from_secs(60);
+
+        let start = Instant::now();
+        let mut escalation = Escalation::default();
+        let subsystem = WatchdogSubsystem::DataWatch;
+        assert_eq!(escalation.next(subsystem, None, start, TIMEOUT), None);
+        assert_eq!(
+            escalation.next(subsystem, Some(TIMEOUT / 2), start, TIMEOUT),
+            None
+        );
+
+        assert_eq!(
+            escalation.next(subsystem, Some(TIMEOUT), start, TIMEOUT),
+            Some(WatchdogAction::Resubscribe)
+        );
+        // Each stage is given the stall timeout to recover
+        let later = start + TIMEOUT / 2;
+        assert_eq!(
+            escalation.next(subsystem, Some(TIMEOUT * 2), later, TIMEOUT),
+            None
+        );
+        let later = start + TIMEOUT;
+        assert_eq!(
+            escalation.next(subsystem, Some(TIMEOUT * 2), later, TIMEOUT),
+            Some(WatchdogAction::Exit)
+        );
+
+        // Progress resets the escalation
+        assert_eq!(escalation.next(subsystem, None, later, TIMEOUT), None);
+        assert_eq!(
+            escalation.next(subsystem, Some(TIMEOUT), later, TIMEOUT),
+            Some(WatchdogAction::Resubscribe)
+        );
+    }
+
+    #[test]
+    fn heartbeat_backlog() {
+        let start = Instant::now();
+        let heartbeat = Heartbeat::new(start, false);
+        let later = start + Duration::from_secs(10);
+        assert_eq!(heartbeat.stalled_for(later), None);
+
+        let pending = heartbeat.begin();
+        assert!(heartbeat.stalled_for(Instant::now()).is_some());
+        drop(pending);
+        assert_eq!(heartbeat.stalled_for(later), None);
+
+        heartbeat.set_backlog(3);
+        assert!(heartbeat
+            .stalled_for(later)
+            .is_some_and(|stalled_for| stalled_for >= Duration::from_secs(9)));
+
+        let runtime = Heartbeat::new(start, true);
+        assert_eq!(runtime.stalled_for(start), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{bail, Context as _};
+use serde::Deserialize;
+use tracing::info;
+use wasmcloud_control_interface::{ScaleComponentCommand, StartProviderCommand};
+
+use crate::wasmbus::ctl::ControlInterfaceServer;
+use crate::wasmbus::Host;
+
+/// Components and providers declared in a local file that the host starts when it starts,
+/// independently of the control interface.
+///
+/// This is primarily intended for hosts running in read-only mode, which refuse control
+/// interface commands that would start workloads. The manifest is a JSON document using the
+/// same format as the corresponding control interface commands, e.g.
+///
+/// ```json
+/// {
+///   "components": [
+///     { "component_id": "echo", "component_ref": "ghcr.io/wasmcloud/components/http-hello-world-rust:0.1.0", "count": 10 }
+///   ],
+///   "providers": [
+///     { "provider_id": "http-server", "provider_ref": "ghcr.io/wasmcloud/http-server:0.23.0", "config": ["http-server-config"] }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkloadManifest {
+    /// Components to scale to the given number of instances
+    #[serde(default)]
+    pub components: Vec<ScaleComponentCommand>,
+    /// Providers to start
+    #[serde(default)]
+    pub providers: Vec<StartProviderCommand>,
+}
+
+impl WorkloadManifest {
+    /// Load a manifest from a JSON file
+    pub async fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let manifest = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("failed to read workload manifest [{}]", path.display()))?;
+        serde_json::from_slice(&manifest)
+            .with_context(|| format!("failed to parse workload manifest [{}]", path.display()))
+    }
+}
+
+impl Host {
+    /// Start all workloads declared in the manifest, failing on the first workload that the host
+    /// refuses to start
+    pub(crate) async fn apply_workload_manifest(
+        self: &Arc<Self>,
+        manifest: WorkloadManifest,
+    ) -> anyhow::Result<()> {
+        for component in manifest.components {
+            let component_id = component.component_id().to_string();
+            info!(component_id, "starting component from workload manifest");
+            let res = <Self as ControlInterfaceServer>::handle_scale_component(
+                Arc::clone(self),
+                component,
+            )
+            .await?;
+            if !res.succeeded() {
+                bail!(
+                    "failed to start component [{component_id}] from workload manifest: {}",
+                    res.message()
+                );
+            }
+        }
+        for provider in manifest.providers {
+            let provider_id = provider.provider_id().to_string();
+            info!(provider_id, "starting provider from workload manifest");
+            if let Some(res) =
+                <Self as ControlInterfaceServer>::handle_start_provider(Arc::clone(self), provider)
+                    .await?
+            {
+                if !res.succeeded() {
+                    bail!(
+                        "failed to start provider [{provider_id}] from workload manifest: {}",
+                        res.message()
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkloadManifest;
+
+    use tokio::fs;
+
+    #[tokio::test]
+    async fn manifests_are_loaded_from_json_files() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let path = tmp.path().join("workloads.json");
+        fs::write(
+            &path,
+            r#"{
+                "components": [
+                    { "component_id": "echo", "component_ref": "ghcr.io/wasmcloud/echo:0.1.0", "count": 10 }
+                ],
+                "providers": [
+                    { "provider_id": "http-server", "provider_ref": "ghcr.io/wasmcloud/http-server:0.23.0", "config": ["http"] }
+                ]
+            }"#,
+        )
+        .await?;
+        let manifest = WorkloadManifest::load(&path).await?;
+        let [component] = manifest.components.as_slice() else {
+            panic!("expected a single component, got {:?}", manifest.components);
+        };
+        assert_eq!(component.component_id(), "echo");
+        assert_eq!(component.component_ref(), "ghcr.io/wasmcloud/echo:0.1.0");
+        assert_eq!(component.max_instances(), 10);
+        let [provider] = manifest.providers.as_slice() else {
+            panic!("expected a single provider, got {:?}", manifest.providers);
+        };
+        assert_eq!(provider.provider_id(), "http-server");
+        assert_eq!(
+            provider.provider_ref(),
+            "ghcr.io/wasmcloud/http-server:0.23.0"
+        );
+        assert_eq!(provider.config(), &vec!["http".to_string()]);
+
+        // Both lists are optional
+        fs::write(&path, r#"{ "providers": [] }"#).await?;
+        let manifest = WorkloadManifest::load(&path).await?;
+        assert!(manifest.components.is_empty());
+        assert!(manifest.providers.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn invalid_manifests_are_rejected() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        let path = tmp.path().join("workloads.json");
+        assert!(WorkloadManifest::load(&path).await.is_err());
+
+        // Typos in field names must not silently drop workloads
+        fs::write(&path, r#"{ "component": [] }"#).await?;
+        let err = WorkloadManifest::load(&path)
+            .await
+            .expect_err("unknown fields should be rejected");
+        assert!(format!("{err:#}").contains("failed to parse workload manifest"));
+        Ok(())
+    }
+}
@@ -370,7 +370,7 @@ mod tests {
 
     use std::collections::HashMap;
 
-    use anyhow::{ensure, Context as _};
+    use anyhow::ensure;
     use bytes::Bytes;
     use hyper_util::rt::TokioIo;
     use tokio::net::TcpListener;
@@ -0,0 +1,341 @@
+//! This module contains the implementation of the `wrpc:http/incoming-handler` provider in ingress mode.
+//!
+//! In ingress mode, the HTTP server listens on a single address and routes requests to components
+//! according to the ingress routing table of the lattice, which maps hostnames and path prefixes
+//! to components. The routing table is stored in the lattice data bucket (managed with
+//! `wash route ingress`), so every provider in ingress mode on every host serves the same routes
+//! without any per-provider or per-link configuration.
+
+use core::time::Duration;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use async_nats::jetstream::kv::Operation;
+use axum::extract;
+use axum::handler::Handler;
+use axum_server::tls_rustls::RustlsConfig;
+use axum_server::Handle;
+use futures::StreamExt as _;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, instrument, warn};
+use wasmcloud_provider_sdk::provider::{ProviderConnection, WrpcClient};
+use wasmcloud_provider_sdk::{HostData, Provider};
+
+use crate::{
+    build_request, get_cors_layer, get_tcp_listener, invoke_component, load_settings,
+    ServiceSettings,
+};
+
+/// Prefix of keys in the lattice data bucket that store ingress routes
+const INGRESS_ROUTE_KEY_PREFIX: &str = "INGRESS_";
+
+/// An entry of the ingress routing table, as stored in the lattice data bucket
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct IngressRoute {
+    /// Hostname the route applies to, or `None` for any hostname
+    #[serde(default)]
+    hostname: Option<String>,
+    /// Path prefix the route applies to
+    path_prefix: String,
+    /// ID of the component handling the matching requests
+    component_id: String,
+}
+
+impl IngressRoute {
+    /// Returns whether the route applies to a request for `host` and `path`
+    fn matches(&self, host: &str, path: &str) -> bool {
+        if self
+            .hostname
+            .as_deref()
+            .is_some_and(|hostname| !hostname.eq_ignore_ascii_case(host))
+        {
+            return false;
+        }
+        let prefix = self.path_prefix.trim_end_matches('/');
+        match path.strip_prefix(prefix) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/'),
+            None => false,
+        }
+    }
+}
+
+/// The ingress routing table of the lattice, keyed by route name
+#[derive(Default)]
+struct Router {
+    routes: HashMap<String, IngressRoute>,
+    /// wRPC clients of the components receiving requests, keyed by component ID
+    clients: HashMap<String, WrpcClient>,
+}
+
+impl Router {
+    /// Select the route of a request, preferring routes for the hostname of the request over
+    /// routes for any hostname, and longer path prefixes over shorter ones
+    fn select(&self, host: &str, path: &str) -> Option<&IngressRoute> {
+        self.routes
+            .values()
+            .filter(|route| route.matches(host, path))
+            .max_by_key(|route| {
+                (
+                    route.hostname.is_some(),
+                    route.path_prefix.trim_end_matches('/').len(),
+                )
+            })
+    }
+}
+
+/// `wrpc:http/incoming-handler` provider implementation routing by the lattice ingress routing table
+#[derive(Clone)]
+pub struct HttpServerProvider {
+    /// The ingress routing table of the lattice
+    router: Arc<RwLock<Router>>,
+    /// JetStream domain of the lattice data bucket, if any
+    js_domain: Option<String>,
+    /// [`Handle`] to the server task
+    handle: Handle,
+    /// Task handle for the server task
+    task: Arc<JoinHandle<()>>,
+}
+
+impl Drop for HttpServerProvider {
+    fn drop(&mut self) {
+        self.handle.shutdown();
+        self.task.abort();
+    }
+}
+
+impl HttpServerProvider {
+    pub(crate) async fn new(host_data: &HostData) -> anyhow::Result<Self> {
+        let default_address = host_data
+            .config
+            .get("default_address")
+            .map(|s| SocketAddr::from_str(s))
+            .transpose()
+            .context("failed to parse default_address")?;
+        let js_domain = host_data.config.get("js_domain").cloned();
+        let settings = load_settings(default_address, &host_data.config)
+            .context("failed to load settings in ingress mode")?;
+        let settings = Arc::new(settings);
+
+        let router = Arc::default();
+
+        let addr = settings.address;
+        info!(
+            %addr,
+            "httpserver starting listener in ingress mode",
+        );
+        let cors = get_cors_layer(&settings)?;
+        let listener = get_tcp_listener(&settings)?;
+        let service = handle_request.layer(cors);
+
+        let handle = axum_server::Handle::new();
+        let task_handle = handle.clone();
+        let task_router = Arc::clone(&router);
+        let task = if let (Some(crt), Some(key)) =
+            (&settings.tls_cert_file, &settings.tls_priv_key_file)
+        {
+            debug!(?addr, "bind HTTPS listener");
+            let tls = RustlsConfig::from_pem_file(crt, key)
+                .await
+                .context("failed to construct TLS config")?;
+
+            tokio::spawn(async move {
+                if let Err(e) = axum_server::from_tcp_rustls(listener, tls)
+                    .handle(task_handle)
+                    .serve(
+                        service
+                            .with_state(RequestContext {
+                                router: task_router,
+                                scheme: http::uri::Scheme::HTTPS,
+                                settings: Arc::clone(&settings),
+                            })
+                            .into_make_service(),
+                    )
+                    .await
+                {
+                    error!(error = %e, "failed to serve HTTPS for ingress mode");
+                }
+            })
+        } else {
+            debug!(?addr, "bind HTTP listener");
+
+            tokio::spawn(async move {
+                if let Err(e) = axum_server::from_tcp(listener)
+                    .handle(task_handle)
+                    .serve(
+                        service
+                            .with_state(RequestContext {
+                                router: task_router,
+                                scheme: http::uri::Scheme::HTTP,
+                                settings: Arc::clone(&settings),
+                            })
+                            .into_make_service(),
+                    )
+                    .await
+                {
+                    error!(error = %e, "failed to serve HTTP for ingress mode");
+                }
+            })
+        };
+
+        Ok(Self {
+            router,
+            js_domain,
+            handle,
+            task: Arc::new(task),
+        })
+    }
+
+    /// Watch the ingress routing table in the lattice data bucket, applying every change to the
+    /// routes served by this provider until the watch ends
+    pub(crate) async fn watch_routes(&self, connection: &ProviderConnection) -> anyhow::Result<()> {
+        let nats = async_nats::Client::clone(&connection.nats);
+        let jetstream = match &self.js_domain {
+            Some(domain) => async_nats::jetstream::with_domain(nats, domain),
+            None => async_nats::jetstream::new(nats),
+        };
+        let bucket = format!("LATTICEDATA_{}", connection.lattice);
+        let store = jetstream
+            .get_key_value(&bucket)
+            .await
+            .with_context(|| format!("failed to access lattice data bucket {bucket}"))?;
+        let mut entries = store
+            .watch_with_history(">")
+            .await
+            .context("failed to watch ingress routes")?;
+        while let Some(entry) = entries.next().await {
+            let entry = entry.context("failed to receive ingress route update")?;
+            let Some(name) = entry.key.strip_prefix(INGRESS_ROUTE_KEY_PREFIX) else {
+                continue;
+            };
+            let mut router = self.router.write().await;
+            match entry.operation {
+                Operation::Put => match serde_json::from_slice::<IngressRoute>(&entry.value) {
+                    Ok(route) => {
+                        if !router.clients.contains_key(&route.component_id) {
+                            let wrpc = connection
+                                .get_wrpc_client(&route.component_id)
+                                .await
+                                .context("failed to construct wRPC client")?;
+                            router.clients.insert(route.component_id.clone(), wrpc);
+                        }
+                        info!(name, ?route, "applying ingress route");
+                        router.routes.insert(name.to_string(), route);
+                    }
+                    Err(error) => warn!(name, %error, "ignoring invalid ingress route"),
+                },
+                Operation::Delete | Operation::Purge => {
+                    info!(name, "removing ingress route");
+                    router.routes.remove(name);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Provider for HttpServerProvider {
+    /// Handle shutdown request by shutting down the http server task
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.handle.shutdown();
+        self.task.abort();
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct RequestContext {
+    router: Arc<RwLock<Router>>,
+    scheme: http::uri::Scheme,
+    settings: Arc<ServiceSettings>,
+}
+
+/// Handle an HTTP request by looking up the ingress route of its host and path and invoking the
+/// component of the route
+#[instrument(level = "debug", skip(router, settings))]
+async fn handle_request(
+    extract::State(RequestContext {
+        router,
+        scheme,
+        settings,
+    }): extract::State<RequestContext>,
+    axum_extra::extract::Host(authority): axum_extra::extract::Host,
+    request: extract::Request,
+) -> impl axum::response::IntoResponse {
+    let timeout = settings.timeout_ms.map(Duration::from_millis);
+    // Hostnames of routes never include the port
+    let host = authority
+        .rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+        .map_or(authority.as_str(), |(host, _)| host)
+        .to_string();
+    let req = build_request(request, scheme, authority, &settings).map_err(|err| *err)?;
+    let Some((target_component, wrpc)) = ({
+        let router = router.read().await;
+        router.select(&host, req.uri().path()).and_then(|route| {
+            let wrpc = router.clients.get(&route.component_id)?;
+            Some((route.component_id.clone(), wrpc.clone()))
+        })
+    }) else {
+        Err((http::StatusCode::NOT_FOUND, "no ingress route found"))?
+    };
+    axum::response::Result::<_, axum::response::ErrorResponse>::Ok(
+        invoke_component(
+            &wrpc,
+            &target_component,
+            req,
+            timeout,
+            settings.cache_control.as_ref(),
+        )
+        .await,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(hostname: Option<&str>, path_prefix: &str, component_id: &str) -> IngressRoute {
+        IngressRoute {
+            hostname: hostname.map(String::from),
+            path_prefix: path_prefix.to_string(),
+            component_id: component_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn selects_most_specific_route() {
+        let router = Router {
+            routes: HashMap::from([
+                ("default".to_string(), route(None, "/", "fallback")),
+                ("api".to_string(), route(None, "/api", "api")),
+                (
+                    "admin".to_string(),
+                    route(Some("admin.example.com"), "/", "admin"),
+                ),
+            ]),
+            clients: HashMap::new(),
+        };
+        let selected = |host, path| {
+            router
+                .select(host, path)
+                .map(|route| route.component_id.as_str())
+        };
+        assert_eq!(selected("example.com", "/api/users"), Some("api"));
+        assert_eq!(selected("example.com", "/api"), Some("api"));
+        assert_eq!(selected("example.com", "/apix"), Some("fallback"));
+        assert_eq!(selected("Admin.Example.com", "/api"), Some("admin"));
+
+        let router = Router {
+            routes: HashMap::from([("api".to_string(), route(None, "/api/", "api"))]),
+            clients: HashMap::new(),
+        };
+        assert!(router.select("example.com", "/").is_none());
+    }
+}
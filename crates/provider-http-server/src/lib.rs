@@ -38,7 +38,7 @@ use pin_project_lite::pin_project;
 use tokio::task::JoinHandle;
 use tokio::{spawn, time};
 use tower_http::cors::{self, CorsLayer};
-use tracing::{debug, info, trace};
+use tracing::{debug, error, info, trace};
 use wasmcloud_core::http::{load_settings, ServiceSettings};
 use wasmcloud_provider_sdk::provider::WrpcClient;
 use wasmcloud_provider_sdk::{initialize_observability, load_host_data, run_provider};
@@ -46,6 +46,7 @@ use wrpc_interface_http::InvokeIncomingHandler as _;
 
 mod address;
 mod host;
+mod ingress;
 mod path;
 
 pub async fn run() -> anyhow::Result<()> {
@@ -86,6 +87,23 @@ pub async fn run() -> anyhow::Result<()> {
             .await?
             .await;
         }
+        Some("ingress") => {
+            let provider = ingress::HttpServerProvider::new(host_data).await.context(
+                "failed to create ingress-mode HTTP server provider from hostdata configuration",
+            )?;
+            let shutdown = run_provider(provider.clone(), "http-server-provider").await?;
+            // The routing table is watched once the provider is connected to the lattice
+            let watcher = tokio::spawn(async move {
+                if let Err(err) = provider
+                    .watch_routes(wasmcloud_provider_sdk::get_connection())
+                    .await
+                {
+                    error!(?err, "failed to watch ingress routing table");
+                }
+            });
+            shutdown.await;
+            watcher.abort();
+        }
         Some(other) => bail!("unknown routing_mode: {other}"),
     };
 
@@ -4,13 +4,10 @@ use anyhow::{Context as _, Result};
 use tokio::time::Duration;
 use tokio::try_join;
 
-use wasmcloud_test_util::control_interface::ClientBuilder;
 use wasmcloud_test_util::lattice::link::{assert_advertise_link, assert_remove_link};
-use wasmcloud_test_util::nats::wait_for_nats_connection;
 use wasmcloud_test_util::provider::StartProviderArgs;
-use wasmcloud_test_util::testcontainers::{AsyncRunner as _, ImageExt, NatsServer};
 use wasmcloud_test_util::{
-    assert_config_put, assert_scale_component, assert_start_provider, WasmCloudTestHost,
+    assert_config_put, assert_scale_component, assert_start_provider, TestLattice,
 };
 
 const PROVIDER_HTTP_SERVER_IMAGE_REF: &str = "ghcr.io/wasmcloud/http-server:0.23.2";
@@ -45,40 +42,27 @@ async fn duplicate_link_shadowing() -> Result<()> {
     );
     let endpoint_url = format!("http://{provider_http_address}");
 
-    // Start NATS
-    let nats_container = NatsServer::default()
-        .with_cmd(["--jetstream"])
-        .start()
-        .await
-        .expect("failed to start nats-server container");
-    let nats_port = nats_container
-        .get_host_port_ipv4(4222)
-        .await
-        .expect("should be able to find the NATS port");
-    let nats_address = format!("nats://127.0.0.1:{nats_port}");
-
-    // Start the host
-    let host = WasmCloudTestHost::start(&nats_address, lattice).await?;
-    let host_id = Arc::new(host.host_key().public_key());
-
-    // Once you have a host (AKA a single-member wasmCloud lattice), you'll want a NATS client
-    // which you can use to control the host and the lattice:
-    let nats_client = wait_for_nats_connection(&nats_address).await?;
-    let ctl_client = ClientBuilder::new(nats_client)
-        .lattice(host.lattice_name().to_string())
-        .build();
+    // Start NATS and a host (AKA a single-member wasmCloud lattice), along with a control
+    // client which you can use to control the host and the lattice
+    let lattice = TestLattice::builder()
+        .lattice(lattice)
+        .nats_container()
+        .build()
+        .await?;
+    let host_id = Arc::new(lattice.host().host_id());
+    let ctl_client = lattice.ctl_client();
 
     // Perform all link and config puts
     // (NOTE: this *must* be sequential, as we are testing order)
     assert_config_put(
-        &ctl_client,
+        ctl_client,
         provider_http_config_name,
         [("address".to_string(), provider_http_address.clone())],
     )
     .await
     .context("failed to set config for provider")?;
     assert_advertise_link(
-        &ctl_client,
+        ctl_client,
         provider_http_id,
         component_hello_rust_id,
         "default",
@@ -90,7 +74,7 @@ async fn duplicate_link_shadowing() -> Result<()> {
     )
     .await?;
     assert_advertise_link(
-        &ctl_client,
+        ctl_client,
         provider_http_id,
         component_hello_rust_id,
         "hello",
@@ -102,7 +86,7 @@ async fn duplicate_link_shadowing() -> Result<()> {
     )
     .await?;
     assert_advertise_link(
-        &ctl_client,
+        ctl_client,
         provider_http_id,
         component_jsonify_rust_id,
         "jsonify",
@@ -117,14 +101,14 @@ async fn duplicate_link_shadowing() -> Result<()> {
     // Start the provider and components
     let ((), (), ()) = try_join!(
         assert_start_provider(StartProviderArgs {
-            client: &ctl_client,
+            client: ctl_client,
             host_id: &host_id,
             provider_id: provider_http_id,
             provider_ref: PROVIDER_HTTP_SERVER_IMAGE_REF,
             config: vec![provider_http_config_name.into()],
         }),
         assert_scale_component(
-            &ctl_client,
+            ctl_client,
             host_id.as_ref(),
             COMPONENT_HELLO_WORLD_RUST_IMAGE_REF,
             component_hello_rust_id,
@@ -134,7 +118,7 @@ async fn duplicate_link_shadowing() -> Result<()> {
             Duration::from_secs(10),
         ),
         assert_scale_component(
-            &ctl_client,
+            ctl_client,
             host_id.as_ref(),
             COMPONENT_HTTP_JSONIFY_RUST_IMAGE_REF,
             component_jsonify_rust_id,
@@ -167,7 +151,7 @@ async fn duplicate_link_shadowing() -> Result<()> {
     );
 
     // Delete the default link
-    assert_remove_link(&ctl_client, provider_http_id, "wasi", "http", "default")
+    assert_remove_link(ctl_client, provider_http_id, "wasi", "http", "default")
         .await
         .context("failed to remove link 'default'")?;
 
@@ -179,7 +163,7 @@ async fn duplicate_link_shadowing() -> Result<()> {
     );
 
     // Delete the rust link
-    assert_remove_link(&ctl_client, provider_http_id, "wasi", "http", "rust")
+    assert_remove_link(ctl_client, provider_http_id, "wasi", "http", "rust")
         .await
         .context("failed to remove link 'rust'")?;
 
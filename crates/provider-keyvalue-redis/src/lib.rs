@@ -475,7 +475,7 @@ impl keyvalue::batch::Handler<Option<Context>> for KvRedisProvider {
         {
             Ok(v) => v
                 .into_iter()
-                .zip(keys.into_iter())
+                .zip(keys)
                 .map(|(val, key)| val.map(|b| (key, b)))
                 .collect::<Vec<_>>(),
             Err(err) => {
@@ -400,7 +400,7 @@ impl bindings::exports::wasmcloud::messaging::consumer::Handler<Option<Context>>
             Some(Context { ref tracing, .. }) if !tracing.is_empty() => tracing
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect::<Vec<(String, String)>>(),
+                .collect::<wasmcloud_provider_sdk::core::TraceContext>(),
 
             _ => TraceContextInjector::default_with_span()
                 .iter()
@@ -483,7 +483,7 @@ impl bindings::exports::wasmcloud::messaging::consumer::Handler<Option<Context>>
             Some(Context { ref tracing, .. }) if !tracing.is_empty() => tracing
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect::<Vec<(String, String)>>(),
+                .collect::<wasmcloud_provider_sdk::core::TraceContext>(),
 
             _ => TraceContextInjector::default_with_span()
                 .iter()
@@ -0,0 +1,274 @@
+//! Crash reports for capability providers.
+//!
+//! When a provider is started with [`run_provider`](crate::run_provider), a panic hook is
+//! installed that captures the panic message, a backtrace and metadata about the provider into a
+//! [`CrashReport`] when the provider crashes. The report is published on the lattice diagnostics
+//! subject returned by [`crash_report_subject`] before the previous panic hook runs, so crashes of
+//! providers running in the field can be debugged after the fact.
+//!
+//! Only panics that terminate the process are reported: panics of the main thread and, when a
+//! provider is built with `panic = "abort"`, every panic. Panics of tasks, which the runtime
+//! recovers from, are left to the previous panic hook.
+//!
+//! Reports are also written to disk if a directory is configured with [`CRASH_REPORT_DIR_ENV`],
+//! and include the most recent log lines if [`CRASH_REPORT_LOG_LINES_ENV`] is set. Both are
+//! opt-in, since logs may contain sensitive data. The directory is created readable by the current
+//! user only, as is every report written to it.
+
+use core::time::Duration;
+
+use std::backtrace::Backtrace;
+use std::io::Write as _;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Once};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::provider::load_host_data;
+
+/// Environment variable setting the directory crash reports are written to. Reports are not
+/// written to disk unless it is set
+pub const CRASH_REPORT_DIR_ENV: &str = "WASMCLOUD_PROVIDER_CRASH_DIR";
+
+/// Environment variable setting the number of recent log lines included in crash reports. Log
+/// lines are not retained unless it is set
+pub const CRASH_REPORT_LOG_LINES_ENV: &str = "WASMCLOUD_PROVIDER_CRASH_LOG_LINES";
+
+/// Maximum amount of time the panic hook waits for a crash report to be published
+const CRASH_REPORT_PUBLISH_TIMEOUT: Duration = Duration::from_secs(2);
+
+static INSTALL_PANIC_HOOK: Once = Once::new();
+
+/// Report describing a provider crash
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CrashReport {
+    /// Friendly name of the provider
+    pub provider_name: String,
+    /// ID of the provider
+    pub provider_id: String,
+    /// Unique instance ID of this provider process
+    pub instance_id: String,
+    /// ID of the host running the provider
+    pub host_id: String,
+    /// Lattice the provider is running in
+    pub lattice: String,
+    /// Version of the provider SDK the provider was built with
+    pub sdk_version: String,
+    /// Panic message
+    pub message: String,
+    /// Source location of the panic, if known
+    pub location: Option<String>,
+    /// Name of the thread that panicked, if it had one
+    pub thread: Option<String>,
+    /// Backtrace captured at the time of the panic
+    pub backtrace: String,
+    /// Log lines emitted by the provider prior to the panic, oldest first. Empty unless enabled
+    /// with [`CRASH_REPORT_LOG_LINES_ENV`]
+    pub recent_logs: Vec<String>,
+    /// Time of the crash, in milliseconds since the UNIX epoch
+    pub timestamp_ms: u64,
+}
+
+impl CrashReport {
+    /// Build a crash report for the current provider from panic information
+    #[must_use]
+    pub fn from_panic(provider_name: &str, info: &PanicHookInfo<'_>) -> Self {
+        let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            (*s).to_string()
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Box<dyn Any>".to_string()
+        };
+        let (provider_id, instance_id, host_id, lattice) = load_host_data()
+            .map(|hd| {
+                (
+                    hd.provider_key.clone(),
+                    hd.instance_id.clone(),
+                    hd.host_id.clone(),
+                    hd.lattice_rpc_prefix.clone(),
+                )
+            })
+            .unwrap_or_default();
+        Self {
+            provider_name: provider_name.to_string(),
+            provider_id,
+            instance_id,
+            host_id,
+            lattice,
+            sdk_version: env!("CARGO_PKG_VERSION").to_string(),
+            message,
+            location: info.location().map(ToString::to_string),
+            thread: std::thread::current().name().map(ToString::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+            recent_logs: wasmcloud_tracing::recent_logs(),
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Write the crash report as JSON into `dir`, returning the path of the written file. The
+    /// directory is created if missing, readable by the current user only, as is the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory could not be created or the report could not be written
+    pub fn write_to(&self, dir: &Path) -> std::io::Result<PathBuf> {
+        let mut builder = std::fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(dir)?;
+        let id = if self.provider_id.is_empty() {
+            &self.provider_name
+        } else {
+            &self.provider_id
+        };
+        let path = dir.join(format!("{id}-{}.json", self.timestamp_ms));
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(&path)?
+            .write_all(&serde_json::to_vec_pretty(self)?)?;
+        Ok(path)
+    }
+}
+
+/// Returns the lattice subject crash reports of a provider are published on
+#[must_use]
+pub fn crash_report_subject(lattice: &str, provider_id: &str) -> String {
+    format!("wasmbus.diag.{lattice}.provider.{provider_id}.crash")
+}
+
+/// Returns the directory crash reports are written to, which is set by [`CRASH_REPORT_DIR_ENV`],
+/// if any
+#[must_use]
+pub fn crash_report_dir() -> Option<PathBuf> {
+    std::env::var_os(CRASH_REPORT_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Returns whether a panic of the thread named `thread` terminates the process. Panics of other
+/// threads only unwind the panicking thread or task, which is recovered from by the runtime
+fn is_fatal(thread: Option<&str>) -> bool {
+    cfg!(panic = "abort") || thread == Some("main")
+}
+
+/// Install a panic hook that produces a [`CrashReport`] for every panic terminating the process,
+/// publishing it to the lattice using `nats` and writing it to the [`crash_report_dir`], if any.
+/// The previously installed hook is invoked afterwards.
+///
+/// Only the first call installs a hook, subsequent calls do nothing.
+pub(crate) fn install_panic_hook(provider_name: &str, nats: Arc<async_nats::Client>) {
+    let provider_name = provider_name.to_string();
+    INSTALL_PANIC_HOOK.call_once(move || {
+        if let Some(lines) = std::env::var(CRASH_REPORT_LOG_LINES_ENV)
+            .ok()
+            .and_then(|lines| lines.parse().ok())
+        {
+            wasmcloud_tracing::capture_recent_logs(lines);
+        }
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !is_fatal(std::thread::current().name()) {
+                previous(info);
+                return;
+            }
+            let report = CrashReport::from_panic(&provider_name, info);
+            if let Some(dir) = crash_report_dir() {
+                match report.write_to(&dir) {
+                    Ok(path) => eprintln!("provider crash report written to {}", path.display()),
+                    Err(e) => eprintln!("failed to write provider crash report: {e}"),
+                }
+            }
+            if let Err(e) = publish_report(&nats, &report) {
+                eprintln!("failed to publish provider crash report: {e}");
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Publish a crash report to the lattice, waiting at most [`CRASH_REPORT_PUBLISH_TIMEOUT`].
+///
+/// The panic may happen on a runtime thread, so the report is published from a dedicated thread
+/// with its own runtime rather than blocking on the provider's runtime.
+fn publish_report(nats: &Arc<async_nats::Client>, report: &CrashReport) -> anyhow::Result<()> {
+    let subject = crash_report_subject(&report.lattice, &report.provider_id);
+    let payload = serde_json::to_vec(report)?;
+    let nats = Arc::clone(nats);
+    let (tx, rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("crash-report".into())
+        .spawn(move || {
+            let res = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(anyhow::Error::from)
+                .and_then(|rt| {
+                    rt.block_on(async {
+                        nats.publish(subject, payload.into()).await?;
+                        nats.flush().await?;
+                        anyhow::Ok(())
+                    })
+                });
+            // The receiver may have given up waiting already
+            let _ = tx.send(res);
+        })?;
+    rx.recv_timeout(CRASH_REPORT_PUBLISH_TIMEOUT)
+        .map_err(|_| anyhow::anyhow!("timed out publishing crash report"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_process_terminating_panics_are_fatal() {
+        assert!(is_fatal(Some("main")));
+        assert_eq!(
+            is_fatal(Some("tokio-runtime-worker")),
+            cfg!(panic = "abort")
+        );
+        assert_eq!(is_fatal(None), cfg!(panic = "abort"));
+    }
+
+    #[test]
+    fn reports_are_written_privately() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let report = CrashReport {
+            provider_name: "test-provider".into(),
+            message: "boom".into(),
+            timestamp_ms: 42,
+            ..Default::default()
+        };
+        let path = report.write_to(&dir.path().join("crashes"))?;
+        assert_eq!(
+            path,
+            dir.path().join("crashes").join("test-provider-42.json")
+        );
+        let written: CrashReport = serde_json::from_slice(&std::fs::read(&path)?)?;
+        assert_eq!(written.message, "boom");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+
+            let dir = std::fs::metadata(dir.path().join("crashes"))?;
+            assert_eq!(dir.permissions().mode() & 0o777, 0o700);
+            assert_eq!(
+                std::fs::metadata(&path)?.permissions().mode() & 0o777,
+                0o600
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Serving multiple versions of the interfaces exported by a provider.
+//!
+//! Components linked to a provider invoke the exact version of the interface they were built
+//! against, e.g. `wasi:keyvalue/store@0.2.0-draft`. To let components move to a newer version one
+//! at a time, a provider can generate bindings for every version it supports with
+//! [`wit-bindgen-wrpc`] and serve all of them at once with [`ProviderExports`]. Handler code is
+//! shared by implementing the generated handler trait of each version for a [`VersionAdapter`] of
+//! the provider, which converts between the types of that version and the ones of the provider.
+//!
+//! ```rust,ignore
+//! use wasmcloud_provider_sdk::exports::{ProviderExports, VersionAdapter};
+//!
+//! /// Marker of the `0.2.0-draft` bindings
+//! struct Draft;
+//!
+//! impl bindings_draft::exports::wasi::keyvalue::store::Handler<Option<Context>>
+//!     for VersionAdapter<KvProvider, Draft>
+//! {
+//!     async fn get(&self, cx: Option<Context>, bucket: String, key: String) -> anyhow::Result<..> {
+//!         // Delegate to the handler of the latest version, converting types as needed
+//!         self.provider().get(cx, bucket, key).await.map(..)
+//!     }
+//! }
+//!
+//! ProviderExports::new(&wrpc)
+//!     .serve(provider.clone(), bindings::serve)
+//!     .await?
+//!     .serve(VersionAdapter::<_, Draft>::new(provider), bindings_draft::serve)
+//!     .await?
+//!     .run(shutdown)
+//!     .await?;
+//! ```
+//!
+//! [`wit-bindgen-wrpc`]: https://github.com/bytecodealliance/wrpc
+
+use core::fmt;
+use core::future::Future;
+use core::marker::PhantomData;
+
+use std::collections::HashSet;
+
+use anyhow::{bail, Context as _};
+
+use crate::provider::{serve_invocations, InvocationStreams, WrpcClient};
+
+/// Exports of a provider, which may include multiple versions of the same interface, served
+/// together on a single wRPC client
+#[must_use]
+pub struct ProviderExports<'a> {
+    client: &'a WrpcClient,
+    invocations: InvocationStreams,
+}
+
+impl<'a> ProviderExports<'a> {
+    /// Create an empty set of exports served on `client`
+    pub fn new(client: &'a WrpcClient) -> Self {
+        Self {
+            client,
+            invocations: Vec::new(),
+        }
+    }
+
+    /// Add the exports served by `handler` using the `serve` function generated by
+    /// [`wit-bindgen-wrpc`] for one version of the provider's interfaces
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the exports could not be served or if a function is already served by
+    /// previously added exports, e.g. because the same version was added twice
+    ///
+    /// [`wit-bindgen-wrpc`]: https://github.com/bytecodealliance/wrpc
+    pub async fn serve<H, F, Fut>(mut self, handler: H, serve: F) -> anyhow::Result<Self>
+    where
+        F: FnOnce(&'a WrpcClient, H) -> Fut,
+        Fut: Future<Output = anyhow::Result<InvocationStreams>> + wrpc_transport::Captures<'a>,
+    {
+        let invocations = serve(self.client, handler)
+            .await
+            .context("failed to serve exports")?;
+        let served: HashSet<_> = self
+            .invocations
+            .iter()
+            .map(|(instance, name, _)| (*instance, *name))
+            .collect();
+        for (instance, name, _) in &invocations {
+            if served.contains(&(*instance, *name)) {
+                bail!("function `{name}` of `{instance}` is exported more than once");
+            }
+        }
+        self.invocations.extend(invocations);
+        Ok(self)
+    }
+
+    /// Returns the instances and functions of all added exports, e.g. to log them on startup
+    pub fn functions(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
+        self.invocations
+            .iter()
+            .map(|(instance, name, _)| (*instance, *name))
+    }
+
+    /// Serve invocations of all added exports until `shutdown` completes
+    ///
+    /// # Errors
+    ///
+    /// Currently never returns an error, failures of individual invocations are logged instead
+    pub async fn run(self, shutdown: impl Future<Output = ()>) -> anyhow::Result<()> {
+        serve_invocations(self.invocations, shutdown).await
+    }
+}
+
+/// Adapter of a provider to the bindings of one version of its interfaces, identified by the
+/// marker type `V`.
+///
+/// The generated handler traits of each version can be implemented for the adapter of that
+/// version, so that the provider itself only implements the traits of a single version.
+pub struct VersionAdapter<P, V> {
+    provider: P,
+    version: PhantomData<fn() -> V>,
+}
+
+impl<P, V> VersionAdapter<P, V> {
+    /// Adapt `provider` to the bindings identified by `V`
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            version: PhantomData,
+        }
+    }
+
+    /// Returns the adapted provider
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Returns the adapted provider, consuming the adapter
+    pub fn into_inner(self) -> P {
+        self.provider
+    }
+}
+
+impl<P: Clone, V> Clone for VersionAdapter<P, V> {
+    fn clone(&self) -> Self {
+        Self::new(self.provider.clone())
+    }
+}
+
+impl<P: fmt::Debug, V> fmt::Debug for VersionAdapter<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VersionAdapter")
+            .field("provider", &self.provider)
+            .field("version", &core::any::type_name::<V>())
+            .finish()
+    }
+}
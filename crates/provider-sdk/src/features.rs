@@ -0,0 +1,314 @@
+//! Feature flags for capability providers, read from provider configuration.
+//!
+//! Providers declare the flags they understand along with their defaults using
+//! [`FeatureFlags::builder`], and resolve them against the configuration they were started with:
+//!
+//! ```rust
+//! use std::collections::HashMap;
+//!
+//! use wasmcloud_provider_sdk::features::FeatureFlags;
+//!
+//! let config = HashMap::from([
+//!     ("feature.streaming-uploads".to_string(), "on".to_string()),
+//!     ("feature.max-batch-size".to_string(), "64".to_string()),
+//! ]);
+//! let flags = FeatureFlags::builder()
+//!     .flag("streaming-uploads", false)
+//!     .flag("max-batch-size", 16)
+//!     .flag("compression", "none")
+//!     .build(&config);
+//! assert!(flags.enabled("streaming-uploads"));
+//! assert_eq!(flags.get::<i64>("max-batch-size"), Some(64));
+//! assert_eq!(flags.get::<String>("compression").as_deref(), Some("none"));
+//! ```
+//!
+//! Flags are set with configuration keys of the form `feature.<name>`. Values are coerced to the
+//! type of the flag default; values that cannot be coerced are ignored in favor of the default.
+//! The effective value of every flag is logged when the flags are built, so that the behavior of a
+//! provider running in the field can be determined from its logs.
+
+use core::fmt;
+
+use std::collections::{BTreeMap, HashMap};
+
+use tracing::{info, warn};
+
+/// Prefix of configuration keys that set feature flags
+pub const FEATURE_FLAG_PREFIX: &str = "feature.";
+
+/// Value of a feature flag
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlagValue {
+    /// A boolean flag, set with `true`/`false`, `1`/`0`, `yes`/`no` or `on`/`off`
+    Bool(bool),
+    /// An integer flag
+    Int(i64),
+    /// A floating point flag
+    Float(f64),
+    /// A string flag, which accepts any value
+    String(String),
+}
+
+impl FlagValue {
+    /// Parse `value` into a flag value of the same type as `self`
+    fn coerce(&self, value: &str) -> Option<Self> {
+        let value = value.trim();
+        match self {
+            Self::Bool(_) => match value.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" | "enabled" => Some(Self::Bool(true)),
+                "false" | "0" | "no" | "off" | "disabled" => Some(Self::Bool(false)),
+                _ => None,
+            },
+            Self::Int(_) => value.parse().ok().map(Self::Int),
+            Self::Float(_) => value.parse().ok().map(Self::Float),
+            Self::String(_) => Some(Self::String(value.to_string())),
+        }
+    }
+
+    /// Name of the type of the flag, used in logs
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Int(_) => "int",
+            Self::Float(_) => "float",
+            Self::String(_) => "string",
+        }
+    }
+}
+
+impl fmt::Display for FlagValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::Int(v) => write!(f, "{v}"),
+            Self::Float(v) => write!(f, "{v}"),
+            Self::String(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl From<bool> for FlagValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i64> for FlagValue {
+    fn from(v: i64) -> Self {
+        Self::Int(v)
+    }
+}
+
+impl From<i32> for FlagValue {
+    fn from(v: i32) -> Self {
+        Self::Int(v.into())
+    }
+}
+
+impl From<u32> for FlagValue {
+    fn from(v: u32) -> Self {
+        Self::Int(v.into())
+    }
+}
+
+impl From<f64> for FlagValue {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<&str> for FlagValue {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_string())
+    }
+}
+
+impl From<String> for FlagValue {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+/// Types that feature flag values can be read as, see [`FeatureFlags::get`]
+pub trait FromFlagValue: Sized {
+    /// Convert a flag value, returning `None` if it has a different type
+    fn from_flag_value(value: &FlagValue) -> Option<Self>;
+}
+
+impl FromFlagValue for bool {
+    fn from_flag_value(value: &FlagValue) -> Option<Self> {
+        match value {
+            FlagValue::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromFlagValue for i64 {
+    fn from_flag_value(value: &FlagValue) -> Option<Self> {
+        match value {
+            FlagValue::Int(v) => Some(*v),
+            _ => None,
+        }
+    }
+}
+
+impl FromFlagValue for u64 {
+    fn from_flag_value(value: &FlagValue) -> Option<Self> {
+        match value {
+            FlagValue::Int(v) => (*v).try_into().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromFlagValue for usize {
+    fn from_flag_value(value: &FlagValue) -> Option<Self> {
+        match value {
+            FlagValue::Int(v) => (*v).try_into().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromFlagValue for f64 {
+    fn from_flag_value(value: &FlagValue) -> Option<Self> {
+        match value {
+            FlagValue::Float(v) => Some(*v),
+            #[allow(clippy::cast_precision_loss)]
+            FlagValue::Int(v) => Some(*v as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromFlagValue for String {
+    fn from_flag_value(value: &FlagValue) -> Option<Self> {
+        match value {
+            FlagValue::String(v) => Some(v.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Where the effective value of a feature flag came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlagSource {
+    /// The flag was not set in configuration, or was set to an invalid value
+    Default,
+    /// The flag was set in configuration
+    Config,
+}
+
+/// Builder of [`FeatureFlags`], declaring the flags a provider understands
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct FeatureFlagsBuilder {
+    defaults: BTreeMap<String, FlagValue>,
+}
+
+impl FeatureFlagsBuilder {
+    /// Declare a flag with the given name and default value. The type of the default determines
+    /// how configured values are coerced.
+    pub fn flag(mut self, name: impl Into<String>, default: impl Into<FlagValue>) -> Self {
+        self.defaults.insert(name.into(), default.into());
+        self
+    }
+
+    /// Resolve the declared flags against provider configuration, e.g. the one returned by
+    /// [`ProviderInitConfig::get_config`](crate::ProviderInitConfig::get_config), and log their
+    /// effective values
+    #[must_use]
+    pub fn build(self, config: &HashMap<String, String>) -> FeatureFlags {
+        for key in config.keys() {
+            if let Some(name) = key.strip_prefix(FEATURE_FLAG_PREFIX) {
+                if !self.defaults.contains_key(name) {
+                    warn!(
+                        flag = name,
+                        "ignoring unknown feature flag in provider config"
+                    );
+                }
+            }
+        }
+        let flags = self
+            .defaults
+            .into_iter()
+            .map(|(name, default)| {
+                let configured = config
+                    .get(&format!("{FEATURE_FLAG_PREFIX}{name}"))
+                    .and_then(|value| {
+                        let coerced = default.coerce(value);
+                        if coerced.is_none() {
+                            warn!(
+                                flag = name,
+                                value,
+                                expected = default.type_name(),
+                                "ignoring invalid feature flag value in provider config, using default",
+                            );
+                        }
+                        coerced
+                    });
+                let flag = match configured {
+                    Some(value) => (value, FlagSource::Config),
+                    None => (default, FlagSource::Default),
+                };
+                (name, flag)
+            })
+            .collect();
+        let flags = FeatureFlags { flags };
+        flags.log_effective();
+        flags
+    }
+}
+
+/// Resolved feature flags of a provider
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureFlags {
+    flags: BTreeMap<String, (FlagValue, FlagSource)>,
+}
+
+impl FeatureFlags {
+    /// Create a builder to declare the flags a provider understands
+    pub fn builder() -> FeatureFlagsBuilder {
+        FeatureFlagsBuilder::default()
+    }
+
+    /// Get the value of a flag as `T`, returning `None` if the flag was not declared or is of a
+    /// different type
+    #[must_use]
+    pub fn get<T: FromFlagValue>(&self, name: &str) -> Option<T> {
+        self.value(name).and_then(T::from_flag_value)
+    }
+
+    /// Returns whether the given boolean flag is enabled. Undeclared flags are disabled.
+    #[must_use]
+    pub fn enabled(&self, name: &str) -> bool {
+        self.get(name).unwrap_or_default()
+    }
+
+    /// Get the raw value of a flag
+    #[must_use]
+    pub fn value(&self, name: &str) -> Option<&FlagValue> {
+        self.flags.get(name).map(|(value, _)| value)
+    }
+
+    /// Get where the effective value of a flag came from
+    #[must_use]
+    pub fn source(&self, name: &str) -> Option<FlagSource> {
+        self.flags.get(name).map(|(_, source)| *source)
+    }
+
+    /// Iterate over the names and effective values of all flags, in sorted order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FlagValue)> {
+        self.flags
+            .iter()
+            .map(|(name, (value, _))| (name.as_str(), value))
+    }
+
+    /// Log the effective value of every flag
+    pub fn log_effective(&self) {
+        for (name, (value, source)) in &self.flags {
+            info!(flag = name, %value, ?source, "provider feature flag");
+        }
+    }
+}
@@ -0,0 +1,633 @@
+//! Leader election between the instances of a provider running in a lattice.
+//!
+//! Providers that must run singleton background work, such as polling an external system once
+//! per lattice, can elect a leader among their instances with [`LeaderElection`]. The leader holds
+//! a lease stored in a NATS JetStream key-value bucket of the lattice, which expires unless it is
+//! renewed within the lease TTL. Every instance is a candidate: when the leader stops or loses its
+//! connection, its lease expires and another candidate takes over.
+//!
+//! ```rust,no_run
+//! use wasmcloud_provider_sdk::leader::LeaderElection;
+//! use wasmcloud_provider_sdk::get_connection;
+//!
+//! async fn run() -> anyhow::Result<()> {
+//!     let election = LeaderElection::builder("poller")
+//!         .on_elected(|| tracing::info!("starting poller"))
+//!         .on_demoted(|| tracing::info!("stopping poller"))
+//!         .start(get_connection())
+//!         .await?;
+//!     let mut leader = election.subscribe();
+//!     while leader.changed().await.is_ok() {
+//!         // ...
+//!     }
+//!     election.resign().await;
+//!     Ok(())
+//! }
+//! ```
+
+use core::time::Duration;
+
+use core::future::Future;
+
+use std::sync::Arc;
+
+use anyhow::{ensure, Context as _};
+use async_nats::jetstream::kv::{self, Operation};
+use bytes::Bytes;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout, Instant};
+use tracing::{debug, info, instrument, warn};
+
+use crate::provider::ProviderConnection;
+
+/// Default TTL of leadership leases
+pub const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(15);
+
+/// Fraction of the lease TTL each request to the lease bucket must complete within. Together with
+/// renewing three times per TTL, this guarantees that a leader whose renewal stalls steps down
+/// before its lease can expire and be acquired by another candidate.
+const OP_TIMEOUT_DIVISOR: u32 = 4;
+
+/// Get the name of the key-value bucket holding the leadership leases of a lattice
+#[must_use]
+pub fn leader_bucket(lattice: &str) -> String {
+    format!("PROVIDER_LEADERS_{lattice}")
+}
+
+type Callback = Arc<dyn Fn() + Send + Sync>;
+
+/// Builder of a [`LeaderElection`]
+#[must_use]
+pub struct LeaderElectionBuilder {
+    name: String,
+    candidate_id: Option<String>,
+    ttl: Duration,
+    on_elected: Option<Callback>,
+    on_demoted: Option<Callback>,
+}
+
+impl LeaderElectionBuilder {
+    /// Set the ID of this candidate, which defaults to the ID of the host and the provider. The ID
+    /// must be unique among the candidates of the election.
+    pub fn candidate_id(self, candidate_id: impl Into<String>) -> Self {
+        Self {
+            candidate_id: Some(candidate_id.into()),
+            ..self
+        }
+    }
+
+    /// Set the TTL of the leadership lease, which is renewed three times per TTL.
+    ///
+    /// Every request to the lease bucket must complete within a quarter of the TTL. A leader whose
+    /// renewal fails or times out steps down, so that it never acts as the leader after its lease
+    /// may have expired.
+    ///
+    /// The TTL is a property of the lease bucket and only applies if the bucket does not exist
+    /// yet. Otherwise, the TTL of the existing bucket is used.
+    pub fn ttl(self, ttl: Duration) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// Set a callback invoked when this candidate becomes the leader.
+    ///
+    /// Callbacks are invoked in order on a dedicated thread, so slow callbacks delay later
+    /// callbacks but not the renewal of the lease. Use [`LeaderElection::subscribe`] for the
+    /// current leadership status.
+    pub fn on_elected(self, f: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            on_elected: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Set a callback invoked when this candidate stops being the leader, including when it
+    /// resigns
+    pub fn on_demoted(self, f: impl Fn() + Send + Sync + 'static) -> Self {
+        Self {
+            on_demoted: Some(Arc::new(f)),
+            ..self
+        }
+    }
+
+    /// Join the election as a candidate using the lattice connection of the provider
+    pub async fn start(self, connection: &ProviderConnection) -> anyhow::Result<LeaderElection> {
+        let candidate_id = self
+            .candidate_id
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", connection.host_id, connection.provider_id));
+        self.start_with_client(
+            connection.nats.as_ref().clone(),
+            &connection.lattice,
+            candidate_id,
+        )
+        .await
+    }
+
+    /// Join the election as a candidate using the given NATS client
+    #[instrument(level = "debug", skip(self, nats))]
+    pub async fn start_with_client(
+        self,
+        nats: async_nats::Client,
+        lattice: &str,
+        candidate_id: String,
+    ) -> anyhow::Result<LeaderElection> {
+        ensure!(
+            !self.name.is_empty()
+                && self
+                    .name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.')),
+            "election name must be non-empty and only contain alphanumeric characters, `-`, `_` and `.`"
+        );
+        ensure!(!candidate_id.is_empty(), "candidate ID must be non-empty");
+        ensure!(!self.ttl.is_zero(), "lease TTL must be non-zero");
+
+        let js = async_nats::jetstream::new(nats);
+        let bucket = leader_bucket(lattice);
+        let store = match js.get_key_value(&bucket).await {
+            Ok(store) => store,
+            Err(_) => js
+                .create_key_value(kv::Config {
+                    bucket: bucket.clone(),
+                    description: "Leadership leases of provider instances".into(),
+                    history: 1,
+                    max_age: self.ttl,
+                    ..Default::default()
+                })
+                .await
+                .with_context(|| format!("failed to create leader bucket `{bucket}`"))?,
+        };
+        let ttl = store
+            .status()
+            .await
+            .map(|status| status.max_age())
+            .ok()
+            .filter(|ttl| !ttl.is_zero())
+            .unwrap_or(self.ttl);
+
+        Ok(LeaderElection::spawn(
+            store,
+            self.name,
+            Bytes::from(candidate_id),
+            ttl,
+            self.on_elected,
+            self.on_demoted,
+        ))
+    }
+}
+
+/// Membership in a leader election, see the [module documentation](self).
+///
+/// Dropping the election withdraws the candidate, releasing the lease if it is the leader.
+pub struct LeaderElection {
+    leader: watch::Receiver<bool>,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl LeaderElection {
+    /// Create a builder of an election with the given name, which is shared by all candidates
+    pub fn builder(name: impl Into<String>) -> LeaderElectionBuilder {
+        LeaderElectionBuilder {
+            name: name.into(),
+            candidate_id: None,
+            ttl: DEFAULT_LEASE_TTL,
+            on_elected: None,
+            on_demoted: None,
+        }
+    }
+
+    fn spawn(
+        store: impl LeaseStore,
+        key: String,
+        id: Bytes,
+        ttl: Duration,
+        on_elected: Option<Callback>,
+        on_demoted: Option<Callback>,
+    ) -> Self {
+        let (leader_tx, leader_rx) = watch::channel(false);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let notifier = (on_elected.is_some() || on_demoted.is_some())
+            .then(|| Notifier::spawn(key.clone(), on_elected, on_demoted));
+        let candidate = Candidate {
+            store,
+            key,
+            id,
+            ttl,
+            renew_interval: ttl / 3,
+            op_timeout: ttl / OP_TIMEOUT_DIVISOR,
+            leader: leader_tx,
+            notifier,
+        };
+        Self {
+            leader: leader_rx,
+            shutdown: Some(shutdown_tx),
+            task: tokio::spawn(candidate.run(shutdown_rx)),
+        }
+    }
+
+    /// Returns whether this candidate is currently the leader
+    #[must_use]
+    pub fn is_leader(&self) -> bool {
+        *self.leader.borrow()
+    }
+
+    /// Subscribe to changes of leadership of this candidate
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.leader.clone()
+    }
+
+    /// Withdraw from the election, releasing the lease if this candidate is the leader so that
+    /// another candidate can take over without waiting for the lease to expire. Returns once the
+    /// pending callbacks, including the `on_demoted` callback of a leader, have been invoked.
+    pub async fn resign(mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Err(err) = (&mut self.task).await {
+            warn!(?err, "leader election task failed");
+        }
+    }
+}
+
+impl Drop for LeaderElection {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+/// Operations on the key-value bucket holding the leadership leases
+trait LeaseStore: Send + Sync + 'static {
+    /// Create `key`, failing if it exists, and return its revision
+    fn create(&self, key: &str, value: Bytes) -> impl Future<Output = anyhow::Result<u64>> + Send;
+
+    /// Update `key` if its revision is `revision`, and return the new revision
+    fn update(
+        &self,
+        key: &str,
+        value: Bytes,
+        revision: u64,
+    ) -> impl Future<Output = anyhow::Result<u64>> + Send;
+
+    /// Get the value and revision of `key`, unless it does not exist or was deleted
+    fn get(&self, key: &str) -> impl Future<Output = anyhow::Result<Option<(Bytes, u64)>>> + Send;
+
+    /// Delete `key` if its revision is `revision`
+    fn delete(&self, key: &str, revision: u64) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+impl LeaseStore for kv::Store {
+    async fn create(&self, key: &str, value: Bytes) -> anyhow::Result<u64> {
+        Ok(kv::Store::create(self, key, value).await?)
+    }
+
+    async fn update(&self, key: &str, value: Bytes, revision: u64) -> anyhow::Result<u64> {
+        Ok(kv::Store::update(self, key, value, revision).await?)
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<(Bytes, u64)>> {
+        match self.entry(key).await? {
+            Some(kv::Entry {
+                value,
+                revision,
+                operation: Operation::Put,
+                ..
+            }) => Ok(Some((value, revision))),
+            _ => Ok(None),
+        }
+    }
+
+    async fn delete(&self, key: &str, revision: u64) -> anyhow::Result<()> {
+        Ok(self.delete_expect_revision(key, Some(revision)).await?)
+    }
+}
+
+/// Invokes the leadership callbacks on a dedicated thread, in the order of the changes
+struct Notifier {
+    changes: mpsc::UnboundedSender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl Notifier {
+    fn spawn(key: String, on_elected: Option<Callback>, on_demoted: Option<Callback>) -> Self {
+        let (changes, mut rx) = mpsc::unbounded_channel();
+        let task = tokio::task::spawn_blocking(move || {
+            while let Some(leader) = rx.blocking_recv() {
+                let callback = if leader { &on_elected } else { &on_demoted };
+                if let Some(f) = callback {
+                    debug!(election = key, leader, "invoking leadership callback");
+                    f();
+                }
+            }
+        });
+        Self { changes, task }
+    }
+}
+
+/// A lease held by this candidate
+#[derive(Clone, Copy, Debug)]
+struct Lease {
+    revision: u64,
+    /// When the request that last created or renewed the lease was sent, which is no later than
+    /// when the TTL of the lease started
+    renewed_at: Instant,
+}
+
+struct Candidate<S> {
+    store: S,
+    key: String,
+    id: Bytes,
+    ttl: Duration,
+    renew_interval: Duration,
+    op_timeout: Duration,
+    leader: watch::Sender<bool>,
+    notifier: Option<Notifier>,
+}
+
+impl<S: LeaseStore> Candidate<S> {
+    async fn run(mut self, mut shutdown: oneshot::Receiver<()>) {
+        let mut interval = tokio::time::interval(self.renew_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut lease = None;
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                _ = interval.tick() => {}
+            }
+            lease = self
+                .acquire(lease)
+                .await
+                .filter(|lease| self.is_valid(lease));
+            self.set_leader(lease.is_some());
+        }
+        if let Some(Lease { revision, .. }) = lease {
+            match timeout(self.op_timeout, self.store.delete(&self.key, revision)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => warn!(
+                    ?err,
+                    election = self.key,
+                    "failed to release leadership lease"
+                ),
+                Err(_) => warn!(election = self.key, "timed out releasing leadership lease"),
+            }
+        }
+        self.set_leader(false);
+        if let Some(Notifier { changes, task }) = self.notifier.take() {
+            drop(changes);
+            if let Err(err) = task.await {
+                warn!(?err, election = self.key, "leadership callback panicked");
+            }
+        }
+    }
+
+    /// Returns whether the lease is certain not to have expired by the time the next renewal
+    /// completes or times out
+    fn is_valid(&self, lease: &Lease) -> bool {
+        lease.renewed_at.elapsed() + self.renew_interval + self.op_timeout < self.ttl
+    }
+
+    /// Acquire or renew the lease, returning it if this candidate holds it
+    async fn acquire(&self, lease: Option<Lease>) -> Option<Lease> {
+        let sent = Instant::now();
+        if let Some(lease) = lease {
+            match timeout(
+                self.op_timeout,
+                self.store
+                    .update(&self.key, self.id.clone(), lease.revision),
+            )
+            .await
+            {
+                Ok(Ok(revision)) => {
+                    return Some(Lease {
+                        revision,
+                        renewed_at: sent,
+                    })
+                }
+                Ok(Err(err)) => {
+                    warn!(
+                        ?err,
+                        election = self.key,
+                        "failed to renew leadership lease"
+                    );
+                }
+                Err(_) => {
+                    warn!(
+                        election = self.key,
+                        "timed out renewing leadership lease, stepping down"
+                    );
+                    return None;
+                }
+            }
+            // The lease may still be held by this candidate if the renewal failed after being
+            // applied, in which case it is adopted rather than waiting for it to expire. It may
+            // have been renewed later, so its TTL is conservatively assumed to have started with
+            // the last known renewal.
+            return match timeout(self.op_timeout, self.store.get(&self.key)).await {
+                Ok(Ok(Some((value, revision)))) if value == self.id => Some(Lease {
+                    revision,
+                    renewed_at: lease.renewed_at,
+                }),
+                Ok(Ok(_)) => None,
+                Ok(Err(err)) => {
+                    debug!(?err, election = self.key, "failed to get leadership lease");
+                    None
+                }
+                Err(_) => {
+                    debug!(election = self.key, "timed out getting leadership lease");
+                    None
+                }
+            };
+        }
+        match timeout(
+            self.op_timeout,
+            self.store.create(&self.key, self.id.clone()),
+        )
+        .await
+        {
+            Ok(Ok(revision)) => Some(Lease {
+                revision,
+                renewed_at: sent,
+            }),
+            Ok(Err(_)) => None,
+            Err(_) => {
+                debug!(election = self.key, "timed out acquiring leadership lease");
+                None
+            }
+        }
+    }
+
+    fn set_leader(&self, leader: bool) {
+        if !self.leader.send_if_modified(|current| {
+            let changed = *current != leader;
+            *current = leader;
+            changed
+        }) {
+            return;
+        }
+        if leader {
+            info!(election = self.key, "elected as leader");
+        } else {
+            info!(election = self.key, "no longer the leader");
+        }
+        if let Some(Notifier { changes, .. }) = &self.notifier {
+            let _ = changes.send(leader);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use anyhow::bail;
+
+    use super::*;
+
+    /// In-memory lease bucket holding a single lease, whose requests can be stalled
+    #[derive(Clone, Default)]
+    struct MemoryStore {
+        lease: Arc<Mutex<Option<(Bytes, u64)>>>,
+        revision: Arc<AtomicU64>,
+        renewals: Arc<AtomicUsize>,
+        stalled: Arc<AtomicBool>,
+    }
+
+    impl MemoryStore {
+        async fn stall(&self) {
+            if self.stalled.load(Ordering::Relaxed) {
+                std::future::pending::<()>().await;
+            }
+        }
+
+        fn holder(&self) -> Option<Bytes> {
+            self.lease
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|(id, _)| id.clone())
+        }
+    }
+
+    impl LeaseStore for MemoryStore {
+        async fn create(&self, _key: &str, value: Bytes) -> anyhow::Result<u64> {
+            self.stall().await;
+            let mut lease = self.lease.lock().unwrap();
+            if lease.is_some() {
+                bail!("lease exists");
+            }
+            let revision = self.revision.fetch_add(1, Ordering::Relaxed) + 1;
+            *lease = Some((value, revision));
+            Ok(revision)
+        }
+
+        async fn update(&self, _key: &str, value: Bytes, revision: u64) -> anyhow::Result<u64> {
+            self.stall().await;
+            let mut lease = self.lease.lock().unwrap();
+            if lease.as_ref().map(|(_, current)| *current) != Some(revision) {
+                bail!("wrong last revision");
+            }
+            let revision = self.revision.fetch_add(1, Ordering::Relaxed) + 1;
+            *lease = Some((value, revision));
+            self.renewals.fetch_add(1, Ordering::Relaxed);
+            Ok(revision)
+        }
+
+        async fn get(&self, _key: &str) -> anyhow::Result<Option<(Bytes, u64)>> {
+            self.stall().await;
+            Ok(self.lease.lock().unwrap().clone())
+        }
+
+        async fn delete(&self, _key: &str, revision: u64) -> anyhow::Result<()> {
+            self.stall().await;
+            let mut lease = self.lease.lock().unwrap();
+            if lease.as_ref().map(|(_, current)| *current) != Some(revision) {
+                bail!("wrong last revision");
+            }
+            *lease = None;
+            Ok(())
+        }
+    }
+
+    fn elect(
+        store: &MemoryStore,
+        id: &'static str,
+        ttl: Duration,
+        on_elected: Option<Callback>,
+        on_demoted: Option<Callback>,
+    ) -> LeaderElection {
+        LeaderElection::spawn(
+            store.clone(),
+            "test".into(),
+            Bytes::from(id),
+            ttl,
+            on_elected,
+            on_demoted,
+        )
+    }
+
+    async fn becomes(election: &LeaderElection, leader: bool, within: Duration) -> bool {
+        let mut rx = election.subscribe();
+        let changed = timeout(within, rx.wait_for(|current| *current == leader)).await;
+        changed.is_ok()
+    }
+
+    #[tokio::test]
+    async fn stalled_renewal_steps_down_before_lease_expires() {
+        let ttl = Duration::from_millis(1200);
+        let store = MemoryStore::default();
+        let election = elect(&store, "a", ttl, None, None);
+        assert!(becomes(&election, true, Duration::from_secs(1)).await);
+
+        // The lease was renewed at most a third of the TTL ago, so it cannot expire before
+        // two thirds of the TTL have passed
+        store.stalled.store(true, Ordering::Relaxed);
+        assert!(becomes(&election, false, ttl * 2 / 3).await);
+        assert_eq!(store.holder(), Some(Bytes::from("a")));
+    }
+
+    #[tokio::test]
+    async fn slow_callbacks_do_not_delay_renewals() {
+        let ttl = Duration::from_millis(300);
+        let store = MemoryStore::default();
+        let demoted = Arc::new(AtomicBool::default());
+        let election = elect(
+            &store,
+            "a",
+            ttl,
+            Some(Arc::new(|| std::thread::sleep(Duration::from_secs(1)))),
+            Some(Arc::new({
+                let demoted = Arc::clone(&demoted);
+                move || demoted.store(true, Ordering::Relaxed)
+            })),
+        );
+        assert!(becomes(&election, true, Duration::from_secs(1)).await);
+
+        tokio::time::sleep(ttl * 2).await;
+        assert!(election.is_leader());
+        assert!(store.renewals.load(Ordering::Relaxed) >= 2);
+        assert!(!demoted.load(Ordering::Relaxed));
+
+        election.resign().await;
+        assert!(demoted.load(Ordering::Relaxed));
+        assert_eq!(store.holder(), None);
+    }
+
+    #[tokio::test]
+    async fn resigning_hands_over_leadership() {
+        let ttl = Duration::from_millis(300);
+        let store = MemoryStore::default();
+        let a = elect(&store, "a", ttl, None, None);
+        assert!(becomes(&a, true, Duration::from_secs(1)).await);
+        let b = elect(&store, "b", ttl, None, None);
+        assert!(!becomes(&b, true, ttl).await);
+
+        a.resign().await;
+        assert!(becomes(&b, true, Duration::from_secs(1)).await);
+        assert_eq!(store.holder(), Some(Bytes::from("b")));
+    }
+}
@@ -5,17 +5,30 @@ use std::collections::HashMap;
 
 use anyhow::Context as _;
 use async_nats::{ConnectOptions, Event};
+use link_state::{LinkRecord, LinkRole};
 use provider::ProviderInitState;
 use tracing::{error, info, warn};
 use wasmcloud_core::secrets::SecretValue;
 
+pub mod crash;
 pub mod error;
+pub mod exports;
+pub mod features;
+pub mod leader;
+pub mod link_state;
 pub mod provider;
+pub mod watch;
 
 #[cfg(feature = "otel")]
 pub mod otel;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
 pub use anyhow;
+pub use exports::{ProviderExports, VersionAdapter};
+pub use features::{FeatureFlags, FlagValue};
+pub use leader::LeaderElection;
 pub use provider::{
     get_connection, load_host_data, run_provider, serve_provider_exports, ProviderConnection,
 };
@@ -27,6 +40,7 @@ pub use wasmcloud_core::{
     WitInterface, WitNamespace, WitPackage,
 };
 pub use wasmcloud_tracing;
+pub use watch::{PendingEvent, WatchFilter, WatchRegistry};
 
 /// Parse an sufficiently specified WIT operation/method into constituent parts.
 ///
@@ -122,6 +136,17 @@ impl Context {
             .get("link-name")
             .map_or("default", String::as_str)
     }
+
+    /// Get the ID of the invocation, which the caller sent in
+    /// [`INVOCATION_ID_HEADER`](wasmcloud_core::id::INVOCATION_ID_HEADER) so that the caller and
+    /// the provider can log the invocation under the same ID. Invocations from callers that did not
+    /// send an ID are assigned a new one when they are received.
+    #[must_use]
+    pub fn invocation_id(&self) -> Option<&str> {
+        self.tracing
+            .get(wasmcloud_core::id::INVOCATION_ID_HEADER)
+            .map(String::as_str)
+    }
 }
 
 /// Configuration of a link that is passed to a provider
@@ -319,6 +344,21 @@ pub trait Provider<E = anyhow::Error>: Sync {
         async { Ok(()) }
     }
 
+    /// Clean up after a link that was deleted while the provider was not running
+    ///
+    /// If link state is recorded, called on startup for every link recorded by the previous run of
+    /// the provider that the host no longer delivers, see [`link_state`]. The default
+    /// implementation calls [`Provider::delete_link_as_source`] or
+    /// [`Provider::delete_link_as_target`], depending on the role of the provider in the link.
+    fn cleanup_stale_link(&self, link: &LinkRecord) -> impl Future<Output = Result<(), E>> + Send {
+        async move {
+            match link.role {
+                LinkRole::Source => self.delete_link_as_source(link).await,
+                LinkRole::Target => self.delete_link_as_target(link).await,
+            }
+        }
+    }
+
     /// Perform health check. Called at regular intervals by host
     /// Default implementation always returns healthy
     fn health_request(
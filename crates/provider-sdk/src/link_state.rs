@@ -0,0 +1,322 @@
+//! Persisted link state of capability providers.
+//!
+//! Links deleted while a provider is not running, e.g. while it is being restarted after a crash,
+//! are never delivered to it, so resources the provider created for them outside of its process
+//! (queues, connections, buckets) would leak. Providers started with
+//! [`run_provider`](crate::run_provider) can therefore record the links they established in a
+//! [`LinkStateStore`]. On startup, links recorded by the previous run that are not part of the
+//! links delivered by the host are passed to
+//! [`Provider::cleanup_stale_link`](crate::Provider::cleanup_stale_link) before the delivered links
+//! are established.
+//!
+//! Link configuration routinely contains credentials, so link state is only recorded if a
+//! directory is configured with [`LINK_STATE_DIR_ENV`]. The directory is created readable by the
+//! current user only, as is every file written to it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt as _;
+use wasmcloud_core::InterfaceLinkDefinition;
+
+use crate::LinkDeleteInfo;
+
+/// Environment variable setting the directory link state is written to. Link state is not
+/// recorded unless it is set
+pub const LINK_STATE_DIR_ENV: &str = "WASMCLOUD_PROVIDER_STATE_DIR";
+
+/// Role of the provider in a link
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkRole {
+    /// The provider is the source of the link
+    Source,
+    /// The provider is the target of the link
+    Target,
+}
+
+/// A link established by a provider, as recorded in a [`LinkStateStore`]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct LinkRecord {
+    /// Role of the provider in the link
+    pub role: LinkRole,
+    /// Source of the link
+    pub source_id: String,
+    /// Target of the link
+    pub target: String,
+    /// Name of the link
+    pub name: String,
+    /// WIT namespace of the link
+    pub wit_namespace: String,
+    /// WIT package of the link
+    pub wit_package: String,
+    /// WIT interfaces of the link
+    pub interfaces: Vec<String>,
+    /// Configuration the provider received for the link, which can be used to find the resources
+    /// created for it. Secrets are never recorded
+    pub config: HashMap<String, String>,
+}
+
+impl LinkRecord {
+    /// Create a record of link `ld` established by the provider `provider_id`
+    #[must_use]
+    pub fn new(ld: &InterfaceLinkDefinition, provider_id: &str) -> Self {
+        let (role, config) = if ld.source_id == provider_id {
+            (LinkRole::Source, &ld.source_config)
+        } else {
+            (LinkRole::Target, &ld.target_config)
+        };
+        Self {
+            role,
+            source_id: ld.source_id.clone(),
+            target: ld.target.clone(),
+            name: ld.name.clone(),
+            wit_namespace: ld.wit_namespace.clone(),
+            wit_package: ld.wit_package.clone(),
+            interfaces: ld.interfaces.clone(),
+            config: config.clone(),
+        }
+    }
+
+    /// Returns true if this record describes the link `ld`
+    #[must_use]
+    pub fn matches(&self, ld: &InterfaceLinkDefinition) -> bool {
+        self.source_id == ld.source_id
+            && self.target == ld.target
+            && self.name == ld.name
+            && self.wit_namespace == ld.wit_namespace
+            && self.wit_package == ld.wit_package
+    }
+}
+
+impl LinkDeleteInfo for &LinkRecord {
+    fn get_source_id(&self) -> &str {
+        &self.source_id
+    }
+
+    fn get_target_id(&self) -> &str {
+        &self.target
+    }
+
+    fn get_link_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Returns the records of `recorded` for which `delivered` contains no link
+#[must_use]
+pub fn stale_links(
+    recorded: Vec<LinkRecord>,
+    delivered: &[InterfaceLinkDefinition],
+) -> Vec<LinkRecord> {
+    recorded
+        .into_iter()
+        .filter(|record| !delivered.iter().any(|ld| record.matches(ld)))
+        .collect()
+}
+
+/// File storing the links established by a provider
+#[derive(Clone, Debug)]
+pub struct LinkStateStore {
+    path: PathBuf,
+}
+
+impl LinkStateStore {
+    /// Create a store at `path`
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Create the store of provider `provider_id` run by host `host_id` in `lattice`, located in
+    /// [`link_state_dir`]. Returns `None` if no link state directory is configured
+    #[must_use]
+    pub fn for_provider(lattice: &str, host_id: &str, provider_id: &str) -> Option<Self> {
+        let dir = link_state_dir()?;
+        Some(Self::new(dir.join(format!(
+            "{lattice}-{host_id}-{provider_id}-links.json"
+        ))))
+    }
+
+    /// Returns the path of the file backing the store
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the recorded links, which are empty if nothing was recorded yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but could not be read or parsed
+    pub async fn load(&self) -> io::Result<Vec<LinkRecord>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(buf) => Ok(serde_json::from_slice(&buf)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replace the recorded links by `links`
+    ///
+    /// The file is replaced atomically, so that a crash while saving does not lose the links
+    /// recorded before. The directory of the file is created if missing, readable by the current
+    /// user only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory could not be created or the file could not be written
+    pub async fn save(&self, links: impl IntoIterator<Item = LinkRecord>) -> io::Result<()> {
+        // Sort the records so that the file does not change if the links did not
+        let links: BTreeMap<_, _> = links
+            .into_iter()
+            .map(|record| {
+                let key = (
+                    record.source_id.clone(),
+                    record.target.clone(),
+                    record.wit_namespace.clone(),
+                    record.wit_package.clone(),
+                    record.name.clone(),
+                );
+                (key, record)
+            })
+            .collect();
+        let links: Vec<_> = links.into_values().collect();
+        let buf = serde_json::to_vec_pretty(&links)?;
+        if let Some(dir) = self.path.parent() {
+            let mut builder = tokio::fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            builder.mode(0o700);
+            builder.create(dir).await?;
+        }
+        // Concurrent saves must not write to the same temporary file
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", wasmcloud_core::id::generate()));
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let res = async {
+            let mut file = options.open(&tmp).await?;
+            file.write_all(&buf).await?;
+            file.sync_all().await?;
+            tokio::fs::rename(&tmp, &self.path).await
+        }
+        .await;
+        if res.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        res
+    }
+}
+
+/// Returns the directory link state is written to, which is set by [`LINK_STATE_DIR_ENV`], if any
+#[must_use]
+pub fn link_state_dir() -> Option<PathBuf> {
+    std::env::var_os(LINK_STATE_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(source_id: &str, target: &str, name: &str) -> InterfaceLinkDefinition {
+        InterfaceLinkDefinition {
+            source_id: source_id.into(),
+            target: target.into(),
+            name: name.into(),
+            wit_namespace: "wasi".into(),
+            wit_package: "keyvalue".into(),
+            interfaces: vec!["store".into()],
+            source_config: HashMap::from([("bucket".into(), "source".into())]),
+            target_config: HashMap::from([("bucket".into(), "target".into())]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn records_config_of_provider_role() {
+        let record = LinkRecord::new(&link("component", "provider", "default"), "provider");
+        assert_eq!(record.role, LinkRole::Target);
+        assert_eq!(record.config["bucket"], "target");
+        let record = LinkRecord::new(&link("provider", "component", "default"), "provider");
+        assert_eq!(record.role, LinkRole::Source);
+        assert_eq!(record.config["bucket"], "source");
+    }
+
+    #[test]
+    fn stale_links_are_the_ones_not_delivered() {
+        let recorded = vec![
+            LinkRecord::new(&link("a", "provider", "default"), "provider"),
+            LinkRecord::new(&link("b", "provider", "default"), "provider"),
+            LinkRecord::new(&link("a", "provider", "other"), "provider"),
+        ];
+        let mut delivered = link("a", "provider", "default");
+        // Links are identified regardless of their interfaces and config
+        delivered.interfaces = vec!["atomics".into()];
+        delivered.target_config.clear();
+
+        let stale = stale_links(recorded.clone(), &[delivered]);
+        assert_eq!(stale, recorded[1..]);
+        assert_eq!(stale_links(recorded, &[]).len(), 3);
+        assert!(stale_links(Vec::new(), &[link("a", "provider", "default")]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn store_round_trips_links() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let store = LinkStateStore::new(dir.path().join("state").join("links.json"));
+        assert!(store.load().await?.is_empty());
+
+        let b = LinkRecord::new(&link("b", "provider", "default"), "provider");
+        let a = LinkRecord::new(&link("a", "provider", "default"), "provider");
+        store.save([b.clone(), a.clone()]).await?;
+        assert_eq!(store.load().await?, [a.clone(), b]);
+
+        // Concurrent saves each write their own temporary file
+        let (first, second) = tokio::join!(store.save([a.clone()]), store.save([a.clone()]));
+        first?;
+        second?;
+        assert_eq!(store.load().await?, [a]);
+        let mut entries = tokio::fs::read_dir(dir.path().join("state")).await?;
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            names.push(entry.file_name());
+        }
+        assert_eq!(names, ["links.json"]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+
+            let dir = std::fs::metadata(dir.path().join("state"))?;
+            assert_eq!(dir.permissions().mode() & 0o777, 0o700);
+            let file = std::fs::metadata(store.path())?;
+            assert_eq!(file.permissions().mode() & 0o777, 0o600);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn stores_are_keyed_by_host() {
+        // The only test modifying the environment of the process
+        std::env::remove_var(LINK_STATE_DIR_ENV);
+        assert!(LinkStateStore::for_provider("default", "host", "provider").is_none());
+
+        std::env::set_var(LINK_STATE_DIR_ENV, "/var/lib/wasmcloud");
+        let a = LinkStateStore::for_provider("default", "host-a", "provider")
+            .expect("store should be created");
+        let b = LinkStateStore::for_provider("default", "host-b", "provider")
+            .expect("store should be created");
+        std::env::remove_var(LINK_STATE_DIR_ENV);
+        assert_ne!(a.path(), b.path());
+        assert!(a.path().starts_with("/var/lib/wasmcloud"));
+    }
+}
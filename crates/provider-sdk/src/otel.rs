@@ -14,7 +14,7 @@ macro_rules! propagate_trace_for_ctx {
                 .tracing
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect::<Vec<(String, String)>>(),
+                .collect::<$crate::core::TraceContext>(),
 
             _ => TraceContextInjector::default_with_span()
                 .iter()
@@ -50,56 +50,15 @@ macro_rules! initialize_observability {
                 otel_config,
                 structured_logging,
                 log_level,
+                log_directives,
                 ..
             } = $crate::provider::load_host_data().context("failed to load host data")?;
 
             // Update OTEL configuration with overrides if provided via config to the provider
             let mut otel_config = otel_config.clone();
             for (k, v) in config.iter() {
-                match k.to_uppercase().as_str() {
-                    "OTEL_EXPORTER_OTLP_ENDPOINT" => {
-                        otel_config.observability_endpoint = Some(v.clone())
-                    }
-                    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT" => {
-                        otel_config.traces_endpoint = Some(v.clone())
-                    }
-                    "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT" => {
-                        otel_config.metrics_endpoint = Some(v.clone())
-                    }
-                    "OTEL_EXPORTER_OTLP_LOGS_ENDPOINT" => {
-                        otel_config.logs_endpoint = Some(v.clone())
-                    }
-                    "OTEL_TRACES_SAMPLER" => {
-                        otel_config.traces_sampler = Some(v.clone())
-                    }
-                    "OTEL_TRACES_SAMPLER_ARG" => {
-                        otel_config.traces_sampler_arg = Some(v.clone())
-                    }
-                    "OTEL_BSP_MAX_CONCURRENT_EXPORTS" => {
-                        let parsed = match v.parse::<usize>() {
-                            Ok(v) => v,
-                            Err(_) => {
-                                eprintln!(
-                                    "Failed to parse OTEL_BSP_MAX_CONCURRENT_EXPORTS as usize, using previously set value or default"
-                                );
-                                continue
-                            }
-                        };
-                        otel_config.concurrent_exports = Some(parsed)
-                    }
-                    "OTEL_BSP_MAX_QUEUE_SIZE" => {
-                        let parsed = match v.parse::<usize>() {
-                            Ok(v) => v,
-                            Err(_) => {
-                                eprintln!(
-                                    "Failed to parse OTEL_BSP_MAX_QUEUE_SIZE as usize, using previously set value or default"
-                                );
-                                continue
-                            }
-                        };
-                        otel_config.max_batch_queue_size = Some(parsed)
-                    }
-                    _ => {}
+                if let Err(e) = otel_config.apply_env([(k, v)]) {
+                    eprintln!("{e:#}, using previously set value or default");
                 }
             }
 
@@ -113,6 +72,7 @@ macro_rules! initialize_observability {
                 *structured_logging,
                 $maybe_flamegraphs_path,
                 log_level.as_ref(),
+                log_directives,
                 Some(&otel_config.trace_level),
             )
             .context("failed to configure observability")?;
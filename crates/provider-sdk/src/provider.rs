@@ -7,6 +7,7 @@ use core::time::Duration;
 use std::collections::HashMap;
 use std::io::BufRead;
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{bail, Context as _, Result};
 use async_nats::subject::ToSubject as _;
@@ -15,12 +16,15 @@ use base64::Engine;
 use bytes::Bytes;
 use futures::{stream, Stream, StreamExt as _, TryStreamExt as _};
 use nkeys::XKey;
-use once_cell::sync::OnceCell;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, mpsc, oneshot, RwLock};
 use tokio::task::{spawn_blocking, JoinSet};
 use tokio::{select, spawn, try_join};
 use tracing::{debug, error, info, instrument, trace, warn, Instrument as _};
+use wasmcloud_core::config_ref::{config_blob_bucket, resolve_config_refs, ConfigBlobRef};
+use wasmcloud_core::id::INVOCATION_ID_HEADER;
+use wasmcloud_core::metrics::{InvocationDirection, InvocationMetricAttributes, WorkloadKind};
 use wasmcloud_core::nats::convert_header_map_to_hashmap;
 use wasmcloud_core::rpc::{health_subject, link_del_subject, link_put_subject, shutdown_subject};
 use wasmcloud_core::secrets::SecretValue;
@@ -33,9 +37,11 @@ use wasmcloud_core::{
 use wasmcloud_core::TraceContext;
 #[cfg(feature = "otel")]
 use wasmcloud_tracing::context::attach_span_context;
+use wasmcloud_tracing::{InvocationMetrics, KeyValue};
 use wrpc_transport::InvokeExt as _;
 
 use crate::error::{ProviderInitError, ProviderInitResult};
+use crate::link_state::{stale_links, LinkRecord, LinkStateStore};
 use crate::{with_connection_event_logging, Context, LinkConfig, Provider, DEFAULT_NATS_ADDR};
 
 /// Name of the header that should be passed for invocations that identifies the source
@@ -44,6 +50,12 @@ const WRPC_SOURCE_ID_HEADER_NAME: &str = "source-id";
 static HOST_DATA: OnceCell<HostData> = OnceCell::new();
 static CONNECTION: OnceCell<ProviderConnection> = OnceCell::new();
 
+/// Standard invocation metrics of the provider, created on first use so that they are recorded by
+/// the meter provider configured when initializing observability
+static INVOCATION_METRICS: Lazy<InvocationMetrics> = Lazy::new(|| {
+    InvocationMetrics::new(&wasmcloud_tracing::global::meter("wasmcloud-provider-sdk"))
+});
+
 /// Retrieves the currently configured connection to the lattice. DO NOT call this method until
 /// after the provider is running (meaning [`run_provider`] has been called)
 /// or this method will panic. Only in extremely rare cases should this be called manually and it
@@ -808,6 +820,7 @@ pub async fn run_provider(
     friendly_name: &str,
 ) -> ProviderInitResult<impl Future<Output = ()>> {
     let init_state = init_provider(friendly_name).await?;
+    crate::crash::install_panic_hook(friendly_name, Arc::clone(&init_state.nats));
 
     // Run user-implemented provider-internal specific initialization
     if let Err(e) = provider.init(&init_state).await {
@@ -831,7 +844,15 @@ pub async fn run_provider(
         provider_private_xkey: provider_xkey,
     } = init_state;
 
-    let connection = ProviderConnection::new(
+    let link_state = LinkStateStore::for_provider(&lattice_rpc_prefix, &host_id, &provider_key);
+    let recorded_links = match &link_state {
+        Some(link_state) => link_state.load().await.unwrap_or_else(|err| {
+            warn!(%err, path = %link_state.path().display(), "failed to load link state");
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let mut connection = ProviderConnection::new(
         Arc::clone(&nats),
         provider_key,
         lattice_rpc_prefix,
@@ -840,11 +861,33 @@ pub async fn run_provider(
         provider_xkey,
         host_xkey,
     )?;
+    if let Some(link_state) = link_state {
+        connection = connection.with_link_state(link_state);
+    }
     CONNECTION.set(connection).map_err(|_| {
         ProviderInitError::Initialization("Provider connection was already initialized".to_string())
     })?;
     let connection = get_connection();
 
+    // Clean up after links deleted while the provider was not running, before establishing the
+    // delivered links, which replaces the recorded ones
+    for link in stale_links(recorded_links, &link_definitions) {
+        info!(
+            source = &link.source_id,
+            target = &link.target,
+            link_name = &link.name,
+            "cleaning up link deleted while provider was not running"
+        );
+        if let Err(e) = provider.cleanup_stale_link(&link).await {
+            error!(
+                error = %e,
+                source = &link.source_id,
+                target = &link.target,
+                "failed to clean up stale link",
+            );
+        }
+    }
+
     // Provide all links to the provider at startup to establish the initial state
     for ld in link_definitions {
         if let Err(e) = receive_link_for_provider(&provider, connection, ld).await {
@@ -854,6 +897,8 @@ pub async fn run_provider(
             );
         }
     }
+    // Forget the stale links even if no links were delivered
+    connection.save_link_state().await;
 
     debug!(?friendly_name, "provider finished initialization");
     Ok(handle_provider_commands(
@@ -878,6 +923,9 @@ pub type InvocationStreams = Vec<(
 )>;
 
 /// Serve exports of the provider using the `serve` function generated by [`wit-bindgen-wrpc`]
+///
+/// To serve multiple versions of the provider's interfaces, use
+/// [`ProviderExports`](crate::exports::ProviderExports) instead
 pub async fn serve_provider_exports<'a, P, F, Fut>(
     client: &'a WrpcClient,
     provider: P,
@@ -891,6 +939,14 @@ where
     let invocations = serve(client, provider)
         .await
         .context("failed to serve exports")?;
+    serve_invocations(invocations, shutdown).await
+}
+
+/// Serve the given invocation streams until `shutdown` completes
+pub(crate) async fn serve_invocations(
+    invocations: InvocationStreams,
+    shutdown: impl Future<Output = ()>,
+) -> anyhow::Result<()> {
     let mut invocations = stream::select_all(
         invocations
             .into_iter()
@@ -903,8 +959,25 @@ where
             Some((instance, name, res)) = invocations.next() => {
                 match res {
                     Ok(fut) => {
+                        let attributes = CONNECTION.get().map(|connection| {
+                            connection.invocation_attributes(
+                                InvocationDirection::Incoming,
+                                instance,
+                                name,
+                                None,
+                            )
+                        });
                         tasks.spawn(async move {
-                            if let Err(err) = fut.await {
+                            let start = Instant::now();
+                            let res = fut.await;
+                            if let Some(attributes) = attributes {
+                                INVOCATION_METRICS.record_incoming(
+                                    &attributes,
+                                    start.elapsed(),
+                                    res.is_err(),
+                                );
+                            }
+                            if let Err(err) = res {
                                 warn!(?err, instance, name, "failed to serve invocation");
                             }
                             trace!(instance, name, "successfully served invocation");
@@ -949,6 +1022,9 @@ pub struct ProviderConnection {
     // TODO: Reference this field to get static config
     #[allow(unused)]
     pub config: HashMap<String, String>,
+
+    /// Store the established links are recorded in, if any, see [`crate::link_state`]
+    pub link_state: Option<Arc<LinkStateStore>>,
 }
 
 impl fmt::Debug for ProviderConnection {
@@ -965,18 +1041,22 @@ impl fmt::Debug for ProviderConnection {
 pub fn invocation_context(headers: &HeaderMap) -> Context {
     #[cfg(feature = "otel")]
     {
-        let trace_context: TraceContext = convert_header_map_to_hashmap(headers)
-            .into_iter()
-            .collect::<Vec<(String, String)>>();
+        let trace_context = TraceContext::from(convert_header_map_to_hashmap(headers));
         attach_span_context(&trace_context);
     }
     // Determine source ID for the invocation
     let source_id = headers
         .get(WRPC_SOURCE_ID_HEADER_NAME)
         .map_or_else(|| "<unknown>".into(), ToString::to_string);
+    let mut tracing = convert_header_map_to_hashmap(headers);
+    // Callers predating invocation IDs don't send one, in which case the invocation gets a new ID
+    let invocation_id = tracing
+        .entry(INVOCATION_ID_HEADER.to_string())
+        .or_insert_with(wasmcloud_core::id::invocation_id);
+    trace!(%source_id, %invocation_id, "accepted invocation");
     Context {
         component: Some(source_id),
-        tracing: convert_header_map_to_hashmap(headers),
+        tracing,
     }
 }
 
@@ -986,6 +1066,8 @@ pub struct WrpcClient {
     timeout: Duration,
     provider_id: Arc<str>,
     target: Arc<str>,
+    lattice: Arc<str>,
+    host_id: Arc<str>,
 }
 
 impl wrpc_transport::Invoke for WrpcClient {
@@ -1007,10 +1089,30 @@ impl wrpc_transport::Invoke for WrpcClient {
         let mut headers = cx.unwrap_or_default();
         headers.insert("source-id", &*self.provider_id);
         headers.insert("target-id", &*self.target);
-        self.nats
+        if headers.get(INVOCATION_ID_HEADER).is_none() {
+            headers.insert(
+                INVOCATION_ID_HEADER,
+                wasmcloud_core::id::invocation_id().as_str(),
+            );
+        }
+        let params_size = params.len();
+        let res = self
+            .nats
             .timeout(self.timeout)
             .invoke(Some(headers), instance, func, params, paths)
-            .await
+            .await;
+        let attributes = InvocationMetrics::attributes(&InvocationMetricAttributes {
+            lattice: &self.lattice,
+            host_id: &self.host_id,
+            workload_kind: WorkloadKind::Provider,
+            workload_id: &self.provider_id,
+            direction: InvocationDirection::Outgoing,
+            instance,
+            func,
+            peer_id: Some(&self.target),
+        });
+        INVOCATION_METRICS.record_outgoing(&attributes, params_size, res.is_err());
+        res
     }
 }
 
@@ -1056,9 +1158,19 @@ impl ProviderConnection {
             config,
             provider_xkey: provider_private_xkey.into(),
             host_xkey: host_public_xkey.into(),
+            link_state: None,
         })
     }
 
+    /// Record the established links in `store`, see [`crate::link_state`]
+    #[must_use]
+    pub fn with_link_state(self, store: LinkStateStore) -> Self {
+        Self {
+            link_state: Some(Arc::new(store)),
+            ..self
+        }
+    }
+
     /// Retrieve a wRPC client that can be used based on the NATS client of this connection
     ///
     /// # Arguments
@@ -1080,7 +1192,7 @@ impl ProviderConnection {
         target: &str,
         timeout: Option<Duration>,
     ) -> anyhow::Result<WrpcClient> {
-        let prefix = Arc::from(format!("{}.{target}", &self.lattice));
+        let prefix = Arc::from(format!("{}.{target}", self.lattice));
         let nats = wrpc_transport_nats::Client::new(
             Arc::clone(&self.nats),
             Arc::clone(&prefix),
@@ -1092,6 +1204,29 @@ impl ProviderConnection {
             provider_id: Arc::clone(&self.provider_id),
             target: Arc::from(target),
             timeout: timeout.unwrap_or_else(|| Duration::from_secs(10)),
+            lattice: Arc::clone(&self.lattice),
+            host_id: Arc::from(self.host_id.as_str()),
+        })
+    }
+
+    /// Returns the attributes of the standard invocation metrics of an invocation of `instance`
+    /// and `func` served or sent by the provider
+    fn invocation_attributes(
+        &self,
+        direction: InvocationDirection,
+        instance: &str,
+        func: &str,
+        peer_id: Option<&str>,
+    ) -> Vec<KeyValue> {
+        InvocationMetrics::attributes(&InvocationMetricAttributes {
+            lattice: &self.lattice,
+            host_id: &self.host_id,
+            workload_kind: WorkloadKind::Provider,
+            workload_id: &self.provider_id,
+            direction,
+            instance,
+            func,
+            peer_id,
         })
     }
 
@@ -1101,6 +1236,29 @@ impl ProviderConnection {
         &self.provider_id
     }
 
+    /// Replace config values that reference blobs of the lattice config object store by the
+    /// content of the blobs, see [`wasmcloud_core::config_ref`].
+    ///
+    /// Config passed to the provider by the host is already resolved, this is meant for config
+    /// the provider obtains by other means. The object store is looked up through the lattice RPC
+    /// connection, which must have access to the JetStream domain of the lattice.
+    pub async fn resolve_config_refs(
+        &self,
+        config: HashMap<String, String>,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        if !config
+            .values()
+            .any(|value| ConfigBlobRef::parse(value).is_some())
+        {
+            return Ok(config);
+        }
+        let store = async_nats::jetstream::new(self.nats.as_ref().clone())
+            .get_object_store(config_blob_bucket(&self.lattice))
+            .await
+            .context("failed to get config object store")?;
+        resolve_config_refs(&store, config).await
+    }
+
     /// Stores link in the [`ProviderConnection`], either as a source link or target link
     /// depending on if the provider is the source or target of the link
     pub async fn put_link(&self, ld: InterfaceLinkDefinition) {
@@ -1115,6 +1273,7 @@ impl ProviderConnection {
                 .await
                 .insert(ld.source_id.to_string(), ld);
         }
+        self.save_link_state().await;
     }
 
     /// Deletes link from the [`ProviderConnection`], either a source link or target link
@@ -1125,6 +1284,31 @@ impl ProviderConnection {
         } else if target == &*self.provider_id {
             self.target_links.write().await.remove(source_id);
         }
+        self.save_link_state().await;
+    }
+
+    /// Record the established links in the link state store, if any
+    async fn save_link_state(&self) {
+        let Some(store) = &self.link_state else {
+            return;
+        };
+        let mut links: Vec<_> = self
+            .source_links
+            .read()
+            .await
+            .values()
+            .map(|ld| LinkRecord::new(ld, &self.provider_id))
+            .collect();
+        links.extend(
+            self.target_links
+                .read()
+                .await
+                .values()
+                .map(|ld| LinkRecord::new(ld, &self.provider_id)),
+        );
+        if let Err(err) = store.save(links).await {
+            warn!(%err, path = %store.path().display(), "failed to save link state");
+        }
     }
 
     /// Returns true if the source is linked to this provider or if the provider is linked to the target
@@ -1172,3 +1356,25 @@ impl ProviderConnection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invocations_keep_the_id_of_the_caller() {
+        let mut headers = HeaderMap::new();
+        headers.insert(WRPC_SOURCE_ID_HEADER_NAME, "component");
+        let cx = invocation_context(&headers);
+        let assigned = cx
+            .invocation_id()
+            .expect("invocation should be assigned an ID");
+        assert!(wasmcloud_core::id::is_valid(assigned));
+
+        let sent = wasmcloud_core::id::invocation_id();
+        headers.insert(INVOCATION_ID_HEADER, sent.as_str());
+        let cx = invocation_context(&headers);
+        assert_eq!(cx.invocation_id(), Some(sent.as_str()));
+        assert_eq!(cx.component.as_deref(), Some("component"));
+    }
+}
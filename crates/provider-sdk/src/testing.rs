@@ -0,0 +1,563 @@
+//! Test doubles standing in for the components linked to a provider, available with the
+//! `testing` feature.
+//!
+//! Providers invoke linked components through a [`WrpcClient`](crate::provider::WrpcClient),
+//! usually via generated bindings which accept any [`wrpc_transport::Invoke`] implementation.
+//! The clients in this module implement the same interface, so provider integration tests can run
+//! deterministically without deploying the actual components:
+//!
+//! - [`RecordingClient`] wraps a real client, forwards every invocation to it and records the
+//!   responses into a [`Cassette`], which can be saved to a file.
+//! - [`ReplayClient`] answers invocations from a [`Cassette`], optionally injecting latency and
+//!   errors to exercise the provider's timeout and failure handling.
+//!
+//! ```rust,no_run
+//! use core::time::Duration;
+//!
+//! use wasmcloud_provider_sdk::testing::{Cassette, RecordingClient, ReplayClient};
+//! use wasmcloud_provider_sdk::get_connection;
+//!
+//! async fn record() -> anyhow::Result<()> {
+//!     let client = RecordingClient::new(get_connection().get_wrpc_client("greeter").await?);
+//!     // ... exercise the provider with `client` ...
+//!     client.cassette().save("tests/fixtures/greeter.json")
+//! }
+//!
+//! async fn replay() -> anyhow::Result<()> {
+//!     let client = ReplayClient::new(Cassette::load("tests/fixtures/greeter.json")?)
+//!         .with_latency(Duration::from_millis(50))
+//!         .with_function_error("wasmcloud:example/greeter", "greet", "component trapped");
+//!     // ... exercise the provider with `client` ...
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Only invocations of functions without asynchronous parameters or results, such as streams and
+//! futures, can be recorded and replayed.
+
+use core::time::Duration;
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Context as _};
+use async_nats::HeaderMap;
+use bytes::Bytes;
+use futures::StreamExt as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _, DuplexStream, ReadHalf, WriteHalf};
+use tracing::{debug, warn};
+use wrpc_transport::frame::{Accept, Incoming, Outgoing, Server};
+use wrpc_transport::{Invoke, Serve as _};
+
+/// Size of the in-memory buffer between a test double and the client invoking it
+const CONNECTION_BUFFER_SIZE: usize = 8192;
+
+/// Invocations of linked components, recorded by a [`RecordingClient`]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Cassette {
+    /// Recorded invocations in the order they were made
+    pub invocations: Vec<RecordedInvocation>,
+}
+
+impl Cassette {
+    /// Load a cassette from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let cassette = std::fs::read(path)
+            .with_context(|| format!("failed to read cassette `{}`", path.display()))?;
+        serde_json::from_slice(&cassette)
+            .with_context(|| format!("failed to parse cassette `{}`", path.display()))
+    }
+
+    /// Save the cassette to a JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let cassette = serde_json::to_vec_pretty(self).context("failed to serialize cassette")?;
+        std::fs::write(path, cassette)
+            .with_context(|| format!("failed to write cassette `{}`", path.display()))
+    }
+}
+
+/// A single invocation of a function of a linked component
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordedInvocation {
+    /// Instance (interface) of the invoked function, e.g. `wasi:http/incoming-handler`
+    pub instance: String,
+    /// Name of the invoked function
+    pub func: String,
+    /// Encoded parameters of the invocation
+    #[serde(with = "base64_bytes")]
+    pub params: Bytes,
+    /// Response to the invocation
+    pub outcome: RecordedOutcome,
+}
+
+/// Response of a linked component to a [`RecordedInvocation`]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    /// The invocation returned the encoded results
+    Results(#[serde(with = "base64_bytes")] Bytes),
+    /// The invocation failed with the error message
+    Error(String),
+}
+
+/// Instance, function and parameters of an invocation
+type InvocationKey = (String, String, Bytes);
+
+/// Latency and errors injected into the invocations of a function by a [`ReplayClient`]
+#[derive(Clone, Debug, Default)]
+struct Fault {
+    latency: Option<Duration>,
+    error: Option<String>,
+}
+
+/// Client answering invocations from a [`Cassette`]
+///
+/// Identical invocations, i.e. of the same function with the same parameters, are answered with
+/// the recorded responses in order, repeating the last one once all were replayed.
+#[derive(Clone)]
+pub struct ReplayClient {
+    responder: Arc<Responder>,
+    invocations: Arc<[RecordedInvocation]>,
+    /// Number of times each invocation was replayed, keyed by instance, function and parameters
+    replayed: Arc<Mutex<HashMap<InvocationKey, usize>>>,
+    latency: Duration,
+    faults: HashMap<(String, String), Fault>,
+}
+
+impl ReplayClient {
+    /// Create a client replaying the invocations of `cassette`
+    #[must_use]
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            responder: Arc::default(),
+            invocations: cassette.invocations.into(),
+            replayed: Arc::default(),
+            latency: Duration::ZERO,
+            faults: HashMap::default(),
+        }
+    }
+
+    /// Delay every invocation by `latency`
+    #[must_use]
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Delay invocations of function `func` of `instance` by `latency`, instead of the latency
+    /// configured for all invocations
+    #[must_use]
+    pub fn with_function_latency(
+        mut self,
+        instance: impl Into<String>,
+        func: impl Into<String>,
+        latency: Duration,
+    ) -> Self {
+        self.faults
+            .entry((instance.into(), func.into()))
+            .or_default()
+            .latency = Some(latency);
+        self
+    }
+
+    /// Fail invocations of function `func` of `instance` with `error`, regardless of the
+    /// recorded responses
+    #[must_use]
+    pub fn with_function_error(
+        mut self,
+        instance: impl Into<String>,
+        func: impl Into<String>,
+        error: impl Into<String>,
+    ) -> Self {
+        self.faults
+            .entry((instance.into(), func.into()))
+            .or_default()
+            .error = Some(error.into());
+        self
+    }
+
+    /// Find the response to replay for an invocation
+    fn replay(
+        &self,
+        instance: &str,
+        func: &str,
+        params: &Bytes,
+    ) -> anyhow::Result<RecordedOutcome> {
+        let recorded: Vec<_> = self
+            .invocations
+            .iter()
+            .filter(|invocation| {
+                invocation.instance == instance
+                    && invocation.func == func
+                    && invocation.params == params
+            })
+            .collect();
+        let Some(last) = recorded.last() else {
+            bail!("no invocation of `{instance}#{func}` with matching parameters was recorded");
+        };
+        let mut replayed = self
+            .replayed
+            .lock()
+            .map_err(|_| anyhow!("replayed invocations lock poisoned"))?;
+        let count = replayed
+            .entry((instance.to_string(), func.to_string(), params.clone()))
+            .or_default();
+        let invocation = recorded.get(*count).unwrap_or(last);
+        *count = count.saturating_add(1);
+        Ok(invocation.outcome.clone())
+    }
+}
+
+impl Invoke for ReplayClient {
+    type Context = Option<HeaderMap>;
+    type Outgoing = Outgoing;
+    type Incoming = Incoming;
+
+    async fn invoke<P>(
+        &self,
+        _cx: Self::Context,
+        instance: &str,
+        func: &str,
+        params: Bytes,
+        paths: impl AsRef<[P]> + Send,
+    ) -> anyhow::Result<(Self::Outgoing, Self::Incoming)>
+    where
+        P: AsRef<[Option<usize>]> + Send + Sync,
+    {
+        let fault = self
+            .faults
+            .get(&(instance.to_string(), func.to_string()))
+            .cloned()
+            .unwrap_or_default();
+        let latency = fault.latency.unwrap_or(self.latency);
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        if let Some(error) = fault.error {
+            bail!(error);
+        }
+        match self.replay(instance, func, &params)? {
+            RecordedOutcome::Results(results) => {
+                self.responder
+                    .respond(instance, func, params, paths, results)
+                    .await
+            }
+            RecordedOutcome::Error(error) => bail!(error),
+        }
+    }
+}
+
+/// Client forwarding invocations to another client and recording them into a [`Cassette`]
+#[derive(Clone)]
+pub struct RecordingClient<T> {
+    inner: T,
+    responder: Arc<Responder>,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl<T> RecordingClient<T> {
+    /// Create a client recording the invocations forwarded to `inner`
+    #[must_use]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            responder: Arc::default(),
+            cassette: Arc::default(),
+        }
+    }
+
+    /// Get the invocations recorded so far
+    #[must_use]
+    pub fn cassette(&self) -> Cassette {
+        self.cassette
+            .lock()
+            .map(|cassette| cassette.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl<T> Invoke for RecordingClient<T>
+where
+    T: Invoke<Context = Option<HeaderMap>>,
+{
+    type Context = Option<HeaderMap>;
+    type Outgoing = Outgoing;
+    type Incoming = Incoming;
+
+    async fn invoke<P>(
+        &self,
+        cx: Self::Context,
+        instance: &str,
+        func: &str,
+        params: Bytes,
+        paths: impl AsRef<[P]> + Send,
+    ) -> anyhow::Result<(Self::Outgoing, Self::Incoming)>
+    where
+        P: AsRef<[Option<usize>]> + Send + Sync,
+    {
+        let outcome = match self
+            .inner
+            .invoke(cx, instance, func, params.clone(), paths.as_ref())
+            .await
+        {
+            // Parameters are all sent with the invocation, so the parameter channel can be
+            // shut down right away and the results read until the component closes the stream
+            Ok((mut outgoing, mut incoming)) => {
+                let mut results = Vec::new();
+                match outgoing.shutdown().await {
+                    Ok(()) => match incoming.read_to_end(&mut results).await {
+                        Ok(_) => RecordedOutcome::Results(results.into()),
+                        Err(err) => {
+                            RecordedOutcome::Error(format!("failed to read results: {err}"))
+                        }
+                    },
+                    Err(err) => RecordedOutcome::Error(format!(
+                        "failed to shutdown synchronous parameter channel: {err}"
+                    )),
+                }
+            }
+            Err(err) => RecordedOutcome::Error(format!("{err:#}")),
+        };
+        debug!(instance, func, ?outcome, "recorded invocation");
+        if let Ok(mut cassette) = self.cassette.lock() {
+            cassette.invocations.push(RecordedInvocation {
+                instance: instance.to_string(),
+                func: func.to_string(),
+                params: params.clone(),
+                outcome: outcome.clone(),
+            });
+        }
+        match outcome {
+            RecordedOutcome::Results(results) => {
+                self.responder
+                    .respond(instance, func, params, paths, results)
+                    .await
+            }
+            RecordedOutcome::Error(error) => bail!(error),
+        }
+    }
+}
+
+/// In-memory wRPC server answering invocations with given results
+#[derive(Default)]
+struct Responder {
+    server: Server<Bytes, ReadHalf<DuplexStream>, WriteHalf<DuplexStream>>,
+    /// Functions for which invocations are being served, keyed by instance and function
+    served: tokio::sync::Mutex<HashSet<(String, String)>>,
+}
+
+impl Responder {
+    /// Invoke `func` of `instance` over an in-memory connection, which is answered with `results`
+    async fn respond<P>(
+        &self,
+        instance: &str,
+        func: &str,
+        params: Bytes,
+        paths: impl AsRef<[P]> + Send,
+        results: Bytes,
+    ) -> anyhow::Result<(Outgoing, Incoming)>
+    where
+        P: AsRef<[Option<usize>]> + Send + Sync,
+    {
+        self.serve(instance, func).await?;
+        let (client, server) = tokio::io::duplex(CONNECTION_BUFFER_SIZE);
+        let (client_rx, client_tx) = tokio::io::split(client);
+        let (server_rx, server_tx) = tokio::io::split(server);
+        let connection = Connection(Mutex::new(Some((results, server_tx, server_rx))));
+        let (accepted, invoked) = tokio::join!(
+            self.server.accept(connection),
+            wrpc_transport::frame::invoke(client_tx, client_rx, instance, func, params, paths),
+        );
+        accepted.map_err(|err| anyhow!("failed to accept invocation: {err}"))?;
+        invoked
+    }
+
+    /// Start answering invocations of `func` of `instance`, unless already doing so
+    async fn serve(&self, instance: &str, func: &str) -> anyhow::Result<()> {
+        let mut served = self.served.lock().await;
+        if !served.insert((instance.to_string(), func.to_string())) {
+            return Ok(());
+        }
+        let invocations = self
+            .server
+            .serve(instance, func, Vec::<Box<[Option<usize>]>>::new())
+            .await
+            .with_context(|| format!("failed to serve `{instance}#{func}`"))?;
+        tokio::spawn(async move {
+            let mut invocations = Box::pin(invocations);
+            while let Some(invocation) = invocations.next().await {
+                let (results, mut tx, rx) = match invocation {
+                    Ok(invocation) => invocation,
+                    Err(err) => {
+                        warn!(?err, "failed to accept invocation");
+                        continue;
+                    }
+                };
+                tokio::spawn(async move {
+                    if let Err(err) = tx.write_all(&results).await {
+                        warn!(?err, "failed to write results");
+                    } else if let Err(err) = tx.shutdown().await {
+                        warn!(?err, "failed to shutdown results");
+                    }
+                    // Keep receiving the parameters until the results were sent
+                    drop(rx);
+                });
+            }
+        });
+        Ok(())
+    }
+}
+
+/// A single in-memory connection, accepted once, whose context is the results to answer with
+struct Connection(Mutex<Option<AcceptedConnection>>);
+
+/// Results, outgoing and incoming streams of an accepted [`Connection`]
+type AcceptedConnection = (Bytes, WriteHalf<DuplexStream>, ReadHalf<DuplexStream>);
+
+impl Accept for Connection {
+    type Context = Bytes;
+    type Outgoing = WriteHalf<DuplexStream>;
+    type Incoming = ReadHalf<DuplexStream>;
+
+    async fn accept(&self) -> std::io::Result<(Self::Context, Self::Outgoing, Self::Incoming)> {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|mut connection| connection.take())
+            .ok_or_else(|| std::io::Error::other("connection was already accepted"))
+    }
+}
+
+/// (De)serialization of bytes as base64 strings
+mod base64_bytes {
+    use base64::Engine as _;
+
+    use super::{Bytes, Deserialize as _, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Bytes, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map(Bytes::from)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GREETER: &str = "wasmcloud:example/greeter";
+
+    /// Invoke `func` of the greeter and read all of its results
+    async fn invoke<T>(client: &T, func: &str, params: &'static [u8]) -> anyhow::Result<Bytes>
+    where
+        T: Invoke<Context = Option<HeaderMap>>,
+    {
+        let (mut outgoing, mut incoming) = client
+            .invoke(
+                None,
+                GREETER,
+                func,
+                Bytes::from_static(params),
+                Vec::<Box<[Option<usize>]>>::new(),
+            )
+            .await?;
+        outgoing.shutdown().await?;
+        let mut results = Vec::new();
+        incoming.read_to_end(&mut results).await?;
+        Ok(results.into())
+    }
+
+    fn invocation(
+        func: &str,
+        params: &'static [u8],
+        outcome: RecordedOutcome,
+    ) -> RecordedInvocation {
+        RecordedInvocation {
+            instance: GREETER.to_string(),
+            func: func.to_string(),
+            params: Bytes::from_static(params),
+            outcome,
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_invocations_are_replayed() -> anyhow::Result<()> {
+        // The linked component is itself a test double answering with known responses
+        let component = ReplayClient::new(Cassette {
+            invocations: vec![
+                invocation(
+                    "greet",
+                    b"\x05alice",
+                    RecordedOutcome::Results(Bytes::from_static(b"\x0bhello alice")),
+                ),
+                invocation(
+                    "greet",
+                    b"\x05alice",
+                    RecordedOutcome::Results(Bytes::from_static(b"\x09hi alice")),
+                ),
+                invocation("leave", b"", RecordedOutcome::Error("trapped".to_string())),
+            ],
+        });
+        let recorder = RecordingClient::new(component);
+        assert_eq!(
+            invoke(&recorder, "greet", b"\x05alice").await?,
+            &b"\x0bhello alice"[..]
+        );
+        assert_eq!(
+            invoke(&recorder, "greet", b"\x05alice").await?,
+            &b"\x09hi alice"[..]
+        );
+        let err = invoke(&recorder, "leave", b"")
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("trapped"));
+
+        let tmp = tempfile::tempdir()?;
+        let path = tmp.path().join("greeter.json");
+        recorder.cassette().save(&path)?;
+        let cassette = Cassette::load(&path)?;
+        assert_eq!(cassette.invocations.len(), 3);
+
+        let replay = ReplayClient::new(cassette.clone());
+        assert_eq!(
+            invoke(&replay, "greet", b"\x05alice").await?,
+            &b"\x0bhello alice"[..]
+        );
+        assert_eq!(
+            invoke(&replay, "greet", b"\x05alice").await?,
+            &b"\x09hi alice"[..]
+        );
+        // The last response is repeated once all were replayed
+        assert_eq!(
+            invoke(&replay, "greet", b"\x05alice").await?,
+            &b"\x09hi alice"[..]
+        );
+        let err = invoke(&replay, "leave", b"")
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("trapped"));
+        let err = invoke(&replay, "greet", b"\x03bob")
+            .await
+            .expect_err("unrecorded invocations should fail");
+        assert!(err.to_string().contains("no invocation"));
+
+        // Injected errors take precedence over the recorded responses
+        let faulty =
+            ReplayClient::new(cassette).with_function_error(GREETER, "greet", "overloaded");
+        let err = invoke(&faulty, "greet", b"\x05alice")
+            .await
+            .expect_err("should fail");
+        assert!(err.to_string().contains("overloaded"));
+        Ok(())
+    }
+}
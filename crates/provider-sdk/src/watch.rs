@@ -0,0 +1,429 @@
+//! Plumbing for providers that push change notifications to linked components, for example when
+//! implementing `wasi:keyvalue/watch`-style interfaces.
+//!
+//! A [`WatchRegistry`] keeps track of which components are interested in which keys, fans events
+//! out to every matching component and retains events that could not be delivered so they can
+//! be replayed once the component becomes available again (e.g. after a restart).
+
+use core::future::Future;
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, warn};
+
+/// Default number of undelivered events retained per component
+pub const DEFAULT_MAX_PENDING_EVENTS: usize = 1024;
+
+/// Selects which keys a component is watching
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WatchFilter {
+    /// Watch a single, exact key
+    Key(String),
+    /// Watch all keys that start with the given prefix
+    Prefix(String),
+    /// Watch every key
+    All,
+}
+
+impl WatchFilter {
+    /// Returns true if the given key is selected by this filter
+    #[must_use]
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            WatchFilter::Key(k) => k == key,
+            WatchFilter::Prefix(prefix) => key.starts_with(prefix.as_str()),
+            WatchFilter::All => true,
+        }
+    }
+}
+
+/// An event that is waiting to be delivered to a component
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingEvent<E> {
+    /// Sequence number assigned by the registry when the event was fanned out
+    pub sequence: u64,
+    /// Key the event relates to
+    pub key: String,
+    /// The event itself
+    pub event: E,
+}
+
+#[derive(Debug)]
+struct Subscription<E> {
+    filters: Vec<WatchFilter>,
+    pending: VecDeque<PendingEvent<E>>,
+    /// Held while delivering pending events, so that concurrent replays deliver them one at a
+    /// time and in order
+    delivery: Arc<Mutex<()>>,
+}
+
+impl<E> Default for Subscription<E> {
+    fn default() -> Self {
+        Self {
+            filters: Vec::default(),
+            pending: VecDeque::default(),
+            delivery: Arc::default(),
+        }
+    }
+}
+
+impl<E> Subscription<E> {
+    fn matches(&self, key: &str) -> bool {
+        self.filters.iter().any(|f| f.matches(key))
+    }
+}
+
+#[derive(Debug)]
+struct Inner<E> {
+    subscriptions: HashMap<String, Subscription<E>>,
+    sequence: u64,
+}
+
+impl<E> Default for Inner<E> {
+    fn default() -> Self {
+        Self {
+            subscriptions: HashMap::default(),
+            sequence: 0,
+        }
+    }
+}
+
+/// Registry of component watch subscriptions, indexed by component ID
+///
+/// The registry is cheap to clone and can be shared between the provider's link handlers and
+/// whatever task observes changes in the backing store.
+#[derive(Debug)]
+pub struct WatchRegistry<E> {
+    inner: Arc<RwLock<Inner<E>>>,
+    max_pending: usize,
+}
+
+impl<E> Clone for WatchRegistry<E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            max_pending: self.max_pending,
+        }
+    }
+}
+
+impl<E> Default for WatchRegistry<E> {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PENDING_EVENTS)
+    }
+}
+
+impl<E> WatchRegistry<E> {
+    /// Create a new registry that retains at most `max_pending` undelivered events per component.
+    /// When the limit is reached, the oldest pending event is dropped.
+    #[must_use]
+    pub fn new(max_pending: usize) -> Self {
+        Self {
+            inner: Arc::default(),
+            max_pending,
+        }
+    }
+}
+
+impl<E: Clone> WatchRegistry<E> {
+    /// Register interest of a component in the keys selected by `filter`
+    pub async fn subscribe(&self, component_id: impl Into<String>, filter: WatchFilter) {
+        let mut inner = self.inner.write().await;
+        let sub = inner.subscriptions.entry(component_id.into()).or_default();
+        if !sub.filters.contains(&filter) {
+            sub.filters.push(filter);
+        }
+    }
+
+    /// Remove a single filter for a component. Pending events are kept until the component has
+    /// no filters left.
+    pub async fn unsubscribe(&self, component_id: &str, filter: &WatchFilter) {
+        let mut inner = self.inner.write().await;
+        if let Some(sub) = inner.subscriptions.get_mut(component_id) {
+            sub.filters.retain(|f| f != filter);
+            if sub.filters.is_empty() {
+                inner.subscriptions.remove(component_id);
+            }
+        }
+    }
+
+    /// Remove all subscriptions and pending events for a component, usually called when the
+    /// link to the component is deleted
+    pub async fn remove_component(&self, component_id: &str) {
+        self.inner.write().await.subscriptions.remove(component_id);
+    }
+
+    /// Returns the IDs of all components watching the given key
+    pub async fn watchers(&self, key: &str) -> Vec<String> {
+        self.inner
+            .read()
+            .await
+            .subscriptions
+            .iter()
+            .filter(|(_, sub)| sub.matches(key))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    /// Returns the number of events waiting to be delivered to a component
+    pub async fn pending_count(&self, component_id: &str) -> usize {
+        self.inner
+            .read()
+            .await
+            .subscriptions
+            .get(component_id)
+            .map_or(0, |sub| sub.pending.len())
+    }
+
+    /// Deliver an event for `key` to every component watching it.
+    ///
+    /// Events are delivered in order: if a component still has undelivered events, the new
+    /// event is queued behind them and delivery is retried from the oldest one. Events that fail
+    /// to deliver are retained (up to the configured limit) and can be retried with
+    /// [`WatchRegistry::replay`].
+    ///
+    /// Returns the number of components the event was successfully delivered to.
+    pub async fn fan_out<F, Fut>(&self, key: &str, event: E, deliver: F) -> usize
+    where
+        F: Fn(String, PendingEvent<E>) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let watchers = {
+            let mut inner = self.inner.write().await;
+            inner.sequence += 1;
+            let sequence = inner.sequence;
+            let mut watchers = Vec::new();
+            for (id, sub) in &mut inner.subscriptions {
+                if !sub.matches(key) {
+                    continue;
+                }
+                if sub.pending.len() >= self.max_pending {
+                    if let Some(dropped) = sub.pending.pop_front() {
+                        warn!(
+                            component_id = id,
+                            sequence = dropped.sequence,
+                            "dropping oldest undelivered watch event"
+                        );
+                    }
+                }
+                sub.pending.push_back(PendingEvent {
+                    sequence,
+                    key: key.to_string(),
+                    event: event.clone(),
+                });
+                watchers.push(id.clone());
+            }
+            watchers
+        };
+        let mut delivered = 0;
+        for id in watchers {
+            if self.replay(&id, &deliver).await {
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+
+    /// Attempt to deliver all pending events to a component, oldest first. Delivery stops at the
+    /// first failure so that ordering is preserved.
+    ///
+    /// This should be called when a component becomes available again, for instance when a link
+    /// to a restarted component is re-established. Concurrent replays for the same component
+    /// deliver each event once: an event is removed from the pending events before it is
+    /// delivered and is only put back if delivery fails. An event whose delivery is cancelled by
+    /// dropping the returned future is not retried.
+    ///
+    /// Returns true if no events remain pending for the component.
+    pub async fn replay<F, Fut>(&self, component_id: &str, deliver: F) -> bool
+    where
+        F: Fn(String, PendingEvent<E>) -> Fut,
+        Fut: Future<Output = anyhow::Result<()>>,
+    {
+        let delivery = match self.inner.read().await.subscriptions.get(component_id) {
+            Some(sub) => Arc::clone(&sub.delivery),
+            None => return true,
+        };
+        let _delivery = delivery.lock().await;
+        loop {
+            let next = {
+                let mut inner = self.inner.write().await;
+                match inner.subscriptions.get_mut(component_id) {
+                    // The component was removed and subscribed again while waiting for delivery
+                    Some(sub) if !Arc::ptr_eq(&sub.delivery, &delivery) => return false,
+                    Some(sub) => sub.pending.pop_front(),
+                    None => return true,
+                }
+            };
+            let Some(next) = next else {
+                return true;
+            };
+            let retry = next.clone();
+            if let Err(err) = deliver(component_id.to_string(), next).await {
+                debug!(
+                    component_id,
+                    sequence = retry.sequence,
+                    ?err,
+                    "failed to deliver watch event, will retry on replay"
+                );
+                let mut inner = self.inner.write().await;
+                if let Some(sub) = inner
+                    .subscriptions
+                    .get_mut(component_id)
+                    .filter(|sub| Arc::ptr_eq(&sub.delivery, &delivery))
+                {
+                    if sub.pending.len() < self.max_pending {
+                        sub.pending.push_front(retry);
+                    } else {
+                        warn!(
+                            component_id,
+                            sequence = retry.sequence,
+                            "dropping oldest undelivered watch event"
+                        );
+                    }
+                }
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use anyhow::bail;
+
+    use super::*;
+
+    /// Deliveries recorded by [`deliver_to`] as `(component ID, sequence)`
+    type Deliveries = Arc<StdMutex<Vec<(String, u64)>>>;
+
+    fn deliver_to(
+        deliveries: &Deliveries,
+        fail_at: Option<u64>,
+    ) -> impl Fn(
+        String,
+        PendingEvent<&'static str>,
+    ) -> futures::future::BoxFuture<'static, anyhow::Result<()>> {
+        let deliveries = Arc::clone(deliveries);
+        move |component_id, event| {
+            let deliveries = Arc::clone(&deliveries);
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                if fail_at == Some(event.sequence) {
+                    bail!("component unavailable");
+                }
+                deliveries
+                    .lock()
+                    .unwrap()
+                    .push((component_id, event.sequence));
+                Ok(())
+            })
+        }
+    }
+
+    #[test]
+    fn filters_match_keys() {
+        assert!(WatchFilter::Key("a".into()).matches("a"));
+        assert!(!WatchFilter::Key("a".into()).matches("ab"));
+        assert!(WatchFilter::Prefix("a".into()).matches("ab"));
+        assert!(!WatchFilter::Prefix("b".into()).matches("ab"));
+        assert!(WatchFilter::All.matches(""));
+    }
+
+    #[tokio::test]
+    async fn failed_events_are_retained_in_order() {
+        let registry = WatchRegistry::default();
+        registry
+            .subscribe("a", WatchFilter::Prefix("user/".into()))
+            .await;
+        registry
+            .subscribe("b", WatchFilter::Key("other".into()))
+            .await;
+        let deliveries = Deliveries::default();
+
+        assert_eq!(
+            registry
+                .fan_out("user/1", "put", deliver_to(&deliveries, Some(1)))
+                .await,
+            0
+        );
+        assert_eq!(
+            registry
+                .fan_out("user/2", "put", deliver_to(&deliveries, Some(1)))
+                .await,
+            0
+        );
+        assert_eq!(registry.pending_count("a").await, 2);
+        assert_eq!(registry.pending_count("b").await, 0);
+        assert!(deliveries.lock().unwrap().is_empty());
+
+        assert!(registry.replay("a", deliver_to(&deliveries, None)).await);
+        assert_eq!(registry.pending_count("a").await, 0);
+        assert_eq!(
+            *deliveries.lock().unwrap(),
+            [("a".to_string(), 1), ("a".to_string(), 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_replays_deliver_each_event_once() {
+        let registry = WatchRegistry::default();
+        registry.subscribe("a", WatchFilter::All).await;
+        let deliveries = Deliveries::default();
+        for key in ["1", "2", "3"] {
+            registry
+                .fan_out(key, "put", deliver_to(&deliveries, Some(1)))
+                .await;
+        }
+        assert_eq!(registry.pending_count("a").await, 3);
+
+        let (first, second) = tokio::join!(
+            registry.replay("a", deliver_to(&deliveries, None)),
+            registry.replay("a", deliver_to(&deliveries, None)),
+        );
+        assert!(first && second);
+        assert_eq!(
+            *deliveries.lock().unwrap(),
+            [
+                ("a".to_string(), 1),
+                ("a".to_string(), 2),
+                ("a".to_string(), 3)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn oldest_events_are_dropped_at_the_limit() {
+        let registry = WatchRegistry::new(2);
+        registry.subscribe("a", WatchFilter::All).await;
+        let deliveries = Deliveries::default();
+        for key in ["1", "2"] {
+            registry
+                .fan_out(key, "put", deliver_to(&deliveries, Some(1)))
+                .await;
+        }
+        assert_eq!(registry.pending_count("a").await, 2);
+
+        // Dropping the oldest event unblocks delivery of the newer ones
+        registry
+            .fan_out("3", "put", deliver_to(&deliveries, Some(1)))
+            .await;
+        assert_eq!(registry.pending_count("a").await, 0);
+        assert_eq!(
+            *deliveries.lock().unwrap(),
+            [("a".to_string(), 2), ("a".to_string(), 3)]
+        );
+
+        registry
+            .fan_out("4", "put", deliver_to(&deliveries, Some(4)))
+            .await;
+        assert_eq!(registry.pending_count("a").await, 1);
+        registry.remove_component("a").await;
+        assert_eq!(registry.pending_count("a").await, 0);
+        assert!(registry.replay("a", deliver_to(&deliveries, None)).await);
+        assert_eq!(deliveries.lock().unwrap().len(), 2);
+    }
+}
@@ -30,6 +30,8 @@ use wrpc_runtime_wasmtime::{
 
 use crate::capability::{self, wrpc};
 use crate::experimental::Features;
+use crate::memory::{MemoryTracker, MemoryUsage};
+use crate::scheduling::{self, CpuTime, PriorityClass};
 use crate::Runtime;
 
 pub use bus::{Bus, Error};
@@ -249,6 +251,9 @@ where
     instance_pre: wasmtime::component::InstancePre<Ctx<H>>,
     host_resources: Arc<HashMap<Box<str>, HashMap<Box<str>, (ResourceType, ResourceType)>>>,
     max_execution_time: Duration,
+    priority: PriorityClass,
+    cpu_time: CpuTime,
+    memory_usage: MemoryUsage,
     experimental_features: Features,
     max_memory_limit: usize,
 }
@@ -266,6 +271,7 @@ where
     #[allow(unused)]
     host_resources: Arc<HashMap<Box<str>, HashMap<Box<str>, (ResourceType, ResourceType)>>>,
     max_execution_time: Duration,
+    cpu_time: CpuTime,
 }
 
 impl<C> CustomCtxComponent<C>
@@ -324,16 +330,28 @@ where
             instance_pre,
             host_resources,
             max_execution_time: rt.max_execution_time,
+            cpu_time: CpuTime::default(),
         })
     }
 
     /// Creates a new component store for instantiation
     pub fn new_store(&self, ctx: C) -> wasmtime::Store<C> {
         let mut store = wasmtime::Store::new(&self.engine, ctx);
-        store.set_epoch_deadline(self.max_execution_time.as_secs());
+        scheduling::configure_store(
+            &mut store,
+            self.max_execution_time,
+            PriorityClass::default(),
+            &self.cpu_time,
+        );
         store
     }
 
+    /// Returns the time spent executing guest code of this component across all of its stores
+    #[must_use]
+    pub fn cpu_time(&self) -> &CpuTime {
+        &self.cpu_time
+    }
+
     /// Returns the precompiled component instance
     #[must_use]
     pub fn instance_pre(&self) -> &wasmtime::component::InstancePre<C> {
@@ -350,6 +368,7 @@ where
             .field("claims", &self.claims)
             .field("runtime", &"wasmtime")
             .field("max_execution_time", &self.max_execution_time)
+            .field("priority", &self.priority)
             .finish_non_exhaustive()
     }
 }
@@ -358,6 +377,9 @@ fn new_store<H: Handler>(
     engine: &wasmtime::Engine,
     handler: H,
     max_execution_time: Duration,
+    priority: PriorityClass,
+    cpu_time: &CpuTime,
+    memory_usage: &MemoryUsage,
 ) -> wasmtime::Store<Ctx<H>> {
     let table = ResourceTable::new();
     let wasi = WasiCtxBuilder::new()
@@ -375,9 +397,11 @@ fn new_store<H: Handler>(
             shared_resources: SharedResourceTable::default(),
             timeout: max_execution_time,
             parent_context: None,
+            memory: MemoryTracker::new(memory_usage),
         },
     );
-    store.set_epoch_deadline(max_execution_time.as_secs());
+    store.limiter(|ctx| &mut ctx.memory);
+    scheduling::configure_store(&mut store, max_execution_time, priority, cpu_time);
     store
 }
 
@@ -479,6 +503,9 @@ where
             instance_pre,
             host_resources,
             max_execution_time: rt.max_execution_time,
+            priority: PriorityClass::default(),
+            cpu_time: CpuTime::default(),
+            memory_usage: MemoryUsage::default(),
             experimental_features: rt.experimental_features,
             max_memory_limit: rt.max_linear_memory,
         })
@@ -788,6 +815,9 @@ where
             instance_pre,
             host_resources,
             max_execution_time: rt.max_execution_time,
+            priority: PriorityClass::default(),
+            cpu_time: CpuTime::default(),
+            memory_usage: MemoryUsage::default(),
             experimental_features: rt.experimental_features,
             max_memory_limit,
         })
@@ -801,6 +831,25 @@ where
         self
     }
 
+    /// Sets the scheduling priority of this component, see [`scheduling`] for details.
+    #[instrument(level = "trace", skip_all)]
+    pub fn set_priority(&mut self, priority: PriorityClass) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Returns the time spent executing guest code of this component across all of its instances
+    #[must_use]
+    pub fn cpu_time(&self) -> &CpuTime {
+        &self.cpu_time
+    }
+
+    /// Returns the linear memory allocated by all instances of this component
+    #[must_use]
+    pub fn memory_usage(&self) -> &MemoryUsage {
+        &self.memory_usage
+    }
+
     /// Reads the WebAssembly binary asynchronously and calls [Component::new].
     ///
     /// # Errors
@@ -844,6 +893,9 @@ where
             pre: self.instance_pre.clone(),
             handler,
             max_execution_time: self.max_execution_time,
+            priority: self.priority,
+            cpu_time: self.cpu_time.clone(),
+            memory_usage: self.memory_usage.clone(),
             events,
             experimental_features: self.experimental_features,
             max_memory_limit: self.max_memory_limit,
@@ -868,6 +920,7 @@ where
         S::Context: Deref<Target = tracing::Span>,
     {
         let max_execution_time = self.max_execution_time;
+        let priority = self.priority;
         let mut invocations = vec![];
         let instance = self.instantiate(handler.clone(), events.clone());
         for (name, ty) in self
@@ -919,13 +972,21 @@ where
                     let engine = self.engine.clone();
                     let handler = handler.clone();
                     let pre = self.instance_pre.clone();
+                    let cpu_time = self.cpu_time.clone();
+                    let memory_usage = self.memory_usage.clone();
                     debug!(?name, "serving root function");
                     let func = srv
                         .serve_function(
                             move || {
                                 let span = info_span!("call_instance_function");
-                                let mut store =
-                                    new_store(&engine, handler.clone(), max_execution_time);
+                                let mut store = new_store(
+                                    &engine,
+                                    handler.clone(),
+                                    max_execution_time,
+                                    priority,
+                                    &cpu_time,
+                                    &memory_usage,
+                                );
                                 store.data_mut().parent_context = Some(span.context());
                                 store
                             },
@@ -980,6 +1041,8 @@ where
                                 let engine = self.engine.clone();
                                 let handler = handler.clone();
                                 let pre = self.instance_pre.clone();
+                                let cpu_time = self.cpu_time.clone();
+                                let memory_usage = self.memory_usage.clone();
                                 debug!(?instance_name, ?name, "serving instance function");
                                 let func = srv
                                     .serve_function(
@@ -989,6 +1052,9 @@ where
                                                 &engine,
                                                 handler.clone(),
                                                 max_execution_time,
+                                                priority,
+                                                &cpu_time,
+                                                &memory_usage,
                                             );
                                             store.data_mut().parent_context = Some(span.context());
                                             store
@@ -1082,6 +1148,9 @@ where
     pre: wasmtime::component::InstancePre<Ctx<H>>,
     handler: H,
     max_execution_time: Duration,
+    priority: PriorityClass,
+    cpu_time: CpuTime,
+    memory_usage: MemoryUsage,
     events: mpsc::Sender<WrpcServeEvent<C>>,
     experimental_features: Features,
     max_memory_limit: usize,
@@ -1097,6 +1166,9 @@ where
             pre: self.pre.clone(),
             handler: self.handler.clone(),
             max_execution_time: self.max_execution_time,
+            priority: self.priority,
+            cpu_time: self.cpu_time.clone(),
+            memory_usage: self.memory_usage.clone(),
             events: self.events.clone(),
             experimental_features: self.experimental_features,
             max_memory_limit: self.max_memory_limit,
@@ -1133,6 +1205,7 @@ where
     shared_resources: SharedResourceTable,
     timeout: Duration,
     parent_context: Option<opentelemetry::Context>,
+    memory: MemoryTracker,
 }
 
 impl<H: MinimalHandler> IoView for Ctx<H> {
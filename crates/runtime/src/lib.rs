@@ -18,6 +18,10 @@ pub mod experimental;
 /// Shared wasmCloud runtime engine
 pub mod runtime;
 
+pub mod memory;
+
+pub mod scheduling;
+
 /// wasmCloud I/O functionality
 pub mod io;
 
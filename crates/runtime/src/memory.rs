@@ -0,0 +1,117 @@
+//! Accounting of the linear memory allocated by components.
+//!
+//! Every store created for a component is given a [`MemoryTracker`] as its resource limiter. The
+//! tracker never denies a request, the configured memory limits are still enforced by wasmtime,
+//! but records every allocation in the [`MemoryUsage`] shared by all instances of the component.
+//! Memory held by an instance is released from the usage again once its store is dropped, so the
+//! resident memory and instance count of a component are always up to date.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use std::sync::Arc;
+
+use wasmtime::ResourceLimiter;
+
+#[derive(Debug, Default)]
+struct MemoryUsageInner {
+    allocated: AtomicU64,
+    peak: AtomicU64,
+    resident: AtomicU64,
+    instances: AtomicU64,
+}
+
+/// Linear memory allocated by the instances of a component
+#[derive(Clone, Debug, Default)]
+pub struct MemoryUsage(Arc<MemoryUsageInner>);
+
+impl MemoryUsage {
+    /// Total bytes of linear memory allocated or grown by all instances so far
+    #[must_use]
+    pub fn allocated_bytes(&self) -> u64 {
+        self.0.allocated.load(Ordering::Relaxed)
+    }
+
+    /// Largest linear memory of a single instance, in bytes, since the peak was last reset
+    #[must_use]
+    pub fn peak_bytes(&self) -> u64 {
+        self.0.peak.load(Ordering::Relaxed)
+    }
+
+    /// Bytes of linear memory currently held by live instances
+    #[must_use]
+    pub fn resident_bytes(&self) -> u64 {
+        self.0.resident.load(Ordering::Relaxed)
+    }
+
+    /// Number of instances currently live
+    #[must_use]
+    pub fn instances(&self) -> u64 {
+        self.0.instances.load(Ordering::Relaxed)
+    }
+
+    /// Reset the peak returned by [`Self::peak_bytes`], e.g. at the start of a profile
+    pub fn reset_peak(&self) {
+        self.0.peak.store(0, Ordering::Relaxed);
+    }
+
+    /// Record growth of an instance's memory, returning the number of bytes grown
+    fn record(&self, current: usize, desired: usize) -> u64 {
+        let grown = u64::try_from(desired.saturating_sub(current)).unwrap_or(u64::MAX);
+        self.0.allocated.fetch_add(grown, Ordering::Relaxed);
+        self.0.resident.fetch_add(grown, Ordering::Relaxed);
+        let desired = u64::try_from(desired).unwrap_or(u64::MAX);
+        self.0.peak.fetch_max(desired, Ordering::Relaxed);
+        grown
+    }
+}
+
+/// Resource limiter of a single store, recording memory growth in a [`MemoryUsage`]
+#[derive(Debug)]
+pub(crate) struct MemoryTracker {
+    usage: MemoryUsage,
+    /// Bytes of linear memory held by this store
+    resident: u64,
+}
+
+impl MemoryTracker {
+    pub(crate) fn new(usage: &MemoryUsage) -> Self {
+        usage.0.instances.fetch_add(1, Ordering::Relaxed);
+        Self {
+            usage: usage.clone(),
+            resident: 0,
+        }
+    }
+}
+
+impl Drop for MemoryTracker {
+    fn drop(&mut self) {
+        self.usage
+            .0
+            .resident
+            .fetch_sub(self.resident, Ordering::Relaxed);
+        self.usage.0.instances.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl ResourceLimiter for MemoryTracker {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        self.resident = self
+            .resident
+            .saturating_add(self.usage.record(current, desired));
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        Ok(true)
+    }
+}
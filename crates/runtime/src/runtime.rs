@@ -1,3 +1,4 @@
+use crate::scheduling::DEFAULT_SCHEDULING_QUANTUM;
 use crate::{experimental::Features, ComponentConfig};
 
 use core::fmt;
@@ -29,6 +30,7 @@ pub struct RuntimeBuilder {
     max_component_size: u64,
     max_linear_memory: u32,
     max_execution_time: Duration,
+    scheduling_quantum: Duration,
     component_config: ComponentConfig,
     force_pooling_allocator: bool,
     experimental_features: Features,
@@ -52,6 +54,7 @@ impl RuntimeBuilder {
             max_linear_memory: MAX_LINEAR_MEMORY,
             max_core_instances_per_component: DEFAULT_MAX_CORE_INSTANCES_PER_COMPONENT,
             max_execution_time: Duration::from_secs(10 * 60),
+            scheduling_quantum: DEFAULT_SCHEDULING_QUANTUM,
             component_config: ComponentConfig::default(),
             force_pooling_allocator: false,
             experimental_features: Features::default(),
@@ -114,6 +117,19 @@ impl RuntimeBuilder {
         }
     }
 
+    /// Sets the scheduling quantum, the interval at which executing components are interrupted to
+    /// check whether they should yield to other components. Defaults to 10 milliseconds.
+    /// Any value below 1 millisecond will be interpreted as 1 millisecond.
+    ///
+    /// See [`scheduling`](crate::scheduling) for details.
+    #[must_use]
+    pub fn scheduling_quantum(self, scheduling_quantum: Duration) -> Self {
+        Self {
+            scheduling_quantum: scheduling_quantum.max(Duration::from_millis(1)),
+            ..self
+        }
+    }
+
     /// Forces the use of the pooling allocator. This may cause the runtime to fail if there isn't enough memory for the pooling allocator
     #[must_use]
     pub fn force_pooling_allocator(self) -> Self {
@@ -194,8 +210,9 @@ impl RuntimeBuilder {
         };
         let epoch = {
             let engine = engine.weak();
+            let quantum = self.scheduling_quantum;
             thread::spawn(move || loop {
-                thread::sleep(Duration::from_secs(1));
+                thread::sleep(quantum);
                 let Some(engine) = engine.upgrade() else {
                     return Ok(());
                 };
@@ -0,0 +1,124 @@
+//! Fair scheduling of components sharing a [Runtime](crate::Runtime).
+//!
+//! Component execution is time-sliced using wasmtime epoch interruption. The runtime increments
+//! the engine epoch once per scheduling quantum, and whenever a component exhausts its time slice
+//! it yields back to the async executor, so that a single busy component cannot monopolize the
+//! threads running other components. The length of a time slice is determined by the
+//! [`PriorityClass`] of a component.
+
+use core::fmt;
+use core::str::FromStr;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use wasmtime::{CallHook, Trap, UpdateDeadline};
+
+/// Default duration of a scheduling quantum, the interval at which the engine epoch is incremented
+pub const DEFAULT_SCHEDULING_QUANTUM: Duration = Duration::from_millis(10);
+
+/// Scheduling priority of a component, which determines how many scheduling quanta a component
+/// may execute for before yielding to other components
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PriorityClass {
+    /// Yield after every quantum
+    Low,
+    /// Yield after 2 quanta
+    #[default]
+    Normal,
+    /// Yield after 4 quanta
+    High,
+}
+
+impl PriorityClass {
+    /// Number of scheduling quanta a component of this class executes for before yielding
+    #[must_use]
+    pub fn weight(self) -> u64 {
+        match self {
+            Self::Low => 1,
+            Self::Normal => 2,
+            Self::High => 4,
+        }
+    }
+}
+
+impl FromStr for PriorityClass {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            _ => anyhow::bail!("invalid priority class `{s}`, expected `low`, `normal` or `high`"),
+        }
+    }
+}
+
+impl fmt::Display for PriorityClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+        })
+    }
+}
+
+/// Time spent executing guest code of a component, shared by all of its instances
+#[derive(Clone, Debug, Default)]
+pub struct CpuTime(Arc<AtomicU64>);
+
+impl CpuTime {
+    /// Total time spent executing guest code.
+    ///
+    /// This is measured from entering guest code until it either returns or calls into the host,
+    /// so it includes time a component spends waiting to be resumed after yielding at the end of
+    /// a time slice.
+    #[must_use]
+    pub fn get(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Relaxed))
+    }
+
+    fn add(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.0.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Configure `store` to yield once every time slice of `priority`, to trap once
+/// `max_execution_time` has elapsed and to account time spent in guest code to `cpu_time`
+pub(crate) fn configure_store<T>(
+    store: &mut wasmtime::Store<T>,
+    max_execution_time: Duration,
+    priority: PriorityClass,
+    cpu_time: &CpuTime,
+) {
+    let started_at = Instant::now();
+    let slice = priority.weight();
+    store.set_epoch_deadline(slice);
+    store.epoch_deadline_callback(move |_| {
+        if started_at.elapsed() >= max_execution_time {
+            return Err(Trap::Interrupt.into());
+        }
+        Ok(UpdateDeadline::Yield(slice))
+    });
+
+    let cpu_time = cpu_time.clone();
+    let mut entered_guest_at = None;
+    store.call_hook(move |_, hook| {
+        match hook {
+            CallHook::CallingWasm | CallHook::ReturningFromHost => {
+                entered_guest_at = Some(Instant::now());
+            }
+            CallHook::ReturningFromWasm | CallHook::CallingHost => {
+                if let Some(entered_guest_at) = entered_guest_at.take() {
+                    cpu_time.add(entered_guest_at.elapsed());
+                }
+            }
+        }
+        Ok(())
+    });
+}
@@ -203,7 +203,7 @@ async fn run(args: RunCommand) -> anyhow::Result<()> {
             .await
             .context(format!(
                 "failed to read NATS credentials file '{}'",
-                &creds_file
+                creds_file
             ))?
             .connect(&args.nats_address)
             .await
@@ -244,7 +244,7 @@ async fn put(args: PutCommand) -> anyhow::Result<()> {
             .await
             .context(format!(
                 "failed to read NATS credentials file '{}'",
-                &creds_file
+                creds_file
             ))?
             .name("secrets-nats-kv")
             .connect(&args.nats_address)
@@ -295,7 +295,7 @@ async fn get(args: GetCommand) -> anyhow::Result<()> {
             .await
             .context(format!(
                 "failed to read NATS credentials file '{}'",
-                &creds_file
+                creds_file
             ))?
             .name("secrets-nats-kv")
             .connect(&args.nats_address)
@@ -354,7 +354,7 @@ async fn add_mapping(args: AddSecretMappingCommand) -> anyhow::Result<()> {
             .await
             .context(format!(
                 "failed to read NATS credentials file '{}'",
-                &creds_file
+                creds_file
             ))?
             .name("secrets-nats-kv")
             .connect(&args.nats_address)
@@ -398,7 +398,7 @@ async fn remove_mapping(args: RemoveSecretMappingCommand) -> anyhow::Result<()>
             .await
             .context(format!(
                 "failed to read NATS credentials file '{}'",
-                &creds_file
+                creds_file
             ))?
             .name("secrets-nats-kv")
             .connect(&args.nats_address)
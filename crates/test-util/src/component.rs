@@ -114,7 +114,7 @@ pub async fn assert_scale_component(
             component_ref,
             component_id,
             count,
-            annotations,
+            annotations.map(Into::into),
             config,
         )
         .await
@@ -12,7 +12,8 @@ use tokio::task::JoinSet;
 use url::Url;
 
 use wasmcloud_control_interface::{Client as WasmcloudCtlClient, ClientBuilder};
-use wasmcloud_host::nats::connect_nats;
+use wasmcloud_host::nats::connect_nats_with_health;
+use wasmcloud_host::nats::health::NatsHealth;
 use wasmcloud_host::wasmbus::host_config::PolicyService;
 use wasmcloud_host::wasmbus::{Features, Host, HostConfig};
 
@@ -115,9 +116,18 @@ impl WasmCloudTestHost {
             ..Default::default()
         };
 
-        let nats_client = connect_nats(nats_url.as_str(), None, None, false, None, None)
-            .await
-            .context("failed to connect to NATS")?;
+        let nats_health = Arc::new(NatsHealth::default());
+        let nats_client = connect_nats_with_health(
+            nats_url.as_str(),
+            None,
+            None,
+            false,
+            None,
+            None,
+            Arc::clone(&nats_health),
+        )
+        .await
+        .context("failed to connect to NATS")?;
 
         let nats_builder = wasmcloud_host::nats::builder::NatsHostBuilder::new(
             nats_client.clone(),
@@ -131,7 +141,8 @@ impl WasmCloudTestHost {
             true,
         )
         .await?
-        .with_event_publisher(host_key.public_key());
+        .with_event_publisher(host_key.public_key())
+        .with_nats_health(nats_health);
 
         let nats_builder = if let Some(secrets_topic_prefix) = secrets_topic_prefix {
             nats_builder.with_secrets_manager(secrets_topic_prefix)?
@@ -0,0 +1,280 @@
+//! Programmatic construction of test lattices, consisting of a NATS server and one or more
+//! in-process hosts
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, ensure, Context as _, Result};
+use nkeys::KeyPair;
+use wascap::jwt;
+use wasmcloud_control_interface::{Client as WasmCloudCtlClient, ClientBuilder, Link};
+use wasmcloud_host::wasmbus::{serialize_component_claims, Features};
+
+use crate::host::WasmCloudTestHost;
+use crate::lattice::config::assert_config_put;
+use crate::lattice::events::EventRecorder;
+use crate::nats::TestNatsServer;
+
+/// Where the NATS server of a test lattice comes from
+#[derive(Clone, Debug, Default)]
+enum NatsSource {
+    #[default]
+    Spawn,
+    Bin(PathBuf),
+    Url(String),
+    #[cfg(feature = "testcontainers")]
+    Container,
+}
+
+/// Builder for a [`TestLattice`]
+///
+/// ```rust,ignore
+/// use wasmcloud_test_util::TestLattice;
+///
+/// # async fn example() -> anyhow::Result<()> {
+/// let lattice = TestLattice::builder()
+///     .hosts(2)
+///     .host_label("zone", "us-east-1")
+///     .config("http-config", [("address".to_string(), "0.0.0.0:8080".to_string())])
+///     .build()
+///     .await?;
+/// let mut events = lattice.events(["component_scaled"]).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct TestLatticeBuilder {
+    lattice: String,
+    nats: NatsSource,
+    hosts: usize,
+    labels: BTreeMap<String, String>,
+    features: Option<Features>,
+    config: Vec<(String, HashMap<String, String>)>,
+    links: Vec<Link>,
+    claims: Vec<jwt::Claims<jwt::Component>>,
+}
+
+impl Default for TestLatticeBuilder {
+    fn default() -> Self {
+        // Use a unique lattice name, so that lattices sharing a NATS server do not interfere
+        let id = KeyPair::new_cluster().public_key();
+        Self {
+            lattice: format!("test-{}", id[1..13].to_ascii_lowercase()),
+            nats: NatsSource::default(),
+            hosts: 1,
+            labels: BTreeMap::new(),
+            features: None,
+            config: Vec::new(),
+            links: Vec::new(),
+            claims: Vec::new(),
+        }
+    }
+}
+
+impl TestLatticeBuilder {
+    /// Set the name of the lattice, which defaults to a unique name
+    pub fn lattice(mut self, lattice: impl Into<String>) -> Self {
+        self.lattice = lattice.into();
+        self
+    }
+
+    /// Spawn the given `nats-server` binary instead of the one found in `PATH`
+    pub fn nats_bin(mut self, bin: impl Into<PathBuf>) -> Self {
+        self.nats = NatsSource::Bin(bin.into());
+        self
+    }
+
+    /// Use an already running NATS server instead of spawning a `nats-server` process
+    pub fn nats_url(mut self, url: impl Into<String>) -> Self {
+        self.nats = NatsSource::Url(url.into());
+        self
+    }
+
+    /// Run NATS in a container instead of spawning a `nats-server` process
+    #[cfg(feature = "testcontainers")]
+    pub fn nats_container(mut self) -> Self {
+        self.nats = NatsSource::Container;
+        self
+    }
+
+    /// Set the number of hosts to start, which defaults to 1
+    pub fn hosts(mut self, hosts: usize) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// Put a label on every host
+    pub fn host_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the experimental features enabled on every host
+    pub fn features(mut self, features: Features) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    /// Put named configuration once the hosts are started
+    pub fn config(
+        mut self,
+        name: impl Into<String>,
+        config: impl Into<HashMap<String, String>>,
+    ) -> Self {
+        self.config.push((name.into(), config.into()));
+        self
+    }
+
+    /// Put a link once the hosts are started
+    pub fn link(mut self, link: Link) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    /// Store component claims in the lattice once the hosts are started
+    pub fn claims(mut self, claims: jwt::Claims<jwt::Component>) -> Self {
+        self.claims.push(claims);
+        self
+    }
+
+    /// Start NATS and all hosts, then seed the lattice with the configured labels, config, links
+    /// and claims
+    pub async fn build(self) -> Result<TestLattice> {
+        ensure!(self.hosts > 0, "a test lattice requires at least one host");
+        let nats = match &self.nats {
+            NatsSource::Spawn => TestNatsServer::spawn().await?,
+            NatsSource::Bin(bin) => TestNatsServer::spawn_bin(bin).await?,
+            NatsSource::Url(url) => TestNatsServer::connect(url).await?,
+            #[cfg(feature = "testcontainers")]
+            NatsSource::Container => TestNatsServer::start_container().await?,
+        };
+
+        let mut hosts = Vec::with_capacity(self.hosts);
+        for _ in 0..self.hosts {
+            let host = WasmCloudTestHost::start_custom(
+                nats.url().as_str(),
+                &self.lattice,
+                None,
+                None,
+                None,
+                None,
+                self.features,
+            )
+            .await
+            .context("failed to start test host")?;
+            hosts.push(host);
+        }
+
+        let ctl_client = ClientBuilder::new(nats.client())
+            .lattice(&self.lattice)
+            .build();
+        for host in &hosts {
+            for (key, value) in &self.labels {
+                let resp = ctl_client
+                    .put_label(&host.host_id(), key, value)
+                    .await
+                    .map_err(|e| anyhow!(e).context("failed to put label"))?;
+                ensure!(resp.succeeded(), "failed to put label: {}", resp.message());
+            }
+        }
+        for (name, config) in self.config {
+            assert_config_put(&ctl_client, name, config).await?;
+        }
+        for link in self.links {
+            let resp = ctl_client
+                .put_link(link)
+                .await
+                .map_err(|e| anyhow!(e).context("failed to put link"))?;
+            ensure!(resp.succeeded(), "failed to put link: {}", resp.message());
+        }
+        if !self.claims.is_empty() {
+            let store = async_nats::jetstream::new(nats.client())
+                .get_key_value(format!("LATTICEDATA_{}", self.lattice))
+                .await
+                .context("failed to open lattice data bucket")?;
+            for claims in self.claims {
+                let key = format!("CLAIMS_{}", claims.subject);
+                let stored = serialize_component_claims(claims)?;
+                store
+                    .put(key, stored.into())
+                    .await
+                    .context("failed to store claims")?;
+            }
+        }
+
+        Ok(TestLattice {
+            lattice: self.lattice,
+            nats,
+            hosts,
+            ctl_client,
+        })
+    }
+}
+
+/// A lattice used in testing, consisting of a NATS server and one or more in-process hosts
+pub struct TestLattice {
+    lattice: String,
+    nats: TestNatsServer,
+    hosts: Vec<WasmCloudTestHost>,
+    ctl_client: WasmCloudCtlClient,
+}
+
+impl TestLattice {
+    /// Create a [`TestLatticeBuilder`]
+    pub fn builder() -> TestLatticeBuilder {
+        TestLatticeBuilder::default()
+    }
+
+    /// Get the name of the lattice
+    #[must_use]
+    pub fn lattice_name(&self) -> &str {
+        &self.lattice
+    }
+
+    /// Get the NATS server backing the lattice
+    #[must_use]
+    pub fn nats(&self) -> &TestNatsServer {
+        &self.nats
+    }
+
+    /// Get a control interface client for the lattice
+    #[must_use]
+    pub fn ctl_client(&self) -> &WasmCloudCtlClient {
+        &self.ctl_client
+    }
+
+    /// Get all hosts of the lattice
+    #[must_use]
+    pub fn hosts(&self) -> &[WasmCloudTestHost] {
+        &self.hosts
+    }
+
+    /// Get the first host of the lattice
+    #[must_use]
+    pub fn host(&self) -> &WasmCloudTestHost {
+        &self.hosts[0]
+    }
+
+    /// Start recording lattice events of the given types, e.g. `component_scaled`
+    pub async fn events(
+        &self,
+        event_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<EventRecorder> {
+        EventRecorder::start(&self.ctl_client, event_types).await
+    }
+
+    /// Stop all hosts, then the NATS server if it was started for this lattice
+    pub async fn stop(self) -> Result<()> {
+        for host in self.hosts {
+            host.stop().await?;
+        }
+        self.nats.stop().await
+    }
+}
+
+impl AsRef<WasmCloudCtlClient> for TestLattice {
+    fn as_ref(&self) -> &WasmCloudCtlClient {
+        &self.ctl_client
+    }
+}
@@ -0,0 +1,104 @@
+//! Utilities for asserting on lattice events
+
+use core::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use cloudevents::{AttributesReader as _, Data, Event};
+use tokio::sync::mpsc::Receiver;
+use tokio::time::Instant;
+use wasmcloud_control_interface::Client as WasmCloudCtlClient;
+
+/// Records lattice events so that tests can wait for and assert on them.
+///
+/// Event types are matched without the CloudEvents namespace, e.g. `component_scaled` matches
+/// `com.wasmcloud.lattice.component_scaled`.
+pub struct EventRecorder {
+    receiver: Receiver<Event>,
+    received: Vec<Event>,
+}
+
+impl EventRecorder {
+    /// Start recording events of the given types, e.g. `component_scaled`
+    pub async fn start(
+        client: impl Into<&WasmCloudCtlClient>,
+        event_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self> {
+        let receiver = client
+            .into()
+            .events_receiver(event_types.into_iter().map(Into::into).collect())
+            .await
+            .map_err(|e| anyhow!(e).context("failed to subscribe to lattice events"))?;
+        Ok(Self {
+            receiver,
+            received: Vec::new(),
+        })
+    }
+
+    /// All events received so far, in the order they were received
+    #[must_use]
+    pub fn received(&self) -> &[Event] {
+        &self.received
+    }
+
+    /// Wait up to `timeout` for an event of the given type
+    pub async fn wait_for(&mut self, event_type: &str, timeout: Duration) -> Result<Event> {
+        self.wait_for_matching(event_type, timeout, |_| true).await
+    }
+
+    /// Wait up to `timeout` for an event of the given type whose JSON data matches `predicate`,
+    /// skipping over any other events
+    pub async fn wait_for_matching(
+        &mut self,
+        event_type: &str,
+        timeout: Duration,
+        predicate: impl Fn(&serde_json::Value) -> bool,
+    ) -> Result<Event> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let Some(evt) = self.next_before(deadline).await? else {
+                bail!("timed out after {timeout:?} waiting for `{event_type}` event");
+            };
+            if is_type(&evt, event_type) && event_data(&evt).is_some_and(&predicate) {
+                return Ok(evt);
+            }
+        }
+    }
+
+    /// Ensure that no event of the given type is received within `duration`
+    pub async fn assert_no_event(&mut self, event_type: &str, duration: Duration) -> Result<()> {
+        let deadline = Instant::now() + duration;
+        while let Some(evt) = self.next_before(deadline).await? {
+            if is_type(&evt, event_type) {
+                bail!("unexpected `{event_type}` event: {evt}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive the next event, returning `None` once `deadline` has passed
+    async fn next_before(&mut self, deadline: Instant) -> Result<Option<Event>> {
+        match tokio::time::timeout_at(deadline, self.receiver.recv()).await {
+            Ok(Some(evt)) => {
+                self.received.push(evt.clone());
+                Ok(Some(evt))
+            }
+            Ok(None) => bail!("lattice event stream closed"),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Returns whether the event has the given type, ignoring the CloudEvents namespace
+#[must_use]
+pub fn is_type(evt: &Event, event_type: &str) -> bool {
+    evt.ty().rsplit('.').next() == Some(event_type)
+}
+
+/// Returns the JSON data of an event, if it has any
+#[must_use]
+pub fn event_data(evt: &Event) -> Option<&serde_json::Value> {
+    match evt.data() {
+        Some(Data::Json(data)) => Some(data),
+        _ => None,
+    }
+}
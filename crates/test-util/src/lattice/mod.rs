@@ -1,4 +1,6 @@
 //! Utilities for lattice management during testing
 
+pub mod builder;
 pub mod config;
+pub mod events;
 pub mod link;
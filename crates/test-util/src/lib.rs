@@ -81,5 +81,8 @@ pub use wasmcloud_control_interface as control_interface;
 pub use crate::component::assert_scale_component;
 pub use crate::host::WasmCloudTestHost;
 pub use crate::host::{assert_delete_label, assert_put_label};
+pub use crate::lattice::builder::{TestLattice, TestLatticeBuilder};
 pub use crate::lattice::config::assert_config_put;
+pub use crate::lattice::events::EventRecorder;
+pub use crate::nats::TestNatsServer;
 pub use crate::provider::assert_start_provider;
@@ -1,6 +1,20 @@
+//! Utilities for running and connecting to NATS servers during testing
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
 use anyhow::{Context as _, Result};
 use async_nats::{Client, ToServerAddrs};
+use serde::Deserialize;
+use tempfile::TempDir;
+use tokio::process::{Child, Command};
 use tokio::time::Duration;
+use url::Url;
+
+/// Environment variable used to override the `nats-server` binary started by
+/// [`TestNatsServer::spawn`]
+pub const TEST_NATS_BIN_ENV: &str = "TEST_NATS_BIN";
 
 /// Wait for a given NATS connection to be available
 pub async fn wait_for_nats_connection(url: impl ToServerAddrs) -> Result<Client> {
@@ -15,3 +29,171 @@ pub async fn wait_for_nats_connection(url: impl ToServerAddrs) -> Result<Client>
     .await
     .context("failed to connect NATS server client")
 }
+
+/// A NATS server with JetStream enabled, used to back a test lattice.
+///
+/// Servers started by this type are stopped when it is dropped or [`TestNatsServer::stop`] is
+/// called.
+pub struct TestNatsServer {
+    url: Url,
+    client: Client,
+    instance: NatsInstance,
+}
+
+enum NatsInstance {
+    External,
+    Process {
+        child: Child,
+        _data_dir: TempDir,
+    },
+    #[cfg(feature = "testcontainers")]
+    Container {
+        container: Box<crate::testcontainers::ContainerAsync<crate::testcontainers::NatsServer>>,
+    },
+}
+
+impl TestNatsServer {
+    /// Use an already running NATS server, which is left running when this is dropped
+    pub async fn connect(url: impl AsRef<str>) -> Result<Self> {
+        let url = Url::parse(url.as_ref()).context("failed to parse NATS URL")?;
+        let client = wait_for_nats_connection(url.as_str()).await?;
+        Ok(Self {
+            url,
+            client,
+            instance: NatsInstance::External,
+        })
+    }
+
+    /// Start a `nats-server` process listening on a random local port, storing JetStream data in a
+    /// temporary directory.
+    ///
+    /// The port is chosen by the server itself and read back from its ports file, so that no other
+    /// process can claim it in between.
+    ///
+    /// The binary is looked up in `PATH` unless overridden with [`TEST_NATS_BIN_ENV`].
+    pub async fn spawn() -> Result<Self> {
+        Self::spawn_bin(
+            env::var_os(TEST_NATS_BIN_ENV).unwrap_or_else(|| OsString::from("nats-server")),
+        )
+        .await
+    }
+
+    /// Like [`TestNatsServer::spawn`], but using the given `nats-server` binary
+    pub async fn spawn_bin(bin: impl AsRef<OsStr>) -> Result<Self> {
+        let data_dir = tempfile::tempdir().context("failed to create temporary directory")?;
+        let child = Command::new(bin)
+            .arg("--jetstream")
+            .arg("--store_dir")
+            .arg(data_dir.path())
+            .arg("--ports_file_dir")
+            .arg(data_dir.path())
+            .args(["--addr", "127.0.0.1", "--port", "-1"])
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to spawn `nats-server`")?;
+        // The process is killed when dropped, should this fail
+        let url = tokio::time::timeout(Duration::from_secs(3), read_client_url(data_dir.path()))
+            .await
+            .context("timed out waiting for `nats-server` to write its ports file")??;
+        let client = wait_for_nats_connection(url.as_str()).await?;
+        Ok(Self {
+            url,
+            client,
+            instance: NatsInstance::Process {
+                child,
+                _data_dir: data_dir,
+            },
+        })
+    }
+
+    /// Start a NATS server container with JetStream enabled
+    #[cfg(feature = "testcontainers")]
+    pub async fn start_container() -> Result<Self> {
+        use crate::testcontainers::{AsyncRunner as _, ImageExt as _, NatsServer};
+
+        let container = NatsServer::default()
+            .with_cmd(["--jetstream"])
+            .start()
+            .await
+            .context("failed to start nats-server container")?;
+        let port = container
+            .get_host_port_ipv4(4222)
+            .await
+            .context("failed to find NATS port of container")?;
+        let url =
+            Url::parse(&format!("nats://127.0.0.1:{port}")).context("failed to parse NATS URL")?;
+        let client = wait_for_nats_connection(url.as_str()).await?;
+        Ok(Self {
+            url,
+            client,
+            instance: NatsInstance::Container {
+                container: Box::new(container),
+            },
+        })
+    }
+
+    /// Get the URL of the NATS server
+    #[must_use]
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Get a client connected to the NATS server
+    #[must_use]
+    pub fn client(&self) -> Client {
+        self.client.clone()
+    }
+
+    /// Stop the NATS server and wait for it to exit, unless it was started externally
+    pub async fn stop(self) -> Result<()> {
+        match self.instance {
+            NatsInstance::External => Ok(()),
+            NatsInstance::Process { mut child, .. } => {
+                child.kill().await.context("failed to stop `nats-server`")
+            }
+            #[cfg(feature = "testcontainers")]
+            NatsInstance::Container { container } => container
+                .stop()
+                .await
+                .context("failed to stop nats-server container"),
+        }
+    }
+}
+
+/// Wait for `nats-server` to write its ports file to `dir` and return the client URL in it
+async fn read_client_url(dir: &Path) -> Result<Url> {
+    loop {
+        let mut entries = tokio::fs::read_dir(dir)
+            .await
+            .context("failed to read ports file directory")?;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context("failed to read ports file directory entry")?
+        {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "ports") {
+                let buf = tokio::fs::read(&path)
+                    .await
+                    .context("failed to read ports file")?;
+                // The file may be observed before it is fully written
+                let Ok(ports) = serde_json::from_slice::<ServerPorts>(&buf) else {
+                    continue;
+                };
+                let url = ports
+                    .nats
+                    .first()
+                    .context("ports file does not contain a client URL")?;
+                return Url::parse(url).context("failed to parse NATS URL");
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+/// Ports file written by `nats-server` when started with `--ports_file_dir`
+#[derive(Deserialize)]
+struct ServerPorts {
+    #[serde(default)]
+    nats: Vec<String>,
+}
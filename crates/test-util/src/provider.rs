@@ -65,7 +65,9 @@ pub async fn assert_start_provider(
     }: StartProviderArgs<'_>,
 ) -> Result<()> {
     let lattice = client.lattice();
-    let rpc_client = client.nats_client();
+    let rpc_client = client
+        .nats_client()
+        .context("control interface client has no NATS connection")?;
     let resp = client
         .start_provider(host_id, provider_ref, provider_id, None, config)
         .await
@@ -136,7 +138,9 @@ pub async fn assert_stop_provider(
     }: StopProviderArgs<'_>,
 ) -> Result<()> {
     let lattice = client.lattice();
-    let rpc_client = client.nats_client();
+    let rpc_client = client
+        .nats_client()
+        .context("control interface client has no NATS connection")?;
     let resp = client
         .stop_provider(host_id, provider_id)
         .await
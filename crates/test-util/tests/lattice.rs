@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, ensure, Context as _, Result};
+use nkeys::KeyPair;
+use wascap::jwt;
+use wascap::prelude::ClaimsBuilder;
+use wasmcloud_test_util::{TestLattice, TestNatsServer};
+
+#[tokio::test]
+async fn test_lattice_requires_a_host() -> Result<()> {
+    let err = TestLattice::builder()
+        .hosts(0)
+        .build()
+        .await
+        .err()
+        .context("building a lattice without hosts should fail")?;
+    ensure!(err.to_string().contains("at least one host"));
+    Ok(())
+}
+
+/// Ensure that lattices are seeded as configured and stopped completely
+///
+/// This test is ignored by default as it requires `nats-server` to be installed.
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn test_lattice_is_seeded() -> Result<()> {
+    let component = KeyPair::new_module();
+    let claims = ClaimsBuilder::new()
+        .issuer(&KeyPair::new_account().public_key())
+        .subject(&component.public_key())
+        .with_metadata(jwt::Component {
+            name: Some("seeded".to_string()),
+            ..Default::default()
+        })
+        .build();
+    let lattice = TestLattice::builder()
+        .hosts(2)
+        .host_label("zone", "test")
+        .config("seeded", [("key".to_string(), "value".to_string())])
+        .claims(claims)
+        .build()
+        .await?;
+    let ctl_client = lattice.ctl_client();
+
+    ensure!(lattice.hosts().len() == 2);
+    for host in lattice.hosts() {
+        let inventory = ctl_client
+            .get_host_inventory(host.host_id())
+            .await
+            .map_err(|e| anyhow!(e))?
+            .into_data()
+            .context("inventory should be returned")?;
+        ensure!(inventory.labels().get("zone").map(String::as_str) == Some("test"));
+    }
+
+    let config = ctl_client
+        .get_config("seeded")
+        .await
+        .map_err(|e| anyhow!(e))?
+        .into_data()
+        .context("config should be returned")?;
+    ensure!(config.get("key").map(String::as_str) == Some("value"));
+
+    // Claims are picked up by the hosts asynchronously
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let claims = ctl_client
+                .get_claims()
+                .await
+                .map_err(|e| anyhow!(e))?
+                .into_data()
+                .unwrap_or_default();
+            if claims.iter().any(|claims| {
+                claims.get("subject") == Some(&component.public_key())
+                    && claims.get("name").map(String::as_str) == Some("seeded")
+            }) {
+                return anyhow::Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .context("seeded claims were not observed in time")??;
+
+    let nats_url = lattice.nats().url().clone();
+    lattice.stop().await?;
+    ensure!(
+        async_nats::connect(nats_url.as_str()).await.is_err(),
+        "NATS server should be stopped with the lattice"
+    );
+    Ok(())
+}
+
+/// Ensure that spawned NATS servers do not compete for ports
+///
+/// This test is ignored by default as it requires `nats-server` to be installed.
+#[tokio::test]
+#[ignore]
+async fn test_nats_servers_use_distinct_ports() -> Result<()> {
+    let first = TestNatsServer::spawn().await?;
+    let second = TestNatsServer::spawn().await?;
+    ensure!(first.url() != second.url());
+    first.stop().await?;
+    second.stop().await?;
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! Handling of ended spans while the export queue of the batch span processor is full, as
+//! configured by [`OtelConfig::queue_full_policy`]
+//!
+//! The batch span processor drops spans that do not fit into its queue. [`QueueCapacity`] tracks
+//! the spans handed to it until their export finished, which allows counting dropped spans in the
+//! [`DROPPED_SPANS`] metric and, with [`OtelQueueFullPolicy::Block`], waiting for room instead of
+//! dropping them.
+
+use std::sync::{Arc, Condvar, Mutex, OnceLock, PoisonError};
+
+use opentelemetry::metrics::Counter;
+use opentelemetry::Context;
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use opentelemetry_sdk::Resource;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use wasmcloud_core::{OtelConfig, OtelQueueFullPolicy};
+
+/// Default queue size of the batch span processor, used if `max_batch_queue_size` is not set
+const DEFAULT_MAX_QUEUE_SIZE: usize = 2048;
+
+/// Counter of spans dropped because the export queue was full
+const DROPPED_SPANS: &str = "wasmcloud.tracing.spans.dropped";
+
+#[derive(Debug, Default)]
+struct QueueState {
+    /// Number of spans queued or being exported
+    pending: usize,
+    /// Number of spans dropped since the last release
+    dropped: usize,
+    /// Set on shutdown, after which nothing waits for room anymore
+    closed: bool,
+}
+
+/// Capacity of the export queue of the batch span processor
+#[derive(Debug)]
+pub(crate) struct QueueCapacity {
+    max_queue_size: usize,
+    policy: OtelQueueFullPolicy,
+    state: Mutex<QueueState>,
+    released: Condvar,
+    /// Created on first use, so that it is recorded with the meter provider installed after
+    /// tracing was set up
+    dropped_spans: OnceLock<Counter<u64>>,
+}
+
+impl QueueCapacity {
+    pub(crate) fn new(otel_config: &OtelConfig) -> Self {
+        Self {
+            max_queue_size: otel_config
+                .max_batch_queue_size
+                .unwrap_or(DEFAULT_MAX_QUEUE_SIZE),
+            policy: otel_config.queue_full_policy.unwrap_or_default(),
+            state: Mutex::default(),
+            released: Condvar::new(),
+            dropped_spans: OnceLock::new(),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes room for a span in the queue, waiting for room if the queue is full and the policy
+    /// is to block. Returns false if the span does not fit and will be dropped.
+    fn acquire(&self) -> bool {
+        let mut state = self.state();
+        if state.closed {
+            return true;
+        }
+        if state.pending < self.max_queue_size {
+            state.pending += 1;
+            return true;
+        }
+        if self.policy == OtelQueueFullPolicy::Block {
+            drop(state);
+            match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+                // Waiting would block the only thread able to run the export
+                Ok(RuntimeFlavor::CurrentThread) => {}
+                // Let the runtime move its other tasks, including the export, off this thread
+                Ok(_) => return tokio::task::block_in_place(|| self.wait()),
+                Err(_) => return self.wait(),
+            }
+            state = self.state();
+        }
+        state.dropped += 1;
+        false
+    }
+
+    /// Waits until the queue has room and takes it
+    fn wait(&self) -> bool {
+        let mut state = self
+            .released
+            .wait_while(self.state(), |state| {
+                !state.closed && state.pending >= self.max_queue_size
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        if !state.closed {
+            state.pending += 1;
+        }
+        true
+    }
+
+    /// Releases the room of `count` spans whose export finished, counting the spans dropped since
+    /// the last release. Spans are not counted as they are dropped, which would record the metric
+    /// for every span while the queue is full
+    fn release(&self, count: usize) {
+        let mut state = self.state();
+        state.pending = state.pending.saturating_sub(count);
+        let dropped = std::mem::take(&mut state.dropped);
+        drop(state);
+        self.released.notify_all();
+        if dropped > 0 {
+            self.dropped_spans
+                .get_or_init(|| {
+                    opentelemetry::global::meter("wasmcloud-tracing")
+                        .u64_counter(DROPPED_SPANS)
+                        .with_description(
+                            "Number of spans dropped because the span export queue was full",
+                        )
+                        .build()
+                })
+                .add(dropped.try_into().unwrap_or(u64::MAX), &[]);
+        }
+    }
+
+    fn close(&self) {
+        self.state().closed = true;
+        self.released.notify_all();
+    }
+}
+
+/// Guard releasing the room of exported spans in a [`QueueCapacity`] when dropped
+pub(crate) struct QueueRelease {
+    capacity: Arc<QueueCapacity>,
+    count: usize,
+}
+
+impl QueueRelease {
+    pub(crate) fn new(capacity: Arc<QueueCapacity>, count: usize) -> Self {
+        Self { capacity, count }
+    }
+}
+
+impl Drop for QueueRelease {
+    fn drop(&mut self) {
+        self.capacity.release(self.count);
+    }
+}
+
+/// Span processor taking room in a [`QueueCapacity`] for every sampled span before handing it to
+/// the wrapped batch span processor, whose exporter releases the room after export
+#[derive(Debug)]
+pub(crate) struct BackpressureSpanProcessor<P> {
+    inner: P,
+    capacity: Arc<QueueCapacity>,
+}
+
+impl<P: SpanProcessor> BackpressureSpanProcessor<P> {
+    pub(crate) fn new(inner: P, capacity: Arc<QueueCapacity>) -> Self {
+        Self { inner, capacity }
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for BackpressureSpanProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, span: SpanData) {
+        // Unsampled spans are never queued by the batch span processor
+        if span.span_context.is_sampled() && !self.capacity.acquire() {
+            return;
+        }
+        self.inner.on_end(span);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.capacity.close();
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
@@ -5,13 +5,27 @@
 use std::collections::HashMap;
 use std::ops::Deref;
 
-use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use opentelemetry::baggage::{Baggage, BaggageExt as _};
+use opentelemetry::propagation::{
+    Extractor, Injector, TextMapCompositePropagator, TextMapPropagator,
+};
 use opentelemetry::trace::TraceContextExt;
-use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::propagation::{BaggagePropagator, TraceContextPropagator};
 use tracing::span::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use wasmcloud_core::TraceContext;
 
+/// Returns the propagator of the W3C `traceparent`, `tracestate` and `baggage` entries of a
+/// [`TraceContext`], so that baggage such as tenant or request IDs travels along with the trace
+#[must_use]
+pub fn propagator() -> TextMapCompositePropagator {
+    TextMapCompositePropagator::new(vec![
+        Box::new(TraceContextPropagator::new()),
+        Box::new(BaggagePropagator::new()),
+    ])
+}
+
 /// A convenience type that wraps an invocation [`TraceContext`] and implements the [`Extractor`] trait
 #[derive(Debug)]
 pub struct TraceContextExtractor<'a> {
@@ -28,16 +42,11 @@ impl<'a> TraceContextExtractor<'a> {
 
 impl Extractor for TraceContextExtractor<'_> {
     fn get(&self, key: &str) -> Option<&str> {
-        // NOTE(thomastaylor312): I don't like that we have to iterate to find this, but I didn't
-        // want to allocate hashmap for now. If this starts to cause performance issues, we can see
-        // what the tradeoff is for increasing space usage for a faster lookup
-        self.inner
-            .iter()
-            .find_map(|(k, v)| (k == key).then_some(v.as_str()))
+        self.inner.get(key).map(String::as_str)
     }
 
     fn keys(&self) -> Vec<&str> {
-        self.inner.iter().map(|(k, _)| k.as_str()).collect()
+        self.inner.keys().collect()
     }
 }
 
@@ -70,7 +79,7 @@ impl TraceContextInjector {
     // Creates a new injector with the context extracted from the given extractor. If the context is empty, it will use the current span's context
     pub fn new_with_extractor(extractor: &dyn Extractor) -> Self {
         let mut header_map = Self::default();
-        let ctx_propagator = TraceContextPropagator::new();
+        let ctx_propagator = propagator();
         let context = ctx_propagator.extract(extractor);
 
         // Check if the extracted context is empty and use the current span's context if necessary
@@ -94,15 +103,23 @@ impl TraceContextInjector {
 
     /// Injects the context from the current span into the headers
     pub fn inject_context(&mut self) {
-        let ctx_propagator = TraceContextPropagator::new();
+        let ctx_propagator = propagator();
         ctx_propagator.inject_context(&Span::current().context(), self);
     }
 
     /// Injects the context from the given span into the headers
     pub fn inject_context_from_span(&mut self, span: &Span) {
-        let ctx_propagator = TraceContextPropagator::new();
+        let ctx_propagator = propagator();
         ctx_propagator.inject_context(&span.context(), self);
     }
+
+    /// Injects the context from the current span into the headers, adding the given members to
+    /// its baggage
+    pub fn inject_context_with_baggage(&mut self, baggage: impl IntoIterator<Item = KeyValue>) {
+        let ctx_propagator = propagator();
+        let context = Span::current().context().with_baggage(baggage);
+        ctx_propagator.inject_context(&context, self);
+    }
 }
 
 impl Injector for TraceContextInjector {
@@ -133,14 +150,14 @@ impl From<TraceContext> for TraceContextInjector {
 
 impl From<TraceContextInjector> for TraceContext {
     fn from(inj: TraceContextInjector) -> Self {
-        inj.inner.into_iter().collect()
+        inj.inner.into()
     }
 }
 
 /// A convenience function that will extract the [`opentelemetry::Context`] from the given
 /// [`TraceContext`]. If you want to do something more advanced, use the [`TraceContextExtractor`]
 pub fn get_span_context(trace_context: &TraceContext) -> opentelemetry::Context {
-    let ctx_propagator = TraceContextPropagator::new();
+    let ctx_propagator = propagator();
     let extractor = TraceContextExtractor::new(trace_context);
     ctx_propagator.extract(&extractor)
 }
@@ -157,3 +174,21 @@ pub fn attach_span_context(trace_context: &TraceContext) {
     let parent_ctx = get_span_context(trace_context);
     Span::current().set_parent(parent_ctx);
 }
+
+/// A convenience function that will extract the [`Baggage`] from the given [`TraceContext`]
+pub fn get_baggage(trace_context: &TraceContext) -> Baggage {
+    copy_baggage(get_span_context(trace_context).baggage())
+}
+
+/// Returns the [`Baggage`] of the current span, e.g. as attached with [`attach_span_context`]
+pub fn current_baggage() -> Baggage {
+    copy_baggage(Span::current().context().baggage())
+}
+
+/// [`Baggage`] does not implement [`Clone`], so copy it entry by entry
+fn copy_baggage(baggage: &Baggage) -> Baggage {
+    baggage
+        .iter()
+        .map(|(key, (value, metadata))| (key.clone(), (value.clone(), metadata.clone())))
+        .collect()
+}
@@ -0,0 +1,143 @@
+//! Exporters writing telemetry to stdout or a file as newline-delimited OTLP-JSON, which allows
+//! inspecting it without running a collector
+
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{self, Write as _};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use anyhow::Context as _;
+use opentelemetry_proto::tonic::collector::logs::v1::ExportLogsServiceRequest;
+use opentelemetry_proto::tonic::collector::metrics::v1::ExportMetricsServiceRequest;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::transform::common::tonic::ResourceAttributesWithSchema;
+use opentelemetry_proto::transform::logs::tonic::group_logs_by_resource_and_scope;
+use opentelemetry_proto::transform::trace::tonic::group_spans_by_resource_and_scope;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::logs::LogBatch;
+use opentelemetry_sdk::metrics::data::ResourceMetrics;
+use opentelemetry_sdk::metrics::Temporality;
+use opentelemetry_sdk::trace::SpanData;
+use opentelemetry_sdk::Resource;
+use serde::Serialize;
+use wasmcloud_core::OtelExporter;
+
+#[derive(Debug)]
+enum Output {
+    Stdout(io::Stdout),
+    File(File),
+}
+
+/// Exporter of spans, logs and metrics writing each exported batch as a single line of OTLP-JSON,
+/// i.e. the JSON encoding of the request a collector would receive
+#[derive(Clone, Debug)]
+pub(crate) struct JsonExporter {
+    output: Arc<Mutex<Output>>,
+    resource: Resource,
+}
+
+impl JsonExporter {
+    /// Returns the exporter configured by `exporter`, or `None` if telemetry should be exported
+    /// via OTLP instead
+    pub(crate) fn new(exporter: &OtelExporter) -> anyhow::Result<Option<Self>> {
+        let output = match exporter {
+            OtelExporter::Otlp => return Ok(None),
+            OtelExporter::Stdout => Output::Stdout(io::stdout()),
+            OtelExporter::File { path } => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map(Output::File)
+                .with_context(|| format!("failed to open otel export file `{}`", path.display()))?,
+        };
+        Ok(Some(Self {
+            output: Arc::new(Mutex::new(output)),
+            resource: Resource::builder_empty().build(),
+        }))
+    }
+
+    fn write(&self, request: &impl Serialize) -> OTelSdkResult {
+        let mut line = serde_json::to_vec(request)
+            .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))?;
+        line.push(b'\n');
+        // Lines are written at once, so that processes appending to the same file do not
+        // interleave their output
+        let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        match &mut *output {
+            Output::Stdout(stdout) => stdout.lock().write_all(&line),
+            Output::File(file) => file.write_all(&line),
+        }
+        .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))
+    }
+
+    fn flush(&self) -> OTelSdkResult {
+        let mut output = self.output.lock().unwrap_or_else(PoisonError::into_inner);
+        match &mut *output {
+            Output::Stdout(stdout) => stdout.flush(),
+            Output::File(file) => file.flush(),
+        }
+        .map_err(|err| OTelSdkError::InternalFailure(err.to_string()))
+    }
+}
+
+impl opentelemetry_sdk::trace::SpanExporter for JsonExporter {
+    fn export(
+        &mut self,
+        batch: Vec<SpanData>,
+    ) -> Pin<Box<dyn Future<Output = OTelSdkResult> + Send + 'static>> {
+        let resource = ResourceAttributesWithSchema::from(&self.resource);
+        let res = self.write(&ExportTraceServiceRequest {
+            resource_spans: group_spans_by_resource_and_scope(batch, &resource),
+        });
+        Box::pin(std::future::ready(res))
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        self.flush()
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+impl opentelemetry_sdk::logs::LogExporter for JsonExporter {
+    async fn export(&self, batch: LogBatch<'_>) -> OTelSdkResult {
+        let resource = ResourceAttributesWithSchema::from(&self.resource);
+        self.write(&ExportLogsServiceRequest {
+            resource_logs: group_logs_by_resource_and_scope(batch, &resource),
+        })
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        self.flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.resource = resource.clone();
+    }
+}
+
+#[async_trait::async_trait]
+impl opentelemetry_sdk::metrics::exporter::PushMetricExporter for JsonExporter {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        self.write(&ExportMetricsServiceRequest::from(&*metrics))
+    }
+
+    async fn force_flush(&self) -> OTelSdkResult {
+        self.flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.flush()
+    }
+
+    fn temporality(&self) -> Temporality {
+        Temporality::Cumulative
+    }
+}
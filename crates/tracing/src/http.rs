@@ -1,5 +1,4 @@
 use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
-use opentelemetry_sdk::propagation::TraceContextPropagator;
 use tracing::span::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
@@ -23,7 +22,7 @@ impl Injector for HeaderInjector<'_> {
 impl HeaderInjector<'_> {
     /// Injects the context from the current span into the headers
     pub fn inject_context(&mut self) {
-        let ctx_propagator = TraceContextPropagator::new();
+        let ctx_propagator = crate::context::propagator();
         ctx_propagator.inject_context(&Span::current().context(), self);
     }
 }
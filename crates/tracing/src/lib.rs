@@ -5,25 +5,38 @@ use heck::ToKebabCase;
 #[cfg(feature = "otel")]
 pub use opentelemetry::{
     global,
-    metrics::{Counter, Gauge, Histogram, Meter, ObservableGauge, UpDownCounter},
+    metrics::{
+        Counter, Gauge, Histogram, Meter, ObservableCounter, ObservableGauge, UpDownCounter,
+    },
     InstrumentationScope, KeyValue,
 };
-use wasmcloud_core::logging::Level;
+use wasmcloud_core::logging::{Level, LogDirectives};
 #[cfg(feature = "otel")]
 use wasmcloud_core::tls;
 use wasmcloud_core::OtelConfig;
 
+#[cfg(feature = "otel")]
+mod backpressure;
 #[cfg(feature = "otel")]
 pub mod context;
 #[cfg(feature = "otel")]
+mod export;
+#[cfg(feature = "otel")]
 pub mod http;
+#[cfg(feature = "otel")]
+mod retry;
+#[cfg(feature = "otel")]
+mod scrub;
 
 mod traces;
 
+pub use traces::{capture_recent_logs, recent_logs};
 #[cfg(feature = "otel")]
-pub use traces::FlushGuard;
+pub use traces::{reload_handle, FlushGuard, ReloadHandle};
 
 mod metrics;
+#[cfg(feature = "otel")]
+pub use metrics::InvocationMetrics;
 
 #[cfg(not(feature = "otel"))]
 pub fn configure_observability(
@@ -32,6 +45,7 @@ pub fn configure_observability(
     use_structured_logging: bool,
     flame_graph: Option<impl AsRef<Path>>,
     log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
 ) -> anyhow::Result<(tracing::Dispatch, traces::FlushGuard)> {
     // if OTEL is not enabled, explicitly do not emit observability
     let otel_config = OtelConfig::default();
@@ -41,6 +55,7 @@ pub fn configure_observability(
         use_structured_logging,
         flame_graph,
         log_level_override,
+        log_directives,
     )
 }
 
@@ -52,6 +67,7 @@ pub fn configure_observability(
     use_structured_logging: bool,
     flame_graph: Option<impl AsRef<Path>>,
     log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
     trace_level_override: Option<&Level>,
 ) -> anyhow::Result<(tracing::Dispatch, traces::FlushGuard)> {
     let normalized_service_name = service_name.to_kebab_case();
@@ -66,10 +82,65 @@ pub fn configure_observability(
         use_structured_logging,
         flame_graph,
         log_level_override,
+        log_directives,
         trace_level_override,
     )
 }
 
+/// Builds the resource attached to all exported telemetry.
+///
+/// Attributes configured in [`OtelConfig`] take precedence over the ones detected from the
+/// environment, and a configured service name takes precedence over `service_name`.
+#[cfg(feature = "otel")]
+pub(crate) fn get_resource(
+    service_name: &str,
+    otel_config: &OtelConfig,
+) -> opentelemetry_sdk::Resource {
+    use opentelemetry::KeyValue;
+
+    let mut builder = opentelemetry_sdk::Resource::builder_empty()
+        .with_detector(Box::new(
+            opentelemetry_sdk::resource::EnvResourceDetector::new(),
+        ))
+        .with_attributes(
+            otel_config
+                .resource_attributes
+                .iter()
+                .map(|(key, value)| KeyValue::new(key.clone(), value.clone())),
+        );
+    if let Some(namespace) = &otel_config.service_namespace {
+        builder = builder.with_attribute(KeyValue::new("service.namespace", namespace.clone()));
+    }
+    builder
+        .with_attribute(KeyValue::new(
+            "service.name",
+            otel_config
+                .service_name
+                .clone()
+                .unwrap_or_else(|| service_name.to_string()),
+        ))
+        .build()
+}
+
+/// Converts headers to the gRPC metadata sent by the tonic exporters
+#[cfg(feature = "otel")]
+pub(crate) fn get_grpc_metadata(
+    headers: std::collections::HashMap<String, String>,
+) -> anyhow::Result<tonic::metadata::MetadataMap> {
+    use anyhow::Context as _;
+
+    let headers = headers
+        .into_iter()
+        .map(|(name, value)| {
+            let name = ::http::HeaderName::try_from(name).context("invalid otel header name")?;
+            let value = ::http::HeaderValue::try_from(value)
+                .with_context(|| format!("invalid value of otel header `{name}`"))?;
+            Ok((name, value))
+        })
+        .collect::<anyhow::Result<::http::HeaderMap>>()?;
+    Ok(tonic::metadata::MetadataMap::from_headers(headers))
+}
+
 /// Configures a reqwest http client with additional certificates
 #[cfg(feature = "otel")]
 pub(crate) fn get_http_client(otel_config: &OtelConfig) -> anyhow::Result<reqwest::Client> {
@@ -8,48 +8,54 @@ pub fn configure_metrics(
     service_name: &str,
     otel_config: &wasmcloud_core::OtelConfig,
 ) -> anyhow::Result<()> {
-    use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+    use opentelemetry_otlp::{WithExportConfig, WithHttpConfig, WithTonicConfig};
     use opentelemetry_sdk::metrics::{
         periodic_reader_with_async_runtime::PeriodicReader, SdkMeterProvider,
     };
     use wasmcloud_core::OtelProtocol;
 
-    let exporter = match otel_config.protocol {
-        OtelProtocol::Http => {
-            let client = crate::get_http_client(otel_config)
-                .context("failed to get an http client for otel metrics exporter")?;
-            opentelemetry_otlp::MetricExporter::builder()
-                .with_http()
-                .with_http_client(client)
-                .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
-                .with_endpoint(otel_config.metrics_endpoint())
-                .build()
-                .context("failed to create OTEL http exporter")?
-        }
-        OtelProtocol::Grpc => {
-            // TODO(joonas): Configure tonic::transport::ClientTlsConfig via .with_tls_config(...), passing in additional certificates.
-            opentelemetry_otlp::MetricExporter::builder()
-                .with_tonic()
-                .with_endpoint(otel_config.metrics_endpoint())
-                .build()
-                .context("failed to create OTEL tonic exporter")?
+    let retry_policy = otel_config.export_retry_policy();
+    let reader = if let Some(exporter) = crate::export::JsonExporter::new(&otel_config.exporter)? {
+        PeriodicReader::builder(exporter, opentelemetry_sdk::runtime::Tokio).build()
+    } else {
+        let headers = otel_config
+            .metrics_headers()
+            .context("failed to resolve headers for otel metrics exporter")?;
+        let exporter = match otel_config.metrics_protocol() {
+            OtelProtocol::Http => {
+                let client = crate::get_http_client(otel_config)
+                    .context("failed to get an http client for otel metrics exporter")?;
+                opentelemetry_otlp::MetricExporter::builder()
+                    .with_http()
+                    .with_http_client(client)
+                    .with_headers(headers)
+                    .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
+                    .with_endpoint(otel_config.metrics_endpoint())
+                    .build()
+                    .context("failed to create OTEL http exporter")?
+            }
+            OtelProtocol::Grpc => {
+                // TODO(joonas): Configure tonic::transport::ClientTlsConfig via .with_tls_config(...), passing in additional certificates.
+                opentelemetry_otlp::MetricExporter::builder()
+                    .with_tonic()
+                    .with_metadata(crate::get_grpc_metadata(headers)?)
+                    .with_endpoint(otel_config.metrics_endpoint())
+                    .build()
+                    .context("failed to create OTEL tonic exporter")?
+            }
+        };
+        let mut reader = PeriodicReader::builder(
+            crate::retry::RetryingMetricExporter::new(exporter, retry_policy),
+            opentelemetry_sdk::runtime::Tokio,
+        );
+        if let Some(export_timeout) = crate::retry::export_timeout(&retry_policy) {
+            reader = reader.with_timeout(export_timeout);
         }
+        reader.build()
     };
 
-    let reader = PeriodicReader::builder(exporter, opentelemetry_sdk::runtime::Tokio).build();
-
     let meter_provider = SdkMeterProvider::builder()
-        .with_resource(
-            opentelemetry_sdk::Resource::builder_empty()
-                .with_detector(Box::new(
-                    opentelemetry_sdk::resource::EnvResourceDetector::new(),
-                ))
-                .with_attribute(opentelemetry::KeyValue::new(
-                    "service.name",
-                    service_name.to_string(),
-                ))
-                .build(),
-        )
+        .with_resource(crate::get_resource(service_name, otel_config))
         .with_reader(reader)
         .build();
 
@@ -57,3 +63,85 @@ pub fn configure_metrics(
 
     Ok(())
 }
+
+/// Instruments of the standard invocation metrics defined in [`wasmcloud_core::metrics`], shared
+/// by hosts and providers
+#[cfg(feature = "otel")]
+#[derive(Clone, Debug)]
+pub struct InvocationMetrics {
+    invocations: opentelemetry::metrics::Counter<u64>,
+    errors: opentelemetry::metrics::Counter<u64>,
+    duration: opentelemetry::metrics::Histogram<f64>,
+    params_size: opentelemetry::metrics::Histogram<u64>,
+}
+
+#[cfg(feature = "otel")]
+impl InvocationMetrics {
+    /// Creates the instruments with the given meter
+    #[must_use]
+    pub fn new(meter: &opentelemetry::metrics::Meter) -> Self {
+        use wasmcloud_core::metrics;
+
+        Self {
+            invocations: meter
+                .u64_counter(metrics::INVOCATIONS)
+                .with_description("Number of invocations")
+                .build(),
+            errors: meter
+                .u64_counter(metrics::INVOCATION_ERRORS)
+                .with_description("Number of failed invocations")
+                .build(),
+            duration: meter
+                .f64_histogram(metrics::INVOCATION_DURATION)
+                .with_description("Duration of served invocations")
+                .with_unit(metrics::DURATION_UNIT)
+                .build(),
+            params_size: meter
+                .u64_histogram(metrics::INVOCATION_PARAMS_SIZE)
+                .with_description("Size of the encoded parameters of sent invocations")
+                .with_unit(metrics::SIZE_UNIT)
+                .build(),
+        }
+    }
+
+    /// Converts the attributes of an invocation into the attributes to record metrics with
+    #[must_use]
+    pub fn attributes(
+        attributes: &wasmcloud_core::metrics::InvocationMetricAttributes<'_>,
+    ) -> Vec<opentelemetry::KeyValue> {
+        attributes
+            .to_pairs()
+            .into_iter()
+            .map(|(key, value)| opentelemetry::KeyValue::new(key, value))
+            .collect()
+    }
+
+    /// Records a served invocation, which took `elapsed` to handle
+    pub fn record_incoming(
+        &self,
+        attributes: &[opentelemetry::KeyValue],
+        elapsed: std::time::Duration,
+        error: bool,
+    ) {
+        self.invocations.add(1, attributes);
+        if error {
+            self.errors.add(1, attributes);
+        }
+        self.duration.record(elapsed.as_secs_f64(), attributes);
+    }
+
+    /// Records a sent invocation with `params_size` bytes of encoded parameters
+    pub fn record_outgoing(
+        &self,
+        attributes: &[opentelemetry::KeyValue],
+        params_size: usize,
+        error: bool,
+    ) {
+        self.invocations.add(1, attributes);
+        if error {
+            self.errors.add(1, attributes);
+        }
+        self.params_size
+            .record(u64::try_from(params_size).unwrap_or(u64::MAX), attributes);
+    }
+}
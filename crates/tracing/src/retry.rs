@@ -0,0 +1,191 @@
+//! Retries of failed telemetry exports with exponential backoff, as configured by
+//! [`OtelConfig::export_retry_policy`](wasmcloud_core::OtelConfig::export_retry_policy).
+//!
+//! The OpenTelemetry SDK leaves retries to the exporters and the OTLP exporters do not retry, so
+//! without these wrappers every batch exported while a collector is unavailable is lost.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::logs::{LogBatch, LogExporter};
+use opentelemetry_sdk::metrics::data::ResourceMetrics;
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::metrics::Temporality;
+use opentelemetry_sdk::runtime::{Runtime as _, Tokio};
+use opentelemetry_sdk::trace::{SpanData, SpanExporter};
+use opentelemetry_sdk::Resource;
+use wasmcloud_core::OtelRetryPolicy;
+
+use crate::backpressure::{QueueCapacity, QueueRelease};
+
+/// Timeout of a single export attempt, matching the default export timeout of the SDK
+const EXPORT_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Returns the timeout the batch processors and readers must allow for an export including all of
+/// its retries, or `None` if exports are not retried and the default of the SDK applies
+pub(crate) fn export_timeout(policy: &OtelRetryPolicy) -> Option<Duration> {
+    if policy.max_retries == 0 {
+        return None;
+    }
+    Some(
+        EXPORT_ATTEMPT_TIMEOUT
+            .saturating_mul(policy.max_retries.saturating_add(1))
+            .saturating_add(policy.total_backoff()),
+    )
+}
+
+/// Returns true if a failed export may succeed when retried
+fn is_retryable(err: &OTelSdkError) -> bool {
+    !matches!(err, OTelSdkError::AlreadyShutdown)
+}
+
+/// Span exporter retrying failed exports of the wrapped exporter. The [`QueueCapacity`] taken by
+/// exported spans is released once their export finished, whether it succeeded or not.
+#[derive(Debug)]
+pub(crate) struct RetryingSpanExporter<E> {
+    inner: Arc<Mutex<E>>,
+    policy: OtelRetryPolicy,
+    capacity: Arc<QueueCapacity>,
+}
+
+impl<E: SpanExporter> RetryingSpanExporter<E> {
+    pub(crate) fn new(inner: E, policy: OtelRetryPolicy, capacity: Arc<QueueCapacity>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+            policy,
+            capacity,
+        }
+    }
+
+    fn inner(&self) -> std::sync::MutexGuard<'_, E> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<E: SpanExporter + 'static> SpanExporter for RetryingSpanExporter<E> {
+    fn export(
+        &mut self,
+        mut batch: Vec<SpanData>,
+    ) -> Pin<Box<dyn Future<Output = OTelSdkResult> + Send + 'static>> {
+        let inner = Arc::clone(&self.inner);
+        let policy = self.policy;
+        // Released on drop, so that capacity is also released if the export times out
+        let release = QueueRelease::new(Arc::clone(&self.capacity), batch.len());
+        Box::pin(async move {
+            let _release = release;
+            let mut retry = 0;
+            loop {
+                // Only clone the batch if it may have to be exported again
+                let attempt = if retry < policy.max_retries {
+                    batch.clone()
+                } else {
+                    std::mem::take(&mut batch)
+                };
+                let export = inner
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .export(attempt);
+                match export.await {
+                    Err(err) if retry < policy.max_retries && is_retryable(&err) => {
+                        Tokio.delay(policy.backoff(retry)).await;
+                        retry += 1;
+                    }
+                    res => return res,
+                }
+            }
+        })
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        self.inner().shutdown()
+    }
+
+    fn force_flush(&mut self) -> OTelSdkResult {
+        self.inner().force_flush()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner().set_resource(resource);
+    }
+}
+
+/// Log exporter retrying failed exports of the wrapped exporter
+#[derive(Debug)]
+pub(crate) struct RetryingLogExporter<E> {
+    inner: E,
+    policy: OtelRetryPolicy,
+}
+
+impl<E: LogExporter> RetryingLogExporter<E> {
+    pub(crate) fn new(inner: E, policy: OtelRetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl<E: LogExporter> LogExporter for RetryingLogExporter<E> {
+    async fn export(&self, batch: LogBatch<'_>) -> OTelSdkResult {
+        let records: Vec<_> = batch.iter().collect();
+        let mut retry = 0;
+        loop {
+            match self.inner.export(LogBatch::new(&records)).await {
+                Err(err) if retry < self.policy.max_retries && is_retryable(&err) => {
+                    Tokio.delay(self.policy.backoff(retry)).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    fn shutdown(&mut self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// Metric exporter retrying failed exports of the wrapped exporter
+#[derive(Debug)]
+pub(crate) struct RetryingMetricExporter<E> {
+    inner: E,
+    policy: OtelRetryPolicy,
+}
+
+impl<E: PushMetricExporter> RetryingMetricExporter<E> {
+    pub(crate) fn new(inner: E, policy: OtelRetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+#[async_trait::async_trait]
+impl<E: PushMetricExporter> PushMetricExporter for RetryingMetricExporter<E> {
+    async fn export(&self, metrics: &mut ResourceMetrics) -> OTelSdkResult {
+        let mut retry = 0;
+        loop {
+            match self.inner.export(metrics).await {
+                Err(err) if retry < self.policy.max_retries && is_retryable(&err) => {
+                    Tokio.delay(self.policy.backoff(retry)).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    async fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush().await
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.inner.temporality()
+    }
+}
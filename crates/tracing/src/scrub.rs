@@ -0,0 +1,94 @@
+//! Enforcement of the span limits and attribute redaction configured in [`OtelConfig`] before
+//! spans are exported
+
+use opentelemetry::{Context, KeyValue, StringValue, Value};
+use opentelemetry_sdk::error::OTelSdkResult;
+use opentelemetry_sdk::trace::{Span, SpanData, SpanProcessor};
+use opentelemetry_sdk::Resource;
+use wasmcloud_core::OtelConfig;
+
+/// Value replacing the values of redacted attributes
+const REDACTED: &str = "[REDACTED]";
+
+/// Span processor limiting the events and attribute lengths of ended spans and redacting
+/// attributes, before handing them to the wrapped processor for export
+#[derive(Debug)]
+pub(crate) struct ScrubbingSpanProcessor<P> {
+    inner: P,
+    otel_config: OtelConfig,
+}
+
+impl<P: SpanProcessor> ScrubbingSpanProcessor<P> {
+    pub(crate) fn new(inner: P, otel_config: &OtelConfig) -> Self {
+        Self {
+            inner,
+            otel_config: otel_config.clone(),
+        }
+    }
+
+    fn scrub_attributes(&self, attributes: &mut [KeyValue]) {
+        for KeyValue { key, value, .. } in attributes {
+            if self.otel_config.is_redacted_attribute(key.as_str()) {
+                *value = Value::String(REDACTED.into());
+                continue;
+            }
+            let Some(max_len) = self.otel_config.max_attribute_length else {
+                continue;
+            };
+            if let Value::String(s) = value {
+                if s.as_str().len() > max_len {
+                    *s = StringValue::from(truncate(s.as_str(), max_len).to_string());
+                }
+            }
+        }
+    }
+}
+
+impl<P: SpanProcessor> SpanProcessor for ScrubbingSpanProcessor<P> {
+    fn on_start(&self, span: &mut Span, cx: &Context) {
+        self.inner.on_start(span, cx);
+    }
+
+    fn on_end(&self, mut span: SpanData) {
+        if let Some(max_events) = self.otel_config.max_events_per_span {
+            let max_events = usize::try_from(max_events).unwrap_or(usize::MAX);
+            let events = &mut span.events;
+            if events.events.len() > max_events {
+                let dropped = events.events.len() - max_events;
+                events.events.truncate(max_events);
+                events.dropped_count = events
+                    .dropped_count
+                    .saturating_add(u32::try_from(dropped).unwrap_or(u32::MAX));
+            }
+        }
+        self.scrub_attributes(&mut span.attributes);
+        for event in &mut span.events.events {
+            self.scrub_attributes(&mut event.attributes);
+        }
+        for link in &mut span.links.links {
+            self.scrub_attributes(&mut link.attributes);
+        }
+        self.inner.on_end(span);
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.inner.force_flush()
+    }
+
+    fn shutdown(&self) -> OTelSdkResult {
+        self.inner.shutdown()
+    }
+
+    fn set_resource(&mut self, resource: &Resource) {
+        self.inner.set_resource(resource);
+    }
+}
+
+/// Truncates `s` to at most `max_len` bytes, without splitting a character
+fn truncate(s: &str, max_len: usize) -> &str {
+    let mut end = max_len.min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
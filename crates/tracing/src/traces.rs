@@ -1,9 +1,13 @@
+use std::collections::VecDeque;
 use std::env;
 use std::fs::File;
-use std::io::{BufWriter, IsTerminal};
+use std::io::{self, BufWriter, IsTerminal};
 use std::path::Path;
 #[cfg(feature = "otel")]
 use std::sync::Arc;
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "otel")]
+use std::sync::{PoisonError, RwLock};
 
 #[cfg(feature = "otel")]
 use anyhow::Context as _;
@@ -14,21 +18,217 @@ use tracing_flame::FlameLayer;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::fmt::format::{DefaultFields, Format, Full, Json, JsonFields, Writer};
 use tracing_subscriber::fmt::time::SystemTime;
+use tracing_subscriber::fmt::writer::{MakeWriterExt as _, OptionalWriter};
+use tracing_subscriber::fmt::MakeWriter;
 use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::registry::LookupSpan;
+#[cfg(feature = "otel")]
+use tracing_subscriber::reload;
 use tracing_subscriber::EnvFilter;
 #[cfg(feature = "otel")]
 use tracing_subscriber::Layer;
-use wasmcloud_core::logging::Level;
+use wasmcloud_core::logging::{Level, LogDirective, LogDirectives};
 use wasmcloud_core::OtelConfig;
 #[cfg(feature = "otel")]
 use wasmcloud_core::OtelProtocol;
 
+#[cfg(feature = "otel")]
+use crate::backpressure::{BackpressureSpanProcessor, QueueCapacity};
+#[cfg(feature = "otel")]
+use crate::export::JsonExporter;
+#[cfg(feature = "otel")]
+use crate::retry::{RetryingLogExporter, RetryingSpanExporter};
+#[cfg(feature = "otel")]
+use crate::scrub::ScrubbingSpanProcessor;
+
 #[cfg(feature = "otel")]
 static LOG_PROVIDER: once_cell::sync::OnceCell<opentelemetry_sdk::logs::SdkLoggerProvider> =
     once_cell::sync::OnceCell::new();
 
+#[cfg(feature = "otel")]
+static RELOAD_HANDLE: RwLock<Option<ReloadHandle>> = RwLock::new(None);
+
+/// Returns the [`ReloadHandle`] of the most recently configured subscriber, if any
+#[cfg(feature = "otel")]
+pub fn reload_handle() -> Option<ReloadHandle> {
+    RELOAD_HANDLE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Reloads a filter of the subscriber, erasing the type of the subscriber the filter applies to
+#[cfg(feature = "otel")]
+type ReloadFilterFn = Box<dyn Fn(EnvFilter) -> anyhow::Result<()> + Send + Sync>;
+
+/// Handle to the tracing configuration of a running process, which allows changing the trace
+/// level and sampler without restarting it
+#[cfg(feature = "otel")]
+#[derive(Clone)]
+pub struct ReloadHandle(Arc<ReloadState>);
+
+#[cfg(feature = "otel")]
+struct ReloadState {
+    otel_config: Mutex<OtelConfig>,
+    log_level: Option<Level>,
+    log_directives: LogDirectives,
+    global_filter: ReloadFilterFn,
+    trace_filter: Option<ReloadFilterFn>,
+    sampler: ReloadableSampler,
+}
+
+#[cfg(feature = "otel")]
+impl ReloadHandle {
+    /// Returns the configuration currently in effect
+    #[must_use]
+    pub fn otel_config(&self) -> OtelConfig {
+        self.0
+            .otel_config
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Applies the `trace_level`, `traces_sampler` and `traces_sampler_arg` of `otel_config`.
+    ///
+    /// Other settings, such as exporters and endpoints, only take effect on restart.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sampler is not supported or its argument is invalid, in which case
+    /// the configuration in effect is left unchanged
+    pub fn update(&self, otel_config: &OtelConfig) -> anyhow::Result<()> {
+        validate_sampler(otel_config)?;
+        let trace_level = Some(&otel_config.trace_level);
+        (self.0.global_filter)(get_global_filter(
+            self.0.log_level.as_ref(),
+            &self.0.log_directives,
+            trace_level,
+        ))?;
+        if let Some(reload) = &self.0.trace_filter {
+            reload(get_trace_level_filter(trace_level))?;
+        }
+        self.0.sampler.set(get_sampler(otel_config));
+
+        let mut current = self
+            .0
+            .otel_config
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        current.trace_level = otel_config.trace_level.clone();
+        current
+            .traces_sampler
+            .clone_from(&otel_config.traces_sampler);
+        current
+            .traces_sampler_arg
+            .clone_from(&otel_config.traces_sampler_arg);
+        Ok(())
+    }
+}
+
+/// Sampler delegating to a sampler that can be replaced at runtime
+#[cfg(feature = "otel")]
+#[derive(Clone, Debug)]
+struct ReloadableSampler(Arc<RwLock<opentelemetry_sdk::trace::Sampler>>);
+
+#[cfg(feature = "otel")]
+impl ReloadableSampler {
+    fn new(sampler: opentelemetry_sdk::trace::Sampler) -> Self {
+        Self(Arc::new(RwLock::new(sampler)))
+    }
+
+    fn set(&self, sampler: opentelemetry_sdk::trace::Sampler) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = sampler;
+    }
+}
+
+#[cfg(feature = "otel")]
+impl opentelemetry_sdk::trace::ShouldSample for ReloadableSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&opentelemetry::Context>,
+        trace_id: opentelemetry::trace::TraceId,
+        name: &str,
+        span_kind: &opentelemetry::trace::SpanKind,
+        attributes: &[opentelemetry::KeyValue],
+        links: &[opentelemetry::trace::Link],
+    ) -> opentelemetry::trace::SamplingResult {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .should_sample(parent_context, trace_id, name, span_kind, attributes, links)
+    }
+}
+
+/// Formatted log lines retained in memory for [`recent_logs`], once enabled by
+/// [`capture_recent_logs`]
+static RECENT_LOGS: OnceLock<RecentLogs> = OnceLock::new();
+
+/// Start retaining the last `capacity` log lines formatted by the subscribers configured by this
+/// crate, so that diagnostics such as crash reports can include the logs leading up to a failure.
+///
+/// Capturing is disabled by default, since it adds a lock and an allocation to every log line.
+/// Only the first call has an effect.
+pub fn capture_recent_logs(capacity: usize) {
+    RECENT_LOGS.get_or_init(|| RecentLogs {
+        capacity,
+        lines: Mutex::new(VecDeque::with_capacity(capacity)),
+    });
+}
+
+/// Returns the most recently emitted log lines, oldest first, which are empty unless enabled with
+/// [`capture_recent_logs`]
+pub fn recent_logs() -> Vec<String> {
+    RECENT_LOGS
+        .get()
+        .and_then(|logs| logs.lines.lock().ok())
+        .map(|lines| lines.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+struct RecentLogs {
+    capacity: usize,
+    lines: Mutex<VecDeque<String>>,
+}
+
+impl io::Write for &RecentLogs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Never block or fail logging because the buffer is poisoned
+        if let Ok(mut lines) = self.lines.lock() {
+            for line in String::from_utf8_lossy(buf).lines() {
+                if line.is_empty() || self.capacity == 0 {
+                    continue;
+                }
+                if lines.len() == self.capacity {
+                    lines.pop_front();
+                }
+                lines.push_back(line.to_string());
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writer that records formatted log lines into [`RECENT_LOGS`] if capturing is enabled and
+/// discards them otherwise
+#[derive(Clone, Copy, Default)]
+struct RecentLogsWriter;
+
+impl<'a> MakeWriter<'a> for RecentLogsWriter {
+    type Writer = OptionalWriter<&'static RecentLogs>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RECENT_LOGS
+            .get()
+            .map_or_else(OptionalWriter::none, OptionalWriter::some)
+    }
+}
+
 /// A struct that allows us to dynamically choose JSON formatting without using dynamic dispatch.
 /// This is just so we avoid any sort of possible slow down in logging code
 enum JsonOrNot {
@@ -78,17 +278,18 @@ pub fn configure_tracing(
     use_structured_logging: bool,
     flame_graph: Option<impl AsRef<Path>>,
     log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
 ) -> anyhow::Result<(tracing::Dispatch, FlushGuard)> {
     let flame = flame_graph.map(FlameLayer::with_file).transpose()?;
     let (flame, flame_guard) = flame.map(|(l, g)| (Some(l), Some(g))).unwrap_or_default();
     let reg = tracing_subscriber::Registry::default()
-        .with(get_log_level_filter(log_level_override))
+        .with(get_log_level_filter(log_level_override, log_directives))
         .with(flame);
     let stderr = std::io::stderr();
     let ansi = stderr.is_terminal();
     let (stderr, stderr_guard) = tracing_appender::non_blocking(stderr);
     let fmt = tracing_subscriber::fmt::layer()
-        .with_writer(stderr)
+        .with_writer(stderr.and(RecentLogsWriter))
         .with_ansi(ansi);
 
     let dispatch = if use_structured_logging {
@@ -130,24 +331,43 @@ pub fn configure_tracing(
     use_structured_logging: bool,
     flame_graph: Option<impl AsRef<Path>>,
     log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
     trace_level_override: Option<&Level>,
 ) -> anyhow::Result<(tracing::Dispatch, FlushGuard)> {
     let service_name = Arc::from(service_name);
 
-    let log_level_filter = get_log_level_filter(log_level_override);
+    let log_level_filter = get_log_level_filter(log_level_override, log_directives);
+    let (global_filter, global_filter_handle) = reload::Layer::new(get_global_filter(
+        log_level_override,
+        log_directives,
+        trace_level_override,
+    ));
+    let sampler = ReloadableSampler::new(get_sampler(otel_config));
+    let mut trace_filter_handle = None;
     let traces = otel_config
         .traces_enabled()
         .then(|| {
+            let (trace_level_filter, handle) =
+                reload::Layer::new(get_trace_level_filter(trace_level_override));
+            trace_filter_handle = Some(handle);
             get_otel_tracing_layer(
                 Arc::clone(&service_name),
                 otel_config,
-                get_trace_level_filter(trace_level_override),
+                trace_level_filter,
+                sampler.clone(),
             )
         })
         .transpose()?;
     let logs = otel_config
         .logs_enabled()
-        .then(|| get_otel_logging_layer(Arc::clone(&service_name), otel_config, log_level_override))
+        .then(|| {
+            get_otel_logging_layer(
+                Arc::clone(&service_name),
+                otel_config,
+                log_level_override,
+                log_directives,
+            )
+        })
         .transpose()?;
     let flame = flame_graph.map(FlameLayer::with_file).transpose()?;
     let (flame, flame_guard) = flame
@@ -159,7 +379,7 @@ pub fn configure_tracing(
         })
         .unwrap_or_default();
     let registry = tracing_subscriber::Registry::default()
-        .with(get_log_level_filter(log_level_override))
+        .with(global_filter)
         .with(traces)
         .with(logs)
         .with(flame);
@@ -167,7 +387,7 @@ pub fn configure_tracing(
     let ansi = stderr.is_terminal();
     let (stderr, stderr_guard) = tracing_appender::non_blocking(stderr);
     let fmt = tracing_subscriber::fmt::layer()
-        .with_writer(stderr)
+        .with_writer(stderr.and(RecentLogsWriter))
         .with_ansi(ansi);
 
     let dispatch = if use_structured_logging {
@@ -188,6 +408,28 @@ pub fn configure_tracing(
             .into()
     };
 
+    let trace_filter = trace_filter_handle.map(|handle| -> ReloadFilterFn {
+        Box::new(move |filter| {
+            handle
+                .reload(filter)
+                .context("failed to reload trace level filter")
+        })
+    });
+    *RELOAD_HANDLE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(ReloadHandle(Arc::new(ReloadState {
+        otel_config: Mutex::new(otel_config.clone()),
+        log_level: log_level_override.cloned(),
+        log_directives: log_directives.clone(),
+        global_filter: Box::new(move |filter| {
+            global_filter_handle
+                .reload(filter)
+                .context("failed to reload global level filter")
+        }),
+        trace_filter,
+        sampler,
+    })));
+
     Ok((
         dispatch,
         FlushGuard {
@@ -198,47 +440,133 @@ pub fn configure_tracing(
 }
 
 #[cfg(feature = "otel")]
-fn get_otel_tracing_layer<S>(
+fn get_otel_tracing_layer<S, F>(
     service_name: Arc<str>,
     otel_config: &OtelConfig,
-    trace_level_filter: EnvFilter,
+    trace_level_filter: F,
+    sampler: ReloadableSampler,
 ) -> anyhow::Result<impl tracing_subscriber::Layer<S>>
 where
     S: Subscriber,
     S: for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    F: tracing_subscriber::layer::Filter<S> + Send + Sync + 'static,
 {
     use opentelemetry::trace::TracerProvider as _;
-    use opentelemetry_otlp::WithHttpConfig;
-    use opentelemetry_sdk::trace::{BatchConfigBuilder, Sampler};
+    use opentelemetry_otlp::{WithHttpConfig, WithTonicConfig};
     use tracing_opentelemetry::OpenTelemetryLayer;
 
-    let exporter = match otel_config.protocol {
-        OtelProtocol::Http => {
-            let client = crate::get_http_client(otel_config)
-                .context("failed to get an http client for otel tracing exporter")?;
-            opentelemetry_otlp::SpanExporter::builder()
-                .with_http()
-                .with_http_client(client)
-                .with_endpoint(otel_config.traces_endpoint())
-                .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
-                .build()
-                .context("failed to build OTEL span exporter")?
+    let capacity = Arc::new(QueueCapacity::new(otel_config));
+    let processor = if let Some(exporter) = JsonExporter::new(&otel_config.exporter)? {
+        get_batch_span_processor(exporter, otel_config, &capacity)
+    } else {
+        let headers = otel_config
+            .traces_headers()
+            .context("failed to resolve headers for otel tracing exporter")?;
+        let exporter = match otel_config.traces_protocol() {
+            OtelProtocol::Http => {
+                let client = crate::get_http_client(otel_config)
+                    .context("failed to get an http client for otel tracing exporter")?;
+                opentelemetry_otlp::SpanExporter::builder()
+                    .with_http()
+                    .with_http_client(client)
+                    .with_headers(headers)
+                    .with_endpoint(otel_config.traces_endpoint())
+                    .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
+                    .build()
+                    .context("failed to build OTEL span exporter")?
+            }
+            OtelProtocol::Grpc => {
+                // TODO(joonas): Configure tonic::transport::ClientTlsConfig via .with_tls_config(...), passing in additional certificates.
+                opentelemetry_otlp::SpanExporter::builder()
+                    .with_tonic()
+                    .with_metadata(crate::get_grpc_metadata(headers)?)
+                    .with_endpoint(otel_config.traces_endpoint())
+                    .build()
+                    .context("failed to build OTEL span exporter")?
+            }
+        };
+        get_batch_span_processor(exporter, otel_config, &capacity)
+    };
+    let processor = BackpressureSpanProcessor::new(processor, capacity);
+
+    let tracer = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_sampler(sampler)
+        .with_resource(crate::get_resource(&service_name, otel_config))
+        .with_span_processor(ScrubbingSpanProcessor::new(processor, otel_config))
+        .build()
+        .tracer("wasmcloud-tracing");
+
+    Ok(OpenTelemetryLayer::new(tracer).with_filter(trace_level_filter))
+}
+
+#[cfg(feature = "otel")]
+fn get_batch_span_processor(
+    exporter: impl opentelemetry_sdk::trace::SpanExporter + 'static,
+    otel_config: &OtelConfig,
+    capacity: &Arc<QueueCapacity>,
+) -> opentelemetry_sdk::trace::span_processor_with_async_runtime::BatchSpanProcessor<
+    opentelemetry_sdk::runtime::Tokio,
+> {
+    use opentelemetry_sdk::trace::BatchConfigBuilder;
+
+    let retry_policy = otel_config.export_retry_policy();
+    let mut batch_builder = BatchConfigBuilder::default();
+    if let Some(max_batch_queue_size) = otel_config.max_batch_queue_size {
+        batch_builder = batch_builder.with_max_queue_size(max_batch_queue_size);
+    }
+    if let Some(concurrent_exports) = otel_config.concurrent_exports {
+        batch_builder = batch_builder.with_max_concurrent_exports(concurrent_exports);
+    }
+    if let Some(export_timeout) = crate::retry::export_timeout(&retry_policy) {
+        batch_builder = batch_builder.with_max_export_timeout(export_timeout);
+    }
+    let batch_config = batch_builder.build();
+
+    opentelemetry_sdk::trace::span_processor_with_async_runtime::BatchSpanProcessor::builder(
+        RetryingSpanExporter::new(exporter, retry_policy, Arc::clone(capacity)),
+        opentelemetry_sdk::runtime::Tokio,
+    )
+    .with_batch_config(batch_config)
+    .build()
+}
+
+/// Checks that the sampler configured by the `traces_sampler` and `traces_sampler_arg` settings is
+/// supported, rather than falling back to the default like [`get_sampler`]
+#[cfg(feature = "otel")]
+fn validate_sampler(otel_config: &OtelConfig) -> anyhow::Result<()> {
+    match otel_config.traces_sampler.as_deref() {
+        None
+        | Some("always_on" | "always_off" | "parentbased_always_on" | "parentbased_always_off") => {
+            Ok(())
         }
-        OtelProtocol::Grpc => {
-            // TODO(joonas): Configure tonic::transport::ClientTlsConfig via .with_tls_config(...), passing in additional certificates.
-            opentelemetry_otlp::SpanExporter::builder()
-                .with_tonic()
-                .with_endpoint(otel_config.traces_endpoint())
-                .build()
-                .context("failed to build OTEL span exporter")?
+        Some("traceidratio" | "parentbased_traceidratio") => {
+            let arg = otel_config
+                .traces_sampler_arg
+                .as_deref()
+                .unwrap_or_default();
+            let ratio = arg
+                .parse::<f64>()
+                .with_context(|| format!("invalid traces sampler ratio `{arg}`"))?;
+            anyhow::ensure!(
+                (0.0..=1.0).contains(&ratio),
+                "traces sampler ratio must be between 0 and 1"
+            );
+            Ok(())
         }
-    };
+        Some(sampler) => anyhow::bail!("unsupported traces sampler `{sampler}`"),
+    }
+}
+
+/// Returns the sampler configured by the `traces_sampler` and `traces_sampler_arg` settings
+#[cfg(feature = "otel")]
+fn get_sampler(otel_config: &OtelConfig) -> opentelemetry_sdk::trace::Sampler {
+    use opentelemetry_sdk::trace::Sampler;
 
     // NOTE(thomastaylor312): This is copied and modified from the opentelemetry-sdk crate. We
     // currently need this because providers map config back into the vars needed to configure the
     // SDK. When we update providers to be managed externally and remove host-managed ones, we can
     // remove this. But for now we need to parse all the possible options
-    let sampler = match otel_config.traces_sampler.as_deref() {
+    match otel_config.traces_sampler.as_deref() {
         Some("always_on") => Sampler::AlwaysOn,
         Some("always_off") => Sampler::AlwaysOff,
         Some("traceidratio") => {
@@ -272,43 +600,7 @@ where
             Sampler::ParentBased(Box::new(Sampler::AlwaysOn))
         }
         None => Sampler::ParentBased(Box::new(Sampler::AlwaysOn)),
-    };
-
-    let mut batch_builder = BatchConfigBuilder::default();
-    if let Some(max_batch_queue_size) = otel_config.max_batch_queue_size {
-        batch_builder = batch_builder.with_max_queue_size(max_batch_queue_size);
     }
-    if let Some(concurrent_exports) = otel_config.concurrent_exports {
-        batch_builder = batch_builder.with_max_concurrent_exports(concurrent_exports);
-    }
-    let batch_config = batch_builder.build();
-
-    let processor =
-        opentelemetry_sdk::trace::span_processor_with_async_runtime::BatchSpanProcessor::builder(
-            exporter,
-            opentelemetry_sdk::runtime::Tokio,
-        )
-        .with_batch_config(batch_config)
-        .build();
-
-    let tracer = opentelemetry_sdk::trace::SdkTracerProvider::builder()
-        .with_sampler(sampler)
-        .with_resource(
-            opentelemetry_sdk::Resource::builder_empty()
-                .with_detector(Box::new(
-                    opentelemetry_sdk::resource::EnvResourceDetector::new(),
-                ))
-                .with_attribute(opentelemetry::KeyValue::new(
-                    "service.name",
-                    service_name.to_string(),
-                ))
-                .build(),
-        )
-        .with_span_processor(processor)
-        .build()
-        .tracer("wasmcloud-tracing");
-
-    Ok(OpenTelemetryLayer::new(tracer).with_filter(trace_level_filter))
 }
 
 #[cfg(feature = "otel")]
@@ -316,54 +608,48 @@ fn get_otel_logging_layer<S>(
     service_name: Arc<str>,
     otel_config: &OtelConfig,
     log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
 ) -> anyhow::Result<impl tracing_subscriber::Layer<S>>
 where
     S: Subscriber,
     S: for<'a> tracing_subscriber::registry::LookupSpan<'a>,
 {
-    use opentelemetry_otlp::WithHttpConfig;
-
-    let exporter = match otel_config.protocol {
-        OtelProtocol::Http => {
-            let client = crate::get_http_client(otel_config)
-                .context("failed to get an http client for otel logging exporter")?;
-            opentelemetry_otlp::LogExporter::builder()
-                .with_http()
-                .with_http_client(client)
-                .with_endpoint(otel_config.logs_endpoint())
-                .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
-                .build()
-                .context("failed to create OTEL http log exporter")?
-        }
-        OtelProtocol::Grpc => {
-            // TODO(joonas): Configure tonic::transport::ClientTlsConfig via .with_tls_config(...), passing in additional certificates.
-            opentelemetry_otlp::LogExporter::builder()
-                .with_tonic()
-                .with_endpoint(otel_config.logs_endpoint())
-                .build()
-                .context("failed to create OTEL http log exporter")?
-        }
-    };
+    use opentelemetry_otlp::{WithHttpConfig, WithTonicConfig};
 
-    let processor =
-        opentelemetry_sdk::logs::log_processor_with_async_runtime::BatchLogProcessor::builder(
-            exporter,
-            opentelemetry_sdk::runtime::Tokio,
-        )
-        .build();
+    let processor = if let Some(exporter) = JsonExporter::new(&otel_config.exporter)? {
+        get_batch_log_processor(exporter, otel_config)
+    } else {
+        let headers = otel_config
+            .logs_headers()
+            .context("failed to resolve headers for otel logging exporter")?;
+        let exporter = match otel_config.logs_protocol() {
+            OtelProtocol::Http => {
+                let client = crate::get_http_client(otel_config)
+                    .context("failed to get an http client for otel logging exporter")?;
+                opentelemetry_otlp::LogExporter::builder()
+                    .with_http()
+                    .with_http_client(client)
+                    .with_headers(headers)
+                    .with_endpoint(otel_config.logs_endpoint())
+                    .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
+                    .build()
+                    .context("failed to create OTEL http log exporter")?
+            }
+            OtelProtocol::Grpc => {
+                // TODO(joonas): Configure tonic::transport::ClientTlsConfig via .with_tls_config(...), passing in additional certificates.
+                opentelemetry_otlp::LogExporter::builder()
+                    .with_tonic()
+                    .with_metadata(crate::get_grpc_metadata(headers)?)
+                    .with_endpoint(otel_config.logs_endpoint())
+                    .build()
+                    .context("failed to create OTEL http log exporter")?
+            }
+        };
+        get_batch_log_processor(exporter, otel_config)
+    };
 
     let log_provider = opentelemetry_sdk::logs::SdkLoggerProvider::builder()
-        .with_resource(
-            opentelemetry_sdk::Resource::builder_empty()
-                .with_detector(Box::new(
-                    opentelemetry_sdk::resource::EnvResourceDetector::new(),
-                ))
-                .with_attribute(opentelemetry::KeyValue::new(
-                    "service.name",
-                    service_name.to_string(),
-                ))
-                .build(),
-        )
+        .with_resource(crate::get_resource(&service_name, otel_config))
         .with_log_processor(processor)
         .build();
 
@@ -375,11 +661,34 @@ where
     let log_layer = opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge::new(
         LOG_PROVIDER.get().unwrap(),
     )
-    .with_filter(get_log_level_filter(log_level_override));
+    .with_filter(get_log_level_filter(log_level_override, log_directives));
 
     Ok(log_layer)
 }
 
+#[cfg(feature = "otel")]
+fn get_batch_log_processor(
+    exporter: impl opentelemetry_sdk::logs::LogExporter + 'static,
+    otel_config: &OtelConfig,
+) -> opentelemetry_sdk::logs::log_processor_with_async_runtime::BatchLogProcessor<
+    opentelemetry_sdk::runtime::Tokio,
+> {
+    use opentelemetry_sdk::logs::BatchConfigBuilder;
+
+    let retry_policy = otel_config.export_retry_policy();
+    let mut batch_builder = BatchConfigBuilder::default();
+    if let Some(export_timeout) = crate::retry::export_timeout(&retry_policy) {
+        batch_builder = batch_builder.with_max_export_timeout(export_timeout);
+    }
+
+    opentelemetry_sdk::logs::log_processor_with_async_runtime::BatchLogProcessor::builder(
+        RetryingLogExporter::new(exporter, retry_policy),
+        opentelemetry_sdk::runtime::Tokio,
+    )
+    .with_batch_config(batch_builder.build())
+    .build()
+}
+
 #[cfg(feature = "otel")]
 fn get_trace_level_filter(trace_level_override: Option<&Level>) -> EnvFilter {
     if let Some(trace_level) = trace_level_override {
@@ -390,7 +699,29 @@ fn get_trace_level_filter(trace_level_override: Option<&Level>) -> EnvFilter {
     }
 }
 
-fn get_log_level_filter(log_level_override: Option<&Level>) -> EnvFilter {
+/// Returns the filter applied to all layers, which lets through the events of the log level and
+/// the spans of the trace level, whichever is more verbose. The log level defaults to `INFO`,
+/// like in [`get_log_level_filter`]
+#[cfg(feature = "otel")]
+fn get_global_filter(
+    log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
+    trace_level_override: Option<&Level>,
+) -> EnvFilter {
+    let filter = get_log_level_filter(log_level_override, log_directives);
+    let log_level = log_level_override.map_or(LevelFilter::INFO, wasi_level_to_tracing_level);
+    match trace_level_override.map(wasi_level_to_tracing_level) {
+        Some(trace_level) if trace_level > log_level => filter.add_directive(trace_level.into()),
+        _ => filter,
+    }
+}
+
+/// Returns the filter of the log level, with `log_directives` applied on top of the default
+/// directives and `RUST_LOG` applied on top of `log_directives`
+fn get_log_level_filter(
+    log_level_override: Option<&Level>,
+    log_directives: &LogDirectives,
+) -> EnvFilter {
     if let Some(log_level) = log_level_override {
         let level = wasi_level_to_tracing_level(log_level);
         // SAFETY: We can unwrap here because we control all inputs
@@ -402,6 +733,7 @@ fn get_log_level_filter(log_level_override: Option<&Level>) -> EnvFilter {
             .add_directive("cranelift_codegen=warn".parse().unwrap())
             .add_directive("hyper=info".parse().unwrap())
             .add_directive("oci_client=info".parse().unwrap());
+        filter = add_log_directives(filter, log_directives);
 
         // Allow RUST_LOG to override the other directives
         if let Ok(rust_log) = env::var("RUST_LOG") {
@@ -423,10 +755,31 @@ fn get_log_level_filter(log_level_override: Option<&Level>) -> EnvFilter {
 
         filter
     } else {
-        EnvFilter::default().add_directive(LevelFilter::INFO.into())
+        add_log_directives(
+            EnvFilter::default().add_directive(LevelFilter::INFO.into()),
+            log_directives,
+        )
     }
 }
 
+fn add_log_directives(mut filter: EnvFilter, log_directives: &LogDirectives) -> EnvFilter {
+    for LogDirective { target, level } in log_directives.iter() {
+        let level = wasi_level_to_tracing_level(level);
+        let directive = match target {
+            Some(target) => match format!("{target}={level}").parse() {
+                Ok(directive) => directive,
+                Err(err) => {
+                    eprintln!("ERROR: Ignoring invalid log directive for `{target}`: {err}");
+                    continue;
+                }
+            },
+            None => level.into(),
+        };
+        filter = filter.add_directive(directive);
+    }
+    filter
+}
+
 fn wasi_level_to_tracing_level(level: &Level) -> LevelFilter {
     match level {
         Level::Error | Level::Critical => LevelFilter::ERROR,
@@ -436,3 +789,108 @@ fn wasi_level_to_tracing_level(level: &Level) -> LevelFilter {
         Level::Trace => LevelFilter::TRACE,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::*;
+
+    #[test]
+    fn recent_logs_are_captured_once_enabled() -> io::Result<()> {
+        RecentLogsWriter.make_writer().write_all(b"before\n")?;
+        assert!(recent_logs().is_empty());
+
+        capture_recent_logs(2);
+        // Only the first call has an effect
+        capture_recent_logs(10);
+        let mut writer = RecentLogsWriter.make_writer();
+        writer.write_all(b"one\n\ntwo\n")?;
+        writer.write_all(b"three\n")?;
+        assert_eq!(recent_logs(), ["two", "three"]);
+        Ok(())
+    }
+
+    #[cfg(feature = "otel")]
+    fn sampler_config(sampler: &str, arg: Option<&str>) -> OtelConfig {
+        OtelConfig {
+            traces_sampler: Some(sampler.to_string()),
+            traces_sampler_arg: arg.map(ToString::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn samplers_are_validated() {
+        assert!(validate_sampler(&OtelConfig::default()).is_ok());
+        for sampler in [
+            "always_on",
+            "always_off",
+            "parentbased_always_on",
+            "parentbased_always_off",
+        ] {
+            assert!(validate_sampler(&sampler_config(sampler, None)).is_ok());
+        }
+        for sampler in ["traceidratio", "parentbased_traceidratio"] {
+            assert!(validate_sampler(&sampler_config(sampler, Some("0.5"))).is_ok());
+            assert!(validate_sampler(&sampler_config(sampler, Some("1.5"))).is_err());
+            assert!(validate_sampler(&sampler_config(sampler, Some("half"))).is_err());
+            assert!(validate_sampler(&sampler_config(sampler, None)).is_err());
+        }
+        assert!(validate_sampler(&sampler_config("jaeger_remote", None)).is_err());
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn trace_level_can_be_raised_and_lowered() -> anyhow::Result<()> {
+        let global_filters = Arc::new(Mutex::new(Vec::new()));
+        let trace_filters = Arc::new(Mutex::new(Vec::new()));
+        let record = |filters: &Arc<Mutex<Vec<String>>>| -> ReloadFilterFn {
+            let filters = Arc::clone(filters);
+            Box::new(move |filter: EnvFilter| {
+                filters.lock().unwrap().push(filter.to_string());
+                Ok(())
+            })
+        };
+        let handle = ReloadHandle(Arc::new(ReloadState {
+            otel_config: Mutex::new(OtelConfig::default()),
+            log_level: None,
+            log_directives: LogDirectives::default(),
+            global_filter: record(&global_filters),
+            trace_filter: Some(record(&trace_filters)),
+            sampler: ReloadableSampler::new(opentelemetry_sdk::trace::Sampler::AlwaysOn),
+        }));
+
+        handle.update(&OtelConfig {
+            trace_level: Level::Trace,
+            ..sampler_config("traceidratio", Some("0.1"))
+        })?;
+        // Spans are let through even though the log level defaults to `INFO`
+        assert!(global_filters.lock().unwrap()[0].contains("trace"));
+        assert!(trace_filters.lock().unwrap()[0].contains("trace"));
+        assert_eq!(handle.otel_config().trace_level, Level::Trace);
+
+        handle.update(&OtelConfig {
+            trace_level: Level::Warn,
+            ..sampler_config("always_on", None)
+        })?;
+        assert!(!global_filters.lock().unwrap()[1].contains("trace"));
+        assert!(trace_filters.lock().unwrap()[1].contains("warn"));
+        let config = handle.otel_config();
+        assert_eq!(config.trace_level, Level::Warn);
+        assert_eq!(config.traces_sampler.as_deref(), Some("always_on"));
+        assert_eq!(config.traces_sampler_arg, None);
+
+        // Invalid samplers leave the configuration in effect unchanged
+        assert!(handle
+            .update(&OtelConfig {
+                trace_level: Level::Debug,
+                ..sampler_config("traceidratio", Some("2"))
+            })
+            .is_err());
+        assert_eq!(global_filters.lock().unwrap().len(), 2);
+        assert_eq!(handle.otel_config().trace_level, Level::Warn);
+        Ok(())
+    }
+}
@@ -34,8 +34,16 @@ use wash::cli::app::{self, AppCliCommand};
 use wash::cli::build::{self, BuildCommand};
 use wash::cli::call::{self, CallCli};
 use wash::cli::cmd::config::{self, ConfigCliCommand};
+use wash::cli::cmd::demo::{self, DemoCommand};
 use wash::cli::cmd::dev::{self, DevCommand};
+use wash::cli::cmd::doctor::{self, DoctorCommand};
+use wash::cli::cmd::events::{self, EventsCommand};
 use wash::cli::cmd::link;
+use wash::cli::cmd::loadgen::{self, LoadgenCommand};
+use wash::cli::cmd::policy::{self, PolicyCommand};
+use wash::cli::cmd::profile::{self, ProfileCommand};
+use wash::cli::cmd::repl::{self, ReplCommand};
+use wash::cli::cmd::route::{self, RouteCommand};
 use wash::cli::cmd::up::{self, UpCommand};
 use wash::cli::cmd::wit::{self, WitCommand};
 use wash::cli::common;
@@ -149,6 +157,7 @@ ________________________________________________________________________________
                 ("app", "Manage declarative applications and deployments (wadm)"),
                 ("spy", "Spy on all invocations a component sends and receives"),
                 ("ui", "Serve a web UI for wasmCloud"),
+                ("demo", "Launch a sample application on a local wasmCloud environment"),
             ],
         },
         HelpTopic {
@@ -174,8 +183,25 @@ ________________________________________________________________________________
                 ),
                 (
                     "secrets",
-                    "Create secret references for components, capability providers and links",
+                    "Manage secrets for components, capability providers and links",
                 ),
+                (
+                    "repl",
+                    "Start an interactive session with a persistent connection to a lattice",
+                ),
+                (
+                    "profile",
+                    "Profile the performance of a component running in a host",
+                ),
+                (
+                    "route",
+                    "Split the traffic sent to a link target between component versions",
+                ),
+                (
+                    "loadgen",
+                    "Generate HTTP load against a component to measure its latency",
+                ),
+                ("events", "Show lattice events, live or replayed from history"),
             ],
         },
         HelpTopic {
@@ -190,10 +216,12 @@ ________________________________________________________________________________
             commands: vec![
                 ("completions", "Generate shell completions for wash"),
                 ("ctx", "Manage wasmCloud host configuration contexts"),
+                ("doctor", "Check the local environment for common problems and suggest fixes"),
                 ("drain", "Manage contents of local wasmCloud caches"),
                 ("keys", "Generate and manage signing keys"),
                 ("claims", "Generate and manage JWTs for wasmCloud components and capability providers"),
                 ("plugin", "Manage wash plugins"),
+                ("policy", "Manage the policy pack wash enforces for your organization"),
             ],
         },
         HelpTopic {
@@ -314,15 +342,24 @@ enum CliCommand {
     /// Manage wasmCloud host configuration contexts
     #[clap(name = "ctx", alias = "context", alias = "contexts", subcommand)]
     Ctx(CtxCommand),
+    /// Launch a sample application on a local wasmCloud environment
+    #[clap(name = "demo")]
+    Demo(DemoCommand),
     /// Start a developer loop to hot-reload a local wasmCloud component
     #[clap(name = "dev")]
     Dev(DevCommand),
+    /// Check the local environment for common problems and suggest fixes
+    #[clap(name = "doctor")]
+    Doctor(DoctorCommand),
     /// Tear down a local wasmCloud environment (launched with wash up)
     #[clap(name = "down")]
     Down(DownCommand),
     /// Manage contents of local wasmCloud caches
     #[clap(name = "drain", subcommand)]
     Drain(DrainSelection),
+    /// Show lattice events, live or replayed from history
+    #[clap(name = "events")]
+    Events(EventsCommand),
     /// Get information about different running wasmCloud resources
     #[clap(name = "get", subcommand)]
     Get(GetCommand),
@@ -335,6 +372,9 @@ enum CliCommand {
     /// Link one component to another on a set of interfaces
     #[clap(name = "link", alias = "links", subcommand)]
     Link(LinkCommand),
+    /// Generate HTTP load against a component to measure its latency
+    #[clap(name = "loadgen")]
+    Loadgen(LoadgenCommand),
     /// Create a new project from a template or git repository
     #[clap(name = "new", subcommand)]
     New(NewCliCommand),
@@ -344,13 +384,25 @@ enum CliCommand {
     /// Manage wash plugins
     #[clap(name = "plugin", subcommand)]
     Plugin(PluginCommand),
+    /// Manage the policy pack wash enforces for your organization
+    #[clap(name = "policy", subcommand)]
+    Policy(PolicyCommand),
     /// Push an artifact to an OCI compliant registry
     #[clap(name = "push")]
     RegPush(RegistryPushCommand),
+    /// Profile the performance of a component running in a host
+    #[clap(name = "profile", subcommand)]
+    Profile(ProfileCommand),
     /// Pull an artifact from an OCI compliant registry
     #[clap(name = "pull")]
     RegPull(RegistryPullCommand),
-    /// Create secret references for components, capability providers and links
+    /// Split the traffic sent to a link target between component versions
+    #[clap(name = "route", subcommand)]
+    Route(RouteCommand),
+    /// Start an interactive session with a persistent connection to a lattice
+    #[clap(name = "repl")]
+    Repl(ReplCommand),
+    /// Manage secrets for components, capability providers and links
     #[clap(name = "secrets", alias = "secret", subcommand)]
     Secrets(SecretsCliCommand),
     /// Spy on all invocations a component sends and receives
@@ -547,9 +599,12 @@ async fn main() {
         }
         CliCommand::Config(config_cli) => config::handle_command(config_cli, output_kind).await,
         CliCommand::Ctx(ctx_cli) => ctx::handle_command(ctx_cli).await,
+        CliCommand::Demo(demo_cli) => demo::handle_command(demo_cli, output_kind).await,
         CliCommand::Dev(dev_cli) => dev::handle_command(dev_cli, output_kind).await,
+        CliCommand::Doctor(doctor_cli) => doctor::handle_command(doctor_cli, output_kind).await,
         CliCommand::Down(down_cli) => down::handle_command(down_cli, output_kind).await,
         CliCommand::Drain(drain_cli) => drain::handle_command(drain_cli),
+        CliCommand::Events(events_cli) => events::handle_command(events_cli, output_kind).await,
         CliCommand::Get(get_cli) => common::get_cmd::handle_command(get_cli, output_kind).await,
         CliCommand::Inspect(inspect_cli) => {
             wash::lib::cli::inspect::handle_command(inspect_cli, output_kind).await
@@ -559,12 +614,17 @@ async fn main() {
         CliCommand::New(new_cli) => generate::handle_command(new_cli).await,
         CliCommand::Par(par_cli) => par::handle_command(par_cli, output_kind).await,
         CliCommand::Plugin(plugin_cli) => plugin::handle_command(plugin_cli, output_kind).await,
+        CliCommand::Policy(policy_cli) => policy::handle_command(policy_cli, output_kind).await,
         CliCommand::RegPush(reg_push_cli) => {
             common::registry_cmd::registry_push(reg_push_cli, output_kind).await
         }
         CliCommand::RegPull(reg_pull_cli) => {
             common::registry_cmd::registry_pull(reg_pull_cli, output_kind).await
         }
+        CliCommand::Loadgen(loadgen_cli) => loadgen::handle_command(loadgen_cli, output_kind).await,
+        CliCommand::Profile(profile_cli) => profile::handle_command(profile_cli, output_kind).await,
+        CliCommand::Repl(repl_cli) => repl::handle_command(repl_cli, output_kind).await,
+        CliCommand::Route(route_cli) => route::handle_command(route_cli, output_kind).await,
         CliCommand::Spy(spy_cli) => {
             if !cli.experimental {
                 experimental_error_message("spy")
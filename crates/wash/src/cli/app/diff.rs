@@ -0,0 +1,131 @@
+//! Line diffs between revisions of an application manifest, as shown by `wash app history --diff`
+
+/// Number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Unchanged(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl DiffLine<'_> {
+    fn is_change(&self) -> bool {
+        !matches!(self, DiffLine::Unchanged(_))
+    }
+}
+
+/// Compute the lines removed from `old` and added in `new`, based on their longest common
+/// subsequence of lines
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push(DiffLine::Unchanged(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().copied().map(DiffLine::Removed));
+    lines.extend(new[j..].iter().copied().map(DiffLine::Added));
+    lines
+}
+
+/// Render the differences between two manifests, prefixing removed lines with `-` and added lines
+/// with `+`. Unchanged lines further than a few lines away from a change are elided.
+///
+/// Returns `None` if the manifests are identical
+pub fn render_diff(old: &str, new: &str) -> Option<String> {
+    let lines = diff_lines(old, new);
+    if !lines.iter().any(DiffLine::is_change) {
+        return None;
+    }
+
+    let mut shown = vec![false; lines.len()];
+    for idx in (0..lines.len()).filter(|&idx| lines[idx].is_change()) {
+        let start = idx.saturating_sub(CONTEXT_LINES);
+        let end = (idx + CONTEXT_LINES + 1).min(lines.len());
+        shown[start..end].fill(true);
+    }
+
+    let mut out = String::new();
+    let mut elided = false;
+    for (line, shown) in lines.iter().zip(shown) {
+        if !shown {
+            elided = true;
+            continue;
+        }
+        if elided {
+            out.push_str("  ...\n");
+            elided = false;
+        }
+        let (prefix, text) = match line {
+            DiffLine::Unchanged(text) => (' ', text),
+            DiffLine::Removed(text) => ('-', text),
+            DiffLine::Added(text) => ('+', text),
+        };
+        out.push(prefix);
+        out.push(' ');
+        out.push_str(text);
+        out.push('\n');
+    }
+    if elided {
+        out.push_str("  ...\n");
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_manifests_have_no_diff() {
+        let manifest = "name: app\nversion: v1\n";
+        assert_eq!(render_diff(manifest, manifest), None);
+    }
+
+    #[test]
+    fn changed_lines_are_marked() {
+        let old = "name: app\nversion: v1\nimage: a:1\n";
+        let new = "name: app\nversion: v2\nimage: a:1\nreplicas: 2\n";
+        assert_eq!(
+            render_diff(old, new).as_deref(),
+            Some("  name: app\n- version: v1\n+ version: v2\n  image: a:1\n+ replicas: 2\n")
+        );
+    }
+
+    #[test]
+    fn distant_unchanged_lines_are_elided() {
+        let old: String = (0..20).map(|i| format!("line {i}\n")).collect();
+        let new = old.replace("line 10\n", "line ten\n");
+        let diff = render_diff(&old, &new).expect("manifests should differ");
+        assert_eq!(
+            diff,
+            "  ...\n  line 7\n  line 8\n  line 9\n- line 10\n+ line ten\n  line 11\n  line 12\n  line 13\n  ...\n"
+        );
+    }
+}
@@ -0,0 +1,609 @@
+//! Dependency graphs of wadm applications, rendered as mermaid or graphviz (dot) diagrams
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use wadm_types::{ConfigProperty, Manifest, Properties, SecretProperty, TraitProperty};
+
+/// Format of a rendered application graph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// A mermaid flowchart, which renders in markdown on e.g. GitHub
+    #[default]
+    Mermaid,
+    /// A graphviz digraph, which can be rendered with `dot -Tsvg`
+    Dot,
+}
+
+/// Kind of a node of an application graph
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Component,
+    Provider,
+    Config,
+    Secret,
+}
+
+/// Kind of an edge of an application graph
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// A link from the source to the target of invocations
+    Link,
+    /// Named config used by a component or provider, or by one side of a link
+    Config,
+    /// A secret used by a component or provider, or by one side of a link
+    Secret,
+}
+
+/// How a node or edge differs from the revision the graph is compared to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Change {
+    #[default]
+    Unchanged,
+    Added,
+    Removed,
+    /// The node exists in both revisions, but its properties (e.g. image or config values) differ
+    Changed,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub name: String,
+    /// Details shown below the name, e.g. the image of a component
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub change: Change,
+    /// Properties compared between revisions, which are not shown as they may be sensitive
+    #[serde(skip)]
+    fingerprint: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct EdgeKey {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Edge {
+    #[serde(flatten)]
+    pub key: EdgeKey,
+    pub change: Change,
+}
+
+/// The components, providers, links, config and secrets of an application version
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AppGraph {
+    pub name: String,
+    pub version: String,
+    /// Nodes by ID
+    pub nodes: BTreeMap<String, Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl AppGraph {
+    /// Build the graph of the given manifest
+    #[must_use]
+    pub fn from_manifest(manifest: &Manifest) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeSet::new();
+        let add_config = |nodes: &mut BTreeMap<String, Node>,
+                          edges: &mut BTreeSet<EdgeKey>,
+                          from: &str,
+                          label: &str,
+                          config: &[ConfigProperty],
+                          secrets: &[SecretProperty]| {
+            for config in config {
+                let id = node_id(NodeKind::Config, &config.name);
+                let fingerprint = config
+                    .properties
+                    .as_ref()
+                    .map(|properties| {
+                        format!("{:?}", properties.iter().collect::<BTreeMap<_, _>>())
+                    })
+                    .unwrap_or_default();
+                nodes.entry(id.clone()).or_insert_with(|| Node {
+                    kind: NodeKind::Config,
+                    name: config.name.clone(),
+                    detail: None,
+                    change: Change::Unchanged,
+                    fingerprint,
+                });
+                edges.insert(EdgeKey {
+                    from: from.to_string(),
+                    to: id,
+                    kind: EdgeKind::Config,
+                    label: label.to_string(),
+                });
+            }
+            for secret in secrets {
+                let id = node_id(NodeKind::Secret, &secret.name);
+                nodes.entry(id.clone()).or_insert_with(|| Node {
+                    kind: NodeKind::Secret,
+                    name: secret.name.clone(),
+                    detail: Some(format!(
+                        "{}: {}",
+                        secret.properties.policy, secret.properties.key
+                    )),
+                    change: Change::Unchanged,
+                    fingerprint: format!("{:?}", secret.properties),
+                });
+                edges.insert(EdgeKey {
+                    from: from.to_string(),
+                    to: id,
+                    kind: EdgeKind::Secret,
+                    label: label.to_string(),
+                });
+            }
+        };
+
+        for component in &manifest.spec.components {
+            let (kind, image, config, secrets) = match &component.properties {
+                Properties::Component { properties } => (
+                    NodeKind::Component,
+                    &properties.image,
+                    &properties.config,
+                    &properties.secrets,
+                ),
+                Properties::Capability { properties } => (
+                    NodeKind::Provider,
+                    &properties.image,
+                    &properties.config,
+                    &properties.secrets,
+                ),
+            };
+            let id = node_id(kind, &component.name);
+            nodes.insert(
+                id.clone(),
+                Node {
+                    kind,
+                    name: component.name.clone(),
+                    detail: image.clone(),
+                    change: Change::Unchanged,
+                    fingerprint: image.clone().unwrap_or_default(),
+                },
+            );
+            add_config(&mut nodes, &mut edges, &id, "", config, secrets);
+
+            for link in component.traits.iter().flatten() {
+                let TraitProperty::Link(link) = &link.properties else {
+                    continue;
+                };
+                let target = node_id(NodeKind::Component, &link.target.name);
+                let mut interfaces = link.interfaces.clone();
+                interfaces.sort();
+                let label = format!(
+                    "{}:{}/{}",
+                    link.namespace,
+                    link.package,
+                    interfaces.join(",")
+                );
+                let label = match &link.name {
+                    Some(name) if name != "default" => format!("{label} ({name})"),
+                    _ => label,
+                };
+                edges.insert(EdgeKey {
+                    from: id.clone(),
+                    to: target.clone(),
+                    kind: EdgeKind::Link,
+                    label: label.clone(),
+                });
+                if let Some(source) = &link.source {
+                    add_config(
+                        &mut nodes,
+                        &mut edges,
+                        &id,
+                        &format!("{label} source"),
+                        &source.config,
+                        &source.secrets,
+                    );
+                }
+                add_config(
+                    &mut nodes,
+                    &mut edges,
+                    &target,
+                    &format!("{label} target"),
+                    &link.target.config,
+                    &link.target.secrets,
+                );
+            }
+        }
+
+        Self {
+            name: manifest.metadata.name.clone(),
+            version: manifest.version().to_string(),
+            nodes,
+            edges: edges
+                .into_iter()
+                .map(|key| Edge {
+                    key,
+                    change: Change::Unchanged,
+                })
+                .collect(),
+        }
+    }
+
+    /// Mark the differences of this graph to the graph of another revision. Nodes and edges that
+    /// only exist in `other` are added to this graph as removed.
+    pub fn compare(&mut self, other: &AppGraph) {
+        for (id, node) in &mut self.nodes {
+            node.change = match other.nodes.get(id) {
+                None => Change::Added,
+                Some(previous) if previous.fingerprint != node.fingerprint => Change::Changed,
+                Some(_) => Change::Unchanged,
+            };
+        }
+        for (id, node) in &other.nodes {
+            if !self.nodes.contains_key(id) {
+                self.nodes.insert(
+                    id.clone(),
+                    Node {
+                        change: Change::Removed,
+                        ..node.clone()
+                    },
+                );
+            }
+        }
+
+        let previous: BTreeSet<&EdgeKey> = other.edges.iter().map(|e| &e.key).collect();
+        let current: BTreeSet<EdgeKey> = self.edges.iter().map(|e| e.key.clone()).collect();
+        for edge in &mut self.edges {
+            edge.change = if previous.contains(&edge.key) {
+                Change::Unchanged
+            } else {
+                Change::Added
+            };
+        }
+        self.edges.extend(
+            other
+                .edges
+                .iter()
+                .filter(|e| !current.contains(&e.key))
+                .map(|e| Edge {
+                    key: e.key.clone(),
+                    change: Change::Removed,
+                }),
+        );
+        self.edges.sort_by(|a, b| a.key.cmp(&b.key));
+    }
+
+    /// Render the graph in the given format
+    #[must_use]
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Mermaid => self.render_mermaid(),
+            GraphFormat::Dot => self.render_dot(),
+        }
+    }
+
+    fn render_mermaid(&self) -> String {
+        let ids: BTreeMap<&str, String> = self
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(idx, id)| (id.as_str(), format!("n{idx}")))
+            .collect();
+        let mut out = String::from("flowchart LR\n");
+        for (id, node) in &self.nodes {
+            let label = mermaid_escape(&node.label());
+            let (open, close) = match node.kind {
+                NodeKind::Component => ("[", "]"),
+                NodeKind::Provider => ("[[", "]]"),
+                NodeKind::Config => ("[/", "/]"),
+                NodeKind::Secret => ("{{", "}}"),
+            };
+            let _ = writeln!(out, "    {}{open}\"{label}\"{close}", ids[id.as_str()]);
+            if node.change != Change::Unchanged {
+                let _ = writeln!(
+                    out,
+                    "    class {} {}",
+                    ids[id.as_str()],
+                    change_name(node.change)
+                );
+            }
+        }
+        let mut link_styles: BTreeMap<Change, Vec<usize>> = BTreeMap::new();
+        for (idx, edge) in self.edges.iter().enumerate() {
+            let arrow = match (edge.key.kind, edge.change) {
+                (_, Change::Removed) => "-.->",
+                (EdgeKind::Link, _) => "==>",
+                _ => "-->",
+            };
+            let (from, to) = (&ids[edge.key.from.as_str()], &ids[edge.key.to.as_str()]);
+            if edge.key.label.is_empty() {
+                let _ = writeln!(out, "    {from} {arrow} {to}");
+            } else {
+                let label = mermaid_escape(&edge.key.label);
+                let _ = writeln!(out, "    {from} {arrow}|\"{label}\"| {to}");
+            }
+            if edge.change != Change::Unchanged {
+                link_styles.entry(edge.change).or_default().push(idx);
+            }
+        }
+        for (change, indices) in link_styles {
+            let indices = indices
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            let _ = writeln!(
+                out,
+                "    linkStyle {indices} stroke:{}",
+                change_color(change)
+            );
+        }
+        for change in [Change::Added, Change::Removed, Change::Changed] {
+            let _ = writeln!(
+                out,
+                "    classDef {} stroke:{},stroke-width:3px",
+                change_name(change),
+                change_color(change)
+            );
+        }
+        out
+    }
+
+    fn render_dot(&self) -> String {
+        let mut out = format!(
+            "digraph \"{}\" {{\n    rankdir=LR;\n",
+            dot_escape(&self.name)
+        );
+        for (id, node) in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Component => "box",
+                NodeKind::Provider => "box3d",
+                NodeKind::Config => "note",
+                NodeKind::Secret => "hexagon",
+            };
+            let mut attrs = format!("label=\"{}\", shape={shape}", dot_escape(&node.label()));
+            match node.change {
+                Change::Unchanged => {}
+                Change::Removed => {
+                    let _ = write!(
+                        attrs,
+                        ", color=\"{}\", style=dashed",
+                        change_color(node.change)
+                    );
+                }
+                change => {
+                    let _ = write!(attrs, ", color=\"{}\", penwidth=2", change_color(change));
+                }
+            }
+            let _ = writeln!(out, "    \"{}\" [{attrs}];", dot_escape(id));
+        }
+        for edge in &self.edges {
+            let mut attrs = vec![format!("label=\"{}\"", dot_escape(&edge.key.label))];
+            if edge.key.kind == EdgeKind::Link {
+                attrs.push("penwidth=2".to_string());
+            } else {
+                attrs.push("style=dotted".to_string());
+            }
+            match edge.change {
+                Change::Unchanged => {}
+                Change::Removed => {
+                    attrs.push(format!("color=\"{}\"", change_color(edge.change)));
+                    attrs.push("style=dashed".to_string());
+                }
+                change => attrs.push(format!("color=\"{}\"", change_color(change))),
+            }
+            let _ = writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [{}];",
+                dot_escape(&edge.key.from),
+                dot_escape(&edge.key.to),
+                attrs.join(", ")
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl Node {
+    fn label(&self) -> String {
+        let kind = match self.kind {
+            NodeKind::Component => "component",
+            NodeKind::Provider => "provider",
+            NodeKind::Config => "config",
+            NodeKind::Secret => "secret",
+        };
+        let mut label = format!("{} ({kind})", self.name);
+        if let Some(detail) = &self.detail {
+            label.push('\n');
+            label.push_str(detail);
+        }
+        if self.change != Change::Unchanged {
+            let _ = write!(label, "\n[{}]", change_name(self.change));
+        }
+        label
+    }
+}
+
+/// ID of a node, where components and providers share a namespace as they do in a manifest
+fn node_id(kind: NodeKind, name: &str) -> String {
+    let kind = match kind {
+        NodeKind::Component | NodeKind::Provider => "component",
+        NodeKind::Config => "config",
+        NodeKind::Secret => "secret",
+    };
+    format!("{kind}:{name}")
+}
+
+fn change_name(change: Change) -> &'static str {
+    match change {
+        Change::Unchanged => "unchanged",
+        Change::Added => "added",
+        Change::Removed => "removed",
+        Change::Changed => "changed",
+    }
+}
+
+fn change_color(change: Change) -> &'static str {
+    match change {
+        Change::Unchanged => "#333333",
+        Change::Added => "#2da44e",
+        Change::Removed => "#cf222e",
+        Change::Changed => "#bf8700",
+    }
+}
+
+fn mermaid_escape(value: &str) -> String {
+    value.replace('"', "#quot;").replace('\n', "<br/>")
+}
+
+fn dot_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST_V1: &str = r#"
+apiVersion: core.oam.dev/v1beta1
+kind: Application
+metadata:
+  name: hello
+  annotations:
+    version: v0.0.1
+spec:
+  components:
+    - name: http-component
+      type: component
+      properties:
+        image: ghcr.io/wasmcloud/components/http-hello-world-rust:0.1.0
+        config:
+          - name: greeting
+            properties:
+              lang: en
+    - name: httpserver
+      type: capability
+      properties:
+        image: ghcr.io/wasmcloud/http-server:0.23.0
+      traits:
+        - type: link
+          properties:
+            target:
+              name: http-component
+            namespace: wasi
+            package: http
+            interfaces: [incoming-handler]
+            source:
+              config:
+                - name: default-http
+                  properties:
+                    address: 0.0.0.0:8080
+"#;
+
+    const MANIFEST_V2: &str = r#"
+apiVersion: core.oam.dev/v1beta1
+kind: Application
+metadata:
+  name: hello
+  annotations:
+    version: v0.0.2
+spec:
+  components:
+    - name: http-component
+      type: component
+      properties:
+        image: ghcr.io/wasmcloud/components/http-hello-world-rust:0.2.0
+        secrets:
+          - name: api-key
+            properties:
+              policy: vault
+              key: hello/api-key
+    - name: httpserver
+      type: capability
+      properties:
+        image: ghcr.io/wasmcloud/http-server:0.23.0
+      traits:
+        - type: link
+          properties:
+            target:
+              name: http-component
+            namespace: wasi
+            package: http
+            interfaces: [incoming-handler]
+            source:
+              config:
+                - name: default-http
+                  properties:
+                    address: 0.0.0.0:8080
+"#;
+
+    fn graph(manifest: &str) -> AppGraph {
+        let manifest: Manifest = serde_yaml::from_str(manifest).expect("failed to parse manifest");
+        AppGraph::from_manifest(&manifest)
+    }
+
+    #[test]
+    fn builds_graph_from_manifest() {
+        let graph = graph(MANIFEST_V1);
+        assert_eq!(graph.name, "hello");
+        assert_eq!(graph.version, "v0.0.1");
+        assert_eq!(
+            graph.nodes.keys().collect::<Vec<_>>(),
+            vec![
+                "component:http-component",
+                "component:httpserver",
+                "config:default-http",
+                "config:greeting",
+            ]
+        );
+        assert_eq!(graph.nodes["component:httpserver"].kind, NodeKind::Provider);
+        assert!(graph.edges.iter().any(|e| e.key.kind == EdgeKind::Link
+            && e.key.from == "component:httpserver"
+            && e.key.to == "component:http-component"
+            && e.key.label == "wasi:http/incoming-handler"));
+
+        let mermaid = graph.render(GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("==>|\"wasi:http/incoming-handler\"|"));
+        let dot = graph.render(GraphFormat::Dot);
+        assert!(dot.starts_with("digraph \"hello\" {"));
+        assert!(dot.contains("\"component:httpserver\" -> \"component:http-component\""));
+    }
+
+    #[test]
+    fn compares_revisions() {
+        let mut current = graph(MANIFEST_V2);
+        current.compare(&graph(MANIFEST_V1));
+
+        assert_eq!(
+            current.nodes["component:http-component"].change,
+            Change::Changed
+        );
+        assert_eq!(
+            current.nodes["component:httpserver"].change,
+            Change::Unchanged
+        );
+        assert_eq!(current.nodes["config:greeting"].change, Change::Removed);
+        assert_eq!(current.nodes["secret:api-key"].change, Change::Added);
+        let change = |to: &str| {
+            current
+                .edges
+                .iter()
+                .find(|e| e.key.to == to)
+                .map(|e| e.change)
+        };
+        assert_eq!(change("config:greeting"), Some(Change::Removed));
+        assert_eq!(change("secret:api-key"), Some(Change::Added));
+        assert_eq!(change("config:default-http"), Some(Change::Unchanged));
+
+        let mermaid = current.render(GraphFormat::Mermaid);
+        assert!(mermaid.contains("classDef removed"));
+        assert!(mermaid.contains("linkStyle"));
+    }
+}
@@ -6,13 +6,16 @@ use crate::lib::app::{load_app_manifest, validate_manifest_file, AppManifest};
 use crate::lib::cli::get::parse_watch_interval;
 use crate::lib::cli::{CliConnectionOpts, CommandOutput, OutputKind};
 use crate::lib::config::WashConnectionOptions;
+use crate::lib::policy::Policy;
+use crate::lib::registry::{resolve_oci_digest, OciPullOptions};
 use anyhow::{bail, Context};
 use async_nats::RequestErrorKind;
 use clap::{Args, Subcommand};
 use serde_json::json;
 use wadm_client::Result;
-use wadm_types::api::ModelSummary;
+use wadm_types::api::{ModelSummary, VersionInfo};
 use wadm_types::validation::{ValidationFailure, ValidationOutput};
+use wadm_types::{Manifest, VERSION_ANNOTATION_KEY};
 
 use crate::appearance::spinner::Spinner;
 use crossterm::{
@@ -21,6 +24,8 @@ use crossterm::{
 };
 use std::io::Write;
 
+mod diff;
+mod graph;
 mod output;
 
 #[derive(Debug, Clone, Subcommand)]
@@ -37,6 +42,9 @@ pub enum AppCliCommand {
     /// Get the version history of a given application
     #[clap(name = "history")]
     History(HistoryCommand),
+    /// Roll back an application to a previous version, as numbered by `wash app history`
+    #[clap(name = "rollback")]
+    Rollback(RollbackCommand),
     /// Delete an application version
     #[clap(name = "delete", alias = "del")]
     Delete(DeleteCommand),
@@ -52,6 +60,9 @@ pub enum AppCliCommand {
     /// Validate an application manifest
     #[clap(name = "validate")]
     Validate(ValidateCommand),
+    /// Draw a diagram of the components, providers, links, config and secrets of an application
+    #[clap(name = "graph")]
+    Graph(GraphCommand),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -92,6 +103,43 @@ pub struct DeployCommand {
     #[clap(long = "replace")]
     replace: bool,
 
+    /// After deploying, keep watching an OCI reference (e.g. `ghcr.io/org/app:canary`) and redeploy the application
+    /// whenever the reference points to a new digest. Images in the manifest matching the reference are pinned to the new digest.
+    #[clap(long = "watch-oci", value_name = "REFERENCE")]
+    watch_oci: Option<String>,
+
+    /// How often to check the watched OCI reference for a new digest.
+    ///
+    /// Duration can be specified in ms (as number) or in [humantime](https://docs.rs/humantime) (eg: 5s, 2m, 15ms). Defaults to 30s.
+    #[clap(long = "watch-interval", default_value = "30s", value_parser = parse_watch_interval, requires = "watch_oci")]
+    watch_interval: Duration,
+
+    /// URL to POST a JSON notification to whenever the watched application is redeployed, or fails to redeploy
+    #[clap(long = "notify-webhook", value_name = "URL", requires = "watch_oci")]
+    notify_webhook: Option<url::Url>,
+
+    /// OCI username used to watch the reference, if omitted anonymous authentication will be used
+    #[clap(
+        long = "oci-user",
+        env = "WASH_REG_USER",
+        hide_env_values = true,
+        requires = "watch_oci"
+    )]
+    oci_user: Option<String>,
+
+    /// OCI password used to watch the reference, if omitted anonymous authentication will be used
+    #[clap(
+        long = "oci-password",
+        env = "WASH_REG_PASSWORD",
+        hide_env_values = true,
+        requires = "watch_oci"
+    )]
+    oci_password: Option<String>,
+
+    /// Allow insecure (HTTP) registry connections when watching the reference
+    #[clap(long = "oci-insecure", requires = "watch_oci")]
+    oci_insecure: bool,
+
     #[clap(flatten)]
     opts: CliConnectionOpts,
 }
@@ -162,6 +210,26 @@ pub struct HistoryCommand {
     #[clap(name = "name")]
     app_name: String,
 
+    /// Show the changes made to the manifest between two revisions, e.g. `--diff 2 4`.
+    /// If a single revision is given, it is compared to the revision before it
+    #[clap(long = "diff", value_name = "N", num_args = 1..=2)]
+    diff: Vec<usize>,
+
+    #[clap(flatten)]
+    opts: CliConnectionOpts,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RollbackCommand {
+    /// The name of the application
+    #[clap(name = "name")]
+    app_name: String,
+
+    /// Revision of the application to deploy, as numbered by `wash app history`.
+    /// Defaults to the revision before the deployed one
+    #[clap(long = "revision", value_name = "N")]
+    revision: Option<usize>,
+
     #[clap(flatten)]
     opts: CliConnectionOpts,
 }
@@ -176,11 +244,32 @@ pub struct ValidateCommand {
     check_image_refs: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct GraphCommand {
+    /// The name of the application
+    #[clap(name = "name")]
+    app_name: String,
+
+    /// Format of the diagram
+    #[clap(long = "format", value_enum, default_value_t)]
+    format: graph::GraphFormat,
+
+    /// Revision of the application to compare the deployed version to, as numbered by `wash app history`.
+    /// Components, links, config and secrets that were added, removed or changed since that revision are highlighted
+    #[clap(long = "compare-revision", value_name = "N")]
+    compare_revision: Option<usize>,
+
+    #[clap(flatten)]
+    opts: CliConnectionOpts,
+}
+
 pub async fn handle_command(
     command: AppCliCommand,
     output_kind: OutputKind,
 ) -> anyhow::Result<CommandOutput> {
-    use AppCliCommand::{Delete, Deploy, Get, History, List, Put, Status, Undeploy, Validate};
+    use AppCliCommand::{
+        Delete, Deploy, Get, Graph, History, List, Put, Rollback, Status, Undeploy, Validate,
+    };
     let sp: Spinner = Spinner::new(&output_kind)?;
     let command_output: wadm_client::Result<CommandOutput> = match command {
         List(cmd) => {
@@ -204,6 +293,10 @@ pub async fn handle_command(
             sp.update_spinner_message("Getting application version history ... ".to_string());
             get_application_versions(cmd).await
         }
+        Rollback(cmd) => {
+            sp.update_spinner_message("Rolling back application ... ".to_string());
+            rollback_model(cmd).await
+        }
         Delete(cmd) => {
             sp.update_spinner_message("Deleting application version ... ".to_string());
             delete_application_version(cmd).await
@@ -224,6 +317,10 @@ pub async fn handle_command(
             sp.update_spinner_message("Validating application manifest ... ".to_string());
             handle_validate(cmd).await
         }
+        Graph(cmd) => {
+            sp.update_spinner_message("Building application graph ... ".to_string());
+            get_application_graph(cmd).await
+        }
     };
 
     // Basic match to give a nicer error than "no responders"
@@ -317,6 +414,15 @@ async fn deploy_model(cmd: DeployCommand) -> Result<CommandOutput> {
         <CliConnectionOpts as TryInto<WashConnectionOptions>>::try_into(cmd.opts)?;
     let lattice = Some(connection_opts.get_lattice());
 
+    if let Some(policy) = Policy::load().await? {
+        let mut violations =
+            policy.check_deploy(&connection_opts.ctx.name, &connection_opts.get_lattice());
+        if cmd.watch_oci.is_some() {
+            violations.extend(policy.check_registry(cmd.oci_insecure, false));
+        }
+        policy.enforce("app deploy", violations).await?;
+    }
+
     let client = connection_opts.into_nats_client().await?;
 
     let app_manifest = match cmd.app_name {
@@ -345,7 +451,156 @@ async fn deploy_model(cmd: DeployCommand) -> Result<CommandOutput> {
         }
     }
 
-    deploy_model_from_manifest(&client, lattice, app_manifest, cmd.version).await
+    let Some(watch_oci) = cmd.watch_oci else {
+        return deploy_model_from_manifest(&client, lattice, app_manifest, cmd.version).await;
+    };
+    let AppManifest::SerializedModel(ref manifest) = app_manifest else {
+        return Err(anyhow::anyhow!(
+            "--watch-oci requires a manifest file or URL, not the name of a stored application"
+        )
+        .into());
+    };
+    let manifest: Manifest =
+        serde_yaml::from_value(manifest.clone()).context("failed to parse application manifest")?;
+    let image: oci_client::Reference = watch_oci
+        .parse()
+        .with_context(|| format!("invalid OCI reference [{watch_oci}]"))?;
+    let pull_options = || OciPullOptions {
+        user: cmd.oci_user.clone(),
+        password: cmd.oci_password.clone(),
+        insecure: cmd.oci_insecure,
+        ..Default::default()
+    };
+
+    let mut digest = resolve_oci_digest(&image, pull_options()).await?;
+    let output =
+        deploy_model_from_manifest(&client, lattice.clone(), app_manifest, cmd.version).await?;
+    println!("{}", output.text);
+    println!(
+        "Watching [{image}] for new digests every {}, press Ctrl+C to stop",
+        humantime::format_duration(cmd.watch_interval)
+    );
+
+    let mut ctrlc = std::pin::pin!(tokio::signal::ctrl_c());
+    let mut interval = tokio::time::interval(cmd.watch_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    interval.tick().await;
+    let mut redeploys = 0_usize;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = &mut ctrlc => break,
+        }
+        let latest = match resolve_oci_digest(&image, pull_options()).await {
+            Ok(latest) => latest,
+            Err(e) => {
+                eprintln!("🟨 Failed to check [{image}] for updates: {e:#}");
+                continue;
+            }
+        };
+        if latest == digest {
+            continue;
+        }
+
+        let notification =
+            match redeploy_pinned(&client, lattice.clone(), &manifest, &image, &latest).await {
+                Ok((name, version)) => {
+                    redeploys += 1;
+                    println!(
+                    "Deployed application \"{name}\", version \"{version}\" for [{image}@{latest}]"
+                );
+                    json!({
+                        "event": "redeployed",
+                        "model_name": name,
+                        "model_version": version,
+                        "image": image.whole(),
+                        "digest": latest,
+                    })
+                }
+                Err(e) => {
+                    eprintln!("🟥 Failed to redeploy application for [{image}@{latest}]: {e}");
+                    json!({
+                        "event": "redeploy_failed",
+                        "model_name": manifest.metadata.name,
+                        "image": image.whole(),
+                        "digest": latest,
+                        "error": e.to_string(),
+                    })
+                }
+            };
+        digest = latest;
+        if let Some(url) = &cmd.notify_webhook {
+            if let Err(e) = reqwest::Client::new()
+                .post(url.clone())
+                .json(&notification)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+            {
+                eprintln!("🟨 Failed to send notification to [{url}]: {e}");
+            }
+        }
+    }
+
+    let mut map = HashMap::new();
+    map.insert("model_name".to_string(), json!(manifest.metadata.name));
+    map.insert("redeploys".to_string(), json!(redeploys));
+    map.insert("digest".to_string(), json!(digest));
+    Ok(CommandOutput::new(
+        format!(
+            "Stopped watching [{image}], application \"{}\" was redeployed {redeploys} time(s)",
+            manifest.metadata.name
+        ),
+        map,
+    ))
+}
+
+/// Deploy a new version of the manifest with all images matching `image` pinned to `digest`,
+/// returning the name and version of the deployed application.
+///
+/// The version is derived from the manifest version and digest, so a digest that was deployed
+/// before is redeployed from the existing version.
+async fn redeploy_pinned(
+    client: &async_nats::Client,
+    lattice: Option<String>,
+    manifest: &Manifest,
+    image: &oci_client::Reference,
+    digest: &str,
+) -> Result<(String, String)> {
+    let mut manifest = manifest.clone();
+    if crate::lib::app::pin_image_references(&mut manifest, image, digest) == 0 {
+        return Err(anyhow::anyhow!("no images in the manifest refer to [{image}]").into());
+    }
+    let short_digest: String = digest
+        .trim_start_matches("sha256:")
+        .chars()
+        .take(12)
+        .collect();
+    let version = match manifest.version() {
+        "" => short_digest,
+        base => format!("{base}-{short_digest}"),
+    };
+    manifest
+        .metadata
+        .annotations
+        .insert(VERSION_ANNOTATION_KEY.to_string(), version.clone());
+
+    let model = serde_yaml::to_string(&manifest).context("failed to convert manifest to string")?;
+    match crate::lib::app::put_and_deploy_model(client, lattice.clone(), &model).await {
+        Ok(deployed) => Ok(deployed),
+        // The digest was deployed before, so its version is stored already
+        Err(e) if e.to_string().contains("already exists") => {
+            crate::lib::app::deploy_model(
+                client,
+                lattice,
+                &manifest.metadata.name,
+                Some(version.clone()),
+            )
+            .await?;
+            Ok((manifest.metadata.name, version))
+        }
+        Err(e) => Err(e),
+    }
 }
 
 pub(crate) async fn deploy_model_from_manifest(
@@ -424,15 +679,183 @@ async fn get_application_versions(cmd: HistoryCommand) -> Result<CommandOutput>
 
     let client = connection_opts.into_nats_client().await?;
 
-    let versions = crate::lib::app::get_model_history(&client, lattice, &cmd.app_name).await?;
+    let versions =
+        crate::lib::app::get_model_history(&client, lattice.clone(), &cmd.app_name).await?;
+    let (from, to) = match cmd.diff[..] {
+        [] => {
+            let mut map = HashMap::new();
+            map.insert("revisions".to_string(), json!(versions));
+            return Ok(CommandOutput::new(
+                output::list_revisions_table(versions),
+                map,
+            ));
+        }
+        [revision] => (revision.saturating_sub(1), revision),
+        [from, to] => (from, to),
+        _ => unreachable!("--diff takes at most two revisions"),
+    };
+    let from_version = find_revision(&cmd.app_name, &versions, from)?;
+    let to_version = find_revision(&cmd.app_name, &versions, to)?;
+
+    let mut manifests = Vec::with_capacity(2);
+    for version in [from_version, to_version] {
+        let manifest = crate::lib::app::get_model_details(
+            &client,
+            lattice.clone(),
+            &cmd.app_name,
+            Some(version.version.clone()),
+        )
+        .await?;
+        let yaml =
+            serde_yaml::to_string(&manifest).context("failed to convert manifest to YAML")?;
+        manifests.push(yaml);
+    }
+    let diff = diff::render_diff(&manifests[0], &manifests[1]);
+
+    let text = match &diff {
+        Some(diff) => format!(
+            "Changes from revision {from} (version \"{}\") to revision {to} (version \"{}\"):\n{diff}",
+            from_version.version, to_version.version
+        ),
+        None => format!(
+            "No changes between revision {from} (version \"{}\") and revision {to} (version \"{}\")",
+            from_version.version, to_version.version
+        ),
+    };
     let mut map = HashMap::new();
-    map.insert("revisions".to_string(), json!(versions));
+    map.insert("from_revision".to_string(), json!(from));
+    map.insert("from_version".to_string(), json!(from_version.version));
+    map.insert("to_revision".to_string(), json!(to));
+    map.insert("to_version".to_string(), json!(to_version.version));
+    map.insert("diff".to_string(), json!(diff));
+    Ok(CommandOutput::new(text, map))
+}
+
+async fn rollback_model(cmd: RollbackCommand) -> Result<CommandOutput> {
+    let connection_opts =
+        <CliConnectionOpts as TryInto<WashConnectionOptions>>::try_into(cmd.opts)?;
+    let lattice = Some(connection_opts.get_lattice());
+
+    if let Some(policy) = Policy::load().await? {
+        let violations =
+            policy.check_deploy(&connection_opts.ctx.name, &connection_opts.get_lattice());
+        policy.enforce("app rollback", violations).await?;
+    }
+
+    let client = connection_opts.into_nats_client().await?;
+
+    let versions =
+        crate::lib::app::get_model_history(&client, lattice.clone(), &cmd.app_name).await?;
+    // Revisions are numbered from 1, so the revision before the deployed one is its index
+    let deployed = versions.iter().position(|v| v.deployed);
+    let revision = match (cmd.revision, deployed) {
+        (Some(revision), _) => revision,
+        (None, Some(0)) => {
+            return Err(anyhow::anyhow!(
+                "application `{}` has no revision before the deployed one",
+                cmd.app_name
+            )
+            .into())
+        }
+        (None, Some(deployed)) => deployed,
+        (None, None) => {
+            return Err(anyhow::anyhow!(
+                "application `{}` is not deployed, specify the revision to deploy with --revision",
+                cmd.app_name
+            )
+            .into())
+        }
+    };
+    let target = find_revision(&cmd.app_name, &versions, revision)?;
+    if target.deployed {
+        return Err(anyhow::anyhow!(
+            "revision {revision} (version \"{}\") of application `{}` is already deployed",
+            target.version,
+            cmd.app_name
+        )
+        .into());
+    }
+
+    crate::lib::app::deploy_model(
+        &client,
+        lattice,
+        &cmd.app_name,
+        Some(target.version.clone()),
+    )
+    .await?;
+
+    let previous_version = deployed.map(|idx| versions[idx].version.clone());
+    let mut map = HashMap::new();
+    map.insert("deployed".to_string(), json!(true));
+    map.insert("model_name".to_string(), json!(cmd.app_name));
+    map.insert("model_version".to_string(), json!(target.version));
+    map.insert("revision".to_string(), json!(revision));
+    map.insert("previous_version".to_string(), json!(previous_version));
     Ok(CommandOutput::new(
-        output::list_revisions_table(versions),
+        format!(
+            "Rolled back application \"{}\" to version \"{}\" (revision {revision})",
+            cmd.app_name, target.version
+        ),
         map,
     ))
 }
 
+/// Look up a revision of an application, as numbered by `wash app history`
+fn find_revision<'a>(
+    app_name: &str,
+    versions: &'a [VersionInfo],
+    revision: usize,
+) -> Result<&'a VersionInfo> {
+    revision
+        .checked_sub(1)
+        .and_then(|idx| versions.get(idx))
+        .ok_or_else(|| {
+            wadm_client::error::ClientError::ManifestLoad(anyhow::anyhow!(
+                "revision {revision} of application `{app_name}` does not exist, it has {} revision(s)",
+                versions.len()
+            ))
+        })
+}
+
+async fn get_application_graph(cmd: GraphCommand) -> Result<CommandOutput> {
+    let connection_opts =
+        <CliConnectionOpts as TryInto<WashConnectionOptions>>::try_into(cmd.opts)?;
+    let lattice = Some(connection_opts.get_lattice());
+
+    let client = connection_opts.into_nats_client().await?;
+
+    let versions =
+        crate::lib::app::get_model_history(&client, lattice.clone(), &cmd.app_name).await?;
+    // Graph the deployed version, or the latest version if the application is not deployed
+    let version = versions
+        .iter()
+        .find(|v| v.deployed)
+        .or_else(|| versions.last())
+        .map(|v| v.version.clone());
+    let manifest =
+        crate::lib::app::get_model_details(&client, lattice.clone(), &cmd.app_name, version)
+            .await?;
+    let mut app_graph = graph::AppGraph::from_manifest(&manifest);
+
+    if let Some(revision) = cmd.compare_revision {
+        let compare_version = find_revision(&cmd.app_name, &versions, revision)?;
+        let previous = crate::lib::app::get_model_details(
+            &client,
+            lattice,
+            &cmd.app_name,
+            Some(compare_version.version.clone()),
+        )
+        .await?;
+        app_graph.compare(&graph::AppGraph::from_manifest(&previous));
+    }
+
+    let diagram = app_graph.render(cmd.format);
+    let mut map = HashMap::new();
+    map.insert("diagram".to_string(), json!(diagram));
+    map.insert("graph".to_string(), json!(app_graph));
+    Ok(CommandOutput::new(diagram, map))
+}
+
 async fn get_model_status(cmd: StatusCommand) -> Result<CommandOutput> {
     let connection_opts =
         <CliConnectionOpts as TryInto<WashConnectionOptions>>::try_into(cmd.opts)?;
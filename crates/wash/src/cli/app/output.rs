@@ -9,15 +9,17 @@ use super::ModelSummary;
 
 pub fn list_revisions_table(revisions: Vec<VersionInfo>) -> String {
     let mut table = Table::new();
-    crate::util::configure_table_style(&mut table, 2);
+    crate::util::configure_table_style(&mut table, 3);
 
     table.add_row(Row::new(vec![
+        TableCell::new_with_alignment("Revision", 1, Alignment::Left),
         TableCell::new_with_alignment("Version", 1, Alignment::Left),
         TableCell::new_with_alignment("Deployed", 1, Alignment::Left),
     ]));
 
-    for r in &revisions {
+    for (idx, r) in revisions.iter().enumerate() {
         table.add_row(Row::new(vec![
+            TableCell::new_with_alignment(idx + 1, 1, Alignment::Left),
             TableCell::new_with_alignment(r.version.clone(), 1, Alignment::Left),
             TableCell::new_with_alignment(r.deployed, 1, Alignment::Left),
         ]));
@@ -124,7 +126,7 @@ pub fn status_table(model_name: String, status: Status) -> String {
         if !s.info.message.is_empty() {
             table_output.push_str(&format!(
                 "  {}\n    └ {}\n\n",
-                &s.name.replace(&model_name_replacer, ""),
+                s.name.replace(&model_name_replacer, ""),
                 s.info.message
             ));
         }
@@ -135,6 +137,6 @@ pub fn status_table(model_name: String, status: Status) -> String {
     let version = status.version;
     format!(
         "{}@{} - {:?}{}",
-        &model_name, version, status.info.status_type, table_output
+        model_name, version, status.info.status_type, table_output
     )
 }
@@ -1,6 +1,6 @@
 use std::{collections::HashMap, path::PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 use serde_json::json;
 
@@ -8,6 +8,7 @@ use crate::lib::{
     build::{build_project, sign_component_wasm, SignConfig},
     cli::{CommandOutput, CommonPackageArgs},
     parser::{load_config, TypeConfig},
+    registry::write_oci_image_archive,
 };
 
 /// Build (and sign) a wasmCloud component, provider, or interface
@@ -59,6 +60,15 @@ pub struct BuildCommand {
     /// (useful for airgapped or disconnected environments)
     #[clap(long = "skip-fetch")]
     pub skip_wit_fetch: bool,
+
+    /// Also package the component as an OCI image archive next to the built artifact, which can be
+    /// imported with `docker load` or `ctr image import` and run by containerd wasm shims (runwasi)
+    #[clap(long = "oci-runtime-image")]
+    pub oci_runtime_image: bool,
+
+    /// Name of the image in the OCI image archive. Defaults to `<project name>:<version>`
+    #[clap(long = "oci-image-name", requires = "oci_runtime_image")]
+    pub oci_image_name: Option<String>,
 }
 
 pub async fn handle_command(command: BuildCommand) -> Result<CommandOutput> {
@@ -108,23 +118,47 @@ pub async fn handle_command(command: BuildCommand) -> Result<CommandOutput> {
                 .await?
             };
 
-            let json_output = HashMap::from([
+            let mut json_output = HashMap::from([
                 ("component_path".to_string(), json!(component_path)),
                 ("built".to_string(), json!(!command.sign_only)),
                 ("signed".to_string(), json!(!command.build_only)),
             ]);
-            Ok(CommandOutput::new(
-                if command.build_only {
-                    format!("Component built and can be found at {component_path:?}")
-                } else if command.sign_only {
-                    format!("Component signed and can be found at {component_path:?}")
-                } else {
-                    format!("Component built and signed and can be found at {component_path:?}")
-                },
-                json_output,
-            ))
+            let mut text = if command.build_only {
+                format!("Component built and can be found at {component_path:?}")
+            } else if command.sign_only {
+                format!("Component signed and can be found at {component_path:?}")
+            } else {
+                format!("Component built and signed and can be found at {component_path:?}")
+            };
+
+            if command.oci_runtime_image {
+                let image_name = command.oci_image_name.unwrap_or_else(|| {
+                    format!(
+                        "{}:{}",
+                        config.common.name.to_lowercase().replace(' ', "-"),
+                        config.common.version
+                    )
+                });
+                let image = image_name
+                    .parse()
+                    .with_context(|| format!("invalid OCI image name [{image_name}]"))?;
+                let image_path = component_path.with_extension("oci.tar");
+                let digest = write_oci_image_archive(&component_path, &image, &image_path)
+                    .await
+                    .context("failed to write OCI image archive")?;
+                text.push_str(&format!(
+                    "\nOCI image archive for [{image_name}] can be found at {image_path:?}"
+                ));
+                json_output.insert("oci_image_path".to_string(), json!(image_path));
+                json_output.insert("oci_image_digest".to_string(), json!(digest));
+            }
+
+            Ok(CommandOutput::new(text, json_output))
         }
         TypeConfig::Provider(ref provider_config) => {
+            if command.oci_runtime_image {
+                bail!("--oci-runtime-image is only supported for components");
+            }
             let path = build_project(
                 &config,
                 Some(&SignConfig {
@@ -182,5 +216,20 @@ mod test {
         assert_eq!(cmd.issuer, Some("/tmp/iss.nk".to_string()));
         assert_eq!(cmd.subject, Some("/tmp/sub.nk".to_string()));
         assert_eq!(cmd.keys_directory, Some(PathBuf::from("/tmp")));
+        assert!(!cmd.oci_runtime_image);
+
+        let cmd: BuildCommand = Parser::try_parse_from([
+            "build",
+            "--oci-runtime-image",
+            "--oci-image-name",
+            "ghcr.io/org/app:0.1.0",
+        ])
+        .unwrap();
+        assert!(cmd.oci_runtime_image);
+        assert_eq!(
+            cmd.oci_image_name,
+            Some("ghcr.io/org/app:0.1.0".to_string())
+        );
+        assert!(BuildCommand::try_parse_from(["build", "--oci-image-name", "app:0.1.0"]).is_err());
     }
 }
@@ -130,7 +130,7 @@ pub async fn handle_command(
         .await
         .context("failed to create async nats client")?;
     let wrpc_client =
-        wrpc_transport_nats::Client::new(nc, format!("{}.{component_id}", &lattice), None).await?;
+        wrpc_transport_nats::Client::new(nc, format!("{}.{component_id}", lattice), None).await?;
 
     let (namespace, package, interface, name) = parse_wit_meta_from_operation(&function).context(
         "Invalid function supplied. Must be in the form of `namespace:package/interface.function`",
@@ -478,7 +478,7 @@ pub fn call_output(
 ) -> Result<CommandOutput> {
     if let Some(ref save_path) = save_output {
         std::fs::write(save_path, response)
-            .with_context(|| format!("Error saving results to {}", &save_path.display()))?;
+            .with_context(|| format!("Error saving results to {}", save_path.display()))?;
 
         return Ok(CommandOutput::new(
             "",
@@ -552,13 +552,13 @@ async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nat
 
     let nc = if let Some(jwt_file) = jwt {
         let jwt_contents = extract_arg_value(jwt_file)
-            .with_context(|| format!("Failed to extract jwt contents from {}", &jwt_file))?;
+            .with_context(|| format!("Failed to extract jwt contents from {}", jwt_file))?;
         let kp = std::sync::Arc::new(if let Some(seed) = seed {
             nkeys::KeyPair::from_seed(
                 &extract_arg_value(seed)
-                    .with_context(|| format!("Failed to extract seed value {}", &seed))?,
+                    .with_context(|| format!("Failed to extract seed value {}", seed))?,
             )
-            .with_context(|| format!("Failed to create keypair from seed value {}", &seed))?
+            .with_context(|| format!("Failed to create keypair from seed value {}", seed))?
         } else {
             nkeys::KeyPair::new_user()
         });
@@ -581,7 +581,7 @@ async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nat
             .with_context(|| {
                 format!(
                     "Failed to connect to NATS server {}:{} while creating client",
-                    &host, &port
+                    host, port
                 )
             })?
     } else if let Some(credsfile_path) = credsfile {
@@ -590,7 +590,7 @@ async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nat
             .with_context(|| {
                 format!(
                     "Failed to authenticate to NATS with credentials file {:?}",
-                    &credsfile_path
+                    credsfile_path
                 )
             })?;
 
@@ -606,7 +606,7 @@ async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nat
             .with_context(|| {
                 format!(
                     "Failed to connect to NATS {} with credentials file {:?}",
-                    &nats_url, &credsfile_path
+                    nats_url, credsfile_path
                 )
             })?
     } else {
@@ -621,7 +621,7 @@ async fn create_client_from_opts_wrpc(opts: &ConnectionOpts) -> Result<async_nat
         opts.name("wash-cli")
             .connect(&nats_url)
             .await
-            .with_context(|| format!("Failed to connect to NATS {}", &nats_url))?
+            .with_context(|| format!("Failed to connect to NATS {}", nats_url))?
     };
     Ok(nc)
 }
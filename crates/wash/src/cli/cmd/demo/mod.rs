@@ -0,0 +1,533 @@
+//! `wash demo` launches curated sample applications on a local wasmCloud environment, so that
+//! evaluating wasmCloud takes a single command.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+use crate::app::deploy_model_from_manifest;
+use crate::appearance::spinner::Spinner;
+use crate::cmd::up::{
+    handle_up, nats_client_from_wasmcloud_opts, NatsOpts, UpCommand, WadmOpts, WasmcloudOpts,
+};
+use crate::config::DEFAULT_LATTICE;
+use crate::down::{handle_down, DownCommand};
+use crate::lib::app::{delete_model_version, undeploy_model, AppManifest};
+use crate::lib::cli::{CommandOutput, OutputKind};
+use crate::lib::config::{host_pid_file, WASH_DIRECTORIES};
+use crate::lib::generate::emoji;
+
+/// Name of the file that tracks demos deployed by `wash demo`, stored alongside the pid files
+const DEMO_STATE_FILE_NAME: &str = "wash-demo.json";
+
+/// Default amount of time to wait for a demo to become reachable
+const DEFAULT_DEMO_TIMEOUT_MS: &str = "120000";
+
+/// Interval between attempts to deploy a demo or reach its endpoint
+const DEMO_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct DemoCommand {
+    #[clap(subcommand)]
+    pub command: Option<DemoSubcommand>,
+
+    /// Name of the demo to launch
+    #[clap(name = "demo", value_enum)]
+    pub demo: Option<Demo>,
+
+    /// Do not open the demo in a browser once it is reachable
+    #[clap(long = "no-browser")]
+    pub no_browser: bool,
+
+    /// Maximum amount of time to wait for the demo to become reachable, in milliseconds
+    #[clap(long = "timeout-ms", default_value = DEFAULT_DEMO_TIMEOUT_MS)]
+    pub timeout_ms: u64,
+
+    #[clap(flatten)]
+    pub nats_opts: NatsOpts,
+
+    #[clap(flatten)]
+    pub wasmcloud_opts: WasmcloudOpts,
+
+    /// wadm version to download, e.g. `v0.18.0`.
+    #[clap(long = "wadm-version", env = "WADM_VERSION")]
+    pub wadm_version: Option<String>,
+
+    /// The `JetStream` domain to use for wadm
+    #[clap(long = "wadm-js-domain", env = "WADM_JS_DOMAIN")]
+    pub wadm_js_domain: Option<String>,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Subcommand)]
+pub enum DemoSubcommand {
+    /// List the available demos
+    #[clap(name = "list")]
+    List,
+    /// Remove demos deployed with `wash demo`, stopping wasmCloud if it was started for them
+    #[clap(name = "destroy")]
+    Destroy(DestroyCommand),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct DestroyCommand {
+    /// Demo to remove, defaults to all demos deployed with `wash demo`
+    #[clap(name = "demo", value_enum)]
+    pub demo: Option<Demo>,
+
+    /// Leave wasmCloud running even if it was started by `wash demo`
+    #[clap(long = "keep-running")]
+    pub keep_running: bool,
+
+    #[clap(flatten)]
+    pub wasmcloud_opts: WasmcloudOpts,
+}
+
+/// Curated sample applications that can be launched with `wash demo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Demo {
+    /// HTTP hello world
+    Hello,
+    /// HTTP counter backed by NATS JetStream key-value storage
+    Kvcounter,
+    /// Random dog pictures, using both incoming and outgoing HTTP
+    DogFetcher,
+}
+
+impl Demo {
+    /// All available demos
+    pub const ALL: [Self; 3] = [Self::Hello, Self::Kvcounter, Self::DogFetcher];
+
+    /// Name used to refer to the demo on the command line
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hello => "hello",
+            Self::Kvcounter => "kvcounter",
+            Self::DogFetcher => "dog-fetcher",
+        }
+    }
+
+    /// Short description of what the demo shows
+    #[must_use]
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Hello => "HTTP hello world",
+            Self::Kvcounter => "HTTP counter backed by NATS JetStream key-value storage",
+            Self::DogFetcher => "Random dog pictures, using both incoming and outgoing HTTP",
+        }
+    }
+
+    /// Name of the wadm application deployed for the demo
+    #[must_use]
+    pub fn app_name(self) -> &'static str {
+        match self {
+            Self::Hello => "wash-demo-hello",
+            Self::Kvcounter => "wash-demo-kvcounter",
+            Self::DogFetcher => "wash-demo-dog-fetcher",
+        }
+    }
+
+    /// URL at which the demo can be reached once deployed
+    #[must_use]
+    pub fn url(self) -> &'static str {
+        match self {
+            Self::Hello => "http://127.0.0.1:8000",
+            Self::Kvcounter => "http://127.0.0.1:8001",
+            Self::DogFetcher => "http://127.0.0.1:8002",
+        }
+    }
+
+    /// Raw wadm manifest for the demo
+    fn manifest(self) -> &'static str {
+        match self {
+            Self::Hello => include_str!("manifests/hello.wadm.yaml"),
+            Self::Kvcounter => include_str!("manifests/kvcounter.wadm.yaml"),
+            Self::DogFetcher => include_str!("manifests/dog-fetcher.wadm.yaml"),
+        }
+    }
+}
+
+/// Demos deployed by `wash demo`, and whether the local environment was started for them
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DemoState {
+    /// Whether `wash demo` ran `wash up`, and should therefore run `wash down` on destroy
+    started_environment: bool,
+    /// Demos that are currently deployed
+    deployed: BTreeSet<Demo>,
+}
+
+impl DemoState {
+    fn path() -> Result<PathBuf> {
+        WASH_DIRECTORIES.create_in_downloads_dir(DEMO_STATE_FILE_NAME)
+    }
+
+    async fn load() -> Result<Self> {
+        let path = Self::path()?;
+        match tokio::fs::read(&path).await {
+            Ok(contents) => serde_json::from_slice(&contents)
+                .with_context(|| format!("failed to parse demo state [{}]", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("failed to read demo state [{}]", path.display()))
+            }
+        }
+    }
+
+    async fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if self.deployed.is_empty() && !self.started_environment {
+            return match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)
+                    .with_context(|| format!("failed to remove demo state [{}]", path.display())),
+                _ => Ok(()),
+            };
+        }
+        tokio::fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .await
+            .with_context(|| format!("failed to write demo state [{}]", path.display()))
+    }
+}
+
+/// Handle `wash demo`
+pub async fn handle_command(cmd: DemoCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    match (cmd.command.clone(), cmd.demo) {
+        (Some(DemoSubcommand::List), _) => Ok(list_demos()),
+        (Some(DemoSubcommand::Destroy(destroy)), _) => handle_destroy(destroy, output_kind).await,
+        (None, Some(demo)) => handle_launch(demo, cmd, output_kind).await,
+        (None, None) => bail!("no demo specified, run `wash demo list` to see the available demos"),
+    }
+}
+
+fn list_demos() -> CommandOutput {
+    let mut text = String::from("Available demos:\n");
+    for demo in Demo::ALL {
+        let _ = writeln!(text, "  {:<14}{}", demo.name(), demo.description());
+    }
+    text.push_str("\nLaunch one with `wash demo <name>`");
+    let demos: Vec<_> = Demo::ALL
+        .iter()
+        .map(|demo| {
+            json!({
+                "name": demo.name(),
+                "description": demo.description(),
+                "url": demo.url(),
+            })
+        })
+        .collect();
+    CommandOutput::new(text, HashMap::from([("demos".to_string(), json!(demos))]))
+}
+
+async fn handle_launch(
+    demo: Demo,
+    cmd: DemoCommand,
+    output_kind: OutputKind,
+) -> Result<CommandOutput> {
+    let mut state = DemoState::load().await?;
+
+    // Only take ownership of the environment if nothing was running before we started it
+    let already_running = tokio::fs::try_exists(host_pid_file()?)
+        .await
+        .unwrap_or(false);
+    handle_up(
+        UpCommand {
+            detached: true,
+            config: None,
+            print_config: false,
+            nats_opts: cmd.nats_opts,
+            wasmcloud_opts: cmd.wasmcloud_opts.clone(),
+            wadm_opts: WadmOpts {
+                wadm_version: cmd.wadm_version,
+                disable_wadm: false,
+                wadm_js_domain: cmd.wadm_js_domain,
+                wadm_manifest: None,
+            },
+        },
+        output_kind,
+    )
+    .await
+    .context("failed to start a local wasmCloud environment")?;
+    if !already_running {
+        state.started_environment = true;
+        state.save().await?;
+    }
+
+    let spinner = Spinner::new(&output_kind)?;
+    spinner.update_spinner_message(format!(" Deploying demo {} ...", demo.name()));
+    let timeout = Duration::from_millis(cmd.timeout_ms);
+    let deadline = Instant::now() + timeout;
+    let nats_client = nats_client_from_wasmcloud_opts(&cmd.wasmcloud_opts).await?;
+    let manifest: serde_yaml::Value =
+        serde_yaml::from_str(demo.manifest()).context("failed to parse demo manifest")?;
+    // wadm may still be starting up, so retry the deployment until the deadline
+    loop {
+        match deploy_model_from_manifest(
+            &nats_client,
+            cmd.wasmcloud_opts.lattice.clone(),
+            AppManifest::SerializedModel(manifest.clone()),
+            None,
+        )
+        .await
+        {
+            Ok(_) => break,
+            Err(e) if Instant::now() < deadline => {
+                debug!(?e, demo = demo.name(), "failed to deploy demo, retrying");
+                tokio::time::sleep(DEMO_POLL_INTERVAL).await;
+            }
+            Err(e) => {
+                spinner.finish_and_clear();
+                return Err(e).with_context(|| format!("failed to deploy demo {}", demo.name()));
+            }
+        }
+    }
+    state.deployed.insert(demo);
+    state.save().await?;
+
+    spinner.update_spinner_message(format!(
+        " Waiting for demo {} to be reachable ...",
+        demo.name()
+    ));
+    let reachable = wait_for_endpoint(demo.url(), deadline).await;
+    spinner.finish_and_clear();
+
+    let mut out_text = format!(
+        "{} Demo {} deployed as application {}",
+        emoji::SPARKLE,
+        demo.name(),
+        demo.app_name()
+    );
+    if reachable {
+        let _ = write!(
+            out_text,
+            "\n🌐 {} is reachable at {}",
+            demo.name(),
+            demo.url()
+        );
+        if !cmd.no_browser && output_kind == OutputKind::Text {
+            if let Err(e) = open_browser(demo.url()) {
+                warn!(?e, "failed to open browser");
+            }
+        }
+    } else {
+        let _ = write!(
+            out_text,
+            "\n🟨 {} is still starting, it will be reachable at {} shortly. Check progress with `wash app status {}`",
+            demo.name(),
+            demo.url(),
+            demo.app_name()
+        );
+    }
+    out_text.push_str("\n\n⬇️  To remove the demo, run \"wash demo destroy\"");
+
+    let out_json = HashMap::from([
+        ("demo".to_string(), json!(demo.name())),
+        ("model_name".to_string(), json!(demo.app_name())),
+        ("url".to_string(), json!(demo.url())),
+        ("reachable".to_string(), json!(reachable)),
+    ]);
+    Ok(CommandOutput::new(out_text, out_json))
+}
+
+async fn handle_destroy(cmd: DestroyCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    let mut state = DemoState::load().await?;
+    let demos: Vec<Demo> = match cmd.demo {
+        Some(demo) => vec![demo],
+        None if state.deployed.is_empty() => Demo::ALL.to_vec(),
+        None => state.deployed.iter().copied().collect(),
+    };
+
+    let spinner = Spinner::new(&output_kind)?;
+    spinner.update_spinner_message(" Removing demos ...".to_string());
+    let mut out_text = String::new();
+    let mut removed = Vec::new();
+    match nats_client_from_wasmcloud_opts(&cmd.wasmcloud_opts).await {
+        Ok(nats_client) => {
+            for demo in demos {
+                let lattice = cmd.wasmcloud_opts.lattice.clone();
+                // Undeploying an application that was never deployed is not an error here
+                if let Err(e) = undeploy_model(&nats_client, lattice.clone(), demo.app_name()).await
+                {
+                    debug!(?e, demo = demo.name(), "failed to undeploy demo");
+                }
+                match delete_model_version(&nats_client, lattice, demo.app_name(), None).await {
+                    Ok(true) => {
+                        let _ = writeln!(out_text, "✅ Removed demo {}", demo.name());
+                        removed.push(demo.name());
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        let _ = writeln!(out_text, "❌ Could not remove demo {}: {e}", demo.name());
+                        continue;
+                    }
+                }
+                state.deployed.remove(&demo);
+            }
+        }
+        Err(e) => {
+            warn!(
+                ?e,
+                "couldn't connect to NATS, unable to remove demo applications"
+            );
+            // Nothing is left to remove if the environment is already gone
+            state.deployed.clear();
+        }
+    }
+    spinner.finish_and_clear();
+
+    let mut stopped = false;
+    if state.deployed.is_empty() && state.started_environment && !cmd.keep_running {
+        let down = handle_down(
+            DownCommand {
+                lattice: cmd
+                    .wasmcloud_opts
+                    .lattice
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_LATTICE.to_string()),
+                ctl_host: cmd.wasmcloud_opts.ctl_host,
+                ctl_port: cmd.wasmcloud_opts.ctl_port,
+                ctl_credsfile: cmd.wasmcloud_opts.ctl_credsfile,
+                ctl_seed: cmd.wasmcloud_opts.ctl_seed,
+                ctl_jwt: cmd.wasmcloud_opts.ctl_jwt,
+                ctl_tls_ca_file: cmd.wasmcloud_opts.ctl_tls_ca_file,
+                ctl_tls_first: Some(cmd.wasmcloud_opts.ctl_tls_first),
+                ..Default::default()
+            },
+            output_kind,
+        )
+        .await?;
+        out_text.push_str(&down.text);
+        state.started_environment = false;
+        stopped = true;
+    } else if removed.is_empty() {
+        out_text.push_str("🛁 No demos to remove");
+    } else {
+        out_text.push_str("🛁 wash demo destroy completed successfully");
+    }
+    state.save().await?;
+
+    let out_json = HashMap::from([
+        ("removed".to_string(), json!(removed)),
+        ("environment_stopped".to_string(), json!(stopped)),
+    ]);
+    Ok(CommandOutput::new(out_text, out_json))
+}
+
+/// Poll the given URL until it responds or the deadline passes, returning whether it responded
+async fn wait_for_endpoint(url: &str, deadline: Instant) -> bool {
+    let client = reqwest::Client::new();
+    loop {
+        match client.get(url).timeout(DEMO_POLL_INTERVAL).send().await {
+            // Any HTTP response means the component is linked and serving requests
+            Ok(_) => return true,
+            Err(e) if Instant::now() < deadline => {
+                debug!(?e, url, "demo endpoint not reachable yet");
+                tokio::time::sleep(DEMO_POLL_INTERVAL).await;
+            }
+            Err(_) => return false,
+        }
+    }
+}
+
+/// Open the given URL with the platform's default browser
+fn open_browser(url: &str) -> Result<()> {
+    browser_command(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .context("failed to launch browser")?;
+    Ok(())
+}
+
+/// Command opening the given URL with the platform's default browser
+fn browser_command(url: &str) -> std::process::Command {
+    let mut cmd = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    cmd.arg(url);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use wadm_types::validation::{validate_manifest, ValidationOutput};
+    use wadm_types::Manifest;
+
+    use super::{browser_command, Demo, DemoCommand, DemoSubcommand};
+
+    #[tokio::test]
+    async fn demo_manifests_are_valid() {
+        for demo in Demo::ALL {
+            let manifest: Manifest = serde_yaml::from_str(demo.manifest())
+                .unwrap_or_else(|e| panic!("demo {} has an invalid manifest: {e}", demo.name()));
+            assert_eq!(manifest.metadata.name, demo.app_name());
+            let failures = validate_manifest(&manifest)
+                .await
+                .expect("failed to validate manifest");
+            assert!(
+                failures.valid(),
+                "demo {} failed validation: {failures:?}",
+                demo.name()
+            );
+            assert!(
+                demo.manifest()
+                    .contains(demo.url().trim_start_matches("http://127.0.0.1")),
+                "demo {} does not listen on the port of its URL",
+                demo.name()
+            );
+        }
+    }
+
+    #[test]
+    fn opens_demo_url_in_browser() {
+        let url = Demo::Hello.url();
+        let cmd = browser_command(url);
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args.last().copied(), Some(url.as_ref()));
+        let program = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(target_os = "windows") {
+            "cmd"
+        } else {
+            "xdg-open"
+        };
+        assert_eq!(cmd.get_program(), program);
+    }
+
+    #[test]
+    fn parses_demo_and_subcommands() {
+        let cmd = DemoCommand::try_parse_from(["demo", "kvcounter", "--no-browser"])
+            .expect("failed to parse demo launch");
+        assert_eq!(cmd.demo, Some(Demo::Kvcounter));
+        assert!(cmd.no_browser);
+        assert!(cmd.command.is_none());
+
+        let cmd = DemoCommand::try_parse_from(["demo", "destroy", "dog-fetcher"])
+            .expect("failed to parse demo destroy");
+        match cmd.command {
+            Some(DemoSubcommand::Destroy(destroy)) => {
+                assert_eq!(destroy.demo, Some(Demo::DogFetcher));
+                assert!(!destroy.keep_running);
+            }
+            other => panic!("unexpected subcommand {other:?}"),
+        }
+
+        assert!(DemoCommand::try_parse_from(["demo", "petclinic"]).is_err());
+    }
+}
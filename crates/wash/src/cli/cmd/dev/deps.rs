@@ -1166,7 +1166,7 @@ impl ProjectDeps {
                                     && link.target.name == dep_component.name
                                 {
                                     if let Some(interface) = interfaces.clone() {
-                                        link.interfaces.extend(interface.into_iter());
+                                        link.interfaces.extend(interface);
                                     };
                                     return true;
                                 }
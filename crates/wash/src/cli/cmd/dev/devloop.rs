@@ -1,22 +1,27 @@
-use std::collections::{BTreeSet, HashMap};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{bail, ensure, Context as _, Result};
 use console::style;
+use sha2::Digest as _;
 use tracing::{debug, warn};
 use crate::lib::app::AppManifest;
 use crate::lib::cli::stop::stop_provider;
 use crate::lib::component::{scale_component, ScaleComponentArgs};
-use wasmcloud_control_interface::Client as CtlClient;
+use wasmcloud_control_interface::{Annotations, Client as CtlClient, Link, ProviderDescription};
 
 use wadm_types::{ConfigProperty, Manifest, Properties, SecretProperty, SecretSourceProperty};
 use crate::lib::build::{build_project, SignConfig};
 use crate::lib::cli::{CommonPackageArgs, OutputKind};
+use crate::lib::common::boxed_err_to_anyhow;
+use crate::lib::config::DEFAULT_START_PROVIDER_TIMEOUT_MS;
 use crate::lib::generate::emoji;
 use crate::lib::parser::{
     load_config, DevConfigSpec, DevManifestComponentTarget, DevSecretSpec, ProjectConfig,
     TypeConfig,
 };
+use crate::lib::wait::{wait_for_provider_start_event, FindEventOutcome};
 
 use crate::app::deploy_model_from_manifest;
 use crate::appearance::spinner::Spinner;
@@ -25,7 +30,7 @@ use super::deps::{DependencySpec, ProjectDependencyKey, ProjectDeps};
 use super::manifest::{generate_component_from_project_cfg, generate_help_text_for_manifest};
 use super::session::WashDevSession;
 use super::wit::{discover_dependencies_from_wit, parse_component_wit, parse_project_wit};
-use super::DEFAULT_PROVIDER_STOP_TIMEOUT_MS;
+use super::{DEFAULT_PROVIDER_RESTART_TIMEOUT_MS, DEFAULT_PROVIDER_STOP_TIMEOUT_MS};
 
 /// State that is used/updated per loop of `wash dev`
 pub struct RunLoopState<'a> {
@@ -43,6 +48,18 @@ pub struct RunLoopState<'a> {
     pub(crate) package_args: &'a CommonPackageArgs,
     pub(crate) skip_fetch: bool,
     pub(crate) output_kind: OutputKind,
+    /// Local provider projects the project depends on, keyed by their directory
+    pub(crate) dependency_projects: BTreeMap<PathBuf, DependencyProject>,
+}
+
+/// Build state of a local provider project the project under development depends on (see
+/// [`InterfaceComponentOverride::project`](crate::lib::parser::InterfaceComponentOverride::project))
+#[derive(Debug, Default)]
+pub struct DependencyProject {
+    /// Reference to the artifact last built from the project
+    artifact_ref: Option<String>,
+    /// Digest of the artifact last built from the project
+    artifact_digest: Option<String>,
 }
 
 /// Generate manifests that should be deployed, based on the current run loop state
@@ -381,6 +398,21 @@ pub async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
         }
     }
 
+    // Build the provider projects this project depends on, so that the manifests refer to the
+    // latest artifacts
+    let rebuilt_provider_refs = match build_dependency_projects(state).await {
+        Ok(refs) => refs,
+        Err(e) => {
+            eprintln!(
+                "{} {}\n{e:#}",
+                emoji::ERROR,
+                style("Failed to build dependency project:").red(),
+            );
+            return Ok(());
+        }
+    };
+    apply_dependency_project_refs(state.project_cfg, &state.dependency_projects);
+
     // Generate the manifests that we need to deploy/update
     //
     // If the project configuration specified an *existing* manifest, we must merge, not generate
@@ -434,21 +466,47 @@ pub async fn run(state: &mut RunLoopState<'_>) -> Result<()> {
         );
     }
 
-    // Scale the component to zero, trusting that wadm will re-create it
-    scale_down_component(
-        state.ctl_client,
-        state.project_cfg,
-        &state
-            .dev_session
-            .host_data
-            .as_ref()
-            .context("missing host ID for session")?
-            .0,
-        component_id,
-        component_ref,
-    )
-    .await
-    .with_context(|| format!("failed to reload component [{component_id}]"))?;
+    let host_id = &state
+        .dev_session
+        .host_data
+        .as_ref()
+        .context("missing host ID for session")?
+        .0;
+    if matches!(state.project_cfg.project_type, TypeConfig::Provider(_)) && manifests.is_empty() {
+        // Restart the provider from the rebuilt archive, as no updated manifest will replace it
+        reload_provider(state.ctl_client, host_id, component_id, component_ref)
+            .await
+            .with_context(|| format!("failed to reload provider [{component_id}]"))?;
+    } else {
+        // Scale the component to zero, trusting that wadm will re-create it
+        scale_down_component(
+            state.ctl_client,
+            state.project_cfg,
+            host_id,
+            component_id,
+            component_ref,
+        )
+        .await
+        .with_context(|| format!("failed to reload component [{component_id}]"))?;
+    }
+
+    // Restart rebuilt dependency providers, as their manifests refer to the same artifact paths
+    if !rebuilt_provider_refs.is_empty() {
+        let inventory = state
+            .ctl_client
+            .get_host_inventory(host_id)
+            .await
+            .map_err(boxed_err_to_anyhow)?
+            .into_data()
+            .context("host did not return its inventory")?;
+        for provider_ref in &rebuilt_provider_refs {
+            for provider_id in provider_ids_with_ref(inventory.providers(), provider_ref) {
+                reload_provider(state.ctl_client, host_id, &provider_id, provider_ref)
+                    .await
+                    .with_context(|| format!("failed to reload provider [{provider_id}]"))?;
+            }
+        }
+    }
 
     // Apply all manifests
     for manifest in manifests {
@@ -566,3 +624,330 @@ async fn scale_down_component(
 
     Ok(())
 }
+
+/// Restart a provider under development from its rebuilt archive and re-establish its links
+///
+/// Providers managed by wadm are restarted by wadm once stopped, other providers (or providers
+/// wadm did not restart in time) are started again directly, with the same annotations.
+async fn reload_provider(
+    client: &CtlClient,
+    host_id: &str,
+    provider_id: &str,
+    provider_ref: &str,
+) -> Result<()> {
+    let inventory = client
+        .get_host_inventory(host_id)
+        .await
+        .map_err(boxed_err_to_anyhow)?
+        .into_data()
+        .context("host did not return its inventory")?;
+    let Some(provider) = inventory.providers().iter().find(|p| p.id() == provider_id) else {
+        // The provider is not running yet, e.g. because the first build failed, so wadm will
+        // start it once its manifest is deployed
+        debug!(provider_id, "provider is not running, skipping restart");
+        return Ok(());
+    };
+    let annotations = provider.annotations().map(Annotations::from);
+    let managed_by_wadm = annotations
+        .as_ref()
+        .is_some_and(|a| a.is_managed_by("wadm"));
+    let links = provider_links(client, provider_id).await?;
+
+    // Subscribe before stopping the provider so that no start events are missed
+    let mut receiver = client
+        .events_receiver(vec![
+            "provider_started".to_string(),
+            "provider_start_failed".to_string(),
+        ])
+        .await
+        .map_err(boxed_err_to_anyhow)
+        .context("failed to get lattice event channel")?;
+
+    stop_provider(
+        client,
+        Some(host_id),
+        provider_id,
+        false,
+        DEFAULT_PROVIDER_STOP_TIMEOUT_MS,
+    )
+    .await
+    .context("failed to stop provider")?;
+
+    let restarted = if managed_by_wadm {
+        wait_for_provider_start_event(
+            &mut receiver,
+            Duration::from_millis(DEFAULT_PROVIDER_RESTART_TIMEOUT_MS),
+            host_id.to_string(),
+            provider_ref.to_string(),
+        )
+        .await
+        .ok()
+    } else {
+        None
+    };
+    let outcome = if let Some(outcome) = restarted {
+        outcome
+    } else {
+        debug!(provider_id, "starting provider directly");
+        let ack = client
+            .start_provider(
+                host_id,
+                provider_ref,
+                provider_id,
+                annotations,
+                Vec::<String>::new(),
+            )
+            .await
+            .map_err(boxed_err_to_anyhow)
+            .context("failed to start provider")?;
+        ensure!(
+            ack.succeeded(),
+            "start provider ack not accepted: {}",
+            ack.message()
+        );
+        wait_for_provider_start_event(
+            &mut receiver,
+            Duration::from_millis(DEFAULT_START_PROVIDER_TIMEOUT_MS),
+            host_id.to_string(),
+            provider_ref.to_string(),
+        )
+        .await
+        .context("timed out waiting for provider to start")?
+    };
+    if let FindEventOutcome::Failure(e) = outcome {
+        return Err(e.context("provider failed to start"));
+    }
+
+    // Links are kept by the lattice and delivered to the provider when it starts, but any link
+    // removed while the provider was stopped (e.g. by wadm reconciling) is put back
+    let current = provider_links(client, provider_id).await?;
+    for link in links.into_iter().filter(|link| !current.contains(link)) {
+        let (source_id, target, name) = (
+            link.source_id().to_string(),
+            link.target().to_string(),
+            link.name().to_string(),
+        );
+        let ack = client.put_link(link).await.map_err(boxed_err_to_anyhow)?;
+        if !ack.succeeded() {
+            warn!(
+                source_id,
+                target,
+                name,
+                message = ack.message(),
+                "failed to re-establish link"
+            );
+        }
+    }
+
+    eprintln!(
+        "{} Restarted provider [{provider_id}] on host [{host_id}]",
+        emoji::GREEN_CHECK
+    );
+    Ok(())
+}
+
+/// Retrieve the links in the lattice that have the given provider as source or target
+async fn provider_links(client: &CtlClient, provider_id: &str) -> Result<Vec<Link>> {
+    Ok(client
+        .get_links()
+        .await
+        .map_err(boxed_err_to_anyhow)
+        .context("failed to get links")?
+        .into_data()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|link| link.source_id() == provider_id || link.target() == provider_id)
+        .collect())
+}
+
+/// Resolve the directories of the local provider projects that interface overrides of the project
+/// refer to
+pub(crate) fn dependency_project_dirs(project_cfg: &ProjectConfig) -> BTreeSet<PathBuf> {
+    let overrides = &project_cfg.dev.overrides;
+    overrides
+        .imports
+        .iter()
+        .chain(&overrides.exports)
+        .filter_map(|o| o.project.as_ref())
+        .map(|dir| resolve_project_dir(&project_cfg.common.project_dir, dir))
+        .collect()
+}
+
+/// Resolve a project directory relative to the project under development
+fn resolve_project_dir(project_dir: &Path, dir: &Path) -> PathBuf {
+    let dir = project_dir.join(dir);
+    dir.canonicalize().unwrap_or(dir)
+}
+
+/// Build all dependency projects of the project, returning the references of the provider
+/// artifacts that changed since they were last built
+async fn build_dependency_projects(state: &mut RunLoopState<'_>) -> Result<Vec<String>> {
+    let mut rebuilt = Vec::new();
+    for dir in dependency_project_dirs(state.project_cfg) {
+        let project_cfg = load_config(Some(dir.clone()), Some(true))
+            .await
+            .with_context(|| format!("failed to load dependency project [{}]", dir.display()))?;
+        ensure!(
+            matches!(project_cfg.project_type, TypeConfig::Provider(_)),
+            "dependency project [{}] must be a provider project",
+            dir.display()
+        );
+        let artifact_path = build_project(
+            &project_cfg,
+            Some(&SignConfig::default()),
+            state.package_args,
+            state.skip_fetch,
+        )
+        .await
+        .with_context(|| format!("failed to build dependency project [{}]", dir.display()))?;
+        let artifact = tokio::fs::read(&artifact_path).await.with_context(|| {
+            format!(
+                "failed to read built artifact [{}]",
+                artifact_path.display()
+            )
+        })?;
+        let digest = format!("{:x}", sha2::Sha256::digest(&artifact));
+        let artifact_ref = format!("file://{}", artifact_path.display());
+        eprintln!(
+            "{} Successfully built dependency project at [{}]",
+            emoji::GREEN_CHECK,
+            artifact_path.display()
+        );
+
+        let project = state.dependency_projects.entry(dir).or_default();
+        if project
+            .artifact_digest
+            .as_ref()
+            .is_some_and(|previous| *previous != digest)
+        {
+            rebuilt.push(artifact_ref.clone());
+        }
+        project.artifact_ref = Some(artifact_ref);
+        project.artifact_digest = Some(digest);
+    }
+    Ok(rebuilt)
+}
+
+/// Point interface overrides that refer to a dependency project at the artifact last built from it
+fn apply_dependency_project_refs(
+    project_cfg: &mut ProjectConfig,
+    projects: &BTreeMap<PathBuf, DependencyProject>,
+) {
+    let project_dir = project_cfg.common.project_dir.clone();
+    let overrides = &mut project_cfg.dev.overrides;
+    for o in overrides.imports.iter_mut().chain(&mut overrides.exports) {
+        let Some(dir) = &o.project else {
+            continue;
+        };
+        if let Some(artifact_ref) = projects
+            .get(&resolve_project_dir(&project_dir, dir))
+            .and_then(|project| project.artifact_ref.clone())
+        {
+            o.image_ref = Some(artifact_ref);
+        }
+    }
+}
+
+/// Find the IDs of the providers running from the given image reference
+fn provider_ids_with_ref(providers: &[ProviderDescription], image_ref: &str) -> Vec<String> {
+    providers
+        .iter()
+        .filter(|p| p.image_ref() == Some(image_ref))
+        .map(|p| p.id().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use wasmcloud_control_interface::ProviderDescription;
+
+    use super::{
+        apply_dependency_project_refs, dependency_project_dirs, provider_ids_with_ref,
+        DependencyProject,
+    };
+    use crate::lib::parser::load_config;
+
+    const PROJECT_TOML: &str = r#"
+language = "rust"
+type = "component"
+name = "testcomponent"
+version = "0.1.0"
+
+[component]
+
+[[dev.overrides.imports]]
+interface_spec = "wasmcloud:example/greeter"
+project = "../greeter"
+
+[[dev.overrides.exports]]
+interface_spec = "wasi:http/incoming-handler"
+image_ref = "ghcr.io/wasmcloud/http-server:0.26.0"
+"#;
+
+    #[tokio::test]
+    async fn dependency_projects_replace_image_refs() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let project_dir = dir.path().join("app");
+        let greeter_dir = dir.path().join("greeter");
+        tokio::fs::create_dir_all(&project_dir)
+            .await
+            .expect("failed to create project dir");
+        tokio::fs::create_dir_all(&greeter_dir)
+            .await
+            .expect("failed to create dependency project dir");
+        tokio::fs::write(project_dir.join("wasmcloud.toml"), PROJECT_TOML)
+            .await
+            .expect("failed to write project config");
+        let mut project_cfg = load_config(Some(project_dir), Some(false))
+            .await
+            .expect("failed to load project config");
+
+        let greeter_dir = greeter_dir
+            .canonicalize()
+            .expect("failed to canonicalize dependency project dir");
+        assert_eq!(
+            dependency_project_dirs(&project_cfg),
+            BTreeSet::from([greeter_dir.clone()])
+        );
+
+        // Overrides are left alone until the dependency project is built
+        let mut projects = BTreeMap::from([(greeter_dir, DependencyProject::default())]);
+        apply_dependency_project_refs(&mut project_cfg, &projects);
+        assert_eq!(project_cfg.dev.overrides.imports[0].image_ref, None);
+
+        for project in projects.values_mut() {
+            project.artifact_ref = Some("file:///greeter/build/greeter.par.gz".into());
+        }
+        apply_dependency_project_refs(&mut project_cfg, &projects);
+        assert_eq!(
+            project_cfg.dev.overrides.imports[0].image_ref.as_deref(),
+            Some("file:///greeter/build/greeter.par.gz")
+        );
+        assert_eq!(
+            project_cfg.dev.overrides.exports[0].image_ref.as_deref(),
+            Some("ghcr.io/wasmcloud/http-server:0.26.0")
+        );
+    }
+
+    #[test]
+    fn providers_are_found_by_image_ref() {
+        let provider = |id: &str, image_ref: &str| {
+            ProviderDescription::builder()
+                .id(id)
+                .image_ref(image_ref)
+                .build()
+                .expect("failed to build provider description")
+        };
+        let providers = [
+            provider("greeter", "file:///greeter.par.gz"),
+            provider("http-server", "ghcr.io/wasmcloud/http-server:0.26.0"),
+        ];
+        assert_eq!(
+            provider_ids_with_ref(&providers, "file:///greeter.par.gz"),
+            ["greeter"]
+        );
+        assert!(provider_ids_with_ref(&providers, "file:///other.par.gz").is_empty());
+    }
+}
@@ -1,10 +1,11 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{bail, Context as _, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use notify::event::ModifyKind;
 use notify::{event::EventKind, Event as NotifyEvent, RecursiveMode, Watcher};
 use semver::Version;
@@ -27,6 +28,7 @@ use crate::cmd::up::{
 mod deps;
 mod devloop;
 mod manifest;
+pub mod seed;
 mod session;
 mod wit;
 
@@ -36,7 +38,7 @@ const DEFAULT_HTTP_SERVER_PROVIDER_IMAGE: &str = "ghcr.io/wasmcloud/http-server:
 const DEFAULT_BLOBSTORE_FS_PROVIDER_IMAGE: &str = "ghcr.io/wasmcloud/blobstore-fs:0.10.1";
 const DEFAULT_MESSAGING_NATS_PROVIDER_IMAGE: &str = "ghcr.io/wasmcloud/messaging-nats:0.23.1";
 
-const DEFAULT_INCOMING_HANDLER_ADDRESS: &str = "127.0.0.1:8000";
+pub(crate) const DEFAULT_INCOMING_HANDLER_ADDRESS: &str = "127.0.0.1:8000";
 const DEFAULT_MESSAGING_HANDLER_SUBSCRIPTION: &str = "wasmcloud.dev";
 const DEFAULT_BLOBSTORE_ROOT_DIR: &str = "/tmp";
 const DEFAULT_KEYVALUE_BUCKET: &str = "wasmcloud";
@@ -47,6 +49,8 @@ const SESSIONS_FILE_VERSION: Version = Version::new(0, 1, 0);
 const SESSION_ID_LEN: usize = 6;
 
 const DEFAULT_PROVIDER_STOP_TIMEOUT_MS: u64 = 3000;
+/// How long to wait for wadm to restart a provider under development before starting it directly
+const DEFAULT_PROVIDER_RESTART_TIMEOUT_MS: u64 = 10_000;
 
 /// The path to the dev directory for wash
 async fn dev_dir() -> Result<PathBuf> {
@@ -71,7 +75,11 @@ async fn sessions_file_path() -> Result<PathBuf> {
 }
 
 #[derive(Debug, Clone, Parser)]
+#[clap(args_conflicts_with_subcommands = true)]
 pub struct DevCommand {
+    #[clap(subcommand)]
+    pub command: Option<DevSubcommand>,
+
     #[clap(flatten)]
     pub nats_opts: NatsOpts,
 
@@ -135,6 +143,22 @@ pub struct DevCommand {
     /// Serve the washboard UI alongside the application
     #[clap(long = "dashboard", alias = "ui", env = "WASH_DEV_DASHBOARD", default_value = "false")]
     pub dashboard: bool,
+
+    /// JSON file of keys and values to seed the key-value bucket with before the component starts
+    #[clap(long = "seed-keyvalue", env = "WASH_DEV_SEED_KEYVALUE")]
+    pub seed_keyvalue: Option<PathBuf>,
+
+    /// Directory of fixtures to seed the blobstore with before the component starts, where each
+    /// subdirectory is a container
+    #[clap(long = "seed-blobstore", env = "WASH_DEV_SEED_BLOBSTORE")]
+    pub seed_blobstore: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum DevSubcommand {
+    /// Preload the backing stores used by `wash dev` with fixture data
+    #[clap(name = "seed")]
+    Seed(seed::SeedCommand),
 }
 
 /// Handle `wash dev`
@@ -142,6 +166,10 @@ pub async fn handle_command(
     cmd: DevCommand,
     output_kind: crate::lib::cli::OutputKind,
 ) -> Result<CommandOutput> {
+    if let Some(DevSubcommand::Seed(seed_cmd)) = cmd.command {
+        return seed::handle_command(seed_cmd).await;
+    }
+
     let current_dir =
         std::env::current_dir().context("failed to get current directory for wash dev")?;
     let project_path = cmd.code_dir.unwrap_or(current_dir);
@@ -244,6 +272,9 @@ pub async fn handle_command(
     let lattice = ctl_client.lattice();
 
     // Build state for the run loop
+    // Local provider projects the project depends on are watched alongside the project itself
+    let dependency_project_dirs = devloop::dependency_project_dirs(&project_cfg);
+
     let mut run_loop_state = devloop::RunLoopState {
         dev_session: &mut wash_dev_session,
         nats_client: &nats_client,
@@ -259,6 +290,7 @@ pub async fn handle_command(
         package_args: &cmd.package_args,
         skip_fetch: cmd.skip_wit_fetch,
         output_kind,
+        dependency_projects: BTreeMap::new(),
     };
     let mut ui_handle = None;
 
@@ -299,6 +331,23 @@ pub async fn handle_command(
         bail!("failed to initialize dev session, host did not start.");
     }
 
+    if cmd.seed_keyvalue.is_some() || cmd.seed_blobstore.is_some() {
+        match seed::seed(
+            Some(&nats_client),
+            cmd.seed_keyvalue.as_deref(),
+            cmd.seed_blobstore.as_deref(),
+            &seed::SeedTargets::default(),
+        )
+        .await
+        {
+            Ok((keys, objects)) => eprintln!(
+                "{} Seeded {keys} key(s) and {objects} blobstore object(s)",
+                emoji::INFO_SQUARE
+            ),
+            Err(e) => eprintln!("{} Failed to seed backing stores: {e:#}", emoji::WARN),
+        }
+    }
+
     if cmd.dashboard {
         let port = std::env::var("WASMCLOUD_WASH_UI_PORT")
             .ok()
@@ -332,7 +381,10 @@ pub async fn handle_command(
     let watcher_paused = pause_watch.clone();
 
     // Spawn a file watcher to listen for changes and send on reload_tx
-    let project_path_notify = project_path.clone();
+    let watched_paths = std::iter::once(project_path.clone())
+        .chain(dependency_project_dirs)
+        .collect::<Vec<_>>();
+    let watched_paths_notify = watched_paths.clone();
     let mut watcher = notify::recommended_watcher(move |res: _| match res {
         Ok(event) => {
             if let NotifyEvent {
@@ -346,8 +398,11 @@ pub async fn handle_command(
                 // This is primarily here to avoid recursively triggering reloads for files that are
                 // generated by the build process.
                 if paths.iter().any(|p| {
-                    p.strip_prefix(project_path_notify.as_path())
-                        .is_ok_and(|p| cmd.ignore_dirs.iter().any(|ignore| p.starts_with(ignore)))
+                    watched_paths_notify.iter().any(|watched| {
+                        p.strip_prefix(watched).is_ok_and(|p| {
+                            cmd.ignore_dirs.iter().any(|ignore| p.starts_with(ignore))
+                        })
+                    })
                 }) {
                     return;
                 }
@@ -366,7 +421,11 @@ pub async fn handle_command(
             eprintln!("{} Watch failed: {:?}", emoji::ERROR, e);
         }
     })?;
-    watcher.watch(&project_path.clone(), RecursiveMode::Recursive)?;
+    for path in &watched_paths {
+        watcher
+            .watch(path, RecursiveMode::Recursive)
+            .with_context(|| format!("failed to watch [{}]", path.display()))?;
+    }
 
     // NOTE(brooksmtownsend): Yes, it would make more sense to return here. For some reason unknown to me
     // trying to return any error here will just cause the dev loop to hang infinitely and require a force quit.
@@ -442,8 +501,11 @@ async fn stop_dev_session(
             "{} Cleaning up deployed wasmCloud application(s)...",
             emoji::BROOM
         );
+        let nats_client = ctl_client
+            .nats_client()
+            .context("control interface client has no NATS connection")?;
         dependencies
-            .delete_manifests(&ctl_client.nats_client(), ctl_client.lattice())
+            .delete_manifests(&nats_client, ctl_client.lattice())
             .await?;
     }
 
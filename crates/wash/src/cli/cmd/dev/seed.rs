@@ -0,0 +1,274 @@
+//! Seeding of the backing stores wired by `wash dev` with fixture data, so that handlers which
+//! depend on existing data can be tested reproducibly
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{bail, Context as _, Result};
+use bytes::Bytes;
+use clap::Parser;
+use serde_json::json;
+use walkdir::WalkDir;
+
+use crate::cmd::up::{nats_client_from_wasmcloud_opts, WasmcloudOpts};
+use crate::lib::cli::CommandOutput;
+use crate::lib::generate::emoji;
+
+use super::{DEFAULT_BLOBSTORE_ROOT_DIR, DEFAULT_KEYVALUE_BUCKET};
+
+#[derive(Debug, Clone, Parser)]
+pub struct SeedCommand {
+    /// JSON file containing an object of keys and values to store in the key-value bucket used
+    /// by `wash dev`. String values are stored as-is, other values are stored as JSON
+    #[clap(long = "keyvalue")]
+    pub keyvalue: Option<PathBuf>,
+
+    /// Directory of fixtures to copy into the blobstore used by `wash dev`, where each
+    /// subdirectory is a container containing the objects to store
+    #[clap(long = "blobstore")]
+    pub blobstore: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub targets: SeedTargets,
+
+    #[clap(flatten)]
+    pub wasmcloud_opts: WasmcloudOpts,
+}
+
+/// Backing stores seeded with fixture data, which default to the ones wired by `wash dev`
+#[derive(Debug, Clone, Parser)]
+pub struct SeedTargets {
+    /// Name of the NATS JetStream key-value bucket to seed
+    #[clap(long = "keyvalue-bucket", default_value = DEFAULT_KEYVALUE_BUCKET)]
+    pub keyvalue_bucket: String,
+
+    /// Root directory of the filesystem blobstore to seed
+    #[clap(long = "blobstore-root", default_value = DEFAULT_BLOBSTORE_ROOT_DIR)]
+    pub blobstore_root: PathBuf,
+}
+
+impl Default for SeedTargets {
+    fn default() -> Self {
+        Self {
+            keyvalue_bucket: DEFAULT_KEYVALUE_BUCKET.into(),
+            blobstore_root: DEFAULT_BLOBSTORE_ROOT_DIR.into(),
+        }
+    }
+}
+
+/// Handle `wash dev seed`
+pub async fn handle_command(cmd: SeedCommand) -> Result<CommandOutput> {
+    if cmd.keyvalue.is_none() && cmd.blobstore.is_none() {
+        bail!("nothing to seed, specify fixtures with --keyvalue and/or --blobstore");
+    }
+    let nats_client = if cmd.keyvalue.is_some() {
+        Some(
+            nats_client_from_wasmcloud_opts(&cmd.wasmcloud_opts)
+                .await
+                .context("failed to connect to NATS, ensure `wash dev` or `wash up` is running")?,
+        )
+    } else {
+        None
+    };
+    let (keys, objects) = seed(
+        nats_client.as_ref(),
+        cmd.keyvalue.as_deref(),
+        cmd.blobstore.as_deref(),
+        &cmd.targets,
+    )
+    .await?;
+    Ok(CommandOutput::new(
+        format!(
+            "{} Seeded {keys} key(s) into bucket [{}] and {objects} object(s) into [{}]",
+            emoji::GREEN_CHECK,
+            cmd.targets.keyvalue_bucket,
+            cmd.targets.blobstore_root.display(),
+        ),
+        HashMap::from([
+            ("keys".into(), json!(keys)),
+            ("objects".into(), json!(objects)),
+            ("keyvalue_bucket".into(), json!(cmd.targets.keyvalue_bucket)),
+            ("blobstore_root".into(), json!(cmd.targets.blobstore_root)),
+        ]),
+    ))
+}
+
+/// Seed the key-value bucket and blobstore of `targets` with the given fixtures, returning the
+/// number of keys and objects that were written
+pub(crate) async fn seed(
+    nats_client: Option<&async_nats::Client>,
+    keyvalue: Option<&Path>,
+    blobstore: Option<&Path>,
+    targets: &SeedTargets,
+) -> Result<(usize, usize)> {
+    let keys = if let Some(path) = keyvalue {
+        let entries = read_keyvalue_fixtures(path).await?;
+        let nats_client = nats_client.context("NATS client is required to seed key-value data")?;
+        seed_keyvalue(nats_client, &targets.keyvalue_bucket, entries).await?
+    } else {
+        0
+    };
+    let objects = if let Some(dir) = blobstore {
+        seed_blobstore(dir, &targets.blobstore_root).await?
+    } else {
+        0
+    };
+    Ok((keys, objects))
+}
+
+/// Read key-value fixtures from a JSON object, storing string values as-is and all other values
+/// as JSON
+async fn read_keyvalue_fixtures(path: &Path) -> Result<BTreeMap<String, Bytes>> {
+    let contents = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("failed to read key-value fixtures [{}]", path.display()))?;
+    let entries: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&contents)
+        .with_context(|| {
+            format!(
+                "key-value fixtures [{}] must be a JSON object of keys and values",
+                path.display()
+            )
+        })?;
+    entries
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => Bytes::from(s),
+                value => Bytes::from(serde_json::to_vec(&value)?),
+            };
+            Ok::<_, anyhow::Error>((key, value))
+        })
+        .collect()
+}
+
+async fn seed_keyvalue(
+    nats_client: &async_nats::Client,
+    bucket: &str,
+    entries: BTreeMap<String, Bytes>,
+) -> Result<usize> {
+    let js = async_nats::jetstream::new(nats_client.clone());
+    // The bucket is normally created by the keyvalue-nats provider once linked, which may not
+    // have happened yet when seeding before the component starts
+    let store = match js.get_key_value(bucket).await {
+        Ok(store) => store,
+        Err(_) => js
+            .create_key_value(async_nats::jetstream::kv::Config {
+                bucket: bucket.to_string(),
+                ..Default::default()
+            })
+            .await
+            .with_context(|| format!("failed to create key-value bucket [{bucket}]"))?,
+    };
+    let count = entries.len();
+    for (key, value) in entries {
+        store
+            .put(&key, value)
+            .await
+            .with_context(|| format!("failed to put key [{key}] into bucket [{bucket}]"))?;
+    }
+    Ok(count)
+}
+
+/// Copy every file below `fixtures` into `root`, where the first path component of each file is
+/// the container and the rest is the name of the object
+async fn seed_blobstore(fixtures: &Path, root: &Path) -> Result<usize> {
+    let mut count = 0;
+    for entry in WalkDir::new(fixtures).follow_links(true) {
+        let entry = entry.with_context(|| {
+            format!("failed to read blobstore fixtures [{}]", fixtures.display())
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry
+            .path()
+            .strip_prefix(fixtures)
+            .context("fixture is not below the fixtures directory")?;
+        if relative.components().count() < 2
+            || !relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            bail!(
+                "blobstore fixture [{}] must be placed in a container directory",
+                entry.path().display()
+            );
+        }
+        let dest = root.join(relative);
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create dir [{}]", parent.display()))?;
+        }
+        tokio::fs::copy(entry.path(), &dest)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to copy fixture [{}] to [{}]",
+                    entry.path().display(),
+                    dest.display()
+                )
+            })?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_keyvalue_fixtures, seed_blobstore};
+
+    #[tokio::test]
+    async fn reads_keyvalue_fixtures() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("data.json");
+        tokio::fs::write(
+            &path,
+            r#"{"greeting":"hello","counter":3,"user":{"name":"ada"}}"#,
+        )
+        .await
+        .expect("failed to write fixtures");
+        let entries = read_keyvalue_fixtures(&path)
+            .await
+            .expect("failed to read fixtures");
+        assert_eq!(entries["greeting"], "hello");
+        assert_eq!(entries["counter"], "3");
+        assert_eq!(entries["user"], r#"{"name":"ada"}"#);
+
+        tokio::fs::write(&path, "[1, 2]")
+            .await
+            .expect("failed to write fixtures");
+        assert!(read_keyvalue_fixtures(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn seeds_blobstore_containers() {
+        let fixtures = tempfile::tempdir().expect("failed to create temp dir");
+        let root = tempfile::tempdir().expect("failed to create temp dir");
+        tokio::fs::create_dir_all(fixtures.path().join("images/nested"))
+            .await
+            .expect("failed to create container");
+        tokio::fs::write(fixtures.path().join("images/nested/cat.txt"), "meow")
+            .await
+            .expect("failed to write fixture");
+        tokio::fs::write(fixtures.path().join("images/dog.txt"), "woof")
+            .await
+            .expect("failed to write fixture");
+
+        let count = seed_blobstore(fixtures.path(), root.path())
+            .await
+            .expect("failed to seed blobstore");
+        assert_eq!(count, 2);
+        assert_eq!(
+            tokio::fs::read_to_string(root.path().join("images/nested/cat.txt"))
+                .await
+                .expect("failed to read object"),
+            "meow"
+        );
+
+        tokio::fs::write(fixtures.path().join("loose.txt"), "no container")
+            .await
+            .expect("failed to write fixture");
+        assert!(seed_blobstore(fixtures.path(), root.path()).await.is_err());
+    }
+}
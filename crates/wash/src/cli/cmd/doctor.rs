@@ -0,0 +1,597 @@
+//! `wash doctor` checks the local environment for common problems and suggests how to fix them.
+//!
+//! The checks cover the pieces a local wasmCloud environment depends on: the NATS server of the
+//! current context and its version, the versions of the hosts in the lattice, wadm, credentials
+//! of OCI registries, docker, ports used by `wash up` and `wash dev`, and pid files left behind by
+//! a `wash up` that did not shut down cleanly.
+//!
+//! ```console
+//! wash doctor --registry ghcr.io
+//! ```
+//!
+//! Checks that found a problem print a suggested fix. The command fails if any check found an
+//! error, so it can also be used to verify an environment in scripts.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use clap::Parser;
+use oci_client::client::{ClientConfig, ClientProtocol};
+use oci_client::secrets::RegistryAuth;
+use oci_client::{Reference, RegistryOperation};
+use semver::Version;
+use serde::Serialize;
+use serde_json::json;
+use wasmcloud_control_interface::CtlResponse;
+use wasmcloud_core::tls;
+
+use crate::appearance::spinner::Spinner;
+use crate::cmd::dev::DEFAULT_INCOMING_HANDLER_ADDRESS;
+use crate::cmd::up::is_process_running;
+use crate::common::registry_cmd::resolve_registry_credentials;
+use crate::config::{
+    DEFAULT_NATS_PORT, DEFAULT_NATS_WEBSOCKET_PORT, NATS_SERVER_VERSION, WASMCLOUD_HOST_VERSION,
+};
+use crate::lib::app::get_models;
+use crate::lib::cli::{CliConnectionOpts, CommandOutput, OutputKind};
+use crate::lib::config::{host_pid_file, wadm_pid_file, WashConnectionOptions, WASH_DIRECTORIES};
+use crate::lib::generate::emoji;
+use crate::lib::start::nats_pid_path;
+
+/// Maximum amount of time a single check waits for a response
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Oldest NATS server version providing the JetStream features used by wadm and the host
+const MIN_NATS_VERSION: Version = Version::new(2, 10, 0);
+
+/// Repository the credentials of registries are verified against. Registries hand out tokens
+/// for repositories that do not exist as long as the credentials are valid
+const REGISTRY_CHECK_REPOSITORY: &str = "wasmcloud/wash-doctor";
+
+#[derive(Debug, Clone, Parser)]
+pub struct DoctorCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Registry to verify the credentials of, e.g. `ghcr.io`. Can be specified multiple times
+    #[clap(long = "registry")]
+    pub registries: Vec<String>,
+}
+
+/// Outcome of a check
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Skipped,
+    Warning,
+    Error,
+}
+
+/// Result of a single check of the environment
+#[derive(Clone, Debug, Serialize)]
+pub struct Check {
+    /// What was checked, e.g. `nats`
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// Suggested fix of the problem the check found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn skipped(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            ..Self::ok(name, message)
+        }
+    }
+
+    fn warning(name: &str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Warning,
+            fix: Some(fix.into()),
+            ..Self::ok(name, message)
+        }
+    }
+
+    fn error(name: &str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            status: CheckStatus::Error,
+            fix: Some(fix.into()),
+            ..Self::ok(name, message)
+        }
+    }
+}
+
+pub async fn handle_command(cmd: DoctorCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    let spinner = Spinner::new(&output_kind)?;
+    spinner.update_spinner_message(" Checking environment ...".to_string());
+
+    let wco: WashConnectionOptions = cmd.opts.clone().try_into()?;
+    let lattice = wco.get_lattice();
+    let nats_address = format!(
+        "{}:{}",
+        wco.ctl_host
+            .clone()
+            .unwrap_or_else(|| wco.ctx.ctl_host.clone()),
+        wco.ctl_port
+            .clone()
+            .unwrap_or_else(|| wco.ctx.ctl_port.to_string())
+    );
+
+    let mut checks = Vec::new();
+    let nats = match tokio::time::timeout(CHECK_TIMEOUT, wco.clone().into_nats_client()).await {
+        Ok(Ok(nats)) => {
+            checks.push(check_nats_version(
+                &nats_address,
+                &nats.server_info().version,
+            ));
+            Some(nats)
+        }
+        Ok(Err(err)) => {
+            checks.push(nats_unreachable(&nats_address, &format!("{err:#}")));
+            None
+        }
+        Err(_) => {
+            checks.push(nats_unreachable(&nats_address, "timed out"));
+            None
+        }
+    };
+    if let Some(nats) = &nats {
+        checks.push(check_hosts(wco.clone(), &lattice).await);
+        checks.push(check_wadm(nats, &lattice).await);
+    } else {
+        checks.push(Check::skipped("hosts", "NATS is not reachable"));
+        checks.push(Check::skipped("wadm", "NATS is not reachable"));
+    }
+    if cmd.registries.is_empty() {
+        checks.push(Check::skipped(
+            "registry",
+            "pass --registry to verify the credentials of a registry",
+        ));
+    }
+    for registry in &cmd.registries {
+        checks.push(check_registry(registry).await);
+    }
+    checks.push(check_docker().await);
+
+    let install_dir = WASH_DIRECTORIES.create_downloads_dir()?;
+    let nats_managed = pid_file_process_running(&nats_pid_path(&install_dir)).await == Some(true);
+    checks.extend(check_ports(nats.is_some() || nats_managed).await);
+    checks.push(check_pid_files(&install_dir).await?);
+    spinner.finish_and_clear();
+
+    let text = render_checks(&checks);
+    if checks
+        .iter()
+        .any(|check| check.status == CheckStatus::Error)
+    {
+        bail!("{text}");
+    }
+    let mut map = HashMap::new();
+    map.insert("checks".to_string(), json!(checks));
+    Ok(CommandOutput::new(text, map))
+}
+
+fn nats_unreachable(address: &str, err: &str) -> Check {
+    Check::error(
+        "nats",
+        format!("failed to connect to NATS at {address}: {err}"),
+        "run `wash up` to start a local environment, or point wash to your NATS server with `--ctl-host` and `--ctl-port` or `wash ctx`",
+    )
+}
+
+fn check_nats_version(address: &str, version: &str) -> Check {
+    match Version::parse(version.trim_start_matches('v')) {
+        Ok(parsed) if parsed < MIN_NATS_VERSION => Check::warning(
+            "nats",
+            format!("NATS server at {address} runs v{parsed}, older than the supported v{MIN_NATS_VERSION}"),
+            format!("upgrade the NATS server, `wash up` installs {NATS_SERVER_VERSION}"),
+        ),
+        Ok(parsed) => Check::ok("nats", format!("connected to NATS server v{parsed} at {address}")),
+        Err(_) => Check::warning(
+            "nats",
+            format!("NATS server at {address} reports an unknown version `{version}`"),
+            format!("make sure a NATS server v{MIN_NATS_VERSION} or later is running at {address}"),
+        ),
+    }
+}
+
+async fn check_hosts(wco: WashConnectionOptions, lattice: &str) -> Check {
+    let hosts = async {
+        let client = wco.into_ctl_client(None).await?;
+        client
+            .get_hosts()
+            .await
+            .map_err(|err| anyhow::anyhow!(err))
+            .context("failed to get hosts")
+    };
+    match tokio::time::timeout(CHECK_TIMEOUT, hosts).await {
+        Ok(Ok(hosts)) => {
+            let hosts: Vec<_> = hosts
+                .into_iter()
+                .filter_map(CtlResponse::into_data)
+                .map(|host| (host.id().to_string(), host.version().map(String::from)))
+                .collect();
+            check_host_versions(lattice, &hosts)
+        }
+        Ok(Err(err)) => Check::error(
+            "hosts",
+            format!("failed to query hosts of lattice `{lattice}`: {err:#}"),
+            "make sure the NATS user of the context may publish to the control interface of the lattice",
+        ),
+        Err(_) => Check::error(
+            "hosts",
+            format!("timed out querying hosts of lattice `{lattice}`"),
+            "make sure the NATS user of the context may publish to the control interface of the lattice",
+        ),
+    }
+}
+
+/// Check the versions of the hosts in a lattice for skew, given their IDs and versions
+fn check_host_versions(lattice: &str, hosts: &[(String, Option<String>)]) -> Check {
+    if hosts.is_empty() {
+        return Check::warning(
+            "hosts",
+            format!("no hosts are running in lattice `{lattice}`"),
+            "run `wash up` to start a host",
+        );
+    }
+    let mut versions = BTreeMap::<&str, usize>::new();
+    for (_, version) in hosts {
+        *versions
+            .entry(version.as_deref().unwrap_or("unknown"))
+            .or_default() += 1;
+    }
+    if versions.len() > 1 {
+        let versions = versions
+            .iter()
+            .map(|(version, count)| format!("{version} ({count})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Check::warning(
+            "hosts",
+            format!("hosts in lattice `{lattice}` run different versions: {versions}"),
+            "upgrade all hosts of the lattice to the same version",
+        );
+    }
+    let version = versions.keys().next().copied().unwrap_or("unknown");
+    let expected = Version::parse(WASMCLOUD_HOST_VERSION.trim_start_matches('v'))
+        .expect("invalid default host version");
+    match Version::parse(version.trim_start_matches('v')) {
+        Ok(parsed) if parsed.major != expected.major || parsed.minor != expected.minor => {
+            Check::warning(
+                "hosts",
+                format!(
+                    "{} host(s) in lattice `{lattice}` run v{parsed}, this wash expects {WASMCLOUD_HOST_VERSION}",
+                    hosts.len()
+                ),
+                format!("restart local hosts with `wash down && wash up --wasmcloud-version {WASMCLOUD_HOST_VERSION}`"),
+            )
+        }
+        Ok(parsed) => Check::ok(
+            "hosts",
+            format!("{} host(s) in lattice `{lattice}` run v{parsed}", hosts.len()),
+        ),
+        Err(_) => Check::warning(
+            "hosts",
+            format!("hosts in lattice `{lattice}` report an unknown version `{version}`"),
+            format!("upgrade the hosts to {WASMCLOUD_HOST_VERSION}"),
+        ),
+    }
+}
+
+async fn check_wadm(nats: &async_nats::Client, lattice: &str) -> Check {
+    let fix = "run `wash up` without `--disable-wadm`, or start wadm connected to the same NATS server and JetStream domain";
+    match tokio::time::timeout(CHECK_TIMEOUT, get_models(nats, Some(lattice.to_string()))).await {
+        Ok(Ok(models)) => Check::ok(
+            "wadm",
+            format!(
+                "wadm manages {} application(s) in lattice `{lattice}`",
+                models.len()
+            ),
+        ),
+        Ok(Err(err)) => Check::error(
+            "wadm",
+            format!("wadm did not respond in lattice `{lattice}`: {err}"),
+            fix,
+        ),
+        Err(_) => Check::error(
+            "wadm",
+            format!("timed out waiting for wadm in lattice `{lattice}`"),
+            fix,
+        ),
+    }
+}
+
+async fn check_registry(registry: &str) -> Check {
+    let credentials = match resolve_registry_credentials(registry).await {
+        Ok(credentials) => credentials,
+        Err(err) => {
+            return Check::error(
+                "registry",
+                format!("failed to resolve credentials of {registry}: {err:#}"),
+                "check the registry credentials in `wasmcloud.toml` or your docker config",
+            )
+        }
+    };
+    let (auth, user) = match (credentials.username(), credentials.password()) {
+        (Some(user), Some(password)) => (
+            RegistryAuth::Basic(user.to_string(), password.to_string()),
+            Some(user.to_string()),
+        ),
+        _ => (RegistryAuth::Anonymous, None),
+    };
+    let local = registry.starts_with("localhost") || registry.starts_with("127.0.0.1");
+    let client = oci_client::Client::new(ClientConfig {
+        protocol: if local {
+            ClientProtocol::Http
+        } else {
+            ClientProtocol::Https
+        },
+        extra_root_certificates: tls::NATIVE_ROOTS_OCI.to_vec(),
+        ..Default::default()
+    });
+    let reference = Reference::with_tag(
+        registry.to_string(),
+        REGISTRY_CHECK_REPOSITORY.to_string(),
+        "latest".to_string(),
+    );
+    let login = format!(
+        "log in with `docker login {registry}`, or set WASH_REG_USER and WASH_REG_PASSWORD"
+    );
+    match tokio::time::timeout(
+        CHECK_TIMEOUT,
+        client.auth(&reference, &auth, RegistryOperation::Pull),
+    )
+    .await
+    {
+        Ok(Ok(_)) => match user {
+            Some(user) => Check::ok("registry", format!("authenticated to {registry} as {user}")),
+            None => Check::warning(
+                "registry",
+                format!("no credentials found for {registry}, only public artifacts can be pulled"),
+                login,
+            ),
+        },
+        Ok(Err(err)) => Check::error(
+            "registry",
+            format!("failed to authenticate to {registry}: {err}"),
+            login,
+        ),
+        Err(_) => Check::error(
+            "registry",
+            format!("timed out connecting to {registry}"),
+            format!("make sure {registry} is reachable from this machine"),
+        ),
+    }
+}
+
+async fn check_docker() -> Check {
+    let output = tokio::process::Command::new("docker")
+        .args(["version", "--format", "{{.Server.Version}}"])
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(CHECK_TIMEOUT, output).await {
+        Ok(Ok(output)) if output.status.success() => Check::ok(
+            "docker",
+            format!(
+                "docker {} is available",
+                String::from_utf8_lossy(&output.stdout).trim()
+            ),
+        ),
+        Ok(Ok(output)) => Check::warning(
+            "docker",
+            format!(
+                "docker is installed but its daemon is not reachable: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            "start the docker daemon, e.g. Docker Desktop, if you use docker for local registries",
+        ),
+        Ok(Err(err)) if err.kind() == ErrorKind::NotFound => Check::warning(
+            "docker",
+            "docker is not installed",
+            "install docker if you use it for local registries, wash itself does not require it",
+        ),
+        Ok(Err(err)) => Check::warning(
+            "docker",
+            format!("failed to run docker: {err}"),
+            "make sure the `docker` executable on your PATH works",
+        ),
+        Err(_) => Check::warning(
+            "docker",
+            "timed out waiting for the docker daemon",
+            "restart the docker daemon",
+        ),
+    }
+}
+
+/// Check that the ports used by `wash up` and `wash dev` are free. The NATS ports are expected to
+/// be in use if NATS is running
+async fn check_ports(nats_running: bool) -> Vec<Check> {
+    let dev_port = DEFAULT_INCOMING_HANDLER_ADDRESS
+        .rsplit_once(':')
+        .and_then(|(_, port)| port.parse().ok());
+    let ports = [
+        (
+            DEFAULT_NATS_PORT.parse().ok(),
+            "NATS",
+            Some("`wash up --nats-port`"),
+            true,
+        ),
+        (
+            DEFAULT_NATS_WEBSOCKET_PORT.parse().ok(),
+            "NATS websocket",
+            Some("`wash up --nats-websocket-port`"),
+            true,
+        ),
+        (dev_port, "`wash dev` HTTP server", None, false),
+    ];
+    let mut checks = Vec::new();
+    for (port, purpose, alternative, nats) in ports {
+        let Some(port) = port else {
+            continue;
+        };
+        let port: u16 = port;
+        match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(_) => checks.push(Check::ok(
+                "ports",
+                format!("port {port} ({purpose}) is free"),
+            )),
+            Err(_) if nats && nats_running => checks.push(Check::ok(
+                "ports",
+                format!("port {port} ({purpose}) is in use by NATS"),
+            )),
+            Err(err) if err.kind() == ErrorKind::AddrInUse => checks.push(Check::warning(
+                "ports",
+                format!("port {port} ({purpose}) is in use by another process"),
+                match alternative {
+                    Some(alternative) => format!(
+                        "stop the process listening on port {port}, or pick another port with {alternative}"
+                    ),
+                    None => format!("stop the process listening on port {port}"),
+                },
+            )),
+            Err(err) => checks.push(Check::warning(
+                "ports",
+                format!("failed to check port {port} ({purpose}): {err}"),
+                format!("make sure wash may listen on port {port}"),
+            )),
+        }
+    }
+    checks
+}
+
+/// Returns whether the process of a pid file is running, or `None` if there is no pid file
+async fn pid_file_process_running(path: &Path) -> Option<bool> {
+    let contents = tokio::fs::read_to_string(path).await.ok()?;
+    // The host pid file is JSON containing the version and pid of the host
+    let pid = serde_json::from_str::<serde_json::Value>(&contents)
+        .ok()
+        .and_then(|value| value.get("pid").map(ToString::to_string))
+        .unwrap_or_else(|| contents.trim().to_string());
+    Some(is_process_running(&pid))
+}
+
+async fn check_pid_files(install_dir: &Path) -> Result<Check> {
+    let pid_files: [(PathBuf, &str); 3] = [
+        (host_pid_file()?, "wasmCloud host"),
+        (wadm_pid_file()?, "wadm"),
+        (nats_pid_path(install_dir), "NATS server"),
+    ];
+    let mut stale = Vec::new();
+    for (path, process) in pid_files {
+        if pid_file_process_running(&path).await == Some(false) {
+            stale.push(format!("{} ({process})", path.display()));
+        }
+    }
+    if stale.is_empty() {
+        return Ok(Check::ok(
+            "pid files",
+            "no leftover pid files from `wash up`",
+        ));
+    }
+    Ok(Check::warning(
+        "pid files",
+        format!(
+            "pid files of processes that are no longer running: {}",
+            stale.join(", ")
+        ),
+        "run `wash down` to clean up, or delete the files",
+    ))
+}
+
+fn render_checks(checks: &[Check]) -> String {
+    let mut text = String::new();
+    for check in checks {
+        let symbol = match check.status {
+            CheckStatus::Ok => emoji::GREEN_CHECK,
+            CheckStatus::Skipped => emoji::INFO_SQUARE,
+            CheckStatus::Warning => emoji::WARN,
+            CheckStatus::Error => emoji::ERROR,
+        };
+        let _ = writeln!(text, "{symbol} {}: {}", check.name, check.message);
+        if let Some(fix) = &check.fix {
+            let _ = writeln!(text, "   {} {fix}", emoji::WRENCH);
+        }
+    }
+    let problems = checks
+        .iter()
+        .filter(|check| check.status >= CheckStatus::Warning)
+        .count();
+    if problems == 0 {
+        text.push_str("\nNo problems found");
+    } else {
+        let _ = write!(text, "\n{problems} problem(s) found");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(versions: &[&str]) -> Vec<(String, Option<String>)> {
+        versions
+            .iter()
+            .enumerate()
+            .map(|(i, version)| (format!("N{i}"), Some((*version).to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn detects_host_version_skew() {
+        assert_eq!(
+            check_host_versions("default", &[]).status,
+            CheckStatus::Warning
+        );
+        assert_eq!(
+            check_host_versions("default", &hosts(&[WASMCLOUD_HOST_VERSION])).status,
+            CheckStatus::Ok
+        );
+        let mixed = check_host_versions("default", &hosts(&[WASMCLOUD_HOST_VERSION, "v0.82.0"]));
+        assert_eq!(mixed.status, CheckStatus::Warning);
+        assert!(mixed.message.contains("v0.82.0 (1)"), "{}", mixed.message);
+        assert_eq!(
+            check_host_versions("default", &hosts(&["v0.82.0", "0.82.0"])).status,
+            CheckStatus::Warning
+        );
+    }
+
+    #[test]
+    fn checks_nats_version() {
+        assert_eq!(
+            check_nats_version("127.0.0.1:4222", "2.11.3").status,
+            CheckStatus::Ok
+        );
+        assert_eq!(
+            check_nats_version("127.0.0.1:4222", "2.9.25").status,
+            CheckStatus::Warning
+        );
+    }
+
+    #[test]
+    fn renders_fixes_of_problems() {
+        let text = render_checks(&[
+            Check::ok("nats", "connected"),
+            Check::warning("docker", "docker is not installed", "install docker"),
+        ]);
+        assert!(text.contains("install docker"), "{text}");
+        assert!(text.ends_with("1 problem(s) found"), "{text}");
+    }
+}
@@ -0,0 +1,345 @@
+//! `wash events` shows the events published on the lattice, live and from history.
+//!
+//! Without `--since`, events are tailed live until Ctrl+C. With `--since`, the events retained by
+//! the lattice event stream since then are replayed first, and the command exits afterwards unless
+//! `--follow` is given:
+//!
+//! ```console
+//! wash events --type component_scaled --app petclinic --since 10m
+//! wash events --since 1h --follow --output-file events.jsonl
+//! ```
+//!
+//! Events are printed one per line, or as one CloudEvent JSON object per line with `-o json`.
+//! `--output-file` additionally writes every shown event to a file as JSON lines, regardless of the
+//! output kind.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write as _};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context as _, Result};
+use chrono::SecondsFormat;
+use clap::Parser;
+use cloudevents::event::AttributesReader as _;
+use futures::StreamExt as _;
+use serde_json::json;
+use wasmcloud_control_interface::{
+    EventMatcher, EventStreamGap, LatticeEvent, APP_NAME_ANNOTATION,
+};
+
+use crate::lib::cli::get::parse_watch_interval;
+use crate::lib::cli::{CliConnectionOpts, CommandOutput, OutputKind};
+use crate::lib::common::boxed_err_to_anyhow;
+use crate::lib::config::WashConnectionOptions;
+
+/// Number of characters of host IDs shown in the text output
+const HOST_ID_PREFIX_LEN: usize = 8;
+
+#[derive(Debug, Clone, Parser)]
+pub struct EventsCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Only show events of this type, e.g. `component_scaled`. Can be specified multiple times
+    #[clap(long = "type")]
+    pub event_types: Vec<String>,
+
+    /// Only show events about workloads of this application, i.e. events whose `annotations`
+    /// contain the application name set by wadm
+    #[clap(long = "app")]
+    pub app: Option<String>,
+
+    /// Only show events emitted by this host
+    #[clap(long = "host")]
+    pub host_id: Option<String>,
+
+    /// Only show events about this component or provider
+    #[clap(long = "id")]
+    pub workload_id: Option<String>,
+
+    /// Replay the events published within this duration before now, e.g. `10m` or `1h`. Requires
+    /// the lattice events to be retained in a JetStream stream
+    #[clap(long = "since", value_parser = parse_watch_interval)]
+    pub since: Option<Duration>,
+
+    /// Keep tailing live events after replaying the events given by `--since`
+    #[clap(long = "follow", short = 'f', requires = "since")]
+    pub follow: bool,
+
+    /// Also write the shown events to this file, as one CloudEvent JSON object per line
+    #[clap(long = "output-file")]
+    pub output_file: Option<PathBuf>,
+}
+
+/// Filter of the events shown by `wash events`
+#[derive(Debug, Default)]
+struct EventFilter {
+    event_types: Vec<String>,
+    app: Option<String>,
+    host_id: Option<String>,
+    workload_id: Option<String>,
+}
+
+impl EventFilter {
+    /// Returns whether `event` passes the filter
+    fn matches(&self, event: &LatticeEvent) -> bool {
+        (self.event_types.is_empty() || self.event_types.iter().any(|ty| ty == event.event_type()))
+            && self
+                .host_id
+                .as_ref()
+                .is_none_or(|host_id| host_id == event.source())
+            && self.app.as_ref().is_none_or(|app| {
+                event
+                    .field("annotations")
+                    .and_then(|annotations| annotations.get(APP_NAME_ANNOTATION))
+                    .and_then(serde_json::Value::as_str)
+                    == Some(app.as_str())
+            })
+            && self.workload_id.as_ref().is_none_or(|id| {
+                ["component_id", "provider_id"]
+                    .iter()
+                    .any(|field| event.field(field).and_then(serde_json::Value::as_str) == Some(id))
+            })
+    }
+
+    /// Returns the matcher limiting the replayed events to the filtered event types, if any.
+    /// The remaining conditions are checked by [`EventFilter::matches`]
+    fn replay_matcher(&self) -> Option<EventMatcher> {
+        if self.event_types.is_empty() {
+            return None;
+        }
+        Some(
+            self.event_types
+                .iter()
+                .fold(EventMatcher::default(), |matcher, ty| {
+                    matcher.or_event_type(ty.as_str())
+                }),
+        )
+    }
+}
+
+/// Writes the shown events to stdout and the output file
+struct EventPrinter {
+    output_kind: OutputKind,
+    file: Option<BufWriter<File>>,
+    count: usize,
+}
+
+impl EventPrinter {
+    fn print(&mut self, event: &LatticeEvent) -> Result<()> {
+        let json = serde_json::to_string(event.as_event()).context("failed to serialize event")?;
+        match self.output_kind {
+            OutputKind::Json => println!("{json}"),
+            OutputKind::Text => println!("{}", render_event(event)),
+        }
+        if let Some(file) = &mut self.file {
+            writeln!(file, "{json}").context("failed to write event to output file")?;
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<usize> {
+        if let Some(mut file) = self.file {
+            file.flush().context("failed to flush output file")?;
+        }
+        Ok(self.count)
+    }
+}
+
+/// Invoke `wash events`
+pub async fn handle_command(cmd: EventsCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    let filter = EventFilter {
+        event_types: cmd.event_types,
+        app: cmd.app,
+        host_id: cmd.host_id,
+        workload_id: cmd.workload_id,
+    };
+    let file = cmd
+        .output_file
+        .as_ref()
+        .map(|path| {
+            File::create(path)
+                .map(BufWriter::new)
+                .with_context(|| format!("failed to create output file [{}]", path.display()))
+        })
+        .transpose()?;
+    let mut printer = EventPrinter {
+        output_kind,
+        file,
+        count: 0,
+    };
+
+    let wco: WashConnectionOptions = cmd.opts.try_into()?;
+    let client = wco.into_ctl_client(None).await?;
+
+    // Subscribe to live events before replaying, so that no events are missed in between
+    let follow = cmd.since.is_none() || cmd.follow;
+    let receiver = if follow {
+        Some(
+            client
+                .events_receiver(vec![">".to_string()])
+                .await
+                .map_err(boxed_err_to_anyhow)?,
+        )
+    } else {
+        None
+    };
+
+    let mut last_replayed = None;
+    if let Some(since) = cmd.since {
+        let from = SystemTime::now()
+            .checked_sub(since)
+            .context("`--since` is too far in the past")?;
+        let mut events = client
+            .replay_events(from, None, filter.replay_matcher())
+            .await
+            .map_err(boxed_err_to_anyhow)?;
+        while let Some(event) = events.next().await {
+            let event = event.map_err(boxed_err_to_anyhow)?;
+            last_replayed = event.as_event().time().copied();
+            if filter.matches(&event) {
+                printer.print(&event)?;
+            }
+        }
+    }
+
+    if let Some(mut receiver) = receiver {
+        if output_kind == OutputKind::Text {
+            eprintln!("Watching lattice events, press Ctrl+C to stop");
+        }
+        let mut ctrlc = std::pin::pin!(tokio::signal::ctrl_c());
+        loop {
+            let event = tokio::select! {
+                event = receiver.recv() => event,
+                _ = &mut ctrlc => break,
+            };
+            let Some(event) = event else {
+                bail!("lattice event subscription ended");
+            };
+            if let Some(gap) = EventStreamGap::from_event(&event) {
+                eprintln!("🟨 Lattice events may have been missed ({:?})", gap.reason);
+                continue;
+            }
+            let Ok(event) = LatticeEvent::try_from(event) else {
+                continue;
+            };
+            // Events published while replaying are received both ways
+            if last_replayed
+                .is_some_and(|last| event.as_event().time().is_some_and(|time| *time <= last))
+            {
+                continue;
+            }
+            if filter.matches(&event) {
+                printer.print(&event)?;
+            }
+        }
+    }
+
+    let count = printer.finish()?;
+    let mut text = format!("Showed {count} event(s)");
+    if let Some(path) = &cmd.output_file {
+        text.push_str(&format!(", written to [{}]", path.display()));
+    }
+    Ok(CommandOutput::new(
+        text,
+        HashMap::from([
+            ("events".into(), json!(count)),
+            ("output_file".into(), json!(cmd.output_file)),
+        ]),
+    ))
+}
+
+/// Render an event as a single line, e.g.
+/// `2024-01-01T00:00:00.000Z component_scaled NBXMJK3Z component_id=echo max_instances=1`
+fn render_event(event: &LatticeEvent) -> String {
+    let time = event
+        .as_event()
+        .time()
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_else(|| "-".to_string());
+    let source = event.source();
+    let source = source.get(..HOST_ID_PREFIX_LEN).unwrap_or(source);
+    let mut line = format!("{time} {:<24} {source}", event.event_type());
+    if let Some(data) = event.data().as_object() {
+        for (key, value) in data {
+            match value {
+                serde_json::Value::String(s) => line.push_str(&format!(" {key}={s}")),
+                serde_json::Value::Number(_) | serde_json::Value::Bool(_) => {
+                    line.push_str(&format!(" {key}={value}"));
+                }
+                _ => {}
+            }
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use cloudevents::{EventBuilder as _, EventBuilderV10};
+
+    use super::*;
+
+    const HOST_ID: &str = "NBXMJK3ZHL4V6OGCFBMVYOSMLRWAT5H3BHZ2KRCHRQ5E7SOAS6Y7XNCC";
+
+    fn component_scaled(data: serde_json::Value) -> LatticeEvent {
+        let event = EventBuilderV10::new()
+            .id("1")
+            .source(HOST_ID)
+            .ty("com.wasmcloud.lattice.component_scaled")
+            .data("application/json", data)
+            .build()
+            .expect("valid event");
+        LatticeEvent::try_from(event).expect("event with JSON data")
+    }
+
+    #[test]
+    fn filters_events() {
+        let event = component_scaled(json!({
+            "component_id": "petclinic-api",
+            "max_instances": 1,
+            "annotations": { APP_NAME_ANNOTATION: "petclinic" },
+        }));
+        assert!(EventFilter::default().matches(&event));
+
+        let filter = EventFilter {
+            event_types: vec!["component_scaled".into()],
+            app: Some("petclinic".into()),
+            host_id: Some(HOST_ID.into()),
+            workload_id: Some("petclinic-api".into()),
+        };
+        assert!(filter.matches(&event));
+        for filter in [
+            EventFilter {
+                event_types: vec!["provider_started".into()],
+                ..Default::default()
+            },
+            EventFilter {
+                app: Some("other".into()),
+                ..Default::default()
+            },
+            EventFilter {
+                workload_id: Some("other".into()),
+                ..Default::default()
+            },
+        ] {
+            assert!(!filter.matches(&event), "{filter:?} must not match");
+        }
+    }
+
+    #[test]
+    fn renders_scalar_fields() {
+        let event = component_scaled(json!({
+            "component_id": "echo",
+            "max_instances": 2,
+            "annotations": {},
+        }));
+        assert_eq!(
+            render_event(&event),
+            "- component_scaled         NBXMJK3Z component_id=echo max_instances=2"
+        );
+    }
+}
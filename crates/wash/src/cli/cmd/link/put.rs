@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use anyhow::{anyhow, bail, Result};
 use serde_json::json;
 use crate::lib::cli::link::{put_link, LinkPutCommand};
-use crate::lib::cli::{CommandOutput, OutputKind};
+use crate::lib::cli::{with_secrets, CommandOutput, OutputKind};
 use wasmcloud_control_interface::Link;
 
 use crate::appearance::spinner::Spinner;
@@ -22,6 +22,8 @@ pub async fn invoke(
         interfaces,
         source_config,
         target_config,
+        source_secrets,
+        target_secrets,
     }: LinkPutCommand,
     output_kind: OutputKind,
 ) -> Result<CommandOutput> {
@@ -39,8 +41,8 @@ pub async fn invoke(
             .wit_namespace(&wit_namespace)
             .wit_package(&wit_package)
             .interfaces(interfaces)
-            .source_config(source_config)
-            .target_config(target_config)
+            .source_config(with_secrets(source_config, &source_secrets))
+            .target_config(with_secrets(target_config, &target_secrets))
             .build()
             .map_err(|e| anyhow!(e).context("failed to build link"))?,
     )
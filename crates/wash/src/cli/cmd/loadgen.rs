@@ -0,0 +1,611 @@
+//! `wash loadgen` generates HTTP load against a `wasi:http` component to measure its latency.
+//!
+//! The target is either a URL or the ID of a component that an HTTP server provider is linked to,
+//! in which case the address the provider listens on for the component is looked up in the
+//! lattice. Since `wash dev` links components to an HTTP server on the local lattice, a component
+//! under development can be load tested with just its ID:
+//!
+//! ```console
+//! wash loadgen http-hello-world --rps 500 --duration 60s --save before.json
+//! wash loadgen http-hello-world --rps 500 --duration 60s --baseline before.json
+//! ```
+//!
+//! Requests are sent at a constant rate regardless of how fast the component responds, so that a
+//! slow handler shows up as higher latency rather than as a lower request rate. `--max-error-rate`
+//! and `--max-p99` define budgets that fail the command when exceeded, e.g. in CI.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, ensure, Context as _, Result};
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::{mpsc, Semaphore};
+
+use crate::appearance::spinner::Spinner;
+use crate::lib::cli::get::parse_watch_interval;
+use crate::lib::cli::{CliConnectionOpts, CommandOutput, OutputKind};
+use crate::lib::common::{boxed_err_to_anyhow, resolve_component_id};
+use crate::lib::config::WashConnectionOptions;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds
+const HISTOGRAM_BUCKETS_MS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+
+/// Width of the longest bar of the rendered latency histogram
+const HISTOGRAM_WIDTH: u64 = 40;
+
+#[derive(Debug, Clone, Parser)]
+pub struct LoadgenCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// URL to send requests to, or the ID of a component linked to an HTTP server provider
+    #[clap(name = "target")]
+    pub target: String,
+
+    /// Path requested when the target is a component ID
+    #[clap(long = "path", default_value = "/")]
+    pub path: String,
+
+    /// Number of requests to send per second
+    #[clap(long = "rps", default_value_t = 100)]
+    pub rps: u32,
+
+    /// How long to generate load for, e.g. `30s` or `2m`. A plain number is interpreted as
+    /// milliseconds
+    #[clap(long = "duration", default_value = "30s", value_parser = parse_watch_interval)]
+    pub duration: Duration,
+
+    /// Maximum number of requests in flight. Requests that would exceed it are dropped and
+    /// reported rather than delaying the requests that follow
+    #[clap(long = "max-in-flight", default_value_t = 256)]
+    pub max_in_flight: usize,
+
+    /// Timeout of a single request, e.g. `10s`. A plain number is interpreted as milliseconds
+    #[clap(long = "request-timeout", default_value = "10s", value_parser = parse_watch_interval)]
+    pub request_timeout: Duration,
+
+    /// HTTP method of the requests
+    #[clap(long = "method", default_value = "GET")]
+    pub method: String,
+
+    /// Header to send with every request, in the form `name: value`. Can be specified multiple
+    /// times
+    #[clap(short = 'H', long = "header")]
+    pub headers: Vec<String>,
+
+    /// Body to send with every request
+    #[clap(long = "body")]
+    pub body: Option<String>,
+
+    /// Fail if more than this fraction of requests failed or were dropped, e.g. `0.01`
+    #[clap(long = "max-error-rate")]
+    pub max_error_rate: Option<f64>,
+
+    /// Fail if the 99th percentile latency exceeds this duration, e.g. `250ms`
+    #[clap(long = "max-p99", value_parser = parse_watch_interval)]
+    pub max_p99: Option<Duration>,
+
+    /// Save the report of this run as JSON, to compare later runs against it with `--baseline`
+    #[clap(long = "save")]
+    pub save: Option<PathBuf>,
+
+    /// Compare this run against a report saved with `--save`
+    #[clap(long = "baseline")]
+    pub baseline: Option<PathBuf>,
+}
+
+/// Outcome of a single request
+#[derive(Debug)]
+enum Outcome {
+    /// A response was received with the given status after the given latency
+    Response(u16, Duration),
+    /// The request failed before a response was received
+    Failed,
+}
+
+/// Latency percentiles of a run, in microseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub min_us: u64,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p90_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// A bucket of the latency histogram of a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// Upper bound of the bucket in milliseconds, or `None` for the overflow bucket
+    pub le_ms: Option<u64>,
+    pub count: u64,
+}
+
+/// Report of a load generation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadReport {
+    pub url: String,
+    pub target_rps: u32,
+    pub duration_ms: u64,
+    /// Number of requests sent
+    pub requests: u64,
+    /// Number of requests that failed or received a response with a non-success status
+    pub errors: u64,
+    /// Number of requests not sent because too many requests were in flight
+    pub dropped: u64,
+    pub status_codes: BTreeMap<u16, u64>,
+    pub latency: LatencySummary,
+    pub histogram: Vec<HistogramBucket>,
+}
+
+impl LoadReport {
+    fn new(
+        url: String,
+        target_rps: u32,
+        elapsed: Duration,
+        outcomes: &[Outcome],
+        dropped: u64,
+    ) -> Self {
+        let mut status_codes = BTreeMap::new();
+        let mut latencies = Vec::with_capacity(outcomes.len());
+        let mut errors = 0;
+        for outcome in outcomes {
+            match outcome {
+                Outcome::Response(status, latency) => {
+                    *status_codes.entry(*status).or_default() += 1;
+                    latencies.push(*latency);
+                    if !(200..400).contains(status) {
+                        errors += 1;
+                    }
+                }
+                Outcome::Failed => errors += 1,
+            }
+        }
+        latencies.sort_unstable();
+        Self {
+            url,
+            target_rps,
+            duration_ms: elapsed.as_millis().try_into().unwrap_or(u64::MAX),
+            requests: outcomes.len() as u64,
+            errors,
+            dropped,
+            status_codes,
+            latency: summarize(&latencies),
+            histogram: histogram(&latencies),
+        }
+    }
+
+    /// Number of requests per second that were sent
+    fn achieved_rps(&self) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        self.requests as f64 * 1000.0 / self.duration_ms as f64
+    }
+
+    /// Fraction of attempted requests that failed or were dropped
+    fn error_rate(&self) -> f64 {
+        let attempted = self.requests + self.dropped;
+        if attempted == 0 {
+            return 0.0;
+        }
+        (self.errors + self.dropped) as f64 / attempted as f64
+    }
+
+    /// Describe every budget this run exceeded
+    fn budget_violations(
+        &self,
+        max_error_rate: Option<f64>,
+        max_p99: Option<Duration>,
+    ) -> Vec<String> {
+        let mut violations = Vec::new();
+        if let Some(max) = max_error_rate {
+            if self.error_rate() > max {
+                violations.push(format!(
+                    "error rate {:.2}% exceeds {:.2}%",
+                    self.error_rate() * 100.0,
+                    max * 100.0
+                ));
+            }
+        }
+        if let Some(max) = max_p99 {
+            let p99 = Duration::from_micros(self.latency.p99_us);
+            if p99 > max {
+                violations.push(format!("p99 latency {p99:?} exceeds {max:?}"));
+            }
+        }
+        violations
+    }
+}
+
+/// Invoke `wash loadgen`
+pub async fn handle_command(cmd: LoadgenCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    ensure!(cmd.rps > 0, "--rps must be greater than zero");
+    ensure!(
+        cmd.max_in_flight > 0,
+        "--max-in-flight must be greater than zero"
+    );
+    let baseline = match &cmd.baseline {
+        Some(path) => {
+            let report = tokio::fs::read(path)
+                .await
+                .with_context(|| format!("failed to read baseline {}", path.display()))?;
+            Some(
+                serde_json::from_slice::<LoadReport>(&report)
+                    .with_context(|| format!("failed to parse baseline {}", path.display()))?,
+            )
+        }
+        None => None,
+    };
+    let url = if cmd.target.starts_with("http://") || cmd.target.starts_with("https://") {
+        cmd.target.clone()
+    } else {
+        let wco: WashConnectionOptions = cmd.opts.clone().try_into()?;
+        let client = wco.into_ctl_client(None).await?;
+        let component_id = resolve_component_id(&cmd.target, &client).await;
+        let address = component_http_address(&client, &component_id).await?;
+        format!(
+            "{}/{}",
+            address.trim_end_matches('/'),
+            cmd.path.trim_start_matches('/')
+        )
+    };
+
+    let sp = Spinner::new(&output_kind)?;
+    sp.update_spinner_message(format!(
+        "Sending {} requests per second to {url} for {}...",
+        cmd.rps,
+        humantime::format_duration(cmd.duration)
+    ));
+    let report = generate_load(&cmd, url).await;
+    sp.finish_and_clear();
+    let report = report?;
+
+    if let Some(path) = &cmd.save {
+        tokio::fs::write(path, serde_json::to_vec_pretty(&report)?)
+            .await
+            .with_context(|| format!("failed to save report to {}", path.display()))?;
+    }
+    let mut text = render_report(&report);
+    if let Some(baseline) = &baseline {
+        text.push('\n');
+        text.push_str(&render_comparison(baseline, &report));
+    }
+    let violations = report.budget_violations(cmd.max_error_rate, cmd.max_p99);
+    if !violations.is_empty() {
+        bail!(
+            "{text}\nLoad test exceeded its budget: {}",
+            violations.join(", ")
+        );
+    }
+
+    let mut map = HashMap::from([("report".into(), json!(report))]);
+    if let Some(baseline) = baseline {
+        map.insert("baseline".into(), json!(baseline));
+    }
+    Ok(CommandOutput::new(text, map))
+}
+
+/// Look up the address of the HTTP server provider linked to a component on `wasi:http`
+async fn component_http_address(
+    client: &wasmcloud_control_interface::Client,
+    component_id: &str,
+) -> Result<String> {
+    let links = client
+        .get_links()
+        .await
+        .map_err(boxed_err_to_anyhow)?
+        .into_data()
+        .unwrap_or_default();
+    for link in links.iter().filter(|link| {
+        link.target() == component_id
+            && link.wit_namespace() == "wasi"
+            && link.wit_package() == "http"
+            && link.interfaces().iter().any(|i| i == "incoming-handler")
+    }) {
+        for name in link.source_config() {
+            let config = client
+                .get_config(name)
+                .await
+                .map_err(boxed_err_to_anyhow)?
+                .into_data()
+                .unwrap_or_default();
+            if let Some(address) = config.get("address") {
+                return Ok(if address.starts_with("http") {
+                    address.clone()
+                } else {
+                    format!("http://{address}")
+                });
+            }
+        }
+    }
+    bail!(
+        "No HTTP server address found for component [{component_id}], pass a URL instead or link an HTTP server provider to the component with an `address` config"
+    )
+}
+
+/// Send requests to `url` at the configured rate for the configured duration
+async fn generate_load(cmd: &LoadgenCommand, url: String) -> Result<LoadReport> {
+    let method = reqwest::Method::from_bytes(cmd.method.to_uppercase().as_bytes())
+        .with_context(|| format!("invalid HTTP method [{}]", cmd.method))?;
+    let mut headers = reqwest::header::HeaderMap::new();
+    for header in &cmd.headers {
+        let (name, value) = header
+            .split_once(':')
+            .with_context(|| format!("invalid header [{header}], expected `name: value`"))?;
+        headers.insert(
+            reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+                .with_context(|| format!("invalid header name [{name}]"))?,
+            value
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid header value [{value}]"))?,
+        );
+    }
+    let client = reqwest::Client::builder()
+        .timeout(cmd.request_timeout)
+        .default_headers(headers)
+        .build()
+        .context("failed to build HTTP client")?;
+    let body = cmd.body.clone().unwrap_or_default();
+
+    let in_flight = Arc::new(Semaphore::new(cmd.max_in_flight));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / f64::from(cmd.rps)));
+    let mut dropped = 0;
+    let start = Instant::now();
+    while start.elapsed() < cmd.duration {
+        interval.tick().await;
+        let Ok(permit) = Arc::clone(&in_flight).try_acquire_owned() else {
+            dropped += 1;
+            continue;
+        };
+        let request = client.request(method.clone(), &url).body(body.clone());
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let sent = Instant::now();
+            let outcome = match request.send().await {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    match response.bytes().await {
+                        Ok(_) => Outcome::Response(status, sent.elapsed()),
+                        Err(_) => Outcome::Failed,
+                    }
+                }
+                Err(_) => Outcome::Failed,
+            };
+            drop(permit);
+            let _ = tx.send(outcome);
+        });
+    }
+    let elapsed = start.elapsed();
+    // Wait for all requests in flight to complete
+    drop(tx);
+    let mut outcomes = Vec::new();
+    while let Some(outcome) = rx.recv().await {
+        outcomes.push(outcome);
+    }
+    Ok(LoadReport::new(url, cmd.rps, elapsed, &outcomes, dropped))
+}
+
+/// Summarize sorted latencies
+fn summarize(latencies: &[Duration]) -> LatencySummary {
+    let (Some(min), Some(max)) = (latencies.first(), latencies.last()) else {
+        return LatencySummary::default();
+    };
+    let micros = |d: &Duration| d.as_micros().try_into().unwrap_or(u64::MAX);
+    let percentile =
+        |p: usize| micros(&latencies[(latencies.len() * p / 100).min(latencies.len() - 1)]);
+    let total: Duration = latencies.iter().sum();
+    LatencySummary {
+        min_us: micros(min),
+        mean_us: micros(&(total / latencies.len() as u32)),
+        p50_us: percentile(50),
+        p90_us: percentile(90),
+        p99_us: percentile(99),
+        max_us: micros(max),
+    }
+}
+
+/// Count latencies into the buckets of [`HISTOGRAM_BUCKETS_MS`] and an overflow bucket
+fn histogram(latencies: &[Duration]) -> Vec<HistogramBucket> {
+    let mut buckets: Vec<_> = HISTOGRAM_BUCKETS_MS
+        .iter()
+        .map(|le| HistogramBucket {
+            le_ms: Some(*le),
+            count: 0,
+        })
+        .chain([HistogramBucket {
+            le_ms: None,
+            count: 0,
+        }])
+        .collect();
+    for latency in latencies {
+        let index = HISTOGRAM_BUCKETS_MS
+            .iter()
+            .position(|le| *latency <= Duration::from_millis(*le))
+            .unwrap_or(HISTOGRAM_BUCKETS_MS.len());
+        buckets[index].count += 1;
+    }
+    buckets
+}
+
+fn render_report(report: &LoadReport) -> String {
+    let latency = &report.latency;
+    let us = Duration::from_micros;
+    let mut text = format!(
+        "Load test of {} at {} requests per second over {}\n\n",
+        report.url,
+        report.target_rps,
+        humantime::format_duration(Duration::from_millis(report.duration_ms)),
+    );
+    let _ = writeln!(
+        text,
+        "Requests:     {} sent ({:.1}/s), {} errors, {} dropped",
+        report.requests,
+        report.achieved_rps(),
+        report.errors,
+        report.dropped
+    );
+    let _ = writeln!(
+        text,
+        "Status codes: {}",
+        report
+            .status_codes
+            .iter()
+            .map(|(status, count)| format!("{status}={count}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let _ = writeln!(
+        text,
+        "Latency:      min {:?}, mean {:?}, max {:?}",
+        us(latency.min_us),
+        us(latency.mean_us),
+        us(latency.max_us)
+    );
+    let _ = writeln!(
+        text,
+        "Percentiles:  p50 {:?}, p90 {:?}, p99 {:?}\n",
+        us(latency.p50_us),
+        us(latency.p90_us),
+        us(latency.p99_us)
+    );
+    let max = report.histogram.iter().map(|b| b.count).max().unwrap_or(0);
+    for bucket in &report.histogram {
+        let label = match bucket.le_ms {
+            Some(le) => format!("<= {le}ms"),
+            None => format!(
+                "> {}ms",
+                HISTOGRAM_BUCKETS_MS[HISTOGRAM_BUCKETS_MS.len() - 1]
+            ),
+        };
+        let width = (bucket.count * HISTOGRAM_WIDTH)
+            .checked_div(max)
+            .unwrap_or_default();
+        let _ = writeln!(
+            text,
+            "{label:>10} | {:<width$} {}",
+            "#".repeat(width as usize),
+            bucket.count,
+            width = HISTOGRAM_WIDTH as usize
+        );
+    }
+    text
+}
+
+fn render_comparison(baseline: &LoadReport, report: &LoadReport) -> String {
+    let change = |before: f64, after: f64| {
+        if before == 0.0 {
+            "n/a".to_string()
+        } else {
+            format!("{:+.1}%", (after - before) / before * 100.0)
+        }
+    };
+    let us = Duration::from_micros;
+    let mut text = String::from("Compared to baseline:\n");
+    for (name, before, after) in [
+        ("p50", baseline.latency.p50_us, report.latency.p50_us),
+        ("p90", baseline.latency.p90_us, report.latency.p90_us),
+        ("p99", baseline.latency.p99_us, report.latency.p99_us),
+    ] {
+        let _ = writeln!(
+            text,
+            "  {name:<11} {:?} -> {:?} ({})",
+            us(before),
+            us(after),
+            change(before as f64, after as f64)
+        );
+    }
+    let _ = writeln!(
+        text,
+        "  error rate  {:.2}% -> {:.2}%",
+        baseline.error_rate() * 100.0,
+        report.error_rate() * 100.0
+    );
+    let _ = writeln!(
+        text,
+        "  throughput  {:.1}/s -> {:.1}/s ({})",
+        baseline.achieved_rps(),
+        report.achieved_rps(),
+        change(baseline.achieved_rps(), report.achieved_rps())
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcomes(latencies_ms: &[u64]) -> Vec<Outcome> {
+        latencies_ms
+            .iter()
+            .map(|ms| Outcome::Response(200, Duration::from_millis(*ms)))
+            .collect()
+    }
+
+    #[test]
+    fn summarizes_latencies() {
+        let mut outcomes = outcomes(&(1..=100).collect::<Vec<_>>());
+        outcomes.push(Outcome::Response(500, Duration::from_millis(7000)));
+        outcomes.push(Outcome::Failed);
+        let report = LoadReport::new(
+            "http://127.0.0.1:8000/".into(),
+            100,
+            Duration::from_secs(1),
+            &outcomes,
+            2,
+        );
+        assert_eq!(report.requests, 102);
+        assert_eq!(report.errors, 2);
+        assert_eq!(report.status_codes, BTreeMap::from([(200, 100), (500, 1)]));
+        assert_eq!(report.latency.min_us, 1000);
+        assert_eq!(report.latency.p50_us, 51_000);
+        assert_eq!(report.latency.p99_us, 100_000);
+        assert_eq!(report.latency.max_us, 7_000_000);
+        assert_eq!(report.histogram[0].count, 1);
+        assert_eq!(report.histogram[6].count, 50);
+        assert_eq!(report.histogram.last().map(|b| b.count), Some(1));
+        assert_eq!(report.histogram.iter().map(|b| b.count).sum::<u64>(), 101);
+        assert!((report.error_rate() - 4.0 / 104.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn checks_budgets() {
+        let report = LoadReport::new(
+            "http://127.0.0.1:8000/".into(),
+            10,
+            Duration::from_secs(1),
+            &outcomes(&[10, 20, 300]),
+            0,
+        );
+        assert!(report
+            .budget_violations(Some(0.0), Some(Duration::from_secs(1)))
+            .is_empty());
+        assert_eq!(
+            report.budget_violations(None, Some(Duration::from_millis(100))),
+            vec!["p99 latency 300ms exceeds 100ms".to_string()]
+        );
+    }
+
+    #[test]
+    fn report_roundtrips() {
+        let report = LoadReport::new(
+            "http://127.0.0.1:8000/".into(),
+            10,
+            Duration::from_secs(1),
+            &outcomes(&[10, 20]),
+            0,
+        );
+        let json = serde_json::to_vec(&report).expect("failed to serialize report");
+        let parsed: LoadReport = serde_json::from_slice(&json).expect("failed to parse report");
+        assert_eq!(parsed, report);
+        assert!(render_comparison(&report, &parsed).contains("p99         20ms -> 20ms (+0.0%)"));
+    }
+}
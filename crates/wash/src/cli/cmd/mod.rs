@@ -1,7 +1,15 @@
 //! Commands that are exposed by `wash`
 
 pub mod config;
+pub mod demo;
 pub mod dev;
+pub mod doctor;
+pub mod events;
 pub mod link;
+pub mod loadgen;
+pub mod policy;
+pub mod profile;
+pub mod repl;
+pub mod route;
 pub mod up;
 pub mod wit;
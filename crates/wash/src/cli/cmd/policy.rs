@@ -0,0 +1,149 @@
+//! `wash policy` installs the policy pack of an organization, which wash enforces locally.
+//!
+//! ```console
+//! wash policy pull ghcr.io/example/wash-policy:1.0.0
+//! wash policy show
+//! WASH_POLICY_OVERRIDE="registry migration" wash push --insecure localhost:5000/api:0.1.0 api.wasm
+//! wash policy audit
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use clap::{Parser, Subcommand};
+use oci_client::Reference;
+use serde_json::json;
+
+use crate::lib::cli::registry::AuthOpts;
+use crate::lib::cli::{CommandOutput, OutputKind};
+use crate::lib::policy::{
+    audit_log_path, policy_path, pull_policy, read_audit_log, Policy, POLICY_OVERRIDE_ENV,
+};
+use crate::lib::registry::OciPullOptions;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum PolicyCommand {
+    /// Install a policy pack from an OCI registry or a local file, replacing the installed one
+    #[clap(name = "pull")]
+    Pull(PolicyPullCommand),
+
+    /// Show the installed policy pack
+    #[clap(name = "show")]
+    Show,
+
+    /// Remove the installed policy pack
+    #[clap(name = "remove", alias = "rm")]
+    Remove,
+
+    /// Show the policy violations that were overridden
+    #[clap(name = "audit")]
+    Audit,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct PolicyPullCommand {
+    /// OCI reference or path of the policy pack
+    #[clap(name = "policy")]
+    pub policy: String,
+
+    #[clap(flatten)]
+    pub opts: AuthOpts,
+}
+
+/// Invoke `wash policy`
+pub async fn handle_command(command: PolicyCommand, _: OutputKind) -> Result<CommandOutput> {
+    match command {
+        PolicyCommand::Pull(cmd) => {
+            let policy = if Path::new(&cmd.policy).is_file() {
+                tokio::fs::read_to_string(&cmd.policy)
+                    .await
+                    .with_context(|| format!("failed to read policy `{}`", cmd.policy))?
+            } else {
+                let image_ref: Reference = cmd
+                    .policy
+                    .parse()
+                    .with_context(|| format!("invalid policy reference `{}`", cmd.policy))?;
+                pull_policy(
+                    &image_ref,
+                    OciPullOptions {
+                        user: cmd.opts.user,
+                        password: cmd.opts.password,
+                        insecure: cmd.opts.insecure,
+                        insecure_skip_tls_verify: cmd.opts.insecure_skip_tls_verify,
+                        ..Default::default()
+                    },
+                )
+                .await?
+            };
+            let installed = Policy::install(&policy).await?;
+            Ok(CommandOutput::new(
+                format!(
+                    "Installed policy [{}] from {} to {}",
+                    installed.name,
+                    cmd.policy,
+                    policy_path().display()
+                ),
+                HashMap::from([
+                    ("name".into(), json!(installed.name)),
+                    ("source".into(), json!(cmd.policy)),
+                    ("path".into(), json!(policy_path())),
+                ]),
+            ))
+        }
+        PolicyCommand::Show => {
+            let Some(policy) = Policy::load().await? else {
+                return Ok(CommandOutput::new(
+                    "No policy is installed",
+                    HashMap::from([("policy".into(), json!(null))]),
+                ));
+            };
+            let text = toml::to_string_pretty(&policy).context("failed to render policy")?;
+            Ok(CommandOutput::new(
+                text,
+                HashMap::from([("policy".into(), json!(policy))]),
+            ))
+        }
+        PolicyCommand::Remove => {
+            let removed = Policy::uninstall().await?;
+            let text = if removed {
+                "Removed the installed policy"
+            } else {
+                "No policy is installed"
+            };
+            Ok(CommandOutput::new(
+                text,
+                HashMap::from([("removed".into(), json!(removed))]),
+            ))
+        }
+        PolicyCommand::Audit => {
+            let overrides = read_audit_log().await?;
+            let text = if overrides.is_empty() {
+                format!(
+                    "No policy violations were overridden with {POLICY_OVERRIDE_ENV}, audit log: {}",
+                    audit_log_path().display()
+                )
+            } else {
+                overrides
+                    .iter()
+                    .map(|entry| {
+                        format!(
+                            "{} [{}] `wash {}` overrode `{}` by {}: {}",
+                            entry.timestamp,
+                            entry.policy,
+                            entry.command,
+                            entry.rule,
+                            entry.user.as_deref().unwrap_or("unknown user"),
+                            entry.reason
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            Ok(CommandOutput::new(
+                text,
+                HashMap::from([("overrides".into(), json!(overrides))]),
+            ))
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! `wash profile` profiles the performance of workloads running in a lattice.
+//!
+//! `wash profile component` asks the host running a component to record every invocation of the
+//! component for a period of time, along with the time spent executing guest code and the growth
+//! of its linear memory, and renders the resulting report. Hosts that sample guest stacks also
+//! return folded stacks, which can be exported with `--flamegraph` and rendered with e.g.
+//! [inferno](https://github.com/jonhoo/inferno) or `flamegraph.pl`.
+//!
+//! `wash profile memory` lists the components holding the most linear memory, as accounted
+//! continuously by the hosts, to find the workload exhausting the memory of a host.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use tracing::warn;
+use wasmcloud_control_interface::{ComponentMemoryUsage, ComponentProfile};
+
+use crate::appearance::spinner::Spinner;
+use crate::lib::cli::get::parse_watch_interval;
+use crate::lib::cli::{validate_component_id, CliConnectionOpts, CommandOutput, OutputKind};
+use crate::lib::common::{
+    boxed_err_to_anyhow, find_host_id, get_all_inventories, resolve_component_id,
+};
+use crate::lib::config::WashConnectionOptions;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ProfileCommand {
+    /// Profile a component running in a host
+    #[clap(name = "component")]
+    Component(ProfileComponentCommand),
+
+    /// List the components holding the most memory
+    #[clap(name = "memory")]
+    Memory(ProfileMemoryCommand),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ProfileComponentCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Id of the host to profile the component on. If a non-ID is provided, the host will be
+    /// selected based on matching the prefix of the ID or the friendly name. If no host ID is
+    /// passed, the first host running the component is selected
+    #[clap(long = "host-id")]
+    pub host_id: Option<String>,
+
+    /// Unique component Id or a string to match on the prefix of the ID
+    #[clap(name = "component-id", value_parser = validate_component_id)]
+    pub component_id: String,
+
+    /// How long to profile the component for, e.g. `30s` or `2m`. A plain number is interpreted as
+    /// milliseconds
+    #[clap(long = "duration", default_value = "30s", value_parser = parse_watch_interval)]
+    pub duration: Duration,
+
+    /// Write the guest stack samples collected by the host to this file in folded format, ready to
+    /// be rendered as a flamegraph
+    #[clap(long = "flamegraph")]
+    pub flamegraph: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct ProfileMemoryCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Id of the host to list the components of. If a non-ID is provided, the host will be
+    /// selected based on matching the prefix of the ID or the friendly name. If no host ID is
+    /// passed, components of all hosts are listed
+    #[clap(long = "host-id")]
+    pub host_id: Option<String>,
+
+    /// Number of components to list
+    #[clap(long = "top", default_value_t = 10)]
+    pub top: u32,
+}
+
+/// Invoke `wash profile`
+pub async fn handle_command(
+    command: ProfileCommand,
+    output_kind: OutputKind,
+) -> Result<CommandOutput> {
+    match command {
+        ProfileCommand::Component(cmd) => profile_component(cmd, output_kind).await,
+        ProfileCommand::Memory(cmd) => profile_memory(cmd, output_kind).await,
+    }
+}
+
+async fn profile_component(
+    cmd: ProfileComponentCommand,
+    output_kind: OutputKind,
+) -> Result<CommandOutput> {
+    let wco: WashConnectionOptions = cmd.opts.try_into()?;
+    let client = wco.into_ctl_client(None).await?;
+
+    let component_id = resolve_component_id(&cmd.component_id, &client).await;
+    let host_id = if let Some(host_id) = cmd.host_id {
+        find_host_id(&host_id, &client).await?.0.to_string()
+    } else {
+        get_all_inventories(&client)
+            .await?
+            .into_iter()
+            .find(|inv| {
+                inv.components()
+                    .iter()
+                    .any(|component| component.id() == component_id)
+            })
+            .map(|inv| inv.host_id().to_string())
+            .with_context(|| format!("No host found running component [{component_id}]"))?
+    };
+
+    let sp = Spinner::new(&output_kind)?;
+    sp.update_spinner_message(format!(
+        "Profiling component [{component_id}] on host [{host_id}] for {}...",
+        humantime::format_duration(cmd.duration)
+    ));
+    let response = client
+        .profile_component(&host_id, &component_id, cmd.duration)
+        .await
+        .map_err(boxed_err_to_anyhow);
+    sp.finish_and_clear();
+    let response = response?;
+    if !response.succeeded() {
+        bail!(
+            "Failed to profile component [{component_id}]: {}",
+            response.message()
+        );
+    }
+    let profile = response
+        .into_data()
+        .context("Host did not return a profile")?;
+
+    let flamegraph = match cmd.flamegraph {
+        Some(path) if !profile.stack_samples().is_empty() => {
+            tokio::fs::write(&path, folded_stacks(profile.stack_samples()))
+                .await
+                .with_context(|| format!("failed to write stack samples to {}", path.display()))?;
+            Some(path)
+        }
+        Some(_) => {
+            warn!("host [{host_id}] did not sample guest stacks, no flamegraph was written");
+            None
+        }
+        None => None,
+    };
+
+    let mut text = render_profile(&profile);
+    if let Some(path) = &flamegraph {
+        let _ = writeln!(text, "\nStack samples written to {}", path.display());
+    }
+    let mut map = HashMap::from([
+        ("component_id".into(), json!(profile.component_id())),
+        ("host_id".into(), json!(profile.host_id())),
+        ("profile".into(), json!(profile)),
+    ]);
+    if let Some(path) = flamegraph {
+        map.insert("flamegraph".into(), json!(path));
+    }
+    Ok(CommandOutput::new(text, map))
+}
+
+async fn profile_memory(
+    cmd: ProfileMemoryCommand,
+    output_kind: OutputKind,
+) -> Result<CommandOutput> {
+    let top = cmd.top;
+    let wco: WashConnectionOptions = cmd.opts.try_into()?;
+    let client = wco.into_ctl_client(None).await?;
+
+    let host_ids = if let Some(host_id) = cmd.host_id {
+        vec![find_host_id(&host_id, &client).await?.0.to_string()]
+    } else {
+        client
+            .get_hosts()
+            .await
+            .map_err(boxed_err_to_anyhow)?
+            .into_iter()
+            .filter_map(|host| host.into_data().map(|host| host.id().to_string()))
+            .collect()
+    };
+
+    let sp = Spinner::new(&output_kind)?;
+    sp.update_spinner_message("Querying component memory usage...".to_string());
+    let responses = futures::future::join_all(host_ids.into_iter().map(|host_id| {
+        let client = client.clone();
+        async move {
+            let response = client.get_top_memory_consumers(&host_id, top).await;
+            (host_id, response)
+        }
+    }))
+    .await;
+    sp.finish_and_clear();
+
+    let mut usages = Vec::new();
+    for (host_id, response) in responses {
+        match response.map_err(boxed_err_to_anyhow) {
+            Ok(response) if response.succeeded() => usages.extend(
+                response
+                    .into_data()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|usage| (host_id.clone(), usage)),
+            ),
+            Ok(response) => warn!(
+                "host [{host_id}] did not report memory usage: {}",
+                response.message()
+            ),
+            Err(e) => warn!(?e, "failed to query memory usage of host [{host_id}]"),
+        }
+    }
+    // Each host only reports its own top consumers, so rank them again across hosts
+    usages.sort_by(|(_, a), (_, b)| {
+        b.resident_bytes()
+            .cmp(&a.resident_bytes())
+            .then(b.peak_bytes().cmp(&a.peak_bytes()))
+    });
+    usages.truncate(top as usize);
+
+    let text = render_memory_usage(&usages);
+    let components: Vec<_> = usages
+        .iter()
+        .map(|(host_id, usage)| json!({ "host_id": host_id, "usage": usage }))
+        .collect();
+    Ok(CommandOutput::new(
+        text,
+        HashMap::from([("components".into(), json!(components))]),
+    ))
+}
+
+/// Render stack samples in the folded format consumed by flamegraph tools, one `stack count` line
+/// per sampled stack
+fn folded_stacks(samples: &BTreeMap<String, u64>) -> String {
+    samples
+        .iter()
+        .fold(String::new(), |mut out, (stack, count)| {
+            let _ = writeln!(out, "{stack} {count}");
+            out
+        })
+}
+
+fn render_profile(profile: &ComponentProfile) -> String {
+    let invocations = profile.invocations();
+    let mut text = format!(
+        "Profile of component [{}] on host [{}] over {}\n\n",
+        profile.component_id(),
+        profile.host_id(),
+        humantime::format_duration(profile.duration()),
+    );
+    let _ = writeln!(
+        text,
+        "Invocations:  {} ({} failed)",
+        invocations.count(),
+        invocations.errors()
+    );
+    if invocations.count() > 0 {
+        let _ = writeln!(
+            text,
+            "Latency:      min {:?}, mean {:?}, max {:?}",
+            invocations.min(),
+            invocations.mean(),
+            invocations.max()
+        );
+        let _ = writeln!(
+            text,
+            "Percentiles:  p50 {:?}, p90 {:?}, p99 {:?}",
+            invocations.p50(),
+            invocations.p90(),
+            invocations.p99()
+        );
+    }
+    let _ = writeln!(
+        text,
+        "CPU time:     {:?} ({:.1}% utilization)",
+        profile.cpu_time(),
+        profile.cpu_utilization() * 100.0
+    );
+    let _ = writeln!(
+        text,
+        "Memory:       {} allocated, {} peak per instance",
+        format_bytes(profile.memory_allocated_bytes()),
+        format_bytes(profile.peak_memory_bytes())
+    );
+    text
+}
+
+fn render_memory_usage(usages: &[(String, ComponentMemoryUsage)]) -> String {
+    if usages.is_empty() {
+        return "No components running\n".to_string();
+    }
+    let mut text = format!(
+        "{:<32} {:>12} {:>12} {:>10}  {}\n",
+        "COMPONENT", "RESIDENT", "PEAK", "INSTANCES", "HOST"
+    );
+    for (host_id, usage) in usages {
+        let _ = writeln!(
+            text,
+            "{:<32} {:>12} {:>12} {:>10}  {}",
+            usage.component_id(),
+            format_bytes(usage.resident_bytes()),
+            format_bytes(usage.peak_bytes()),
+            format!("{}/{}", usage.running_instances(), usage.max_instances()),
+            host_id,
+        );
+    }
+    text
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_folded_stacks() {
+        let samples = BTreeMap::from([
+            ("main;handle".to_string(), 3),
+            ("main;handle;alloc".to_string(), 1),
+        ]);
+        assert_eq!(
+            folded_stacks(&samples),
+            "main;handle 3\nmain;handle;alloc 1\n"
+        );
+    }
+
+    #[test]
+    fn renders_memory_usage() {
+        let usage = ComponentMemoryUsage::builder()
+            .component_id("echo".into())
+            .resident_bytes(2048)
+            .peak_bytes(4096)
+            .running_instances(1)
+            .max_instances(4)
+            .build();
+        let text = render_memory_usage(&[("NHOST".to_string(), usage)]);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("echo "));
+        assert!(lines[1].contains("2.0 KiB"));
+        assert!(lines[1].contains("4.0 KiB"));
+        assert!(lines[1].contains("1/4"));
+        assert!(lines[1].ends_with("NHOST"));
+    }
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(64 * 1024), "64.0 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 / 2), "1.5 MiB");
+    }
+}
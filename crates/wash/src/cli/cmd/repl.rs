@@ -0,0 +1,751 @@
+//! `wash repl` runs an interactive session against a lattice, keeping a single control interface
+//! connection open between commands.
+//!
+//! The session offers tab completion over the hosts, components and providers running in the
+//! lattice, persistent command history and lightweight scripting: the output of every command is
+//! stored in `$_`, and can be kept with `let NAME = COMMAND` and referenced in later commands as
+//! `$NAME.path.to.field`, e.g. `inventory $hosts.hosts.0.id`.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::io::{BufRead as _, IsTerminal as _, Write as _};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+use anyhow::{bail, ensure, Context as _, Result};
+use clap::Parser;
+use dialoguer::{theme::ColorfulTheme, Completion, History, Input};
+use serde_json::{json, Value};
+use tracing::warn;
+use wasmcloud_control_interface::{Client as CtlClient, CtlResponse};
+
+use crate::ctl::{get_claims_output, get_host_inventories_output, get_hosts_output, links_table};
+use crate::lib::cli::stop::stop_provider;
+use crate::lib::cli::{CliConnectionOpts, CommandOutput, OutputKind};
+use crate::lib::common::{boxed_err_to_anyhow, find_host_id, get_all_inventories};
+use crate::lib::component::{scale_component, ScaleComponentArgs};
+use crate::lib::config::{WashConnectionOptions, WASH_DIRECTORIES};
+
+/// Name of the file in the wash config directory that REPL history is persisted to
+const HISTORY_FILE_NAME: &str = "repl_history";
+
+/// Maximum number of history entries that are kept
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// Name of the variable that holds the output of the last command
+const LAST_OUTPUT_VAR: &str = "_";
+
+/// Keywords offered for tab completion in addition to live lattice resources
+const KEYWORDS: &[&str] = &[
+    "claims",
+    "component",
+    "config",
+    "echo",
+    "exit",
+    "get",
+    "help",
+    "history",
+    "hosts",
+    "inventory",
+    "label",
+    "let",
+    "links",
+    "provider",
+    "quit",
+    "refresh",
+    "scale",
+    "stop",
+    "vars",
+];
+
+const HELP: &str = "\
+Lattice commands:
+  hosts                                       List responsive hosts
+  inventory [HOST]                            Get the inventory of one or all hosts
+  links                                       List links
+  claims                                      List claims
+  config get NAME                             Get a named configuration
+  scale HOST COMPONENT_ID IMAGE_REF MAX       Scale a component on a host
+  stop component COMPONENT_ID [HOST]          Stop a component
+  stop provider PROVIDER_ID [HOST]            Stop a provider
+  label HOST KEY=VALUE                        Label a host
+
+Session commands:
+  let NAME = COMMAND                          Run a command and store its output in $NAME
+  echo ARGS...                                Print arguments after variable substitution
+  vars                                        List variables
+  history                                     Show command history
+  refresh                                     Refresh tab completion from the lattice
+  help                                        Show this help
+  exit, quit                                  Leave the session
+
+The output of the last command is stored in $_. Fields of variables are referenced with dots,
+e.g. `inventory $_.hosts.0.id`.";
+
+#[derive(Debug, Clone, Parser)]
+pub struct ReplCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Run the commands in the given file instead of starting an interactive session, stopping at
+    /// the first command that fails. Commands are also read from stdin when it is not a terminal.
+    #[clap(long = "script")]
+    pub script: Option<PathBuf>,
+}
+
+pub async fn handle_command(cmd: ReplCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    let timeout_ms = cmd.opts.timeout_ms;
+    let wco: WashConnectionOptions = cmd.opts.try_into()?;
+    let client = wco.into_ctl_client(None).await?;
+    let mut session = Session::new(client, timeout_ms, output_kind);
+
+    let executed = if let Some(script) = cmd.script {
+        let script = tokio::fs::read_to_string(&script)
+            .await
+            .with_context(|| format!("failed to read script [{}]", script.display()))?;
+        session.run_script(script.lines()).await?
+    } else if std::io::stdin().is_terminal() {
+        session.run_interactive().await?
+    } else {
+        let lines = std::io::stdin()
+            .lock()
+            .lines()
+            .collect::<std::io::Result<Vec<_>>>()
+            .context("failed to read commands from stdin")?;
+        session.run_script(lines.iter().map(String::as_str)).await?
+    };
+
+    Ok(CommandOutput::new(
+        format!("Executed {executed} command(s)"),
+        HashMap::from([("commands".to_string(), json!(executed))]),
+    ))
+}
+
+/// What the session should do after a line was handled
+#[derive(Debug, PartialEq, Eq)]
+enum Flow {
+    Continue,
+    Exit,
+}
+
+/// A REPL session with a connected control interface client
+struct Session {
+    client: CtlClient,
+    timeout_ms: u64,
+    output_kind: OutputKind,
+    vars: BTreeMap<String, Value>,
+    completer: LatticeCompleter,
+    history: FileHistory,
+}
+
+impl Session {
+    fn new(client: CtlClient, timeout_ms: u64, output_kind: OutputKind) -> Self {
+        Self {
+            client,
+            timeout_ms,
+            output_kind,
+            vars: BTreeMap::new(),
+            completer: LatticeCompleter::default(),
+            history: FileHistory::load(),
+        }
+    }
+
+    /// Read and execute lines from the terminal until the user exits, returning the number of
+    /// executed commands
+    async fn run_interactive(&mut self) -> Result<usize> {
+        println!(
+            "Connected to lattice [{}]. Type `help` for a list of commands.",
+            self.client.lattice()
+        );
+        self.refresh_completions().await;
+        let mut executed = 0;
+        loop {
+            let line = tokio::task::block_in_place(|| {
+                Input::<String>::with_theme(&ColorfulTheme::default())
+                    .with_prompt(format!("wash ({})", self.client.lattice()))
+                    .allow_empty(true)
+                    .completion_with(&self.completer)
+                    .history_with(&mut self.history)
+                    .interact_text()
+            });
+            let line = match line {
+                Ok(line) => line,
+                // Input is interrupted with Ctrl-C or Ctrl-D
+                Err(dialoguer::Error::IO(e)) if e.kind() == std::io::ErrorKind::Interrupted => {
+                    break
+                }
+                Err(e) => return Err(e).context("failed to read command"),
+            };
+            match self.execute_line(&line).await {
+                Ok(Some(Flow::Exit)) => break,
+                Ok(Some(Flow::Continue)) => executed += 1,
+                Ok(None) => {}
+                Err(e) => eprintln!("Error: {e:#}"),
+            }
+        }
+        Ok(executed)
+    }
+
+    /// Execute each line in order, failing on the first command that fails, and return the number
+    /// of executed commands
+    async fn run_script<'a>(&mut self, lines: impl IntoIterator<Item = &'a str>) -> Result<usize> {
+        let mut executed = 0;
+        for (idx, line) in lines.into_iter().enumerate() {
+            match self
+                .execute_line(line)
+                .await
+                .with_context(|| format!("line {}: `{}` failed", idx + 1, line.trim()))?
+            {
+                Some(Flow::Exit) => break,
+                Some(Flow::Continue) => executed += 1,
+                None => {}
+            }
+        }
+        Ok(executed)
+    }
+
+    /// Execute a single line, returning `None` for blank lines and comments
+    async fn execute_line(&mut self, line: &str) -> Result<Option<Flow>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let tokens = tokenize(line)?;
+        let (target, tokens) = match tokens.as_slice() {
+            [kw, name, eq, rest @ ..] if kw == "let" && eq == "=" => {
+                ensure!(
+                    is_valid_var_name(name),
+                    "invalid variable name [{name}], only letters, digits and `_` are allowed"
+                );
+                ensure!(
+                    !rest.is_empty(),
+                    "`let {name} =` must be followed by a command"
+                );
+                (Some(name.clone()), rest.to_vec())
+            }
+            [kw, ..] if kw == "let" => bail!("expected `let NAME = COMMAND`"),
+            _ => (None, tokens),
+        };
+        let args = tokens
+            .iter()
+            .map(|token| substitute(token, &self.vars))
+            .collect::<Result<Vec<_>>>()?;
+
+        let output = match args.first().map(String::as_str) {
+            Some("exit" | "quit") => return Ok(Some(Flow::Exit)),
+            Some("help") => CommandOutput::from_key_and_text("help", HELP),
+            Some("vars") => {
+                let names: Vec<_> = self.vars.keys().map(|name| format!("${name}")).collect();
+                CommandOutput::new(
+                    names.join("\n"),
+                    HashMap::from([("vars".into(), json!(names))]),
+                )
+            }
+            Some("history") => {
+                let entries: Vec<_> = self.history.entries.iter().rev().cloned().collect();
+                let text =
+                    entries
+                        .iter()
+                        .enumerate()
+                        .fold(String::new(), |mut text, (idx, entry)| {
+                            let _ = writeln!(text, "{:>5}  {entry}", idx + 1);
+                            text
+                        });
+                CommandOutput::new(text, HashMap::from([("history".into(), json!(entries))]))
+            }
+            Some("echo") => {
+                let text = args[1..].join(" ");
+                CommandOutput::from_key_and_text("echo", text)
+            }
+            Some("refresh") => {
+                self.refresh_completions().await;
+                CommandOutput::from_key_and_text("result", "Refreshed lattice resources")
+            }
+            _ => {
+                let output = self.run_command(&args).await?;
+                if is_mutating(&args) {
+                    self.refresh_completions().await;
+                }
+                output
+            }
+        };
+        self.print(&output);
+        let value = Value::Object(output.map.into_iter().collect());
+        if let Some(name) = target {
+            self.vars.insert(name, value.clone());
+        }
+        self.vars.insert(LAST_OUTPUT_VAR.to_string(), value);
+        self.completer.set_vars(self.vars.keys());
+        Ok(Some(Flow::Continue))
+    }
+
+    /// Run a lattice command with the session client
+    async fn run_command(&self, args: &[String]) -> Result<CommandOutput> {
+        let args: Vec<_> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            ["hosts"] | ["get", "hosts"] => {
+                let hosts = self
+                    .client
+                    .get_hosts()
+                    .await
+                    .map_err(boxed_err_to_anyhow)?
+                    .into_iter()
+                    .filter_map(CtlResponse::into_data)
+                    .collect();
+                Ok(get_hosts_output(hosts))
+            }
+            ["inventory"] | ["get", "inventory"] => {
+                let inventories = get_all_inventories(&self.client).await?;
+                Ok(get_host_inventories_output(inventories))
+            }
+            ["inventory", host] | ["get", "inventory", host] => {
+                let host_id = find_host_id(host, &self.client).await?.0;
+                let inventory = self
+                    .client
+                    .get_host_inventory(&host_id)
+                    .await
+                    .map_err(boxed_err_to_anyhow)?
+                    .into_data()
+                    .context("host did not respond to inventory query")?;
+                Ok(get_host_inventories_output(vec![inventory]))
+            }
+            ["links"] | ["get", "links"] => {
+                let links = self
+                    .client
+                    .get_links()
+                    .await
+                    .map_err(boxed_err_to_anyhow)?
+                    .into_data()
+                    .unwrap_or_default();
+                let map = HashMap::from([("links".to_string(), json!(links))]);
+                Ok(CommandOutput::new(links_table(links), map))
+            }
+            ["claims"] | ["get", "claims"] => {
+                let claims = self
+                    .client
+                    .get_claims()
+                    .await
+                    .map_err(boxed_err_to_anyhow)?
+                    .into_data()
+                    .unwrap_or_default();
+                Ok(get_claims_output(claims))
+            }
+            ["config", "get", name] => {
+                let config = self
+                    .client
+                    .get_config(name)
+                    .await
+                    .map_err(boxed_err_to_anyhow)?
+                    .into_data()
+                    .with_context(|| format!("configuration [{name}] not found"))?;
+                let text = config.iter().collect::<BTreeMap<_, _>>().into_iter().fold(
+                    String::new(),
+                    |mut text, (k, v)| {
+                        let _ = writeln!(text, "{k}={v}");
+                        text
+                    },
+                );
+                Ok(CommandOutput::new(
+                    text,
+                    HashMap::from([("config".to_string(), json!(config))]),
+                ))
+            }
+            ["scale", host, component_id, component_ref, max_instances] => {
+                let max_instances = max_instances
+                    .parse()
+                    .with_context(|| format!("invalid max instances [{max_instances}]"))?;
+                let host_id = find_host_id(host, &self.client).await?.0;
+                let info = scale_component(ScaleComponentArgs {
+                    client: &self.client,
+                    host_id: &host_id,
+                    component_id,
+                    component_ref,
+                    max_instances,
+                    annotations: None,
+                    config: vec![],
+                    skip_wait: false,
+                    timeout_ms: Some(self.timeout_ms),
+                })
+                .await?;
+                Ok(CommandOutput::new(
+                    format!(
+                        "Component [{}] scaled to {max_instances} on host [{}]",
+                        info.component_id, info.host_id
+                    ),
+                    HashMap::from([
+                        ("component_id".into(), json!(info.component_id)),
+                        ("host_id".into(), json!(info.host_id)),
+                        ("max_instances".into(), json!(max_instances)),
+                    ]),
+                ))
+            }
+            ["stop", "component", component_id, host @ ..] if host.len() <= 1 => {
+                self.stop_component(component_id, host.first().copied())
+                    .await
+            }
+            ["stop", "provider", provider_id, host @ ..] if host.len() <= 1 => {
+                let host_id = match host.first() {
+                    Some(host) => Some(find_host_id(host, &self.client).await?.0.to_string()),
+                    None => None,
+                };
+                stop_provider(
+                    &self.client,
+                    host_id.as_deref(),
+                    provider_id,
+                    false,
+                    self.timeout_ms,
+                )
+                .await?;
+                Ok(CommandOutput::new(
+                    format!("Provider [{provider_id}] stopped"),
+                    HashMap::from([("provider_id".into(), json!(provider_id))]),
+                ))
+            }
+            ["label", host, label] => {
+                let (key, value) = label
+                    .split_once('=')
+                    .with_context(|| format!("invalid label [{label}], expected KEY=VALUE"))?;
+                let host_id = find_host_id(host, &self.client).await?.0;
+                let ack = self
+                    .client
+                    .put_label(&host_id, key, value)
+                    .await
+                    .map_err(boxed_err_to_anyhow)?;
+                ensure!(ack.succeeded(), "Operation failed: {}", ack.message());
+                Ok(CommandOutput::new(
+                    format!("Host [{host_id}] labeled with {key}={value}"),
+                    HashMap::from([
+                        ("host_id".into(), json!(host_id.to_string())),
+                        ("key".into(), json!(key)),
+                        ("value".into(), json!(value)),
+                    ]),
+                ))
+            }
+            [] => bail!("no command given"),
+            [command, ..] => bail!("unknown or incomplete command [{command}], see `help`"),
+        }
+    }
+
+    /// Scale a component to zero on the given host, or the host it was found running on
+    async fn stop_component(
+        &self,
+        component_id: &str,
+        host: Option<&str>,
+    ) -> Result<CommandOutput> {
+        let inventories = match host {
+            Some(host) => {
+                let host_id = find_host_id(host, &self.client).await?.0;
+                self.client
+                    .get_host_inventory(&host_id)
+                    .await
+                    .map_err(boxed_err_to_anyhow)?
+                    .into_data()
+                    .into_iter()
+                    .collect()
+            }
+            None => get_all_inventories(&self.client).await?,
+        };
+        let (host_id, component_ref) = inventories
+            .iter()
+            .find_map(|inv| {
+                inv.components()
+                    .iter()
+                    .find(|c| c.id() == component_id)
+                    .map(|c| (inv.host_id().to_string(), c.image_ref().to_string()))
+            })
+            .with_context(|| format!("no host found running component [{component_id}]"))?;
+        scale_component(ScaleComponentArgs {
+            client: &self.client,
+            host_id: &host_id,
+            component_id,
+            component_ref: &component_ref,
+            max_instances: 0,
+            annotations: None,
+            config: vec![],
+            skip_wait: false,
+            timeout_ms: Some(self.timeout_ms),
+        })
+        .await?;
+        Ok(CommandOutput::new(
+            format!("Component [{component_id}] stopped"),
+            HashMap::from([
+                ("component_id".into(), json!(component_id)),
+                ("host_id".into(), json!(host_id)),
+            ]),
+        ))
+    }
+
+    /// Query the lattice for the resources offered for tab completion
+    async fn refresh_completions(&self) {
+        match get_all_inventories(&self.client).await {
+            Ok(inventories) => {
+                let resources = inventories.iter().flat_map(|inv| {
+                    let host = [inv.host_id().to_string(), inv.friendly_name().to_string()];
+                    let components = inv
+                        .components()
+                        .iter()
+                        .flat_map(|c| [c.id().to_string(), c.image_ref().to_string()]);
+                    let providers = inv.providers().iter().map(|p| p.id().to_string());
+                    host.into_iter().chain(components).chain(providers)
+                });
+                self.completer.set_resources(resources);
+            }
+            Err(e) => warn!(error = %e, "failed to refresh lattice resources for completion"),
+        }
+    }
+
+    fn print(&self, output: &CommandOutput) {
+        match self.output_kind {
+            OutputKind::Text => println!("{}", output.text.trim_end()),
+            OutputKind::Json => match serde_json::to_string_pretty(&output.map) {
+                Ok(json) => println!("{json}"),
+                Err(e) => eprintln!("Error: failed to encode output: {e}"),
+            },
+        }
+    }
+}
+
+/// Returns whether a command changes lattice resources, so that completions should be refreshed
+fn is_mutating(args: &[String]) -> bool {
+    matches!(
+        args.first().map(String::as_str),
+        Some("scale" | "stop" | "label")
+    )
+}
+
+fn is_valid_var_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split a line into whitespace separated tokens, honoring single and double quotes
+fn tokenize(line: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut token: Option<String> = None;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => token.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                token.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => tokens.extend(token.take()),
+            (None, c) => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    ensure!(quote.is_none(), "unterminated quote");
+    tokens.extend(token);
+    Ok(tokens)
+}
+
+/// Replace a token of the form `$NAME.path.to.field` with the referenced variable field. Strings
+/// are substituted as is, other values as JSON. Other tokens are returned unchanged.
+fn substitute(token: &str, vars: &BTreeMap<String, Value>) -> Result<String> {
+    let Some(reference) = token.strip_prefix('$') else {
+        return Ok(token.to_string());
+    };
+    let mut path = reference.split('.');
+    let name = path.next().unwrap_or_default();
+    let mut value = vars
+        .get(name)
+        .with_context(|| format!("unknown variable [${name}]"))?;
+    for segment in path {
+        value = match value {
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|idx| items.get(idx)),
+            Value::Object(fields) => fields.get(segment),
+            _ => None,
+        }
+        .with_context(|| format!("variable [${name}] has no field [{reference}]"))?;
+    }
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string(),
+    })
+}
+
+/// Tab completion over keywords, variables and the resources running in the lattice
+#[derive(Default)]
+struct LatticeCompleter {
+    resources: Mutex<BTreeSet<String>>,
+    vars: Mutex<BTreeSet<String>>,
+}
+
+impl LatticeCompleter {
+    fn set_resources(&self, resources: impl IntoIterator<Item = String>) {
+        *self
+            .resources
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = resources
+            .into_iter()
+            .filter(|resource| !resource.is_empty())
+            .collect();
+    }
+
+    fn set_vars<'a>(&self, names: impl IntoIterator<Item = &'a String>) {
+        *self.vars.lock().unwrap_or_else(PoisonError::into_inner) =
+            names.into_iter().map(|name| format!("${name}")).collect();
+    }
+}
+
+impl Completion for LatticeCompleter {
+    /// Complete the last word of `input` to the longest prefix shared by all candidates
+    fn get(&self, input: &str) -> Option<String> {
+        let start = input.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+        let (head, word) = input.split_at(start);
+        let resources = self
+            .resources
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let vars = self.vars.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut candidates = KEYWORDS
+            .iter()
+            .copied()
+            .chain(resources.iter().map(String::as_str))
+            .chain(vars.iter().map(String::as_str))
+            .filter(|candidate| candidate.starts_with(word));
+        let first = candidates.next()?;
+        let completion = candidates.fold(first, |prefix, candidate| {
+            let len = prefix
+                .char_indices()
+                .zip(candidate.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((idx, a), _)| idx + a.len_utf8());
+            &prefix[..len]
+        });
+        (completion.len() > word.len()).then(|| format!("{head}{completion}"))
+    }
+}
+
+/// Command history that is persisted to the wash config directory
+#[derive(Default)]
+struct FileHistory {
+    path: Option<PathBuf>,
+    /// Entries, most recent first
+    entries: VecDeque<String>,
+}
+
+impl FileHistory {
+    /// Load history from the wash config directory, starting with an empty history if it cannot
+    /// be read
+    fn load() -> Self {
+        let path = match WASH_DIRECTORIES.create_in_config_dir(HISTORY_FILE_NAME) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!(error = %e, "failed to locate REPL history file, history will not be saved");
+                return Self::default();
+            }
+        };
+        let entries = std::fs::read_to_string(&path)
+            .map(|history| {
+                history
+                    .lines()
+                    .rev()
+                    .take(MAX_HISTORY_ENTRIES)
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut file = std::fs::File::create(path)?;
+        for entry in self.entries.iter().rev() {
+            writeln!(file, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: ToString> History<T> for FileHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, val: &T) {
+        let entry = val.to_string();
+        let entry = entry.trim();
+        if entry.is_empty() || self.entries.front().is_some_and(|last| last == entry) {
+            return;
+        }
+        self.entries.push_front(entry.to_string());
+        self.entries.truncate(MAX_HISTORY_ENTRIES);
+        if let Err(e) = self.save() {
+            warn!(error = %e, "failed to save REPL history");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_honors_quotes() -> Result<()> {
+        assert_eq!(
+            tokenize(r#"label host "zone=us east"  'a b'c"#)?,
+            vec!["label", "host", "zone=us east", "a bc"]
+        );
+        assert_eq!(tokenize(r#"echo """#)?, vec!["echo", ""]);
+        assert!(tokenize("echo 'unterminated").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn substitute_resolves_variable_fields() -> Result<()> {
+        let vars = BTreeMap::from([(
+            "hosts".to_string(),
+            json!({ "hosts": [{ "id": "NABC", "uptime": 5 }] }),
+        )]);
+        assert_eq!(substitute("$hosts.hosts.0.id", &vars)?, "NABC");
+        assert_eq!(substitute("$hosts.hosts.0.uptime", &vars)?, "5");
+        assert_eq!(substitute("plain", &vars)?, "plain");
+        assert!(substitute("$missing", &vars).is_err());
+        assert!(substitute("$hosts.hosts.1", &vars).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn completer_extends_to_common_prefix() {
+        let completer = LatticeCompleter::default();
+        completer.set_resources(["NABC123".to_string(), "NABD456".to_string()]);
+        completer.set_vars([&"hosts".to_string()]);
+        assert_eq!(
+            completer.get("inventory NA").as_deref(),
+            Some("inventory NAB")
+        );
+        assert_eq!(
+            completer.get("inventory NABC").as_deref(),
+            Some("inventory NABC123")
+        );
+        assert_eq!(completer.get("inv").as_deref(), Some("inventory"));
+        assert_eq!(completer.get("echo $h").as_deref(), Some("echo $hosts"));
+        assert_eq!(completer.get("stop component x"), None);
+    }
+
+    #[test]
+    fn history_skips_blank_and_repeated_entries() {
+        let mut history = FileHistory::default();
+        History::<String>::write(&mut history, &"hosts".to_string());
+        History::<String>::write(&mut history, &"hosts".to_string());
+        History::<String>::write(&mut history, &"  ".to_string());
+        History::<String>::write(&mut history, &"links".to_string());
+        assert_eq!(
+            History::<String>::read(&history, 0).as_deref(),
+            Some("links")
+        );
+        assert_eq!(
+            History::<String>::read(&history, 1).as_deref(),
+            Some("hosts")
+        );
+        assert_eq!(History::<String>::read(&history, 2), None);
+    }
+}
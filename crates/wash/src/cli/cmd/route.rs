@@ -0,0 +1,301 @@
+//! `wash route` splits the traffic sent to a link target between several components.
+//!
+//! Components linked to a target send their invocations to the component with the ID of the
+//! target. With `wash route set`, hosts instead route the invocations between several components
+//! by weight, which allows rolling out a new version of a component incrementally:
+//!
+//! ```console
+//! wash start component ghcr.io/example/api:2.0.0 api-v2
+//! wash route set api --split api=90,api-v2=10
+//! wash route set api --split api=0,api-v2=100
+//! ```
+//!
+//! `wash route ingress` manages the ingress routing table of the lattice, which HTTP server
+//! providers in `ingress` routing mode use to route inbound requests to components by hostname
+//! and path:
+//!
+//! ```console
+//! wash route ingress put api --path /api --component api
+//! wash route ingress list
+//! ```
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use wasmcloud_control_interface::{IngressRoute, TrafficSplit};
+
+use crate::lib::cli::{validate_component_id, CliConnectionOpts, CommandOutput, OutputKind};
+use crate::lib::common::boxed_err_to_anyhow;
+use crate::lib::config::WashConnectionOptions;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum RouteCommand {
+    /// Split the invocations of a link target between components by weight
+    #[clap(name = "set")]
+    Set(RouteSetCommand),
+
+    /// Show how the invocations of a link target are split
+    #[clap(name = "get")]
+    Get(RouteGetCommand),
+
+    /// Remove the traffic split of a link target, sending all invocations to the target again
+    #[clap(name = "del", alias = "delete")]
+    Del(RouteDelCommand),
+
+    /// Manage the ingress routing table HTTP server providers in `ingress` routing mode serve
+    #[clap(name = "ingress", subcommand)]
+    Ingress(IngressCommand),
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum IngressCommand {
+    /// Add a route to the ingress routing table, replacing any route with the same name
+    #[clap(name = "put")]
+    Put(IngressPutCommand),
+
+    /// List the ingress routing table
+    #[clap(name = "list", alias = "ls")]
+    List(IngressListCommand),
+
+    /// Remove a route from the ingress routing table
+    #[clap(name = "del", alias = "delete")]
+    Del(IngressDelCommand),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct IngressPutCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Name of the route
+    #[clap(name = "name")]
+    pub name: String,
+
+    /// Hostname the route applies to. Routes without a hostname apply to any hostname
+    #[clap(long = "hostname")]
+    pub hostname: Option<String>,
+
+    /// Path prefix the route applies to
+    #[clap(long = "path", default_value = "/")]
+    pub path: String,
+
+    /// ID of the component handling the requests matching the route
+    #[clap(long = "component", value_parser = validate_component_id)]
+    pub component_id: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct IngressListCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct IngressDelCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// Name of the route to remove
+    #[clap(name = "name")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RouteSetCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// The link target whose invocations are split, i.e. the target ID components are linked to
+    #[clap(name = "target", value_parser = validate_component_id)]
+    pub target: String,
+
+    /// Comma-separated weights of the components receiving the invocations, e.g.
+    /// `api-v1=90,api-v2=10`
+    #[clap(long = "split")]
+    pub split: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RouteGetCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// The link target to show the traffic split of
+    #[clap(name = "target", value_parser = validate_component_id)]
+    pub target: String,
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct RouteDelCommand {
+    #[clap(flatten)]
+    pub opts: CliConnectionOpts,
+
+    /// The link target to remove the traffic split of
+    #[clap(name = "target", value_parser = validate_component_id)]
+    pub target: String,
+}
+
+/// Invoke `wash route`
+pub async fn handle_command(command: RouteCommand, _: OutputKind) -> Result<CommandOutput> {
+    match command {
+        RouteCommand::Set(cmd) => {
+            let split =
+                TrafficSplit::parse(&cmd.target, &cmd.split).map_err(boxed_err_to_anyhow)?;
+            let wco: WashConnectionOptions = cmd.opts.try_into()?;
+            let client = wco.into_ctl_client(None).await?;
+            client
+                .put_traffic_split(&split)
+                .await
+                .map_err(boxed_err_to_anyhow)?;
+            Ok(CommandOutput::new(
+                format!(
+                    "Invocations of [{}] are now split {}",
+                    split.target(),
+                    render_weights(&split)
+                ),
+                HashMap::from([
+                    ("target".into(), json!(split.target())),
+                    ("weights".into(), json!(split.weights())),
+                ]),
+            ))
+        }
+        RouteCommand::Get(cmd) => {
+            let wco: WashConnectionOptions = cmd.opts.try_into()?;
+            let client = wco.into_ctl_client(None).await?;
+            let split = client
+                .get_traffic_split(cmd.target.as_str())
+                .await
+                .map_err(boxed_err_to_anyhow)?;
+            let text = match &split {
+                Some(split) => format!(
+                    "Invocations of [{}] are split {}",
+                    cmd.target,
+                    render_weights(split)
+                ),
+                None => format!("Invocations of [{}] are not split", cmd.target),
+            };
+            Ok(CommandOutput::new(
+                text,
+                HashMap::from([
+                    ("target".into(), json!(cmd.target)),
+                    (
+                        "weights".into(),
+                        json!(split.as_ref().map(TrafficSplit::weights)),
+                    ),
+                ]),
+            ))
+        }
+        RouteCommand::Del(cmd) => {
+            let wco: WashConnectionOptions = cmd.opts.try_into()?;
+            let client = wco.into_ctl_client(None).await?;
+            client
+                .delete_traffic_split(cmd.target.as_str())
+                .await
+                .map_err(boxed_err_to_anyhow)?;
+            Ok(CommandOutput::new(
+                format!("Removed traffic split of [{}]", cmd.target),
+                HashMap::from([("target".into(), json!(cmd.target))]),
+            ))
+        }
+        RouteCommand::Ingress(cmd) => handle_ingress_command(cmd).await,
+    }
+}
+
+/// Invoke `wash route ingress`
+async fn handle_ingress_command(command: IngressCommand) -> Result<CommandOutput> {
+    match command {
+        IngressCommand::Put(cmd) => {
+            let route = IngressRoute::new(
+                &cmd.name,
+                cmd.hostname.as_deref(),
+                &cmd.path,
+                &cmd.component_id,
+            )
+            .map_err(boxed_err_to_anyhow)?;
+            let wco: WashConnectionOptions = cmd.opts.try_into()?;
+            let client = wco.into_ctl_client(None).await?;
+            client
+                .put_ingress_route(&route)
+                .await
+                .map_err(boxed_err_to_anyhow)?;
+            Ok(CommandOutput::new(
+                format!(
+                    "Ingress route [{}] now sends {} to [{}]",
+                    route.name(),
+                    render_match(&route),
+                    route.component_id()
+                ),
+                HashMap::from([("route".into(), json!(route))]),
+            ))
+        }
+        IngressCommand::List(cmd) => {
+            let wco: WashConnectionOptions = cmd.opts.try_into()?;
+            let client = wco.into_ctl_client(None).await?;
+            let routes = client
+                .get_ingress_routes()
+                .await
+                .map_err(boxed_err_to_anyhow)?;
+            let text = if routes.is_empty() {
+                "No ingress routes found".to_string()
+            } else {
+                routes
+                    .iter()
+                    .map(|route| {
+                        format!(
+                            "{}: {} -> {}",
+                            route.name(),
+                            render_match(route),
+                            route.component_id()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            Ok(CommandOutput::new(
+                text,
+                HashMap::from([("routes".into(), json!(routes))]),
+            ))
+        }
+        IngressCommand::Del(cmd) => {
+            let wco: WashConnectionOptions = cmd.opts.try_into()?;
+            let client = wco.into_ctl_client(None).await?;
+            client
+                .delete_ingress_route(&cmd.name)
+                .await
+                .map_err(boxed_err_to_anyhow)?;
+            Ok(CommandOutput::new(
+                format!("Removed ingress route [{}]", cmd.name),
+                HashMap::from([("name".into(), json!(cmd.name))]),
+            ))
+        }
+    }
+}
+
+/// Render the requests an ingress route matches, e.g. `api.example.com/v1`
+fn render_match(route: &IngressRoute) -> String {
+    format!("{}{}", route.hostname().unwrap_or("*"), route.path_prefix())
+}
+
+/// Render the weights of a split as percentages, e.g. `api-v1 90%, api-v2 10%`
+fn render_weights(split: &TrafficSplit) -> String {
+    let total = split.total_weight().max(1) as f64;
+    split
+        .weights()
+        .iter()
+        .map(|(id, weight)| format!("{id} {:.0}%", f64::from(*weight) * 100.0 / total))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_weights_as_percentages() {
+        let split = TrafficSplit::parse("api", "api=3,api-v2=1").expect("valid split");
+        assert_eq!(render_weights(&split), "api 75%, api-v2 25%");
+    }
+}
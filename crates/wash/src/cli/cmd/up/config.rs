@@ -0,0 +1,674 @@
+//! Declarative configuration of `wash up`, loaded from a TOML, YAML or JSON file with `--config`.
+//!
+//! The `host` section uses the schema of the host configuration, [`HostConfig`], so that a single
+//! file describes a host whether it is started by `wash up` or directly. The `wasmcloud`,
+//! `nats_server` and `wadm` sections configure the processes `wash up` downloads and starts.
+//!
+//! ```toml
+//! [host]
+//! lattice = "dev"
+//! labels = { team = "payments" }
+//! rpc_timeout = "5s"
+//!
+//! [host.limits]
+//! max_linear_memory = "512MiB"
+//!
+//! [wasmcloud]
+//! version = "v1.4.2"
+//! log_level = "debug"
+//!
+//! [wadm]
+//! manifest = "./wadm.yaml"
+//! ```
+//!
+//! Flags and their environment variables take precedence over values of the file. Flags with a
+//! default value only take precedence if they are set to a value other than their default.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use serde::{Deserialize, Serialize};
+use wasmcloud_core::host_config::{
+    HostConfig, HostLimits, HostNatsOverrides, DEFAULT_CTL_TOPIC_PREFIX, DEFAULT_NATS_HOST,
+    DEFAULT_NATS_PORT, DEFAULT_RPC_TIMEOUT,
+};
+use wasmcloud_core::secrets::SecretString;
+use wasmcloud_core::units::HumanDuration;
+use wasmcloud_core::OtelConfig;
+
+use super::UpCommand;
+use crate::config::{
+    DEFAULT_ALLOW_FILE_LOAD, DEFAULT_MAX_EXECUTION_TIME_MS, DEFAULT_NATS_WEBSOCKET_PORT,
+    DEFAULT_PROV_SHUTDOWN_DELAY_MS, DEFAULT_RPC_TIMEOUT_MS, DEFAULT_STRUCTURED_LOG_LEVEL,
+    NATS_SERVER_VERSION, WASMCLOUD_CTL_TOPIC_PREFIX, WASMCLOUD_MAX_COMPONENTS,
+    WASMCLOUD_MAX_COMPONENT_SIZE, WASMCLOUD_MAX_CORE_INSTANCES_PER_COMPONENT,
+    WASMCLOUD_MAX_LINEAR_MEMORY, WASMCLOUD_OBSERVABILITY_CONFIG, WASMCLOUD_OCI_REGISTRY,
+    WASMCLOUD_OCI_REGISTRY_PASSWORD, WASMCLOUD_OCI_REGISTRY_USER,
+};
+
+/// Name of the file in the wash downloads directory the OpenTelemetry configuration of the host is
+/// written to, as it is passed to the host as a file
+const OBSERVABILITY_CONFIG_FILE: &str = "wasmcloud-observability.json";
+
+/// Format of a `wash up` configuration file, determined by its extension
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ConfigFormat {
+    #[default]
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Determine the format of the file at `path` from its extension
+    pub fn from_path(path: &Path) -> Result<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            Some("json") => Ok(Self::Json),
+            _ => bail!(
+                "unsupported config file `{}`, expected a `.toml`, `.yaml`, `.yml` or `.json` file",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Configuration of `wash up`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpConfig {
+    /// Settings of the host, in the schema of the host configuration
+    pub host: HostConfig,
+    /// Settings of the host process started by `wash up`
+    pub wasmcloud: WasmcloudSection,
+    /// Settings of the NATS server started by `wash up`
+    pub nats_server: NatsServerSection,
+    /// Settings of the wadm process started by `wash up`
+    pub wadm: WadmSection,
+}
+
+/// Settings of the host process started by `wash up`, which are not part of [`HostConfig`]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WasmcloudSection {
+    /// Version of the host to download, e.g. `v1.4.2`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Seed key the host derives its public key from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_seed: Option<SecretString>,
+    /// Seed key the host signs invocations with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cluster_seed: Option<SecretString>,
+    /// Public keys that can be used as issuers of signed invocations
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cluster_issuers: Vec<String>,
+    /// Delay between requesting a provider to shut down and forcibly terminating it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_shutdown_delay: Option<HumanDuration>,
+    /// Whether the host requests supplemental configuration from a config service on startup
+    pub config_service_enabled: bool,
+    /// Whether the host allows starting components and providers from the file system
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_file_load: Option<bool>,
+    /// Whether the host logs JSON structured logs
+    pub structured_logging: bool,
+    /// Log level of the host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Whether IPv6 addressing is enabled
+    pub enable_ipv6: bool,
+    /// Topic prefix of the secrets backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets_topic: Option<String>,
+    /// Topic of the policy service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_topic: Option<String>,
+    /// Path the host logs are written to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<PathBuf>,
+    /// Path to the host binary to start instead of a downloaded one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Whether to fail rather than download the host if it is not installed
+    pub start_only: bool,
+    /// Whether to allow starting additional hosts on this machine
+    pub multi_local: bool,
+}
+
+/// Settings of the NATS server started by `wash up`. The address it listens on is `host.nats`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NatsServerSection {
+    /// Version of the NATS server to download, e.g. `v2.11.3`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Port of the websocket listener
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub websocket_port: Option<u16>,
+    /// Path to a NATS server configuration file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_file: Option<PathBuf>,
+    /// URL of existing NATS infrastructure to extend as a leaf node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_url: Option<String>,
+    /// Credentials file to authenticate to `remote_url` with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credsfile: Option<PathBuf>,
+    /// Whether to fail rather than start a NATS server if none can be connected to
+    pub connect_only: bool,
+    /// JetStream domain of the NATS server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub js_domain: Option<String>,
+}
+
+/// Settings of the wadm process started by `wash up`
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WadmSection {
+    /// Version of wadm to download, e.g. `v0.18.0`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Whether to not start wadm
+    pub disable: bool,
+    /// JetStream domain used by wadm
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub js_domain: Option<String>,
+    /// Path to an application manifest to deploy once the host is up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<PathBuf>,
+}
+
+impl UpConfig {
+    /// Read the configuration from a TOML, YAML or JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config `{}`", path.display()))?;
+        match format {
+            ConfigFormat::Toml => toml::from_str(&contents).map_err(anyhow::Error::from),
+            ConfigFormat::Yaml => serde_yaml::from_str(&contents).map_err(anyhow::Error::from),
+            ConfigFormat::Json => serde_json::from_str(&contents).map_err(anyhow::Error::from),
+        }
+        .with_context(|| format!("invalid config `{}`", path.display()))
+    }
+
+    /// Render the configuration in the given format
+    pub fn render(&self, format: ConfigFormat) -> Result<String> {
+        match format {
+            ConfigFormat::Toml => toml::to_string(self).map_err(anyhow::Error::from),
+            ConfigFormat::Yaml => serde_yaml::to_string(self).map_err(anyhow::Error::from),
+            ConfigFormat::Json => serde_json::to_string_pretty(self).map_err(anyhow::Error::from),
+        }
+        .context("failed to render config")
+    }
+
+    /// Set all settings of `cmd` that were not set by flags to the values of this configuration
+    pub fn apply_to(&self, cmd: &mut UpCommand) -> Result<()> {
+        let host = &self.host;
+        let nats = &mut cmd.nats_opts;
+        let wasmcloud = &mut cmd.wasmcloud_opts;
+        let wadm = &mut cmd.wadm_opts;
+
+        if wasmcloud.lattice.is_none() && host.lattice != crate::config::DEFAULT_LATTICE {
+            wasmcloud.lattice = Some(host.lattice.clone());
+        }
+        if !host.labels.is_empty() {
+            let labels = wasmcloud.label.get_or_insert_with(Vec::new);
+            for (key, value) in host.labels.iter().collect::<BTreeMap<_, _>>() {
+                let flagged = labels
+                    .iter()
+                    .any(|label| label.split_once('=').is_some_and(|(k, _)| k == key));
+                if !flagged {
+                    labels.push(format!("{key}={value}"));
+                }
+            }
+        }
+
+        if nats.nats_host.is_none() && host.nats.host != DEFAULT_NATS_HOST {
+            nats.nats_host = Some(host.nats.host.clone());
+        }
+        if nats.nats_port.is_none() && host.nats.port != DEFAULT_NATS_PORT {
+            nats.nats_port = Some(host.nats.port);
+        }
+        fill(
+            &mut wasmcloud.wasmcloud_js_domain,
+            host.nats.js_domain.as_ref(),
+        );
+
+        fill(&mut wasmcloud.ctl_host, host.ctl.host.as_ref());
+        fill(&mut wasmcloud.ctl_port, host.ctl.port.as_ref());
+        wasmcloud.ctl_tls |= host.ctl.tls;
+        // Credentials are only taken from the file as a whole, as flags could otherwise be mixed
+        // with conflicting credentials of the file
+        if wasmcloud.ctl_jwt.is_none()
+            && wasmcloud.ctl_seed.is_none()
+            && wasmcloud.ctl_credsfile.is_none()
+        {
+            let (jwt, seed, creds) = credentials(host, &host.ctl);
+            wasmcloud.ctl_jwt = jwt;
+            wasmcloud.ctl_seed = seed;
+            wasmcloud.ctl_credsfile = creds;
+        }
+        fill(&mut wasmcloud.rpc_host, host.rpc.host.as_ref());
+        fill(&mut wasmcloud.rpc_port, host.rpc.port.as_ref());
+        wasmcloud.rpc_tls |= host.rpc.tls;
+        if wasmcloud.rpc_jwt.is_none()
+            && wasmcloud.rpc_seed.is_none()
+            && wasmcloud.rpc_credsfile.is_none()
+        {
+            let (jwt, seed, creds) = credentials(host, &host.rpc);
+            wasmcloud.rpc_jwt = jwt;
+            wasmcloud.rpc_seed = seed;
+            wasmcloud.rpc_credsfile = creds;
+        }
+        if is_default(wasmcloud.rpc_timeout_ms, DEFAULT_RPC_TIMEOUT_MS)
+            && host.rpc_timeout.0 != DEFAULT_RPC_TIMEOUT
+        {
+            wasmcloud.rpc_timeout_ms = Some(millis(host.rpc_timeout.0));
+        }
+
+        wasmcloud.allow_latest |= host.oci.allow_latest;
+        if !host.oci.allowed_insecure.is_empty() {
+            fill(
+                &mut wasmcloud.allowed_insecure,
+                Some(&host.oci.allowed_insecure),
+            );
+        }
+        if is_default(
+            Some(wasmcloud.max_execution_time),
+            DEFAULT_MAX_EXECUTION_TIME_MS,
+        ) && host.limits.max_execution_time != HostLimits::default().max_execution_time
+        {
+            wasmcloud.max_execution_time = millis(host.limits.max_execution_time.0);
+        }
+
+        let section = &self.wasmcloud;
+        fill(&mut wasmcloud.wasmcloud_version, section.version.as_ref());
+        if wasmcloud.host_seed.is_none() {
+            wasmcloud.host_seed = section.host_seed.as_ref().map(|s| s.expose().clone());
+        }
+        if wasmcloud.cluster_seed.is_none() {
+            wasmcloud.cluster_seed = section.cluster_seed.as_ref().map(|s| s.expose().clone());
+        }
+        if !section.cluster_issuers.is_empty() {
+            fill(
+                &mut wasmcloud.cluster_issuers,
+                Some(&section.cluster_issuers),
+            );
+        }
+        if let Some(delay) = section.provider_shutdown_delay {
+            if is_default(
+                Some(wasmcloud.provider_delay),
+                DEFAULT_PROV_SHUTDOWN_DELAY_MS,
+            ) {
+                wasmcloud.provider_delay = u32::try_from(delay.0.as_millis())
+                    .context("`wasmcloud.provider_shutdown_delay` is too long")?;
+            }
+        }
+        wasmcloud.config_service_enabled |= section.config_service_enabled;
+        if let Some(allow_file_load) = section.allow_file_load {
+            if is_default(wasmcloud.allow_file_load, DEFAULT_ALLOW_FILE_LOAD) {
+                wasmcloud.allow_file_load = Some(allow_file_load);
+            }
+        }
+        wasmcloud.enable_structured_logging |= section.structured_logging;
+        if let Some(log_level) = &section.log_level {
+            if wasmcloud.structured_log_level == DEFAULT_STRUCTURED_LOG_LEVEL {
+                wasmcloud.structured_log_level.clone_from(log_level);
+            }
+        }
+        wasmcloud.enable_ipv6 |= section.enable_ipv6;
+        fill(&mut wasmcloud.secrets_topic, section.secrets_topic.as_ref());
+        fill(&mut wasmcloud.policy_topic, section.policy_topic.as_ref());
+        fill(&mut wasmcloud.host_log_path, section.log_path.as_ref());
+        fill(&mut wasmcloud.host_path, section.path.as_ref());
+        wasmcloud.start_only |= section.start_only;
+        wasmcloud.multi_local |= section.multi_local;
+
+        let section = &self.nats_server;
+        if let Some(version) = &section.version {
+            if nats.nats_version == NATS_SERVER_VERSION {
+                nats.nats_version.clone_from(version);
+            }
+        }
+        if let Some(port) = section.websocket_port {
+            if is_default(Some(nats.nats_websocket_port), DEFAULT_NATS_WEBSOCKET_PORT) {
+                nats.nats_websocket_port = port;
+            }
+        }
+        fill(&mut nats.nats_configfile, section.config_file.as_ref());
+        fill(&mut nats.nats_remote_url, section.remote_url.as_ref());
+        fill(&mut nats.nats_credsfile, section.credsfile.as_ref());
+        nats.connect_only |= section.connect_only;
+        fill(&mut nats.nats_js_domain, section.js_domain.as_ref());
+
+        let section = &self.wadm;
+        fill(&mut wadm.wadm_version, section.version.as_ref());
+        wadm.disable_wadm |= section.disable;
+        fill(&mut wadm.wadm_js_domain, section.js_domain.as_ref());
+        fill(&mut wadm.wadm_manifest, section.manifest.as_ref());
+        Ok(())
+    }
+
+    /// Returns the effective configuration of `cmd`, i.e. this configuration with all settings
+    /// replaced by the values of `cmd`. Settings without a flag keep the values of this
+    /// configuration
+    #[must_use]
+    pub fn effective(&self, cmd: &UpCommand) -> Self {
+        let mut config = self.clone();
+        let nats = &cmd.nats_opts;
+        let wasmcloud = &cmd.wasmcloud_opts;
+        let wadm = &cmd.wadm_opts;
+
+        let host = &mut config.host;
+        replace(&mut host.lattice, wasmcloud.lattice.as_ref());
+        if let Some(labels) = &wasmcloud.label {
+            host.labels = labels
+                .iter()
+                .filter_map(|label| label.split_once('='))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+        }
+        replace(&mut host.nats.host, nats.nats_host.as_ref());
+        replace(&mut host.nats.port, nats.nats_port.as_ref());
+        if wasmcloud.wasmcloud_js_domain.is_some() {
+            host.nats
+                .js_domain
+                .clone_from(&wasmcloud.wasmcloud_js_domain);
+        }
+        host.ctl = HostNatsOverrides {
+            host: wasmcloud.ctl_host.clone(),
+            port: wasmcloud.ctl_port,
+            jwt: wasmcloud.ctl_jwt.clone(),
+            seed: wasmcloud.ctl_seed.clone().map(Into::into),
+            creds: wasmcloud.ctl_credsfile.clone(),
+            tls: wasmcloud.ctl_tls,
+        };
+        host.rpc = HostNatsOverrides {
+            host: wasmcloud.rpc_host.clone(),
+            port: wasmcloud.rpc_port,
+            jwt: wasmcloud.rpc_jwt.clone(),
+            seed: wasmcloud.rpc_seed.clone().map(Into::into),
+            creds: wasmcloud.rpc_credsfile.clone(),
+            tls: wasmcloud.rpc_tls,
+        };
+        // The credentials of the shared connection were applied to the individual connections
+        host.nats.jwt = None;
+        host.nats.seed = None;
+        host.nats.creds = None;
+        if let Some(rpc_timeout_ms) = wasmcloud.rpc_timeout_ms {
+            host.rpc_timeout = Duration::from_millis(rpc_timeout_ms).into();
+        }
+        host.oci.allow_latest = wasmcloud.allow_latest;
+        if let Some(allowed_insecure) = &wasmcloud.allowed_insecure {
+            host.oci.allowed_insecure.clone_from(allowed_insecure);
+        }
+        host.limits.max_execution_time = Duration::from_millis(wasmcloud.max_execution_time).into();
+
+        config.wasmcloud = WasmcloudSection {
+            version: wasmcloud.wasmcloud_version.clone(),
+            host_seed: wasmcloud.host_seed.clone().map(Into::into),
+            cluster_seed: wasmcloud.cluster_seed.clone().map(Into::into),
+            cluster_issuers: wasmcloud.cluster_issuers.clone().unwrap_or_default(),
+            provider_shutdown_delay: Some(
+                Duration::from_millis(wasmcloud.provider_delay.into()).into(),
+            ),
+            config_service_enabled: wasmcloud.config_service_enabled,
+            allow_file_load: wasmcloud.allow_file_load,
+            structured_logging: wasmcloud.enable_structured_logging,
+            log_level: Some(wasmcloud.structured_log_level.clone()),
+            enable_ipv6: wasmcloud.enable_ipv6,
+            secrets_topic: wasmcloud.secrets_topic.clone(),
+            policy_topic: wasmcloud.policy_topic.clone(),
+            log_path: wasmcloud.host_log_path.clone(),
+            path: wasmcloud.host_path.clone(),
+            start_only: wasmcloud.start_only,
+            multi_local: wasmcloud.multi_local,
+        };
+        config.nats_server = NatsServerSection {
+            version: Some(nats.nats_version.clone()),
+            websocket_port: Some(nats.nats_websocket_port),
+            config_file: nats.nats_configfile.clone(),
+            remote_url: nats.nats_remote_url.clone(),
+            credsfile: nats.nats_credsfile.clone(),
+            connect_only: nats.connect_only,
+            js_domain: nats.nats_js_domain.clone(),
+        };
+        config.wadm = WadmSection {
+            version: wadm.wadm_version.clone(),
+            disable: wadm.disable_wadm,
+            js_domain: wadm.wadm_js_domain.clone(),
+            manifest: wadm.wadm_manifest.clone(),
+        };
+        config
+    }
+
+    /// Returns the environment of the host process for the host settings that have no flag.
+    /// The OpenTelemetry configuration is written to a file in `dir`, which is passed to the host
+    pub async fn host_env(&self, dir: &Path) -> Result<HashMap<String, String>> {
+        let host = &self.host;
+        let mut env = HashMap::new();
+        if host.ctl_topic_prefix != DEFAULT_CTL_TOPIC_PREFIX {
+            env.insert(
+                WASMCLOUD_CTL_TOPIC_PREFIX.to_string(),
+                host.ctl_topic_prefix.clone(),
+            );
+        }
+        if let (Some(registry), Some(user), Some(password)) =
+            (&host.oci.registry, &host.oci.user, &host.oci.password)
+        {
+            env.insert(WASMCLOUD_OCI_REGISTRY.to_string(), registry.clone());
+            env.insert(WASMCLOUD_OCI_REGISTRY_USER.to_string(), user.clone());
+            env.insert(
+                WASMCLOUD_OCI_REGISTRY_PASSWORD.to_string(),
+                password.expose().clone(),
+            );
+        }
+
+        let defaults = HostLimits::default();
+        if host.limits.max_linear_memory != defaults.max_linear_memory {
+            env.insert(
+                WASMCLOUD_MAX_LINEAR_MEMORY.to_string(),
+                host.limits.max_linear_memory.0.to_string(),
+            );
+        }
+        if host.limits.max_component_size != defaults.max_component_size {
+            env.insert(
+                WASMCLOUD_MAX_COMPONENT_SIZE.to_string(),
+                host.limits.max_component_size.0.to_string(),
+            );
+        }
+        if host.limits.max_components != defaults.max_components {
+            env.insert(
+                WASMCLOUD_MAX_COMPONENTS.to_string(),
+                host.limits.max_components.to_string(),
+            );
+        }
+        if host.limits.max_core_instances_per_component != defaults.max_core_instances_per_component
+        {
+            env.insert(
+                WASMCLOUD_MAX_CORE_INSTANCES_PER_COMPONENT.to_string(),
+                host.limits.max_core_instances_per_component.to_string(),
+            );
+        }
+
+        let otel = serde_json::to_value(&host.otel).context("failed to encode otel config")?;
+        if otel != serde_json::to_value(OtelConfig::default())? {
+            let path = dir.join(OBSERVABILITY_CONFIG_FILE);
+            tokio::fs::write(&path, serde_json::to_vec(&otel)?)
+                .await
+                .with_context(|| format!("failed to write `{}`", path.display()))?;
+            env.insert(
+                WASMCLOUD_OBSERVABILITY_CONFIG.to_string(),
+                path.to_string_lossy().to_string(),
+            );
+        }
+        Ok(env)
+    }
+}
+
+/// Set `value` to `default` if it is not set
+fn fill<T: Clone>(value: &mut Option<T>, default: Option<&T>) {
+    if value.is_none() {
+        *value = default.cloned();
+    }
+}
+
+/// Set `value` to `replacement` if it is set
+fn replace<T: Clone>(value: &mut T, replacement: Option<&T>) {
+    if let Some(replacement) = replacement {
+        value.clone_from(replacement);
+    }
+}
+
+/// Returns true if a flag is unset or set to its default value
+fn is_default<T: ToString>(value: Option<T>, default: &str) -> bool {
+    value.is_none_or(|value| value.to_string() == default)
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Returns the JWT, seed and credentials file of a connection, falling back to those of the
+/// shared connection if the connection has none of its own
+fn credentials(
+    host: &HostConfig,
+    overrides: &HostNatsOverrides,
+) -> (Option<String>, Option<String>, Option<PathBuf>) {
+    let (jwt, seed, creds) =
+        if overrides.jwt.is_some() || overrides.seed.is_some() || overrides.creds.is_some() {
+            (&overrides.jwt, &overrides.seed, &overrides.creds)
+        } else {
+            (&host.nats.jwt, &host.nats.seed, &host.nats.creds)
+        };
+    (
+        jwt.clone(),
+        seed.as_ref().map(|seed| seed.expose().clone()),
+        creds.clone(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+[host]
+lattice = "file"
+labels = { team = "payments", region = "eu" }
+rpc_timeout = "5s"
+ctl_topic_prefix = "custom.ctl"
+
+[host.nats]
+host = "nats.example.com"
+jwt = "jwt"
+seed = "seed"
+
+[host.limits]
+max_execution_time = "1m"
+max_components = 10
+
+[wasmcloud]
+version = "v1.4.2"
+log_level = "debug"
+
+[nats_server]
+websocket_port = 4444
+
+[wadm]
+disable = true
+"#;
+
+    fn config() -> UpConfig {
+        toml::from_str(CONFIG).expect("failed to parse config")
+    }
+
+    #[test]
+    fn flags_take_precedence_over_file() -> Result<()> {
+        let mut cmd = UpCommand::try_parse_from([
+            "up",
+            "--lattice",
+            "flag",
+            "--label",
+            "team=checkout",
+            "--log-level",
+            "warn",
+        ])?;
+        config().apply_to(&mut cmd)?;
+
+        let wasmcloud = &cmd.wasmcloud_opts;
+        assert_eq!(wasmcloud.lattice.as_deref(), Some("flag"));
+        assert_eq!(wasmcloud.structured_log_level, "warn");
+        let mut labels = wasmcloud.label.clone().unwrap_or_default();
+        labels.sort();
+        assert_eq!(labels, ["region=eu", "team=checkout"]);
+
+        // Settings not set by flags are taken from the file
+        assert_eq!(cmd.nats_opts.nats_host.as_deref(), Some("nats.example.com"));
+        assert_eq!(cmd.nats_opts.nats_websocket_port, 4444);
+        assert_eq!(wasmcloud.rpc_timeout_ms, Some(5000));
+        assert_eq!(wasmcloud.max_execution_time, 60_000);
+        assert_eq!(wasmcloud.ctl_jwt.as_deref(), Some("jwt"));
+        assert_eq!(wasmcloud.rpc_seed.as_deref(), Some("seed"));
+        assert_eq!(wasmcloud.wasmcloud_version.as_deref(), Some("v1.4.2"));
+        assert!(cmd.wadm_opts.disable_wadm);
+        Ok(())
+    }
+
+    #[test]
+    fn effective_config_roundtrips() -> Result<()> {
+        let config = config();
+        let mut cmd = UpCommand::try_parse_from(["up", "--rpc-timeout-ms", "3000"])?;
+        config.apply_to(&mut cmd)?;
+        let effective = config.effective(&cmd);
+
+        assert_eq!(effective.host.lattice, "file");
+        assert_eq!(effective.host.rpc_timeout.0, Duration::from_secs(3));
+        assert_eq!(effective.host.ctl_topic_prefix, "custom.ctl");
+        assert_eq!(effective.host.limits.max_components, 10);
+        assert_eq!(effective.host.ctl.jwt.as_deref(), Some("jwt"));
+        assert!(effective.host.nats.jwt.is_none());
+        effective.host.validate()?;
+
+        // Secrets are redacted when rendering the effective configuration
+        let rendered = effective.render(ConfigFormat::Toml)?;
+        assert!(!rendered.contains("\"seed\""), "{rendered}");
+        let rendered: UpConfig = toml::from_str(&rendered)?;
+        assert_eq!(rendered.host.lattice, "file");
+        assert_eq!(rendered.wasmcloud.log_level.as_deref(), Some("debug"));
+        assert_eq!(rendered.nats_server.websocket_port, Some(4444));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn host_env_covers_settings_without_flags() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let env = config().host_env(dir.path()).await?;
+        assert_eq!(
+            env.get(WASMCLOUD_CTL_TOPIC_PREFIX).map(String::as_str),
+            Some("custom.ctl")
+        );
+        assert_eq!(
+            env.get(WASMCLOUD_MAX_COMPONENTS).map(String::as_str),
+            Some("10")
+        );
+        assert!(!env.contains_key(WASMCLOUD_MAX_LINEAR_MEMORY));
+        assert!(!env.contains_key(WASMCLOUD_OBSERVABILITY_CONFIG));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unknown_sections() {
+        assert!(toml::from_str::<UpConfig>("[hots]\nlattice = \"typo\"").is_err());
+        assert!(ConfigFormat::from_path(Path::new("wash-up.ini")).is_err());
+    }
+}
@@ -56,12 +56,24 @@ use crate::config::{
 
 use crate::down::stop_nats;
 
+pub mod config;
+
+use self::config::{ConfigFormat, UpConfig};
+
 #[derive(Parser, Debug, Clone)]
 pub struct UpCommand {
     /// Launch NATS and wasmCloud detached from the current terminal as background processes
     #[clap(short = 'd', long = "detached", alias = "detach")]
     pub detached: bool,
 
+    /// Path to a TOML, YAML or JSON file configuring the host, NATS and wadm. Flags take precedence over values of the file
+    #[clap(long = "config", env = "WASH_UP_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective configuration, combining the config file, flags and context, without starting anything
+    #[clap(long = "print-config")]
+    pub print_config: bool,
+
     #[clap(flatten)]
     pub nats_opts: NatsOpts,
 
@@ -398,7 +410,13 @@ pub async fn handle_command(command: UpCommand, output_kind: OutputKind) -> Resu
     handle_up(command, output_kind).await
 }
 
-pub async fn handle_up(cmd: UpCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+pub async fn handle_up(mut cmd: UpCommand, output_kind: OutputKind) -> Result<CommandOutput> {
+    let up_config = match &cmd.config {
+        Some(path) => UpConfig::from_file(path)?,
+        None => UpConfig::default(),
+    };
+    up_config.apply_to(&mut cmd)?;
+
     let install_dir = WASH_DIRECTORIES.create_downloads_dir()?;
     let spinner = Spinner::new(&output_kind)?;
 
@@ -432,7 +450,41 @@ pub async fn handle_up(cmd: UpCommand, output_kind: OutputKind) -> Result<Comman
         wasmcloud_version: cmd.wasmcloud_opts.wasmcloud_version.clone(),
         ..cmd.wasmcloud_opts
     };
-    let host_env = configure_host_env(wasmcloud_opts.clone()).await?;
+    let effective_config = up_config.effective(&UpCommand {
+        detached: cmd.detached,
+        config: cmd.config.clone(),
+        print_config: cmd.print_config,
+        nats_opts: NatsOpts {
+            nats_host: Some(nats_host.clone()),
+            nats_port: Some(nats_port),
+            ..cmd.nats_opts.clone()
+        },
+        wasmcloud_opts: wasmcloud_opts.clone(),
+        wadm_opts: cmd.wadm_opts.clone(),
+    });
+    if cmd.config.is_some() {
+        effective_config
+            .host
+            .validate()
+            .context("invalid host configuration")?;
+    }
+    if cmd.print_config {
+        let format = cmd
+            .config
+            .as_deref()
+            .map(ConfigFormat::from_path)
+            .transpose()?
+            .unwrap_or_default();
+        let mut map = HashMap::new();
+        map.insert(
+            "config".to_string(),
+            serde_json::to_value(&effective_config)?,
+        );
+        return Ok(CommandOutput::new(effective_config.render(format)?, map));
+    }
+
+    let mut host_env = configure_host_env(wasmcloud_opts.clone()).await?;
+    host_env.extend(effective_config.host_env(&install_dir).await?);
     let nats_listen_address = format!("{nats_host}:{nats_port}");
 
     let nats_client = nats_client_from_wasmcloud_opts(&wasmcloud_opts).await;
@@ -822,7 +874,7 @@ async fn running_host_count(ctl_client: &CtlClient) -> Result<WasmCloudHostState
 ///
 /// This function uses `panic::catch_unwind` internally to handle potential panics
 /// from the `sysinfo` library, but should not panic itself.
-fn is_process_running(pid: &str) -> bool {
+pub(crate) fn is_process_running(pid: &str) -> bool {
     use std::panic::{self, AssertUnwindSafe};
 
     // Try to parse the PID as a u32
@@ -1,13 +1,14 @@
-use anyhow::Result;
+use anyhow::{bail, ensure, Result};
 use crossterm::{
     cursor, execute,
     terminal::{Clear, ClearType},
 };
-use std::{collections::HashMap, io::Write, time::Duration};
+use serde_json::json;
+use std::{collections::HashMap, future::Future, io::Write, time::Duration};
 use tokio::time::sleep;
 use crate::lib::cli::claims::get_claims;
 use crate::lib::cli::get::{
-    get_host_inventories, get_hosts, GetCommand, GetHostInventoriesCommand, GetLinksCommand,
+    get_host_inventories, get_hosts, GetCommand, GetHostInventoriesCommand,
 };
 use crate::lib::cli::link::{LinkCommand, LinkQueryCommand};
 use crate::lib::cli::{CommandOutput, OutputKind};
@@ -20,8 +21,20 @@ use crate::ctl::{
 
 pub async fn handle_command(command: GetCommand, output_kind: OutputKind) -> Result<CommandOutput> {
     let out: CommandOutput = match command {
-        GetCommand::Links(GetLinksCommand { opts }) => {
-            invoke_link_cmd(LinkCommand::Query(LinkQueryCommand { opts }), output_kind).await?
+        GetCommand::Links(cmd) => {
+            if let Some(contexts) = cmd.contexts.context_names()? {
+                return for_each_context(contexts, |context| {
+                    let mut opts = cmd.opts.clone();
+                    opts.context = Some(context);
+                    invoke_link_cmd(LinkCommand::Query(LinkQueryCommand { opts }), output_kind)
+                })
+                .await;
+            }
+            invoke_link_cmd(
+                LinkCommand::Query(LinkQueryCommand { opts: cmd.opts }),
+                output_kind,
+            )
+            .await?
         }
         GetCommand::Claims(cmd) => {
             let sp: Spinner = Spinner::new(&output_kind)?;
@@ -32,11 +45,36 @@ pub async fn handle_command(command: GetCommand, output_kind: OutputKind) -> Res
         GetCommand::Hosts(cmd) => {
             let sp: Spinner = Spinner::new(&output_kind)?;
             sp.update_spinner_message(" Retrieving Hosts ...".to_string());
+            if let Some(contexts) = cmd.contexts.context_names()? {
+                return for_each_context(contexts, |context| {
+                    let mut cmd = cmd.clone();
+                    cmd.opts.context = Some(context);
+                    async { Ok(get_hosts_output(get_hosts(cmd).await?)) }
+                })
+                .await;
+            }
             let hosts = get_hosts(cmd).await?;
             get_hosts_output(hosts)
         }
         GetCommand::HostInventories(cmd) => {
             let sp: Spinner = Spinner::new(&output_kind)?;
+            if let Some(contexts) = cmd.contexts.context_names()? {
+                ensure!(
+                    cmd.watch.is_none(),
+                    "--watch cannot be used with multiple contexts"
+                );
+                sp.update_spinner_message(" Retrieving inventories ...".to_string());
+                return for_each_context(contexts, |context| {
+                    let mut cmd = cmd.clone();
+                    cmd.opts.context = Some(context);
+                    async {
+                        Ok(get_host_inventories_output(
+                            get_host_inventories(cmd).await?,
+                        ))
+                    }
+                })
+                .await;
+            }
             if let Some(id) = cmd.host_id.as_ref() {
                 sp.update_spinner_message(format!(" Retrieving inventory for host {id} ..."));
             } else {
@@ -49,6 +87,44 @@ pub async fn handle_command(command: GetCommand, output_kind: OutputKind) -> Res
     Ok(out)
 }
 
+/// Run `op` against each of the given contexts concurrently, grouping the output of every context
+/// under its name. Failures of individual contexts are reported in the output, and only fail the
+/// command if every context failed.
+async fn for_each_context<F, Fut>(contexts: Vec<String>, op: F) -> Result<CommandOutput>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<CommandOutput>>,
+{
+    ensure!(!contexts.is_empty(), "no contexts were selected");
+    let results = futures::future::join_all(contexts.iter().cloned().map(op)).await;
+
+    let mut text = String::new();
+    let mut map = serde_json::Map::new();
+    let mut failures = 0;
+    for (context, result) in contexts.iter().zip(results) {
+        text.push_str(&format!("Context: {context}\n"));
+        match result {
+            Ok(output) => {
+                text.push_str(&output.text);
+                map.insert(context.clone(), json!(output.map));
+            }
+            Err(e) => {
+                failures += 1;
+                text.push_str(&format!("Error: {e:#}\n"));
+                map.insert(context.clone(), json!({ "error": format!("{e:#}") }));
+            }
+        }
+        text.push('\n');
+    }
+    if failures == contexts.len() {
+        bail!("command failed for all contexts:\n{text}");
+    }
+    Ok(CommandOutput::new(
+        text.trim_end().to_string(),
+        HashMap::from([("contexts".to_string(), serde_json::Value::Object(map))]),
+    ))
+}
+
 async fn get_inventory_handler(
     cmd: GetHostInventoriesCommand,
     sp: Spinner,
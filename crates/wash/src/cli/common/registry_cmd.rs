@@ -11,10 +11,12 @@ use tracing::warn;
 use crate::lib::cli::registry::{RegistryPullCommand, RegistryPushCommand};
 use crate::lib::cli::{input_vec_to_hashmap, CommandOutput, OutputKind};
 use crate::lib::parser::{load_config, ProjectConfig};
+use crate::lib::policy::Policy;
 use crate::lib::registry::{
     identify_artifact, pull_oci_artifact, push_oci_artifact, ArtifactType, OciPullOptions,
     OciPushOptions,
 };
+use crate::lib::secret_scan::scan_file;
 use wasmcloud_control_interface::RegistryCredential;
 
 use crate::appearance::spinner::Spinner;
@@ -28,6 +30,11 @@ pub async fn registry_pull(
     output_kind: OutputKind,
 ) -> Result<CommandOutput> {
     let image: Reference = resolve_artifact_ref(&cmd.url, &cmd.registry.unwrap_or_default(), None)?;
+    if let Some(policy) = Policy::load().await? {
+        let violations =
+            policy.check_registry(cmd.opts.insecure, cmd.opts.insecure_skip_tls_verify);
+        policy.enforce("pull", violations).await?;
+    }
     let spinner = Spinner::new(&output_kind)?;
     spinner.update_spinner_message(format!(" Downloading {} ...", image.whole()));
 
@@ -98,10 +105,42 @@ pub async fn registry_push(
         project_config.as_ref(),
     )?;
     let artifact_url = image.whole();
+    let insecure = cmd.opts.insecure
+        || project_config
+            .as_ref()
+            .is_some_and(|c| c.common.registry.push.push_insecure);
+    if let Some(policy) = Policy::load().await? {
+        let artifact = tokio::fs::read(&cmd.artifact)
+            .await
+            .with_context(|| format!("failed to read artifact `{}`", cmd.artifact))?;
+        let mut violations = policy.check_registry(insecure, cmd.opts.insecure_skip_tls_verify);
+        violations.extend(policy.check_push(&artifact).await);
+        policy.enforce("push", violations).await?;
+    }
     if artifact_url.starts_with("localhost:") && !cmd.opts.insecure {
         warn!(" Unless an SSL certificate has been installed, pushing to localhost without the --insecure option will fail");
     }
 
+    if cmd.allow_secrets {
+        warn!(" Skipping scan for embedded credentials due to --allow-secrets");
+    } else {
+        let mut findings = scan_file(&cmd.artifact).await?;
+        if let Some(config) = &cmd.config {
+            findings.extend(scan_file(config).await?);
+        }
+        if !findings.is_empty() {
+            let findings = findings
+                .iter()
+                .map(|f| format!("  - {f}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            bail!(
+                "refusing to push {}, it appears to contain embedded credentials:\n{findings}\nRemove the credentials or pass --allow-secrets to push anyway",
+                cmd.artifact
+            );
+        }
+    }
+
     let spinner = Spinner::new(&output_kind)?;
     spinner.update_spinner_message(format!(" Pushing {} to {} ...", cmd.artifact, artifact_url));
 
@@ -129,8 +168,7 @@ pub async fn registry_push(
             allow_latest: cmd.allow_latest,
             user: credentials.username().map(String::from),
             password: credentials.password().map(String::from),
-            insecure: cmd.opts.insecure
-                || project_config.is_some_and(|c| c.common.registry.push.push_insecure),
+            insecure,
             insecure_skip_tls_verify: cmd.opts.insecure_skip_tls_verify,
             annotations,
             monolithic_push: cmd.monolithic_push,
@@ -199,7 +237,7 @@ fn resolve_artifact_ref(
     }
 }
 
-async fn resolve_registry_credentials(registry: &str) -> Result<RegistryCredential> {
+pub(crate) async fn resolve_registry_credentials(registry: &str) -> Result<RegistryCredential> {
     let credentials = if let Ok(credentials) = load_config(None, Some(true))
         .await
         .and_then(|config| config.resolve_registry_credentials(registry))
@@ -131,6 +131,7 @@ mod test {
                 constraints,
                 auction_timeout_ms,
                 config,
+                secrets,
                 skip_wait,
             })) => {
                 assert_eq!(&opts.ctl_host.unwrap(), CTL_HOST);
@@ -144,6 +145,7 @@ mod test {
                 assert_eq!(provider_ref, "ghcr.io/provider:v1".to_string());
                 assert_eq!(provider_id, "providerv1".to_string());
                 assert!(config.is_empty());
+                assert!(secrets.is_empty());
                 assert!(skip_wait);
             }
             cmd => panic!("ctl start provider constructed incorrect command {cmd:?}"),
@@ -196,14 +196,16 @@ mod test {
             CONTEXT_PATH,
             "--host-timeout",
             &HOST_TIMEOUT_MS.to_string(),
+            "--decommission",
         ])?;
         match stop_host_all.command {
             CtlCliCommand::Stop(StopCommand::Host(StopHostCommand {
                 opts,
                 host_id,
                 host_shutdown_timeout,
-                ..
+                decommission,
             })) => {
+                assert!(decommission);
                 assert_eq!(&opts.ctl_host.unwrap(), CTL_HOST);
                 assert_eq!(&opts.ctl_port.unwrap(), CTL_PORT);
                 assert_eq!(&opts.lattice.unwrap(), DEFAULT_LATTICE);
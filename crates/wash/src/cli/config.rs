@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 use anyhow::{anyhow, Result};
+use wasmcloud_core::host_config::{HostConfig, HostNatsOverrides, HostOciConfig};
 
 use crate::cmd::up::WasmcloudOpts;
 use crate::creds::parse_credsfile;
@@ -13,7 +15,7 @@ pub use crate::lib::common::{
 
 // NATS isolation configuration variables
 pub const WASMCLOUD_LATTICE: &str = "WASMCLOUD_LATTICE";
-pub const DEFAULT_LATTICE: &str = "default";
+pub const DEFAULT_LATTICE: &str = wasmcloud_core::host_config::DEFAULT_LATTICE;
 pub const WASMCLOUD_JS_DOMAIN: &str = "WASMCLOUD_JS_DOMAIN";
 pub const WASMCLOUD_POLICY_TOPIC: &str = "WASMCLOUD_POLICY_TOPIC";
 pub const WASMCLOUD_SECRETS_TOPIC: &str = "WASMCLOUD_SECRETS_TOPIC";
@@ -52,6 +54,18 @@ pub const WASMCLOUD_PROV_SHUTDOWN_DELAY_MS: &str = "WASMCLOUD_PROV_SHUTDOWN_DELA
 pub const DEFAULT_PROV_SHUTDOWN_DELAY_MS: &str = "300";
 pub const WASMCLOUD_OCI_ALLOWED_INSECURE: &str = "WASMCLOUD_OCI_ALLOWED_INSECURE";
 pub const WASMCLOUD_OCI_ALLOW_LATEST: &str = "WASMCLOUD_OCI_ALLOW_LATEST";
+pub const WASMCLOUD_OCI_REGISTRY: &str = "WASMCLOUD_OCI_REGISTRY";
+pub const WASMCLOUD_OCI_REGISTRY_USER: &str = "WASMCLOUD_OCI_REGISTRY_USER";
+pub const WASMCLOUD_OCI_REGISTRY_PASSWORD: &str = "WASMCLOUD_OCI_REGISTRY_PASSWORD";
+
+// Host settings without a `wash up` flag, set from `wash up --config`
+pub const WASMCLOUD_CTL_TOPIC_PREFIX: &str = "WASMCLOUD_CTL_TOPIC_PREFIX";
+pub const WASMCLOUD_MAX_LINEAR_MEMORY: &str = "WASMCLOUD_MAX_LINEAR_MEMORY";
+pub const WASMCLOUD_MAX_COMPONENT_SIZE: &str = "WASMCLOUD_MAX_COMPONENT_SIZE";
+pub const WASMCLOUD_MAX_COMPONENTS: &str = "WASMCLOUD_MAX_COMPONENTS";
+pub const WASMCLOUD_MAX_CORE_INSTANCES_PER_COMPONENT: &str =
+    "WASMCLOUD_MAX_CORE_INSTANCES_PER_COMPONENT";
+pub const WASMCLOUD_OBSERVABILITY_CONFIG: &str = "WASMCLOUD_OBSERVABILITY_CONFIG";
 
 // Extra configuration (logs, IPV6, config service)
 pub const WASMCLOUD_LOG_LEVEL: &str = "WASMCLOUD_LOG_LEVEL";
@@ -67,6 +81,7 @@ pub const DEFAULT_ALLOW_FILE_LOAD: &str = "true";
 /// Helper function to convert `WasmcloudOpts` to the host environment map.
 /// Takes `NatsOpts` as well to provide reasonable defaults
 pub async fn configure_host_env(wasmcloud_opts: WasmcloudOpts) -> Result<HashMap<String, String>> {
+    validate_host_opts(&wasmcloud_opts)?;
     let mut host_config = HashMap::new();
     // NATS isolation configuration variables
     host_config.insert(
@@ -217,6 +232,47 @@ pub async fn configure_host_env(wasmcloud_opts: WasmcloudOpts) -> Result<HashMap
     Ok(host_config)
 }
 
+/// Checks the host settings among `wasmcloud_opts` against the host configuration schema, so
+/// that invalid settings are reported before a host is started with them
+fn validate_host_opts(wasmcloud_opts: &WasmcloudOpts) -> Result<()> {
+    let mut host_config = HostConfig {
+        lattice: wasmcloud_opts
+            .lattice
+            .clone()
+            .unwrap_or_else(|| DEFAULT_LATTICE.to_string()),
+        ctl: HostNatsOverrides {
+            host: wasmcloud_opts.ctl_host.clone(),
+            port: wasmcloud_opts.ctl_port,
+            jwt: wasmcloud_opts.ctl_jwt.clone(),
+            seed: wasmcloud_opts.ctl_seed.clone().map(Into::into),
+            creds: wasmcloud_opts.ctl_credsfile.clone(),
+            tls: wasmcloud_opts.ctl_tls,
+        },
+        rpc: HostNatsOverrides {
+            host: wasmcloud_opts.rpc_host.clone(),
+            port: wasmcloud_opts.rpc_port,
+            jwt: wasmcloud_opts.rpc_jwt.clone(),
+            seed: wasmcloud_opts.rpc_seed.clone().map(Into::into),
+            creds: wasmcloud_opts.rpc_credsfile.clone(),
+            tls: wasmcloud_opts.rpc_tls,
+        },
+        oci: HostOciConfig {
+            allow_latest: wasmcloud_opts.allow_latest,
+            allowed_insecure: wasmcloud_opts.allowed_insecure.clone().unwrap_or_default(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    host_config.nats.js_domain = wasmcloud_opts.wasmcloud_js_domain.clone();
+    if let Some(rpc_timeout_ms) = wasmcloud_opts.rpc_timeout_ms {
+        host_config.rpc_timeout = Duration::from_millis(rpc_timeout_ms).into();
+    }
+    host_config.limits.max_execution_time =
+        Duration::from_millis(wasmcloud_opts.max_execution_time).into();
+    host_config.validate()?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
@@ -235,4 +291,21 @@ mod tests {
         .is_err());
         Ok(())
     }
+
+    /// Ensure configuring the host env rejects settings the host would reject
+    #[tokio::test]
+    async fn invalid_host_settings() -> Result<()> {
+        let err = configure_host_env(WasmcloudOpts {
+            lattice: Some("my.lattice".into()),
+            rpc_port: Some(0),
+            max_execution_time: 600_000,
+            ..Default::default()
+        })
+        .await
+        .expect_err("invalid settings should be rejected");
+        let err = err.to_string();
+        assert!(err.contains("`lattice`"), "{err}");
+        assert!(err.contains("`rpc.port`"), "{err}");
+        Ok(())
+    }
 }
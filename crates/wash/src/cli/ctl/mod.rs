@@ -192,7 +192,7 @@ mod test {
             "2001",
         ])?;
         match get_hosts_all.command {
-            CtlCliCommand::Get(CtlGetCommand::Hosts(GetHostsCommand { opts })) => {
+            CtlCliCommand::Get(CtlGetCommand::Hosts(GetHostsCommand { opts, .. })) => {
                 assert_eq!(&opts.ctl_host.unwrap(), CTL_HOST);
                 assert_eq!(&opts.ctl_port.unwrap(), CTL_PORT);
                 assert_eq!(&opts.lattice.unwrap(), DEFAULT_LATTICE);
@@ -218,7 +218,7 @@ mod test {
             CtlCliCommand::Get(CtlGetCommand::HostInventories(GetHostInventoriesCommand {
                 opts,
                 host_id,
-                watch: _,
+                ..
             })) => {
                 assert_eq!(&opts.ctl_host.unwrap(), CTL_HOST);
                 assert_eq!(&opts.ctl_port.unwrap(), CTL_PORT);
@@ -285,6 +285,8 @@ mod test {
                 interfaces,
                 source_config,
                 target_config,
+                source_secrets,
+                target_secrets,
                 link_name,
             })) => {
                 assert_eq!(&opts.ctl_host.unwrap(), CTL_HOST);
@@ -299,6 +301,8 @@ mod test {
                 assert_eq!(interfaces.as_slice(), &["foo".to_string()]);
                 assert!(source_config.is_empty());
                 assert!(target_config.is_empty());
+                assert!(source_secrets.is_empty());
+                assert!(target_secrets.is_empty());
             }
             cmd => panic!("ctl link put constructed incorrect command {cmd:?}"),
         }
@@ -392,4 +396,41 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_get_multiple_contexts() -> anyhow::Result<()> {
+        let cmd: Cmd = Parser::try_parse_from(["ctl", "get", "hosts", "--contexts", "dev,prod"])?;
+        match cmd.command {
+            CtlCliCommand::Get(CtlGetCommand::Hosts(GetHostsCommand { contexts, .. })) => {
+                assert!(!contexts.all_contexts);
+                assert_eq!(contexts.contexts, ["dev", "prod"]);
+                assert_eq!(
+                    contexts.context_names()?,
+                    Some(vec!["dev".into(), "prod".into()])
+                );
+            }
+            cmd => panic!("ctl get hosts constructed incorrect command {cmd:?}"),
+        }
+        let cmd: Cmd = Parser::try_parse_from(["ctl", "get", "hosts"])?;
+        match cmd.command {
+            CtlCliCommand::Get(CtlGetCommand::Hosts(GetHostsCommand { contexts, .. })) => {
+                assert_eq!(contexts.context_names()?, None);
+            }
+            cmd => panic!("ctl get hosts constructed incorrect command {cmd:?}"),
+        }
+        assert!(
+            Cmd::try_parse_from(["ctl", "get", "hosts", "--all-contexts", "--context", "dev"])
+                .is_err()
+        );
+        assert!(Cmd::try_parse_from([
+            "ctl",
+            "get",
+            "hosts",
+            "--all-contexts",
+            "--contexts",
+            "dev"
+        ])
+        .is_err());
+        Ok(())
+    }
 }
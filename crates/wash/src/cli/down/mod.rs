@@ -188,8 +188,8 @@ pub async fn handle_down(cmd: DownCommand, output_kind: OutputKind) -> Result<Co
 
         if cmd.purge == PurgeJetstream::All || cmd.purge == PurgeJetstream::Wasmcloud {
             join_all(vec![
-                delete_kv_idempotent(&js_client, format!("CONFIGDATA_{}", &cmd.lattice)),
-                delete_kv_idempotent(&js_client, format!("LATTICEDATA_{}", &cmd.lattice)),
+                delete_kv_idempotent(&js_client, format!("CONFIGDATA_{}", cmd.lattice)),
+                delete_kv_idempotent(&js_client, format!("LATTICEDATA_{}", cmd.lattice)),
             ])
             .await
             .iter()
@@ -255,7 +255,7 @@ where
     let bin_path = bin_path.as_ref();
     let pid_file = nats_pid_path(work_dir.as_ref());
     let signal = if pid_file.is_file() {
-        format!("stop={}", &pid_file.display())
+        format!("stop={}", pid_file.display())
     } else {
         return Err(anyhow::anyhow!(
             "No pidfile found for nats-server, assuming it's managed externally"
@@ -245,7 +245,7 @@ pub async fn handle_command(
 /// Creates a provider archive using an initial architecture target, provider, and signing keys
 pub async fn handle_create(cmd: CreateCommand, output_kind: OutputKind) -> Result<CommandOutput> {
     let mut f = File::open(cmd.binary.clone())
-        .with_context(|| format!("failed to load binary [{}]", &cmd.binary))?;
+        .with_context(|| format!("failed to load binary [{}]", cmd.binary))?;
     let mut lib = Vec::new();
     f.read_to_end(&mut lib)?;
 
@@ -320,11 +320,11 @@ pub async fn handle_create(cmd: CreateCommand, output_kind: OutputKind) -> Resul
 pub async fn handle_insert(cmd: InsertCommand, output_kind: OutputKind) -> Result<CommandOutput> {
     let mut buf = Vec::new();
     let mut f = File::open(cmd.archive.clone())
-        .with_context(|| format!("failed to load provider archive [{}]", &cmd.archive))?;
+        .with_context(|| format!("failed to load provider archive [{}]", cmd.archive))?;
     f.read_to_end(&mut buf)?;
 
     let mut f = File::open(cmd.binary.clone())
-        .with_context(|| format!("failed to load binary [{}]", &cmd.archive))?;
+        .with_context(|| format!("failed to load binary [{}]", cmd.archive))?;
     let mut lib = Vec::new();
     f.read_to_end(&mut lib)?;
 
@@ -1,14 +1,33 @@
-use std::collections::HashMap;
+//! `wash secrets` manages the secret references stored in a lattice and, for the `nats-kv` and
+//! `vault` backends, writes the secret values themselves to the backend.
+//!
+//! Secret references are stored as named configuration prefixed with `SECRET_`, which hosts resolve
+//! against the backend when a component, provider or link refers to them.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::PathBuf;
 
 use crate::lib::cli::{input_vec_to_hashmap, CliConnectionOpts, CommandOutput, OutputKind};
-use anyhow::bail;
-use clap::Subcommand;
+use crate::lib::common::boxed_err_to_anyhow;
+use crate::lib::config::WashConnectionOptions;
+use anyhow::{bail, Context as _};
+use clap::{Args, Subcommand};
+use futures::TryStreamExt as _;
+use serde_json::json;
 use tracing::trace;
 use wasmcloud_secrets_types::{SecretConfig, SECRET_PREFIX};
 
+use crate::appearance::spinner::Spinner;
 use crate::cmd;
 
+/// Name of the secrets backend storing secrets in NATS KV
+const NATS_KV_BACKEND: &str = "nats-kv";
+/// Name of the secrets backend storing secrets in HashiCorp Vault
+const VAULT_BACKEND: &str = "vault";
+
 #[derive(Debug, Clone, Subcommand)]
+#[allow(clippy::large_enum_variant)]
 pub enum SecretsCliCommand {
     #[clap(name = "put", alias = "create", about = "Put secret reference")]
     PutCommand {
@@ -32,6 +51,20 @@ pub enum SecretsCliCommand {
         /// Freeform policy properties to pass to the secrets backend, in the form of `key=value`. Can be specified multiple times.
         #[clap(long = "property")]
         policy_properties: Vec<String>,
+        /// The value of the secret to write to the backend before creating the reference. Only
+        /// supported for the `nats-kv` and `vault` backends
+        #[clap(
+            long = "value",
+            env = "WASH_SECRET_VALUE",
+            hide_env_values = true,
+            conflicts_with = "value_file"
+        )]
+        value: Option<String>,
+        /// Path to a file containing the value of the secret to write to the backend
+        #[clap(long = "value-file")]
+        value_file: Option<PathBuf>,
+        #[clap(flatten)]
+        backend_opts: SecretsBackendOpts,
     },
 
     /// Get a secret reference by name
@@ -43,6 +76,13 @@ pub enum SecretsCliCommand {
         name: String,
     },
 
+    /// List the secret references in the lattice
+    #[clap(name = "list", alias = "ls")]
+    ListCommand {
+        #[clap(flatten)]
+        opts: CliConnectionOpts,
+    },
+
     /// Delete a secret reference by name
     #[clap(name = "del", alias = "delete")]
     DelCommand {
@@ -53,6 +93,56 @@ pub enum SecretsCliCommand {
     },
 }
 
+/// Options for writing secret values to a secrets backend
+#[derive(Args, Debug, Clone)]
+pub struct SecretsBackendOpts {
+    /// The subject prefix the `nats-kv` backend listens on
+    #[clap(
+        long = "nats-kv-subject-base",
+        env = "WASH_SECRETS_NATS_KV_SUBJECT_BASE",
+        default_value = "wasmcloud.secrets"
+    )]
+    pub nats_kv_subject_base: String,
+
+    /// The transit XKey of the `nats-kv` backend, used to encrypt the secret value in transit.
+    /// Either the public key or the seed of the XKey
+    #[clap(
+        long = "nats-kv-transit-xkey",
+        env = "WASH_SECRETS_NATS_KV_TRANSIT_XKEY"
+    )]
+    pub nats_kv_transit_xkey: Option<String>,
+
+    /// Public keys of the components and providers allowed to read the secret from the `nats-kv`
+    /// backend. Can be specified multiple times
+    #[clap(long = "allow")]
+    pub allow: Vec<String>,
+
+    /// Address of the Vault server
+    #[clap(long = "vault-addr", env = "VAULT_ADDR")]
+    pub vault_addr: Option<String>,
+
+    /// Token to authenticate to the Vault server with
+    #[clap(long = "vault-token", env = "VAULT_TOKEN", hide_env_values = true)]
+    pub vault_token: Option<String>,
+
+    /// Mount path of the KV v2 secrets engine in Vault
+    #[clap(long = "vault-mount", default_value = "secret")]
+    pub vault_mount: String,
+}
+
+impl Default for SecretsBackendOpts {
+    fn default() -> Self {
+        Self {
+            nats_kv_subject_base: "wasmcloud.secrets".to_string(),
+            nats_kv_transit_xkey: None,
+            allow: Vec::new(),
+            vault_addr: None,
+            vault_token: None,
+            vault_mount: "secret".to_string(),
+        }
+    }
+}
+
 pub async fn handle_command(
     command: SecretsCliCommand,
     output_kind: OutputKind,
@@ -66,7 +156,28 @@ pub async fn handle_command(
             field,
             version,
             policy_properties,
+            value,
+            value_file,
+            backend_opts,
         } => {
+            let value = match (value, value_file) {
+                (Some(value), _) => Some(value.into_bytes()),
+                (None, Some(path)) => Some(tokio::fs::read(&path).await.with_context(|| {
+                    format!("failed to read secret value from [{}]", path.display())
+                })?),
+                (None, None) => None,
+            };
+            if let Some(value) = value {
+                let wco: WashConnectionOptions = opts.clone().try_into()?;
+                let sp = Spinner::new(&output_kind)?;
+                sp.update_spinner_message(format!("Writing secret to backend [{backend}] ..."));
+                let written =
+                    put_secret_value(wco, &backend, &key, field.as_deref(), value, &backend_opts)
+                        .await;
+                sp.finish_and_clear();
+                written?;
+            }
+
             let policy_property_map = input_vec_to_hashmap(policy_properties)?;
             let secret_name = name.clone();
             let secret_config = SecretConfig::new(
@@ -88,12 +199,222 @@ pub async fn handle_command(
         SecretsCliCommand::GetCommand { opts, name } => {
             cmd::config::get::invoke(opts, &secret_configdata_key(&name), output_kind).await
         }
+        SecretsCliCommand::ListCommand { opts } => list_secrets(opts, output_kind).await,
         SecretsCliCommand::DelCommand { opts, name } => {
             cmd::config::delete::invoke(opts, &secret_configdata_key(&name), output_kind).await
         }
     }
 }
 
+/// Write the value of a secret to the backend the secret reference will point to
+async fn put_secret_value(
+    wco: WashConnectionOptions,
+    backend: &str,
+    key: &str,
+    field: Option<&str>,
+    value: Vec<u8>,
+    backend_opts: &SecretsBackendOpts,
+) -> anyhow::Result<()> {
+    match backend {
+        NATS_KV_BACKEND => {
+            let transit_xkey = backend_opts
+                .nats_kv_transit_xkey
+                .as_deref()
+                .context("--nats-kv-transit-xkey is required to write secrets to the nats-kv backend")?;
+            let transit_xkey = if transit_xkey.starts_with('S') {
+                nkeys::XKey::from_seed(transit_xkey)
+            } else {
+                nkeys::XKey::from_public_key(transit_xkey)
+            }
+            .context("invalid nats-kv transit xkey")?;
+            let (string_secret, binary_secret) = match String::from_utf8(value) {
+                Ok(value) => (Some(value), None),
+                Err(err) => (None, Some(err.into_bytes())),
+            };
+            let nats_client = wco.into_nats_client().await?;
+            let subject_base = &backend_opts.nats_kv_subject_base;
+            secrets_nats_kv::client::put_secret(
+                &nats_client,
+                subject_base,
+                &transit_xkey,
+                secrets_nats_kv::PutSecretRequest {
+                    key: key.to_string(),
+                    version: String::new(),
+                    string_secret,
+                    binary_secret,
+                },
+            )
+            .await
+            .with_context(|| format!("failed to write secret [{key}] to the nats-kv backend"))?;
+            for public_key in &backend_opts.allow {
+                secrets_nats_kv::client::add_mapping(
+                    &nats_client,
+                    subject_base,
+                    public_key,
+                    HashSet::from([key.to_string()]),
+                )
+                .await
+                .with_context(|| format!("failed to allow [{public_key}] to read secret [{key}]"))?;
+            }
+            Ok(())
+        }
+        VAULT_BACKEND => {
+            let addr = backend_opts
+                .vault_addr
+                .as_deref()
+                .context("--vault-addr is required to write secrets to the vault backend")?;
+            let token = backend_opts
+                .vault_token
+                .as_deref()
+                .context("--vault-token is required to write secrets to the vault backend")?;
+            // The vault backend returns the whole secret as JSON when no field is referenced
+            let field = field.context("--field is required to write secrets to the vault backend")?;
+            let value = String::from_utf8(value)
+                .context("the vault backend only supports UTF-8 secret values")?;
+            put_vault_secret(addr, token, &backend_opts.vault_mount, key, field, &value).await
+        }
+        backend => bail!(
+            "writing secret values is only supported for the {NATS_KV_BACKEND} and {VAULT_BACKEND} backends, not [{backend}]. Create the reference without a value instead"
+        ),
+    }
+}
+
+/// Set a field of a secret in a Vault KV v2 secrets engine, keeping the other fields of the secret
+async fn put_vault_secret(
+    addr: &str,
+    token: &str,
+    mount: &str,
+    key: &str,
+    field: &str,
+    value: &str,
+) -> anyhow::Result<()> {
+    let url = format!(
+        "{}/v1/{}/data/{}",
+        addr.trim_end_matches('/'),
+        mount.trim_matches('/'),
+        key.trim_start_matches('/')
+    );
+    let body = json!({ "data": { field: value } });
+    let client = reqwest::Client::new();
+    // Patching only updates the given field, but fails if the secret does not exist yet
+    let resp = client
+        .patch(&url)
+        .header("X-Vault-Token", token)
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            "application/merge-patch+json",
+        )
+        .body(body.to_string())
+        .send()
+        .await
+        .with_context(|| format!("failed to send request to Vault at [{addr}]"))?;
+    let resp = if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        client
+            .post(&url)
+            .header("X-Vault-Token", token)
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("failed to send request to Vault at [{addr}]"))?
+    } else {
+        resp
+    };
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().await.unwrap_or_default();
+        bail!("failed to write secret [{key}] to Vault: {status} {text}");
+    }
+    Ok(())
+}
+
+/// List the secret references stored in the configuration bucket of the lattice
+async fn list_secrets(
+    opts: CliConnectionOpts,
+    output_kind: OutputKind,
+) -> anyhow::Result<CommandOutput> {
+    let sp = Spinner::new(&output_kind)?;
+    sp.update_spinner_message("Listing secrets ...".to_string());
+    let wco: WashConnectionOptions = opts.try_into()?;
+    let bucket = format!("CONFIGDATA_{}", wco.get_lattice());
+    let js_domain = wco.js_domain.clone();
+    let ctl_client = wco.into_ctl_client(None).await?;
+
+    let nats_client = ctl_client
+        .nats_client()
+        .context("control interface client has no NATS connection")?;
+    let js = if let Some(domain) = js_domain {
+        async_nats::jetstream::with_domain(nats_client, domain)
+    } else {
+        async_nats::jetstream::new(nats_client)
+    };
+    let names: Vec<String> = match js.get_key_value(&bucket).await {
+        Ok(store) => store
+            .keys()
+            .await
+            .context("failed to list configuration names")?
+            .try_filter(|name| futures::future::ready(is_secret(name)))
+            .try_collect()
+            .await
+            .context("failed to list configuration names")?,
+        // The bucket is only created once configuration is first put
+        Err(_) => Vec::new(),
+    };
+
+    let configs = if names.is_empty() {
+        HashMap::new()
+    } else {
+        let resp = ctl_client
+            .get_configs(names)
+            .await
+            .map_err(boxed_err_to_anyhow)?;
+        if !resp.succeeded() {
+            bail!("Failed to get secret references: {}", resp.message());
+        }
+        resp.into_data().unwrap_or_default()
+    };
+    sp.finish_and_clear();
+
+    let secrets: BTreeMap<String, HashMap<String, String>> = configs
+        .into_iter()
+        .filter_map(|(name, config)| {
+            let name = name
+                .strip_prefix(&format!("{SECRET_PREFIX}_"))
+                .unwrap_or(&name)
+                .to_string();
+            config.map(|config| (name, config))
+        })
+        .collect();
+    Ok(CommandOutput::new(
+        render_secrets(&secrets),
+        HashMap::from([("secrets".into(), json!(secrets))]),
+    ))
+}
+
+fn render_secrets(secrets: &BTreeMap<String, HashMap<String, String>>) -> String {
+    if secrets.is_empty() {
+        return "No secrets found\n".to_string();
+    }
+    let field = |config: &HashMap<String, String>, name: &str| {
+        config.get(name).cloned().unwrap_or_default()
+    };
+    let mut text = format!(
+        "{:<24} {:<12} {:<32} {:<16} {}\n",
+        "NAME", "BACKEND", "KEY", "FIELD", "VERSION"
+    );
+    for (name, config) in secrets {
+        let _ = writeln!(
+            text,
+            "{:<24} {:<12} {:<32} {:<16} {}",
+            name,
+            field(config, "backend"),
+            field(config, "key"),
+            field(config, "field"),
+            field(config, "version"),
+        );
+    }
+    text
+}
+
 /// Ensure that a given config KV name is *not* a secret
 pub(crate) fn ensure_not_secret(name: &str) -> anyhow::Result<()> {
     if name.starts_with(SECRET_PREFIX) {
@@ -115,3 +436,41 @@ pub(crate) fn secret_configdata_key(name: &str) -> String {
         format!("{SECRET_PREFIX}_{name}")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::cli::with_secrets;
+
+    #[test]
+    fn secret_references_are_prefixed() {
+        assert_eq!(secret_configdata_key("db"), "SECRET_db");
+        assert_eq!(secret_configdata_key("SECRET_db"), "SECRET_db");
+        assert_eq!(
+            with_secrets(
+                vec!["settings".into()],
+                &["db".into(), "SECRET_api-key".into()]
+            ),
+            ["settings", "SECRET_db", "SECRET_api-key"]
+        );
+    }
+
+    #[test]
+    fn renders_secret_references() {
+        let secrets = BTreeMap::from([(
+            "db".to_string(),
+            HashMap::from([
+                ("backend".to_string(), "nats-kv".to_string()),
+                ("key".to_string(), "db-password".to_string()),
+            ]),
+        )]);
+        let text = render_secrets(&secrets);
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("NAME"));
+        assert!(lines[1].starts_with("db "));
+        assert!(lines[1].contains("nats-kv"));
+        assert!(lines[1].contains("db-password"));
+        assert_eq!(render_secrets(&BTreeMap::new()), "No secrets found\n");
+    }
+}
@@ -24,7 +24,7 @@ pub fn extract_arg_value(arg: &str) -> Result<String> {
         Ok(mut f) => {
             let mut value = String::new();
             f.read_to_string(&mut value)
-                .with_context(|| format!("Failed to read file {}", &arg))?;
+                .with_context(|| format!("Failed to read file {}", arg))?;
             Ok(value)
         }
         Err(_) => Ok(arg.to_string()),
@@ -15,7 +15,9 @@ pub mod lib {
     pub mod keys;
     pub mod parser;
     pub mod plugin;
+    pub mod policy;
     pub mod registry;
+    pub mod secret_scan;
     pub mod spier;
     pub mod start;
     pub mod wait;
@@ -508,6 +508,42 @@ pub fn extract_image_references(manifest: &Manifest) -> Vec<String> {
     image_refs
 }
 
+/// Pin every image in the manifest that refers to `image` to the given digest, returning the
+/// number of images that were updated.
+///
+/// References are compared after normalization, so `ghcr.io/org/app:v1` matches an image written
+/// as `ghcr.io/org/app:v1` or `ghcr.io/org/app:v1@sha256:...`.
+pub fn pin_image_references(
+    manifest: &mut Manifest,
+    image: &oci_client::Reference,
+    digest: &str,
+) -> usize {
+    let same_image = |candidate: &str| {
+        candidate.parse::<oci_client::Reference>().is_ok_and(|r| {
+            r.registry() == image.registry()
+                && r.repository() == image.repository()
+                && r.tag() == image.tag()
+        })
+    };
+    let pinned = image.clone_with_digest(digest.to_string()).whole();
+    let mut updated = 0;
+    for component in &mut manifest.spec.components {
+        let image_ref = match &mut component.properties {
+            Properties::Component {
+                properties: ComponentProperties { image, .. },
+            }
+            | Properties::Capability {
+                properties: CapabilityProperties { image, .. },
+            } => image,
+        };
+        if let Some(current) = image_ref.as_mut().filter(|current| same_image(current)) {
+            current.clone_from(&pinned);
+            updated += 1;
+        }
+    }
+    updated
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -614,4 +650,47 @@ mapping:
         ));
         Ok(())
     }
+
+    #[test]
+    fn test_pin_image_references() -> Result<()> {
+        let old_digest = format!("sha256:{}", "a".repeat(64));
+        let new_digest = format!("sha256:{}", "b".repeat(64));
+        let mut manifest: Manifest = serde_yaml::from_str(&format!(
+            r"
+apiVersion: core.oam.dev/v1beta1
+kind: Application
+metadata:
+  name: hello
+  annotations:
+    version: v0.0.1
+spec:
+  components:
+    - name: http-component
+      type: component
+      properties:
+        image: ghcr.io/org/hello:v1
+    - name: other-component
+      type: component
+      properties:
+        image: ghcr.io/org/other:v1
+    - name: httpserver
+      type: capability
+      properties:
+        image: ghcr.io/org/hello:v1@{old_digest}
+"
+        ))
+        .context("failed to parse manifest")?;
+
+        let image = "ghcr.io/org/hello:v1".parse()?;
+        assert_eq!(pin_image_references(&mut manifest, &image, &new_digest), 2);
+        assert_eq!(
+            extract_image_references(&manifest),
+            vec![
+                format!("ghcr.io/org/hello@{new_digest}"),
+                "ghcr.io/org/other:v1".to_string(),
+                format!("ghcr.io/org/hello@{new_digest}"),
+            ]
+        );
+        Ok(())
+    }
 }
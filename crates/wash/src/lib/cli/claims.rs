@@ -596,7 +596,7 @@ fn generate_provider(provider: ProviderMetadata, output_kind: OutputKind) -> Res
 
 pub fn sign_file(cmd: SignCommand, output_kind: OutputKind) -> Result<CommandOutput> {
     let mut sfile = File::open(&cmd.source)
-        .with_context(|| format!("Failed to open file for signing '{}'", &cmd.source))?;
+        .with_context(|| format!("Failed to open file for signing '{}'", cmd.source))?;
     let mut buf = Vec::new();
     sfile.read_to_end(&mut buf).unwrap();
 
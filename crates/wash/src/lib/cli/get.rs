@@ -3,10 +3,11 @@ use std::str::FromStr;
 use crate::lib::{
     common::{boxed_err_to_anyhow, get_all_inventories},
     config::WashConnectionOptions,
+    context::{fs::ContextDir, ContextManager},
     id::ServerId,
 };
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Args, Parser};
 use wasmcloud_control_interface::{Host, HostInventory};
 
 use super::CliConnectionOpts;
@@ -17,11 +18,45 @@ pub struct GetClaimsCommand {
     pub opts: CliConnectionOpts,
 }
 
+/// Selection of multiple contexts to run a read-only command against, grouping the output per
+/// context. Connection options passed on the command line apply to every selected context.
+#[derive(Args, Debug, Clone, Default)]
+pub struct ContextSelection {
+    /// Run the command against every context, grouping the output per context
+    #[clap(long = "all-contexts", conflicts_with_all = ["contexts", "context"])]
+    pub all_contexts: bool,
+
+    /// Comma-separated names of contexts to run the command against, grouping the output per context
+    #[clap(long = "contexts", value_delimiter = ',', conflicts_with = "context")]
+    pub contexts: Vec<String>,
+}
+
+impl ContextSelection {
+    /// Returns the names of the selected contexts, or `None` if the command should only run
+    /// against a single context
+    pub fn context_names(&self) -> Result<Option<Vec<String>>> {
+        if self.all_contexts {
+            let mut names = ContextDir::new()?
+                .list_contexts()
+                .context("failed to list contexts")?;
+            names.sort();
+            Ok(Some(names))
+        } else if self.contexts.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(self.contexts.clone()))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Parser)]
 pub struct GetHostInventoriesCommand {
     #[clap(flatten)]
     pub opts: CliConnectionOpts,
 
+    #[clap(flatten)]
+    pub contexts: ContextSelection,
+
     /// Host ID to retrieve inventory for. If not provided, wash will query the inventories of all running hosts.
     #[clap(name = "host-id", value_parser)]
     pub host_id: Option<ServerId>,
@@ -35,12 +70,18 @@ pub struct GetHostInventoriesCommand {
 pub struct GetLinksCommand {
     #[clap(flatten)]
     pub opts: CliConnectionOpts,
+
+    #[clap(flatten)]
+    pub contexts: ContextSelection,
 }
 
 #[derive(Debug, Clone, Parser)]
 pub struct GetHostsCommand {
     #[clap(flatten)]
     pub opts: CliConnectionOpts,
+
+    #[clap(flatten)]
+    pub contexts: ContextSelection,
 }
 
 #[derive(Debug, Clone, Parser)]
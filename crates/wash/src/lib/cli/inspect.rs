@@ -82,7 +82,7 @@ pub async fn handle_command(
         let mut f = File::open(&command.target).map_err(|e| {
             std::io::Error::new(
                 e.kind(),
-                format!("failed to target file [{}]: {e}", &command.target),
+                format!("failed to target file [{}]: {e}", command.target),
             )
         })?;
         f.read_to_end(&mut buf)?;
@@ -263,7 +263,7 @@ fn token_label(pk: &str) -> String {
     }
 }
 
-fn render_core<T>(claims: &Claims<T>, validation: TokenValidation) -> Table
+fn render_core<T>(claims: &Claims<T>, validation: TokenValidation) -> Table<'_>
 where
     T: serde::Serialize + DeserializeOwned + WascapEntity,
 {
@@ -3,7 +3,9 @@ use clap::Parser;
 use wasmcloud_control_interface::{CtlResponse, Link};
 
 use crate::lib::{
-    cli::CliConnectionOpts, common::boxed_err_to_anyhow, config::WashConnectionOptions,
+    cli::CliConnectionOpts,
+    common::{boxed_err_to_anyhow, resolve_component_id},
+    config::WashConnectionOptions,
 };
 
 use super::validate_component_id;
@@ -71,6 +73,14 @@ pub struct LinkPutCommand {
     #[clap(long = "target-config")]
     pub target_config: Vec<String>,
 
+    /// List of secrets to make available to the source, created with `wash secrets put`
+    #[clap(long = "source-secret")]
+    pub source_secrets: Vec<String>,
+
+    /// List of secrets to make available to the target, created with `wash secrets put`
+    #[clap(long = "target-secret")]
+    pub target_secrets: Vec<String>,
+
     /// Link name, defaults to "default". Used for scenarios where a single source
     /// may have multiple links to the same target, or different targets with the same
     /// WIT namespace, package, and interface.
@@ -160,6 +170,7 @@ pub async fn delete_link(
     wit_package: &str,
 ) -> Result<CtlResponse<()>> {
     let ctl_client = wco.into_ctl_client(None).await?;
+    let source_id = &resolve_component_id(source_id, &ctl_client).await;
     ctl_client
         .delete_link(source_id, link_name, wit_namespace, wit_package)
         .await
@@ -204,6 +215,23 @@ pub async fn delete_link(
 /// ```
 pub async fn put_link(wco: WashConnectionOptions, link: Link) -> Result<CtlResponse<()>> {
     let ctl_client = wco.into_ctl_client(None).await?;
+    let source_id = resolve_component_id(link.source_id(), &ctl_client).await;
+    let target = resolve_component_id(link.target(), &ctl_client).await;
+    let link = if source_id != link.source_id() || target != link.target() {
+        Link::builder()
+            .source_id(&source_id)
+            .target(&target)
+            .name(link.name())
+            .wit_namespace(link.wit_namespace())
+            .wit_package(link.wit_package())
+            .interfaces(link.interfaces().clone())
+            .source_config(link.source_config().clone())
+            .target_config(link.target_config().clone())
+            .build()
+            .map_err(|e| anyhow::anyhow!(e).context("failed to build link"))?
+    } else {
+        link
+    };
     ctl_client
         .put_link(link.clone())
         .await
@@ -23,6 +23,7 @@ use wasm_pkg_client::{
     caching::{CachingClient, FileCache},
     RegistryMapping,
 };
+use wasmcloud_secrets_types::SECRET_PREFIX;
 
 use crate::lib::{
     config::{
@@ -475,6 +476,20 @@ pub fn input_vec_to_hashmap(values: Vec<String>) -> Result<HashMap<String, Strin
     Ok(hm)
 }
 
+/// Appends references to the given secrets to a list of named configuration, which is how secrets
+/// are passed to components, providers and links. Secret names may omit the `SECRET_` prefix
+#[must_use]
+pub fn with_secrets(mut config: Vec<String>, secrets: &[String]) -> Vec<String> {
+    config.extend(secrets.iter().map(|name| {
+        if name.starts_with(SECRET_PREFIX) {
+            name.clone()
+        } else {
+            format!("{SECRET_PREFIX}_{name}")
+        }
+    }));
+    config
+}
+
 /// This function is a simple helper to ensure that a component ID is a valid
 /// string containing only alphanumeric characters, underscores or dashes
 pub fn validate_component_id(id: &str) -> anyhow::Result<String> {
@@ -103,4 +103,8 @@ pub struct RegistryPushCommand {
     /// Push the artifact monolithically instead of chunked
     #[clap(long = "monolithic-push", env = "WASH_MONOLITHIC_PUSH")]
     pub monolithic_push: bool,
+
+    /// Push the artifact even if the artifact or its config appear to contain embedded credentials
+    #[clap(long = "allow-secrets")]
+    pub allow_secrets: bool,
 }
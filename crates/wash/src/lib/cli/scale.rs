@@ -4,7 +4,7 @@ use anyhow::Result;
 use clap::Parser;
 
 use crate::lib::cli::{input_vec_to_hashmap, CliConnectionOpts, CommandOutput};
-use crate::lib::common::find_host_id;
+use crate::lib::common::{find_host_id, resolve_component_id};
 use crate::lib::component::{scale_component, ScaleComponentArgs};
 use crate::lib::config::WashConnectionOptions;
 use crate::lib::context::default_component_operation_timeout_ms;
@@ -73,7 +73,7 @@ pub async fn handle_scale_component(cmd: ScaleComponentCommand) -> Result<Comman
         // NOTE(thomastaylor312): In the future, we could check if this is interactive and then
         // prompt the user to choose if more than one thing matches
         host_id: &find_host_id(&cmd.host_id, &client).await?.0,
-        component_id: &cmd.component_id,
+        component_id: &resolve_component_id(&cmd.component_id, &client).await,
         component_ref: &component_ref,
         max_instances: cmd.max_instances,
         annotations: Some(annotations),
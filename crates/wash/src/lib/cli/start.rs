@@ -4,7 +4,7 @@ use anyhow::{bail, Context, Result};
 use clap::Parser;
 use tokio::time::Duration;
 
-use crate::lib::cli::{input_vec_to_hashmap, CliConnectionOpts, CommandOutput};
+use crate::lib::cli::{input_vec_to_hashmap, with_secrets, CliConnectionOpts, CommandOutput};
 use crate::lib::common::{boxed_err_to_anyhow, find_host_id};
 use crate::lib::component::{scale_component, ComponentScaledInfo, ScaleComponentArgs};
 use crate::lib::config::{
@@ -74,13 +74,17 @@ pub struct StartComponentCommand {
     /// List of named configuration to apply to the component, may be empty
     #[clap(long = "config")]
     pub config: Vec<String>,
+
+    /// List of secrets to make available to the component, created with `wash secrets put`
+    #[clap(long = "secret")]
+    pub secrets: Vec<String>,
 }
 
 /// Utility function for resolving component and provider references
 pub(crate) async fn resolve_ref(s: impl AsRef<str>) -> Result<String> {
     let resolved = match s.as_ref() {
         s if s.starts_with('/') => {
-            format!("file://{}", &s) // prefix with file:// if it's an absolute path
+            format!("file://{}", s) // prefix with file:// if it's an absolute path
         }
         s if tokio::fs::try_exists(s).await.is_ok_and(|exists| exists) => {
             format!(
@@ -138,7 +142,7 @@ pub async fn handle_start_component(cmd: StartComponentCommand) -> Result<Comman
             .with_context(|| {
                 format!(
                     "Failed to auction component {} to hosts in lattice",
-                    &component_ref
+                    component_ref
                 )
             })?;
         if suitable_hosts.is_empty() {
@@ -169,7 +173,7 @@ pub async fn handle_start_component(cmd: StartComponentCommand) -> Result<Comman
         skip_wait: cmd.skip_wait,
         timeout_ms: Some(timeout_ms),
         annotations: None,
-        config: cmd.config,
+        config: with_secrets(cmd.config, &cmd.secrets),
     })
     .await?;
 
@@ -226,6 +230,10 @@ pub struct StartProviderCommand {
     #[clap(long = "config")]
     pub config: Vec<String>,
 
+    /// List of secrets to make available to the provider, created with `wash secrets put`
+    #[clap(long = "secret")]
+    pub secrets: Vec<String>,
+
     /// By default, the command will wait until the provider has been started.
     /// If this flag is passed, the command will return immediately after acknowledgement from the host, without waiting for the provider to start.
     /// If this flag is omitted, the timeout will be adjusted to 30 seconds to account for provider download times
@@ -261,7 +269,7 @@ pub async fn handle_start_provider(cmd: StartProviderCommand) -> Result<CommandO
             .with_context(|| {
                 format!(
                     "Failed to auction provider {} with link name {} to hosts in lattice",
-                    &provider_ref, &cmd.link_name
+                    provider_ref, cmd.link_name
                 )
             })?;
         if suitable_hosts.is_empty() {
@@ -288,13 +296,19 @@ pub async fn handle_start_provider(cmd: StartProviderCommand) -> Result<CommandO
         .context("Failed to get lattice event channel")?;
 
     let ack = client
-        .start_provider(&host, &provider_ref, &cmd.provider_id, None, cmd.config)
+        .start_provider(
+            &host,
+            &provider_ref,
+            &cmd.provider_id,
+            None,
+            with_secrets(cmd.config, &cmd.secrets),
+        )
         .await
         .map_err(boxed_err_to_anyhow)
         .with_context(|| {
             format!(
                 "Failed to start provider {} on host {:?}",
-                &cmd.provider_id, &host
+                cmd.provider_id, host
             )
         })?;
 
@@ -303,7 +317,7 @@ pub async fn handle_start_provider(cmd: StartProviderCommand) -> Result<CommandO
     }
 
     if cmd.skip_wait {
-        let text = format!("Start provider request received: {}", &provider_ref);
+        let text = format!("Start provider request received: {}", provider_ref);
         return Ok(CommandOutput::new(
             text.clone(),
             HashMap::from([
@@ -325,7 +339,7 @@ pub async fn handle_start_provider(cmd: StartProviderCommand) -> Result<CommandO
     .with_context(|| {
         format!(
             "Timed out waiting for start event for provider {} on host {}",
-            &provider_ref, &host
+            provider_ref, host
         )
     })?;
 
@@ -337,7 +351,7 @@ pub async fn handle_start_provider(cmd: StartProviderCommand) -> Result<CommandO
         }) => {
             let text = format!(
                 "Provider [{}] (ref: [{}]) started on host [{}]",
-                &provider_id, &provider_ref, &host_id
+                provider_id, provider_ref, host_id
             );
             Ok(CommandOutput::new(
                 text.clone(),
@@ -349,11 +363,7 @@ pub async fn handle_start_provider(cmd: StartProviderCommand) -> Result<CommandO
                 ]),
             ))
         }
-        FindEventOutcome::Failure(err) => Err(err).with_context(|| {
-            format!(
-                "Failed starting provider {} on host {}",
-                &provider_ref, &host
-            )
-        }),
+        FindEventOutcome::Failure(err) => Err(err)
+            .with_context(|| format!("Failed starting provider {} on host {}", provider_ref, host)),
     }
 }
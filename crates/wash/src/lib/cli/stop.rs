@@ -7,7 +7,10 @@ use wasmcloud_control_interface::HostInventory;
 
 use crate::lib::{
     cli::{CliConnectionOpts, CommandOutput},
-    common::{boxed_err_to_anyhow, find_host_id, get_all_inventories, FindIdError, Match},
+    common::{
+        boxed_err_to_anyhow, find_host_id, get_all_inventories, resolve_component_id, FindIdError,
+        Match,
+    },
     component::{scale_component, ComponentScaledInfo, ScaleComponentArgs},
     config::{host_pid_file, WashConnectionOptions},
     context::default_timeout_ms,
@@ -99,6 +102,11 @@ pub struct StopHostCommand {
         default_value_t = default_timeout_ms()
     )]
     pub host_shutdown_timeout: u64,
+
+    /// Stop the host permanently, e.g. before removing its node. The host records that it was
+    /// decommissioned in the lattice and removes its provider logs and flight recordings
+    #[clap(long = "decommission")]
+    pub decommission: bool,
 }
 
 pub async fn handle_stop_provider(cmd: StopProviderCommand) -> Result<CommandOutput> {
@@ -115,9 +123,9 @@ pub async fn handle_stop_provider(cmd: StopProviderCommand) -> Result<CommandOut
     .await?;
 
     let text = if cmd.skip_wait {
-        format!("Provider {} stop request received", &cmd.provider_id)
+        format!("Provider {} stop request received", cmd.provider_id)
     } else {
-        format!("Provider [{}] stopped successfully", &cmd.provider_id)
+        format!("Provider [{}] stopped successfully", cmd.provider_id)
     };
 
     Ok(CommandOutput::new(
@@ -182,9 +190,10 @@ pub async fn handle_stop_component(cmd: StopComponentCommand) -> Result<CommandO
     let wco: WashConnectionOptions = cmd.opts.try_into()?;
     let client = wco.into_ctl_client(None).await?;
 
-    let component_id = cmd.component_id;
+    let component_id = resolve_component_id(&cmd.component_id, &client).await;
 
     let inventory = if let Some(host_id) = cmd.host_id {
+        let host_id = find_host_id(&host_id, &client).await?.0;
         client
             .get_host_inventory(&host_id)
             .await
@@ -257,6 +266,24 @@ pub async fn stop_host(cmd: StopHostCommand) -> Result<CommandOutput> {
     let wco: WashConnectionOptions = cmd.opts.try_into()?;
     let client = wco.into_ctl_client(None).await?;
 
+    if cmd.decommission {
+        let response = client
+            .decommission_host(&cmd.host_id, None)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        if !response.succeeded() {
+            bail!(
+                "Failed to decommission host {}: {}",
+                cmd.host_id,
+                response.message()
+            );
+        }
+        return Ok(CommandOutput::from_key_and_text(
+            "result",
+            format!("Host {} acknowledged decommission request", cmd.host_id),
+        ));
+    }
+
     let (_, hosts_remain) = stop_hosts(client, Some(&cmd.host_id), false).await?;
     let pid_file_exists = tokio::fs::try_exists(host_pid_file()?).await?;
     if !hosts_remain && pid_file_exists {
@@ -3,7 +3,7 @@ use clap::Parser;
 use wasmcloud_control_interface::HostInventory;
 
 use crate::lib::{
-    common::{boxed_err_to_anyhow, get_all_inventories},
+    common::{boxed_err_to_anyhow, find_host_id, get_all_inventories, resolve_component_id},
     component::update_component,
     config::WashConnectionOptions,
 };
@@ -39,11 +39,13 @@ pub struct UpdateComponentCommand {
     pub new_component_ref: String,
 }
 
-pub async fn handle_update_component(cmd: UpdateComponentCommand) -> Result<CommandOutput> {
-    let wco: WashConnectionOptions = cmd.opts.try_into()?;
+pub async fn handle_update_component(mut cmd: UpdateComponentCommand) -> Result<CommandOutput> {
+    let wco: WashConnectionOptions = cmd.opts.clone().try_into()?;
     let client = wco.into_ctl_client(None).await?;
+    cmd.component_id = resolve_component_id(&cmd.component_id, &client).await;
 
-    let inventory = if let Some(host_id) = cmd.host_id {
+    let inventory = if let Some(host_id) = &cmd.host_id {
+        let host_id = find_host_id(host_id, &client).await?.0;
         client
             .get_host_inventory(&host_id)
             .await
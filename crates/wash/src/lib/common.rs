@@ -8,7 +8,7 @@ use semver::Version;
 use tokio::process::Command;
 
 use anyhow::Context;
-use tracing::error;
+use tracing::{debug, error};
 use wasmcloud_control_interface::HostInventory;
 
 use crate::lib::id::{ModuleId, ServerId, ServiceId};
@@ -201,8 +201,9 @@ async fn find_id_matches<T: FromStr + ToString + Display>(
 /// If the string is a valid host ID, it will be returned unchanged. If it is not an ID, it will
 /// attempt to resolve an ID in the following order:
 ///
-/// 1. The value matches the prefix of the ID of a host
-/// 2. The value is contained in the friendly name field of a host
+/// 1. The value is an alias registered on the lattice for a host
+/// 2. The value matches the prefix of the ID of a host
+/// 3. The value is contained in the friendly name field of a host
 ///
 /// If more than one matches, then an error will be returned indicating the options to choose from
 pub async fn find_host_id(
@@ -213,6 +214,13 @@ pub async fn find_host_id(
         return Ok((id, String::new()));
     }
 
+    if let Some(id) = resolve_alias(value, ctl_client)
+        .await
+        .and_then(|target| target.host_id().and_then(|id| ServerId::from_str(id).ok()))
+    {
+        return Ok((id, value.to_string()));
+    }
+
     // Case insensitive searching here to make things nicer
     let value = value.to_lowercase();
 
@@ -256,6 +264,35 @@ pub async fn find_host_id(
     }
 }
 
+/// Given a string, attempts to resolve a component ID using the aliases registered on the lattice.
+///
+/// Since component IDs are arbitrary strings, the value is returned unchanged if it is not an alias
+/// for a component.
+pub async fn resolve_component_id(
+    value: &str,
+    ctl_client: &wasmcloud_control_interface::Client,
+) -> String {
+    resolve_alias(value, ctl_client)
+        .await
+        .and_then(|target| target.component_id().map(ToString::to_string))
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Look up an alias on the lattice, treating lookup failures (e.g. a lattice without a data
+/// bucket) as the alias not being registered
+async fn resolve_alias(
+    value: &str,
+    ctl_client: &wasmcloud_control_interface::Client,
+) -> Option<wasmcloud_control_interface::AliasTarget> {
+    match ctl_client.resolve_alias(value).await {
+        Ok(target) => target,
+        Err(e) => {
+            debug!(alias = value, "failed to resolve alias: {e}");
+            None
+        }
+    }
+}
+
 pub async fn get_all_inventories(
     client: &wasmcloud_control_interface::Client,
 ) -> anyhow::Result<Vec<HostInventory>> {
@@ -343,7 +380,7 @@ pub async fn clone_git_repo(
         {
             repo_url.replace("git+", "")
         } else if repo_url.starts_with("github.com/") {
-            format!("https://{}", &repo_url)
+            format!("https://{}", repo_url)
         } else {
             format!("https://github.com/{}", repo_url.trim_start_matches('/'))
         }
@@ -1,8 +1,8 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::HashMap;
 
 use anyhow::{bail, Context, Result};
 use tokio::time::Duration;
-use wasmcloud_control_interface::{Client as CtlClient, CtlResponse};
+use wasmcloud_control_interface::{Annotations, Client as CtlClient, CtlResponse};
 
 use crate::lib::common::boxed_err_to_anyhow;
 use crate::lib::config::DEFAULT_START_COMPONENT_TIMEOUT_MS;
@@ -78,7 +78,7 @@ pub async fn scale_component(
             component_ref,
             component_id,
             max_instances,
-            annotations.map(BTreeMap::from_iter),
+            annotations.map(Annotations::from_iter),
             config,
         )
         .await
@@ -4,11 +4,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::LazyLock as Lazy;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use async_nats::Client;
 use etcetera::{app_strategy, AppStrategy, AppStrategyArgs};
 use tokio::io::AsyncReadExt;
-use wasmcloud_control_interface::{Client as CtlClient, ClientBuilder as CtlClientBuilder};
+use wasmcloud_control_interface::{
+    Client as CtlClient, ClientBuilder as CtlClientBuilder, NatsConnectOptions,
+};
 
 use crate::lib::context::WashContext;
 
@@ -457,7 +459,7 @@ async fn extract_arg_value(arg: &str) -> Result<String> {
             let mut value = String::new();
             f.read_to_string(&mut value)
                 .await
-                .with_context(|| format!("Failed to read file {}", &arg))?;
+                .with_context(|| format!("Failed to read file {}", arg))?;
             Ok(value)
         }
         Err(_) => Ok(arg.into()),
@@ -475,88 +477,29 @@ pub async fn create_nats_client_from_opts(
     tls_first: bool,
 ) -> Result<Client> {
     let nats_url = format!("{host}:{port}");
-    use async_nats::ConnectOptions;
-
-    let nc = if let Some(jwt_file) = jwt {
+    let mut opts = NatsConnectOptions::new(&nats_url)
+        .name("wash-lib")
+        .tls_first(tls_first);
+    if let Some(jwt_file) = jwt {
         let jwt_contents = extract_arg_value(&jwt_file)
             .await
-            .with_context(|| format!("Failed to extract jwt contents from {}", &jwt_file))?;
-        let kp = std::sync::Arc::new(if let Some(seed) = seed {
-            nkeys::KeyPair::from_seed(
-                &extract_arg_value(&seed)
+            .with_context(|| format!("Failed to extract jwt contents from {}", jwt_file))?;
+        opts = opts.jwt(jwt_contents);
+        if let Some(seed) = seed {
+            opts = opts.seed(
+                extract_arg_value(&seed)
                     .await
-                    .with_context(|| format!("Failed to extract seed value {}", &seed))?,
-            )
-            .with_context(|| format!("Failed to create keypair from seed value {}", &seed))?
-        } else {
-            nkeys::KeyPair::new_user()
-        });
-
-        // You must provide the JWT via a closure
-        let mut opts = async_nats::ConnectOptions::with_jwt(jwt_contents, move |nonce| {
-            let key_pair = kp.clone();
-            async move { key_pair.sign(&nonce).map_err(async_nats::AuthError::new) }
-        });
-
-        if let Some(ca_file) = tls_ca_file {
-            opts = opts.add_root_certificates(ca_file).require_tls(true);
-        }
-
-        if tls_first {
-            opts = opts.tls_first();
+                    .with_context(|| format!("Failed to extract seed value {}", seed))?,
+            );
         }
-
-        opts.name("wash-lib")
-            .connect(&nats_url)
-            .await
-            .with_context(|| {
-                format!(
-                    "Failed to connect to NATS server {}:{} while creating client",
-                    &host, &port
-                )
-            })?
     } else if let Some(credsfile_path) = credsfile {
-        let mut opts = ConnectOptions::with_credentials_file(credsfile_path.clone())
-            .await
-            .with_context(|| {
-                format!(
-                    "Failed to authenticate to NATS with credentials file {:?}",
-                    &credsfile_path
-                )
-            })?;
-
-        if let Some(ca_file) = tls_ca_file {
-            opts = opts.add_root_certificates(ca_file).require_tls(true);
-        }
-
-        if tls_first {
-            opts = opts.tls_first();
-        }
-
-        opts.name("wash-lib")
-            .connect(&nats_url)
-            .await
-            .with_context(|| {
-                format!(
-                    "Failed to connect to NATS {} with credentials file {:?}",
-                    &nats_url, &credsfile_path
-                )
-            })?
-    } else {
-        let mut opts = ConnectOptions::new();
-
-        if let Some(ca_file) = tls_ca_file {
-            opts = opts.add_root_certificates(ca_file).require_tls(true);
-        }
-
-        if tls_first {
-            opts = opts.tls_first();
-        }
-
-        opts.name("wash-lib")
-            .connect(&nats_url)
-            .await
-            .with_context(|| format!("Failed to connect to NATS {}", &nats_url))?
-    };
-    Ok(nc)
+        opts = opts.credsfile(credsfile_path);
+    }
+    if let Some(ca_file) = tls_ca_file {
+        opts = opts.tls_ca_file(ca_file);
+    }
+    opts.connect()
+        .await
+        .map_err(|e| anyhow!(e))
+        .with_context(|| format!("Failed to connect to NATS {}", nats_url))
 }
@@ -117,18 +117,18 @@ impl WkgFetcher {
                     let output_path = tempdir.path().join("unpacked");
                     let http_client = crate::lib::start::get_download_client()?;
                     let req = http_client.get(url.clone()).send().await.with_context(|| {
-                        format!("failed to retrieve WIT output from URL [{}]", &url)
+                        format!("failed to retrieve WIT output from URL [{}]", url)
                     })?;
                     let mut archive = tokio_tar::Archive::new(GzipDecoder::new(
                         tokio_util::io::StreamReader::new(req.bytes_stream().map_err(|e| {
                             std::io::Error::other(format!(
                             "failed to receive byte stream while downloading from URL [{}]: {e}",
-                            &url
+                            url
                         ))
                         })),
                     ));
                     archive.unpack(&output_path).await.with_context(|| {
-                        format!("failed to unpack archive downloaded from URL [{}]", &url)
+                        format!("failed to unpack archive downloaded from URL [{}]", url)
                     })?;
 
                     // Find the first nested directory named 'wit', if present
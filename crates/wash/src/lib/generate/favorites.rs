@@ -77,7 +77,7 @@ pub fn load_favorites(path: Option<&PathBuf>) -> Result<Favorites> {
     // load parameter file, if provided
     let data = if let Some(path) = path {
         fs::read_to_string(path)
-            .with_context(|| format!("reading favorites file {}", &path.display()))?
+            .with_context(|| format!("reading favorites file {}", path.display()))?
     } else {
         DEFAULT_FAVORITES.to_string()
     };
@@ -104,11 +104,7 @@ pub fn pick_favorite(
                     .find(|f| &f.name == *name)
                     .ok_or_else(|| {
                         any_msg(
-                            &format!(
-                                "no {} template with the name '{}'.",
-                                &kind.to_string(),
-                                name
-                            ),
+                            &format!("no {} template with the name '{}'.", kind, name),
                             "",
                         )
                     })?
@@ -135,7 +131,7 @@ pub fn pick_favorite(
 fn prompt_for_template(options: &[TemplateSource], prompt: &str) -> Result<usize> {
     let choices = options
         .iter()
-        .map(|s| format!("{}: {}", &s.name, &s.description))
+        .map(|s| format!("{}: {}", s.name, s.description))
         .collect::<Vec<String>>();
 
     let entry = crate::lib::generate::project_variables::StringEntry {
@@ -26,6 +26,7 @@ pub struct Config {
     pub(crate) placeholders: Vec<TomlMap>,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct ConfigValues {
     pub(crate) values: TomlMap,
@@ -69,6 +70,7 @@ pub struct RenameConfig {
     pub(crate) to: String,
 }
 
+#[allow(dead_code)]
 #[derive(Deserialize, Debug, PartialEq)]
 pub struct TemplateSlotsTable(pub(crate) TomlMap);
 
@@ -80,13 +82,13 @@ impl Config {
         let contents = fs::read_to_string(path).with_context(|| {
             format!(
                 "Error reading template configuration `{}`",
-                &path.as_ref().display()
+                path.as_ref().display()
             )
         })?;
         let config = toml::from_str::<Self>(&contents).with_context(|| {
             format!(
                 "Error parsing template configuration '{}'",
-                &path.as_ref().display()
+                path.as_ref().display()
             )
         })?;
         Ok(config)
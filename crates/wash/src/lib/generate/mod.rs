@@ -203,9 +203,9 @@ async fn make_project(project: Project) -> std::result::Result<PathBuf, anyhow::
     // load optional values file
     let mut values = if let Some(values_file) = &project.values {
         let string = fs::read_to_string(values_file)
-            .with_context(|| format!("reading values file {}", &values_file.display()))?;
+            .with_context(|| format!("reading values file {}", values_file.display()))?;
         let tm = toml::from_str::<TomlMap>(&string)
-            .with_context(|| format!("parsing values file {}", &values_file.display()))?;
+            .with_context(|| format!("parsing values file {}", values_file.display()))?;
         if let Some(toml::Value::Table(values)) = tm.get("values") {
             toml_to_json(values)?
         } else {
@@ -351,7 +351,7 @@ where
                     "Missing Config:",
                     &format!(
                         "did not find {} in {} or any of its parents.",
-                        &search_folder.display(),
+                        search_folder.display(),
                         CONFIG_FILE_NAME
                     ),
                 )
@@ -455,10 +455,10 @@ fn copy_path_template_into_temp(project: &Project) -> Result<TempDir> {
     // args.path is already Some() when we get here
     let path = project.path.as_ref().unwrap();
     if !path.is_dir() {
-        return Err(any_msg(&format!("template path {} not found - please try another template or fix the favorites path", &path.display()),""));
+        return Err(any_msg(&format!("template path {} not found - please try another template or fix the favorites path", path.display()),""));
     }
     copy_dir_all(path, path_clone_dir.path())
-        .with_context(|| format!("copying template project from {}", &path.display()))?;
+        .with_context(|| format!("copying template project from {}", path.display()))?;
     Ok(path_clone_dir)
 }
 
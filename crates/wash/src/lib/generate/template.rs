@@ -152,7 +152,7 @@ pub fn process_template_dir(
                         || {
                             format!(
                                 "error processing template filename '{}'. Project variables: {:?}",
-                                rename_path, &values
+                                rename_path, values
                             )
                         },
                     )?)
@@ -162,10 +162,7 @@ pub fn process_template_dir(
                 let dest_path = project_dir.join(&dest_rel_path);
                 // convert to absolute canonical path for safety check
                 let dest_path = dest_path.absolutize().with_context(|| {
-                    format!(
-                        "invalid file destination path: {}",
-                        &dest_rel_path.display()
-                    )
+                    format!("invalid file destination path: {}", dest_rel_path.display())
                 })?;
                 // Safety check: block attempts to write outside project dir
                 if !dest_path.starts_with(project_dir) {
@@ -195,7 +192,7 @@ pub fn process_template_dir(
                         })?;
                     let rendered = renderer.render_template(&contents, values).map_err(|e| {
                         any_msg(
-                            &format!("rendering template file {}", &src_relative.display()),
+                            &format!("rendering template file {}", src_relative.display()),
                             &e.to_string(),
                         )
                     })?;
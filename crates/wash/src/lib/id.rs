@@ -5,6 +5,7 @@ use std::{convert::AsRef, fmt::Display, ops::Deref, str::FromStr};
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use wasmcloud_control_interface::{HostId, IntoId};
 
 /// An error type describing the types of errors when parsing an ID
 #[derive(Error, Debug, Eq, PartialEq)]
@@ -72,6 +73,18 @@ impl<const PREFIX: char> Id<PREFIX> {
     }
 }
 
+impl IntoId<HostId> for ServerId {
+    fn into_id(self) -> Result<HostId, Box<dyn std::error::Error + Send + Sync>> {
+        HostId::parse(self.0)
+    }
+}
+
+impl IntoId<HostId> for &ServerId {
+    fn into_id(self) -> Result<HostId, Box<dyn std::error::Error + Send + Sync>> {
+        HostId::parse(&self.0)
+    }
+}
+
 /// A wrapper around specific seed types. This is not meant to be a full nkey, but simple validation
 /// for use in serialized/deserialized types
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -44,6 +44,7 @@ pub mod drain;
 pub mod id;
 pub mod keys;
 pub mod registry;
+pub mod secret_scan;
 #[cfg(feature = "nats")]
 pub mod spier;
 #[cfg(feature = "nats")]
@@ -629,6 +629,13 @@ pub struct InterfaceComponentOverride {
     ///
     /// This is only required when there are *more than one* overrides that conflict (i.e. there is no "default")
     pub link_name: Option<String>,
+
+    /// Path to a local provider project (a directory containing a `wasmcloud.toml`) that implements
+    /// the interface, relative to the project under development
+    ///
+    /// `wash dev` builds and watches this project, using the built provider in place of `image_ref`
+    /// and restarting it whenever it is rebuilt.
+    pub project: Option<PathBuf>,
 }
 
 /// String that represents a specification of a WIT interface (normally used when specifying [`InterfaceComponentOverride`]s)
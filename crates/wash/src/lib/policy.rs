@@ -0,0 +1,483 @@
+//! Organization policy packs enforced by wash
+//!
+//! An organization distributes a policy pack, a TOML file stored as an OCI artifact, which is
+//! installed with `wash policy pull`. While a policy is installed, wash refuses commands that
+//! violate it, for example:
+//!
+//! ```toml
+//! name = "acme"
+//!
+//! [registry]
+//! # Refuse `--insecure` and `--insecure-skip-tls-verify` registry connections
+//! deny_insecure = true
+//!
+//! [push]
+//! # Only push components and provider archives with embedded, signed claims
+//! require_signed = true
+//!
+//! # Only deploy to lattices matching the patterns from the given contexts. Contexts that are not
+//! # listed are unrestricted, unless a `*` entry is present.
+//! [deploy.contexts]
+//! prod = ["prod", "prod-*"]
+//! "*" = ["dev-*"]
+//! ```
+//!
+//! A violation can be overridden by setting [`POLICY_OVERRIDE_ENV`] to the reason for the
+//! override, unless the policy disallows overrides. Every override is appended to an audit log in
+//! the wash data directory.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use anyhow::{bail, Context as _, Result};
+use etcetera::AppStrategy as _;
+use oci_client::client::{Client, ClientConfig, ClientProtocol};
+use oci_client::secrets::RegistryAuth;
+use oci_client::Reference;
+use provider_archive::ProviderArchive;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt as _;
+use tracing::warn;
+use wasmcloud_core::tls;
+
+use crate::lib::config::WASH_DIRECTORIES;
+use crate::lib::registry::OciPullOptions;
+
+/// Media type of the layer containing a policy pack in an OCI artifact
+pub const POLICY_MEDIA_TYPE: &str = "application/vnd.wasmcloud.wash.policy.v1+toml";
+/// Name of the file the installed policy pack is stored in, in the wash config directory
+pub const POLICY_FILE: &str = "policy.toml";
+/// Name of the file policy overrides are recorded in, in the wash data directory
+pub const POLICY_AUDIT_FILE: &str = "policy-audit.jsonl";
+/// Environment variable that overrides policy violations when set to the reason for the override
+pub const POLICY_OVERRIDE_ENV: &str = "WASH_POLICY_OVERRIDE";
+
+/// A policy pack distributed by an organization
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Name of the policy, shown in violations and recorded in the audit log
+    pub name: String,
+    /// Rules for connections to OCI registries
+    #[serde(default)]
+    pub registry: RegistryPolicy,
+    /// Rules for pushing artifacts
+    #[serde(default)]
+    pub push: PushPolicy,
+    /// Rules for deploying applications
+    #[serde(default)]
+    pub deploy: DeployPolicy,
+    /// Whether and how violations may be overridden
+    #[serde(default)]
+    pub overrides: OverridePolicy,
+}
+
+/// Rules for connections to OCI registries
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegistryPolicy {
+    /// Refuse insecure (HTTP) registry connections and skipping TLS certificate verification
+    #[serde(default)]
+    pub deny_insecure: bool,
+}
+
+/// Rules for pushing artifacts
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PushPolicy {
+    /// Only push components and provider archives with embedded, signed claims
+    #[serde(default)]
+    pub require_signed: bool,
+}
+
+/// Rules for deploying applications
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeployPolicy {
+    /// Lattice patterns that applications may be deployed to, by name of the wash context used to
+    /// deploy. A pattern ending with `*` matches all lattices starting with the rest of the
+    /// pattern. The patterns of the `*` context apply to contexts that are not listed.
+    #[serde(default)]
+    pub contexts: BTreeMap<String, Vec<String>>,
+}
+
+/// Whether and how violations may be overridden
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OverridePolicy {
+    /// Allow overriding violations with [`POLICY_OVERRIDE_ENV`]
+    #[serde(default = "default_allow_overrides")]
+    pub allow: bool,
+}
+
+impl Default for OverridePolicy {
+    fn default() -> Self {
+        Self {
+            allow: default_allow_overrides(),
+        }
+    }
+}
+
+fn default_allow_overrides() -> bool {
+    true
+}
+
+/// A command that violates a rule of a [`Policy`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyViolation {
+    /// The violated rule, e.g. `registry.deny_insecure`
+    pub rule: &'static str,
+    /// Description of the violation
+    pub message: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (rule `{}`)", self.message, self.rule)
+    }
+}
+
+/// An override of a [`PolicyViolation`], as recorded in the audit log
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PolicyOverride {
+    /// When the violation was overridden, in RFC 3339 format
+    pub timestamp: String,
+    /// Name of the overridden policy
+    pub policy: String,
+    /// The overridden rule
+    pub rule: String,
+    /// Description of the violation
+    pub message: String,
+    /// The wash command that violated the policy
+    pub command: String,
+    /// Reason given for the override
+    pub reason: String,
+    /// Name of the user that overrode the violation, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+}
+
+/// Returns the path of the installed policy pack
+#[must_use]
+pub fn policy_path() -> PathBuf {
+    WASH_DIRECTORIES.in_config_dir(POLICY_FILE)
+}
+
+/// Returns the path of the audit log of policy overrides
+#[must_use]
+pub fn audit_log_path() -> PathBuf {
+    WASH_DIRECTORIES.in_data_dir(POLICY_AUDIT_FILE)
+}
+
+impl Policy {
+    /// Parse a policy pack
+    pub fn parse(policy: &str) -> Result<Self> {
+        let policy: Self = toml::from_str(policy).context("failed to parse policy")?;
+        if policy.name.trim().is_empty() {
+            bail!("policy must have a name");
+        }
+        Ok(policy)
+    }
+
+    /// Load the installed policy pack, if any
+    pub async fn load() -> Result<Option<Self>> {
+        let path = policy_path();
+        match tokio::fs::read_to_string(&path).await {
+            Ok(policy) => Self::parse(&policy)
+                .with_context(|| format!("invalid policy installed at `{}`", path.display()))
+                .map(Some),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => {
+                Err(err).with_context(|| format!("failed to read policy at `{}`", path.display()))
+            }
+        }
+    }
+
+    /// Install the policy pack, replacing the installed one
+    pub async fn install(policy: &str) -> Result<Self> {
+        let parsed = Self::parse(policy)?;
+        let path = WASH_DIRECTORIES.create_in_config_dir(POLICY_FILE)?;
+        tokio::fs::write(&path, policy)
+            .await
+            .with_context(|| format!("failed to write policy to `{}`", path.display()))?;
+        Ok(parsed)
+    }
+
+    /// Remove the installed policy pack, returning whether a policy was installed
+    pub async fn uninstall() -> Result<bool> {
+        let path = policy_path();
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => {
+                Err(err).with_context(|| format!("failed to remove policy at `{}`", path.display()))
+            }
+        }
+    }
+
+    /// Check a connection to an OCI registry
+    #[must_use]
+    pub fn check_registry(
+        &self,
+        insecure: bool,
+        insecure_skip_tls_verify: bool,
+    ) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        if self.registry.deny_insecure && insecure {
+            violations.push(PolicyViolation {
+                rule: "registry.deny_insecure",
+                message: "insecure (HTTP) registry connections are not allowed".to_string(),
+            });
+        }
+        if self.registry.deny_insecure && insecure_skip_tls_verify {
+            violations.push(PolicyViolation {
+                rule: "registry.deny_insecure",
+                message: "skipping TLS certificate verification of registries is not allowed"
+                    .to_string(),
+            });
+        }
+        violations
+    }
+
+    /// Check an artifact that is about to be pushed
+    pub async fn check_push(&self, artifact: &[u8]) -> Vec<PolicyViolation> {
+        if self.push.require_signed && !is_signed(artifact).await {
+            vec![PolicyViolation {
+                rule: "push.require_signed",
+                message: "only artifacts with embedded, signed claims may be pushed".to_string(),
+            }]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Check a deployment to `lattice` using the wash context named `context`
+    #[must_use]
+    pub fn check_deploy(&self, context: &str, lattice: &str) -> Vec<PolicyViolation> {
+        let Some(patterns) = self
+            .deploy
+            .contexts
+            .get(context)
+            .or_else(|| self.deploy.contexts.get("*"))
+        else {
+            return Vec::new();
+        };
+        if patterns
+            .iter()
+            .any(|pattern| matches_pattern(pattern, lattice))
+        {
+            Vec::new()
+        } else {
+            vec![PolicyViolation {
+                rule: "deploy.contexts",
+                message: format!(
+                    "deploying to lattice `{lattice}` from context `{context}` is not allowed, allowed lattices are: {}",
+                    patterns.join(", ")
+                ),
+            }]
+        }
+    }
+
+    /// Fail with the given violations, unless they are overridden with [`POLICY_OVERRIDE_ENV`]
+    ///
+    /// Overrides are recorded in the audit log.
+    pub async fn enforce(&self, command: &str, violations: Vec<PolicyViolation>) -> Result<()> {
+        if violations.is_empty() {
+            return Ok(());
+        }
+        let reason = std::env::var(POLICY_OVERRIDE_ENV)
+            .ok()
+            .filter(|reason| !reason.trim().is_empty());
+        let summary = violations
+            .iter()
+            .map(|violation| format!("  - {violation}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let Some(reason) = reason else {
+            let hint = if self.overrides.allow {
+                format!(
+                    "\nSet {POLICY_OVERRIDE_ENV} to the reason for overriding the policy to proceed anyway, the override is recorded in `{}`",
+                    audit_log_path().display()
+                )
+            } else {
+                String::new()
+            };
+            bail!(
+                "`wash {command}` violates policy `{}`:\n{summary}{hint}",
+                self.name
+            );
+        };
+        if !self.overrides.allow {
+            bail!(
+                "`wash {command}` violates policy `{}`, which does not allow overrides:\n{summary}",
+                self.name
+            );
+        }
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok();
+        let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+        for violation in violations {
+            warn!(policy = %self.name, %violation, %reason, "overriding policy violation");
+            record_override(&PolicyOverride {
+                timestamp: timestamp.clone(),
+                policy: self.name.clone(),
+                rule: violation.rule.to_string(),
+                message: violation.message,
+                command: command.to_string(),
+                reason: reason.clone(),
+                user: user.clone(),
+            })
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Pull a policy pack from an OCI registry
+pub async fn pull_policy(image_ref: &Reference, options: OciPullOptions) -> Result<String> {
+    let client = Client::new(ClientConfig {
+        protocol: if options.insecure {
+            ClientProtocol::Http
+        } else {
+            ClientProtocol::Https
+        },
+        extra_root_certificates: tls::NATIVE_ROOTS_OCI.to_vec(),
+        accept_invalid_certificates: options.insecure_skip_tls_verify,
+        ..Default::default()
+    });
+    let auth = match (options.user, options.password) {
+        (Some(user), Some(password)) => RegistryAuth::Basic(user, password),
+        _ => RegistryAuth::Anonymous,
+    };
+    let image = client
+        .pull(image_ref, &auth, vec![POLICY_MEDIA_TYPE])
+        .await
+        .with_context(|| format!("failed to pull policy `{image_ref}`"))?;
+    let layer = image
+        .layers
+        .into_iter()
+        .next()
+        .context("policy artifact has no layers")?;
+    String::from_utf8(layer.data).context("policy is not valid UTF-8")
+}
+
+/// Read the audit log of policy overrides, oldest first
+pub async fn read_audit_log() -> Result<Vec<PolicyOverride>> {
+    let path = audit_log_path();
+    let log = match tokio::fs::read_to_string(&path).await {
+        Ok(log) => log,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("failed to read audit log `{}`", path.display()))
+        }
+    };
+    log.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).context("invalid entry in policy audit log"))
+        .collect()
+}
+
+/// Append an override to the audit log
+async fn record_override(entry: &PolicyOverride) -> Result<()> {
+    let dir = WASH_DIRECTORIES.data_dir();
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("failed to create directory `{}`", dir.display()))?;
+    let path = audit_log_path();
+    let mut line = serde_json::to_vec(entry).context("failed to serialize policy override")?;
+    line.push(b'\n');
+    let mut log = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await
+        .with_context(|| format!("failed to open audit log `{}`", path.display()))?;
+    log.write_all(&line)
+        .await
+        .with_context(|| format!("failed to record policy override in `{}`", path.display()))
+}
+
+/// Returns whether the artifact is a component or provider archive with embedded, signed claims
+async fn is_signed(artifact: &[u8]) -> bool {
+    if wasmparser::Parser::is_component(artifact) || wasmparser::Parser::is_core_wasm(artifact) {
+        return matches!(wascap::wasm::extract_claims(artifact), Ok(Some(_)));
+    }
+    ProviderArchive::try_load(artifact)
+        .await
+        .is_ok_and(|par| par.claims().is_some())
+}
+
+/// Returns whether `value` matches `pattern`, which matches all values starting with its prefix if
+/// it ends with `*`
+fn matches_pattern(pattern: &str, value: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => pattern == value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+name = "acme"
+
+[registry]
+deny_insecure = true
+
+[push]
+require_signed = true
+
+[deploy.contexts]
+prod = ["prod", "prod-*"]
+"*" = ["dev-*"]
+"#;
+
+    #[test]
+    fn test_parse_policy() {
+        let policy = Policy::parse(POLICY).expect("failed to parse policy");
+        assert_eq!(policy.name, "acme");
+        assert!(policy.registry.deny_insecure);
+        assert!(policy.push.require_signed);
+        assert!(policy.overrides.allow);
+
+        assert!(Policy::parse("name = \"\"").is_err());
+        assert!(Policy::parse("name = \"acme\"\n[registry]\nallow_insecure = true").is_err());
+    }
+
+    #[test]
+    fn test_check_registry() {
+        let policy = Policy::parse(POLICY).expect("failed to parse policy");
+        assert!(policy.check_registry(false, false).is_empty());
+        assert_eq!(policy.check_registry(true, true).len(), 2);
+        assert!(Policy::parse("name = \"open\"")
+            .expect("failed to parse policy")
+            .check_registry(true, true)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_check_deploy() {
+        let policy = Policy::parse(POLICY).expect("failed to parse policy");
+        assert!(policy.check_deploy("prod", "prod").is_empty());
+        assert!(policy.check_deploy("prod", "prod-eu").is_empty());
+        assert_eq!(
+            policy.check_deploy("prod", "dev-1")[0].rule,
+            "deploy.contexts"
+        );
+        assert!(policy.check_deploy("laptop", "dev-1").is_empty());
+        assert!(!policy.check_deploy("laptop", "prod").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_push_requires_signed_artifact() {
+        let policy = Policy::parse(POLICY).expect("failed to parse policy");
+        let violations = policy.check_push(b"not a component").await;
+        assert_eq!(violations[0].rule, "push.require_signed");
+    }
+}
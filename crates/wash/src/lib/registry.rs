@@ -2,6 +2,7 @@
 
 use std::{
     collections::BTreeMap,
+    io::Cursor,
     path::{Path, PathBuf},
 };
 
@@ -16,7 +17,7 @@ use oci_wasm::{ToConfig, WasmConfig, WASM_LAYER_MEDIA_TYPE, WASM_MANIFEST_MEDIA_
 use provider_archive::ProviderArchive;
 use sha2::Digest;
 use tokio::fs::File;
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use wasmcloud_core::tls;
 
 const PROVIDER_ARCHIVE_MEDIA_TYPE: &str = "application/vnd.wasmcloud.provider.archive.layer.v1+par";
@@ -24,6 +25,14 @@ const PROVIDER_ARCHIVE_CONFIG_MEDIA_TYPE: &str =
     "application/vnd.wasmcloud.provider.archive.config";
 const WASM_MEDIA_TYPE: &str = "application/vnd.module.wasm.content.layer.v1+wasm";
 const OCI_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar";
+const OCI_IMAGE_INDEX_MEDIA_TYPE: &str = "application/vnd.oci.image.index.v1+json";
+
+/// Annotations with which container runtimes that run wasm through a dedicated handler (e.g. crun
+/// and youki) recognize wasm images. containerd shims such as runwasi detect the wasm layer itself
+const WASM_RUNTIME_ANNOTATIONS: [(&str, &str); 2] = [
+    ("module.wasm.image/variant", "compat-smart"),
+    ("run.oci.handler", "wasm"),
+];
 
 /// Additional options for pulling an OCI artifact
 #[derive(Default)]
@@ -181,6 +190,31 @@ pub async fn pull_oci_artifact(image_ref: &Reference, options: OciPullOptions) -
         .collect::<Vec<_>>())
 }
 
+/// Resolve the digest of the manifest the given reference currently points to, without pulling
+/// the artifact. Only the authentication and transport settings of `options` are used.
+pub async fn resolve_oci_digest(image_ref: &Reference, options: OciPullOptions) -> Result<String> {
+    let client = Client::new(ClientConfig {
+        protocol: if options.insecure {
+            ClientProtocol::Http
+        } else {
+            ClientProtocol::Https
+        },
+        extra_root_certificates: tls::NATIVE_ROOTS_OCI.to_vec(),
+        accept_invalid_certificates: options.insecure_skip_tls_verify,
+        ..Default::default()
+    });
+
+    let auth = match (options.user, options.password) {
+        (Some(user), Some(password)) => RegistryAuth::Basic(user, password),
+        _ => RegistryAuth::Anonymous,
+    };
+
+    client
+        .fetch_manifest_digest(image_ref, &auth)
+        .await
+        .with_context(|| format!("failed to resolve digest of [{image_ref}]"))
+}
+
 /// Pushes the artifact to the given repo and returns a tuple containing the tag (if one was set) and the digest
 pub async fn push_oci_artifact(
     url: String,
@@ -262,6 +296,96 @@ pub async fn push_oci_artifact(
     Ok((image.tag().map(ToString::to_string), digest))
 }
 
+/// Writes a component as an OCI image layout archive, which can be imported into container runtimes
+/// with e.g. `docker load` or `ctr image import` and run by containerd wasm shims (runwasi), and
+/// returns the digest of the image manifest.
+///
+/// The image is the same single layer wasm artifact that [`push_oci_artifact`] pushes, so it can be
+/// pushed from the container runtime to a registry and run by wasmCloud as well.
+pub async fn write_oci_image_archive(
+    artifact: impl AsRef<Path>,
+    image: &Reference,
+    output: impl AsRef<Path>,
+) -> Result<String> {
+    let artifact_buf = tokio::fs::read(&artifact)
+        .await
+        .with_context(|| format!("failed to read artifact [{}]", artifact.as_ref().display()))?;
+    let (config, layer) = match parse_component(artifact_buf) {
+        Ok(SupportedArtifacts::Wasm(config, layer)) => (config, layer),
+        _ => bail!("only components can be packaged as OCI runtime images"),
+    };
+    let platform: serde_json::Value =
+        serde_json::from_slice(&config.data).context("failed to parse component image config")?;
+    let architecture = platform
+        .get("architecture")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("wasm");
+    let os = platform
+        .get("os")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or("wasip2");
+
+    let annotations = WASM_RUNTIME_ANNOTATIONS
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let mut manifest =
+        OciImageManifest::build(std::slice::from_ref(&layer), &config, Some(annotations));
+    manifest.media_type = Some(WASM_MANIFEST_MEDIA_TYPE.to_string());
+    let manifest_buf = serde_json::to_vec(&manifest)?;
+    let manifest_digest = sha256_digest(&manifest_buf);
+
+    let index = serde_json::json!({
+        "schemaVersion": 2,
+        "mediaType": OCI_IMAGE_INDEX_MEDIA_TYPE,
+        "manifests": [{
+            "mediaType": WASM_MANIFEST_MEDIA_TYPE,
+            "digest": manifest_digest,
+            "size": manifest_buf.len(),
+            "platform": {
+                "architecture": architecture,
+                "os": os,
+            },
+            "annotations": {
+                "io.containerd.image.name": image.whole(),
+                "org.opencontainers.image.ref.name": image.tag().unwrap_or("latest"),
+            },
+        }],
+    });
+
+    let file = File::create(&output)
+        .await
+        .with_context(|| format!("failed to create [{}]", output.as_ref().display()))?;
+    let mut builder = tokio_tar::Builder::new(file);
+    let entries = [
+        (
+            "oci-layout".to_string(),
+            br#"{"imageLayoutVersion":"1.0.0"}"#.to_vec(),
+        ),
+        ("index.json".to_string(), serde_json::to_vec(&index)?),
+        (blob_path(&manifest_digest), manifest_buf),
+        (blob_path(&config.sha256_digest()), config.data),
+        (blob_path(&layer.sha256_digest()), layer.data),
+    ];
+    for (path, data) in entries {
+        let mut header = tokio_tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, Cursor::new(data))
+            .await?;
+    }
+    let mut file = builder.into_inner().await?;
+    file.flush().await?;
+    Ok(manifest_digest)
+}
+
+/// Path of a blob in an OCI image layout
+fn blob_path(digest: &str) -> String {
+    format!("blobs/{}", digest.replacen(':', "/", 1))
+}
+
 /// Helper function to determine artifact type and parse it into a config and layer ready for use in
 /// pushing to OCI
 pub async fn parse_and_validate_artifact(artifact: &[u8]) -> Result<SupportedArtifacts> {
@@ -313,3 +437,79 @@ async fn parse_provider_archive(artifact: &[u8]) -> Result<SupportedArtifacts> {
         Err(e) => bail!("Invalid provider archive: {}", e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use futures::StreamExt as _;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn oci_image_archive_contains_component_image() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let artifact = wat::parse_str("(component)")?;
+        let artifact_path = dir.path().join("component.wasm");
+        tokio::fs::write(&artifact_path, &artifact).await?;
+        let image: Reference = "localhost:5000/test/component:0.1.0".parse()?;
+        let output = dir.path().join("image.tar");
+
+        let digest = write_oci_image_archive(&artifact_path, &image, &output).await?;
+
+        let mut files = HashMap::new();
+        let mut archive = tokio_tar::Archive::new(File::open(&output).await?);
+        let mut entries = archive.entries()?;
+        while let Some(entry) = entries.next().await {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().to_string();
+            let mut buf = Vec::new();
+            entry.read_to_end(&mut buf).await?;
+            files.insert(path, buf);
+        }
+        assert_eq!(files.len(), 5);
+        assert_eq!(
+            files["oci-layout"],
+            br#"{"imageLayoutVersion":"1.0.0"}"#.to_vec()
+        );
+
+        let index: serde_json::Value = serde_json::from_slice(&files["index.json"])?;
+        assert_eq!(index["mediaType"], OCI_IMAGE_INDEX_MEDIA_TYPE);
+        let entry = &index["manifests"][0];
+        assert_eq!(entry["digest"], digest);
+        assert_eq!(entry["mediaType"], WASM_MANIFEST_MEDIA_TYPE);
+        assert_eq!(
+            entry["annotations"]["io.containerd.image.name"],
+            image.whole()
+        );
+        assert_eq!(
+            entry["annotations"]["org.opencontainers.image.ref.name"],
+            "0.1.0"
+        );
+
+        // Every blob is stored under its own digest
+        for (path, data) in &files {
+            if let Some(hex) = path.strip_prefix("blobs/sha256/") {
+                assert_eq!(sha256_digest(data), format!("sha256:{hex}"));
+            }
+        }
+        let manifest_buf = &files[&blob_path(&digest)];
+        assert_eq!(entry["size"], manifest_buf.len());
+        let manifest: OciImageManifest = serde_json::from_slice(manifest_buf)?;
+        assert_eq!(
+            manifest.media_type.as_deref(),
+            Some(WASM_MANIFEST_MEDIA_TYPE)
+        );
+        let annotations = manifest.annotations.unwrap_or_default();
+        for (key, value) in WASM_RUNTIME_ANNOTATIONS {
+            assert_eq!(annotations.get(key).map(String::as_str), Some(value));
+        }
+        assert!(files.contains_key(&blob_path(&manifest.config.digest)));
+        let [layer] = manifest.layers.as_slice() else {
+            panic!("expected a single layer, got {:?}", manifest.layers);
+        };
+        assert_eq!(layer.media_type, WASM_LAYER_MEDIA_TYPE);
+        assert_eq!(files[&blob_path(&layer.digest)], artifact);
+        Ok(())
+    }
+}
@@ -72,7 +72,7 @@ impl Spier {
         let component_stream = nats_client.subscribe(rpc_topic).await?;
 
         let mut subs = futures::future::join_all(linked_component.iter().map(|prov| {
-            let topic = format!("{lattice}.{}.wrpc.>", &prov.id);
+            let topic = format!("{lattice}.{}.wrpc.>", prov.id);
             nats_client.subscribe(topic)
         }))
         .await
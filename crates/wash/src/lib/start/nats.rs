@@ -85,7 +85,7 @@ where
             let re = regex::Regex::new(r"^nats-server:[^\s]*").unwrap();
             if re.replace(&stdout, "").to_string().trim() == version {
                 // nats-server already at correct version, return early
-                eprintln!("✅ Using nats-server version [{}]", &version);
+                eprintln!("✅ Using nats-server version [{}]", version);
                 return Ok(nats_bin_path);
             }
         }
@@ -93,7 +93,7 @@ where
 
     eprintln!(
         "🎣 Downloading new nats-server from {}",
-        &nats_url(os, arch, version)
+        nats_url(os, arch, version)
     );
 
     // Download NATS binary
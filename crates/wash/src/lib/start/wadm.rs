@@ -87,7 +87,7 @@ where
     // Download wadm tarball
     eprintln!(
         "🎣 Downloading new wadm from {}",
-        &wadm_url(os, arch, version)
+        wadm_url(os, arch, version)
     );
 
     let res = download_binary_from_github(&wadm_url(os, arch, version), dir, WADM_BINARY).await;
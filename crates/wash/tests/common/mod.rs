@@ -31,6 +31,7 @@ use wash::lib::start::{
     ensure_nats_server, start_nats_server_with_timeout, NatsConfig, WADM_BINARY, WASMCLOUD_HOST_BIN,
 };
 use wasmcloud_control_interface::Host;
+use wasmcloud_test_util::TestLattice;
 
 #[allow(unused)]
 pub const LOCAL_REGISTRY: &str = "localhost:5001";
@@ -176,6 +177,21 @@ pub async fn start_nats(port: u16, nats_install_dir: impl AsRef<Path>) -> Result
     .await
 }
 
+/// Start a [`TestLattice`] with in-process hosts, backed by a downloaded `nats-server`
+///
+/// The returned directory contains the `nats-server` binary and must outlive the lattice.
+#[allow(unused)]
+pub async fn start_test_lattice() -> Result<(TestLattice, TempDir)> {
+    let nats_dir = tempfile::tempdir()?;
+    let nats_binary = ensure_nats_server(wash::lib::common::NATS_SERVER_VERSION, &nats_dir).await?;
+    let lattice = TestLattice::builder()
+        .nats_bin(nats_binary)
+        .build()
+        .await
+        .context("failed to start test lattice")?;
+    Ok((lattice, nats_dir))
+}
+
 /// Returns an open port on the interface, searching within the range endpoints, inclusive
 pub async fn find_open_port() -> Result<u16> {
     TcpListener::bind(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))
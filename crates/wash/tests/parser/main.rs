@@ -547,8 +547,8 @@ async fn separate_project_paths() {
             build_artifact: Some(build_artifact),
             destination: Some(destination),
             ..
-        }) if build_artifact == PathBuf::from("build/testcomponent_raw.wasm")
-        && destination == PathBuf::from("./build/testcomponent.wasm")
+        }) if build_artifact == *"build/testcomponent_raw.wasm"
+        && destination == *"./build/testcomponent.wasm"
     ));
 
     assert!(matches!(
@@ -557,7 +557,7 @@ async fn separate_project_paths() {
             cargo_path: Some(cargo_path),
             target_path: Some(target_path),
             debug: false,
-        }) if cargo_path == PathBuf::from("../cargo")
-        && target_path == PathBuf::from("./target")
+        }) if cargo_path == *"../cargo"
+        && target_path == *"./target"
     ));
 }
@@ -33,6 +33,10 @@ fn integration_help_subcommand_check() -> Result<()> {
     assert!(output.contains("call"));
     assert!(output.contains("label"));
     assert!(output.contains("config"));
+    assert!(output.contains("repl"));
+    assert!(output.contains("profile"));
+    assert!(output.contains("route"));
+    assert!(output.contains("loadgen"));
     assert!(output.contains("pull"));
     assert!(output.contains("push"));
     assert!(output.contains("reg"));
@@ -41,6 +45,7 @@ fn integration_help_subcommand_check() -> Result<()> {
     assert!(output.contains("drain"));
     assert!(output.contains("keys"));
     assert!(output.contains("claims"));
+    assert!(output.contains("policy"));
     Ok(())
 }
 
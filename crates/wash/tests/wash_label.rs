@@ -1,6 +1,6 @@
 mod common;
 
-use common::TestWashInstance;
+use common::start_test_lattice;
 
 use anyhow::{Context, Result};
 use serial_test::serial;
@@ -10,17 +10,24 @@ use wash::lib::cli::output::LabelHostCommandOutput;
 #[tokio::test]
 #[serial]
 async fn integration_label_host_serial() -> Result<()> {
-    let wash_instance = TestWashInstance::create().await?;
+    let (lattice, _nats_dir) = start_test_lattice().await?;
+    let nats_port = lattice
+        .nats()
+        .url()
+        .port()
+        .context("NATS URL has no port")?;
 
     let output = Command::new(env!("CARGO_BIN_EXE_wash"))
         .args([
             "label",
-            &wash_instance.host_id,
+            &lattice.host().host_id(),
             "key1=value1",
             "--output",
             "json",
             "--ctl-port",
-            &wash_instance.nats_port.to_string(),
+            &nats_port.to_string(),
+            "--lattice",
+            lattice.lattice_name(),
         ])
         .kill_on_drop(true)
         .output()
@@ -35,23 +42,30 @@ async fn integration_label_host_serial() -> Result<()> {
         cmd_output.processed,
         vec![(String::from("key1"), String::from("value1"))],
     );
-    Ok(())
+    lattice.stop().await
 }
 
 #[tokio::test]
 #[serial]
 async fn integration_label_host_no_hostcore_serial() -> Result<()> {
-    let wash_instance = TestWashInstance::create().await?;
+    let (lattice, _nats_dir) = start_test_lattice().await?;
+    let nats_port = lattice
+        .nats()
+        .url()
+        .port()
+        .context("NATS URL has no port")?;
 
     let output = Command::new(env!("CARGO_BIN_EXE_wash"))
         .args([
             "label",
-            &wash_instance.host_id,
+            &lattice.host().host_id(),
             "hostcore.example=test",
             "--output",
             "json",
             "--ctl-port",
-            &wash_instance.nats_port.to_string(),
+            &nats_port.to_string(),
+            "--lattice",
+            lattice.lattice_name(),
         ])
         .kill_on_drop(true)
         .output()
@@ -63,5 +77,5 @@ async fn integration_label_host_no_hostcore_serial() -> Result<()> {
 
     assert!(!cmd_output.deleted);
     assert!(cmd_output.processed.is_empty());
-    Ok(())
+    lattice.stop().await
 }
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 
 use anyhow::{bail, Context as _};
 use common::TestWashInstance;
-use wash::cli::secrets::SecretsCliCommand;
+use wash::cli::secrets::{SecretsBackendOpts, SecretsCliCommand};
 use wash::lib::cli::{CliConnectionOpts, OutputKind};
 use wasmcloud_secrets_types::{SECRET_POLICY_PROPERTIES_TYPE, SECRET_TYPE};
 
@@ -25,6 +25,9 @@ async fn test_secret_put_and_get() -> anyhow::Result<()> {
         field: None,
         version: None,
         policy_properties: vec![],
+        value: None,
+        value_file: None,
+        backend_opts: SecretsBackendOpts::default(),
     };
 
     // Put the config
@@ -96,6 +99,9 @@ async fn test_secret_put_and_get_complex() -> anyhow::Result<()> {
         field: Some("myfield".to_string()),
         version: Some("v1.0.0".to_string()),
         policy_properties: vec!["role=operator".to_string(), "app_id=1234".to_string()],
+        value: None,
+        value_file: None,
+        backend_opts: SecretsBackendOpts::default(),
     };
 
     // Put the config
@@ -1,4 +1,6 @@
 use core::net::SocketAddr;
+use core::num::NonZeroU32;
+use core::str::FromStr;
 
 use std::collections::{HashMap, HashSet};
 use std::env;
@@ -14,14 +16,23 @@ use tokio::time::{timeout, timeout_at};
 use tokio::{select, signal};
 use tracing::{warn, Level as TracingLogLevel};
 use tracing_subscriber::util::SubscriberInitExt as _;
-use url::Url;
-use wasmcloud_core::logging::Level as WasmcloudLogLevel;
-use wasmcloud_core::{OtelConfig, OtelProtocol};
+use wasmcloud_core::host_config::{
+    HostConfig, HostLimits, HostNatsConfig, HostNatsOverrides, HostOciConfig, DEFAULT_LATTICE,
+    DEFAULT_MAX_COMPONENTS, DEFAULT_MAX_COMPONENT_SIZE, DEFAULT_MAX_CORE_INSTANCES_PER_COMPONENT,
+    DEFAULT_MAX_LINEAR_MEMORY, DEFAULT_NATS_HOST, DEFAULT_NATS_PORT,
+};
+use wasmcloud_core::logging::{Level as WasmcloudLogLevel, LogDirectives};
+use wasmcloud_core::{OtelConfig, OtelExporter, OtelProtocol, OtelQueueFullPolicy};
+use wasmcloud_host::event::EventSchemaVersion;
 use wasmcloud_host::nats::builder::NatsHostBuilder;
+use wasmcloud_host::nats::rate_limit::CtlRateLimits;
 use wasmcloud_host::oci::Config as OciConfig;
 use wasmcloud_host::workload_identity::WorkloadIdentityConfig;
 use wasmcloud_host::WasmbusHostConfig;
-use wasmcloud_host::{nats::connect_nats, wasmbus::Features};
+use wasmcloud_host::{
+    nats::{connect_nats_with_health, health::NatsHealth},
+    wasmbus::{Features, WatchdogConfig},
+};
 use wasmcloud_tracing::configure_observability;
 
 #[derive(Debug, Parser)]
@@ -35,17 +46,26 @@ struct Args {
     /// Controls the verbosity of logs from the wasmCloud host
     #[clap(long = "log-level", alias = "structured-log-level", default_value_t = TracingLogLevel::INFO, env = "WASMCLOUD_LOG_LEVEL")]
     pub log_level: TracingLogLevel,
+    /// Per-target log filtering directives applied on top of the log level, in the format of
+    /// `RUST_LOG` (e.g. `wasmcloud_host=debug,async_nats=warn`). Also passed to providers
+    #[clap(
+        long = "log-directives",
+        default_value = "",
+        env = "WASMCLOUD_LOG_DIRECTIVES",
+        value_parser = LogDirectives::from_str
+    )]
+    pub log_directives: LogDirectives,
     /// NATS server host to connect to
     #[clap(
         long = "nats-host",
-        default_value = "127.0.0.1",
+        default_value = DEFAULT_NATS_HOST,
         env = "WASMCLOUD_NATS_HOST"
     )]
     nats_host: String,
     /// NATS server port to connect to
     #[clap(
         long = "nats-port",
-        default_value_t = 4222,
+        default_value_t = DEFAULT_NATS_PORT,
         env = "WASMCLOUD_NATS_PORT"
     )]
     nats_port: u16,
@@ -72,7 +92,7 @@ struct Args {
     #[clap(
         short = 'x',
         long = "lattice",
-        default_value = "default",
+        default_value = DEFAULT_LATTICE,
         env = "WASMCLOUD_LATTICE"
     )]
     lattice: String,
@@ -206,15 +226,15 @@ struct Args {
     #[clap(long = "max-execution-time-ms", default_value = "600000", env = "WASMCLOUD_MAX_EXECUTION_TIME_MS", value_parser = parse_duration_millis)]
     max_execution_time: Duration,
     /// The maximum amount of memory bytes that a component can allocate (default 256 MiB)
-    #[clap(long = "max-linear-memory-bytes", default_value_t = 256 * 1024 * 1024, env = "WASMCLOUD_MAX_LINEAR_MEMORY")]
+    #[clap(long = "max-linear-memory-bytes", default_value_t = DEFAULT_MAX_LINEAR_MEMORY, env = "WASMCLOUD_MAX_LINEAR_MEMORY")]
     max_linear_memory: u32,
     /// The maximum byte size of a component binary that can be loaded (default 50 MiB)
-    #[clap(long = "max-component-size-bytes", default_value_t = 50 * 1024 * 1024, env = "WASMCLOUD_MAX_COMPONENT_SIZE")]
+    #[clap(long = "max-component-size-bytes", default_value_t = DEFAULT_MAX_COMPONENT_SIZE, env = "WASMCLOUD_MAX_COMPONENT_SIZE")]
     max_component_size: u64,
     /// The maximum number of components that can be run simultaneously
     #[clap(
         long = "max-components",
-        default_value_t = 10_000,
+        default_value_t = DEFAULT_MAX_COMPONENTS,
         env = "WASMCLOUD_MAX_COMPONENTS"
     )]
     max_components: u32,
@@ -222,7 +242,7 @@ struct Args {
     /// The maximum number of core instances per component
     #[clap(
         long = "max-core-instances-per-component",
-        default_value_t = 30,
+        default_value_t = DEFAULT_MAX_CORE_INSTANCES_PER_COMPONENT,
         env = "WASMCLOUD_MAX_CORE_INSTANCES_PER_COMPONENT"
     )]
     max_core_instances_per_component: u32,
@@ -344,6 +364,177 @@ struct Args {
     )]
     observability_protocol: Option<OtelProtocol>,
 
+    /// Overrides the protocol used for exporting traces. Defaults to the observability protocol.
+    #[clap(
+        long = "override-traces-protocol",
+        env = "WASMCLOUD_OBSERVABILITY_TRACES_PROTOCOL",
+        hide = true
+    )]
+    traces_protocol: Option<OtelProtocol>,
+
+    /// Overrides the protocol used for exporting metrics. Defaults to the observability protocol.
+    #[clap(
+        long = "override-metrics-protocol",
+        env = "WASMCLOUD_OBSERVABILITY_METRICS_PROTOCOL",
+        hide = true
+    )]
+    metrics_protocol: Option<OtelProtocol>,
+
+    /// Overrides the protocol used for exporting logs. Defaults to the observability protocol.
+    #[clap(
+        long = "override-logs-protocol",
+        env = "WASMCLOUD_OBSERVABILITY_LOGS_PROTOCOL",
+        hide = true
+    )]
+    logs_protocol: Option<OtelProtocol>,
+
+    /// Determines whether the observability configuration is forwarded to the capability providers
+    /// started by the host. This defaults to 'true'. Overrides for individual providers can be set
+    /// in the `provider_overrides` of the observability config file
+    #[clap(
+        long = "observability-forward-to-providers",
+        env = "WASMCLOUD_OBSERVABILITY_FORWARD_TO_PROVIDERS"
+    )]
+    observability_forward_to_providers: Option<bool>,
+
+    /// Path to a JSON file with the OpenTelemetry configuration of the host, either at the top level
+    /// or in an `otel` section. Standard `OTEL_*` environment variables and command line flags
+    /// take precedence over the file
+    #[clap(long = "observability-config", env = "WASMCLOUD_OBSERVABILITY_CONFIG")]
+    observability_config: Option<PathBuf>,
+
+    /// Configures where the enabled telemetry is exported to: 'otlp' to export it to a collector,
+    /// 'stdout' or 'file:<path>' to write it as newline-delimited OTLP-JSON. This defaults to 'otlp'.
+    #[clap(
+        long = "observability-exporter",
+        env = "WASMCLOUD_OBSERVABILITY_EXPORTER"
+    )]
+    observability_exporter: Option<OtelExporter>,
+
+    /// Headers to include when exporting telemetry, as a repeatable set of `key=value` pairs, e.g.
+    /// API keys of hosted observability backends. Values prefixed with `env:` are read from the
+    /// named environment variable and values prefixed with `file:` are read from the given file
+    #[clap(
+        long = "observability-header",
+        env = "WASMCLOUD_OBSERVABILITY_HEADERS",
+        value_delimiter = ',',
+        value_parser = parse_key_value
+    )]
+    observability_headers: Vec<(String, String)>,
+
+    /// Headers to include when exporting traces, overriding the observability headers.
+    #[clap(
+        long = "override-traces-header",
+        env = "WASMCLOUD_OBSERVABILITY_TRACES_HEADERS",
+        value_delimiter = ',',
+        value_parser = parse_key_value,
+        hide = true
+    )]
+    traces_headers: Vec<(String, String)>,
+
+    /// Headers to include when exporting metrics, overriding the observability headers.
+    #[clap(
+        long = "override-metrics-header",
+        env = "WASMCLOUD_OBSERVABILITY_METRICS_HEADERS",
+        value_delimiter = ',',
+        value_parser = parse_key_value,
+        hide = true
+    )]
+    metrics_headers: Vec<(String, String)>,
+
+    /// Headers to include when exporting logs, overriding the observability headers.
+    #[clap(
+        long = "override-logs-header",
+        env = "WASMCLOUD_OBSERVABILITY_LOGS_HEADERS",
+        value_delimiter = ',',
+        value_parser = parse_key_value,
+        hide = true
+    )]
+    logs_headers: Vec<(String, String)>,
+
+    /// Overrides the `service.name` resource attribute of the telemetry exported by the host.
+    /// Defaults to `wasmcloud-host`
+    #[clap(
+        long = "observability-service-name",
+        env = "WASMCLOUD_OBSERVABILITY_SERVICE_NAME",
+        hide = true
+    )]
+    observability_service_name: Option<String>,
+
+    /// The `service.namespace` resource attribute of the telemetry exported by the host and its
+    /// providers, e.g. to distinguish the hosts of different clusters
+    #[clap(
+        long = "observability-service-namespace",
+        env = "WASMCLOUD_OBSERVABILITY_SERVICE_NAMESPACE"
+    )]
+    observability_service_namespace: Option<String>,
+
+    /// Resource attributes to attach to the telemetry exported by the host and its providers, as a
+    /// repeatable set of `key=value` pairs
+    #[clap(
+        long = "observability-resource-attribute",
+        env = "WASMCLOUD_OBSERVABILITY_RESOURCE_ATTRIBUTES",
+        value_delimiter = ',',
+        value_parser = parse_key_value
+    )]
+    observability_resource_attributes: Vec<(String, String)>,
+
+    /// The maximum number of events recorded per span exported by the host and its providers.
+    /// Events beyond the limit are dropped
+    #[clap(
+        long = "observability-max-events-per-span",
+        env = "WASMCLOUD_OBSERVABILITY_MAX_EVENTS_PER_SPAN"
+    )]
+    observability_max_events_per_span: Option<u32>,
+
+    /// The maximum length in bytes of string attribute values of spans exported by the host and
+    /// its providers. Longer values are truncated
+    #[clap(
+        long = "observability-max-attribute-length",
+        env = "WASMCLOUD_OBSERVABILITY_MAX_ATTRIBUTE_LENGTH"
+    )]
+    observability_max_attribute_length: Option<usize>,
+
+    /// Keys of span attributes whose values are redacted before export, e.g. `authorization`.
+    /// Keys match case-insensitively, in full or as the last `.`-separated segment of a key
+    #[clap(
+        long = "observability-redact-attribute",
+        env = "WASMCLOUD_OBSERVABILITY_REDACTED_ATTRIBUTES",
+        value_delimiter = ','
+    )]
+    observability_redacted_attributes: Vec<String>,
+
+    /// The maximum number of times a failed export of telemetry to a collector is retried, with
+    /// exponential backoff. This defaults to 0, i.e. telemetry is dropped when an export fails
+    #[clap(
+        long = "observability-export-max-retries",
+        env = "WASMCLOUD_OBSERVABILITY_EXPORT_MAX_RETRIES"
+    )]
+    observability_export_max_retries: Option<u32>,
+
+    /// The delay in milliseconds before the first retry of a failed export, doubling with every
+    /// further retry. This defaults to 1000
+    #[clap(
+        long = "observability-export-initial-backoff-ms",
+        env = "WASMCLOUD_OBSERVABILITY_EXPORT_INITIAL_BACKOFF_MS"
+    )]
+    observability_export_initial_backoff_ms: Option<u64>,
+
+    /// The maximum delay in milliseconds between retries of a failed export. This defaults to 30000
+    #[clap(
+        long = "observability-export-max-backoff-ms",
+        env = "WASMCLOUD_OBSERVABILITY_EXPORT_MAX_BACKOFF_MS"
+    )]
+    observability_export_max_backoff_ms: Option<u64>,
+
+    /// Determines what happens to ended spans while the span export queue is full: 'drop' to drop
+    /// them, or 'block' to wait for room at the expense of latency. This defaults to 'drop'
+    #[clap(
+        long = "observability-queue-full-policy",
+        env = "WASMCLOUD_OBSERVABILITY_QUEUE_FULL_POLICY"
+    )]
+    observability_queue_full_policy: Option<OtelQueueFullPolicy>,
+
     /// Path to generate flame graph at
     #[clap(long = "flame-graph", env = "WASMCLOUD_FLAME_GRAPH")]
     flame_graph: Option<String>,
@@ -394,6 +585,86 @@ struct Args {
     )]
     /// Determines whether capability provider auctions should be enabled (defaults to true)
     enable_provider_auction: Option<bool>,
+
+    /// Run the host in read-only mode, refusing all control interface commands that would mutate
+    /// the host or the lattice and only answering queries. Changes to links and named configs
+    /// made through other hosts of the lattice are still applied to running workloads
+    #[clap(long = "read-only", env = "WASMCLOUD_READ_ONLY")]
+    read_only: bool,
+
+    /// Limit the number of control interface requests per second accepted from a single sender,
+    /// e.g. a controller connection. Requests over the limit are refused with a `RATE_LIMITED`
+    /// response. Requests are not rate limited by default
+    #[clap(long = "ctl-rate-limit", env = "WASMCLOUD_CTL_RATE_LIMIT")]
+    ctl_rate_limit: Option<NonZeroU32>,
+
+    /// Number of control interface requests a sender may send at once after having been idle.
+    /// Defaults to the value of --ctl-rate-limit
+    #[clap(
+        long = "ctl-rate-limit-burst",
+        env = "WASMCLOUD_CTL_RATE_LIMIT_BURST",
+        requires = "ctl_rate_limit"
+    )]
+    ctl_rate_limit_burst: Option<NonZeroU32>,
+
+    /// Path to a JSON manifest of components and providers to start when the host starts
+    #[clap(long = "workload-manifest", env = "WASMCLOUD_WORKLOAD_MANIFEST")]
+    workload_manifest: Option<PathBuf>,
+
+    /// A comma-separated list of OCI references of components and providers to prefetch into the
+    /// artifact cache when the host starts
+    #[clap(
+        long = "prefetch-image",
+        env = "WASMCLOUD_PREFETCH_IMAGES",
+        value_delimiter = ','
+    )]
+    prefetch_images: Vec<String>,
+
+    /// Prefetch the prefetched images again every this many seconds, which pulls images whose
+    /// reference now points to a different digest and restores images evicted from the artifact
+    /// cache. Images are only prefetched once if not set
+    #[clap(
+        long = "prefetch-refresh-interval-seconds",
+        env = "WASMCLOUD_PREFETCH_REFRESH_INTERVAL",
+        value_parser = parse_duration_secs
+    )]
+    prefetch_refresh_interval: Option<Duration>,
+
+    /// Path to a directory in which the host keeps local state, such as provider logs, across
+    /// restarts. The directory is created if it does not exist and checked for integrity on startup
+    #[clap(long = "data-dir", env = "WASMCLOUD_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Enable the watchdog, which recovers host subsystems making no progress for this many
+    /// seconds, e.g. a stuck lattice data watch or components that stopped yielding. Subsystems
+    /// that do not recover make the host exit with code 71 (event publisher), 72 (data watch) or
+    /// 73 (runtime)
+    #[clap(
+        long = "watchdog-stall-timeout-seconds",
+        env = "WASMCLOUD_WATCHDOG_STALL_TIMEOUT",
+        value_parser = parse_duration_secs
+    )]
+    watchdog_stall_timeout: Option<Duration>,
+
+    /// Keep running when a subsystem does not recover from a stall instead of exiting, only
+    /// reporting the stall
+    #[clap(
+        long = "watchdog-no-exit",
+        env = "WASMCLOUD_WATCHDOG_NO_EXIT",
+        requires = "watchdog_stall_timeout"
+    )]
+    watchdog_no_exit: bool,
+
+    /// A comma-separated list of schema versions to publish lattice events in, e.g. `v1,v2`.
+    /// Publishing several versions allows event consumers to migrate to a new event schema
+    /// without upgrading every host and consumer at once. Defaults to `v1`
+    #[clap(
+        long = "event-schema-versions",
+        env = "WASMCLOUD_EVENT_SCHEMA_VERSIONS",
+        value_delimiter = ',',
+        default_value = "v1"
+    )]
+    event_schema_versions: Vec<EventSchemaVersion>,
 }
 
 const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
@@ -414,20 +685,57 @@ async fn main() -> anyhow::Result<()> {
     }
 
     let trace_level = WasmcloudLogLevel::from(args.trace_level);
-    let otel_config = OtelConfig {
-        enable_observability: args.enable_observability,
-        enable_traces: args.enable_traces,
-        enable_metrics: args.enable_metrics,
-        enable_logs: args.enable_logs,
-        observability_endpoint: args.observability_endpoint,
-        traces_endpoint: args.traces_endpoint,
-        metrics_endpoint: args.metrics_endpoint,
-        logs_endpoint: args.logs_endpoint,
-        protocol: args.observability_protocol.unwrap_or_default(),
-        additional_ca_paths: args.tls_ca_paths.clone().unwrap_or_default(),
-        trace_level,
-        ..Default::default()
-    };
+    let otel_config = OtelConfig::from_env_and_file(args.observability_config.as_deref())
+        .context("failed to load observability configuration")?
+        .merge(OtelConfig {
+            enable_observability: args.enable_observability,
+            enable_traces: args.enable_traces,
+            enable_metrics: args.enable_metrics,
+            enable_logs: args.enable_logs,
+            observability_endpoint: args.observability_endpoint,
+            traces_endpoint: args.traces_endpoint,
+            metrics_endpoint: args.metrics_endpoint,
+            logs_endpoint: args.logs_endpoint,
+            protocol: args.observability_protocol.unwrap_or_default(),
+            traces_protocol: args.traces_protocol,
+            metrics_protocol: args.metrics_protocol,
+            logs_protocol: args.logs_protocol,
+            exporter: args.observability_exporter.unwrap_or_default(),
+            headers: args
+                .observability_headers
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+            traces_headers: args
+                .traces_headers
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+            metrics_headers: args
+                .metrics_headers
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+            logs_headers: args
+                .logs_headers
+                .into_iter()
+                .map(|(name, value)| (name, value.into()))
+                .collect(),
+            service_name: args.observability_service_name,
+            service_namespace: args.observability_service_namespace,
+            resource_attributes: args.observability_resource_attributes.into_iter().collect(),
+            additional_ca_paths: args.tls_ca_paths.clone().unwrap_or_default(),
+            trace_level,
+            max_events_per_span: args.observability_max_events_per_span,
+            max_attribute_length: args.observability_max_attribute_length,
+            redacted_attributes: args.observability_redacted_attributes,
+            forward_to_providers: args.observability_forward_to_providers,
+            queue_full_policy: args.observability_queue_full_policy,
+            export_max_retries: args.observability_export_max_retries,
+            export_initial_backoff_ms: args.observability_export_initial_backoff_ms,
+            export_max_backoff_ms: args.observability_export_max_backoff_ms,
+            ..Default::default()
+        });
     let log_level = WasmcloudLogLevel::from(args.log_level);
 
     let _guard = match configure_observability(
@@ -436,6 +744,7 @@ async fn main() -> anyhow::Result<()> {
         args.enable_structured_logging,
         args.flame_graph,
         Some(&log_level),
+        &args.log_directives,
         Some(&otel_config.trace_level),
     ) {
         Ok((dispatch, guard)) => {
@@ -449,7 +758,9 @@ async fn main() -> anyhow::Result<()> {
                 traces_endpoint = %otel_config.traces_endpoint(),
                 metrics_endpoint = %otel_config.metrics_endpoint(),
                 logs_endpoint = %otel_config.logs_endpoint(),
-                protocol = ?otel_config.protocol,
+                traces_protocol = ?otel_config.traces_protocol(),
+                metrics_protocol = ?otel_config.metrics_protocol(),
+                logs_protocol = ?otel_config.logs_protocol(),
                 "combined OpenTelemetry configuration (cli > env > defaults)"
             );
 
@@ -461,18 +772,78 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
-    let ctl_nats_url = Url::parse(&format!(
-        "nats://{}:{}",
-        args.ctl_host.unwrap_or_else(|| args.nats_host.clone()),
-        args.ctl_port.unwrap_or(args.nats_port)
-    ))
-    .context("failed to construct a valid `ctl_nats_url` using `ctl-host` and `ctl-port`")?;
-    let rpc_nats_url = Url::parse(&format!(
-        "nats://{}:{}",
-        args.rpc_host.unwrap_or_else(|| args.nats_host.clone()),
-        args.rpc_port.unwrap_or(args.nats_port)
-    ))
-    .context("failed to construct a valid `rpc_nats_url` using `rpc-host` and `rpc-port`")?;
+    let mut labels = args
+        .label
+        .unwrap_or_default()
+        .iter()
+        .map(|labelpair| parse_label(labelpair))
+        .collect::<anyhow::Result<HashMap<String, String>, anyhow::Error>>()
+        .context("failed to parse labels")?;
+    let labels_from_args: HashSet<String> = labels.keys().cloned().collect();
+    labels.extend(env::vars().filter_map(|(key, value)| {
+        let key = if key.starts_with("WASMCLOUD_LABEL_") {
+            key.strip_prefix("WASMCLOUD_LABEL_")?.to_string()
+        } else {
+            return None;
+        };
+        if labels_from_args.contains(&key) {
+            warn!(
+                ?key,
+                "label provided via args will override label set via environment variable"
+            );
+            return None;
+        }
+        Some((key, value))
+    }));
+
+    let host_config = HostConfig {
+        lattice: args.lattice.clone(),
+        labels: labels.clone(),
+        nats: HostNatsConfig {
+            host: args.nats_host.clone(),
+            port: args.nats_port,
+            jwt: args.nats_jwt.clone(),
+            seed: args.nats_seed.clone().map(Into::into),
+            creds: args.nats_creds.clone(),
+            js_domain: args.js_domain.clone(),
+        },
+        ctl: HostNatsOverrides {
+            host: args.ctl_host.clone(),
+            port: args.ctl_port,
+            jwt: args.ctl_jwt.clone(),
+            seed: args.ctl_seed.clone().map(Into::into),
+            creds: args.ctl_creds.clone(),
+            tls: args.ctl_tls,
+        },
+        ctl_topic_prefix: args.ctl_topic_prefix.clone(),
+        rpc: HostNatsOverrides {
+            host: args.rpc_host.clone(),
+            port: args.rpc_port,
+            jwt: args.rpc_jwt.clone(),
+            seed: args.rpc_seed.clone().map(Into::into),
+            creds: args.rpc_creds.clone(),
+            tls: args.rpc_tls,
+        },
+        rpc_timeout: args.rpc_timeout_ms.into(),
+        oci: HostOciConfig {
+            allow_latest: args.allow_latest,
+            allowed_insecure: args.allowed_insecure.clone(),
+            registry: args.oci_registry.clone(),
+            user: args.oci_user.clone(),
+            password: args.oci_password.clone().map(Into::into),
+        },
+        otel: otel_config.clone(),
+        limits: HostLimits {
+            max_execution_time: args.max_execution_time.into(),
+            max_linear_memory: u64::from(args.max_linear_memory).into(),
+            max_component_size: args.max_component_size.into(),
+            max_components: args.max_components,
+            max_core_instances_per_component: args.max_core_instances_per_component,
+        },
+    };
+    host_config.validate()?;
+    let ctl_nats_url = host_config.ctl_nats_url()?;
+    let rpc_nats_url = host_config.rpc_nats_url()?;
 
     let host_key = args
         .host_seed
@@ -501,29 +872,6 @@ async fn main() -> anyhow::Result<()> {
         oci_password: args.oci_password,
     };
 
-    let mut labels = args
-        .label
-        .unwrap_or_default()
-        .iter()
-        .map(|labelpair| parse_label(labelpair))
-        .collect::<anyhow::Result<HashMap<String, String>, anyhow::Error>>()
-        .context("failed to parse labels")?;
-    let labels_from_args: HashSet<String> = labels.keys().cloned().collect();
-    labels.extend(env::vars().filter_map(|(key, value)| {
-        let key = if key.starts_with("WASMCLOUD_LABEL_") {
-            key.strip_prefix("WASMCLOUD_LABEL_")?.to_string()
-        } else {
-            return None;
-        };
-        if labels_from_args.contains(&key) {
-            warn!(
-                ?key,
-                "label provided via args will override label set via environment variable"
-            );
-            return None;
-        }
-        Some((key, value))
-    }));
     if let Some(secrets_topic) = args.secrets_topic_prefix.as_deref() {
         anyhow::ensure!(
             validate_nats_subject(secrets_topic).is_ok(),
@@ -538,13 +886,15 @@ async fn main() -> anyhow::Result<()> {
     } else {
         None
     };
-    let ctl_nats = connect_nats(
+    let nats_health = Arc::new(NatsHealth::default());
+    let ctl_nats = connect_nats_with_health(
         ctl_nats_url.as_str(),
         ctl_jwt.or_else(|| nats_jwt.clone()).as_ref(),
         ctl_key.or_else(|| nats_key.clone()),
         args.ctl_tls,
         None,
         workload_identity_config.clone(),
+        Arc::clone(&nats_health),
     )
     .await
     .context("failed to establish NATS control connection")?;
@@ -561,7 +911,14 @@ async fn main() -> anyhow::Result<()> {
         args.enable_provider_auction.unwrap_or(true),
     )
     .await?
-    .with_event_publisher(host_key.public_key());
+    .with_event_publisher(host_key.public_key())
+    .with_event_schema_versions(args.event_schema_versions)
+    .with_nats_health(nats_health)
+    .with_ctl_rate_limits(args.ctl_rate_limit.map(|rate| {
+        let limits = CtlRateLimits::per_second(rate);
+        args.ctl_rate_limit_burst
+            .map_or(limits, |burst| limits.with_burst(burst))
+    }));
 
     let builder = if let Some(policy_topic) = args.policy_topic.as_deref() {
         anyhow::ensure!(
@@ -607,6 +964,7 @@ async fn main() -> anyhow::Result<()> {
             rpc_tls: args.rpc_tls,
             allow_file_load: args.allow_file_load,
             log_level,
+            log_directives: args.log_directives,
             enable_structured_logging: args.enable_structured_logging,
             otel_config,
             version: env!("CARGO_PKG_VERSION").to_string(),
@@ -620,6 +978,18 @@ async fn main() -> anyhow::Result<()> {
             http_admin: args.http_admin,
             enable_component_auction: args.enable_component_auction.unwrap_or(true),
             enable_provider_auction: args.enable_provider_auction.unwrap_or(true),
+            read_only: args.read_only,
+            workload_manifest: args.workload_manifest,
+            prefetch_images: args.prefetch_images,
+            prefetch_refresh_interval: args.prefetch_refresh_interval,
+            data_dir: args.data_dir,
+            watchdog: args
+                .watchdog_stall_timeout
+                .map(|stall_timeout| WatchdogConfig {
+                    stall_timeout,
+                    exit_on_stall: !args.watchdog_no_exit,
+                    ..Default::default()
+                }),
         })
         .await?;
     let (host, shutdown) = host_builder
@@ -697,6 +1067,13 @@ fn parse_label(labelpair: &str) -> anyhow::Result<(String, String)> {
     }
 }
 
+fn parse_key_value(pair: &str) -> anyhow::Result<(String, String)> {
+    match pair.split_once('=') {
+        Some((k, v)) if !k.is_empty() => Ok((k.to_string(), v.to_string())),
+        _ => bail!("invalid format `{pair}`. Expected `key=value`"),
+    }
+}
+
 static JWT_RE: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"-----BEGIN NATS USER JWT-----\n(?<jwt>.*)\n------END NATS USER JWT------").unwrap()
 });
@@ -115,6 +115,65 @@ async fn config_updates() -> Result<()> {
     Ok(())
 }
 
+/// Ensure that a config last written before the requested revision of the config bucket is served
+/// right away, since the revision of its entry is older than the bucket's
+#[instrument(skip_all, ret)]
+#[tokio::test(flavor = "multi_thread")]
+async fn config_get_at_revision() -> Result<()> {
+    let (nats_server, nats_url, nats_client) = start_nats(None, true)
+        .await
+        .map(|res| (res.0, res.1, res.2.unwrap()))
+        .context("failed to start backing services")?;
+    let ctl_client = wasmcloud_control_interface::ClientBuilder::new(nats_client)
+        .lattice("config-revision".to_string())
+        .build();
+    let _host = WasmCloudTestHost::start(&nats_url, "config-revision")
+        .await
+        .context("failed to start test host")?;
+
+    ctl_client
+        .put_config("stale", [("star".to_string(), "wars".to_string())])
+        .await
+        .map_err(|e| anyhow!(e))?;
+    ctl_client
+        .put_config("fresh", [("star".to_string(), "trek".to_string())])
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let fresh = ctl_client
+        .get_config_at("fresh", 0)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let revision = fresh.revision().context("config should have a revision")?;
+
+    let start = tokio::time::Instant::now();
+    let stale = ctl_client
+        .get_config_at("stale", revision)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    ensure!(
+        start.elapsed() < Duration::from_millis(500),
+        "read of a key written before the requested revision should not wait"
+    );
+    ensure!(stale.revision().is_some_and(|r| r >= revision));
+    assert_eq!(
+        stale.into_data(),
+        Some(HashMap::from([("star".to_string(), "wars".to_string())]))
+    );
+
+    // Revisions the bucket has not reached yet are rejected once the host stops waiting
+    let unreached = ctl_client
+        .get_config_at("stale", revision + 100)
+        .await
+        .map_err(|e| anyhow!(e))?;
+    ensure!(
+        !unreached.succeeded(),
+        "unreached revision should be rejected"
+    );
+
+    let _ = nats_server.stop().await;
+    Ok(())
+}
+
 #[instrument(skip_all, ret)]
 async fn put_config(
     store: &jetstream::kv::Store,
@@ -0,0 +1,89 @@
+#![cfg(feature = "wasmcloud")]
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use anyhow::{anyhow, ensure, Context, Result};
+use wasmcloud_control_interface::LatticeEvent;
+use wasmcloud_test_util::TestLattice;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn decommission_removes_host_metadata() -> Result<()> {
+    let lattice = TestLattice::builder()
+        .hosts(2)
+        .host_label("zone", "test")
+        .build()
+        .await
+        .context("failed to start test lattice")?;
+    let ctl_client = lattice.ctl_client();
+    let host_id = lattice.hosts()[0].host_id();
+
+    ctl_client
+        .put_host_config(
+            &host_id,
+            "cache",
+            HashMap::from([("size".to_string(), "1024".to_string())]),
+        )
+        .await
+        .map_err(|e| anyhow!(e))?;
+
+    let mut events = ctl_client
+        .events_receiver(vec![
+            "labels_changed".to_string(),
+            "host_decommissioned".to_string(),
+        ])
+        .await
+        .map_err(|e| anyhow!(e))?;
+    ctl_client
+        .decommission_host(&host_id, None)
+        .await
+        .map_err(|e| anyhow!(e))?;
+
+    // The labels of the host are removed before it reports being decommissioned
+    let mut labels = None;
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while let Some(event) = events.recv().await {
+            let Ok(event) = LatticeEvent::try_from(event) else {
+                continue;
+            };
+            if event.source() != host_id {
+                continue;
+            }
+            match event.event_type() {
+                "labels_changed" => {
+                    let changed = event.field("labels").cloned().unwrap_or_default();
+                    labels = Some(serde_json::from_value::<BTreeMap<String, String>>(changed)?);
+                }
+                "host_decommissioned" => return anyhow::Ok(()),
+                _ => {}
+            }
+        }
+        Err(anyhow!("event stream ended"))
+    })
+    .await
+    .context("host was not decommissioned in time")??;
+    ensure!(labels.is_some_and(|labels| labels.is_empty()));
+
+    let config = ctl_client
+        .get_host_config(&host_id, "cache")
+        .await
+        .map_err(|e| anyhow!(e))?;
+    ensure!(config.data().is_none(), "host config should be removed");
+
+    let acks = ctl_client
+        .perform_component_auction(
+            "ghcr.io/wasmcloud/components/http-hello-world-rust:0.1.0",
+            "echo",
+            BTreeMap::new(),
+        )
+        .await
+        .map_err(|e| anyhow!(e))?;
+    ensure!(
+        acks.iter()
+            .filter_map(|ack| ack.data())
+            .all(|ack| ack.host_id() != host_id),
+        "decommissioned host should decline auctions"
+    );
+
+    lattice.stop().await.context("failed to stop test lattice")
+}
@@ -22,7 +22,7 @@ use wasmcloud_test_util::lattice::link::assert_remove_link;
 use wasmcloud_test_util::provider::{assert_start_provider, StartProviderArgs};
 use wasmcloud_test_util::{
     component::assert_scale_component, host::WasmCloudTestHost,
-    lattice::link::assert_advertise_link,
+    lattice::link::assert_advertise_link, TestLattice,
 };
 
 use test_components::RUST_HTTP_HELLO_WORLD;
@@ -49,19 +49,13 @@ async fn link_deletes() -> anyhow::Result<()> {
         )
         .try_init();
 
-    let (nats_server, nats_url, nats_client) = start_nats(None, true)
+    let lattice = TestLattice::builder()
+        .lattice(LATTICE)
+        .build()
         .await
-        .map(|res| (res.0, res.1, res.2.unwrap()))
-        .context("failed to start NATS")?;
-
-    // Build client for interacting with the lattice
-    let ctl_client = wasmcloud_control_interface::ClientBuilder::new(nats_client.clone())
-        .lattice(LATTICE.to_string())
-        .build();
-    // Build the host
-    let _host = WasmCloudTestHost::start(&nats_url, LATTICE)
-        .await
-        .context("failed to start test host")?;
+        .context("failed to start test lattice")?;
+    let nats_client = lattice.nats().client();
+    let ctl_client = lattice.ctl_client().clone();
 
     // Set up a few IDs and interfaces for enumerating tests
     let component_one = "foo";
@@ -175,7 +169,10 @@ async fn link_deletes() -> anyhow::Result<()> {
     let provider_deleted_event = provider_link_deleted_messages.next().await;
     assert!(provider_deleted_event.is_some());
 
-    nats_server.stop().await.context("failed to stop NATS")?;
+    lattice
+        .stop()
+        .await
+        .context("failed to stop test lattice")?;
     Ok(())
 }
 
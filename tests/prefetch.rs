@@ -0,0 +1,68 @@
+#![cfg(feature = "wasmcloud")]
+
+use std::time::Duration;
+
+use anyhow::{anyhow, ensure, Context, Result};
+use wasmcloud_control_interface::{Client, DataCategory, PrefetchStatus, PrefetchedImage};
+use wasmcloud_test_util::TestLattice;
+
+const IMAGE_REF: &str = "file:///nonexistent/prefetch.wasm";
+
+/// Wait until the host reports a settled prefetch status for [`IMAGE_REF`]
+async fn await_prefetched_image(ctl_client: &Client, host_id: &str) -> Result<PrefetchedImage> {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let inventory = ctl_client
+                .get_host_inventory(host_id)
+                .await
+                .map_err(|e| anyhow!(e))?
+                .into_data()
+                .context("inventory should be returned")?;
+            if let Some(image) = inventory
+                .prefetched_images()
+                .iter()
+                .find(|image| image.image_ref() == IMAGE_REF)
+            {
+                if matches!(
+                    image.status(),
+                    PrefetchStatus::Cached | PrefetchStatus::Failed
+                ) {
+                    return anyhow::Ok(image.clone());
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .context("prefetch did not settle in time")?
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prefetch_failures_are_reported_and_refreshed() -> Result<()> {
+    let lattice = TestLattice::builder()
+        .build()
+        .await
+        .context("failed to start test lattice")?;
+    let ctl_client = lattice.ctl_client();
+    let host_id = lattice.host().host_id();
+
+    ctl_client
+        .prefetch_images(&host_id, vec![IMAGE_REF.to_string()])
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let image = await_prefetched_image(ctl_client, &host_id).await?;
+    ensure!(image.status() == PrefetchStatus::Failed);
+    ensure!(image
+        .error()
+        .is_some_and(|err| err.contains("only OCI references can be prefetched")));
+
+    // Cleaning up the artifact cache prefetches the tracked images again
+    ctl_client
+        .cleanup_host_data(&host_id, vec![DataCategory::Artifacts])
+        .await
+        .map_err(|e| anyhow!(e))?;
+    let image = await_prefetched_image(ctl_client, &host_id).await?;
+    ensure!(image.status() == PrefetchStatus::Failed);
+
+    lattice.stop().await.context("failed to stop test lattice")
+}